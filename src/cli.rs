@@ -0,0 +1,310 @@
+//! Command-line verbs forwarded to the running vibar instance.
+
+use std::path::{Path, PathBuf};
+
+pub(crate) const MSG_NAMESPACE: &str = "msg";
+pub(crate) const CONFIG_OPTION: &str = "config";
+pub(crate) const STYLE_OPTION: &str = "style";
+pub(crate) const PROFILE_OPTION: &str = "profile";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CliCommand {
+    /// Re-read the config file and rebuild all bars.
+    Reload,
+    /// Hide or show all bar windows.
+    Toggle,
+    /// Turn focus mode on or off; `None` toggles it.
+    Focus { active: Option<bool> },
+    /// Publish `msg.<key>` into the value-binding store.
+    Msg { key: String, value: String },
+    /// Print the running instance's current backend errors.
+    Inspect,
+    /// Render bars to PNG; `output` limits it to one connector.
+    Screenshot {
+        path: PathBuf,
+        output: Option<String>,
+    },
+}
+
+impl CliCommand {
+    /// Anchors relative file paths at the invoking process's working
+    /// directory, since the running instance may have a different one.
+    pub(crate) fn resolve_paths(self, cwd: Option<&Path>) -> Self {
+        match (self, cwd) {
+            (Self::Screenshot { path, output }, Some(cwd)) if path.is_relative() => {
+                Self::Screenshot {
+                    path: cwd.join(path),
+                    output,
+                }
+            }
+            (command, _) => command,
+        }
+    }
+}
+
+/// Parses the positional arguments left after option parsing (without the
+/// program name). No arguments means "just start/activate the bar".
+pub(crate) fn parse_command(args: &[String]) -> Result<Option<CliCommand>, String> {
+    let Some((verb, rest)) = args.split_first() else {
+        return Ok(None);
+    };
+
+    match verb.as_str() {
+        "reload" | "toggle" | "inspect" if !rest.is_empty() => Err(format!(
+            "'{verb}' takes no arguments, got '{}'",
+            rest.join(" ")
+        )),
+        "reload" => Ok(Some(CliCommand::Reload)),
+        "toggle" => Ok(Some(CliCommand::Toggle)),
+        "inspect" => Ok(Some(CliCommand::Inspect)),
+        "focus" => match rest {
+            [] => Ok(Some(CliCommand::Focus { active: None })),
+            [state] => match state.as_str() {
+                "on" => Ok(Some(CliCommand::Focus { active: Some(true) })),
+                "off" => Ok(Some(CliCommand::Focus { active: Some(false) })),
+                "toggle" => Ok(Some(CliCommand::Focus { active: None })),
+                _ => Err("usage: vibar focus [on|off|toggle]".to_string()),
+            },
+            _ => Err("usage: vibar focus [on|off|toggle]".to_string()),
+        },
+        "msg" => {
+            let Some((key, value)) = rest.split_first() else {
+                return Err("usage: vibar msg <key> [value...]".to_string());
+            };
+            if !is_valid_msg_key(key) {
+                return Err(format!(
+                    "invalid msg key '{key}' (use letters, digits, '-' or '_')"
+                ));
+            }
+            Ok(Some(CliCommand::Msg {
+                key: key.clone(),
+                value: value.join(" "),
+            }))
+        }
+        "screenshot" => match rest {
+            [path] => Ok(Some(CliCommand::Screenshot {
+                path: PathBuf::from(path),
+                output: None,
+            })),
+            [path, output] => Ok(Some(CliCommand::Screenshot {
+                path: PathBuf::from(path),
+                output: Some(output.clone()),
+            })),
+            _ => Err("usage: vibar screenshot <file.png> [output]".to_string()),
+        },
+        other => Err(format!(
+            "unknown command '{other}' (expected reload, toggle, focus, msg, inspect, or screenshot)"
+        )),
+    }
+}
+
+/// Removes `--<option> <path>` / `--<option>=<path>` from `args`. Runs
+/// before `Application::run`, so local-only modes and the bar read the same
+/// files.
+pub(crate) fn take_path_option(
+    args: &mut Vec<String>,
+    option: &str,
+) -> Result<Option<PathBuf>, String> {
+    Ok(take_option_value(args, option, "path")?.map(PathBuf::from))
+}
+
+/// Removes `--profile <name>` from `args`. The profile also decides the
+/// application id, so it has to be known before `Application::run`.
+pub(crate) fn take_profile_option(args: &mut Vec<String>) -> Result<Option<String>, String> {
+    let Some(name) = take_option_value(args, PROFILE_OPTION, "name")? else {
+        return Ok(None);
+    };
+    if !is_valid_profile_name(&name) {
+        return Err(format!(
+            "invalid profile name '{name}' (start with a letter; use letters, digits, '-' or '_')"
+        ));
+    }
+    Ok(Some(name))
+}
+
+fn take_option_value(
+    args: &mut Vec<String>,
+    option: &str,
+    metavar: &str,
+) -> Result<Option<String>, String> {
+    let flag = format!("--{option}");
+    let prefix = format!("{flag}=");
+    let Some(index) = args
+        .iter()
+        .position(|arg| *arg == flag || arg.starts_with(&prefix))
+    else {
+        return Ok(None);
+    };
+
+    let arg = args.remove(index);
+    let value = match arg.strip_prefix(&prefix) {
+        Some(value) => value.to_string(),
+        None if index < args.len() => args.remove(index),
+        None => return Err(format!("usage: vibar {flag} <{metavar}>")),
+    };
+    if value.is_empty() {
+        return Err(format!("usage: vibar {flag} <{metavar}>"));
+    }
+    Ok(Some(value))
+}
+
+fn is_valid_profile_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn is_valid_msg_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_command_without_args_is_none() {
+        assert_eq!(parse_command(&[]), Ok(None));
+    }
+
+    #[test]
+    fn parse_command_supports_verbs() {
+        assert_eq!(
+            parse_command(&args(&["reload"])),
+            Ok(Some(CliCommand::Reload))
+        );
+        assert_eq!(
+            parse_command(&args(&["toggle"])),
+            Ok(Some(CliCommand::Toggle))
+        );
+        assert_eq!(
+            parse_command(&args(&["inspect"])),
+            Ok(Some(CliCommand::Inspect))
+        );
+        assert_eq!(
+            parse_command(&args(&["focus"])),
+            Ok(Some(CliCommand::Focus { active: None }))
+        );
+        assert_eq!(
+            parse_command(&args(&["focus", "on"])),
+            Ok(Some(CliCommand::Focus { active: Some(true) }))
+        );
+        assert_eq!(
+            parse_command(&args(&["msg", "status", "build", "ok"])),
+            Ok(Some(CliCommand::Msg {
+                key: "status".to_string(),
+                value: "build ok".to_string(),
+            }))
+        );
+        assert_eq!(
+            parse_command(&args(&["msg", "status"])),
+            Ok(Some(CliCommand::Msg {
+                key: "status".to_string(),
+                value: String::new(),
+            }))
+        );
+        assert_eq!(
+            parse_command(&args(&["screenshot", "bar.png", "DP-1"])),
+            Ok(Some(CliCommand::Screenshot {
+                path: PathBuf::from("bar.png"),
+                output: Some("DP-1".to_string()),
+            }))
+        );
+    }
+
+    #[test]
+    fn resolve_paths_anchors_relative_screenshot_paths() {
+        let command = CliCommand::Screenshot {
+            path: PathBuf::from("shots/bar.png"),
+            output: None,
+        };
+        assert_eq!(
+            command.clone().resolve_paths(Some(Path::new("/home/user"))),
+            CliCommand::Screenshot {
+                path: PathBuf::from("/home/user/shots/bar.png"),
+                output: None,
+            }
+        );
+        assert_eq!(command.clone().resolve_paths(None), command);
+
+        let absolute = CliCommand::Screenshot {
+            path: PathBuf::from("/tmp/bar.png"),
+            output: None,
+        };
+        assert_eq!(
+            absolute
+                .clone()
+                .resolve_paths(Some(Path::new("/home/user"))),
+            absolute
+        );
+    }
+
+    #[test]
+    fn take_path_option_strips_both_spellings() {
+        let mut values = args(&["--config", "bar.jsonc", "reload"]);
+        assert_eq!(
+            take_path_option(&mut values, CONFIG_OPTION),
+            Ok(Some(PathBuf::from("bar.jsonc")))
+        );
+        assert_eq!(values, args(&["reload"]));
+
+        let mut values = args(&["--stdout", "--config=/tmp/bar.jsonc"]);
+        assert_eq!(
+            take_path_option(&mut values, CONFIG_OPTION),
+            Ok(Some(PathBuf::from("/tmp/bar.jsonc")))
+        );
+        assert_eq!(values, args(&["--stdout"]));
+
+        assert_eq!(
+            take_path_option(&mut args(&["toggle"]), CONFIG_OPTION),
+            Ok(None)
+        );
+        assert!(take_path_option(&mut args(&["--config"]), CONFIG_OPTION).is_err());
+        assert!(take_path_option(&mut args(&["--config="]), CONFIG_OPTION).is_err());
+
+        let mut values = args(&["--style", "bar.css", "--config", "bar.jsonc"]);
+        assert_eq!(
+            take_path_option(&mut values, STYLE_OPTION),
+            Ok(Some(PathBuf::from("bar.css")))
+        );
+        assert_eq!(values, args(&["--config", "bar.jsonc"]));
+    }
+
+    #[test]
+    fn take_profile_option_validates_name() {
+        let mut values = args(&["--profile", "lock", "toggle"]);
+        assert_eq!(
+            take_profile_option(&mut values),
+            Ok(Some("lock".to_string()))
+        );
+        assert_eq!(values, args(&["toggle"]));
+        assert_eq!(
+            take_profile_option(&mut args(&["--profile=side-bar_2"])),
+            Ok(Some("side-bar_2".to_string()))
+        );
+        assert_eq!(take_profile_option(&mut args(&["reload"])), Ok(None));
+        assert!(take_profile_option(&mut args(&["--profile"])).is_err());
+        assert!(take_profile_option(&mut args(&["--profile", "2lock"])).is_err());
+        assert!(take_profile_option(&mut args(&["--profile", "lock.screen"])).is_err());
+    }
+
+    #[test]
+    fn parse_command_rejects_invalid_input() {
+        assert!(parse_command(&args(&["restart"])).is_err());
+        assert!(parse_command(&args(&["reload", "now"])).is_err());
+        assert!(parse_command(&args(&["inspect", "sway"])).is_err());
+        assert!(parse_command(&args(&["msg"])).is_err());
+        assert!(parse_command(&args(&["focus", "maybe"])).is_err());
+        assert!(parse_command(&args(&["msg", "a.b", "x"])).is_err());
+        assert!(parse_command(&args(&["screenshot"])).is_err());
+        assert!(parse_command(&args(&["screenshot", "a.png", "DP-1", "x"])).is_err());
+    }
+}
@@ -1,17 +1,157 @@
+use std::collections::BTreeMap;
 use std::env;
+use std::ffi::OsString;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use serde::Deserialize;
+use serde_json::{Map, Value};
 
+use crate::modules::events::ReactionConfig;
+use crate::modules::focus::FocusConfig;
+use crate::modules::popovers::PopoverConfig;
 use crate::modules::ModuleConfig;
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Clone)]
 pub(crate) struct Config {
     #[serde(default)]
     pub(crate) areas: Areas,
     #[serde(default)]
     pub(crate) style: StyleConfig,
+    /// Named color tokens emitted as `@define-color` rules.
+    #[serde(default)]
+    pub(crate) colors: BTreeMap<String, String>,
+    #[serde(rename = "nerd-fonts", alias = "nerd_fonts", default = "default_true")]
+    pub(crate) nerd_fonts: bool,
+    #[serde(default)]
+    pub(crate) metrics: Option<MetricsConfig>,
+    #[serde(rename = "exclusive-zone", alias = "exclusive_zone", default)]
+    pub(crate) exclusive_zone: ExclusiveZone,
+    #[serde(rename = "center-mode", alias = "center_mode", default)]
+    pub(crate) center_mode: CenterMode,
+    /// Opacity of the `.bar-background` layer; module text stays opaque.
+    #[serde(rename = "background-opacity", alias = "background_opacity", default)]
+    pub(crate) background_opacity: Opacity,
+    /// Replaces `background_opacity` while a fullscreen window is on the
+    /// bar's output.
+    #[serde(
+        rename = "background-opacity-fullscreen",
+        alias = "background_opacity_fullscreen",
+        default
+    )]
+    pub(crate) background_opacity_fullscreen: Option<Opacity>,
+    #[serde(default)]
+    pub(crate) popovers: PopoverConfig,
+    /// Per-output bar settings keyed by connector name (`DP-1`, `eDP-1`).
+    #[serde(default)]
+    pub(crate) outputs: BTreeMap<String, OutputConfig>,
+    #[serde(default)]
+    pub(crate) reactions: Vec<ReactionConfig>,
+    #[serde(default)]
+    pub(crate) focus: FocusConfig,
+    #[serde(default)]
+    pub(crate) layer: BarLayer,
+    #[serde(default)]
+    pub(crate) bar: BarConfig,
+    /// Sway `bar` block whose `mode` and modifier reveal show and hide the
+    /// bars.
+    #[serde(rename = "sway-bar-id", alias = "sway_bar_id", default)]
+    pub(crate) sway_bar_id: Option<String>,
+    /// Whether the bar takes pointer input (clicks, scrolling, popovers).
+    #[serde(default = "default_true")]
+    pub(crate) interactive: bool,
+    /// Alternative bar setups, one of which `--profile` selects.
+    #[serde(default)]
+    pub(crate) profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// Replaces bar-level settings on one output; unset fields keep the
+/// top-level value.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct OutputConfig {
+    #[serde(default)]
+    pub(crate) areas: OutputAreas,
+    #[serde(rename = "exclusive-zone", alias = "exclusive_zone", default)]
+    pub(crate) exclusive_zone: Option<ExclusiveZone>,
+    #[serde(rename = "center-mode", alias = "center_mode", default)]
+    pub(crate) center_mode: Option<CenterMode>,
+    #[serde(rename = "background-opacity", alias = "background_opacity", default)]
+    pub(crate) background_opacity: Option<Opacity>,
+    #[serde(
+        rename = "background-opacity-fullscreen",
+        alias = "background_opacity_fullscreen",
+        default
+    )]
+    pub(crate) background_opacity_fullscreen: Option<Opacity>,
+    #[serde(default)]
+    pub(crate) bar: Option<BarConfig>,
+}
+
+/// Replaces bar-level settings while `--profile` selects it; unset fields
+/// keep the top-level value (for `lock`, the built-in lock profile's).
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct ProfileConfig {
+    #[serde(default)]
+    pub(crate) areas: OutputAreas,
+    #[serde(rename = "exclusive-zone", alias = "exclusive_zone", default)]
+    pub(crate) exclusive_zone: Option<ExclusiveZone>,
+    #[serde(default)]
+    pub(crate) layer: Option<BarLayer>,
+    #[serde(default)]
+    pub(crate) interactive: Option<bool>,
+}
+
+impl ProfileConfig {
+    /// A minimal, click-through bar above other surfaces: keyboard layout
+    /// (hidden outside sway), clock, and battery.
+    fn builtin_lock() -> Self {
+        let mut language = Map::new();
+        language.insert(
+            crate::modules::sway::language::HIDE_WITHOUT_SWAY_KEY.to_string(),
+            Value::Bool(true),
+        );
+        Self {
+            areas: OutputAreas {
+                left: Some(vec![ModuleConfig::new(
+                    crate::modules::sway::language::MODULE_TYPE,
+                    language,
+                )]),
+                center: Some(vec![crate::modules::clock::default_module_config()]),
+                right: Some(vec![ModuleConfig::new(
+                    crate::modules::battery::MODULE_TYPE,
+                    Map::new(),
+                )]),
+            },
+            exclusive_zone: Some(ExclusiveZone::None),
+            layer: Some(BarLayer::Overlay),
+            interactive: Some(false),
+        }
+    }
+
+    fn or(self, fallback: Self) -> Self {
+        Self {
+            areas: OutputAreas {
+                left: self.areas.left.or(fallback.areas.left),
+                center: self.areas.center.or(fallback.areas.center),
+                right: self.areas.right.or(fallback.areas.right),
+            },
+            exclusive_zone: self.exclusive_zone.or(fallback.exclusive_zone),
+            layer: self.layer.or(fallback.layer),
+            interactive: self.interactive.or(fallback.interactive),
+        }
+    }
+}
+
+/// Area lists for one output; each one given replaces that whole area.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct OutputAreas {
+    #[serde(default)]
+    pub(crate) left: Option<Vec<ModuleConfig>>,
+    #[serde(default)]
+    pub(crate) center: Option<Vec<ModuleConfig>>,
+    #[serde(default)]
+    pub(crate) right: Option<Vec<ModuleConfig>>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -36,12 +176,311 @@ pub(crate) struct StyleConfig {
     pub(crate) path: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct MetricsConfig {
+    pub(crate) textfile: String,
+    #[serde(default = "default_metrics_interval_secs")]
+    pub(crate) interval_secs: u32,
+}
+
+/// Space the bar reserves on its screen edge.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(try_from = "ExclusiveZoneValue")]
+pub(crate) enum ExclusiveZone {
+    /// Reserve the bar's own height.
+    #[default]
+    Auto,
+    /// Reserve nothing; windows may extend under the bar.
+    None,
+    /// Reserve exactly this many pixels.
+    Pixels(i32),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExclusiveZoneValue {
+    Keyword(String),
+    Pixels(i64),
+}
+
+impl TryFrom<ExclusiveZoneValue> for ExclusiveZone {
+    type Error = String;
+
+    fn try_from(value: ExclusiveZoneValue) -> Result<Self, Self::Error> {
+        match value {
+            ExclusiveZoneValue::Keyword(keyword) => match keyword.as_str() {
+                "auto" => Ok(Self::Auto),
+                "none" => Ok(Self::None),
+                other => Err(format!(
+                    "invalid exclusive-zone '{other}' (expected 'auto', 'none', or pixels)"
+                )),
+            },
+            ExclusiveZoneValue::Pixels(pixels) => i32::try_from(pixels)
+                .ok()
+                .filter(|pixels| *pixels >= 0)
+                .map(Self::Pixels)
+                .ok_or_else(|| format!("invalid exclusive-zone {pixels} (must be >= 0)")),
+        }
+    }
+}
+
+/// Screen edge, thickness, and margins of the bar.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct BarConfig {
+    #[serde(default)]
+    pub(crate) position: BarPosition,
+    /// Thickness of a `top` / `bottom` bar; unset follows the content.
+    #[serde(default)]
+    pub(crate) height: Option<u32>,
+    /// Thickness of a `left` / `right` bar; unset follows the content.
+    #[serde(default)]
+    pub(crate) width: Option<u32>,
+    #[serde(default)]
+    pub(crate) margin: BarMargin,
+}
+
+impl BarConfig {
+    /// `height` or `width`, whichever applies to the bar's orientation.
+    pub(crate) fn thickness(&self) -> Option<u32> {
+        if self.position.is_vertical() {
+            self.width
+        } else {
+            self.height
+        }
+    }
+}
+
+/// Screen edge the bar is anchored to.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum BarPosition {
+    Top,
+    #[default]
+    Bottom,
+    Left,
+    Right,
+}
+
+impl BarPosition {
+    pub(crate) fn is_vertical(self) -> bool {
+        matches!(self, Self::Left | Self::Right)
+    }
+
+    pub(crate) fn css_class(self) -> &'static str {
+        match self {
+            Self::Top => "position-top",
+            Self::Bottom => "position-bottom",
+            Self::Left => "position-left",
+            Self::Right => "position-right",
+        }
+    }
+}
+
+/// Gap in pixels between the bar and each screen edge; a single number
+/// applies to all four.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(from = "BarMarginValue")]
+pub(crate) struct BarMargin {
+    pub(crate) top: i32,
+    pub(crate) right: i32,
+    pub(crate) bottom: i32,
+    pub(crate) left: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BarMarginValue {
+    All(i32),
+    Edges {
+        #[serde(default)]
+        top: i32,
+        #[serde(default)]
+        right: i32,
+        #[serde(default)]
+        bottom: i32,
+        #[serde(default)]
+        left: i32,
+    },
+}
+
+impl From<BarMarginValue> for BarMargin {
+    fn from(value: BarMarginValue) -> Self {
+        match value {
+            BarMarginValue::All(pixels) => Self {
+                top: pixels,
+                right: pixels,
+                bottom: pixels,
+                left: pixels,
+            },
+            BarMarginValue::Edges {
+                top,
+                right,
+                bottom,
+                left,
+            } => Self {
+                top,
+                right,
+                bottom,
+                left,
+            },
+        }
+    }
+}
+
+/// Layer-shell layer the bar is drawn on.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum BarLayer {
+    Background,
+    Bottom,
+    #[default]
+    Top,
+    /// Above fullscreen windows, and above lock screens whose compositor
+    /// still shows overlay surfaces.
+    Overlay,
+}
+
+/// What the center area is centered in.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum CenterMode {
+    /// The space left between the side areas; a wide side area pushes the
+    /// center away from it.
+    #[default]
+    Available,
+    /// The monitor midpoint, even if a side area then runs under the center.
+    Monitor,
+}
+
+/// Alpha value in `0.0..=1.0`; defaults to fully opaque.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(try_from = "f64")]
+pub(crate) struct Opacity(f64);
+
+impl Opacity {
+    pub(crate) fn get(self) -> f64 {
+        self.0
+    }
+}
+
+impl Default for Opacity {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl TryFrom<f64> for Opacity {
+    type Error = String;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if (0.0..=1.0).contains(&value) {
+            Ok(Self(value))
+        } else {
+            Err(format!("invalid opacity {value} (expected 0.0 to 1.0)"))
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct LoadedConfig {
     pub(crate) config: Config,
     pub(crate) source_path: Option<PathBuf>,
 }
 
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            areas: Areas::default(),
+            style: StyleConfig::default(),
+            colors: BTreeMap::new(),
+            nerd_fonts: true,
+            metrics: None,
+            exclusive_zone: ExclusiveZone::Auto,
+            center_mode: CenterMode::Available,
+            background_opacity: Opacity::default(),
+            background_opacity_fullscreen: None,
+            popovers: PopoverConfig::default(),
+            outputs: BTreeMap::new(),
+            reactions: Vec::new(),
+            focus: FocusConfig::default(),
+            layer: BarLayer::Top,
+            bar: BarConfig::default(),
+            sway_bar_id: None,
+            interactive: true,
+            profiles: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// This config with profile `name` applied. Per-output `outputs` entries
+    /// describe the regular bar, so a profile drops them.
+    fn with_profile(mut self, name: &str) -> Result<Config, String> {
+        let configured = self.profiles.remove(name);
+        let profile = match configured {
+            Some(profile) if name == LOCK_PROFILE => profile.or(ProfileConfig::builtin_lock()),
+            Some(profile) => profile,
+            None if name == LOCK_PROFILE => ProfileConfig::builtin_lock(),
+            None => return Err(format!("unknown profile `{name}`")),
+        };
+
+        if let Some(left) = profile.areas.left {
+            self.areas.left = left;
+        }
+        if let Some(center) = profile.areas.center {
+            self.areas.center = center;
+        }
+        if let Some(right) = profile.areas.right {
+            self.areas.right = right;
+        }
+        if let Some(exclusive_zone) = profile.exclusive_zone {
+            self.exclusive_zone = exclusive_zone;
+        }
+        if let Some(layer) = profile.layer {
+            self.layer = layer;
+        }
+        if let Some(interactive) = profile.interactive {
+            self.interactive = interactive;
+        }
+        self.outputs.clear();
+        Ok(self)
+    }
+
+    /// This config with the `outputs` entry for `connector` applied.
+    pub(crate) fn resolved_for_output(&self, connector: Option<&str>) -> Config {
+        let mut config = self.clone();
+        let Some(output) = connector.and_then(|name| self.outputs.get(name)) else {
+            return config;
+        };
+        let areas = output.areas.clone();
+        if let Some(left) = areas.left {
+            config.areas.left = left;
+        }
+        if let Some(center) = areas.center {
+            config.areas.center = center;
+        }
+        if let Some(right) = areas.right {
+            config.areas.right = right;
+        }
+        if let Some(exclusive_zone) = output.exclusive_zone {
+            config.exclusive_zone = exclusive_zone;
+        }
+        if let Some(center_mode) = output.center_mode {
+            config.center_mode = center_mode;
+        }
+        if let Some(opacity) = output.background_opacity {
+            config.background_opacity = opacity;
+        }
+        if let Some(opacity) = output.background_opacity_fullscreen {
+            config.background_opacity_fullscreen = Some(opacity);
+        }
+        if let Some(bar) = output.bar {
+            config.bar = bar;
+        }
+        config
+    }
+}
+
 impl Default for Areas {
     fn default() -> Self {
         Self {
@@ -70,39 +509,139 @@ fn default_right() -> Vec<ModuleConfig> {
 }
 
 const CONFIG_BASENAME: &str = "config.jsonc";
+const STYLE_BASENAME: &str = "style.css";
+const DEFAULT_METRICS_INTERVAL_SECS: u32 = 15;
+const TEMPLATES_KEY: &str = "templates";
+const OUTPUTS_KEY: &str = "outputs";
+const PROFILES_KEY: &str = "profiles";
+/// Profile with built-in defaults, used even when the config has no entry.
+const LOCK_PROFILE: &str = "lock";
+const EXTENDS_KEY: &str = "extends";
+const GROUP_MODULES_KEY: &str = "modules";
+const MODULE_OVERRIDES_KEY: &str = "overrides";
+const AREA_NAMES: [&str; 3] = ["left", "center", "right"];
+const CONFIG_VERSION_KEY: &str = "config-version";
+const CONFIG_VERSION_ALIAS: &str = "config_version";
+/// Layout version written by this build; bump it together with a new
+/// `CONFIG_MIGRATIONS` step whenever an option is renamed or moved.
+pub(crate) const CURRENT_CONFIG_VERSION: u64 = 1;
+/// `CONFIG_MIGRATIONS[n]` upgrades a version `n` config to `n + 1`.
+const CONFIG_MIGRATIONS: [ConfigMigration; CURRENT_CONFIG_VERSION as usize] = [migrate_v0_to_v1];
 const APP_CONFIG_DIRNAME: &str = "vibar";
+const SYSTEM_CONFIG_DIR: &str = "/etc";
 const EMBEDDED_DEFAULT_CONFIG: &str = include_str!("../config.jsonc");
 
+/// Set once from `--config` before the application starts.
+static CONFIG_PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Makes `load_config` read only `path` (relative paths are anchored at the
+/// current directory) instead of searching the default locations.
+pub(crate) fn set_config_path_override(path: PathBuf) {
+    let path = match env::current_dir() {
+        Ok(cwd) if path.is_relative() => cwd.join(path),
+        _ => path,
+    };
+    let _ = CONFIG_PATH_OVERRIDE.set(path);
+}
+
+/// Set once from `--profile` before the application starts.
+static PROFILE_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+/// Makes every loaded config use profile `name`.
+pub(crate) fn set_profile_override(name: String) {
+    let _ = PROFILE_OVERRIDE.set(name);
+}
+
+/// Set once from `--style` before the application starts.
+static STYLE_PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Makes the user stylesheet `path` regardless of `style.path`.
+pub(crate) fn set_style_path_override(path: PathBuf) {
+    let path = match env::current_dir() {
+        Ok(cwd) if path.is_relative() => cwd.join(path),
+        _ => path,
+    };
+    let _ = STYLE_PATH_OVERRIDE.set(path);
+}
+
+/// User stylesheet layered over the default CSS: `--style`, then
+/// `style.path`, then `style.css` in the user config directory if present.
+pub(crate) fn user_style_path(
+    style: &StyleConfig,
+    config_source: Option<&Path>,
+) -> Option<PathBuf> {
+    select_user_style_path(
+        STYLE_PATH_OVERRIDE.get().cloned(),
+        style
+            .path
+            .as_deref()
+            .map(|path| resolve_config_relative_path(path, config_source)),
+        user_config_dir()
+            .map(|dir| dir.join(APP_CONFIG_DIRNAME).join(STYLE_BASENAME))
+            .filter(|path| path.is_file()),
+    )
+}
+
+fn select_user_style_path(
+    cli_override: Option<PathBuf>,
+    configured: Option<PathBuf>,
+    existing_default: Option<PathBuf>,
+) -> Option<PathBuf> {
+    cli_override.or(configured).or(existing_default)
+}
+
 pub(crate) fn load_config() -> LoadedConfig {
+    if let Some(path) = CONFIG_PATH_OVERRIDE.get() {
+        if !path.is_file() {
+            eprintln!(
+                "Config file {} not found; using built-in defaults",
+                path.display()
+            );
+        }
+        return load_config_from_paths(std::slice::from_ref(path));
+    }
     let candidate_paths = default_config_paths();
     load_config_from_paths(&candidate_paths)
 }
 
 fn default_config_paths() -> Vec<PathBuf> {
-    let mut paths = Vec::new();
+    config_search_paths(env::var_os("XDG_CONFIG_HOME"), env::var_os("HOME"))
+}
 
-    if let Some(path) = home_config_path() {
-        paths.push(path);
+/// `$XDG_CONFIG_HOME/vibar`, `~/.config/vibar`, then `/etc/vibar`. A relative
+/// `$XDG_CONFIG_HOME` is ignored, as the XDG spec requires.
+fn config_search_paths(xdg_config_home: Option<OsString>, home: Option<OsString>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = xdg_config_home
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+    {
+        dirs.push(dir);
+    }
+    if let Some(home) = home.filter(|home| !home.is_empty()) {
+        dirs.push(PathBuf::from(home).join(".config"));
     }
+    dirs.push(PathBuf::from(SYSTEM_CONFIG_DIR));
+    dirs.dedup();
+
+    dirs.into_iter()
+        .map(|dir| dir.join(APP_CONFIG_DIRNAME).join(CONFIG_BASENAME))
+        .collect()
+}
 
-    paths
+pub(crate) fn home_config_path() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join(APP_CONFIG_DIRNAME).join(CONFIG_BASENAME))
 }
 
-fn home_config_path() -> Option<PathBuf> {
+/// `$XDG_CONFIG_HOME`, falling back to `~/.config`.
+pub(crate) fn user_config_dir() -> Option<PathBuf> {
     if let Ok(xdg_config_home) = env::var("XDG_CONFIG_HOME") {
-        return Some(
-            PathBuf::from(xdg_config_home)
-                .join(APP_CONFIG_DIRNAME)
-                .join(CONFIG_BASENAME),
-        );
+        return Some(PathBuf::from(xdg_config_home));
     }
 
-    env::var("HOME").ok().map(|home| {
-        PathBuf::from(home)
-            .join(".config")
-            .join(APP_CONFIG_DIRNAME)
-            .join(CONFIG_BASENAME)
-    })
+    env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".config"))
 }
 
 fn load_config_from_paths(paths: &[PathBuf]) -> LoadedConfig {
@@ -138,14 +677,15 @@ fn load_config_from_paths(paths: &[PathBuf]) -> LoadedConfig {
     }
 }
 
-pub(crate) fn resolve_style_path(style_path: &str, config_source: Option<&Path>) -> PathBuf {
-    if let Some(stripped) = style_path.strip_prefix("~/") {
+/// Expands `~/` and resolves relative paths against the config file's directory.
+pub(crate) fn resolve_config_relative_path(path: &str, config_source: Option<&Path>) -> PathBuf {
+    if let Some(stripped) = path.strip_prefix("~/") {
         if let Ok(home) = env::var("HOME") {
             return PathBuf::from(home).join(stripped);
         }
     }
 
-    let path = PathBuf::from(style_path);
+    let path = PathBuf::from(path);
     if path.is_absolute() {
         return path;
     }
@@ -163,8 +703,293 @@ fn default_true() -> bool {
     true
 }
 
-pub(crate) fn parse_config(content: &str) -> Result<Config, json5::Error> {
-    json5::from_str::<Config>(content)
+fn default_metrics_interval_secs() -> u32 {
+    DEFAULT_METRICS_INTERVAL_SECS
+}
+
+pub(crate) fn parse_config(content: &str) -> Result<Config, String> {
+    let mut raw = json5::from_str::<Value>(content).map_err(|err| err.to_string())?;
+    for warning in migrate_config(&mut raw)? {
+        eprintln!("config: {warning}");
+    }
+    expand_module_templates(&mut raw)?;
+    let config = serde_json::from_value::<Config>(raw).map_err(|err| err.to_string())?;
+    match PROFILE_OVERRIDE.get() {
+        Some(name) => config.with_profile(name),
+        None => Ok(config),
+    }
+}
+
+type ConfigMigration = fn(&mut Map<String, Value>, &mut Vec<String>);
+
+/// Upgrades a raw config from its `config-version` (`0` when unset) to
+/// `CURRENT_CONFIG_VERSION` and drops the version field. Returns one warning
+/// per translated option, plus a closing hint when anything was translated.
+fn migrate_config(raw: &mut Value) -> Result<Vec<String>, String> {
+    let Value::Object(root) = raw else {
+        return Ok(Vec::new());
+    };
+
+    let version = match root
+        .remove(CONFIG_VERSION_KEY)
+        .or_else(|| root.remove(CONFIG_VERSION_ALIAS))
+    {
+        None => 0,
+        Some(value) => value.as_u64().ok_or_else(|| {
+            format!("field `{CONFIG_VERSION_KEY}` must be a non-negative integer")
+        })?,
+    };
+    if version > CURRENT_CONFIG_VERSION {
+        return Ok(vec![format!(
+            "`{CONFIG_VERSION_KEY}` {version} is newer than this build supports \
+             ({CURRENT_CONFIG_VERSION}); unknown options are ignored"
+        )]);
+    }
+
+    let mut warnings = Vec::new();
+    for migration in CONFIG_MIGRATIONS.iter().skip(version as usize) {
+        migration(root, &mut warnings);
+    }
+    if !warnings.is_empty() {
+        warnings.push(format!(
+            "update these options and set `\"{CONFIG_VERSION_KEY}\": {CURRENT_CONFIG_VERSION}` \
+             to silence the warnings above"
+        ));
+    }
+    Ok(warnings)
+}
+
+/// `playerctl` `marquee` became `scroll-text`, the name every other
+/// scrolling module uses.
+fn migrate_v0_to_v1(root: &mut Map<String, Value>, warnings: &mut Vec<String>) {
+    rename_module_key(root, "playerctl", "marquee", "scroll-text", warnings);
+}
+
+/// Renames `from` to `to` in every module entry of `module_type`, including
+/// entries that only get their `type` through `extends` and per-output
+/// `overrides`. An entry that already sets `to` keeps it and loses `from`.
+fn rename_module_key(
+    root: &mut Map<String, Value>,
+    module_type: &str,
+    from: &str,
+    to: &str,
+    warnings: &mut Vec<String>,
+) {
+    for_each_module_entry(root, &mut |location, entry_type, fields| {
+        if entry_type != Some(module_type) {
+            return;
+        }
+        let Some(value) = fields.remove(from) else {
+            return;
+        };
+        if fields.contains_key(to) {
+            warnings.push(format!(
+                "`{location}.{from}` was dropped in favor of `{to}`"
+            ));
+        } else {
+            fields.insert(to.to_string(), value);
+            warnings.push(format!("`{location}.{from}` was renamed to `{to}`"));
+        }
+    });
+}
+
+/// The entry's `type`, or the one it inherits through its `extends` chain.
+fn module_entry_type<'a>(
+    fields: &'a Map<String, Value>,
+    templates: &'a Map<String, Value>,
+) -> Option<&'a str> {
+    let mut fields = fields;
+    // Bounded so a template that extends itself cannot loop forever;
+    // `expand_module_templates` reports that error later.
+    for _ in 0..=templates.len() {
+        if let Some(module_type) = fields.get("type").and_then(Value::as_str) {
+            return Some(module_type);
+        }
+        let name = fields.get(EXTENDS_KEY).and_then(Value::as_str)?;
+        fields = templates.get(name).and_then(Value::as_object)?;
+    }
+    None
+}
+
+/// Visitor of [`for_each_module_entry`]: the entry's location, its type
+/// (own or through `extends`), and its fields.
+type ModuleEntryVisitor<'a> = dyn FnMut(&str, Option<&str>, &mut Map<String, Value>) + 'a;
+
+/// Visits every module entry with its location in the config: area
+/// entries, per-output areas, `templates`, and `group` children of each.
+/// Each value of an entry's `overrides` is visited as an entry of the same
+/// type.
+fn for_each_module_entry(root: &mut Map<String, Value>, visit: &mut ModuleEntryVisitor) {
+    let templates = match root.get(TEMPLATES_KEY) {
+        Some(Value::Object(templates)) => templates.clone(),
+        _ => Map::new(),
+    };
+    if let Some(Value::Object(areas)) = root.get_mut("areas") {
+        visit_area_entries("areas", areas, &templates, visit);
+    }
+    for key in [OUTPUTS_KEY, PROFILES_KEY] {
+        if let Some(Value::Object(bars)) = root.get_mut(key) {
+            for (name, bar) in bars.iter_mut() {
+                if let Some(Value::Object(areas)) = bar.get_mut("areas") {
+                    visit_area_entries(&format!("{key}.{name}.areas"), areas, &templates, visit);
+                }
+            }
+        }
+    }
+    if let Some(Value::Object(entries)) = root.get_mut(TEMPLATES_KEY) {
+        for (name, template) in entries.iter_mut() {
+            if let Value::Object(fields) = template {
+                visit_module_entry(
+                    &format!("{TEMPLATES_KEY}.{name}"),
+                    fields,
+                    &templates,
+                    visit,
+                );
+            }
+        }
+    }
+}
+
+fn visit_area_entries(
+    prefix: &str,
+    areas: &mut Map<String, Value>,
+    templates: &Map<String, Value>,
+    visit: &mut ModuleEntryVisitor,
+) {
+    for area in AREA_NAMES {
+        if let Some(Value::Array(entries)) = areas.get_mut(area) {
+            visit_module_entries(&format!("{prefix}.{area}"), entries, templates, visit);
+        }
+    }
+}
+
+fn visit_module_entries(
+    prefix: &str,
+    entries: &mut [Value],
+    templates: &Map<String, Value>,
+    visit: &mut ModuleEntryVisitor,
+) {
+    for (index, entry) in entries.iter_mut().enumerate() {
+        if let Value::Object(fields) = entry {
+            visit_module_entry(&format!("{prefix}[{index}]"), fields, templates, visit);
+        }
+    }
+}
+
+fn visit_module_entry(
+    location: &str,
+    fields: &mut Map<String, Value>,
+    templates: &Map<String, Value>,
+    visit: &mut ModuleEntryVisitor,
+) {
+    let entry_type = module_entry_type(fields, templates).map(str::to_string);
+    visit(location, entry_type.as_deref(), fields);
+    if let Some(Value::Object(overrides)) = fields.get_mut(MODULE_OVERRIDES_KEY) {
+        for (connector, output_fields) in overrides.iter_mut() {
+            if let Value::Object(output_fields) = output_fields {
+                visit(
+                    &format!("{location}.{MODULE_OVERRIDES_KEY}.{connector}"),
+                    entry_type.as_deref(),
+                    output_fields,
+                );
+            }
+        }
+    }
+    if let Some(Value::Array(children)) = fields.get_mut(GROUP_MODULES_KEY) {
+        visit_module_entries(
+            &format!("{location}.{GROUP_MODULES_KEY}"),
+            children,
+            templates,
+            visit,
+        );
+    }
+}
+
+/// Replaces every module entry's `extends` reference with the named entry
+/// from top-level `templates`, then drops `templates` itself.
+///
+/// Entry fields win over template fields (shallow merge). Templates may
+/// extend other templates; `group` children are expanded recursively.
+fn expand_module_templates(raw: &mut Value) -> Result<(), String> {
+    let Value::Object(root) = raw else {
+        return Ok(());
+    };
+
+    let templates = match root.remove(TEMPLATES_KEY) {
+        None => Map::new(),
+        Some(Value::Object(templates)) => templates,
+        Some(_) => return Err(format!("field `{TEMPLATES_KEY}` must be an object")),
+    };
+
+    if let Some(Value::Object(areas)) = root.get_mut("areas") {
+        expand_area_entries(areas, &templates)?;
+    }
+    for key in [OUTPUTS_KEY, PROFILES_KEY] {
+        if let Some(Value::Object(bars)) = root.get_mut(key) {
+            for bar in bars.values_mut() {
+                if let Some(Value::Object(areas)) = bar.get_mut("areas") {
+                    expand_area_entries(areas, &templates)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn expand_area_entries(
+    areas: &mut Map<String, Value>,
+    templates: &Map<String, Value>,
+) -> Result<(), String> {
+    for area in AREA_NAMES {
+        if let Some(Value::Array(entries)) = areas.get_mut(area) {
+            expand_module_entries(entries, templates)?;
+        }
+    }
+    Ok(())
+}
+
+fn expand_module_entries(
+    entries: &mut [Value],
+    templates: &Map<String, Value>,
+) -> Result<(), String> {
+    for entry in entries {
+        let Value::Object(fields) = entry else {
+            continue;
+        };
+        *fields = resolve_module_template(fields, templates, &mut Vec::new())?;
+        if let Some(Value::Array(children)) = fields.get_mut(GROUP_MODULES_KEY) {
+            expand_module_entries(children, templates)?;
+        }
+    }
+    Ok(())
+}
+
+fn resolve_module_template(
+    fields: &Map<String, Value>,
+    templates: &Map<String, Value>,
+    chain: &mut Vec<String>,
+) -> Result<Map<String, Value>, String> {
+    let mut fields = fields.clone();
+    let Some(name) = fields.remove(EXTENDS_KEY) else {
+        return Ok(fields);
+    };
+    let Value::String(name) = name else {
+        return Err(format!(
+            "field `{EXTENDS_KEY}` must be a template name string"
+        ));
+    };
+    if chain.contains(&name) {
+        return Err(format!("module template `{name}` extends itself"));
+    }
+    let Some(Value::Object(template)) = templates.get(&name) else {
+        return Err(format!("unknown module template `{name}`"));
+    };
+
+    chain.push(name);
+    let mut resolved = resolve_module_template(template, templates, chain)?;
+    resolved.extend(fields);
+    Ok(resolved)
 }
 
 #[cfg(test)]
@@ -208,6 +1033,408 @@ mod tests {
         assert_eq!(cfg.areas.right.len(), 1);
     }
 
+    #[test]
+    fn parse_config_reads_nerd_fonts_flag() {
+        let default_cfg = parse_config("{}").expect("config should parse");
+        assert!(default_cfg.nerd_fonts);
+
+        let cfg = parse_config(r#"{ "nerd-fonts": false }"#).expect("config should parse");
+        assert!(!cfg.nerd_fonts);
+    }
+
+    #[test]
+    fn parse_config_expands_module_templates() {
+        let cfg = parse_config(
+            r#"{
+                templates: {
+                    "small-exec": { type: "exec", interval_secs: 30, class: "small" },
+                    "slow-exec": { extends: "small-exec", interval_secs: 300 }
+                },
+                areas: {
+                    left: [{ extends: "small-exec", command: "echo left" }],
+                    right: [{
+                        type: "group",
+                        modules: [{ extends: "slow-exec", command: "echo nested" }]
+                    }]
+                }
+            }"#,
+        )
+        .expect("config should parse");
+
+        let left = &cfg.areas.left[0];
+        assert_eq!(left.module_type, "exec");
+        assert_eq!(left.config.get("interval_secs"), Some(&Value::from(30)));
+        assert_eq!(left.config.get("command"), Some(&Value::from("echo left")));
+        assert!(!left.config.contains_key(EXTENDS_KEY));
+
+        let children = cfg.areas.right[0]
+            .config
+            .get("modules")
+            .and_then(Value::as_array)
+            .expect("group modules should be an array");
+        assert_eq!(children[0].get("type"), Some(&Value::from("exec")));
+        assert_eq!(children[0].get("interval_secs"), Some(&Value::from(300)));
+        assert_eq!(children[0].get("class"), Some(&Value::from("small")));
+    }
+
+    #[test]
+    fn parse_config_rejects_unknown_and_cyclic_templates() {
+        let err = parse_config(r#"{ areas: { left: [{ extends: "missing" }] } }"#)
+            .expect_err("unknown template should fail");
+        assert!(err.contains("unknown module template `missing`"));
+
+        let err = parse_config(
+            r#"{
+                templates: { a: { extends: "b" }, b: { extends: "a" } },
+                areas: { left: [{ extends: "a" }] }
+            }"#,
+        )
+        .expect_err("cyclic template should fail");
+        assert!(err.contains("extends itself"));
+    }
+
+    #[test]
+    fn migrate_config_renames_legacy_keys_in_every_entry() {
+        let mut raw = json5::from_str::<Value>(
+            r#"{
+                templates: { media: { type: "playerctl", marquee: "open" } },
+                areas: {
+                    right: [
+                        { type: "playerctl", marquee: "hover" },
+                        { type: "group", modules: [{ type: "playerctl", marquee: "always" }] },
+                        { type: "exec", marquee: "kept" }
+                    ]
+                },
+                outputs: {
+                    "DP-1": {
+                        areas: { left: [{ type: "playerctl", marquee: "off", "scroll-text": "hover" }] }
+                    }
+                }
+            }"#,
+        )
+        .expect("raw config should parse");
+
+        let warnings = migrate_config(&mut raw).expect("migration should succeed");
+
+        assert_eq!(raw["areas"]["right"][0]["scroll-text"], "hover");
+        assert_eq!(
+            raw["areas"]["right"][1]["modules"][0]["scroll-text"],
+            "always"
+        );
+        assert_eq!(raw["areas"]["right"][2]["marquee"], "kept");
+        assert_eq!(raw["templates"]["media"]["scroll-text"], "open");
+        let output_entry = &raw["outputs"]["DP-1"]["areas"]["left"][0];
+        assert_eq!(output_entry["scroll-text"], "hover");
+        assert!(output_entry.get("marquee").is_none());
+        assert_eq!(
+            warnings[..4],
+            [
+                "`areas.right[0].marquee` was renamed to `scroll-text`",
+                "`areas.right[1].modules[0].marquee` was renamed to `scroll-text`",
+                "`outputs.DP-1.areas.left[0].marquee` was dropped in favor of `scroll-text`",
+                "`templates.media.marquee` was renamed to `scroll-text`",
+            ]
+        );
+        assert!(warnings[4].contains("\"config-version\": 1"));
+    }
+
+    #[test]
+    fn migrate_config_resolves_type_through_extends() {
+        let mut raw = json5::from_str::<Value>(
+            r#"{
+                templates: {
+                    media: { type: "playerctl" },
+                    spotify: { extends: "media", player: "spotify" },
+                    clock: { type: "clock" }
+                },
+                areas: {
+                    right: [
+                        { extends: "spotify", marquee: "hover" },
+                        { extends: "clock", marquee: "kept" }
+                    ]
+                }
+            }"#,
+        )
+        .expect("raw config should parse");
+
+        let warnings = migrate_config(&mut raw).expect("migration should succeed");
+
+        assert_eq!(raw["areas"]["right"][0]["scroll-text"], "hover");
+        assert!(raw["areas"]["right"][0].get("marquee").is_none());
+        assert_eq!(raw["areas"]["right"][1]["marquee"], "kept");
+        assert_eq!(
+            warnings[0],
+            "`areas.right[0].marquee` was renamed to `scroll-text`"
+        );
+    }
+
+    #[test]
+    fn migrate_config_renames_keys_in_output_overrides() {
+        let mut raw = json5::from_str::<Value>(
+            r#"{
+                templates: { media: { type: "playerctl" } },
+                areas: {
+                    right: [
+                        {
+                            type: "playerctl",
+                            "scroll-text": "hover",
+                            overrides: { "DP-1": { marquee: "always" } }
+                        },
+                        { extends: "media", overrides: { "eDP-1": { marquee: "off" } } },
+                        { type: "exec", overrides: { "DP-1": { marquee: "kept" } } }
+                    ]
+                }
+            }"#,
+        )
+        .expect("raw config should parse");
+
+        let warnings = migrate_config(&mut raw).expect("migration should succeed");
+
+        let right = &raw["areas"]["right"];
+        assert_eq!(right[0]["overrides"]["DP-1"]["scroll-text"], "always");
+        assert!(right[0]["overrides"]["DP-1"].get("marquee").is_none());
+        assert_eq!(right[1]["overrides"]["eDP-1"]["scroll-text"], "off");
+        assert_eq!(right[2]["overrides"]["DP-1"]["marquee"], "kept");
+        assert_eq!(
+            warnings[0],
+            "`areas.right[0].overrides.DP-1.marquee` was renamed to `scroll-text`"
+        );
+    }
+
+    #[test]
+    fn migrate_config_skips_current_and_checks_version() {
+        let mut raw = json5::from_str::<Value>(
+            r#"{ "config-version": 1, areas: { left: [{ type: "playerctl", marquee: "hover" }] } }"#,
+        )
+        .expect("raw config should parse");
+        assert!(migrate_config(&mut raw)
+            .expect("migration should succeed")
+            .is_empty());
+        assert_eq!(raw["areas"]["left"][0]["marquee"], "hover");
+        assert!(raw.get(CONFIG_VERSION_KEY).is_none());
+
+        let mut unversioned = json5::from_str::<Value>("{}").expect("raw config should parse");
+        assert!(migrate_config(&mut unversioned)
+            .expect("migration should succeed")
+            .is_empty());
+
+        let mut newer =
+            json5::from_str::<Value>(r#"{ config_version: 99 }"#).expect("raw config should parse");
+        let warnings = migrate_config(&mut newer).expect("newer version should load");
+        assert!(warnings[0].contains("newer than this build supports"));
+
+        let err = parse_config(r#"{ "config-version": "one" }"#)
+            .expect_err("non-numeric version should fail");
+        assert!(err.contains("must be a non-negative integer"));
+    }
+
+    #[test]
+    fn parse_config_reads_metrics_section() {
+        let default_cfg = parse_config("{}").expect("config should parse");
+        assert!(default_cfg.metrics.is_none());
+
+        let cfg = parse_config(r#"{ metrics: { textfile: "/tmp/vibar.prom" } }"#)
+            .expect("config should parse");
+        let metrics = cfg.metrics.expect("metrics section should parse");
+        assert_eq!(metrics.textfile, "/tmp/vibar.prom");
+        assert_eq!(metrics.interval_secs, DEFAULT_METRICS_INTERVAL_SECS);
+    }
+
+    #[test]
+    fn parse_config_reads_colors_section() {
+        let default_cfg = parse_config("{}").expect("config should parse");
+        assert!(default_cfg.colors.is_empty());
+
+        let cfg = parse_config(r##"{ colors: { accent: "#ff8800", critical: "red" } }"##)
+            .expect("config should parse");
+        assert_eq!(
+            cfg.colors.get("accent").map(String::as_str),
+            Some("#ff8800")
+        );
+        assert_eq!(cfg.colors.get("critical").map(String::as_str), Some("red"));
+    }
+
+    #[test]
+    fn parse_config_reads_exclusive_zone() {
+        let default_cfg = parse_config("{}").expect("config should parse");
+        assert_eq!(default_cfg.exclusive_zone, ExclusiveZone::Auto);
+
+        let zone = |content: &str| parse_config(content).map(|cfg| cfg.exclusive_zone);
+        assert_eq!(
+            zone(r#"{ "exclusive-zone": "none" }"#),
+            Ok(ExclusiveZone::None)
+        );
+        assert_eq!(
+            zone(r#"{ exclusive_zone: 48 }"#),
+            Ok(ExclusiveZone::Pixels(48))
+        );
+        assert!(zone(r#"{ "exclusive-zone": -1 }"#).is_err());
+        assert!(zone(r#"{ "exclusive-zone": "big" }"#).is_err());
+    }
+
+    #[test]
+    fn parse_config_reads_center_mode() {
+        let default_cfg = parse_config("{}").expect("config should parse");
+        assert_eq!(default_cfg.center_mode, CenterMode::Available);
+
+        let mode = |content: &str| parse_config(content).map(|cfg| cfg.center_mode);
+        assert_eq!(
+            mode(r#"{ "center-mode": "monitor" }"#),
+            Ok(CenterMode::Monitor)
+        );
+        assert_eq!(
+            mode(r#"{ center_mode: "available" }"#),
+            Ok(CenterMode::Available)
+        );
+        assert!(mode(r#"{ "center-mode": "screen" }"#).is_err());
+    }
+
+    #[test]
+    fn parse_config_reads_background_opacity() {
+        let default_cfg = parse_config("{}").expect("config should parse");
+        assert_eq!(default_cfg.background_opacity.get(), 1.0);
+        assert_eq!(default_cfg.background_opacity_fullscreen, None);
+
+        let cfg =
+            parse_config(r#"{ "background-opacity": 0.6, background_opacity_fullscreen: 1 }"#)
+                .expect("config should parse");
+        assert_eq!(cfg.background_opacity.get(), 0.6);
+        assert_eq!(
+            cfg.background_opacity_fullscreen.map(Opacity::get),
+            Some(1.0)
+        );
+        assert!(parse_config(r#"{ "background-opacity": 1.5 }"#).is_err());
+        assert!(parse_config(r#"{ "background-opacity": -0.1 }"#).is_err());
+    }
+
+    #[test]
+    fn resolved_for_output_replaces_only_configured_areas() {
+        let cfg = parse_config(
+            r#"{
+                templates: { small: { type: "exec", interval_secs: 30 } },
+                areas: {
+                    left: [{ type: "exec", command: "echo left" }],
+                    right: [{ type: "clock" }]
+                },
+                outputs: {
+                    "HDMI-A-1": {
+                        areas: { left: [{ extends: "small", command: "echo tv" }], right: [] },
+                        "center-mode": "monitor",
+                        "background-opacity": 0.5
+                    }
+                }
+            }"#,
+        )
+        .expect("config should parse");
+
+        let tv = cfg.resolved_for_output(Some("HDMI-A-1"));
+        assert_eq!(tv.areas.left.len(), 1);
+        assert_eq!(
+            tv.areas.left[0].config.get("interval_secs"),
+            Some(&Value::from(30))
+        );
+        assert!(tv.areas.right.is_empty());
+        assert_eq!(tv.center_mode, CenterMode::Monitor);
+        assert_eq!(tv.exclusive_zone, ExclusiveZone::Auto);
+        assert_eq!(tv.background_opacity.get(), 0.5);
+
+        for connector in [Some("DP-1"), None] {
+            let other = cfg.resolved_for_output(connector);
+            assert_eq!(other.areas.right.len(), 1);
+            assert_eq!(other.center_mode, CenterMode::Available);
+            assert_eq!(other.background_opacity.get(), 1.0);
+        }
+    }
+
+    #[test]
+    fn bar_config_parses_position_and_margins() {
+        let cfg = parse_config("{}").expect("config should parse");
+        assert_eq!(cfg.bar, BarConfig::default());
+        assert_eq!(cfg.bar.position, BarPosition::Bottom);
+
+        let cfg = parse_config(
+            r#"{
+                bar: { position: "left", width: 36, height: 20, margin: { top: 4, left: 2 } },
+                outputs: { "DP-1": { bar: { position: "top", margin: 6 } } }
+            }"#,
+        )
+        .expect("config should parse");
+        assert!(cfg.bar.position.is_vertical());
+        assert_eq!(cfg.bar.thickness(), Some(36));
+        assert_eq!(
+            cfg.bar.margin,
+            BarMargin {
+                top: 4,
+                right: 0,
+                bottom: 0,
+                left: 2
+            }
+        );
+
+        let dp = cfg.resolved_for_output(Some("DP-1"));
+        assert_eq!(dp.bar.position, BarPosition::Top);
+        assert_eq!(dp.bar.thickness(), None);
+        assert_eq!(dp.bar.margin.bottom, 6);
+
+        assert!(parse_config(r#"{ bar: { position: "middle" } }"#).is_err());
+    }
+
+    #[test]
+    fn lock_profile_has_builtin_defaults() {
+        let cfg = parse_config(
+            r#"{
+                areas: { left: [{ type: "workspaces" }] },
+                outputs: { "HDMI-A-1": { areas: { right: [] } } }
+            }"#,
+        )
+        .expect("config should parse")
+        .with_profile(LOCK_PROFILE)
+        .expect("lock profile is built in");
+
+        assert_eq!(cfg.areas.left[0].module_type, "sway/language");
+        assert_eq!(
+            cfg.areas.left[0].config.get("hide-without-sway"),
+            Some(&Value::Bool(true))
+        );
+        assert_eq!(cfg.areas.center[0].module_type, "clock");
+        assert_eq!(cfg.areas.right[0].module_type, "battery");
+        assert_eq!(cfg.layer, BarLayer::Overlay);
+        assert!(!cfg.interactive);
+        assert_eq!(cfg.exclusive_zone, ExclusiveZone::None);
+        assert!(cfg.outputs.is_empty());
+    }
+
+    #[test]
+    fn configured_profiles_override_fields_they_set() {
+        let cfg = parse_config(
+            r#"{
+                templates: { layout: { type: "exec", command: "echo us" } },
+                layer: "bottom",
+                profiles: {
+                    lock: { areas: { left: [{ extends: "layout" }] } },
+                    side: { interactive: false }
+                }
+            }"#,
+        )
+        .expect("config should parse");
+        assert_eq!(cfg.layer, BarLayer::Bottom);
+        assert!(cfg.interactive);
+
+        let lock = cfg.clone().with_profile("lock").expect("lock profile");
+        assert_eq!(lock.areas.left[0].module_type, "exec");
+        assert_eq!(lock.areas.right[0].module_type, "battery");
+        assert_eq!(lock.layer, BarLayer::Overlay);
+
+        let side = cfg.clone().with_profile("side").expect("side profile");
+        assert_eq!(side.layer, BarLayer::Bottom);
+        assert!(!side.interactive);
+
+        assert_eq!(
+            cfg.with_profile("missing").err(),
+            Some("unknown profile `missing`".to_string())
+        );
+    }
+
     #[test]
     fn load_config_prefers_first_valid_path() {
         let home_cfg = test_path("home");
@@ -255,15 +1482,56 @@ mod tests {
     }
 
     #[test]
-    fn resolve_style_path_expands_tilde() {
-        let result = resolve_style_path("~/styles/vibar.css", None);
+    fn config_search_paths_follow_xdg_then_home_then_etc() {
+        assert_eq!(
+            config_search_paths(Some("/xdg".into()), Some("/home/user".into())),
+            vec![
+                PathBuf::from("/xdg/vibar/config.jsonc"),
+                PathBuf::from("/home/user/.config/vibar/config.jsonc"),
+                PathBuf::from("/etc/vibar/config.jsonc"),
+            ]
+        );
+        assert_eq!(
+            config_search_paths(Some("relative".into()), None),
+            vec![PathBuf::from("/etc/vibar/config.jsonc")]
+        );
+        assert_eq!(
+            config_search_paths(Some("/home/user/.config".into()), Some("/home/user".into())),
+            vec![
+                PathBuf::from("/home/user/.config/vibar/config.jsonc"),
+                PathBuf::from("/etc/vibar/config.jsonc"),
+            ]
+        );
+    }
+
+    #[test]
+    fn select_user_style_path_prefers_flag_then_config_then_default() {
+        let flag = Some(PathBuf::from("/tmp/flag.css"));
+        let configured = Some(PathBuf::from("/home/user/custom.css"));
+        let default = Some(PathBuf::from("/home/user/.config/vibar/style.css"));
+
+        assert_eq!(
+            select_user_style_path(flag.clone(), configured.clone(), default.clone()),
+            flag
+        );
+        assert_eq!(
+            select_user_style_path(None, configured.clone(), default.clone()),
+            configured
+        );
+        assert_eq!(select_user_style_path(None, None, default.clone()), default);
+        assert_eq!(select_user_style_path(None, None, None), None);
+    }
+
+    #[test]
+    fn resolve_config_relative_path_expands_tilde() {
+        let result = resolve_config_relative_path("~/styles/vibar.css", None);
         assert!(result.is_absolute());
     }
 
     #[test]
-    fn resolve_style_path_uses_config_parent_for_relative_paths() {
+    fn resolve_config_relative_path_uses_config_parent_for_relative_paths() {
         let source = PathBuf::from("/tmp/vibar/config.jsonc");
-        let result = resolve_style_path("style.local.css", Some(&source));
+        let result = resolve_config_relative_path("style.local.css", Some(&source));
         assert_eq!(result, PathBuf::from("/tmp/vibar/style.local.css"));
     }
 }
@@ -0,0 +1,168 @@
+//! `vibar --stdout`: runs module backends without any GTK windows and prints
+//! status lines in the i3bar protocol, so vibar can serve as a swaybar
+//! `status_command`.
+
+use std::cell::{Cell, RefCell};
+use std::io::Write;
+use std::rc::Rc;
+
+use gtk::glib;
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::modules;
+use crate::modules::text::{TextBlock, TextSink};
+
+pub(crate) const STDOUT_FLAG: &str = "--stdout";
+const PROTOCOL_HEADER: &str = r#"{"version":1}"#;
+
+/// Blocks for every configured module, in bar order (left, center, right).
+struct StatusLine {
+    module_types: Vec<String>,
+    blocks: RefCell<Vec<Option<TextBlock>>>,
+    flush_pending: Cell<bool>,
+    first_line: Cell<bool>,
+    main_loop: glib::MainLoop,
+}
+
+impl StatusLine {
+    fn set_block(self: &Rc<Self>, index: usize, block: Option<TextBlock>) {
+        {
+            let mut blocks = self.blocks.borrow_mut();
+            if blocks[index] == block {
+                return;
+            }
+            blocks[index] = block;
+        }
+
+        // Several modules usually update in the same main loop iteration;
+        // print one line for all of them.
+        if self.flush_pending.replace(true) {
+            return;
+        }
+        let status = Rc::clone(self);
+        glib::idle_add_local_once(move || {
+            status.flush_pending.set(false);
+            if let Err(err) = status.print() {
+                eprintln!("vibar: failed to write status line: {err}");
+                status.main_loop.quit();
+            }
+        });
+    }
+
+    fn print(&self) -> std::io::Result<()> {
+        let line = status_line_json(&self.module_types, &self.blocks.borrow());
+        let separator = if self.first_line.replace(false) {
+            ""
+        } else {
+            ","
+        };
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "{separator}{line}")?;
+        stdout.flush()
+    }
+}
+
+/// Runs until stdout is closed (for example when swaybar exits).
+pub(crate) fn run(config: &Config) -> Result<(), String> {
+    let modules = config
+        .areas
+        .left
+        .iter()
+        .chain(&config.areas.center)
+        .chain(&config.areas.right)
+        .collect::<Vec<_>>();
+
+    let status = Rc::new(StatusLine {
+        module_types: modules
+            .iter()
+            .map(|module| module.module_type.clone())
+            .collect(),
+        blocks: RefCell::new(vec![None; modules.len()]),
+        flush_pending: Cell::new(false),
+        first_line: Cell::new(true),
+        main_loop: glib::MainLoop::new(None, false),
+    });
+
+    {
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "{PROTOCOL_HEADER}\n[")
+            .and_then(|()| stdout.flush())
+            .map_err(|err| format!("failed to write to stdout: {err}"))?;
+    }
+
+    for (index, module) in modules.iter().enumerate() {
+        let sink: TextSink = {
+            let status = Rc::clone(&status);
+            Rc::new(move |block| status.set_block(index, block))
+        };
+        if let Err(err) = modules::build_text_module(module, sink) {
+            eprintln!(
+                "Skipping module '{}' in {STDOUT_FLAG} mode: {err}",
+                module.module_type
+            );
+        }
+    }
+
+    status.main_loop.run();
+    Ok(())
+}
+
+/// One i3bar status line; hidden modules are left out.
+fn status_line_json(module_types: &[String], blocks: &[Option<TextBlock>]) -> String {
+    let blocks = module_types
+        .iter()
+        .zip(blocks)
+        .enumerate()
+        .filter_map(|(index, (module_type, block))| {
+            let block = block.as_ref()?;
+            let mut value = json!({
+                "name": module_type,
+                "instance": index.to_string(),
+                "full_text": block.markup,
+                "markup": "pango",
+            });
+            if block.urgent {
+                value["urgent"] = Value::Bool(true);
+            }
+            Some(value)
+        })
+        .collect::<Vec<_>>();
+    Value::Array(blocks).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_line_json_skips_hidden_modules_and_marks_urgent() {
+        let module_types = vec!["cpu".to_string(), "exec".to_string(), "clock".to_string()];
+        let blocks = vec![
+            Some(TextBlock::new("<b>12%</b>").urgent(true)),
+            None,
+            Some(TextBlock::new("12:00")),
+        ];
+
+        let line: Value =
+            serde_json::from_str(&status_line_json(&module_types, &blocks)).expect("valid json");
+        assert_eq!(
+            line,
+            json!([
+                {
+                    "name": "cpu",
+                    "instance": "0",
+                    "full_text": "<b>12%</b>",
+                    "markup": "pango",
+                    "urgent": true
+                },
+                {
+                    "name": "clock",
+                    "instance": "2",
+                    "full_text": "12:00",
+                    "markup": "pango"
+                }
+            ])
+        );
+    }
+}
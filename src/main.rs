@@ -1,62 +1,337 @@
 use gtk::gdk;
+use gtk::glib::translate::ToGlibPtr;
 use gtk::glib::ControlFlow;
 use gtk::prelude::*;
-use gtk::{Application, ApplicationWindow, Box as GtkBox, CenterBox, Orientation};
+use gtk::{
+    Align, Application, ApplicationWindow, Box as GtkBox, CenterBox, Orientation, Overlay, Widget,
+};
 use gtk4_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet};
+use std::ffi::{c_char, CString};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::time::Duration;
 
+mod cli;
 mod config;
+mod headless;
+mod metrics;
 mod modules;
+mod preview;
+mod screenshot;
 mod style;
-
-use config::{load_config, parse_config, Config, LoadedConfig};
+mod waybar_import;
+
+use cli::CliCommand;
+use config::{
+    load_config, parse_config, BarLayer, BarPosition, CenterMode, Config, ExclusiveZone,
+    LoadedConfig,
+};
+use modules::broadcaster::attach_subscription;
+use modules::reuse::{BarModules, ModulePool, PlacedModule};
+use modules::sway::bar_mode::subscribe_sway_bar_state;
+use modules::sway::fullscreen::subscribe_fullscreen_outputs;
 use modules::{ModuleBuildContext, ModuleConfig};
 
 const APP_ID: &str = "dev.haug1.vibar";
+/// Layer-shell namespace; compositors match it in blur/effect rules.
+const LAYER_NAMESPACE: &str = "vibar";
 const CONFIG_RELOAD_DEBOUNCE_MILLIS: u64 = 200;
+const NO_NERD_FONTS_OPTION: &str = "no-nerd-fonts";
+const REPLACE_OPTION: &str = "replace";
+/// Listed for `--help`; the flag itself is handled before GTK starts.
+const STDOUT_OPTION: &str = "stdout";
 
 struct AppRuntime {
     app: Application,
     windows: Rc<RefCell<HashMap<String, ApplicationWindow>>>,
+    /// Modules placed on each tracked bar, keyed like `windows`.
+    bar_modules: RefCell<HashMap<String, Vec<PlacedModule<Widget>>>>,
+    /// Widgets the bars being replaced by a reload offer to their
+    /// successors; only filled while `rebuild_windows` runs.
+    reuse_pools: RefCell<HashMap<String, ModulePool<Widget>>>,
     config: Rc<RefCell<Config>>,
     config_source_path: RefCell<Option<PathBuf>>,
     style_runtime: RefCell<Option<Rc<style::StyleRuntime>>>,
-    _monitor_model: gtk::gio::ListModel,
+    metrics_runtime: RefCell<Option<metrics::MetricsRuntime>>,
+    monitor_model: gtk::gio::ListModel,
     _config_monitor: RefCell<Option<gtk::gio::FileMonitor>>,
     config_reload_source: RefCell<Option<gtk::glib::SourceId>>,
+    cli_no_nerd_fonts: bool,
+    bars_hidden: Cell<bool>,
+    /// Cleared while the sway bar named by `sway-bar-id` is hidden.
+    sway_bar_visible: Cell<bool>,
 }
 
 impl AppRuntime {
-    fn sync_windows(&self) {
-        sync_monitor_windows(&self.app, &self.config, &self.windows);
+    fn sync_windows(self: &Rc<Self>) {
+        let config_snapshot = self.config.borrow().clone();
+        let monitors = connected_monitors();
+        let monitor_keys = monitors
+            .iter()
+            .map(|monitor| (monitor_key(monitor), monitor.clone()))
+            .collect::<Vec<_>>();
+        let active_keys = monitor_keys
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<HashSet<_>>();
+
+        let mut tracked_windows = self.windows.borrow_mut();
+        let removed_keys = stale_window_keys(tracked_windows.keys(), &active_keys);
+        let mut removed_windows = Vec::new();
+        for key in removed_keys {
+            if let Some(window) = tracked_windows.remove(&key) {
+                debug_outputs_log(&format!("removing bar {key}"));
+                removed_windows.push(window);
+            }
+            self.bar_modules.borrow_mut().remove(&key);
+        }
+
+        if monitor_keys.is_empty() {
+            if !tracked_windows.contains_key(FALLBACK_WINDOW_KEY) {
+                debug_outputs_log("no monitors; creating fallback bar");
+                let window = self.build_tracked_window(FALLBACK_WINDOW_KEY, &config_snapshot, None);
+                self.attach_sway_bar_mode(&window, &config_snapshot);
+                debug_dump_dom_if_enabled(&window, None);
+                self.show_new_window(&window);
+                tracked_windows.insert(FALLBACK_WINDOW_KEY.to_string(), window);
+            }
+        } else {
+            for (key, monitor) in monitor_keys {
+                if tracked_windows.contains_key(&key) {
+                    continue;
+                }
+
+                self.attach_monitor_connector_resolve_once(&monitor);
+
+                debug_outputs_log(&format!("creating bar {key}"));
+                let window = self.build_tracked_window(&key, &config_snapshot, Some(&monitor));
+                self.attach_sway_bar_mode(&window, &config_snapshot);
+                let connector = monitor.connector().map(|value| value.to_string());
+                debug_dump_dom_if_enabled(&window, connector.as_deref());
+                self.show_new_window(&window);
+                tracked_windows.insert(key, window);
+            }
+        }
+
+        drop(tracked_windows);
+        defer_destroy_windows(removed_windows);
+    }
+
+    fn build_tracked_window(
+        &self,
+        key: &str,
+        config: &Config,
+        monitor: Option<&gdk::Monitor>,
+    ) -> ApplicationWindow {
+        let pool = self
+            .reuse_pools
+            .borrow_mut()
+            .remove(key)
+            .unwrap_or_default();
+        let (window, placed) = build_window(
+            &self.app,
+            config,
+            self.config_source_path.borrow().as_deref(),
+            monitor,
+            pool,
+        );
+        self.bar_modules
+            .borrow_mut()
+            .insert(key.to_string(), placed);
+        window
+    }
+
+    fn show_new_window(&self, window: &ApplicationWindow) {
+        if self.bars_visible() {
+            window.present();
+        } else {
+            window.set_visible(false);
+        }
+    }
+
+    /// Shows and hides `window` with the sway bar named by `sway-bar-id`.
+    fn attach_sway_bar_mode(self: &Rc<Self>, window: &ApplicationWindow, config: &Config) {
+        let Some(id) = config.sway_bar_id.clone() else {
+            self.sway_bar_visible.set(true);
+            return;
+        };
+        let weak_runtime = Rc::downgrade(self);
+        attach_subscription(
+            window,
+            subscribe_sway_bar_state(id),
+            move |window, state| {
+                let Some(runtime) = weak_runtime.upgrade() else {
+                    return;
+                };
+                runtime.sway_bar_visible.set(state.visible());
+                window.set_visible(runtime.bars_visible());
+            },
+        );
     }
 
-    fn rebuild_windows(&self) {
+    /// Re-syncs once a newly plugged monitor reports its connector, so the
+    /// bar is rebuilt with that output's `overrides`.
+    fn attach_monitor_connector_resolve_once(self: &Rc<Self>, monitor: &gdk::Monitor) {
+        if monitor.connector().is_some() {
+            return;
+        }
+
+        let handler_id = Rc::new(RefCell::new(None));
+        let handler_id_for_cb = Rc::clone(&handler_id);
+        let monitor_for_cb = monitor.clone();
+        let weak_runtime = Rc::downgrade(self);
+        let id = monitor.connect_connector_notify(move |item| {
+            if item.connector().is_none() {
+                return;
+            }
+
+            if let Some(runtime) = weak_runtime.upgrade() {
+                runtime.sync_windows();
+            }
+
+            if let Some(id) = handler_id_for_cb.borrow_mut().take() {
+                monitor_for_cb.disconnect(id);
+            }
+        });
+        *handler_id.borrow_mut() = Some(id);
+    }
+
+    /// Replaces every bar. With `reuse_modules`, modules whose identity and
+    /// config are unchanged move into the new bars instead of being rebuilt.
+    /// The old bars are destroyed only after the new ones are built, so
+    /// backends shared with rebuilt modules keep running.
+    fn rebuild_windows(self: &Rc<Self>, reuse_modules: bool) {
         let removed_windows = {
             let mut tracked_windows = self.windows.borrow_mut();
-            tracked_windows.drain().map(|(_, window)| window).collect()
+            tracked_windows
+                .drain()
+                .map(|(_, window)| window)
+                .collect::<Vec<_>>()
         };
-        close_windows_now(removed_windows);
+        for window in &removed_windows {
+            window.set_visible(false);
+        }
+        let placed = std::mem::take(&mut *self.bar_modules.borrow_mut());
+        if reuse_modules {
+            *self.reuse_pools.borrow_mut() = placed
+                .into_iter()
+                .map(|(key, modules)| (key, ModulePool::new(modules)))
+                .collect();
+        }
         self.sync_windows();
+        self.reuse_pools.borrow_mut().clear();
+        destroy_windows_now(removed_windows);
+        self.apply_bar_visibility();
+    }
+
+    fn bars_visible(&self) -> bool {
+        !self.bars_hidden.get() && self.sway_bar_visible.get()
+    }
+
+    fn apply_bar_visibility(&self) {
+        let visible = self.bars_visible();
+        for window in self.windows.borrow().values() {
+            window.set_visible(visible);
+        }
+    }
+
+    /// Runs a forwarded command; `Ok(Some(text))` is printed by the invoking
+    /// process.
+    fn run_command(self: &Rc<Self>, command: CliCommand) -> Result<Option<String>, String> {
+        match command {
+            CliCommand::Reload => {
+                if self.config_source_path.borrow().is_some() {
+                    self.reload_config_from_source();
+                } else {
+                    self.apply_loaded_config(load_config());
+                }
+            }
+            CliCommand::Toggle => {
+                self.bars_hidden.set(!self.bars_hidden.get());
+                self.apply_bar_visibility();
+            }
+            CliCommand::Focus { active } => {
+                modules::focus::set_active(active);
+            }
+            CliCommand::Msg { key, value } => {
+                modules::state_store::publish(cli::MSG_NAMESPACE, &[(key.as_str(), value)]);
+            }
+            CliCommand::Inspect => {
+                return Ok(Some(modules::backend_errors::inspect_report()));
+            }
+            CliCommand::Screenshot { path, output } => {
+                self.save_screenshots(&path, output.as_deref())?;
+            }
+        }
+        Ok(None)
+    }
+
+    /// Writes each bar (or only the bar on `output`) to PNG, ordered by
+    /// connector name so multi-monitor file names are stable.
+    fn save_screenshots(&self, path: &Path, output: Option<&str>) -> Result<(), String> {
+        let mut bars = self
+            .windows
+            .borrow()
+            .values()
+            .map(|window| (window_connector(window), window.clone()))
+            .filter(|(connector, _)| output.is_none_or(|output| output == connector))
+            .collect::<Vec<_>>();
+        if bars.is_empty() {
+            return Err(match output {
+                Some(output) => format!("no bar on output '{output}'"),
+                None => "no bars to capture".to_string(),
+            });
+        }
+        bars.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        let names = bars
+            .iter()
+            .map(|(connector, _)| connector.clone())
+            .collect::<Vec<_>>();
+        for ((connector, window), file) in bars.iter().zip(screenshot::output_paths(path, &names)) {
+            screenshot::save_window_png(window, &file)
+                .map_err(|err| format!("screenshot of bar '{connector}' failed: {err}"))?;
+        }
+        Ok(())
     }
 
     fn apply_loaded_config(self: &Rc<Self>, loaded_config: LoadedConfig) {
+        // Modules pick their default glyphs and layout direction when built,
+        // so a changed icon set or bar orientation rebuilds all of them.
+        let nerd_fonts = modules::icons::nerd_fonts_enabled();
+        apply_icon_set(&loaded_config.config, self.cli_no_nerd_fonts);
+        let reuse_modules = nerd_fonts == modules::icons::nerd_fonts_enabled()
+            && bar_orientations(&self.config.borrow()) == bar_orientations(&loaded_config.config);
+        modules::popovers::configure(&loaded_config.config.popovers);
+        modules::events::configure(&loaded_config.config.reactions);
+        modules::focus::configure(&loaded_config.config.focus);
         *self.config.borrow_mut() = loaded_config.config;
         *self.config_source_path.borrow_mut() = loaded_config.source_path;
 
         let style_runtime = {
             let config = self.config.borrow();
-            style::StyleRuntime::install(&config.style, self.config_source_path.borrow().as_deref())
+            style::StyleRuntime::install(
+                &config.style,
+                &config.colors,
+                self.config_source_path.borrow().as_deref(),
+            )
         };
         *self.style_runtime.borrow_mut() = style_runtime;
 
+        let metrics_runtime = {
+            let config = self.config.borrow();
+            metrics::MetricsRuntime::install(
+                config.metrics.as_ref(),
+                self.config_source_path.borrow().as_deref(),
+            )
+        };
+        *self.metrics_runtime.borrow_mut() = metrics_runtime;
+
         self.install_config_watch();
-        self.rebuild_windows();
+        self.rebuild_windows(reuse_modules);
     }
 
     fn reload_config_from_source(self: &Rc<Self>) {
@@ -134,119 +409,309 @@ impl AppRuntime {
 }
 
 fn main() {
+    // Local-only modes never touch GTK or the display, so handle them before
+    // the application (and its single-instance handling) is set up.
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let profile = match cli::take_profile_option(&mut args) {
+        Ok(profile) => profile,
+        Err(err) => {
+            eprintln!("vibar: {err}");
+            std::process::exit(2);
+        }
+    };
+    if let Some(name) = profile.clone() {
+        config::set_profile_override(name);
+    }
+    for (option, set_override) in [
+        (
+            cli::CONFIG_OPTION,
+            config::set_config_path_override as fn(PathBuf),
+        ),
+        (cli::STYLE_OPTION, config::set_style_path_override),
+    ] {
+        match cli::take_path_option(&mut args, option) {
+            Ok(Some(path)) => set_override(path),
+            Ok(None) => {}
+            Err(err) => {
+                eprintln!("vibar: {err}");
+                std::process::exit(2);
+            }
+        }
+    }
+    if let Some((verb, rest)) = args.split_first() {
+        if verb == waybar_import::COMMAND {
+            if let Err(err) = waybar_import::run(rest) {
+                eprintln!("vibar: {err}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        if verb == preview::COMMAND {
+            let loaded_config = load_config();
+            apply_icon_set(&loaded_config.config, false);
+            if let Err(err) = preview::run(rest) {
+                eprintln!("vibar: {err}");
+                std::process::exit(1);
+            }
+            return;
+        }
+    }
+    if args.iter().any(|arg| arg == headless::STDOUT_FLAG) {
+        let loaded_config = load_config();
+        let no_nerd_fonts = args
+            .iter()
+            .any(|arg| arg.strip_prefix("--") == Some(NO_NERD_FONTS_OPTION));
+        apply_icon_set(&loaded_config.config, no_nerd_fonts);
+        if let Err(err) = headless::run(&loaded_config.config) {
+            eprintln!("vibar: {err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Each profile is its own single instance, so a lock bar can run next
+    // to the regular one and `vibar --profile lock reload` reaches it.
+    // Valid profile names are valid id elements as they are, so the name is
+    // used unchanged and `a-b` and `a_b` stay separate instances.
+    let app_id = match profile.as_deref() {
+        Some(name) => format!("{APP_ID}.{name}"),
+        None => APP_ID.to_string(),
+    };
     let app = Application::builder()
-        .application_id(APP_ID)
-        .flags(gtk::gio::ApplicationFlags::NON_UNIQUE)
+        .application_id(app_id)
+        .flags(
+            gtk::gio::ApplicationFlags::HANDLES_COMMAND_LINE
+                | gtk::gio::ApplicationFlags::ALLOW_REPLACEMENT,
+        )
         .build();
 
-    app.connect_activate(|app| {
+    app.add_main_option(
+        NO_NERD_FONTS_OPTION,
+        gtk::glib::Char::from(0),
+        gtk::glib::OptionFlags::NONE,
+        gtk::glib::OptionArg::None,
+        "Use plain Unicode icon defaults instead of Nerd Font glyphs",
+        None,
+    );
+    app.add_main_option(
+        REPLACE_OPTION,
+        gtk::glib::Char::from(0),
+        gtk::glib::OptionFlags::NONE,
+        gtk::glib::OptionArg::None,
+        "Replace the running vibar instance",
+        None,
+    );
+    // Already applied above; registered so GLib accepts and strips it.
+    app.add_main_option(
+        cli::CONFIG_OPTION,
+        gtk::glib::Char::from(0),
+        gtk::glib::OptionFlags::NONE,
+        gtk::glib::OptionArg::Filename,
+        "Read this config file instead of searching the default locations",
+        Some("PATH"),
+    );
+    app.add_main_option(
+        cli::STYLE_OPTION,
+        gtk::glib::Char::from(0),
+        gtk::glib::OptionFlags::NONE,
+        gtk::glib::OptionArg::Filename,
+        "Load this CSS file on top of the default style instead of style.path",
+        Some("PATH"),
+    );
+    app.add_main_option(
+        cli::PROFILE_OPTION,
+        gtk::glib::Char::from(0),
+        gtk::glib::OptionFlags::NONE,
+        gtk::glib::OptionArg::String,
+        "Use this entry of the config's profiles (lock is built in)",
+        Some("NAME"),
+    );
+    app.add_main_option(
+        STDOUT_OPTION,
+        gtk::glib::Char::from(0),
+        gtk::glib::OptionFlags::NONE,
+        gtk::glib::OptionArg::None,
+        "Print i3bar protocol status lines to stdout instead of showing bars",
+        None,
+    );
+    let cli_no_nerd_fonts = Rc::new(Cell::new(false));
+    app.connect_handle_local_options({
+        let cli_no_nerd_fonts = Rc::clone(&cli_no_nerd_fonts);
+        move |app, options| {
+            cli_no_nerd_fonts.set(options.contains(NO_NERD_FONTS_OPTION));
+            if options.contains(REPLACE_OPTION) {
+                app.set_flags(app.flags() | gtk::gio::ApplicationFlags::REPLACE);
+            }
+            std::ops::ControlFlow::Continue(())
+        }
+    });
+
+    let runtime_slot: Rc<RefCell<Option<Rc<AppRuntime>>>> = Rc::new(RefCell::new(None));
+    app.connect_command_line({
+        let runtime_slot = Rc::clone(&runtime_slot);
+        move |app, command_line| {
+            let args = command_line
+                .arguments()
+                .into_iter()
+                .skip(1)
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+            let command = match cli::parse_command(&args) {
+                Ok(command) => {
+                    command.map(|command| command.resolve_paths(command_line.cwd().as_deref()))
+                }
+                Err(err) => {
+                    printerr_to_command_line(command_line, &format!("vibar: {err}\n"));
+                    return gtk::glib::ExitCode::from(2);
+                }
+            };
+
+            let Some(command) = command else {
+                app.activate();
+                return gtk::glib::ExitCode::SUCCESS;
+            };
+            let runtime = runtime_slot.borrow().clone();
+            match runtime {
+                Some(runtime) => match runtime.run_command(command) {
+                    Ok(output) => {
+                        if let Some(output) = output {
+                            print_to_command_line(command_line, &output);
+                        }
+                        gtk::glib::ExitCode::SUCCESS
+                    }
+                    Err(err) => {
+                        printerr_to_command_line(command_line, &format!("vibar: {err}\n"));
+                        gtk::glib::ExitCode::FAILURE
+                    }
+                },
+                None => {
+                    printerr_to_command_line(
+                        command_line,
+                        &format!("vibar: no running instance to send {command:?} to\n"),
+                    );
+                    gtk::glib::ExitCode::FAILURE
+                }
+            }
+        }
+    });
+
+    app.connect_activate(move |app| {
+        if runtime_slot.borrow().is_some() {
+            return;
+        }
+
         let loaded_config = load_config();
+        apply_icon_set(&loaded_config.config, cli_no_nerd_fonts.get());
+        modules::popovers::configure(&loaded_config.config.popovers);
+        modules::events::configure(&loaded_config.config.reactions);
+        modules::focus::configure(&loaded_config.config.focus);
         let initial_style_runtime = style::StyleRuntime::install(
             &loaded_config.config.style,
+            &loaded_config.config.colors,
+            loaded_config.source_path.as_deref(),
+        );
+        let initial_metrics_runtime = metrics::MetricsRuntime::install(
+            loaded_config.config.metrics.as_ref(),
             loaded_config.source_path.as_deref(),
         );
         let current_config = Rc::new(RefCell::new(loaded_config.config.clone()));
 
-        let windows = Rc::new(RefCell::new(HashMap::new()));
-        sync_monitor_windows(app, &current_config, &windows);
-
         let Some(display) = gdk::Display::default() else {
             return;
         };
         let monitor_model = display.monitors();
-        monitor_model.connect_items_changed({
-            let app = app.clone();
-            let config = Rc::clone(&current_config);
-            let windows = Rc::clone(&windows);
-            move |_, _, _, _| {
-                sync_monitor_windows(&app, &config, &windows);
-            }
-        });
 
         let app_runtime = Rc::new(AppRuntime {
             app: app.clone(),
-            windows,
+            windows: Rc::new(RefCell::new(HashMap::new())),
+            bar_modules: RefCell::new(HashMap::new()),
+            reuse_pools: RefCell::new(HashMap::new()),
             config: current_config,
             config_source_path: RefCell::new(loaded_config.source_path),
             style_runtime: RefCell::new(initial_style_runtime),
-            _monitor_model: monitor_model,
+            metrics_runtime: RefCell::new(initial_metrics_runtime),
+            monitor_model,
             _config_monitor: RefCell::new(None),
             config_reload_source: RefCell::new(None),
+            cli_no_nerd_fonts: cli_no_nerd_fonts.get(),
+            bars_hidden: Cell::new(false),
+            sway_bar_visible: Cell::new(true),
+        });
+        app_runtime.sync_windows();
+        app_runtime.monitor_model.connect_items_changed({
+            let weak_runtime = Rc::downgrade(&app_runtime);
+            move |_, _, _, _| {
+                if let Some(runtime) = weak_runtime.upgrade() {
+                    runtime.sync_windows();
+                }
+            }
         });
         app_runtime.install_config_watch();
-        let app_runtime_for_shutdown = Rc::clone(&app_runtime);
-        app.connect_shutdown(move |_| {
-            let _ = &app_runtime_for_shutdown;
+        *runtime_slot.borrow_mut() = Some(Rc::clone(&app_runtime));
+        app.connect_shutdown({
+            let runtime_slot = Rc::clone(&runtime_slot);
+            move |_| {
+                runtime_slot.borrow_mut().take();
+            }
         });
     });
 
     app.run();
 }
 
-fn sync_monitor_windows(
-    app: &Application,
-    config: &Rc<RefCell<Config>>,
-    windows: &Rc<RefCell<HashMap<String, ApplicationWindow>>>,
-) {
-    let config_snapshot = config.borrow().clone();
-    let monitors = connected_monitors();
-    let monitor_keys = monitors
-        .iter()
-        .map(|monitor| (monitor_key(monitor), monitor.clone()))
-        .collect::<Vec<_>>();
-    let active_keys = monitor_keys
-        .iter()
-        .map(|(key, _)| key.clone())
-        .collect::<HashSet<_>>();
-
-    let mut tracked_windows = windows.borrow_mut();
-    let mut removed_keys = tracked_windows
-        .keys()
-        .filter(|key| *key != FALLBACK_WINDOW_KEY && !active_keys.contains(*key))
-        .cloned()
-        .collect::<Vec<_>>();
-    if !monitor_keys.is_empty() && tracked_windows.contains_key(FALLBACK_WINDOW_KEY) {
-        removed_keys.push(FALLBACK_WINDOW_KEY.to_string());
-    }
-
-    let mut removed_windows = Vec::new();
-    for key in removed_keys {
-        if let Some(window) = tracked_windows.remove(&key) {
-            removed_windows.push(window);
-        }
-    }
-
-    if monitor_keys.is_empty() {
-        if !tracked_windows.contains_key(FALLBACK_WINDOW_KEY) {
-            let window = build_window(app, &config_snapshot, None);
-            debug_dump_dom_if_enabled(&window, None);
-            window.present();
-            tracked_windows.insert(FALLBACK_WINDOW_KEY.to_string(), window);
-        }
-        drop(tracked_windows);
-        defer_close_windows(removed_windows);
-        return;
-    }
+/// Prints on the terminal that ran the command, which for a forwarded
+/// command is not this process's stdout.
+fn print_to_command_line(command_line: &gtk::gio::ApplicationCommandLine, text: &str) {
+    write_to_command_line(
+        command_line,
+        text,
+        gtk::gio::ffi::g_application_command_line_print,
+    );
+}
 
-    for (key, monitor) in monitor_keys {
-        if tracked_windows.contains_key(&key) {
-            continue;
-        }
+/// Like [`print_to_command_line`], for the invoking terminal's stderr.
+fn printerr_to_command_line(command_line: &gtk::gio::ApplicationCommandLine, text: &str) {
+    write_to_command_line(
+        command_line,
+        text,
+        gtk::gio::ffi::g_application_command_line_printerr,
+    );
+}
 
-        attach_monitor_connector_resolve_once(&monitor, app, config, windows);
+type CommandLinePrintFn =
+    unsafe extern "C" fn(*mut gtk::gio::ffi::GApplicationCommandLine, *const c_char, ...);
 
-        let window = build_window(app, &config_snapshot, Some(&monitor));
-        let connector = monitor.connector().map(|value| value.to_string());
-        debug_dump_dom_if_enabled(&window, connector.as_deref());
-        window.present();
-        tracked_windows.insert(key, window);
+/// Passes `text` through a `%s` format, since the non-variadic
+/// `print_literal` / `printerr_literal` bindings need GLib 2.80.
+fn write_to_command_line(
+    command_line: &gtk::gio::ApplicationCommandLine,
+    text: &str,
+    print: CommandLinePrintFn,
+) {
+    let Ok(text) = CString::new(text) else {
+        return;
+    };
+    // SAFETY: `%s` consumes exactly the one NUL-terminated string passed,
+    // and `command_line` outlives the call.
+    unsafe {
+        print(command_line.to_glib_none().0, c"%s".as_ptr(), text.as_ptr());
     }
+}
 
-    drop(tracked_windows);
-    defer_close_windows(removed_windows);
+fn apply_icon_set(config: &Config, cli_no_nerd_fonts: bool) {
+    modules::icons::set_nerd_fonts(config.nerd_fonts && !cli_no_nerd_fonts);
 }
 
 const FALLBACK_WINDOW_KEY: &str = "__fallback__";
+const FALLBACK_BAR_NAME: &str = "bar";
+
+fn window_connector(window: &ApplicationWindow) -> String {
+    window
+        .monitor()
+        .and_then(|monitor| monitor.connector())
+        .map(|connector| connector.to_string())
+        .unwrap_or_else(|| FALLBACK_BAR_NAME.to_string())
+}
 
 fn monitor_key(monitor: &gdk::Monitor) -> String {
     let pointer = monitor.as_ptr();
@@ -256,58 +721,49 @@ fn monitor_key(monitor: &gdk::Monitor) -> String {
     format!("ptr:{pointer:p}")
 }
 
-fn attach_monitor_connector_resolve_once(
-    monitor: &gdk::Monitor,
-    app: &Application,
-    config: &Rc<RefCell<Config>>,
-    windows: &Rc<RefCell<HashMap<String, ApplicationWindow>>>,
-) {
-    if monitor.connector().is_some() {
-        return;
-    }
-
-    let monitor = monitor.clone();
-    let handler_id = Rc::new(RefCell::new(None));
-    let handler_id_for_cb = Rc::clone(&handler_id);
-    let monitor_for_cb = monitor.clone();
-    let id = monitor.connect_connector_notify({
-        let app = app.clone();
-        let config = Rc::clone(config);
-        let windows = Rc::clone(windows);
-        move |item| {
-            if item.connector().is_none() {
-                return;
-            }
-
-            sync_monitor_windows(&app, &config, &windows);
-
-            if let Some(id) = handler_id_for_cb.borrow_mut().take() {
-                monitor_for_cb.disconnect(id);
+/// Tracked bar keys whose monitor is gone. The fallback bar is stale as soon
+/// as any real monitor is present.
+fn stale_window_keys<'a>(
+    tracked_keys: impl Iterator<Item = &'a String>,
+    active_keys: &HashSet<String>,
+) -> Vec<String> {
+    tracked_keys
+        .filter(|key| {
+            if key.as_str() == FALLBACK_WINDOW_KEY {
+                !active_keys.is_empty()
+            } else {
+                !active_keys.contains(*key)
             }
-        }
-    });
-    *handler_id.borrow_mut() = Some(id);
+        })
+        .cloned()
+        .collect()
 }
 
-fn defer_close_windows(removed_windows: Vec<ApplicationWindow>) {
+/// Destroys bars on idle so a monitor removal signal never tears down widgets
+/// mid-dispatch. Destroying (rather than hiding) runs each module's destroy
+/// handlers, which drop their backend subscriptions.
+fn defer_destroy_windows(removed_windows: Vec<ApplicationWindow>) {
     if removed_windows.is_empty() {
         return;
     }
 
     gtk::glib::idle_add_local_once(move || {
-        for window in removed_windows {
-            window.close();
-        }
+        destroy_windows_now(removed_windows);
     });
 }
 
-fn close_windows_now(removed_windows: Vec<ApplicationWindow>) {
-    if removed_windows.is_empty() {
-        return;
+fn destroy_windows_now(removed_windows: Vec<ApplicationWindow>) {
+    for window in removed_windows {
+        window.destroy();
     }
+}
 
-    for window in removed_windows {
-        window.close();
+fn debug_outputs_log(message: &str) {
+    let enabled = std::env::var("VIBAR_DEBUG_OUTPUTS")
+        .map(|v| matches!(v.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false);
+    if enabled {
+        eprintln!("vibar/outputs: {message}");
     }
 }
 
@@ -323,11 +779,20 @@ fn connected_monitors() -> Vec<gdk::Monitor> {
         .collect()
 }
 
+/// Builds a bar, taking unchanged modules from `pool`, and returns it with
+/// the modules it placed.
 fn build_window(
     app: &Application,
     config: &Config,
+    config_source: Option<&Path>,
     monitor: Option<&gdk::Monitor>,
-) -> ApplicationWindow {
+    pool: ModulePool<Widget>,
+) -> (ApplicationWindow, Vec<PlacedModule<Widget>>) {
+    let monitor_connector = monitor
+        .and_then(|item| item.connector())
+        .map(|connector| connector.to_string());
+    let config = &config.resolved_for_output(monitor_connector.as_deref());
+
     let window = ApplicationWindow::builder()
         .application(app)
         .title("vibar")
@@ -336,70 +801,224 @@ fn build_window(
     window.add_css_class("vibar-window");
 
     window.init_layer_shell();
-    window.set_layer(Layer::Top);
+    window.set_namespace(Some(LAYER_NAMESPACE));
+    window.set_layer(match config.layer {
+        BarLayer::Background => Layer::Background,
+        BarLayer::Bottom => Layer::Bottom,
+        BarLayer::Top => Layer::Top,
+        BarLayer::Overlay => Layer::Overlay,
+    });
     window.set_keyboard_mode(KeyboardMode::None);
-    window.set_anchor(Edge::Left, true);
-    window.set_anchor(Edge::Right, true);
-    window.set_anchor(Edge::Bottom, true);
-    window.auto_exclusive_zone_enable();
+    let bar = config.bar;
+    let (edge, span) = match bar.position {
+        BarPosition::Top => (Edge::Top, [Edge::Left, Edge::Right]),
+        BarPosition::Bottom => (Edge::Bottom, [Edge::Left, Edge::Right]),
+        BarPosition::Left => (Edge::Left, [Edge::Top, Edge::Bottom]),
+        BarPosition::Right => (Edge::Right, [Edge::Top, Edge::Bottom]),
+    };
+    for anchor in [edge, span[0], span[1]] {
+        window.set_anchor(anchor, true);
+    }
+    window.set_margin(Edge::Top, bar.margin.top);
+    window.set_margin(Edge::Right, bar.margin.right);
+    window.set_margin(Edge::Bottom, bar.margin.bottom);
+    window.set_margin(Edge::Left, bar.margin.left);
+    match config.exclusive_zone {
+        ExclusiveZone::Auto => window.auto_exclusive_zone_enable(),
+        ExclusiveZone::None => window.set_exclusive_zone(0),
+        ExclusiveZone::Pixels(pixels) => window.set_exclusive_zone(pixels),
+    }
     window.set_focusable(false);
     window.set_focus_on_click(false);
     if let Some(monitor) = monitor {
         window.set_monitor(Some(monitor));
     }
 
-    let root = CenterBox::builder()
-        .orientation(Orientation::Horizontal)
-        .build();
-    root.add_css_class("bar");
-    root.set_focusable(false);
-    root.set_focus_on_click(false);
+    let context = ModuleBuildContext {
+        monitor_connector,
+        monitor: monitor.cloned(),
+        tooltip_format: None,
+        vertical: bar.position.is_vertical(),
+        config_source: config_source.map(Path::to_path_buf),
+        state_namespaces: Default::default(),
+        state_namespace: None,
+    };
+    let orientation = context.orientation();
 
-    let left = GtkBox::new(Orientation::Horizontal, 6);
+    // On vertical bars `left` is the top area and `right` the bottom one.
+    let left = GtkBox::new(orientation, 6);
     left.add_css_class("left");
     left.set_focusable(false);
     left.set_focus_on_click(false);
 
-    let center = GtkBox::new(Orientation::Horizontal, 6);
+    let center = GtkBox::new(orientation, 6);
     center.add_css_class("center");
     center.set_focusable(false);
     center.set_focus_on_click(false);
 
-    let right = GtkBox::new(Orientation::Horizontal, 6);
+    let right = GtkBox::new(orientation, 6);
     right.add_css_class("right");
     right.set_focusable(false);
     right.set_focus_on_click(false);
 
-    let context = ModuleBuildContext {
-        monitor_connector: monitor
-            .and_then(|item| item.connector())
-            .map(|connector| connector.to_string()),
-        monitor: monitor.cloned(),
-    };
+    let mut bar_modules = BarModules::new(pool);
+    build_area(&left, &config.areas.left, &context, &mut bar_modules);
+    build_area(&center, &config.areas.center, &context, &mut bar_modules);
+    build_area(&right, &config.areas.right, &context, &mut bar_modules);
 
-    build_area(&left, &config.areas.left, &context);
-    build_area(&center, &config.areas.center, &context);
-    build_area(&right, &config.areas.right, &context);
+    let root = build_bar_root(config.center_mode, orientation, &left, &center, &right);
+    root.add_css_class("bar");
+    root.add_css_class(bar.position.css_class());
+    if context.vertical {
+        root.add_css_class("vertical");
+    }
+    root.set_focusable(false);
+    root.set_focus_on_click(false);
+    if let Some(connector) = context.monitor_connector.as_deref() {
+        root.add_css_class(&output_css_class(connector));
+    }
 
-    root.set_start_widget(Some(&left));
-    root.set_center_widget(Some(&center));
-    root.set_end_widget(Some(&right));
+    let layers = build_bar_layers(&root, config, context.monitor_connector);
+    if let Some(thickness) = bar.thickness() {
+        let thickness = i32::try_from(thickness).unwrap_or(i32::MAX);
+        if context.vertical {
+            layers.set_size_request(thickness, -1);
+        } else {
+            layers.set_size_request(-1, thickness);
+        }
+    }
+    if !config.interactive {
+        // Pointer input passes through to whatever is below the bar.
+        layers.set_can_target(false);
+        window.connect_realize(|window| {
+            if let Some(surface) = window.surface() {
+                surface.set_input_region(&gtk::cairo::Region::create());
+            }
+        });
+    }
+    window.set_child(Some(&layers));
+    (window, bar_modules.into_placed())
+}
 
-    window.set_child(Some(&root));
-    window
+/// Whether the top-level bar and each per-output bar override run
+/// vertically.
+fn bar_orientations(config: &Config) -> Vec<(Option<String>, bool)> {
+    std::iter::once((None, config.bar))
+        .chain(
+            config
+                .outputs
+                .iter()
+                .filter_map(|(connector, output)| Some((Some(connector.clone()), output.bar?))),
+        )
+        .map(|(connector, bar)| (connector, bar.position.is_vertical()))
+        .collect()
 }
 
-fn build_area(container: &GtkBox, modules: &[ModuleConfig], context: &ModuleBuildContext) {
-    for module in modules {
-        match modules::build_module(module, context) {
-            Ok(widget) => container.append(&widget),
-            Err(err) => {
-                eprintln!("Failed to initialize module {module:?}: {err}");
+/// Stacks a `.bar-background` layer under the bar, so `background-opacity`
+/// fades the background without fading module text.
+fn build_bar_layers(bar: &Widget, config: &Config, connector: Option<String>) -> Widget {
+    let background = GtkBox::new(Orientation::Horizontal, 0);
+    background.add_css_class("bar-background");
+    background.set_focusable(false);
+    background.set_opacity(config.background_opacity.get());
+
+    if let Some(fullscreen_opacity) = config.background_opacity_fullscreen {
+        let opacity = config.background_opacity.get();
+        attach_subscription(
+            &background,
+            subscribe_fullscreen_outputs(),
+            move |background, fullscreen| {
+                background.set_opacity(if fullscreen.covers(connector.as_deref()) {
+                    fullscreen_opacity.get()
+                } else {
+                    opacity
+                });
+            },
+        );
+    }
+
+    let layers = Overlay::new();
+    layers.set_child(Some(&background));
+    layers.add_overlay(bar);
+    layers.set_measure_overlay(bar, true);
+    layers.upcast()
+}
+
+fn build_bar_root(
+    mode: CenterMode,
+    orientation: Orientation,
+    left: &GtkBox,
+    center: &GtkBox,
+    right: &GtkBox,
+) -> Widget {
+    let sides = CenterBox::builder().orientation(orientation).build();
+    sides.set_start_widget(Some(left));
+    sides.set_end_widget(Some(right));
+
+    match mode {
+        CenterMode::Available => {
+            sides.set_center_widget(Some(center));
+            sides.upcast()
+        }
+        CenterMode::Monitor => {
+            // Overlay children are allocated against the full bar width, so
+            // the center area stays on the monitor midpoint no matter how
+            // wide the side areas get.
+            sides.set_focusable(false);
+            sides.set_focus_on_click(false);
+            let overlay = Overlay::new();
+            overlay.set_child(Some(&sides));
+            if orientation == Orientation::Vertical {
+                center.set_valign(Align::Center);
+            } else {
+                center.set_halign(Align::Center);
             }
+            overlay.add_overlay(center);
+            overlay.set_measure_overlay(center, true);
+            overlay.upcast()
         }
     }
 }
 
+fn output_css_class(connector: &str) -> String {
+    let sanitized: String = connector
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("output-{sanitized}")
+}
+
+fn build_area(
+    container: &GtkBox,
+    modules: &[ModuleConfig],
+    context: &ModuleBuildContext,
+    bar_modules: &mut BarModules<Widget>,
+) {
+    for module in modules {
+        let (identity, reused) = bar_modules.next(module);
+        let widget = match reused {
+            Some(widget) => {
+                context.state_namespaces.skip(module);
+                // Still attached to the replaced bar, which is destroyed
+                // once every new bar is built.
+                if let Some(old_area) = widget.parent().and_downcast::<GtkBox>() {
+                    old_area.remove(&widget);
+                }
+                widget
+            }
+            None => match modules::build_module(module, context) {
+                Ok(widget) => widget,
+                Err(err) => {
+                    eprintln!("Failed to initialize module {module:?}: {err}");
+                    continue;
+                }
+            },
+        };
+        container.append(&widget);
+        bar_modules.record(identity, module, &widget);
+    }
+}
+
 fn debug_dump_dom_if_enabled(window: &ApplicationWindow, connector: Option<&str>) {
     if !dom_debug_enabled() {
         return;
@@ -469,6 +1088,34 @@ fn dump_dom_snapshot(window: &ApplicationWindow, monitor_name: &str) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn stale_window_keys_drops_unplugged_monitors_and_fallback() {
+        let tracked = [
+            "connector:DP-1|ptr:0x1".to_string(),
+            "connector:HDMI-A-1|ptr:0x2".to_string(),
+            FALLBACK_WINDOW_KEY.to_string(),
+        ];
+        let active = HashSet::from(["connector:DP-1|ptr:0x1".to_string()]);
+        let mut stale = stale_window_keys(tracked.iter(), &active);
+        stale.sort();
+        assert_eq!(
+            stale,
+            vec![
+                FALLBACK_WINDOW_KEY.to_string(),
+                "connector:HDMI-A-1|ptr:0x2".to_string()
+            ]
+        );
+
+        let none_active = HashSet::new();
+        assert_eq!(
+            stale_window_keys(tracked.iter(), &none_active),
+            vec![
+                "connector:DP-1|ptr:0x1".to_string(),
+                "connector:HDMI-A-1|ptr:0x2".to_string()
+            ]
+        );
+    }
+
     #[test]
     fn parse_config_defaults_to_builtin_areas() {
         let cfg = config::parse_config("{}").expect("config should parse");
@@ -485,13 +1132,36 @@ mod tests {
 
         let exec_cfg =
             modules::exec::parse_config(&cfg.areas.left[0]).expect("exec config expected");
-        assert_eq!(exec_cfg.interval_secs, 5);
+        assert_eq!(exec_cfg.interval, modules::exec::ExecInterval::Secs(5));
     }
 
     #[test]
     fn normalized_exec_interval_enforces_lower_bound() {
-        assert_eq!(modules::exec::normalized_exec_interval(0), 1);
-        assert_eq!(modules::exec::normalized_exec_interval(1), 1);
-        assert_eq!(modules::exec::normalized_exec_interval(10), 10);
+        use modules::exec::{normalized_exec_interval, ExecInterval, ExecIntervalMode};
+
+        assert_eq!(
+            normalized_exec_interval(ExecInterval::Secs(0)),
+            ExecInterval::Secs(1)
+        );
+        assert_eq!(
+            normalized_exec_interval(ExecInterval::Secs(1)),
+            ExecInterval::Secs(1)
+        );
+        assert_eq!(
+            normalized_exec_interval(ExecInterval::Secs(10)),
+            ExecInterval::Secs(10)
+        );
+        for mode in [ExecIntervalMode::Once, ExecIntervalMode::Signal] {
+            assert_eq!(
+                normalized_exec_interval(ExecInterval::Mode(mode)),
+                ExecInterval::Mode(mode)
+            );
+        }
+    }
+
+    #[test]
+    fn output_css_class_sanitizes_connector() {
+        assert_eq!(output_css_class("DP-1"), "output-DP-1");
+        assert_eq!(output_css_class("HDMI A.1"), "output-HDMI-A-1");
     }
 }
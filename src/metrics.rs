@@ -0,0 +1,170 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+use crate::config::{resolve_config_relative_path, MetricsConfig};
+use crate::modules::runtime_stats::{self, RuntimeStats};
+use crate::modules::state_store;
+
+const MIN_METRICS_INTERVAL_SECS: u32 = 1;
+
+/// Periodically writes Prometheus text-format metrics to a file for the
+/// node_exporter textfile collector. Dropping the runtime stops the writer.
+pub(crate) struct MetricsRuntime {
+    _stop_tx: mpsc::Sender<()>,
+}
+
+impl MetricsRuntime {
+    pub(crate) fn install(
+        metrics: Option<&MetricsConfig>,
+        config_source: Option<&Path>,
+    ) -> Option<Self> {
+        let metrics = metrics?;
+        let path = resolve_config_relative_path(&metrics.textfile, config_source);
+        let interval = Duration::from_secs(u64::from(
+            metrics.interval_secs.max(MIN_METRICS_INTERVAL_SECS),
+        ));
+
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        std::thread::spawn(move || loop {
+            if let Err(err) = write_metrics_file(&path) {
+                eprintln!("vibar/metrics: failed to write {}: {err}", path.display());
+            }
+            match stop_rx.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => {}
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+            }
+        });
+
+        Some(Self { _stop_tx: stop_tx })
+    }
+}
+
+/// Writes via a temporary sibling file + rename so the collector never reads
+/// a partially written file.
+fn write_metrics_file(path: &PathBuf) -> std::io::Result<()> {
+    let content = render_metrics(&state_store::snapshot(), &runtime_stats::snapshot());
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, path)
+}
+
+fn render_metrics(values: &[(String, String)], stats: &RuntimeStats) -> String {
+    let mut out = String::new();
+
+    out.push_str("# HELP vibar_module_value Latest numeric value published by a module.\n");
+    out.push_str("# TYPE vibar_module_value gauge\n");
+    for (key, value) in values {
+        let Some((module, field)) = key.split_once('.') else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse::<f64>() else {
+            continue;
+        };
+        if !value.is_finite() {
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "vibar_module_value{{module=\"{}\",key=\"{}\"}} {value}",
+            escape_label_value(module),
+            escape_label_value(field)
+        );
+    }
+
+    write_metric(
+        &mut out,
+        "vibar_backend_workers_started_total",
+        "counter",
+        "Shared backend workers started since launch.",
+        stats.backends_started as f64,
+    );
+    write_metric(
+        &mut out,
+        "vibar_backend_workers_active",
+        "gauge",
+        "Shared backend workers currently running.",
+        stats.active_backends() as f64,
+    );
+    write_metric(
+        &mut out,
+        "vibar_broadcasts_total",
+        "counter",
+        "Backend updates broadcast to subscribers.",
+        stats.broadcasts as f64,
+    );
+    write_metric(
+        &mut out,
+        "vibar_ui_updates_total",
+        "counter",
+        "Backend updates applied to widgets on the GTK main loop.",
+        stats.ui_updates as f64,
+    );
+    write_metric(
+        &mut out,
+        "vibar_ui_update_seconds_total",
+        "counter",
+        "Time spent applying backend updates to widgets.",
+        stats.ui_update_time.as_secs_f64(),
+    );
+
+    out
+}
+
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> RuntimeStats {
+        RuntimeStats {
+            backends_started: 4,
+            backends_stopped: 1,
+            broadcasts: 10,
+            ui_updates: 8,
+            ui_update_time: Duration::from_millis(1500),
+        }
+    }
+
+    #[test]
+    fn render_metrics_exports_numeric_module_values_only() {
+        let values = vec![
+            ("battery.percent".to_string(), "81".to_string()),
+            ("battery.status".to_string(), "Discharging".to_string()),
+            ("cpu.used_percentage".to_string(), "12.5".to_string()),
+        ];
+        let text = render_metrics(&values, &stats());
+        assert!(text.contains("vibar_module_value{module=\"battery\",key=\"percent\"} 81\n"));
+        assert!(text.contains("vibar_module_value{module=\"cpu\",key=\"used_percentage\"} 12.5\n"));
+        assert!(!text.contains("Discharging"));
+    }
+
+    #[test]
+    fn render_metrics_exports_runtime_stats() {
+        let text = render_metrics(&[], &stats());
+        assert!(text.contains("vibar_backend_workers_started_total 4\n"));
+        assert!(text.contains("vibar_backend_workers_active 3\n"));
+        assert!(text.contains("vibar_ui_update_seconds_total 1.5\n"));
+    }
+
+    #[test]
+    fn escape_label_value_escapes_quotes_and_backslashes() {
+        assert_eq!(escape_label_value("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
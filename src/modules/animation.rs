@@ -0,0 +1,181 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+use gtk::glib::ControlFlow;
+use gtk::prelude::*;
+use gtk::{TickCallbackId, Widget};
+
+/// Result of a single animation step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AnimationStep {
+    /// Keep receiving frame ticks.
+    Running,
+    /// Nothing is moving; stop ticking until [`FrameAnimation::wake`] is called.
+    Idle,
+}
+
+/// Frame-clock driven animation bound to a widget.
+///
+/// The step callback receives the monotonic time elapsed since the previous
+/// frame (zero on the first frame after a wake). The tick callback is only
+/// installed while the step reports [`AnimationStep::Running`], so idle
+/// animations cost nothing.
+#[derive(Clone)]
+pub(crate) struct FrameAnimation {
+    inner: Rc<FrameAnimationInner>,
+}
+
+struct FrameAnimationInner {
+    widget: gtk::glib::WeakRef<Widget>,
+    step: RefCell<Box<dyn FnMut(Duration) -> AnimationStep>>,
+    tick_id: RefCell<Option<TickCallbackId>>,
+    last_frame_us: Cell<Option<i64>>,
+}
+
+impl FrameAnimation {
+    pub(crate) fn new(
+        widget: &impl IsA<Widget>,
+        step: impl FnMut(Duration) -> AnimationStep + 'static,
+    ) -> Self {
+        Self {
+            inner: Rc::new(FrameAnimationInner {
+                widget: widget.as_ref().downgrade(),
+                step: RefCell::new(Box::new(step)),
+                tick_id: RefCell::new(None),
+                last_frame_us: Cell::new(None),
+            }),
+        }
+    }
+
+    /// Starts ticking if the animation is currently idle.
+    pub(crate) fn wake(&self) {
+        if self.inner.tick_id.borrow().is_some() {
+            return;
+        }
+        let Some(widget) = self.inner.widget.upgrade() else {
+            return;
+        };
+
+        self.inner.last_frame_us.set(None);
+        let inner = Rc::downgrade(&self.inner);
+        let tick_id = widget.add_tick_callback(move |_, frame_clock| {
+            let Some(inner) = inner.upgrade() else {
+                return ControlFlow::Break;
+            };
+
+            let now_us = frame_clock.frame_time();
+            let elapsed = frame_delta(inner.last_frame_us.replace(Some(now_us)), now_us);
+            let step = match inner.step.try_borrow_mut() {
+                Ok(mut step) => step(elapsed),
+                Err(_) => AnimationStep::Running,
+            };
+
+            match step {
+                AnimationStep::Running => ControlFlow::Continue,
+                AnimationStep::Idle => {
+                    inner.tick_id.borrow_mut().take();
+                    ControlFlow::Break
+                }
+            }
+        });
+        *self.inner.tick_id.borrow_mut() = Some(tick_id);
+    }
+}
+
+fn frame_delta(previous_us: Option<i64>, now_us: i64) -> Duration {
+    previous_us
+        .map(|previous| Duration::from_micros(now_us.saturating_sub(previous).max(0) as u64))
+        .unwrap_or(Duration::ZERO)
+}
+
+/// Fixed-duration interpolation between two values, advanced by frame deltas.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Tween {
+    from: f64,
+    to: f64,
+    duration: Duration,
+    elapsed: Duration,
+    easing: fn(f64) -> f64,
+}
+
+impl Tween {
+    pub(crate) fn new(from: f64, to: f64, duration: Duration, easing: fn(f64) -> f64) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            elapsed: Duration::ZERO,
+            easing,
+        }
+    }
+
+    /// Advances by `delta` and returns the current value.
+    pub(crate) fn advance(&mut self, delta: Duration) -> f64 {
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+        self.value()
+    }
+
+    pub(crate) fn value(&self) -> f64 {
+        if self.duration.is_zero() {
+            return self.to;
+        }
+        let progress = self.elapsed.as_secs_f64() / self.duration.as_secs_f64();
+        lerp(self.from, self.to, (self.easing)(progress))
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+pub(crate) fn lerp(from: f64, to: f64, t: f64) -> f64 {
+    from + (to - from) * t
+}
+
+pub(crate) fn ease_out_cubic(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+    1.0 - (1.0 - t).powi(3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_delta_is_zero_on_first_frame() {
+        assert_eq!(frame_delta(None, 1_000_000), Duration::ZERO);
+        assert_eq!(
+            frame_delta(Some(1_000_000), 1_016_000),
+            Duration::from_millis(16)
+        );
+        assert_eq!(frame_delta(Some(2_000), 1_000), Duration::ZERO);
+    }
+
+    #[test]
+    fn ease_out_cubic_clamps_and_hits_endpoints() {
+        assert_eq!(ease_out_cubic(-1.0), 0.0);
+        assert_eq!(ease_out_cubic(0.0), 0.0);
+        assert_eq!(ease_out_cubic(1.0), 1.0);
+        assert_eq!(ease_out_cubic(2.0), 1.0);
+        assert!(ease_out_cubic(0.5) > 0.5);
+    }
+
+    #[test]
+    fn tween_advances_to_target_and_finishes() {
+        let mut tween = Tween::new(100.0, 0.0, Duration::from_millis(200), ease_out_cubic);
+        assert_eq!(tween.value(), 100.0);
+        let mid = tween.advance(Duration::from_millis(100));
+        assert!(mid > 0.0 && mid < 50.0);
+        assert!(!tween.is_finished());
+        assert_eq!(tween.advance(Duration::from_millis(500)), 0.0);
+        assert!(tween.is_finished());
+    }
+
+    #[test]
+    fn zero_duration_tween_is_immediately_at_target() {
+        let tween = Tween::new(5.0, 1.0, Duration::ZERO, ease_out_cubic);
+        assert_eq!(tween.value(), 1.0);
+        assert!(tween.is_finished());
+    }
+}
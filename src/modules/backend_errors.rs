@@ -0,0 +1,180 @@
+//! Deduplicated error logging for backend workers that retry forever.
+//!
+//! The first occurrence of an error is logged right away; while the same
+//! error keeps coming back, only a repeat count is logged every few minutes.
+//! The current error of each backend stays available to `vibar inspect`.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+const REPEAT_SUMMARY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone)]
+struct BackendError {
+    message: String,
+    first_seen: Instant,
+    last_logged: Instant,
+    /// Occurrences of `message` since it first appeared.
+    count: u64,
+    /// Occurrences not yet covered by a log line.
+    unlogged: u64,
+}
+
+type ErrorTable = BTreeMap<String, BackendError>;
+
+fn errors() -> &'static Mutex<ErrorTable> {
+    static ERRORS: OnceLock<Mutex<ErrorTable>> = OnceLock::new();
+    ERRORS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Records a failed attempt of `backend` (for example `"pulseaudio"`) and
+/// logs it unless the same error was already logged recently.
+pub(crate) fn report(backend: &str, message: impl Into<String>) {
+    let line = {
+        let mut errors = errors().lock().expect("backend errors mutex poisoned");
+        record(&mut errors, backend, message.into(), Instant::now())
+    };
+    if let Some(line) = line {
+        eprintln!("vibar/{backend}: {line}");
+    }
+}
+
+/// Marks `backend` healthy again; logs the recovery if it had an error.
+pub(crate) fn clear(backend: &str) {
+    let removed = errors()
+        .lock()
+        .expect("backend errors mutex poisoned")
+        .remove(backend);
+    if let Some(error) = removed {
+        eprintln!(
+            "vibar/{backend}: recovered after {} failed attempt(s)",
+            error.count
+        );
+    }
+}
+
+/// Current error of every failing backend, one per line, for `vibar inspect`.
+pub(crate) fn inspect_report() -> String {
+    let errors = errors().lock().expect("backend errors mutex poisoned");
+    format_report(&errors, Instant::now())
+}
+
+/// Returns the line to log, if any.
+fn record(errors: &mut ErrorTable, backend: &str, message: String, now: Instant) -> Option<String> {
+    match errors.get_mut(backend) {
+        Some(error) if error.message == message => {
+            error.count += 1;
+            error.unlogged += 1;
+            let elapsed = now.saturating_duration_since(error.last_logged);
+            if elapsed < REPEAT_SUMMARY_INTERVAL {
+                return None;
+            }
+            let line = format!(
+                "{message} (repeated {} times in the last {} min)",
+                error.unlogged,
+                elapsed.as_secs() / 60
+            );
+            error.unlogged = 0;
+            error.last_logged = now;
+            Some(line)
+        }
+        _ => {
+            errors.insert(
+                backend.to_string(),
+                BackendError {
+                    message: message.clone(),
+                    first_seen: now,
+                    last_logged: now,
+                    count: 1,
+                    unlogged: 0,
+                },
+            );
+            Some(message)
+        }
+    }
+}
+
+fn format_report(errors: &ErrorTable, now: Instant) -> String {
+    if errors.is_empty() {
+        return "backend errors: none\n".to_string();
+    }
+    let mut report = "backend errors:\n".to_string();
+    for (backend, error) in errors {
+        report.push_str(&format!(
+            "  {backend}: {} ({} time(s), failing for {}s)\n",
+            error.message,
+            error.count,
+            now.saturating_duration_since(error.first_seen).as_secs()
+        ));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_logs_first_occurrence_then_periodic_counts() {
+        let mut errors = ErrorTable::new();
+        let start = Instant::now();
+
+        assert_eq!(
+            record(&mut errors, "sway", "connection refused".to_string(), start),
+            Some("connection refused".to_string())
+        );
+        assert_eq!(
+            record(
+                &mut errors,
+                "sway",
+                "connection refused".to_string(),
+                start + Duration::from_secs(2)
+            ),
+            None
+        );
+        assert_eq!(
+            record(
+                &mut errors,
+                "sway",
+                "connection refused".to_string(),
+                start + REPEAT_SUMMARY_INTERVAL
+            ),
+            Some("connection refused (repeated 2 times in the last 5 min)".to_string())
+        );
+        assert_eq!(
+            record(
+                &mut errors,
+                "sway",
+                "socket not found".to_string(),
+                start + REPEAT_SUMMARY_INTERVAL + Duration::from_secs(1)
+            ),
+            Some("socket not found".to_string())
+        );
+        assert_eq!(errors["sway"].count, 1);
+    }
+
+    #[test]
+    fn format_report_lists_current_errors() {
+        let mut errors = ErrorTable::new();
+        let start = Instant::now();
+        assert_eq!(format_report(&errors, start), "backend errors: none\n");
+
+        record(
+            &mut errors,
+            "pulseaudio",
+            "context failed".to_string(),
+            start,
+        );
+        record(
+            &mut errors,
+            "pulseaudio",
+            "context failed".to_string(),
+            start,
+        );
+        assert_eq!(
+            format_report(&errors, start + Duration::from_secs(30)),
+            "backend errors:\n  pulseaudio: context failed (2 time(s), failing for 30s)\n"
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! `background` shared by every module type: wraps the module in a
+//! `.module-bg` box styled from config (color, corner radius, padding), so
+//! per-module pills need no hand-written CSS. Each distinct style gets one
+//! generated class and rule in a display-wide provider.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use gtk::gdk;
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Orientation, Widget};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+const BACKGROUND_KEY: &str = "background";
+const BACKGROUND_CLASS: &str = "module-bg";
+
+/// Values for one generated rule; unset fields keep the `.module-bg` CSS.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+pub(crate) struct BackgroundStyle {
+    #[serde(default)]
+    pub(crate) color: Option<String>,
+    /// Corner radius in pixels.
+    #[serde(default)]
+    pub(crate) radius: Option<u32>,
+    /// CSS `padding` shorthand, for example `"2px 8px"`.
+    #[serde(default)]
+    pub(crate) padding: Option<String>,
+}
+
+/// `background`: `true` for the default `.module-bg` look, or a style.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BackgroundValue {
+    Enabled(bool),
+    Style(BackgroundStyle),
+}
+
+#[derive(Default)]
+struct GeneratedRules {
+    classes: HashMap<BackgroundStyle, String>,
+    css: String,
+    provider: Option<gtk::CssProvider>,
+}
+
+thread_local! {
+    static RULES: RefCell<GeneratedRules> = RefCell::new(GeneratedRules::default());
+}
+
+/// Removes the `background` key from `config` and parses it. Returns `None`
+/// when the module is not wrapped.
+pub(crate) fn take_background(
+    config: &mut Map<String, Value>,
+) -> Result<Option<BackgroundStyle>, String> {
+    let Some(raw) = config.remove(BACKGROUND_KEY) else {
+        return Ok(None);
+    };
+    let style = match serde_json::from_value(raw)
+        .map_err(|err| format!("invalid `{BACKGROUND_KEY}`: {err}"))?
+    {
+        BackgroundValue::Enabled(false) => return Ok(None),
+        BackgroundValue::Enabled(true) => BackgroundStyle::default(),
+        BackgroundValue::Style(style) => style,
+    };
+    for (field, value) in [("color", &style.color), ("padding", &style.padding)] {
+        if value
+            .as_deref()
+            .is_some_and(|value| value.trim().is_empty() || value.contains([';', '{', '}', '\n']))
+        {
+            return Err(format!(
+                "invalid `{BACKGROUND_KEY}.{field}` '{}'",
+                value.as_deref().unwrap_or_default()
+            ));
+        }
+    }
+    Ok(Some(style))
+}
+
+/// Puts `child` in a `.module-bg` box that follows its visibility, so a
+/// module that hides itself does not leave an empty pill behind.
+pub(crate) fn wrap(module_type: &str, child: &Widget, style: &BackgroundStyle) -> Widget {
+    let wrapper = GtkBox::new(Orientation::Horizontal, 0);
+    wrapper.add_css_class(BACKGROUND_CLASS);
+    wrapper.add_css_class(&type_class(module_type));
+    if let Some(class_name) = style_class(style) {
+        wrapper.add_css_class(&class_name);
+    }
+    wrapper.set_focusable(false);
+    child
+        .bind_property("visible", &wrapper, "visible")
+        .sync_create()
+        .build();
+    wrapper.append(child);
+    wrapper.upcast()
+}
+
+/// `module-bg-<type>`, with `/` and other non-identifier characters as `-`.
+fn type_class(module_type: &str) -> String {
+    let sanitized: String = module_type
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("{BACKGROUND_CLASS}-{sanitized}")
+}
+
+fn style_class(style: &BackgroundStyle) -> Option<String> {
+    let declarations = style_declarations(style);
+    if declarations.is_empty() {
+        return None;
+    }
+    RULES.with(|rules| {
+        let mut rules = rules.borrow_mut();
+        if let Some(class_name) = rules.classes.get(style) {
+            return Some(class_name.clone());
+        }
+        let class_name = format!("{BACKGROUND_CLASS}-style-{}", rules.classes.len());
+        rules.css.push_str(&format!(
+            ".{BACKGROUND_CLASS}.{class_name} {{ {declarations} }}\n"
+        ));
+        rules.classes.insert(style.clone(), class_name.clone());
+        rules.reload();
+        Some(class_name)
+    })
+}
+
+fn style_declarations(style: &BackgroundStyle) -> String {
+    let mut declarations = Vec::new();
+    if let Some(color) = style.color.as_deref() {
+        declarations.push(format!("background: {};", color.trim()));
+    }
+    if let Some(radius) = style.radius {
+        declarations.push(format!("border-radius: {radius}px;"));
+    }
+    if let Some(padding) = style.padding.as_deref() {
+        declarations.push(format!("padding: {};", padding.trim()));
+    }
+    declarations.join(" ")
+}
+
+impl GeneratedRules {
+    fn reload(&mut self) {
+        if self.provider.is_none() {
+            let Some(display) = gdk::Display::default() else {
+                return;
+            };
+            let provider = gtk::CssProvider::new();
+            gtk::style_context_add_provider_for_display(
+                &display,
+                &provider,
+                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+            );
+            self.provider = Some(provider);
+        }
+        if let Some(provider) = self.provider.as_ref() {
+            provider.load_from_data(&self.css);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn fields(value: Value) -> Map<String, Value> {
+        serde_json::from_value(value).expect("config map should parse")
+    }
+
+    #[test]
+    fn take_background_accepts_flag_and_style() {
+        let mut config = fields(json!({ "format": "{}", "background": true }));
+        assert_eq!(
+            take_background(&mut config).expect("flag should parse"),
+            Some(BackgroundStyle::default())
+        );
+        assert!(!config.contains_key(BACKGROUND_KEY));
+
+        let mut config = fields(json!({ "background": false }));
+        assert_eq!(
+            take_background(&mut config).expect("flag should parse"),
+            None
+        );
+
+        let mut config = fields(json!({
+            "background": { "color": "alpha(@accent, 0.2)", "radius": 8, "padding": "2px 8px" }
+        }));
+        let style = take_background(&mut config)
+            .expect("style should parse")
+            .expect("style should be enabled");
+        assert_eq!(
+            style_declarations(&style),
+            "background: alpha(@accent, 0.2); border-radius: 8px; padding: 2px 8px;"
+        );
+
+        let mut config = fields(json!({ "background": { "color": "red; } .bar {" } }));
+        assert!(take_background(&mut config).is_err());
+    }
+
+    #[test]
+    fn type_class_sanitizes_module_type() {
+        assert_eq!(type_class("clock"), "module-bg-clock");
+        assert_eq!(
+            type_class("pulseaudio/mic-meter"),
+            "module-bg-pulseaudio-mic-meter"
+        );
+    }
+}
@@ -2,6 +2,7 @@ use std::fs;
 use std::os::fd::AsRawFd;
 use std::path::Path;
 use std::process::Command;
+use std::rc::Rc;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
@@ -13,8 +14,13 @@ use serde_json::Value;
 use zbus::blocking::{Connection, Proxy};
 
 use crate::modules::broadcaster::{attach_subscription, BackendRegistry, Subscription};
+use crate::modules::fd_wait::wait_for_readable_fd;
+use crate::modules::text::{attach_text_subscription, visible_block, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
+use crate::modules::touch;
 use crate::modules::{
-    escape_markup_text, render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel,
+    escape_markup_text, icons, render_markup_template, ModuleBuildContext, ModuleConfig,
+    ModuleLabel,
 };
 
 use super::ModuleFactory;
@@ -47,9 +53,9 @@ pub(crate) struct BacklightConfig {
     pub(crate) click: Option<String>,
     #[serde(rename = "on-click", default)]
     pub(crate) on_click: Option<String>,
-    #[serde(rename = "on-scroll-up", default)]
+    #[serde(rename = "on-scroll-up", alias = "on_scroll_up", default)]
     pub(crate) on_scroll_up: Option<String>,
-    #[serde(rename = "on-scroll-down", default)]
+    #[serde(rename = "on-scroll-down", alias = "on_scroll_down", default)]
     pub(crate) on_scroll_down: Option<String>,
     #[serde(rename = "scroll-step", default = "default_scroll_step")]
     pub(crate) scroll_step: f64,
@@ -76,6 +82,7 @@ struct BacklightSnapshot {
 #[derive(Debug, Clone)]
 struct BacklightUiUpdate {
     text: String,
+    tooltip: String,
     visible: bool,
     level_class: &'static str,
 }
@@ -112,6 +119,7 @@ struct SharedBacklightState {
 struct BacklightSharedKey {
     device: Option<String>,
     format: String,
+    tooltip_format: Option<String>,
     format_icons: Vec<String>,
     interval_secs: u32,
 }
@@ -125,9 +133,27 @@ impl ModuleFactory for BacklightFactory {
         MODULE_TYPE
     }
 
-    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn handles_scroll_commands(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_backlight_module(parsed, context.tooltip_format.clone()).upcast())
+    }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
         let parsed = parse_config(config)?;
-        Ok(build_backlight_module(parsed).upcast())
+        let interval_secs = normalized_backlight_interval(parsed.interval_secs);
+        let (subscription, _control_tx) = subscribe_shared_backlight(&parsed, None, interval_secs);
+        attach_text_subscription(subscription, sink, |update| {
+            visible_block(&update.text).filter(|_| update.visible)
+        });
+        Ok(())
     }
 }
 
@@ -144,17 +170,10 @@ fn default_min_brightness() -> f64 {
 }
 
 fn default_backlight_icons() -> Vec<String> {
-    vec![
-        "".to_string(),
-        "".to_string(),
-        "".to_string(),
-        "".to_string(),
-        "".to_string(),
-        "".to_string(),
-        "".to_string(),
-        "".to_string(),
-        "".to_string(),
-    ]
+    icons::default_icon_list(
+        &["", "", "", "", "", "", "", "", ""],
+        &["🔅", "🔆", "☀"],
+    )
 }
 
 pub(crate) fn parse_config(module: &ModuleConfig) -> Result<BacklightConfig, String> {
@@ -181,6 +200,7 @@ fn backlight_registry() -> &'static BackendRegistry<BacklightSharedKey, SharedBa
 
 fn subscribe_shared_backlight(
     config: &BacklightConfig,
+    tooltip_format: Option<String>,
     effective_interval_secs: u32,
 ) -> (
     Subscription<BacklightUiUpdate>,
@@ -193,6 +213,7 @@ fn subscribe_shared_backlight(
     let key = BacklightSharedKey {
         device: config.device.clone(),
         format: format.clone(),
+        tooltip_format,
         format_icons: config.format_icons.clone(),
         interval_secs: effective_interval_secs,
     };
@@ -246,7 +267,7 @@ fn start_backlight_worker(
     });
 }
 
-fn build_backlight_module(config: BacklightConfig) -> Label {
+fn build_backlight_module(config: BacklightConfig, tooltip_format: Option<String>) -> Label {
     let BacklightConfig {
         click,
         on_click,
@@ -273,61 +294,61 @@ fn build_backlight_module(config: BacklightConfig) -> Label {
         );
     }
 
+    let tooltip = tooltip_format
+        .is_some()
+        .then(|| ModuleTooltip::attach(&label));
     let (ui_subscription, control_tx) =
-        subscribe_shared_backlight(&config, effective_interval_secs);
+        subscribe_shared_backlight(&config, tooltip_format, effective_interval_secs);
 
-    attach_subscription(&label, ui_subscription, |label, update| {
+    attach_subscription(&label, ui_subscription, move |label, update| {
         apply_backlight_ui_update(label, &update);
+        if let Some(tooltip) = &tooltip {
+            tooltip.set_markup(&update.tooltip);
+        }
     });
 
     let scroll_step = normalized_scroll_step(scroll_step);
     if scroll_step > 0.0 || on_scroll_up.is_some() || on_scroll_down.is_some() {
-        let scroll = EventControllerScroll::new(
-            EventControllerScrollFlags::VERTICAL | EventControllerScrollFlags::DISCRETE,
-        );
-
-        if on_scroll_up.is_some() || on_scroll_down.is_some() {
+        let adjust: Rc<dyn Fn(bool)> = if on_scroll_up.is_some() || on_scroll_down.is_some() {
             let up_command = on_scroll_up;
             let down_command = on_scroll_down;
-            scroll.connect_scroll(move |_, _, dy| {
-                if dy < 0.0 {
-                    if let Some(command) = up_command.as_ref() {
-                        spawn_shell_command(command);
-                    }
-                    return gtk::glib::Propagation::Stop;
-                }
-                if dy > 0.0 {
-                    if let Some(command) = down_command.as_ref() {
-                        spawn_shell_command(command);
-                    }
-                    return gtk::glib::Propagation::Stop;
+            Rc::new(move |increase| {
+                let command = if increase {
+                    up_command.as_ref()
+                } else {
+                    down_command.as_ref()
+                };
+                if let Some(command) = command {
+                    spawn_shell_command(command);
                 }
-                gtk::glib::Propagation::Proceed
-            });
-        } else if scroll_step > 0.0 {
+            })
+        } else {
             let clamped_min_brightness = min_brightness.clamp(0.0, 100.0);
-            scroll.connect_scroll(move |_, _, dy| {
-                if dy < 0.0 {
-                    let _ = control_tx.send(BacklightControlMessage::AdjustByPercent {
-                        increase: true,
-                        step_percent: scroll_step,
-                        min_percent: clamped_min_brightness,
-                    });
-                    return gtk::glib::Propagation::Stop;
-                }
-                if dy > 0.0 {
-                    let _ = control_tx.send(BacklightControlMessage::AdjustByPercent {
-                        increase: false,
-                        step_percent: scroll_step,
-                        min_percent: clamped_min_brightness,
-                    });
-                    return gtk::glib::Propagation::Stop;
-                }
-                gtk::glib::Propagation::Proceed
-            });
-        }
+            Rc::new(move |increase| {
+                let _ = control_tx.send(BacklightControlMessage::AdjustByPercent {
+                    increase,
+                    step_percent: scroll_step,
+                    min_percent: clamped_min_brightness,
+                });
+            })
+        };
 
+        let scroll = EventControllerScroll::new(
+            EventControllerScrollFlags::VERTICAL | EventControllerScrollFlags::DISCRETE,
+        );
+        scroll.connect_scroll({
+            let adjust = Rc::clone(&adjust);
+            move |_, _, dy| {
+                if dy == 0.0 {
+                    return gtk::glib::Propagation::Proceed;
+                }
+                adjust(dy < 0.0);
+                gtk::glib::Propagation::Stop
+            }
+        });
         label.add_controller(scroll);
+
+        touch::attach_vertical_drag_steps(&label, move |increase| adjust(increase));
     }
 
     label
@@ -355,6 +376,7 @@ fn run_backlight_backend_loop(
     interval_secs: u32,
 ) {
     let resync_interval = Duration::from_secs(u64::from(interval_secs));
+    let tooltip_format = key.tooltip_format.as_deref();
     let mut last_resync = Instant::now();
     let mut backend = BacklightBackend::new(preferred_device);
     let mut udev_monitor = match UdevMonitor::new() {
@@ -368,7 +390,7 @@ fn run_backlight_backend_loop(
     backend.refresh_from_sysfs();
     shared
         .broadcaster
-        .broadcast(backend.build_ui_update(&format, &format_icons));
+        .broadcast(backend.build_ui_update(&format, tooltip_format, &format_icons));
 
     loop {
         if shared.broadcaster.subscriber_count() == 0 {
@@ -381,9 +403,11 @@ fn run_backlight_backend_loop(
                 backend.last_error = Some(err);
             }
             backend.refresh_from_sysfs();
-            shared
-                .broadcaster
-                .broadcast(backend.build_ui_update(&format, &format_icons));
+            shared.broadcaster.broadcast(backend.build_ui_update(
+                &format,
+                tooltip_format,
+                &format_icons,
+            ));
         }
 
         let wake_timeout =
@@ -394,9 +418,11 @@ fn run_backlight_backend_loop(
                 Ok(true) => {
                     if monitor.drain_events() {
                         backend.refresh_from_sysfs();
-                        shared
-                            .broadcaster
-                            .broadcast(backend.build_ui_update(&format, &format_icons));
+                        shared.broadcaster.broadcast(backend.build_ui_update(
+                            &format,
+                            tooltip_format,
+                            &format_icons,
+                        ));
                     }
                 }
                 Ok(false) => {}
@@ -411,9 +437,11 @@ fn run_backlight_backend_loop(
 
         if last_resync.elapsed() >= resync_interval {
             backend.refresh_from_sysfs();
-            shared
-                .broadcaster
-                .broadcast(backend.build_ui_update(&format, &format_icons));
+            shared.broadcaster.broadcast(backend.build_ui_update(
+                &format,
+                tooltip_format,
+                &format_icons,
+            ));
             last_resync = Instant::now();
         }
     }
@@ -485,10 +513,18 @@ impl BacklightBackend {
         }
     }
 
-    fn build_ui_update(&self, format: &str, format_icons: &[String]) -> BacklightUiUpdate {
+    fn build_ui_update(
+        &self,
+        format: &str,
+        tooltip_format: Option<&str>,
+        format_icons: &[String],
+    ) -> BacklightUiUpdate {
         if let Some(snapshot) = self.selected.as_ref() {
             return BacklightUiUpdate {
                 text: render_format(format, snapshot, format_icons),
+                tooltip: tooltip_format
+                    .map(|format| render_format(format, snapshot, format_icons))
+                    .unwrap_or_default(),
                 visible: snapshot.device.powered,
                 level_class: brightness_css_class(snapshot.percent),
             };
@@ -501,6 +537,7 @@ impl BacklightBackend {
 
         BacklightUiUpdate {
             text: escape_markup_text(&format!("backlight error: {error}")),
+            tooltip: String::new(),
             visible: true,
             level_class: "brightness-unknown",
         }
@@ -611,37 +648,6 @@ fn set_brightness_via_logind(device_name: &str, brightness: u32) -> Result<(), S
     Err("failed to set brightness via login1 SetBrightness".to_string())
 }
 
-fn wait_for_readable_fd(fd: i32, timeout_millis: u64) -> Result<bool, String> {
-    let mut pollfd = libc::pollfd {
-        fd,
-        events: libc::POLLIN,
-        revents: 0,
-    };
-
-    let timeout_millis = timeout_millis.min(i32::MAX as u64) as i32;
-
-    loop {
-        // SAFETY: we pass a valid pointer to one pollfd entry and a correct count.
-        let rc = unsafe { libc::poll(&mut pollfd, 1, timeout_millis) };
-        if rc > 0 {
-            if (pollfd.revents & libc::POLLIN) != 0 {
-                return Ok(true);
-            }
-            return Err(format!("unexpected poll events: {}", pollfd.revents));
-        }
-
-        if rc == 0 {
-            return Ok(false);
-        }
-
-        let err = std::io::Error::last_os_error();
-        if err.kind() == std::io::ErrorKind::Interrupted {
-            continue;
-        }
-        return Err(format!("poll failed: {err}"));
-    }
-}
-
 fn snapshot_from_device(device: BacklightDevice) -> BacklightSnapshot {
     let percent = if device.max_brightness == 0 {
         100
@@ -0,0 +1,1707 @@
+mod upower;
+
+use std::cell::{Cell, RefCell};
+use std::fs;
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use gtk::glib::{ControlFlow, SourceId};
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+use zbus::blocking::{Connection, Proxy};
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::fd_wait::wait_for_readable_fd;
+use crate::modules::text::{attach_text_subscription, TextBlock, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
+use crate::modules::{
+    escape_markup_text, events, icon_index_for_percentage, icons, render_markup_template,
+    state_store, ModuleBuildContext, ModuleConfig, ModuleLabel,
+};
+
+use super::ModuleFactory;
+
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+const MIN_BATTERY_INTERVAL_SECS: u32 = 1;
+const DEFAULT_BATTERY_INTERVAL_SECS: u32 = 10;
+const DEFAULT_BATTERY_FORMAT: &str = "{capacity}% {icon}";
+const DEFAULT_CRITICAL_ACTION_THRESHOLD: u8 = 5;
+const DEFAULT_CHARGING_ANIMATION_MS: u32 = 500;
+const MIN_CHARGING_ANIMATION_MS: u32 = 100;
+const CRITICAL_ACTION_BLOCKED_CLASS: &str = "critical-action-blocked";
+const BATTERY_LEVEL_CLASSES: [&str; 5] = [
+    "battery-critical",
+    "battery-low",
+    "battery-medium",
+    "battery-high",
+    "battery-unknown",
+];
+const BATTERY_STATUS_CLASSES: [&str; 5] = [
+    "status-charging",
+    "status-discharging",
+    "status-full",
+    "status-not-charging",
+    "status-unknown",
+];
+const BATTERY_STATE_CLASSES: [&str; 2] = ["state-warning", "state-critical"];
+pub(crate) const MODULE_TYPE: &str = "battery";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct BatteryConfig {
+    #[serde(default)]
+    pub(crate) format: Option<String>,
+    #[serde(rename = "format-charging", alias = "format_charging", default)]
+    pub(crate) format_charging: Option<String>,
+    #[serde(rename = "format-discharging", alias = "format_discharging", default)]
+    pub(crate) format_discharging: Option<String>,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default = "default_battery_interval")]
+    pub(crate) interval_secs: u32,
+    #[serde(default)]
+    pub(crate) backend: BatteryBackendKind,
+    #[serde(default)]
+    pub(crate) device: Option<String>,
+    /// Combine every battery into one reading (for example BAT0 + BAT1).
+    #[serde(default)]
+    pub(crate) aggregate: bool,
+    #[serde(rename = "format-icons", default = "default_battery_icons")]
+    pub(crate) format_icons: Vec<String>,
+    #[serde(rename = "critical-action", alias = "critical_action", default)]
+    pub(crate) critical_action: Option<BatteryCriticalActionConfig>,
+    #[serde(
+        rename = "charging-animation-ms",
+        alias = "charging_animation_ms",
+        default = "default_charging_animation_ms"
+    )]
+    pub(crate) charging_animation_ms: u32,
+    #[serde(rename = "warning-threshold", alias = "warning_threshold", default)]
+    pub(crate) warning_threshold: Option<u8>,
+    #[serde(rename = "critical-threshold", alias = "critical_threshold", default)]
+    pub(crate) critical_threshold: Option<u8>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+/// Where readings come from: `sysfs` polls `/sys/class/power_supply` on
+/// udev events, `upower` follows UPower's D-Bus signals.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum BatteryBackendKind {
+    #[default]
+    Sysfs,
+    Upower,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct BatteryCriticalActionConfig {
+    pub(crate) command: String,
+    #[serde(default = "default_critical_action_threshold")]
+    pub(crate) threshold: u8,
+    #[serde(
+        rename = "respect-inhibitors",
+        alias = "respect_inhibitors",
+        default = "default_respect_inhibitors"
+    )]
+    pub(crate) respect_inhibitors: bool,
+}
+
+#[derive(Debug, Clone)]
+struct BatterySnapshot {
+    device_name: String,
+    capacity: u8,
+    status: String,
+    cycles: Option<u32>,
+    health: Option<u32>,
+    energy: Option<BatteryEnergy>,
+    /// Per-device readings behind an aggregate snapshot; empty otherwise.
+    batteries: Vec<BatterySnapshot>,
+}
+
+/// Stored and full energy plus the current (dis)charge rate, in µWh and µW.
+/// `charge_*` batteries are converted with `voltage_now` when it is exposed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BatteryEnergy {
+    now: u64,
+    full: u64,
+    rate: u64,
+}
+
+#[derive(Debug, Clone)]
+struct BatteryUiUpdate {
+    text: String,
+    /// While charging: `text` re-rendered with each icon from the current
+    /// level up to the last `format-icons` entry.
+    charging_frames: Vec<String>,
+    visible: bool,
+    level_class: &'static str,
+    status_class: &'static str,
+    state_class: Option<&'static str>,
+    blocked_by: Vec<String>,
+    /// Per-battery breakdown in aggregate mode.
+    tooltip: Option<String>,
+    /// `tooltip-format` rendered like `text`; empty without one.
+    tooltip_markup: String,
+    published: state_store::PublishedValues,
+}
+
+/// Tracks the critical action latch: it fires once per discharge below the
+/// threshold and re-arms when the battery charges or rises above it.
+#[derive(Debug, Default)]
+struct CriticalActionState {
+    fired: bool,
+    blocked_by: Vec<String>,
+    /// System bus for logind, opened on the first inhibitor check and reused.
+    system_bus: Option<Connection>,
+}
+
+/// `format` plus the optional per-status overrides and `tooltip-format`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BatteryFormats {
+    default: String,
+    charging: Option<String>,
+    discharging: Option<String>,
+    tooltip: Option<String>,
+}
+
+/// Capacity levels at or below which a discharging battery is flagged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+struct BatteryThresholds {
+    warning: Option<u8>,
+    critical: Option<u8>,
+}
+
+impl BatteryFormats {
+    fn for_status(&self, status: &str) -> &str {
+        let specific = if status.eq_ignore_ascii_case("charging") {
+            self.charging.as_deref()
+        } else if status.eq_ignore_ascii_case("discharging") {
+            self.discharging.as_deref()
+        } else {
+            None
+        };
+        specific.unwrap_or(&self.default)
+    }
+}
+
+struct BatteryBackend {
+    kind: BatteryBackendKind,
+    preferred_device: Option<String>,
+    aggregate: bool,
+    snapshot: Option<BatterySnapshot>,
+    last_error: Option<String>,
+}
+
+struct UdevMonitor {
+    monitor: udev::MonitorSocket,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BatterySharedKey {
+    backend: BatteryBackendKind,
+    device: Option<String>,
+    aggregate: bool,
+    formats: BatteryFormats,
+    format_icons: Vec<String>,
+    interval_secs: u32,
+    thresholds: BatteryThresholds,
+    critical_action: Option<BatteryCriticalActionConfig>,
+}
+
+pub(crate) struct BatteryFactory;
+
+pub(crate) const FACTORY: BatteryFactory = BatteryFactory;
+
+impl ModuleFactory for BatteryFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_battery_module(
+            parsed,
+            context.tooltip_format.clone(),
+            context.published_namespace(MODULE_TYPE),
+        )
+        .upcast())
+    }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        let parsed = parse_config(config)?;
+        let subscription = subscribe_shared_battery(shared_key(&parsed, None));
+        attach_text_subscription(subscription, sink, |update| {
+            state_store::publish(MODULE_TYPE, &update.published);
+            update.visible.then(|| {
+                TextBlock::new(update.text).urgent(update.state_class == Some("state-critical"))
+            })
+        });
+        Ok(())
+    }
+}
+
+fn default_battery_interval() -> u32 {
+    DEFAULT_BATTERY_INTERVAL_SECS
+}
+
+fn default_critical_action_threshold() -> u8 {
+    DEFAULT_CRITICAL_ACTION_THRESHOLD
+}
+
+fn default_respect_inhibitors() -> bool {
+    true
+}
+
+fn default_charging_animation_ms() -> u32 {
+    DEFAULT_CHARGING_ANIMATION_MS
+}
+
+fn default_battery_icons() -> Vec<String> {
+    icons::default_icon_list(&["", "", "", "", ""], &["▁", "▂", "▄", "▆", "█"])
+}
+
+pub(crate) fn parse_config(module: &ModuleConfig) -> Result<BatteryConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    let config: BatteryConfig = serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))?;
+    if config.aggregate && config.device.is_some() {
+        return Err(format!(
+            "invalid {MODULE_TYPE} module config: `device` and `aggregate` cannot be combined"
+        ));
+    }
+    Ok(config)
+}
+
+pub(crate) fn normalized_battery_interval(interval_secs: u32) -> u32 {
+    interval_secs.max(MIN_BATTERY_INTERVAL_SECS)
+}
+
+/// `0` disables the charging animation; other values are clamped to a floor.
+fn normalized_charging_animation_ms(millis: u32) -> Option<u32> {
+    (millis != 0).then(|| millis.max(MIN_CHARGING_ANIMATION_MS))
+}
+
+fn shared_key(config: &BatteryConfig, tooltip_format: Option<String>) -> BatterySharedKey {
+    BatterySharedKey {
+        backend: config.backend,
+        device: config.device.clone(),
+        aggregate: config.aggregate,
+        formats: BatteryFormats {
+            default: config
+                .format
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BATTERY_FORMAT.to_string()),
+            charging: config.format_charging.clone(),
+            discharging: config.format_discharging.clone(),
+            tooltip: tooltip_format,
+        },
+        format_icons: config.format_icons.clone(),
+        interval_secs: normalized_battery_interval(config.interval_secs),
+        thresholds: BatteryThresholds {
+            warning: config.warning_threshold,
+            critical: config.critical_threshold,
+        },
+        critical_action: config.critical_action.clone(),
+    }
+}
+
+fn battery_registry() -> &'static BackendRegistry<BatterySharedKey, Broadcaster<BatteryUiUpdate>> {
+    static REGISTRY: OnceLock<BackendRegistry<BatterySharedKey, Broadcaster<BatteryUiUpdate>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_battery(key: BatterySharedKey) -> Subscription<BatteryUiUpdate> {
+    let (broadcaster, start_worker) =
+        battery_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_battery_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_battery_worker(key: BatterySharedKey, broadcaster: Arc<Broadcaster<BatteryUiUpdate>>) {
+    std::thread::spawn(move || {
+        run_battery_backend_loop(&key, &broadcaster);
+    });
+}
+
+pub(crate) fn build_battery_module(
+    config: BatteryConfig,
+    tooltip_format: Option<String>,
+    namespace: String,
+) -> Label {
+    let key = shared_key(&config, tooltip_format);
+    let label = ModuleLabel::new("battery")
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(config.click.or(config.on_click))
+        .into_label();
+
+    if key.interval_secs != config.interval_secs {
+        eprintln!(
+            "battery interval_secs={} is too low; clamping to {} second",
+            config.interval_secs, key.interval_secs
+        );
+    }
+
+    let tooltip = key
+        .formats
+        .tooltip
+        .is_some()
+        .then(|| ModuleTooltip::attach(&label));
+    let subscription = subscribe_shared_battery(key);
+
+    let animation = normalized_charging_animation_ms(config.charging_animation_ms)
+        .map(|millis| Rc::new(ChargingAnimation::new(millis)));
+    attach_subscription(&label, subscription, move |label, update| {
+        state_store::publish(&namespace, &update.published);
+        apply_battery_ui_update(label, &update, animation.as_ref(), tooltip.as_ref());
+    });
+
+    label
+}
+
+/// Steps the label through pre-rendered charging frames on a timer that only
+/// runs while there is more than one frame.
+struct ChargingAnimation {
+    interval: Duration,
+    frames: RefCell<Vec<String>>,
+    index: Cell<usize>,
+    source: RefCell<Option<SourceId>>,
+}
+
+impl ChargingAnimation {
+    fn new(interval_millis: u32) -> Self {
+        Self {
+            interval: Duration::from_millis(u64::from(interval_millis)),
+            frames: RefCell::new(Vec::new()),
+            index: Cell::new(0),
+            source: RefCell::new(None),
+        }
+    }
+
+    fn set_frames(self: &Rc<Self>, label: &Label, frames: Vec<String>) {
+        if frames.len() <= 1 {
+            self.frames.borrow_mut().clear();
+            if let Some(source) = self.source.borrow_mut().take() {
+                source.remove();
+            }
+            return;
+        }
+
+        let restart = *self.frames.borrow() != frames;
+        *self.frames.borrow_mut() = frames;
+        if restart {
+            self.index.set(0);
+        }
+        self.show_current(label);
+        if self.source.borrow().is_some() {
+            return;
+        }
+
+        let animation = Rc::clone(self);
+        let label_weak = label.downgrade();
+        let source = gtk::glib::timeout_add_local(self.interval, move || {
+            let Some(label) = label_weak.upgrade() else {
+                animation.source.borrow_mut().take();
+                return ControlFlow::Break;
+            };
+            let len = animation.frames.borrow().len().max(1);
+            animation.index.set((animation.index.get() + 1) % len);
+            animation.show_current(&label);
+            ControlFlow::Continue
+        });
+        *self.source.borrow_mut() = Some(source);
+    }
+
+    fn show_current(&self, label: &Label) {
+        if let Some(frame) = self.frames.borrow().get(self.index.get()) {
+            label.set_markup(frame);
+        }
+    }
+}
+
+fn apply_battery_ui_update(
+    label: &Label,
+    update: &BatteryUiUpdate,
+    animation: Option<&Rc<ChargingAnimation>>,
+    tooltip: Option<&ModuleTooltip>,
+) {
+    let visible = update.visible && !update.text.trim().is_empty();
+    label.set_visible(visible);
+    if visible {
+        label.set_markup(&update.text);
+    }
+    if let Some(animation) = animation {
+        let frames = if visible {
+            update.charging_frames.clone()
+        } else {
+            Vec::new()
+        };
+        animation.set_frames(label, frames);
+    }
+
+    for class_name in BATTERY_LEVEL_CLASSES {
+        label.remove_css_class(class_name);
+    }
+    for class_name in BATTERY_STATUS_CLASSES {
+        label.remove_css_class(class_name);
+    }
+    for class_name in BATTERY_STATE_CLASSES {
+        label.remove_css_class(class_name);
+    }
+    label.add_css_class(update.level_class);
+    label.add_css_class(update.status_class);
+    if let Some(state_class) = update.state_class {
+        label.add_css_class(state_class);
+    }
+
+    if update.blocked_by.is_empty() {
+        label.remove_css_class(CRITICAL_ACTION_BLOCKED_CLASS);
+    } else {
+        label.add_css_class(CRITICAL_ACTION_BLOCKED_CLASS);
+    }
+    // `tooltip-format` replaces the built-in tooltip, keeping the inhibitor
+    // list below it.
+    match tooltip {
+        Some(tooltip) if update.blocked_by.is_empty() => tooltip.set_markup(&update.tooltip_markup),
+        Some(tooltip) => tooltip.set_markup(&format!(
+            "{}\n{}",
+            update.tooltip_markup,
+            escape_markup_text(&blocked_tooltip_text(&update.blocked_by))
+        )),
+        None if update.blocked_by.is_empty() => label.set_tooltip_text(update.tooltip.as_deref()),
+        None => label.set_tooltip_text(Some(&blocked_tooltip_text(&update.blocked_by))),
+    }
+}
+
+fn run_battery_backend_loop(
+    key: &BatterySharedKey,
+    broadcaster: &Arc<Broadcaster<BatteryUiUpdate>>,
+) {
+    let mut backend = BatteryBackend::new(key.backend, key.device.clone(), key.aggregate);
+    let mut critical = CriticalActionState::default();
+    let mut was_critical = false;
+    let mut refresh = move || {
+        backend.refresh();
+        critical.evaluate(key.critical_action.as_ref(), backend.snapshot.as_ref());
+        let is_critical = backend
+            .snapshot
+            .as_ref()
+            .is_some_and(|snapshot| is_discharging_critical(snapshot, key.thresholds));
+        if is_critical && !was_critical {
+            if let Some(snapshot) = backend.snapshot.as_ref() {
+                events::emit(events::BATTERY_CRITICAL, snapshot.capacity.to_string());
+            }
+        }
+        was_critical = is_critical;
+        broadcaster.broadcast(backend.build_ui_update(
+            &key.formats,
+            &key.format_icons,
+            key.thresholds,
+            &critical.blocked_by,
+        ));
+    };
+
+    refresh();
+
+    let resync_interval = Duration::from_secs(u64::from(key.interval_secs));
+    match key.backend {
+        BatteryBackendKind::Sysfs => {
+            wait_for_udev_changes(key, broadcaster, resync_interval, &mut refresh)
+        }
+        BatteryBackendKind::Upower => {
+            wait_for_upower_changes(key, broadcaster, resync_interval, &mut refresh)
+        }
+    }
+}
+
+fn wait_for_udev_changes(
+    key: &BatterySharedKey,
+    broadcaster: &Arc<Broadcaster<BatteryUiUpdate>>,
+    resync_interval: Duration,
+    refresh: &mut impl FnMut(),
+) {
+    let mut last_resync = Instant::now();
+    let mut udev_monitor = match UdevMonitor::new() {
+        Ok(monitor) => Some(monitor),
+        Err(err) => {
+            eprintln!("battery udev listener unavailable, using polling only: {err}");
+            None
+        }
+    };
+
+    loop {
+        if broadcaster.subscriber_count() == 0 {
+            battery_registry().remove(key, broadcaster);
+            return;
+        }
+
+        let wake_timeout = millis_until_next_resync(last_resync, resync_interval).min(50);
+
+        if let Some(monitor) = udev_monitor.as_mut() {
+            match wait_for_readable_fd(monitor.fd(), wake_timeout) {
+                Ok(true) => {
+                    if monitor.drain_events() {
+                        refresh();
+                    }
+                }
+                Ok(false) => {}
+                Err(err) => {
+                    eprintln!("battery udev wait failed, listener stopped: {err}");
+                    udev_monitor = None;
+                }
+            }
+        } else {
+            std::thread::sleep(Duration::from_millis(wake_timeout.max(1)));
+        }
+
+        if last_resync.elapsed() >= resync_interval {
+            refresh();
+            last_resync = Instant::now();
+        }
+    }
+}
+
+/// UPower already debounces hardware updates, so every signal refreshes;
+/// `interval_secs` only covers a restarted daemon.
+fn wait_for_upower_changes(
+    key: &BatterySharedKey,
+    broadcaster: &Arc<Broadcaster<BatteryUiUpdate>>,
+    resync_interval: Duration,
+    refresh: &mut impl FnMut(),
+) {
+    let (trigger_tx, trigger_rx) = mpsc::channel::<()>();
+    upower::start_signal_listener(upower::device_path(key.device.as_deref()), trigger_tx);
+
+    loop {
+        match trigger_rx.recv_timeout(resync_interval) {
+            Ok(()) => while trigger_rx.try_recv().is_ok() {},
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        if broadcaster.subscriber_count() == 0 {
+            battery_registry().remove(key, broadcaster);
+            return;
+        }
+        refresh();
+    }
+}
+
+fn millis_until_next_resync(last_resync: Instant, interval: Duration) -> u64 {
+    let elapsed = last_resync.elapsed();
+    if elapsed >= interval {
+        return 0;
+    }
+
+    interval
+        .saturating_sub(elapsed)
+        .as_millis()
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+impl BatteryBackend {
+    fn new(kind: BatteryBackendKind, preferred_device: Option<String>, aggregate: bool) -> Self {
+        Self {
+            kind,
+            preferred_device,
+            aggregate,
+            snapshot: None,
+            last_error: None,
+        }
+    }
+
+    fn refresh(&mut self) {
+        let snapshot = match self.kind {
+            BatteryBackendKind::Sysfs => {
+                let root = Path::new(POWER_SUPPLY_PATH);
+                if self.aggregate {
+                    read_aggregate_snapshot(root)
+                } else {
+                    read_battery_snapshot(root, self.preferred_device.as_deref())
+                }
+            }
+            // Without a device UPower's DisplayDevice already combines
+            // every battery, so `aggregate` needs no extra work here.
+            BatteryBackendKind::Upower => {
+                upower::read_snapshot(&upower::device_path(self.preferred_device.as_deref()))
+            }
+        };
+        match snapshot {
+            Ok(snapshot) => {
+                self.snapshot = snapshot;
+                self.last_error = None;
+            }
+            Err(err) => {
+                self.snapshot = None;
+                self.last_error = Some(err);
+            }
+        }
+    }
+
+    fn build_ui_update(
+        &self,
+        formats: &BatteryFormats,
+        format_icons: &[String],
+        thresholds: BatteryThresholds,
+        blocked_by: &[String],
+    ) -> BatteryUiUpdate {
+        if let Some(snapshot) = self.snapshot.as_ref() {
+            let mut values = vec![
+                ("capacity", snapshot.capacity.to_string()),
+                ("percent", snapshot.capacity.to_string()),
+                ("status", snapshot.status.clone()),
+            ];
+            if let Some(cycles) = snapshot.cycles {
+                values.push(("cycles", cycles.to_string()));
+            }
+            if let Some(health) = snapshot.health {
+                values.push(("health", health.to_string()));
+            }
+            for (name, time) in [
+                ("time", snapshot_time_remaining(snapshot)),
+                ("time_to_empty", snapshot_time_to_empty(snapshot)),
+                ("time_to_full", snapshot_time_to_full(snapshot)),
+            ] {
+                if let Some(time) = time {
+                    values.push((name, format_time_remaining(time)));
+                }
+            }
+            let format = formats.for_status(&snapshot.status);
+            let text = render_format(format, snapshot, format_icons);
+            return BatteryUiUpdate {
+                visible: !text.trim().is_empty(),
+                charging_frames: charging_frames(format, snapshot, format_icons),
+                text,
+                level_class: battery_level_css_class(snapshot.capacity),
+                status_class: battery_status_css_class(&snapshot.status),
+                state_class: battery_state_css_class(snapshot, thresholds),
+                blocked_by: blocked_by.to_vec(),
+                tooltip: breakdown_tooltip_text(&snapshot.batteries),
+                tooltip_markup: formats
+                    .tooltip
+                    .as_deref()
+                    .map(|format| render_format(format, snapshot, format_icons))
+                    .unwrap_or_default(),
+                published: values,
+            };
+        }
+
+        if let Some(err) = self.last_error.as_deref() {
+            return BatteryUiUpdate {
+                text: escape_markup_text(&format!("battery error: {err}")),
+                charging_frames: Vec::new(),
+                visible: true,
+                level_class: "battery-unknown",
+                status_class: "status-unknown",
+                state_class: None,
+                blocked_by: Vec::new(),
+                tooltip: None,
+                tooltip_markup: String::new(),
+                published: Vec::new(),
+            };
+        }
+
+        BatteryUiUpdate {
+            text: String::new(),
+            charging_frames: Vec::new(),
+            visible: false,
+            level_class: "battery-unknown",
+            status_class: "status-unknown",
+            state_class: None,
+            blocked_by: Vec::new(),
+            tooltip: None,
+            tooltip_markup: String::new(),
+            published: Vec::new(),
+        }
+    }
+}
+
+impl CriticalActionState {
+    fn evaluate(
+        &mut self,
+        action: Option<&BatteryCriticalActionConfig>,
+        snapshot: Option<&BatterySnapshot>,
+    ) {
+        let Some(action) = action else {
+            return;
+        };
+
+        if !snapshot.is_some_and(|snapshot| critical_action_due(snapshot, action.threshold)) {
+            self.fired = false;
+            self.blocked_by.clear();
+            return;
+        }
+        if self.fired {
+            return;
+        }
+
+        if action.respect_inhibitors {
+            self.blocked_by = self.query_blocking_inhibitors().unwrap_or_else(|err| {
+                // Reconnect on the next check in case the bus went away.
+                self.system_bus = None;
+                eprintln!("battery critical action: failed to list logind inhibitors: {err}");
+                Vec::new()
+            });
+            if !self.blocked_by.is_empty() {
+                return;
+            }
+        }
+
+        self.fired = true;
+        let _ = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(&action.command)
+            .spawn();
+    }
+
+    fn query_blocking_inhibitors(&mut self) -> Result<Vec<String>, String> {
+        let connection = match &mut self.system_bus {
+            Some(connection) => connection,
+            slot => slot.insert(
+                Connection::system()
+                    .map_err(|err| format!("failed to connect to system dbus: {err}"))?,
+            ),
+        };
+        query_blocking_inhibitors(connection)
+    }
+}
+
+fn critical_action_due(snapshot: &BatterySnapshot, threshold: u8) -> bool {
+    snapshot.status.eq_ignore_ascii_case("discharging") && snapshot.capacity <= threshold
+}
+
+type LogindInhibitor = (String, String, String, String, u32, u32);
+
+fn query_blocking_inhibitors(connection: &Connection) -> Result<Vec<String>, String> {
+    let proxy = Proxy::new(
+        connection,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1",
+        "org.freedesktop.login1.Manager",
+    )
+    .map_err(|err| format!("failed to create login1 proxy: {err}"))?;
+
+    let inhibitors: Vec<LogindInhibitor> = proxy
+        .call("ListInhibitors", &())
+        .map_err(|err| format!("ListInhibitors failed: {err}"))?;
+    Ok(blocking_inhibitor_labels(&inhibitors))
+}
+
+/// Returns `who: why` labels for `block`-mode inhibitors on sleep or shutdown.
+fn blocking_inhibitor_labels(inhibitors: &[LogindInhibitor]) -> Vec<String> {
+    inhibitors
+        .iter()
+        .filter(|(what, _, _, mode, _, _)| {
+            mode == "block"
+                && what
+                    .split(':')
+                    .any(|kind| kind == "sleep" || kind == "shutdown")
+        })
+        .map(|(_, who, why, _, _, _)| {
+            if why.is_empty() {
+                who.clone()
+            } else {
+                format!("{who}: {why}")
+            }
+        })
+        .collect()
+}
+
+fn blocked_tooltip_text(blocked_by: &[String]) -> String {
+    let mut text = "Critical battery action held back by:".to_string();
+    for inhibitor in blocked_by {
+        text.push_str("\n- ");
+        text.push_str(inhibitor);
+    }
+    text
+}
+
+impl UdevMonitor {
+    fn new() -> Result<Self, String> {
+        let builder = udev::MonitorBuilder::new().map_err(|err| err.to_string())?;
+        let builder = builder
+            .match_subsystem("power_supply")
+            .map_err(|err| err.to_string())?;
+        let monitor = builder.listen().map_err(|err| err.to_string())?;
+
+        Ok(Self { monitor })
+    }
+
+    fn fd(&self) -> i32 {
+        self.monitor.as_raw_fd()
+    }
+
+    fn drain_events(&mut self) -> bool {
+        let mut had_event = false;
+        for _ in self.monitor.iter() {
+            had_event = true;
+        }
+        had_event
+    }
+}
+
+fn read_battery_snapshot(
+    power_supply_root: &Path,
+    preferred_device: Option<&str>,
+) -> Result<Option<BatterySnapshot>, String> {
+    let Some(device_path) = select_battery_device(power_supply_root, preferred_device)? else {
+        return Ok(None);
+    };
+    read_device_snapshot(&device_path).map(Some)
+}
+
+/// Sums every battery into one snapshot; a single battery is returned as is.
+fn read_aggregate_snapshot(power_supply_root: &Path) -> Result<Option<BatterySnapshot>, String> {
+    let batteries = battery_devices(power_supply_root)?
+        .iter()
+        .map(|path| read_device_snapshot(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(aggregate_snapshots(batteries))
+}
+
+fn read_device_snapshot(device_path: &Path) -> Result<BatterySnapshot, String> {
+    let device_name = device_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("invalid battery device name: {}", device_path.display()))?
+        .to_string();
+    let capacity = read_percentage_file(&device_path.join("capacity"))?;
+    let status = read_trimmed_or_default(&device_path.join("status"), "Unknown");
+    let cycles = read_optional_u64(&device_path.join("cycle_count"))
+        .and_then(|cycles| u32::try_from(cycles).ok());
+    let health = read_battery_health(device_path);
+    let energy = read_battery_energy(device_path);
+
+    Ok(BatterySnapshot {
+        device_name,
+        capacity,
+        status,
+        cycles,
+        health,
+        energy,
+        batteries: Vec::new(),
+    })
+}
+
+fn read_battery_energy(device_path: &Path) -> Option<BatteryEnergy> {
+    let read = |name: &str| read_optional_u64(&device_path.join(name));
+    if let (Some(now), Some(full)) = (read("energy_now"), read("energy_full")) {
+        return Some(BatteryEnergy {
+            now,
+            full,
+            rate: read("power_now").unwrap_or(0),
+        });
+    }
+
+    let now = read("charge_now")?;
+    let full = read("charge_full")?;
+    let rate = read("current_now").unwrap_or(0);
+    // µAh * µV / 10^6 = µWh. Without a voltage the charge values still give
+    // a correct time and percentage for this battery alone.
+    let voltage = read("voltage_now").filter(|voltage| *voltage > 0);
+    let to_energy = |value: u64| match voltage {
+        Some(voltage) => {
+            u64::try_from(u128::from(value) * u128::from(voltage) / 1_000_000).unwrap_or(u64::MAX)
+        }
+        None => value,
+    };
+    Some(BatteryEnergy {
+        now: to_energy(now),
+        full: to_energy(full),
+        rate: to_energy(rate),
+    })
+}
+
+/// Combines per-device snapshots. Capacity is weighted by stored energy when
+/// every battery reports it; `{cycles}` is the highest and `{health}` the
+/// average of the batteries that report them.
+fn aggregate_snapshots(mut batteries: Vec<BatterySnapshot>) -> Option<BatterySnapshot> {
+    if batteries.len() <= 1 {
+        return batteries.pop();
+    }
+
+    let energy = batteries
+        .iter()
+        .map(|battery| battery.energy)
+        .collect::<Option<Vec<_>>>()
+        .map(|energies| BatteryEnergy {
+            now: energies.iter().map(|energy| energy.now).sum(),
+            full: energies.iter().map(|energy| energy.full).sum(),
+            rate: energies.iter().map(|energy| energy.rate).sum(),
+        });
+    let capacity = match energy {
+        Some(energy) if energy.full > 0 => {
+            ((energy.now.saturating_mul(100) + energy.full / 2) / energy.full).min(100) as u8
+        }
+        _ => {
+            let total: u32 = batteries
+                .iter()
+                .map(|battery| u32::from(battery.capacity))
+                .sum();
+            (total / batteries.len() as u32) as u8
+        }
+    };
+    let healths = batteries
+        .iter()
+        .filter_map(|battery| battery.health)
+        .collect::<Vec<_>>();
+
+    Some(BatterySnapshot {
+        device_name: batteries
+            .iter()
+            .map(|battery| battery.device_name.as_str())
+            .collect::<Vec<_>>()
+            .join("+"),
+        capacity,
+        status: aggregate_status(&batteries),
+        cycles: batteries.iter().filter_map(|battery| battery.cycles).max(),
+        health: (!healths.is_empty()).then(|| healths.iter().sum::<u32>() / healths.len() as u32),
+        energy,
+        batteries,
+    })
+}
+
+/// Laptops drain or charge one battery at a time and report the other as
+/// idle, so any active battery decides the combined status.
+fn aggregate_status(batteries: &[BatterySnapshot]) -> String {
+    ["Charging", "Discharging"]
+        .iter()
+        .find(|status| {
+            batteries
+                .iter()
+                .any(|battery| battery.status.eq_ignore_ascii_case(status))
+        })
+        .map(|status| status.to_string())
+        .or_else(|| {
+            batteries
+                .iter()
+                .all(|battery| battery.status.eq_ignore_ascii_case("full"))
+                .then(|| "Full".to_string())
+        })
+        .unwrap_or_else(|| batteries[0].status.clone())
+}
+
+/// Time until empty while discharging or until full while charging.
+fn snapshot_time_remaining(snapshot: &BatterySnapshot) -> Option<Duration> {
+    snapshot_time_to_empty(snapshot).or_else(|| snapshot_time_to_full(snapshot))
+}
+
+fn snapshot_time_to_empty(snapshot: &BatterySnapshot) -> Option<Duration> {
+    if !snapshot.status.eq_ignore_ascii_case("discharging") {
+        return None;
+    }
+    let energy = snapshot.energy?;
+    time_at_rate(energy.now, energy.rate)
+}
+
+fn snapshot_time_to_full(snapshot: &BatterySnapshot) -> Option<Duration> {
+    if !snapshot.status.eq_ignore_ascii_case("charging") {
+        return None;
+    }
+    let energy = snapshot.energy?;
+    time_at_rate(energy.full.saturating_sub(energy.now), energy.rate)
+}
+
+/// `energy` in µWh moved at `rate` µW; `None` without a reported rate.
+fn time_at_rate(energy: u64, rate: u64) -> Option<Duration> {
+    (rate != 0).then(|| Duration::from_secs(energy.saturating_mul(3600) / rate))
+}
+
+fn format_time_remaining(time: Duration) -> String {
+    let minutes = time.as_secs() / 60;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+fn breakdown_tooltip_text(batteries: &[BatterySnapshot]) -> Option<String> {
+    if batteries.is_empty() {
+        return None;
+    }
+    let lines = batteries
+        .iter()
+        .map(|battery| {
+            format!(
+                "{}: {}% {}",
+                battery.device_name, battery.capacity, battery.status
+            )
+        })
+        .collect::<Vec<_>>();
+    Some(lines.join("\n"))
+}
+
+/// Remaining capacity relative to design capacity, in percent. Batteries
+/// report either `charge_*` (µAh) or `energy_*` (µWh) pairs.
+fn read_battery_health(device_path: &Path) -> Option<u32> {
+    ["charge", "energy"].iter().find_map(|prefix| {
+        let full = read_optional_u64(&device_path.join(format!("{prefix}_full")))?;
+        let design = read_optional_u64(&device_path.join(format!("{prefix}_full_design")))?;
+        health_percent(full, design)
+    })
+}
+
+fn health_percent(full: u64, design: u64) -> Option<u32> {
+    if design == 0 {
+        return None;
+    }
+    u32::try_from((full.saturating_mul(100) + design / 2) / design).ok()
+}
+
+fn read_optional_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn select_battery_device(
+    power_supply_root: &Path,
+    preferred_device: Option<&str>,
+) -> Result<Option<PathBuf>, String> {
+    if let Some(device) = preferred_device {
+        let preferred_path = power_supply_root.join(device);
+        if !preferred_path.exists() {
+            return Err(format!(
+                "preferred battery device '{}' not found in {}",
+                device,
+                power_supply_root.display()
+            ));
+        }
+        if !is_battery_device(&preferred_path) {
+            return Err(format!(
+                "preferred device '{}' is not a battery device",
+                preferred_path.display()
+            ));
+        }
+        return Ok(Some(preferred_path));
+    }
+
+    Ok(battery_devices(power_supply_root)?.into_iter().next())
+}
+
+/// Battery devices under `power_supply_root`, sorted by name.
+fn battery_devices(power_supply_root: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(power_supply_root)
+        .map_err(|err| format!("failed to read {}: {err}", power_supply_root.display()))?;
+    let mut candidates = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|err| format!("failed to read power-supply entry: {err}"))?;
+        let path = entry.path();
+        if is_battery_device(&path) {
+            candidates.push(path);
+        }
+    }
+
+    candidates.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+    Ok(candidates)
+}
+
+fn is_battery_device(path: &Path) -> bool {
+    if !path.is_dir() || !path.join("capacity").is_file() {
+        return false;
+    }
+
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    if name.starts_with("BAT") {
+        return true;
+    }
+
+    let type_path = path.join("type");
+    if let Ok(device_type) = fs::read_to_string(type_path) {
+        return device_type.trim().eq_ignore_ascii_case("battery");
+    }
+
+    false
+}
+
+fn read_percentage_file(path: &Path) -> Result<u8, String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+    let parsed = raw
+        .trim()
+        .parse::<u16>()
+        .map_err(|err| format!("failed to parse '{}' as percentage: {err}", raw.trim()))?;
+    Ok(parsed.min(100) as u8)
+}
+
+fn read_trimmed_or_default(path: &Path, default: &str) -> String {
+    fs::read_to_string(path)
+        .map(|value| {
+            let trimmed = value.trim();
+            if trimmed.is_empty() {
+                default.to_string()
+            } else {
+                trimmed.to_string()
+            }
+        })
+        .unwrap_or_else(|_| default.to_string())
+}
+
+fn render_format(format: &str, snapshot: &BatterySnapshot, format_icons: &[String]) -> String {
+    let icon = super::icon_for_percentage(format_icons, snapshot.capacity);
+    render_format_with_icon(format, snapshot, icon)
+}
+
+fn render_format_with_icon(format: &str, snapshot: &BatterySnapshot, icon: &str) -> String {
+    let cycles = snapshot
+        .cycles
+        .map(|cycles| cycles.to_string())
+        .unwrap_or_default();
+    let health = snapshot
+        .health
+        .map(|health| health.to_string())
+        .unwrap_or_default();
+    let time = |duration: Option<Duration>| duration.map(format_time_remaining).unwrap_or_default();
+    render_markup_template(
+        format,
+        &[
+            ("{capacity}", &snapshot.capacity.to_string()),
+            ("{percent}", &snapshot.capacity.to_string()),
+            ("{status}", &snapshot.status),
+            ("{icon}", icon),
+            ("{device}", &snapshot.device_name),
+            ("{cycles}", &cycles),
+            ("{health}", &health),
+            ("{time}", &time(snapshot_time_remaining(snapshot))),
+            ("{time_to_empty}", &time(snapshot_time_to_empty(snapshot))),
+            ("{time_to_full}", &time(snapshot_time_to_full(snapshot))),
+        ],
+    )
+}
+
+/// Frames for the charging animation; empty unless charging and `format`
+/// actually shows a changing `{icon}`.
+fn charging_frames(
+    format: &str,
+    snapshot: &BatterySnapshot,
+    format_icons: &[String],
+) -> Vec<String> {
+    if !snapshot.status.eq_ignore_ascii_case("charging")
+        || !format.contains("{icon}")
+        || format_icons.len() <= 1
+    {
+        return Vec::new();
+    }
+    let start = icon_index_for_percentage(format_icons.len(), snapshot.capacity);
+    format_icons[start..]
+        .iter()
+        .map(|icon| render_format_with_icon(format, snapshot, icon))
+        .collect()
+}
+
+/// In `.state-critical`, or below the `.battery-critical` level and not
+/// charging when no `critical-threshold` is set; crossing into this emits
+/// the `battery-critical` event.
+fn is_discharging_critical(snapshot: &BatterySnapshot, thresholds: BatteryThresholds) -> bool {
+    if thresholds.critical.is_some() {
+        return battery_state_css_class(snapshot, thresholds) == Some("state-critical");
+    }
+    battery_level_css_class(snapshot.capacity) == "battery-critical"
+        && !snapshot.status.eq_ignore_ascii_case("charging")
+}
+
+fn battery_level_css_class(capacity: u8) -> &'static str {
+    if capacity < 15 {
+        "battery-critical"
+    } else if capacity < 35 {
+        "battery-low"
+    } else if capacity < 70 {
+        "battery-medium"
+    } else {
+        "battery-high"
+    }
+}
+
+/// `state-critical` or `state-warning` while discharging at or below the
+/// configured thresholds.
+fn battery_state_css_class(
+    snapshot: &BatterySnapshot,
+    thresholds: BatteryThresholds,
+) -> Option<&'static str> {
+    if !snapshot.status.eq_ignore_ascii_case("discharging") {
+        return None;
+    }
+    let reached = |threshold: Option<u8>| threshold.is_some_and(|level| snapshot.capacity <= level);
+    if reached(thresholds.critical) {
+        Some("state-critical")
+    } else if reached(thresholds.warning) {
+        Some("state-warning")
+    } else {
+        None
+    }
+}
+
+fn battery_status_css_class(status: &str) -> &'static str {
+    if status.eq_ignore_ascii_case("charging") {
+        "status-charging"
+    } else if status.eq_ignore_ascii_case("discharging") {
+        "status-discharging"
+    } else if status.eq_ignore_ascii_case("full") {
+        "status-full"
+    } else if status.eq_ignore_ascii_case("not charging") {
+        "status-not-charging"
+    } else {
+        "status-unknown"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use serde_json::Map;
+
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time should move forward")
+            .as_nanos();
+        env::temp_dir().join(format!("vibar-battery-test-{name}-{nanos}"))
+    }
+
+    fn write(path: &Path, value: &str) {
+        fs::write(path, value).expect("test file should write");
+    }
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'battery'"));
+    }
+
+    #[test]
+    fn normalized_battery_interval_enforces_lower_bound() {
+        assert_eq!(normalized_battery_interval(0), 1);
+        assert_eq!(normalized_battery_interval(1), 1);
+        assert_eq!(normalized_battery_interval(15), 15);
+    }
+
+    #[test]
+    fn select_battery_device_prefers_explicit_device() {
+        let root = test_dir("preferred");
+        let bat0 = root.join("BAT0");
+        fs::create_dir_all(&bat0).expect("battery dir should create");
+        write(&bat0.join("capacity"), "55");
+        write(&bat0.join("type"), "Battery");
+
+        let selected =
+            select_battery_device(&root, Some("BAT0")).expect("device selection should succeed");
+        assert_eq!(selected, Some(bat0));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn select_battery_device_auto_picks_sorted_candidate() {
+        let root = test_dir("auto");
+        let ac = root.join("AC");
+        let bat1 = root.join("BAT1");
+        let bat0 = root.join("BAT0");
+        fs::create_dir_all(&ac).expect("ac dir should create");
+        fs::create_dir_all(&bat1).expect("bat1 dir should create");
+        fs::create_dir_all(&bat0).expect("bat0 dir should create");
+        write(&ac.join("type"), "Mains");
+        write(&bat1.join("capacity"), "44");
+        write(&bat1.join("type"), "Battery");
+        write(&bat0.join("capacity"), "70");
+        write(&bat0.join("type"), "Battery");
+
+        let selected = select_battery_device(&root, None).expect("device selection should succeed");
+        assert_eq!(selected, Some(bat0));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn read_battery_snapshot_returns_none_when_not_found() {
+        let root = test_dir("none");
+        fs::create_dir_all(&root).expect("root dir should create");
+
+        let snapshot = read_battery_snapshot(&root, None).expect("read should succeed");
+        assert!(snapshot.is_none());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn read_aggregate_snapshot_sums_energy_across_batteries() {
+        let root = test_dir("aggregate");
+        let bat0 = root.join("BAT0");
+        let bat1 = root.join("BAT1");
+        fs::create_dir_all(&bat0).expect("bat0 dir should create");
+        fs::create_dir_all(&bat1).expect("bat1 dir should create");
+        for (path, capacity, status, now, full, power) in [
+            (
+                &bat0,
+                "20",
+                "Discharging",
+                "4000000",
+                "20000000",
+                "10000000",
+            ),
+            (&bat1, "100", "Unknown", "20000000", "20000000", "0"),
+        ] {
+            write(&path.join("capacity"), capacity);
+            write(&path.join("status"), status);
+            write(&path.join("energy_now"), now);
+            write(&path.join("energy_full"), full);
+            write(&path.join("power_now"), power);
+        }
+
+        let snapshot = read_aggregate_snapshot(&root)
+            .expect("read should succeed")
+            .expect("batteries should be found");
+        assert_eq!(snapshot.device_name, "BAT0+BAT1");
+        assert_eq!(snapshot.capacity, 60);
+        assert_eq!(snapshot.status, "Discharging");
+        assert_eq!(
+            render_format("{capacity}% {time}", &snapshot, &[]),
+            "60% 2:24"
+        );
+        assert_eq!(
+            breakdown_tooltip_text(&snapshot.batteries).as_deref(),
+            Some("BAT0: 20% Discharging\nBAT1: 100% Unknown")
+        );
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn parse_config_rejects_device_with_aggregate() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(serde_json::json!({ "device": "BAT0", "aggregate": true }))
+                .expect("battery config map should parse"),
+        );
+        let err = parse_config(&module).expect_err("device with aggregate should fail");
+        assert!(err.contains("cannot be combined"));
+    }
+
+    #[test]
+    fn render_format_replaces_placeholders() {
+        let snapshot = BatterySnapshot {
+            device_name: "BAT0".to_string(),
+            capacity: 42,
+            status: "Discharging".to_string(),
+            cycles: Some(312),
+            health: Some(87),
+            energy: None,
+            batteries: Vec::new(),
+        };
+        let icons = vec!["low".to_string(), "high".to_string()];
+        let rendered = render_format(
+            "{capacity} {percent} {status} {icon} {device} {cycles} {health}",
+            &snapshot,
+            &icons,
+        );
+        assert_eq!(rendered, "42 42 Discharging low BAT0 312 87");
+
+        let unknown = BatterySnapshot {
+            cycles: None,
+            health: None,
+            ..snapshot
+        };
+        assert_eq!(
+            render_format("[{cycles}][{health}]", &unknown, &icons),
+            "[][]"
+        );
+    }
+
+    #[test]
+    fn charging_frames_step_from_current_icon_to_last() {
+        let icons = vec![
+            "0".to_string(),
+            "1".to_string(),
+            "2".to_string(),
+            "3".to_string(),
+        ];
+        let snapshot = BatterySnapshot {
+            device_name: "BAT0".to_string(),
+            capacity: 40,
+            status: "Charging".to_string(),
+            cycles: None,
+            health: None,
+            energy: None,
+            batteries: Vec::new(),
+        };
+        assert_eq!(
+            charging_frames("{icon} {capacity}", &snapshot, &icons),
+            vec!["1 40", "2 40", "3 40"]
+        );
+        assert!(charging_frames("{capacity}%", &snapshot, &icons).is_empty());
+
+        let discharging = BatterySnapshot {
+            status: "Discharging".to_string(),
+            ..snapshot
+        };
+        assert!(charging_frames("{icon}", &discharging, &icons).is_empty());
+    }
+
+    #[test]
+    fn read_battery_health_prefers_charge_then_energy() {
+        let root = test_dir("health");
+        fs::create_dir_all(&root).expect("battery dir should create");
+        assert_eq!(read_battery_health(&root), None);
+
+        write(&root.join("energy_full"), "45000000");
+        write(&root.join("energy_full_design"), "50000000");
+        assert_eq!(read_battery_health(&root), Some(90));
+
+        write(&root.join("charge_full"), "3000000\n");
+        write(&root.join("charge_full_design"), "4000000\n");
+        assert_eq!(read_battery_health(&root), Some(75));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn health_percent_rounds_and_rejects_zero_design() {
+        assert_eq!(health_percent(2, 3), Some(67));
+        assert_eq!(health_percent(5, 0), None);
+        assert_eq!(health_percent(110, 100), Some(110));
+    }
+
+    #[test]
+    fn normalized_charging_animation_ms_disables_at_zero() {
+        assert_eq!(normalized_charging_animation_ms(0), None);
+        assert_eq!(normalized_charging_animation_ms(10), Some(100));
+        assert_eq!(normalized_charging_animation_ms(750), Some(750));
+    }
+
+    #[test]
+    fn icon_for_capacity_maps_full_range() {
+        use crate::modules::icon_for_percentage;
+        let icons = vec!["low".to_string(), "mid".to_string(), "high".to_string()];
+        assert_eq!(icon_for_percentage(&icons, 0), "low");
+        assert_eq!(icon_for_percentage(&icons, 50), "mid");
+        assert_eq!(icon_for_percentage(&icons, 100), "high");
+    }
+
+    #[test]
+    fn battery_status_css_class_maps_known_statuses() {
+        assert_eq!(battery_status_css_class("Charging"), "status-charging");
+        assert_eq!(
+            battery_status_css_class("Discharging"),
+            "status-discharging"
+        );
+        assert_eq!(battery_status_css_class("Full"), "status-full");
+        assert_eq!(
+            battery_status_css_class("Not charging"),
+            "status-not-charging"
+        );
+        assert_eq!(battery_status_css_class("Unknown"), "status-unknown");
+    }
+
+    #[test]
+    fn battery_state_css_class_applies_thresholds_while_discharging() {
+        let thresholds = BatteryThresholds {
+            warning: Some(30),
+            critical: Some(15),
+        };
+        let snapshot = |capacity: u8, status: &str| BatterySnapshot {
+            device_name: "BAT0".to_string(),
+            capacity,
+            status: status.to_string(),
+            cycles: None,
+            health: None,
+            energy: None,
+            batteries: Vec::new(),
+        };
+
+        assert_eq!(
+            battery_state_css_class(&snapshot(15, "Discharging"), thresholds),
+            Some("state-critical")
+        );
+        assert_eq!(
+            battery_state_css_class(&snapshot(30, "Discharging"), thresholds),
+            Some("state-warning")
+        );
+        assert_eq!(
+            battery_state_css_class(&snapshot(31, "Discharging"), thresholds),
+            None
+        );
+        assert_eq!(
+            battery_state_css_class(&snapshot(10, "Charging"), thresholds),
+            None
+        );
+        assert_eq!(
+            battery_state_css_class(&snapshot(10, "Discharging"), BatteryThresholds::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn is_discharging_critical_follows_critical_threshold() {
+        let snapshot = |capacity: u8, status: &str| BatterySnapshot {
+            device_name: "BAT0".to_string(),
+            capacity,
+            status: status.to_string(),
+            cycles: None,
+            health: None,
+            energy: None,
+            batteries: Vec::new(),
+        };
+        let thresholds = BatteryThresholds {
+            warning: None,
+            critical: Some(25),
+        };
+
+        assert!(is_discharging_critical(
+            &snapshot(25, "Discharging"),
+            thresholds
+        ));
+        assert!(!is_discharging_critical(
+            &snapshot(26, "Discharging"),
+            thresholds
+        ));
+        assert!(!is_discharging_critical(
+            &snapshot(10, "Charging"),
+            thresholds
+        ));
+
+        let unset = BatteryThresholds::default();
+        assert!(is_discharging_critical(&snapshot(14, "Discharging"), unset));
+        assert!(!is_discharging_critical(
+            &snapshot(15, "Discharging"),
+            unset
+        ));
+    }
+
+    #[test]
+    fn render_format_splits_time_by_direction() {
+        let discharging = BatterySnapshot {
+            device_name: "BAT0".to_string(),
+            capacity: 40,
+            status: "Discharging".to_string(),
+            cycles: None,
+            health: None,
+            energy: Some(BatteryEnergy {
+                now: 20_000_000,
+                full: 50_000_000,
+                rate: 8_000_000,
+            }),
+            batteries: Vec::new(),
+        };
+        let format = "{time}|{time_to_empty}|{time_to_full}";
+        assert_eq!(render_format(format, &discharging, &[]), "2:30|2:30|");
+
+        let charging = BatterySnapshot {
+            status: "Charging".to_string(),
+            ..discharging.clone()
+        };
+        assert_eq!(render_format(format, &charging, &[]), "3:45||3:45");
+
+        let idle = BatterySnapshot {
+            energy: Some(BatteryEnergy {
+                rate: 0,
+                ..discharging.energy.expect("energy is set")
+            }),
+            ..discharging
+        };
+        assert_eq!(render_format(format, &idle, &[]), "||");
+    }
+
+    #[test]
+    fn parse_config_selects_format_by_status() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(serde_json::json!({
+                "backend": "upower",
+                "format": "{capacity}%",
+                "format-charging": "+{capacity}%",
+                "format_discharging": "-{capacity}%"
+            }))
+            .expect("battery config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.backend, BatteryBackendKind::Upower);
+
+        let formats = BatteryFormats {
+            default: cfg.format.expect("format is set"),
+            charging: cfg.format_charging,
+            discharging: cfg.format_discharging,
+            tooltip: None,
+        };
+        assert_eq!(formats.for_status("Charging"), "+{capacity}%");
+        assert_eq!(formats.for_status("Discharging"), "-{capacity}%");
+        assert_eq!(formats.for_status("Full"), "{capacity}%");
+    }
+
+    #[test]
+    fn parse_config_supports_critical_action_defaults() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(serde_json::json!({
+                "critical-action": { "command": "systemctl suspend" }
+            }))
+            .expect("battery config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("battery config should parse");
+        let action = cfg.critical_action.expect("critical action should parse");
+        assert_eq!(action.threshold, DEFAULT_CRITICAL_ACTION_THRESHOLD);
+        assert!(action.respect_inhibitors);
+    }
+
+    #[test]
+    fn critical_action_due_requires_discharging_at_threshold() {
+        let snapshot = |capacity, status: &str| BatterySnapshot {
+            device_name: "BAT0".to_string(),
+            capacity,
+            status: status.to_string(),
+            cycles: None,
+            health: None,
+            energy: None,
+            batteries: Vec::new(),
+        };
+        assert!(critical_action_due(&snapshot(5, "Discharging"), 5));
+        assert!(!critical_action_due(&snapshot(6, "Discharging"), 5));
+        assert!(!critical_action_due(&snapshot(3, "Charging"), 5));
+    }
+
+    #[test]
+    fn blocking_inhibitor_labels_keeps_sleep_and_shutdown_blocks() {
+        let inhibitor = |what: &str, who: &str, why: &str, mode: &str| {
+            (
+                what.to_string(),
+                who.to_string(),
+                why.to_string(),
+                mode.to_string(),
+                1000,
+                42,
+            )
+        };
+        let labels = blocking_inhibitor_labels(&[
+            inhibitor("sleep:idle", "borg", "Backup running", "block"),
+            inhibitor("sleep", "NetworkManager", "", "delay"),
+            inhibitor("handle-lid-switch", "sway", "lid", "block"),
+            inhibitor("shutdown", "apt", "", "block"),
+        ]);
+        assert_eq!(labels, vec!["borg: Backup running", "apt"]);
+    }
+}
@@ -0,0 +1,218 @@
+//! Battery readings from UPower over the system bus, refreshed on the
+//! device's `PropertiesChanged` signals.
+
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use zbus::blocking::{Connection, MessageIterator, Proxy};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::OwnedValue;
+use zbus::MatchRule;
+
+use crate::modules::backend_errors;
+
+use super::{BatteryEnergy, BatterySnapshot};
+
+const UPOWER_SERVICE: &str = "org.freedesktop.UPower";
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+const DEVICE_INTERFACE: &str = "org.freedesktop.UPower.Device";
+const DEVICES_PATH: &str = "/org/freedesktop/UPower/devices";
+/// UPower's combined view of every battery.
+const DISPLAY_DEVICE: &str = "DisplayDevice";
+const LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Listener failures are tracked apart from property reads, which can
+/// succeed while the signal subscription keeps failing.
+const SIGNALS_ERROR_BACKEND: &str = "battery/upower-signals";
+
+type DeviceProperties = HashMap<String, OwnedValue>;
+
+/// UPower names battery objects `battery_<native name>`, for example
+/// `battery_BAT0`.
+pub(super) fn device_path(device: Option<&str>) -> String {
+    match device {
+        Some(device) => format!("{DEVICES_PATH}/battery_{device}"),
+        None => format!("{DEVICES_PATH}/{DISPLAY_DEVICE}"),
+    }
+}
+
+/// `Ok(None)` when UPower reports no battery behind `path`.
+pub(super) fn read_snapshot(path: &str) -> Result<Option<BatterySnapshot>, String> {
+    let connection =
+        Connection::system().map_err(|err| format!("failed to connect to system dbus: {err}"))?;
+    let proxy = Proxy::new(&connection, UPOWER_SERVICE, path, PROPERTIES_INTERFACE)
+        .map_err(|err| format!("failed to create upower proxy: {err}"))?;
+    let properties: DeviceProperties = proxy
+        .call("GetAll", &(DEVICE_INTERFACE,))
+        .map_err(|err| format!("failed to read upower device {path}: {err}"))?;
+    Ok(snapshot_from_properties(&properties))
+}
+
+/// Sends a trigger for every property change on `path` until the receiver
+/// is dropped.
+pub(super) fn start_signal_listener(path: String, trigger_tx: mpsc::Sender<()>) {
+    thread::spawn(move || loop {
+        match listen_for_changes(&path, &trigger_tx) {
+            Ok(()) => return,
+            Err(err) => {
+                backend_errors::report(SIGNALS_ERROR_BACKEND, err);
+                thread::sleep(LISTENER_RETRY_DELAY);
+            }
+        }
+    });
+}
+
+/// `Ok` once the worker is gone; `Err` when the bus connection was lost.
+fn listen_for_changes(path: &str, trigger_tx: &mpsc::Sender<()>) -> Result<(), String> {
+    let connection =
+        Connection::system().map_err(|err| format!("failed to connect to system dbus: {err}"))?;
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .sender(UPOWER_SERVICE)
+        .and_then(|builder| builder.path(path))
+        .and_then(|builder| builder.interface(PROPERTIES_INTERFACE))
+        .and_then(|builder| builder.member("PropertiesChanged"))
+        .map(|builder| builder.build())
+        .map_err(|err| format!("failed to build match rule: {err}"))?;
+    let iterator = MessageIterator::for_match_rule(rule, &connection, Some(64))
+        .map_err(|err| format!("failed to subscribe to upower signals: {err}"))?;
+    backend_errors::clear(SIGNALS_ERROR_BACKEND);
+
+    // UPower may have changed state while the listener was down.
+    if trigger_tx.send(()).is_err() {
+        return Ok(());
+    }
+    for message in iterator {
+        if message.is_ok() && trigger_tx.send(()).is_err() {
+            return Ok(());
+        }
+    }
+    Err("signal stream ended".to_string())
+}
+
+fn snapshot_from_properties(properties: &DeviceProperties) -> Option<BatterySnapshot> {
+    if !property::<bool>(properties, "IsPresent").unwrap_or(false) {
+        return None;
+    }
+
+    let percentage = property::<f64>(properties, "Percentage").unwrap_or(0.0);
+    let native_path = property::<String>(properties, "NativePath").unwrap_or_default();
+    // Energy values are in Wh and W; the sysfs readings use µWh and µW.
+    let micro = |key: &str| {
+        property::<f64>(properties, key)
+            .filter(|value| value.is_finite() && *value >= 0.0)
+            .map(|value| (value * 1_000_000.0).round() as u64)
+    };
+    let energy = match (micro("Energy"), micro("EnergyFull")) {
+        (Some(now), Some(full)) if full > 0 => Some(BatteryEnergy {
+            now,
+            full,
+            rate: micro("EnergyRate").unwrap_or(0),
+        }),
+        _ => None,
+    };
+
+    Some(BatterySnapshot {
+        device_name: if native_path.is_empty() {
+            DISPLAY_DEVICE.to_string()
+        } else {
+            native_path
+        },
+        capacity: percentage.round().clamp(0.0, 100.0) as u8,
+        status: status_from_state(property::<u32>(properties, "State").unwrap_or(0)).to_string(),
+        cycles: property::<i32>(properties, "ChargeCycles")
+            .filter(|cycles| *cycles > 0)
+            .and_then(|cycles| u32::try_from(cycles).ok()),
+        health: property::<f64>(properties, "Capacity")
+            .filter(|health| *health > 0.0)
+            .map(|health| health.round() as u32),
+        energy,
+        batteries: Vec::new(),
+    })
+}
+
+/// Maps UPower's `State` enum onto the sysfs status names.
+fn status_from_state(state: u32) -> &'static str {
+    match state {
+        1 => "Charging",
+        2 | 3 => "Discharging",
+        4 => "Full",
+        5 | 6 => "Not charging",
+        _ => "Unknown",
+    }
+}
+
+fn property<T>(properties: &DeviceProperties, key: &str) -> Option<T>
+where
+    T: TryFrom<OwnedValue>,
+{
+    let value = properties.get(key)?.try_clone().ok()?;
+    T::try_from(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use zbus::zvariant::Str;
+
+    use super::*;
+
+    fn properties(entries: Vec<(&str, OwnedValue)>) -> DeviceProperties {
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect()
+    }
+
+    #[test]
+    fn device_path_uses_display_device_by_default() {
+        assert_eq!(
+            device_path(None),
+            "/org/freedesktop/UPower/devices/DisplayDevice"
+        );
+        assert_eq!(
+            device_path(Some("BAT1")),
+            "/org/freedesktop/UPower/devices/battery_BAT1"
+        );
+    }
+
+    #[test]
+    fn snapshot_from_properties_converts_units_and_state() {
+        let snapshot = snapshot_from_properties(&properties(vec![
+            ("IsPresent", OwnedValue::from(true)),
+            ("NativePath", OwnedValue::from(Str::from("BAT0"))),
+            ("Percentage", OwnedValue::from(41.6_f64)),
+            ("State", OwnedValue::from(2_u32)),
+            ("Energy", OwnedValue::from(20.5_f64)),
+            ("EnergyFull", OwnedValue::from(50.0_f64)),
+            ("EnergyRate", OwnedValue::from(10.25_f64)),
+            ("ChargeCycles", OwnedValue::from(-1_i32)),
+            ("Capacity", OwnedValue::from(88.4_f64)),
+        ]))
+        .expect("present battery should produce a snapshot");
+
+        assert_eq!(snapshot.device_name, "BAT0");
+        assert_eq!(snapshot.capacity, 42);
+        assert_eq!(snapshot.status, "Discharging");
+        assert_eq!(snapshot.cycles, None);
+        assert_eq!(snapshot.health, Some(88));
+        assert_eq!(
+            snapshot.energy,
+            Some(BatteryEnergy {
+                now: 20_500_000,
+                full: 50_000_000,
+                rate: 10_250_000,
+            })
+        );
+    }
+
+    #[test]
+    fn snapshot_from_properties_hides_missing_battery() {
+        assert!(snapshot_from_properties(&properties(vec![(
+            "IsPresent",
+            OwnedValue::from(false)
+        )]))
+        .is_none());
+        assert!(snapshot_from_properties(&DeviceProperties::new()).is_none());
+    }
+}
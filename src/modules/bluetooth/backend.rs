@@ -0,0 +1,360 @@
+//! BlueZ state over the system bus: one `GetManagedObjects` snapshot per
+//! change, refreshed on any `org.bluez` signal.
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use zbus::blocking::{Connection, MessageIterator, Proxy};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
+use zbus::MatchRule;
+
+use crate::modules::backend_errors;
+use crate::modules::broadcaster::Broadcaster;
+
+use super::MODULE_TYPE;
+
+const BLUEZ_SERVICE: &str = "org.bluez";
+const OBJECT_MANAGER_INTERFACE: &str = "org.freedesktop.DBus.ObjectManager";
+const ADAPTER_INTERFACE: &str = "org.bluez.Adapter1";
+const DEVICE_INTERFACE: &str = "org.bluez.Device1";
+const BATTERY_INTERFACE: &str = "org.bluez.Battery1";
+/// Catches bluetoothd restarts, whose signals come from a new owner.
+const RESYNC_INTERVAL: Duration = Duration::from_secs(30);
+/// Connecting a device changes several properties in a row; settle first.
+const SIGNAL_DEBOUNCE: Duration = Duration::from_millis(100);
+const LISTENER_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Listener failures are tracked apart from snapshot queries, which can
+/// succeed while the signal subscription keeps failing.
+const SIGNALS_ERROR_BACKEND: &str = "bluetooth/signals";
+
+type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct BluetoothSnapshot {
+    /// First adapter by object path; `None` without bluetoothd or hardware.
+    pub(super) controller: Option<BluetoothController>,
+    /// Paired or connected devices of `controller`, sorted by alias.
+    pub(super) devices: Vec<BluetoothDevice>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct BluetoothController {
+    pub(super) path: String,
+    pub(super) alias: String,
+    pub(super) powered: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct BluetoothDevice {
+    pub(super) path: String,
+    pub(super) alias: String,
+    pub(super) address: String,
+    pub(super) connected: bool,
+    pub(super) battery_percentage: Option<u8>,
+}
+
+impl BluetoothSnapshot {
+    pub(super) fn connected_devices(&self) -> impl Iterator<Item = &BluetoothDevice> {
+        self.devices.iter().filter(|device| device.connected)
+    }
+}
+
+pub(super) fn run_event_backend(broadcaster: &Arc<Broadcaster<BluetoothSnapshot>>) {
+    let (trigger_tx, trigger_rx) = mpsc::channel::<()>();
+    start_signal_listener(trigger_tx);
+
+    loop {
+        let snapshot = match query_snapshot() {
+            Ok(snapshot) => {
+                backend_errors::clear(MODULE_TYPE);
+                snapshot
+            }
+            Err(err) => {
+                backend_errors::report(MODULE_TYPE, err);
+                BluetoothSnapshot::default()
+            }
+        };
+        broadcaster.broadcast(snapshot);
+
+        match trigger_rx.recv_timeout(RESYNC_INTERVAL) {
+            Ok(()) => {
+                thread::sleep(SIGNAL_DEBOUNCE);
+                while trigger_rx.try_recv().is_ok() {}
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        if broadcaster.subscriber_count() == 0 {
+            return;
+        }
+    }
+}
+
+/// Forwards every `org.bluez` signal (property changes, devices and
+/// adapters appearing or going away) as a refresh trigger.
+fn start_signal_listener(trigger_tx: mpsc::Sender<()>) {
+    thread::spawn(move || loop {
+        match listen_for_signals(&trigger_tx) {
+            Ok(()) => return,
+            Err(err) => {
+                backend_errors::report(SIGNALS_ERROR_BACKEND, err);
+                thread::sleep(LISTENER_RETRY_DELAY);
+            }
+        }
+    });
+}
+
+/// `Ok` once the worker is gone; `Err` when the bus connection was lost.
+fn listen_for_signals(trigger_tx: &mpsc::Sender<()>) -> Result<(), String> {
+    let connection =
+        Connection::system().map_err(|err| format!("failed to connect to system dbus: {err}"))?;
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .sender(BLUEZ_SERVICE)
+        .map(|builder| builder.build())
+        .map_err(|err| format!("failed to build match rule: {err}"))?;
+    let iterator = MessageIterator::for_match_rule(rule, &connection, Some(256))
+        .map_err(|err| format!("failed to subscribe to bluez signals: {err}"))?;
+    backend_errors::clear(SIGNALS_ERROR_BACKEND);
+
+    // bluetoothd may have changed state while the listener was down.
+    if trigger_tx.send(()).is_err() {
+        return Ok(());
+    }
+    for message in iterator {
+        if message.is_ok() && trigger_tx.send(()).is_err() {
+            return Ok(());
+        }
+    }
+    Err("signal stream ended".to_string())
+}
+
+fn query_snapshot() -> Result<BluetoothSnapshot, String> {
+    let connection =
+        Connection::system().map_err(|err| format!("failed to connect to system dbus: {err}"))?;
+    let proxy = Proxy::new(&connection, BLUEZ_SERVICE, "/", OBJECT_MANAGER_INTERFACE)
+        .map_err(|err| format!("failed to create bluez proxy: {err}"))?;
+    let objects: ManagedObjects = proxy
+        .call("GetManagedObjects", &())
+        .map_err(|err| format!("GetManagedObjects failed: {err}"))?;
+    Ok(snapshot_from_objects(&objects))
+}
+
+fn snapshot_from_objects(objects: &ManagedObjects) -> BluetoothSnapshot {
+    let mut adapters = objects
+        .iter()
+        .filter_map(|(path, interfaces)| {
+            let adapter = interfaces.get(ADAPTER_INTERFACE)?;
+            Some(BluetoothController {
+                path: path.to_string(),
+                alias: property::<String>(adapter, "Alias").unwrap_or_default(),
+                powered: property::<bool>(adapter, "Powered").unwrap_or(false),
+            })
+        })
+        .collect::<Vec<_>>();
+    adapters.sort_by(|left, right| left.path.cmp(&right.path));
+    let Some(controller) = adapters.into_iter().next() else {
+        return BluetoothSnapshot::default();
+    };
+
+    let mut devices = objects
+        .iter()
+        .filter_map(|(path, interfaces)| {
+            let device = interfaces.get(DEVICE_INTERFACE)?;
+            let adapter = property::<OwnedObjectPath>(device, "Adapter")?;
+            if adapter.as_str() != controller.path {
+                return None;
+            }
+            let paired = property::<bool>(device, "Paired").unwrap_or(false);
+            let connected = property::<bool>(device, "Connected").unwrap_or(false);
+            if !paired && !connected {
+                return None;
+            }
+            let address = property::<String>(device, "Address").unwrap_or_default();
+            Some(BluetoothDevice {
+                path: path.to_string(),
+                alias: property::<String>(device, "Alias").unwrap_or_else(|| address.clone()),
+                address,
+                connected,
+                battery_percentage: interfaces
+                    .get(BATTERY_INTERFACE)
+                    .and_then(|battery| property::<u8>(battery, "Percentage")),
+            })
+        })
+        .collect::<Vec<_>>();
+    devices.sort_by(|left, right| {
+        left.alias
+            .to_lowercase()
+            .cmp(&right.alias.to_lowercase())
+            .then_with(|| left.address.cmp(&right.address))
+    });
+
+    BluetoothSnapshot {
+        controller: Some(controller),
+        devices,
+    }
+}
+
+fn property<T>(properties: &HashMap<String, OwnedValue>, key: &str) -> Option<T>
+where
+    T: TryFrom<OwnedValue>,
+{
+    let value = properties.get(key)?.try_clone().ok()?;
+    T::try_from(value).ok()
+}
+
+/// Calls `Connect` or `Disconnect` on a device. Blocks until BlueZ answers,
+/// which can take several seconds, so run it off the GTK thread.
+pub(super) fn set_device_connected(device_path: &str, connected: bool) -> Result<(), String> {
+    let method = if connected { "Connect" } else { "Disconnect" };
+    let connection =
+        Connection::system().map_err(|err| format!("failed to connect to system dbus: {err}"))?;
+    let proxy = Proxy::new(&connection, BLUEZ_SERVICE, device_path, DEVICE_INTERFACE)
+        .map_err(|err| format!("failed to create device proxy for {device_path}: {err}"))?;
+    proxy
+        .call_method(method, &())
+        .map_err(|err| format!("{method} failed for {device_path}: {err}"))?;
+    Ok(())
+}
+
+pub(super) fn set_controller_powered(controller_path: &str, powered: bool) -> Result<(), String> {
+    let connection =
+        Connection::system().map_err(|err| format!("failed to connect to system dbus: {err}"))?;
+    let proxy = Proxy::new(
+        &connection,
+        BLUEZ_SERVICE,
+        controller_path,
+        ADAPTER_INTERFACE,
+    )
+    .map_err(|err| format!("failed to create adapter proxy for {controller_path}: {err}"))?;
+    proxy
+        .set_property("Powered", powered)
+        .map_err(|err| format!("failed to set Powered on {controller_path}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use zbus::zvariant::{ObjectPath, Str};
+
+    use super::*;
+
+    fn object_path(path: &str) -> OwnedObjectPath {
+        OwnedObjectPath::try_from(path).expect("object path should parse")
+    }
+
+    fn string(value: &str) -> OwnedValue {
+        OwnedValue::from(Str::from(value.to_string()))
+    }
+
+    fn device(
+        adapter: &str,
+        alias: &str,
+        paired: bool,
+        connected: bool,
+    ) -> HashMap<String, OwnedValue> {
+        HashMap::from([
+            (
+                "Adapter".to_string(),
+                OwnedValue::from(ObjectPath::try_from(adapter.to_string()).expect("valid path")),
+            ),
+            ("Alias".to_string(), string(alias)),
+            ("Address".to_string(), string("00:11:22:33:44:55")),
+            ("Paired".to_string(), OwnedValue::from(paired)),
+            ("Connected".to_string(), OwnedValue::from(connected)),
+        ])
+    }
+
+    #[test]
+    fn snapshot_from_objects_lists_known_devices_of_first_adapter() {
+        let objects: ManagedObjects = HashMap::from([
+            (
+                object_path("/org/bluez/hci1"),
+                HashMap::from([(
+                    ADAPTER_INTERFACE.to_string(),
+                    HashMap::from([("Powered".to_string(), OwnedValue::from(false))]),
+                )]),
+            ),
+            (
+                object_path("/org/bluez/hci0"),
+                HashMap::from([(
+                    ADAPTER_INTERFACE.to_string(),
+                    HashMap::from([
+                        ("Alias".to_string(), string("laptop")),
+                        ("Powered".to_string(), OwnedValue::from(true)),
+                    ]),
+                )]),
+            ),
+            (
+                object_path("/org/bluez/hci0/dev_1"),
+                HashMap::from([
+                    (
+                        DEVICE_INTERFACE.to_string(),
+                        device("/org/bluez/hci0", "Headphones", true, true),
+                    ),
+                    (
+                        BATTERY_INTERFACE.to_string(),
+                        HashMap::from([("Percentage".to_string(), OwnedValue::from(80u8))]),
+                    ),
+                ]),
+            ),
+            (
+                object_path("/org/bluez/hci0/dev_2"),
+                HashMap::from([(
+                    DEVICE_INTERFACE.to_string(),
+                    device("/org/bluez/hci0", "keyboard", true, false),
+                )]),
+            ),
+            (
+                object_path("/org/bluez/hci0/dev_3"),
+                HashMap::from([(
+                    DEVICE_INTERFACE.to_string(),
+                    device("/org/bluez/hci0", "Nearby TV", false, false),
+                )]),
+            ),
+            (
+                object_path("/org/bluez/hci1/dev_4"),
+                HashMap::from([(
+                    DEVICE_INTERFACE.to_string(),
+                    device("/org/bluez/hci1", "Mouse", true, true),
+                )]),
+            ),
+        ]);
+
+        let snapshot = snapshot_from_objects(&objects);
+        assert_eq!(
+            snapshot.controller,
+            Some(BluetoothController {
+                path: "/org/bluez/hci0".to_string(),
+                alias: "laptop".to_string(),
+                powered: true,
+            })
+        );
+        let aliases = snapshot
+            .devices
+            .iter()
+            .map(|device| device.alias.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(aliases, vec!["Headphones", "keyboard"]);
+        assert_eq!(snapshot.devices[0].battery_percentage, Some(80));
+        assert_eq!(snapshot.connected_devices().count(), 1);
+    }
+
+    #[test]
+    fn snapshot_from_objects_without_adapter_is_empty() {
+        let objects: ManagedObjects = HashMap::from([(
+            object_path("/org/bluez/hci0/dev_1"),
+            HashMap::from([(
+                DEVICE_INTERFACE.to_string(),
+                device("/org/bluez/hci0", "Headphones", true, true),
+            )]),
+        )]);
+        assert_eq!(
+            snapshot_from_objects(&objects),
+            BluetoothSnapshot::default()
+        );
+    }
+}
@@ -0,0 +1,354 @@
+mod backend;
+mod ui;
+
+use std::sync::{Arc, OnceLock};
+
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::icons::Glyph;
+use crate::modules::text::{attach_text_subscription, visible_block, TextSink};
+use crate::modules::{render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel};
+
+use self::backend::{run_event_backend, BluetoothSnapshot};
+use self::ui::{build_controls_ui, refresh_controls_ui};
+use super::ModuleFactory;
+
+const DEFAULT_FORMAT: Glyph = Glyph::new("\u{f293} {status}", "ᛒ {status}");
+const DEFAULT_FORMAT_CONNECTED: Glyph = Glyph::new("\u{f293} {device_alias}", "ᛒ {device_alias}");
+const BLUETOOTH_STATE_CLASSES: [&str; 4] = [
+    "bluetooth-no-controller",
+    "bluetooth-off",
+    "bluetooth-on",
+    "bluetooth-connected",
+];
+pub(crate) const MODULE_TYPE: &str = "bluetooth";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct BluetoothConfig {
+    #[serde(default)]
+    pub(crate) format: Option<String>,
+    #[serde(rename = "format-on", alias = "format_on", default)]
+    pub(crate) format_on: Option<String>,
+    #[serde(rename = "format-off", alias = "format_off", default)]
+    pub(crate) format_off: Option<String>,
+    #[serde(rename = "format-connected", alias = "format_connected", default)]
+    pub(crate) format_connected: Option<String>,
+    /// Empty by default, which hides the module on machines without bluetooth.
+    #[serde(
+        rename = "format-no-controller",
+        alias = "format_no_controller",
+        default
+    )]
+    pub(crate) format_no_controller: Option<String>,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) controls: BluetoothControlsConfig,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct BluetoothControlsConfig {
+    #[serde(default)]
+    pub(crate) enabled: bool,
+    #[serde(default)]
+    pub(crate) open: BluetoothControlsOpenMode,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum BluetoothControlsOpenMode {
+    #[serde(alias = "left_click")]
+    #[default]
+    LeftClick,
+    #[serde(alias = "right_click")]
+    RightClick,
+}
+
+/// Formats for each state, with fallbacks to `format` already applied.
+#[derive(Debug, Clone)]
+struct BluetoothFormats {
+    on: String,
+    off: String,
+    connected: String,
+    no_controller: String,
+}
+
+/// The single BlueZ backend is shared by every bluetooth module; formats
+/// are applied per module.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BluetoothSharedKey;
+
+pub(crate) struct BluetoothFactory;
+
+pub(crate) const FACTORY: BluetoothFactory = BluetoothFactory;
+
+impl ModuleFactory for BluetoothFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_bluetooth_module(parsed).upcast())
+    }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        let formats = resolve_formats(&parse_config(config)?);
+        attach_text_subscription(subscribe_shared_bluetooth(), sink, move |snapshot| {
+            visible_block(&render_format(&formats, &snapshot).0)
+        });
+        Ok(())
+    }
+}
+
+pub(crate) fn parse_config(module: &ModuleConfig) -> Result<BluetoothConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn resolve_formats(config: &BluetoothConfig) -> BluetoothFormats {
+    let format = config
+        .format
+        .clone()
+        .unwrap_or_else(|| DEFAULT_FORMAT.get().to_string());
+    BluetoothFormats {
+        on: config.format_on.clone().unwrap_or_else(|| format.clone()),
+        off: config.format_off.clone().unwrap_or_else(|| format.clone()),
+        connected: config
+            .format_connected
+            .clone()
+            .or_else(|| config.format.clone())
+            .unwrap_or_else(|| DEFAULT_FORMAT_CONNECTED.get().to_string()),
+        no_controller: config.format_no_controller.clone().unwrap_or_default(),
+    }
+}
+
+fn bluetooth_registry(
+) -> &'static BackendRegistry<BluetoothSharedKey, Broadcaster<BluetoothSnapshot>> {
+    static REGISTRY: OnceLock<BackendRegistry<BluetoothSharedKey, Broadcaster<BluetoothSnapshot>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_bluetooth() -> Subscription<BluetoothSnapshot> {
+    let (broadcaster, start_worker) =
+        bluetooth_registry().get_or_create(BluetoothSharedKey, Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_bluetooth_worker(broadcaster);
+    }
+
+    receiver
+}
+
+fn start_bluetooth_worker(broadcaster: Arc<Broadcaster<BluetoothSnapshot>>) {
+    std::thread::spawn(move || {
+        run_event_backend(&broadcaster);
+        bluetooth_registry().remove(&BluetoothSharedKey, &broadcaster);
+    });
+}
+
+fn build_bluetooth_module(config: BluetoothConfig) -> Label {
+    let click_command = config.click.clone().or_else(|| config.on_click.clone());
+    let controls_enabled = config.controls.enabled;
+    let left_click_controls =
+        controls_enabled && config.controls.open == BluetoothControlsOpenMode::LeftClick;
+    if left_click_controls && click_command.is_some() {
+        eprintln!("bluetooth click command is ignored when controls.open=left-click");
+    }
+
+    let label = ModuleLabel::new(MODULE_TYPE)
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(if left_click_controls {
+            None
+        } else {
+            click_command
+        })
+        .into_label();
+    let controls_ui = controls_enabled.then(|| build_controls_ui(&label, config.controls.open));
+    let formats = resolve_formats(&config);
+
+    attach_subscription(
+        &label,
+        subscribe_shared_bluetooth(),
+        move |label, snapshot| {
+            let (text, state_class) = render_format(&formats, &snapshot);
+            let visible = !text.trim().is_empty();
+            label.set_visible(visible);
+            if visible {
+                label.set_markup(&text);
+            }
+            for class_name in BLUETOOTH_STATE_CLASSES {
+                label.remove_css_class(class_name);
+            }
+            label.add_css_class(state_class);
+            if let Some(controls_ui) = controls_ui.as_ref() {
+                refresh_controls_ui(controls_ui, &snapshot);
+            }
+        },
+    );
+
+    label
+}
+
+/// Rendered markup and the state class for `snapshot`.
+fn render_format(
+    formats: &BluetoothFormats,
+    snapshot: &BluetoothSnapshot,
+) -> (String, &'static str) {
+    let connected = snapshot.connected_devices().collect::<Vec<_>>();
+    let (format, status) = match snapshot.controller.as_ref() {
+        None => (&formats.no_controller, "no-controller"),
+        Some(controller) if !controller.powered => (&formats.off, "off"),
+        Some(_) if connected.is_empty() => (&formats.on, "on"),
+        Some(_) => (&formats.connected, "connected"),
+    };
+
+    let device = connected.first();
+    let controller_alias = snapshot
+        .controller
+        .as_ref()
+        .map(|controller| controller.alias.as_str())
+        .unwrap_or_default();
+    let device_battery_percentage = device
+        .and_then(|device| device.battery_percentage)
+        .map(|percentage| percentage.to_string())
+        .unwrap_or_default();
+    let text = render_markup_template(
+        format,
+        &[
+            ("{status}", status),
+            ("{controller_alias}", controller_alias),
+            ("{num_connections}", &connected.len().to_string()),
+            (
+                "{device_alias}",
+                device
+                    .map(|device| device.alias.as_str())
+                    .unwrap_or_default(),
+            ),
+            (
+                "{device_address}",
+                device
+                    .map(|device| device.address.as_str())
+                    .unwrap_or_default(),
+            ),
+            ("{device_battery_percentage}", &device_battery_percentage),
+        ],
+    );
+    let state_class = match status {
+        "no-controller" => "bluetooth-no-controller",
+        "off" => "bluetooth-off",
+        "on" => "bluetooth-on",
+        _ => "bluetooth-connected",
+    };
+    (text, state_class)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map};
+
+    use super::backend::{BluetoothController, BluetoothDevice};
+    use super::*;
+
+    fn config(value: Value) -> BluetoothConfig {
+        let Value::Object(map) = value else {
+            panic!("config should be an object");
+        };
+        parse_config(&ModuleConfig::new(MODULE_TYPE, map)).expect("config should parse")
+    }
+
+    fn snapshot(powered: bool, devices: Vec<BluetoothDevice>) -> BluetoothSnapshot {
+        BluetoothSnapshot {
+            controller: Some(BluetoothController {
+                path: "/org/bluez/hci0".to_string(),
+                alias: "laptop".to_string(),
+                powered,
+            }),
+            devices,
+        }
+    }
+
+    fn device(alias: &str, connected: bool, battery_percentage: Option<u8>) -> BluetoothDevice {
+        BluetoothDevice {
+            path: format!("/org/bluez/hci0/dev_{alias}"),
+            alias: alias.to_string(),
+            address: "00:11:22:33:44:55".to_string(),
+            connected,
+            battery_percentage,
+        }
+    }
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'bluetooth'"));
+    }
+
+    #[test]
+    fn parse_config_reads_controls() {
+        let parsed = config(json!({ "controls": { "enabled": true, "open": "right-click" } }));
+        assert!(parsed.controls.enabled);
+        assert_eq!(parsed.controls.open, BluetoothControlsOpenMode::RightClick);
+
+        let parsed = config(json!({}));
+        assert!(!parsed.controls.enabled);
+        assert_eq!(parsed.controls.open, BluetoothControlsOpenMode::LeftClick);
+    }
+
+    #[test]
+    fn render_format_picks_state_format() {
+        let formats = resolve_formats(&config(json!({
+            "format": "{status}",
+            "format-connected": "{device_alias} {device_battery_percentage}% ({num_connections})"
+        })));
+
+        assert_eq!(
+            render_format(&formats, &BluetoothSnapshot::default()),
+            (String::new(), "bluetooth-no-controller")
+        );
+        assert_eq!(
+            render_format(&formats, &snapshot(false, Vec::new())),
+            ("off".to_string(), "bluetooth-off")
+        );
+        assert_eq!(
+            render_format(
+                &formats,
+                &snapshot(true, vec![device("Mouse", false, None)])
+            ),
+            ("on".to_string(), "bluetooth-on")
+        );
+        assert_eq!(
+            render_format(
+                &formats,
+                &snapshot(
+                    true,
+                    vec![
+                        device("Buds & Case", true, Some(60)),
+                        device("Mouse", true, None)
+                    ]
+                )
+            ),
+            ("Buds &amp; Case 60% (2)".to_string(), "bluetooth-connected")
+        );
+    }
+}
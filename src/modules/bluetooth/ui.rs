@@ -0,0 +1,182 @@
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, GestureClick, Label, Orientation, Popover, PositionType};
+
+use super::backend::{
+    set_controller_powered, set_device_connected, BluetoothController, BluetoothDevice,
+    BluetoothSnapshot,
+};
+use super::{BluetoothControlsOpenMode, MODULE_TYPE};
+use crate::modules::{popovers, touch};
+
+#[derive(Clone)]
+pub(super) struct BluetoothControlsUi {
+    content: GtkBox,
+}
+
+pub(super) fn build_controls_ui(
+    label: &Label,
+    open_mode: BluetoothControlsOpenMode,
+) -> BluetoothControlsUi {
+    label.add_css_class("clickable");
+    label.add_css_class("bluetooth-controls-enabled");
+
+    let popover = Popover::new();
+    popover.add_css_class("bluetooth-controls-popover");
+    popover.set_autohide(true);
+    popover.set_has_arrow(true);
+    popover.set_position(PositionType::Top);
+    popover.set_parent(label);
+    popovers::track(&popover, MODULE_TYPE);
+
+    let content = GtkBox::new(Orientation::Vertical, 4);
+    content.add_css_class("bluetooth-controls-content");
+    popover.set_child(Some(&content));
+
+    install_controls_open_gesture(label, &popover, open_mode);
+
+    BluetoothControlsUi { content }
+}
+
+fn install_controls_open_gesture(
+    label: &Label,
+    popover: &Popover,
+    open_mode: BluetoothControlsOpenMode,
+) {
+    let button = match open_mode {
+        BluetoothControlsOpenMode::LeftClick => 1,
+        BluetoothControlsOpenMode::RightClick => 3,
+    };
+    let toggle_popover = {
+        let popover = popover.clone();
+        move || {
+            if popover.is_visible() {
+                popover.popdown();
+            } else {
+                popover.popup();
+            }
+        }
+    };
+    if button == 3 {
+        let toggle_popover = toggle_popover.clone();
+        touch::attach_long_press(label, move |_, _| toggle_popover());
+    }
+    let click = GestureClick::builder().button(button).build();
+    click.connect_pressed(move |_, _, _, _| toggle_popover());
+    label.add_controller(click);
+}
+
+/// Rebuilds the popover rows; BlueZ signals the resulting state change, so
+/// buttons only send the request and never update the rows themselves.
+pub(super) fn refresh_controls_ui(controls_ui: &BluetoothControlsUi, snapshot: &BluetoothSnapshot) {
+    let content = &controls_ui.content;
+    while let Some(child) = content.first_child() {
+        content.remove(&child);
+    }
+
+    let Some(controller) = snapshot.controller.as_ref() else {
+        content.append(&build_empty_label("No bluetooth controller"));
+        return;
+    };
+    content.append(&build_controller_row(controller));
+
+    if !controller.powered {
+        return;
+    }
+    content.append(&build_section_label("Paired devices"));
+    if snapshot.devices.is_empty() {
+        content.append(&build_empty_label("No paired devices"));
+    }
+    for device in &snapshot.devices {
+        content.append(&build_device_row(device));
+    }
+}
+
+fn build_controller_row(controller: &BluetoothController) -> GtkBox {
+    let row = GtkBox::new(Orientation::Horizontal, 6);
+    row.add_css_class("bluetooth-controls-controller-row");
+
+    let name = Label::new(Some(&controller.alias));
+    name.add_css_class("bluetooth-controls-section-title");
+    name.set_xalign(0.0);
+    name.set_hexpand(true);
+    row.append(&name);
+
+    let power_button = Button::with_label(if controller.powered { "On" } else { "Off" });
+    power_button.add_css_class("bluetooth-control-button");
+    if controller.powered {
+        power_button.add_css_class("active");
+    }
+    let path = controller.path.clone();
+    let powered = controller.powered;
+    power_button.connect_clicked(move |button| {
+        button.set_sensitive(false);
+        let path = path.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = set_controller_powered(&path, !powered) {
+                eprintln!("bluetooth: {err}");
+            }
+        });
+    });
+    row.append(&power_button);
+    row
+}
+
+fn build_device_row(device: &BluetoothDevice) -> GtkBox {
+    let row = GtkBox::new(Orientation::Horizontal, 6);
+    row.add_css_class("bluetooth-controls-device-row");
+    if device.connected {
+        row.add_css_class("connected");
+    }
+
+    let name = Label::new(Some(&device.alias));
+    name.add_css_class("bluetooth-controls-device-name");
+    name.set_tooltip_text(Some(&device.address));
+    name.set_xalign(0.0);
+    name.set_hexpand(true);
+    row.append(&name);
+
+    if let Some(percentage) = device.battery_percentage {
+        let battery = Label::new(Some(&format!("{percentage}%")));
+        battery.add_css_class("bluetooth-controls-device-battery");
+        row.append(&battery);
+    }
+
+    let button = Button::with_label(if device.connected {
+        "Disconnect"
+    } else {
+        "Connect"
+    });
+    button.add_css_class("bluetooth-control-button");
+    let path = device.path.clone();
+    let connect = !device.connected;
+    button.connect_clicked(move |button| {
+        button.set_sensitive(false);
+        button.set_label(if connect {
+            "Connecting…"
+        } else {
+            "Disconnecting…"
+        });
+        let path = path.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = set_device_connected(&path, connect) {
+                eprintln!("bluetooth: {err}");
+            }
+        });
+    });
+    row.append(&button);
+    row
+}
+
+fn build_section_label(text: &str) -> Label {
+    let label = Label::new(Some(text));
+    label.add_css_class("bluetooth-controls-section-title");
+    label.set_xalign(0.0);
+    label
+}
+
+fn build_empty_label(text: &str) -> Label {
+    let label = Label::new(Some(text));
+    label.add_css_class("bluetooth-controls-empty");
+    label.set_xalign(0.0);
+    label
+}
@@ -2,11 +2,14 @@ use std::collections::HashMap;
 use std::hash::Hash;
 use std::os::unix::io::RawFd;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use gtk::glib;
 use gtk::glib::IOCondition;
 use gtk::prelude::*;
 
+use crate::modules::runtime_stats;
+
 /// Fan-out broadcaster that sends updates to multiple subscribers.
 ///
 /// Each subscriber receives updates via an mpsc channel paired with a unix
@@ -94,6 +97,7 @@ impl<U: Clone + Send> Broadcaster<U> {
     /// Sends an update to all live subscribers, prunes dead ones, and caches
     /// the value for future subscribers.
     pub(crate) fn broadcast(&self, update: U) {
+        runtime_stats::record_broadcast();
         let mut latest = self
             .latest
             .lock()
@@ -120,6 +124,27 @@ impl<U: Clone + Send> Broadcaster<U> {
             .expect("broadcaster subscribers mutex poisoned")
             .len()
     }
+
+    /// Prunes subscribers whose [`Subscription`] was dropped without sending
+    /// anything, for workers that rarely broadcast.
+    pub(crate) fn prune_closed(&self) {
+        self.subscribers
+            .lock()
+            .expect("broadcaster subscribers mutex poisoned")
+            .retain(|slot| !pipe_reader_closed(slot.notify_fd));
+    }
+}
+
+/// The write end of a pipe reports `POLLERR` once its read end is closed.
+fn pipe_reader_closed(fd: RawFd) -> bool {
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: 0,
+        revents: 0,
+    };
+    // SAFETY: we pass a valid pointer to one pollfd entry and a zero timeout.
+    let rc = unsafe { libc::poll(&mut pollfd, 1, 0) };
+    rc > 0 && (pollfd.revents & (libc::POLLERR | libc::POLLNVAL)) != 0
 }
 
 fn nix_write_byte(fd: RawFd) -> std::io::Result<()> {
@@ -133,7 +158,7 @@ fn nix_write_byte(fd: RawFd) -> std::io::Result<()> {
     }
 }
 
-fn drain_pipe(fd: RawFd) {
+pub(crate) fn drain_pipe(fd: RawFd) {
     let mut buf = [0u8; 64];
     loop {
         let rc = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
@@ -181,7 +206,9 @@ pub(crate) fn attach_subscription<W, U>(
         };
         if let Some(sub) = sub_cell.borrow().as_ref() {
             while let Ok(update) = sub.receiver.try_recv() {
+                let started = Instant::now();
                 apply_fn(&widget, update);
+                runtime_stats::record_ui_update(started.elapsed());
             }
         }
         glib::ControlFlow::Continue
@@ -230,6 +257,7 @@ impl<K: Eq + Hash + Clone, B> BackendRegistry<K, B> {
 
         let backend = Arc::new(init_fn());
         backends.insert(key, Arc::clone(&backend));
+        runtime_stats::record_backend_started();
         (backend, true)
     }
 
@@ -243,6 +271,7 @@ impl<K: Eq + Hash + Clone, B> BackendRegistry<K, B> {
         if let Some(existing) = backends.get(key) {
             if Arc::ptr_eq(existing, backend) {
                 backends.remove(key);
+                runtime_stats::record_backend_stopped();
             }
         }
     }
@@ -309,6 +338,18 @@ mod tests {
         assert_eq!(bc.subscriber_count(), 1);
     }
 
+    #[test]
+    fn broadcaster_prune_closed_drops_dropped_subscriptions_quietly() {
+        let bc = Broadcaster::<String>::new();
+        let sub = bc.subscribe();
+        let alive_sub = bc.subscribe();
+        drop(sub);
+
+        bc.prune_closed();
+        assert_eq!(bc.subscriber_count(), 1);
+        assert!(alive_sub.receiver.try_recv().is_err());
+    }
+
     #[test]
     fn broadcaster_subscriber_count_tracks_subscribers() {
         let bc = Broadcaster::<String>::new();
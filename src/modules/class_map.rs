@@ -0,0 +1,219 @@
+//! Shared `class-map` support: CSS classes picked from the value a module
+//! label shows, so any label module can drive custom styling from config.
+
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+const CLASS_MAP_KEYS: [&str; 2] = ["class-map", "class_map"];
+
+/// One `class-map` entry; every condition that is set must hold.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+struct ClassRule {
+    /// Inclusive lower bound for the first number in the text.
+    #[serde(default)]
+    min: Option<f64>,
+    /// Inclusive upper bound for the first number in the text.
+    #[serde(default)]
+    max: Option<f64>,
+    /// The whole text, trimmed.
+    #[serde(rename = "match", default)]
+    matches: Option<String>,
+    #[serde(default)]
+    contains: Option<String>,
+    /// Whitespace-separated classes added while the entry matches.
+    class: String,
+}
+
+impl ClassRule {
+    fn applies_to(&self, text: &str, number: Option<f64>) -> bool {
+        let in_range = match (self.min, self.max) {
+            (None, None) => true,
+            (min, max) => number.is_some_and(|number| {
+                min.is_none_or(|min| number >= min) && max.is_none_or(|max| number <= max)
+            }),
+        };
+        in_range
+            && self
+                .matches
+                .as_deref()
+                .is_none_or(|value| text.trim() == value)
+            && self
+                .contains
+                .as_deref()
+                .is_none_or(|value| text.contains(value))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ClassMap {
+    rules: Vec<ClassRule>,
+}
+
+impl ClassMap {
+    /// Classes of every entry that matches `text`.
+    fn classes_for(&self, text: &str) -> Vec<&str> {
+        let number = first_number(text);
+        self.rules
+            .iter()
+            .filter(|rule| rule.applies_to(text, number))
+            .flat_map(|rule| rule.class.split_whitespace())
+            .collect()
+    }
+
+    fn all_classes(&self) -> impl Iterator<Item = &str> {
+        self.rules
+            .iter()
+            .flat_map(|rule| rule.class.split_whitespace())
+    }
+}
+
+/// Removes `class-map` from a module config.
+pub(crate) fn take_class_map(config: &mut Map<String, Value>) -> Result<Option<ClassMap>, String> {
+    let raw = CLASS_MAP_KEYS
+        .iter()
+        .filter_map(|key| config.remove(*key))
+        .reduce(|first, _| first);
+    let rules: Vec<ClassRule> = match raw {
+        None | Some(Value::Null) => return Ok(None),
+        Some(value) => {
+            serde_json::from_value(value).map_err(|err| format!("invalid `class-map`: {err}"))?
+        }
+    };
+    if let Some(index) = rules.iter().position(|rule| {
+        rule.min.is_none()
+            && rule.max.is_none()
+            && rule.matches.is_none()
+            && rule.contains.is_none()
+    }) {
+        return Err(format!(
+            "`class-map` entry {index} needs `min`, `max`, `match`, or `contains`"
+        ));
+    }
+    if let Some(index) = rules.iter().position(|rule| {
+        [&rule.matches, &rule.contains]
+            .into_iter()
+            .any(|value| value.as_deref() == Some(""))
+    }) {
+        return Err(format!(
+            "`class-map` entry {index} has an empty `match` or `contains`"
+        ));
+    }
+    Ok((!rules.is_empty()).then_some(ClassMap { rules }))
+}
+
+/// Keeps the mapped classes of `widget` in sync with its text. Only a
+/// single label has one text to match, so other widgets are rejected.
+pub(crate) fn attach(
+    module_type: &str,
+    widget: &Widget,
+    class_map: Option<ClassMap>,
+) -> Result<(), String> {
+    let Some(class_map) = class_map else {
+        return Ok(());
+    };
+    let Some(label) = widget.downcast_ref::<Label>() else {
+        return Err(format!(
+            "module '{module_type}' does not support `class-map` here: its widget is not a single label"
+        ));
+    };
+
+    let apply = move |label: &Label| {
+        let text = label.text();
+        let active = class_map.classes_for(&text);
+        for class_name in class_map.all_classes() {
+            if !active.contains(&class_name) {
+                label.remove_css_class(class_name);
+            }
+        }
+        for class_name in active {
+            label.add_css_class(class_name);
+        }
+    };
+    apply(label);
+    label.connect_notify_local(Some("label"), move |label, _| apply(label));
+    Ok(())
+}
+
+/// The first number in `text`, with an optional sign and decimals.
+fn first_number(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let signed = text[..start].ends_with('-');
+    let digits = &text[start..];
+    let end = digits
+        .char_indices()
+        .find(|&(index, c)| {
+            !(c.is_ascii_digit()
+                || (c == '.'
+                    && digits[..index].chars().all(|c| c.is_ascii_digit())
+                    && digits[index + 1..].starts_with(|c: char| c.is_ascii_digit())))
+        })
+        .map_or(digits.len(), |(index, _)| index);
+    let value: f64 = digits[..end].parse().ok()?;
+    Some(if signed { -value } else { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn class_map(value: Value) -> Result<Option<ClassMap>, String> {
+        let mut config = Map::new();
+        config.insert("class_map".to_string(), value);
+        let class_map = take_class_map(&mut config);
+        assert!(config.is_empty());
+        class_map
+    }
+
+    #[test]
+    fn first_number_reads_signed_decimals() {
+        assert_eq!(first_number("vol 45%"), Some(45.0));
+        assert_eq!(first_number("-12.5°C"), Some(-12.5));
+        assert_eq!(first_number("1.2.3"), Some(1.2));
+        assert_eq!(first_number("v2. ok"), Some(2.0));
+        assert_eq!(first_number("muted"), None);
+    }
+
+    #[test]
+    fn classes_for_applies_every_matching_entry() {
+        let class_map = class_map(json!([
+            { "min": 0, "max": 0, "class": "silent" },
+            { "min": 1, "max": 30, "class": "quiet" },
+            { "min": 80, "class": "loud very-loud" },
+            { "match": "muted", "class": "silent" },
+            { "contains": "bt", "class": "wireless" },
+        ]))
+        .expect("class map should parse")
+        .expect("entries are set");
+
+        assert_eq!(class_map.classes_for("0%"), ["silent"]);
+        assert_eq!(class_map.classes_for("25% bt"), ["quiet", "wireless"]);
+        assert_eq!(class_map.classes_for("95%"), ["loud", "very-loud"]);
+        assert_eq!(class_map.classes_for(" muted "), ["silent"]);
+        assert!(class_map.classes_for("45%").is_empty());
+        assert_eq!(
+            class_map.all_classes().collect::<Vec<_>>(),
+            ["silent", "quiet", "loud", "very-loud", "silent", "wireless"]
+        );
+    }
+
+    #[test]
+    fn take_class_map_validates_entries() {
+        assert_eq!(class_map(Value::Null), Ok(None));
+        assert_eq!(class_map(json!([])), Ok(None));
+        let err = class_map(json!([{ "class": "always" }])).expect_err("no condition");
+        assert!(err.contains("entry 0"));
+        assert!(class_map(json!([{ "min": 1 }])).is_err());
+        assert!(class_map(json!({ "min": 1, "class": "x" })).is_err());
+        let err = class_map(json!([
+            { "match": "on", "class": "on" },
+            { "contains": "", "class": "always" },
+        ]))
+        .expect_err("empty contains");
+        assert!(err.contains("entry 1"));
+        assert!(class_map(json!([{ "match": "", "class": "x" }])).is_err());
+    }
+}
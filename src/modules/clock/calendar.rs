@@ -0,0 +1,399 @@
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+
+/// Days between the Julian Day Number epoch and `NaiveDate::num_days_from_ce`.
+const JDN_CE_OFFSET: i64 = 1_721_425;
+/// Julian Day Number of 1 Muharram AH 1 (civil epoch).
+const ISLAMIC_EPOCH_JDN: i64 = 1_948_440;
+/// Fixed (R.D.) date of 1 Tishrei AM 1.
+const HEBREW_EPOCH_RD: i64 = -1_373_427;
+/// Jalali leap-cycle break years (see jalaali-js / Borkowski).
+const PERSIAN_BREAKS: [i64; 20] = [
+    -61, 9, 38, 199, 426, 686, 756, 818, 1111, 1181, 1210, 1635, 2060, 2097, 2192, 2262, 2324,
+    2394, 2456, 3178,
+];
+
+const PERSIAN_MONTHS: [&str; 12] = [
+    "Farvardin",
+    "Ordibehesht",
+    "Khordad",
+    "Tir",
+    "Mordad",
+    "Shahrivar",
+    "Mehr",
+    "Aban",
+    "Azar",
+    "Dey",
+    "Bahman",
+    "Esfand",
+];
+const ISLAMIC_MONTHS: [&str; 12] = [
+    "Muharram",
+    "Safar",
+    "Rabi' al-awwal",
+    "Rabi' al-thani",
+    "Jumada al-awwal",
+    "Jumada al-thani",
+    "Rajab",
+    "Sha'ban",
+    "Ramadan",
+    "Shawwal",
+    "Dhu al-Qi'dah",
+    "Dhu al-Hijjah",
+];
+/// Indexed by the Nisan-based month number used in the conversion (1 = Nisan).
+const HEBREW_MONTHS: [&str; 13] = [
+    "Nisan", "Iyyar", "Sivan", "Tammuz", "Av", "Elul", "Tishrei", "Cheshvan", "Kislev", "Tevet",
+    "Shevat", "Adar", "Adar II",
+];
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ClockCalendar {
+    #[default]
+    Gregorian,
+    Persian,
+    Hebrew,
+    Islamic,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct CalendarDate {
+    pub(super) year: i64,
+    /// 1-based month number in the calendar's own year order.
+    pub(super) month: u32,
+    pub(super) day: u32,
+    pub(super) month_name: &'static str,
+}
+
+impl ClockCalendar {
+    /// Converts a Gregorian date; returns `None` for `Gregorian` since chrono
+    /// already renders those fields.
+    pub(super) fn convert(self, date: NaiveDate) -> Option<CalendarDate> {
+        let rd = i64::from(date.num_days_from_ce());
+        match self {
+            Self::Gregorian => None,
+            Self::Persian => Some(persian_from_gregorian(date)),
+            Self::Hebrew => Some(hebrew_from_fixed(rd)),
+            Self::Islamic => Some(islamic_from_jdn(rd + JDN_CE_OFFSET)),
+        }
+    }
+}
+
+/// Rewrites the date fields of a strftime-style `format` (`%d`, `%e`, `%m`,
+/// `%B`, `%b`, `%h`, `%Y`, `%y`, plus `-`-flagged variants) with values from
+/// `date`. All other specifiers, including `%%`, are left for the time
+/// formatter.
+pub(super) fn substitute_calendar_fields(format: &str, date: &CalendarDate) -> String {
+    let mut out = String::with_capacity(format.len());
+    let mut chars = format.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            out.push(ch);
+            continue;
+        }
+
+        let unpadded = chars.peek() == Some(&'-');
+        if unpadded {
+            chars.next();
+        }
+        let Some(spec) = chars.next() else {
+            out.push('%');
+            if unpadded {
+                out.push('-');
+            }
+            break;
+        };
+
+        let replacement = match (spec, unpadded) {
+            ('d', false) => Some(format!("{:02}", date.day)),
+            ('e', false) => Some(format!("{:>2}", date.day)),
+            ('d' | 'e', true) => Some(date.day.to_string()),
+            ('m', false) => Some(format!("{:02}", date.month)),
+            ('m', true) => Some(date.month.to_string()),
+            ('B' | 'b' | 'h', _) => Some(date.month_name.to_string()),
+            ('Y', _) => Some(date.year.to_string()),
+            ('y', false) => Some(format!("{:02}", date.year.rem_euclid(100))),
+            ('y', true) => Some(date.year.rem_euclid(100).to_string()),
+            _ => None,
+        };
+
+        match replacement {
+            Some(value) => out.push_str(&value),
+            None => {
+                out.push('%');
+                if unpadded {
+                    out.push('-');
+                }
+                out.push(spec);
+            }
+        }
+    }
+
+    out
+}
+
+fn islamic_from_jdn(jdn: i64) -> CalendarDate {
+    // Tabular (civil) Islamic calendar, 30-year cycle.
+    let mut l = jdn - ISLAMIC_EPOCH_JDN + 10632;
+    let n = (l - 1) / 10631;
+    l = l - 10631 * n + 354;
+    let j = ((10985 - l) / 5316) * ((50 * l) / 17719) + (l / 5670) * ((43 * l) / 15238);
+    l = l - ((30 - j) / 15) * ((17719 * j) / 50) - (j / 16) * ((15238 * j) / 43) + 29;
+    let month = (24 * l) / 709;
+    let day = l - (709 * month) / 24;
+    let year = 30 * n + j - 30;
+
+    CalendarDate {
+        year,
+        month: month as u32,
+        day: day as u32,
+        month_name: ISLAMIC_MONTHS[(month - 1) as usize],
+    }
+}
+
+struct PersianYearInfo {
+    leap: i64,
+    gregorian_year: i64,
+    march_day: i64,
+}
+
+fn persian_year_info(jy: i64) -> PersianYearInfo {
+    let gregorian_year = jy + 621;
+    let mut leap_j = -14;
+    let mut jp = PERSIAN_BREAKS[0];
+    let mut jump = 0;
+    for &jm in &PERSIAN_BREAKS[1..] {
+        jump = jm - jp;
+        if jy < jm {
+            break;
+        }
+        leap_j += jump / 33 * 8 + (jump % 33) / 4;
+        jp = jm;
+    }
+
+    let mut n = jy - jp;
+    leap_j += n / 33 * 8 + ((n % 33) + 3) / 4;
+    if jump % 33 == 4 && jump - n == 4 {
+        leap_j += 1;
+    }
+
+    let leap_g = gregorian_year / 4 - ((gregorian_year / 100 + 1) * 3) / 4 - 150;
+    let march_day = 20 + leap_j - leap_g;
+
+    if jump - n < 6 {
+        n = n - jump + ((jump + 4) / 33) * 33;
+    }
+    let mut leap = (((n + 1) % 33) - 1) % 4;
+    if leap == -1 {
+        leap = 4;
+    }
+
+    PersianYearInfo {
+        leap,
+        gregorian_year,
+        march_day,
+    }
+}
+
+fn persian_from_gregorian(date: NaiveDate) -> CalendarDate {
+    let mut year = i64::from(date.year()) - 621;
+    let info = persian_year_info(year);
+    let nowruz = NaiveDate::from_ymd_opt(info.gregorian_year as i32, 3, info.march_day as u32)
+        .expect("Persian new year should be a valid March date");
+    let mut k = (date - nowruz).num_days();
+
+    let (month, day) = if (0..=185).contains(&k) {
+        (1 + k / 31, k % 31 + 1)
+    } else {
+        if k >= 0 {
+            k -= 186;
+        } else {
+            year -= 1;
+            k += 179;
+            if info.leap == 1 {
+                k += 1;
+            }
+        }
+        (7 + k / 30, k % 30 + 1)
+    };
+
+    CalendarDate {
+        year,
+        month: month as u32,
+        day: day as u32,
+        month_name: PERSIAN_MONTHS[(month - 1) as usize],
+    }
+}
+
+fn hebrew_elapsed_days(year: i64) -> i64 {
+    let months_elapsed = (235 * year - 234).div_euclid(19);
+    let parts_elapsed = 12084 + 13753 * months_elapsed;
+    let days = 29 * months_elapsed + parts_elapsed.div_euclid(25920);
+    if (3 * (days + 1)).rem_euclid(7) < 3 {
+        days + 1
+    } else {
+        days
+    }
+}
+
+fn hebrew_new_year(year: i64) -> i64 {
+    let ny0 = hebrew_elapsed_days(year - 1);
+    let ny1 = hebrew_elapsed_days(year);
+    let ny2 = hebrew_elapsed_days(year + 1);
+    let correction = if ny2 - ny1 == 356 {
+        2
+    } else if ny1 - ny0 == 382 {
+        1
+    } else {
+        0
+    };
+    HEBREW_EPOCH_RD + ny1 + correction
+}
+
+fn hebrew_is_leap_year(year: i64) -> bool {
+    (7 * year + 1).rem_euclid(19) < 7
+}
+
+fn hebrew_last_month(year: i64) -> u32 {
+    if hebrew_is_leap_year(year) {
+        13
+    } else {
+        12
+    }
+}
+
+fn hebrew_month_length(month: u32, year: i64) -> i64 {
+    let days_in_year = hebrew_new_year(year + 1) - hebrew_new_year(year);
+    let short = match month {
+        2 | 4 | 6 | 10 | 13 => true,
+        12 => !hebrew_is_leap_year(year),
+        8 => days_in_year % 10 != 5,
+        9 => days_in_year % 10 == 3,
+        _ => false,
+    };
+    if short {
+        29
+    } else {
+        30
+    }
+}
+
+fn hebrew_month_start(year: i64, month: u32) -> i64 {
+    let months_before: Vec<u32> = if month < 7 {
+        (7..=hebrew_last_month(year)).chain(1..month).collect()
+    } else {
+        (7..month).collect()
+    };
+    hebrew_new_year(year)
+        + months_before
+            .into_iter()
+            .map(|m| hebrew_month_length(m, year))
+            .sum::<i64>()
+}
+
+fn hebrew_from_fixed(rd: i64) -> CalendarDate {
+    let approx = ((rd - HEBREW_EPOCH_RD) * 98496).div_euclid(35_975_351) + 1;
+    let year = if hebrew_new_year(approx) <= rd {
+        approx
+    } else {
+        approx - 1
+    };
+
+    let mut month = if rd < hebrew_month_start(year, 1) {
+        7
+    } else {
+        1
+    };
+    while rd >= hebrew_month_start(year, month) + hebrew_month_length(month, year) {
+        month += 1;
+    }
+    let day = rd - hebrew_month_start(year, month) + 1;
+
+    let last_month = hebrew_last_month(year);
+    // Civil order counts from Tishrei.
+    let civil_month = if month >= 7 {
+        month - 6
+    } else {
+        month + last_month - 6
+    };
+    let month_name = if month == 12 && last_month == 13 {
+        "Adar I"
+    } else {
+        HEBREW_MONTHS[(month - 1) as usize]
+    };
+
+    CalendarDate {
+        year,
+        month: civil_month,
+        day: day as u32,
+        month_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(calendar: ClockCalendar, y: i32, m: u32, d: u32) -> CalendarDate {
+        calendar
+            .convert(NaiveDate::from_ymd_opt(y, m, d).expect("valid date"))
+            .expect("non-gregorian calendar should convert")
+    }
+
+    #[test]
+    fn gregorian_is_passthrough() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).expect("valid date");
+        assert_eq!(ClockCalendar::Gregorian.convert(date), None);
+    }
+
+    #[test]
+    fn persian_conversion_matches_nowruz() {
+        let date = convert(ClockCalendar::Persian, 2025, 3, 21);
+        assert_eq!((date.year, date.month, date.day), (1404, 1, 1));
+        assert_eq!(date.month_name, "Farvardin");
+
+        let date = convert(ClockCalendar::Persian, 2024, 10, 3);
+        assert_eq!((date.year, date.month, date.day), (1403, 7, 12));
+
+        let date = convert(ClockCalendar::Persian, 2025, 3, 14);
+        assert_eq!((date.year, date.month, date.day), (1403, 12, 24));
+    }
+
+    #[test]
+    fn hebrew_conversion_matches_known_dates() {
+        let date = convert(ClockCalendar::Hebrew, 2024, 10, 3);
+        assert_eq!((date.year, date.month, date.day), (5785, 1, 1));
+        assert_eq!(date.month_name, "Tishrei");
+
+        let date = convert(ClockCalendar::Hebrew, 2024, 4, 23);
+        assert_eq!((date.year, date.day), (5784, 15));
+        assert_eq!(date.month_name, "Nisan");
+
+        let date = convert(ClockCalendar::Hebrew, 2024, 3, 11);
+        assert_eq!(date.day, 1);
+        assert_eq!(date.month_name, "Adar II");
+    }
+
+    #[test]
+    fn islamic_conversion_matches_tabular_calendar() {
+        let date = convert(ClockCalendar::Islamic, 2024, 3, 11);
+        assert_eq!((date.year, date.month, date.day), (1445, 9, 1));
+        assert_eq!(date.month_name, "Ramadan");
+    }
+
+    #[test]
+    fn substitute_calendar_fields_rewrites_date_specifiers_only() {
+        let date = CalendarDate {
+            year: 1404,
+            month: 1,
+            day: 5,
+            month_name: "Farvardin",
+        };
+        assert_eq!(
+            substitute_calendar_fields("%a %d %B %Y %H:%M %%d %-d/%-m/%y", &date),
+            "%a 05 Farvardin 1404 %H:%M %%d 5/1/04"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::ffi::{CStr, CString};
+
+use chrono::{DateTime, Local};
+
+const STRFTIME_INITIAL_BUFFER: usize = 128;
+const STRFTIME_MAX_BUFFER: usize = 4096;
+
+/// Renders strftime formats with a specific `LC_TIME` locale via libc,
+/// independent of the process locale.
+pub(super) struct LocaleTimeFormatter {
+    locale: libc::locale_t,
+}
+
+impl LocaleTimeFormatter {
+    pub(super) fn new(name: &str) -> Result<Self, String> {
+        let c_name = CString::new(name).map_err(|_| format!("invalid locale name '{name}'"))?;
+        let locale =
+            unsafe { libc::newlocale(libc::LC_TIME_MASK, c_name.as_ptr(), std::ptr::null_mut()) };
+        if locale.is_null() {
+            return Err(format!("locale '{name}' is not available"));
+        }
+        Ok(Self { locale })
+    }
+
+    pub(super) fn format(&self, format: &str, now: &DateTime<Local>) -> String {
+        if format.is_empty() {
+            return String::new();
+        }
+        let Ok(c_format) = CString::new(format) else {
+            return String::new();
+        };
+
+        let timestamp = now.timestamp() as libc::time_t;
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&timestamp, &mut tm) }.is_null() {
+            return String::new();
+        }
+
+        // strftime returns 0 both for "buffer too small" and for empty
+        // output, so grow until the result fits or we hit the cap.
+        let mut size = STRFTIME_INITIAL_BUFFER;
+        while size <= STRFTIME_MAX_BUFFER {
+            let mut buffer = vec![0 as libc::c_char; size];
+            let written = unsafe {
+                libc::strftime_l(
+                    buffer.as_mut_ptr(),
+                    buffer.len(),
+                    c_format.as_ptr(),
+                    &tm,
+                    self.locale,
+                )
+            };
+            if written > 0 {
+                return unsafe { CStr::from_ptr(buffer.as_ptr()) }
+                    .to_string_lossy()
+                    .into_owned();
+            }
+            size *= 2;
+        }
+
+        String::new()
+    }
+}
+
+impl Drop for LocaleTimeFormatter {
+    fn drop(&mut self) {
+        unsafe { libc::freelocale(self.locale) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_rejects_unknown_locale() {
+        let err = LocaleTimeFormatter::new("xx_INVALID.UTF-8")
+            .err()
+            .expect("unknown locale should fail");
+        assert!(err.contains("not available"));
+    }
+
+    #[test]
+    fn format_with_c_locale_renders_english_names() {
+        let formatter = LocaleTimeFormatter::new("C").expect("C locale should exist");
+        let now = Local::now();
+        assert_eq!(formatter.format("%Y", &now), now.format("%Y").to_string());
+    }
+}
@@ -0,0 +1,527 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use chrono::{DateTime, Local};
+use gtk::glib::ControlFlow;
+use gtk::prelude::*;
+use gtk::{EventControllerScroll, EventControllerScrollFlags, GestureClick, Label, Widget};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::modules::text::{visible_block, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
+use crate::modules::{render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel};
+
+use super::ModuleFactory;
+
+mod calendar;
+mod locale;
+mod popover;
+
+use self::calendar::{substitute_calendar_fields, ClockCalendar};
+use self::locale::LocaleTimeFormatter;
+use self::popover::{install_calendar_popover, CalendarPopoverConfig, CalendarPopoverOpenMode};
+
+const DEFAULT_CLOCK_FMT: &str = "%a %d. %b %H:%M:%S";
+const DEFAULT_CLOCK_TEMPLATE: &str = "{}";
+pub(crate) const MODULE_TYPE: &str = "clock";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct ClockConfig {
+    #[serde(default)]
+    pub(crate) format: Option<String>,
+    #[serde(rename = "time-format", alias = "time_format", default)]
+    pub(crate) time_format: Option<String>,
+    #[serde(default)]
+    pub(crate) formats: Option<Vec<String>>,
+    #[serde(default)]
+    pub(crate) locale: Option<String>,
+    #[serde(default)]
+    pub(crate) calendar: ClockCalendar,
+    #[serde(rename = "calendar-popover", alias = "calendar_popover", default)]
+    pub(crate) calendar_popover: CalendarPopoverConfig,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+pub(crate) struct ClockFactory;
+
+pub(crate) const FACTORY: ClockFactory = ClockFactory;
+
+impl ModuleFactory for ClockFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        let locale = parsed
+            .locale
+            .as_deref()
+            .map(LocaleTimeFormatter::new)
+            .transpose()?;
+        let mut click_command = parsed.click.or(parsed.on_click);
+        let left_click_popover = parsed.calendar_popover.enabled
+            && parsed.calendar_popover.open == CalendarPopoverOpenMode::LeftClick;
+        if parsed.formats.is_some() && left_click_popover {
+            return Err(format!(
+                "{MODULE_TYPE} 'formats' uses left click to cycle; set calendar-popover.open to right-click"
+            ));
+        }
+        if left_click_popover && click_command.take().is_some() {
+            eprintln!("clock click command is ignored when calendar-popover.open=left-click");
+        }
+        if parsed.formats.is_some() && click_command.is_some() {
+            return Err(format!(
+                "{MODULE_TYPE} 'formats' uses left click to cycle; remove 'click'"
+            ));
+        }
+        let (template, time_formats) =
+            resolve_clock_formats(parsed.format, parsed.time_format, parsed.formats)?;
+        let instance_key = format_instance_key(context.monitor_connector.as_deref(), &time_formats);
+        let renderer = ClockTimeRenderer {
+            selected: Cell::new(remembered_format_index(&instance_key, time_formats.len())),
+            time_formats,
+            calendar: parsed.calendar,
+            locale,
+        };
+        let label = build_clock_module(
+            template,
+            context.tooltip_format.clone(),
+            renderer,
+            instance_key,
+            click_command,
+            parsed.class,
+        );
+        if parsed.calendar_popover.enabled {
+            let localized = parsed.locale.is_some() || parsed.calendar != ClockCalendar::Gregorian;
+            let heading = match parsed.calendar_popover.heading_format(localized) {
+                Some(date_format) => Some(ClockTimeRenderer {
+                    time_formats: vec![date_format],
+                    selected: Cell::new(0),
+                    calendar: parsed.calendar,
+                    locale: parsed
+                        .locale
+                        .as_deref()
+                        .map(LocaleTimeFormatter::new)
+                        .transpose()?,
+                }),
+                None => None,
+            };
+            let popover_config = CalendarPopoverConfig {
+                config_source: context.config_source.clone(),
+                ..parsed.calendar_popover
+            };
+            install_calendar_popover(&label, &popover_config, heading);
+        }
+        Ok(label.upcast())
+    }
+
+    /// Renders the first of `formats` (there is no click to cycle them).
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        let parsed = parse_config(config)?;
+        let locale = parsed
+            .locale
+            .as_deref()
+            .map(LocaleTimeFormatter::new)
+            .transpose()?;
+        let (template, time_formats) =
+            resolve_clock_formats(parsed.format, parsed.time_format, parsed.formats)?;
+        let renderer = ClockTimeRenderer {
+            time_formats,
+            selected: Cell::new(0),
+            calendar: parsed.calendar,
+            locale,
+        };
+
+        let update = move || {
+            let rendered_time = renderer.render(&Local::now());
+            sink(visible_block(&render_markup_template(
+                &template,
+                &[("{}", &rendered_time)],
+            )));
+        };
+        update();
+        gtk::glib::timeout_add_seconds_local(1, move || {
+            update();
+            ControlFlow::Continue
+        });
+        Ok(())
+    }
+}
+
+pub(crate) fn default_module_config() -> ModuleConfig {
+    let mut map = Map::new();
+    map.insert("time-format".to_string(), Value::Null);
+    map.insert("format".to_string(), Value::Null);
+    ModuleConfig::new(MODULE_TYPE, map)
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<ClockConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+thread_local! {
+    /// Selected `formats` index per clock instance, kept across bar rebuilds
+    /// (config reload, monitor hotplug) for the rest of the session.
+    static SELECTED_FORMATS: RefCell<HashMap<String, usize>> = RefCell::new(HashMap::new());
+}
+
+/// Identifies a clock across rebuilds by its output and its format list.
+fn format_instance_key(connector: Option<&str>, time_formats: &[String]) -> String {
+    format!(
+        "{}\u{1f}{}",
+        connector.unwrap_or_default(),
+        time_formats.join("\u{1f}")
+    )
+}
+
+fn remembered_format_index(key: &str, len: usize) -> usize {
+    SELECTED_FORMATS
+        .with(|selected| selected.borrow().get(key).copied())
+        .filter(|index| *index < len)
+        .unwrap_or(0)
+}
+
+fn remember_format_index(key: &str, index: usize) {
+    SELECTED_FORMATS.with(|selected| {
+        selected.borrow_mut().insert(key.to_string(), index);
+    });
+}
+
+fn cycle_index(current: usize, len: usize, forward: bool) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    if forward {
+        (current + 1) % len
+    } else {
+        (current + len - 1) % len
+    }
+}
+
+/// Renders the selected time format for a point in time, applying the
+/// configured calendar to date fields and the configured locale to names.
+struct ClockTimeRenderer {
+    time_formats: Vec<String>,
+    selected: Cell<usize>,
+    calendar: ClockCalendar,
+    locale: Option<LocaleTimeFormatter>,
+}
+
+impl ClockTimeRenderer {
+    fn render(&self, now: &DateTime<Local>) -> String {
+        let selected = &self.time_formats[self.selected.get()];
+        let time_format = match self.calendar.convert(now.date_naive()) {
+            Some(date) => substitute_calendar_fields(selected, &date),
+            None => selected.clone(),
+        };
+        match self.locale.as_ref() {
+            Some(locale) => locale.format(&time_format, now),
+            None => now.format(&time_format).to_string(),
+        }
+    }
+}
+
+fn build_clock_module(
+    template: String,
+    tooltip_format: Option<String>,
+    renderer: ClockTimeRenderer,
+    instance_key: String,
+    click_command: Option<String>,
+    class: Option<String>,
+) -> Label {
+    let label = ModuleLabel::new("clock")
+        .with_css_classes(class.as_deref())
+        .with_click_command(click_command)
+        .into_label();
+
+    let cycles = renderer.time_formats.len() > 1;
+    let renderer = Rc::new(renderer);
+
+    let tooltip = tooltip_format.map(|format| (ModuleTooltip::attach(&label), format));
+    let update = {
+        let template = template.clone();
+        let renderer = Rc::clone(&renderer);
+        move |label: &Label| {
+            let rendered_time = renderer.render(&Local::now());
+            let rendered = render_markup_template(&template, &[("{}", &rendered_time)]);
+            let visible = !rendered.trim().is_empty();
+            label.set_visible(visible);
+            if visible {
+                label.set_markup(&rendered);
+            }
+            if let Some((tooltip, format)) = &tooltip {
+                tooltip.set_markup(&render_markup_template(format, &[("{}", &rendered_time)]));
+            }
+        }
+    };
+
+    update(&label);
+
+    if cycles {
+        let step = {
+            let renderer = Rc::clone(&renderer);
+            let update = update.clone();
+            move |label: &Label, forward: bool| {
+                let len = renderer.time_formats.len();
+                let index = cycle_index(renderer.selected.get(), len, forward);
+                renderer.selected.set(index);
+                remember_format_index(&instance_key, index);
+                update(label);
+            }
+        };
+        label.add_css_class("clickable");
+        label.add_css_class("clock-cycle");
+
+        let click = GestureClick::builder().button(1).build();
+        click.connect_pressed({
+            let step = step.clone();
+            move |gesture, _, _, _| {
+                if let Some(label) = gesture.widget().and_downcast::<Label>() {
+                    step(&label, true);
+                }
+            }
+        });
+        label.add_controller(click);
+
+        let scroll = EventControllerScroll::new(
+            EventControllerScrollFlags::VERTICAL | EventControllerScrollFlags::DISCRETE,
+        );
+        scroll.connect_scroll(move |controller, _, dy| {
+            if dy == 0.0 {
+                return gtk::glib::Propagation::Proceed;
+            }
+            if let Some(label) = controller.widget().and_downcast::<Label>() {
+                step(&label, dy > 0.0);
+            }
+            gtk::glib::Propagation::Stop
+        });
+        label.add_controller(scroll);
+    }
+
+    let label_weak = label.downgrade();
+    gtk::glib::timeout_add_seconds_local(1, move || {
+        let Some(label) = label_weak.upgrade() else {
+            return ControlFlow::Break;
+        };
+
+        update(&label);
+
+        ControlFlow::Continue
+    });
+
+    label
+}
+
+/// Returns the display template and the time formats to cycle through
+/// (`formats`, or just `time-format`).
+fn resolve_clock_formats(
+    format: Option<String>,
+    time_format: Option<String>,
+    formats: Option<Vec<String>>,
+) -> Result<(String, Vec<String>), String> {
+    let template = format.unwrap_or_else(|| DEFAULT_CLOCK_TEMPLATE.to_string());
+    let time_formats = match (formats, time_format) {
+        (Some(_), Some(_)) => {
+            return Err(format!(
+                "{MODULE_TYPE} 'formats' replaces 'time-format'; set only one"
+            ))
+        }
+        (Some(formats), None) if formats.is_empty() => {
+            return Err(format!("{MODULE_TYPE} 'formats' must not be empty"))
+        }
+        (Some(formats), None) => formats,
+        (None, time_format) => {
+            vec![time_format.unwrap_or_else(|| DEFAULT_CLOCK_FMT.to_string())]
+        }
+    };
+    Ok((template, time_formats))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use serde_json::Map;
+
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("exec", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'clock'"));
+    }
+
+    #[test]
+    fn parse_config_supports_click_aliases() {
+        let click_module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "click": "foo"
+            }))
+            .expect("module config map should parse"),
+        );
+        let click_cfg = parse_config(&click_module).expect("click config should parse");
+        assert_eq!(click_cfg.click.as_deref(), Some("foo"));
+
+        let on_click_module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "on-click": "bar"
+            }))
+            .expect("module config map should parse"),
+        );
+        let on_click_cfg = parse_config(&on_click_module).expect("on-click config should parse");
+        assert_eq!(on_click_cfg.on_click.as_deref(), Some("bar"));
+    }
+
+    #[test]
+    fn parse_config_reads_locale_and_calendar() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "locale": "fa_IR.UTF-8",
+                "calendar": "persian"
+            }))
+            .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.locale.as_deref(), Some("fa_IR.UTF-8"));
+        assert_eq!(cfg.calendar, ClockCalendar::Persian);
+
+        let cfg = parse_config(&default_module_config()).expect("default config should parse");
+        assert_eq!(cfg.calendar, ClockCalendar::Gregorian);
+    }
+
+    #[test]
+    fn parse_config_reads_calendar_popover() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "calendar-popover": {
+                    "enabled": true,
+                    "open": "right_click",
+                    "ics": ["~/.calendars/personal", "holidays.ics"]
+                }
+            }))
+            .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert!(cfg.calendar_popover.enabled);
+        assert_eq!(
+            cfg.calendar_popover.open,
+            CalendarPopoverOpenMode::RightClick
+        );
+        assert_eq!(cfg.calendar_popover.ics.len(), 2);
+        assert_eq!(cfg.calendar_popover.empty_text, "No events");
+        assert_eq!(cfg.calendar_popover.all_day_text, "All day");
+        assert_eq!(cfg.calendar_popover.heading_format(false), None);
+        assert_eq!(
+            cfg.calendar_popover.heading_format(true).as_deref(),
+            Some("%A, %e %B %Y")
+        );
+    }
+
+    #[test]
+    fn parse_config_reads_calendar_popover_texts() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "calendar-popover": {
+                    "enabled": true,
+                    "date_format": "%d.%m.",
+                    "empty-text": "Keine Termine",
+                    "all_day_text": "Ganztägig"
+                }
+            }))
+            .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.calendar_popover.empty_text, "Keine Termine");
+        assert_eq!(cfg.calendar_popover.all_day_text, "Ganztägig");
+        assert_eq!(
+            cfg.calendar_popover.heading_format(false).as_deref(),
+            Some("%d.%m.")
+        );
+    }
+
+    #[test]
+    fn clock_time_renderer_applies_calendar_fields() {
+        let renderer = ClockTimeRenderer {
+            time_formats: vec!["%d %B %Y".to_string()],
+            selected: Cell::new(0),
+            calendar: ClockCalendar::Persian,
+            locale: None,
+        };
+        let now = chrono::TimeZone::with_ymd_and_hms(&Local, 2025, 3, 21, 12, 0, 0)
+            .single()
+            .expect("valid local time");
+        assert_eq!(renderer.render(&now), "01 Farvardin 1404");
+    }
+
+    #[test]
+    fn resolve_clock_formats_uses_explicit_fields() {
+        let (template, time_fmts) = resolve_clock_formats(
+            Some("<span style=\"italic\">{}</span>".to_string()),
+            Some("%H:%M".to_string()),
+            None,
+        )
+        .expect("formats should resolve");
+        assert_eq!(template, "<span style=\"italic\">{}</span>");
+        assert_eq!(time_fmts, vec!["%H:%M".to_string()]);
+
+        let (template, time_fmts) = resolve_clock_formats(Some("{}".to_string()), None, None)
+            .expect("formats should resolve");
+        assert_eq!(template, "{}");
+        assert_eq!(time_fmts, vec![DEFAULT_CLOCK_FMT.to_string()]);
+    }
+
+    #[test]
+    fn resolve_clock_formats_uses_format_list() {
+        let formats = vec!["%H:%M".to_string(), "%Y-%m-%d".to_string()];
+        let (_, time_fmts) = resolve_clock_formats(None, None, Some(formats.clone()))
+            .expect("formats should resolve");
+        assert_eq!(time_fmts, formats);
+
+        assert!(resolve_clock_formats(None, None, Some(Vec::new())).is_err());
+        assert!(resolve_clock_formats(None, Some("%H".to_string()), Some(formats)).is_err());
+    }
+
+    #[test]
+    fn cycle_index_wraps_both_directions() {
+        assert_eq!(cycle_index(0, 3, true), 1);
+        assert_eq!(cycle_index(2, 3, true), 0);
+        assert_eq!(cycle_index(0, 3, false), 2);
+        assert_eq!(cycle_index(1, 3, false), 0);
+        assert_eq!(cycle_index(0, 0, true), 0);
+    }
+
+    #[test]
+    fn remembered_format_index_is_per_instance_and_bounded() {
+        let formats = vec!["%H:%M".to_string(), "%F".to_string()];
+        let left = format_instance_key(Some("DP-1"), &formats);
+        let right = format_instance_key(Some("HDMI-A-1"), &formats);
+        remember_format_index(&left, 1);
+        assert_eq!(remembered_format_index(&left, formats.len()), 1);
+        assert_eq!(remembered_format_index(&right, formats.len()), 0);
+        assert_eq!(remembered_format_index(&left, 1), 0);
+    }
+}
@@ -0,0 +1,292 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use chrono::{Datelike, Local, NaiveDate, TimeZone};
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Calendar, GestureClick, Label, Orientation, Popover, PositionType};
+use serde::Deserialize;
+
+use crate::modules::broadcaster::attach_subscription;
+use crate::modules::ics::{subscribe_calendar_events, CalendarEvents};
+use crate::modules::{popovers, touch};
+
+use super::{ClockTimeRenderer, MODULE_TYPE};
+
+const DEFAULT_DATE_FORMAT: &str = "%A, %e %B %Y";
+const DEFAULT_EMPTY_TEXT: &str = "No events";
+const DEFAULT_ALL_DAY_TEXT: &str = "All day";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct CalendarPopoverConfig {
+    #[serde(default)]
+    pub(crate) enabled: bool,
+    #[serde(default)]
+    pub(crate) open: CalendarPopoverOpenMode,
+    /// `.ics` files, or directories whose `.ics` files are read.
+    #[serde(default)]
+    pub(crate) ics: Vec<String>,
+    /// Heading with the selected day; shown when set, or when the clock has
+    /// a `locale` or non-Gregorian `calendar` the GTK calendar cannot show.
+    #[serde(rename = "date-format", alias = "date_format", default)]
+    pub(crate) date_format: Option<String>,
+    #[serde(
+        rename = "empty-text",
+        alias = "empty_text",
+        default = "default_empty_text"
+    )]
+    pub(crate) empty_text: String,
+    #[serde(
+        rename = "all-day-text",
+        alias = "all_day_text",
+        default = "default_all_day_text"
+    )]
+    pub(crate) all_day_text: String,
+    /// Config file that relative `ics` paths resolve against; set by the
+    /// clock factory.
+    #[serde(skip)]
+    pub(crate) config_source: Option<PathBuf>,
+}
+
+impl Default for CalendarPopoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            open: CalendarPopoverOpenMode::default(),
+            ics: Vec::new(),
+            date_format: None,
+            empty_text: default_empty_text(),
+            all_day_text: default_all_day_text(),
+            config_source: None,
+        }
+    }
+}
+
+fn default_empty_text() -> String {
+    DEFAULT_EMPTY_TEXT.to_string()
+}
+
+fn default_all_day_text() -> String {
+    DEFAULT_ALL_DAY_TEXT.to_string()
+}
+
+impl CalendarPopoverConfig {
+    /// The heading format, if the popover shows one.
+    pub(super) fn heading_format(&self, localized: bool) -> Option<String> {
+        self.date_format
+            .clone()
+            .or_else(|| localized.then(|| DEFAULT_DATE_FORMAT.to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum CalendarPopoverOpenMode {
+    #[serde(alias = "left_click")]
+    #[default]
+    LeftClick,
+    #[serde(alias = "right_click")]
+    RightClick,
+}
+
+/// `heading` renders the selected day with the clock's `locale` and
+/// `calendar`, which the GTK calendar grid itself does not follow.
+pub(super) fn install_calendar_popover(
+    label: &Label,
+    config: &CalendarPopoverConfig,
+    heading: Option<ClockTimeRenderer>,
+) {
+    label.add_css_class("clickable");
+    label.add_css_class("clock-calendar-enabled");
+
+    let popover = Popover::new();
+    popover.add_css_class("clock-calendar-popover");
+    popover.set_autohide(true);
+    popover.set_has_arrow(true);
+    popover.set_position(PositionType::Top);
+    popover.set_parent(label);
+    popovers::track(&popover, MODULE_TYPE);
+
+    let content = GtkBox::new(Orientation::Vertical, 6);
+    content.add_css_class("clock-calendar-content");
+    let calendar = Calendar::new();
+    calendar.add_css_class("clock-calendar");
+    content.append(&calendar);
+    popover.set_child(Some(&content));
+
+    if let Some(renderer) = heading {
+        let date_label = Label::new(None);
+        date_label.add_css_class("clock-calendar-date");
+        date_label.set_xalign(0.0);
+        content.prepend(&date_label);
+        let renderer = Rc::new(renderer);
+        let update = move |calendar: &Calendar| {
+            if let Some(text) = selected_date(calendar).and_then(|day| render_day(&renderer, day)) {
+                date_label.set_text(&text);
+            }
+        };
+        update(&calendar);
+        connect_selection_changed(&calendar, update);
+    }
+
+    // Reopening always starts on today, not on the last browsed month.
+    popover.connect_show({
+        let calendar = calendar.clone();
+        move |_| {
+            if let Ok(now) = glib::DateTime::now_local() {
+                calendar.select_day(&now);
+            }
+        }
+    });
+
+    install_open_gesture(label, &popover, config.open);
+
+    if config.ics.is_empty() {
+        return;
+    }
+
+    let events_box = GtkBox::new(Orientation::Vertical, 2);
+    events_box.add_css_class("clock-calendar-events");
+    content.append(&events_box);
+
+    let latest = Rc::new(RefCell::new(Arc::new(CalendarEvents::default())));
+    let labels = Rc::new(EventLabels {
+        empty: config.empty_text.clone(),
+        all_day: config.all_day_text.clone(),
+    });
+    let refresh = {
+        let latest = Rc::clone(&latest);
+        let events_box = events_box.clone();
+        move |calendar: &Calendar| {
+            refresh_events(calendar, &events_box, &latest.borrow(), &labels);
+        }
+    };
+    connect_selection_changed(&calendar, refresh.clone());
+
+    attach_subscription(
+        &calendar,
+        subscribe_calendar_events(&config.ics, config.config_source.as_deref()),
+        move |calendar, events| {
+            *latest.borrow_mut() = events;
+            refresh(calendar);
+        },
+    );
+}
+
+/// Runs `handler` whenever the selected date changes.
+fn connect_selection_changed(calendar: &Calendar, handler: impl Fn(&Calendar) + Clone + 'static) {
+    calendar.connect_day_selected({
+        let handler = handler.clone();
+        move |calendar| handler(calendar)
+    });
+    // Month and year navigation moves the selected date as well.
+    calendar.connect_next_month({
+        let handler = handler.clone();
+        move |calendar| handler(calendar)
+    });
+    calendar.connect_prev_month({
+        let handler = handler.clone();
+        move |calendar| handler(calendar)
+    });
+    calendar.connect_next_year({
+        let handler = handler.clone();
+        move |calendar| handler(calendar)
+    });
+    calendar.connect_prev_year(move |calendar| handler(calendar));
+}
+
+fn install_open_gesture(label: &Label, popover: &Popover, open_mode: CalendarPopoverOpenMode) {
+    let button = match open_mode {
+        CalendarPopoverOpenMode::LeftClick => 1,
+        CalendarPopoverOpenMode::RightClick => 3,
+    };
+    let toggle_popover = {
+        let popover = popover.clone();
+        move || {
+            if popover.is_visible() {
+                popover.popdown();
+            } else {
+                popover.popup();
+            }
+        }
+    };
+    if button == 3 {
+        let toggle_popover = toggle_popover.clone();
+        touch::attach_long_press(label, move |_, _| toggle_popover());
+    }
+    let click = GestureClick::builder().button(button).build();
+    click.connect_pressed(move |_, _, _, _| toggle_popover());
+    label.add_controller(click);
+}
+
+/// Texts of the event list.
+struct EventLabels {
+    empty: String,
+    all_day: String,
+}
+
+fn selected_date(calendar: &Calendar) -> Option<NaiveDate> {
+    let date = calendar.date();
+    NaiveDate::from_ymd_opt(date.year(), date.month() as u32, date.day_of_month() as u32)
+}
+
+/// Renders `day` at noon, clear of DST gaps at midnight.
+fn render_day(renderer: &ClockTimeRenderer, day: NaiveDate) -> Option<String> {
+    let noon = Local
+        .from_local_datetime(&day.and_hms_opt(12, 0, 0)?)
+        .earliest()?;
+    Some(renderer.render(&noon))
+}
+
+/// Marks the days of the shown month that have events and lists the
+/// selected day's events.
+fn refresh_events(
+    calendar: &Calendar,
+    events_box: &GtkBox,
+    events: &CalendarEvents,
+    labels: &EventLabels,
+) {
+    let Some(selected) = selected_date(calendar) else {
+        return;
+    };
+    let month_start = selected.with_day(1).unwrap_or(selected);
+    let month_end = month_start
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .unwrap_or(selected);
+
+    calendar.clear_marks();
+    for day in events.days_with_events(month_start, month_end) {
+        calendar.mark_day(day.day());
+    }
+
+    while let Some(child) = events_box.first_child() {
+        events_box.remove(&child);
+    }
+    let day_events = events.events_on(selected);
+    if day_events.is_empty() {
+        let empty = Label::new(Some(&labels.empty));
+        empty.add_css_class("clock-calendar-empty");
+        empty.set_xalign(0.0);
+        events_box.append(&empty);
+        return;
+    }
+    for event in day_events {
+        let time = if event.all_day {
+            labels.all_day.clone()
+        } else {
+            event.start_time().format("%H:%M").to_string()
+        };
+        let row = Label::new(Some(&format!("{time}  {}", event.summary)));
+        row.add_css_class("clock-calendar-event");
+        if event.all_day {
+            row.add_css_class("all-day");
+        }
+        row.set_xalign(0.0);
+        row.set_wrap(true);
+        row.set_max_width_chars(32);
+        events_box.append(&row);
+    }
+}
@@ -0,0 +1,441 @@
+use std::cell::{Cell, RefCell};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, GestureClick, Label, Orientation, Popover, PositionType, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::{attach_subscription, Broadcaster};
+use crate::modules::icons::Glyph;
+use crate::modules::{
+    output_cache, popovers, render_markup_template, touch, ModuleBuildContext, ModuleConfig,
+    ModuleFactory, ModuleLabel,
+};
+
+const DEFAULT_COMMAND: &str = "hyprpicker";
+const DEFAULT_COPY_COMMAND: &str = "wl-copy";
+const DEFAULT_FORMAT: &str = "{swatch} {hex}";
+const DEFAULT_FORMAT_EMPTY: Glyph = Glyph::new("\u{f1fb}", "🎨");
+const DEFAULT_HISTORY_SIZE: usize = 10;
+const HISTORY_CACHE_KEY: &str = "history";
+pub(crate) const MODULE_TYPE: &str = "colorpicker";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct ColorPickerConfig {
+    /// Picker command; the first hex color it prints is used.
+    #[serde(default = "default_command")]
+    pub(crate) command: String,
+    /// Receives the picked color on stdin; empty disables copying.
+    #[serde(
+        rename = "copy-command",
+        alias = "copy_command",
+        default = "default_copy_command"
+    )]
+    pub(crate) copy_command: String,
+    #[serde(default)]
+    pub(crate) format: Option<String>,
+    #[serde(rename = "format-empty", alias = "format_empty", default)]
+    pub(crate) format_empty: Option<String>,
+    #[serde(
+        rename = "history-size",
+        alias = "history_size",
+        default = "default_history_size"
+    )]
+    pub(crate) history_size: usize,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+pub(crate) struct ColorPickerFactory;
+
+pub(crate) const FACTORY: ColorPickerFactory = ColorPickerFactory;
+
+impl ModuleFactory for ColorPickerFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_colorpicker_module(parsed).upcast())
+    }
+}
+
+fn default_command() -> String {
+    DEFAULT_COMMAND.to_string()
+}
+
+fn default_copy_command() -> String {
+    DEFAULT_COPY_COMMAND.to_string()
+}
+
+fn default_history_size() -> usize {
+    DEFAULT_HISTORY_SIZE
+}
+
+pub(crate) fn parse_config(module: &ModuleConfig) -> Result<ColorPickerConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+/// Label, history, and popover of one module; the newest color comes first.
+struct ColorPickerUi {
+    label: Label,
+    popover: Popover,
+    content: GtkBox,
+    history: RefCell<Vec<String>>,
+    history_size: usize,
+    format: String,
+    format_empty: String,
+    copy_command: String,
+    history_path: Option<PathBuf>,
+}
+
+fn build_colorpicker_module(config: ColorPickerConfig) -> Label {
+    let label = ModuleLabel::new(MODULE_TYPE)
+        .with_css_classes(config.class.as_deref())
+        .into_label();
+    label.add_css_class("clickable");
+
+    let popover = Popover::new();
+    popover.add_css_class("colorpicker-popover");
+    popover.set_autohide(true);
+    popover.set_has_arrow(true);
+    popover.set_position(PositionType::Top);
+    popover.set_parent(&label);
+    popovers::track(&popover, MODULE_TYPE);
+    let content = GtkBox::new(Orientation::Vertical, 4);
+    content.add_css_class("colorpicker-content");
+    popover.set_child(Some(&content));
+
+    let history_path = output_cache::cache_path(MODULE_TYPE, &HISTORY_CACHE_KEY);
+    let mut history = history_path
+        .as_deref()
+        .and_then(output_cache::load::<Vec<String>>)
+        .unwrap_or_default();
+    history.retain(|hex| parse_picked_color(hex).as_deref() == Some(hex.as_str()));
+    history.truncate(config.history_size);
+
+    let ui = Rc::new(ColorPickerUi {
+        label: label.clone(),
+        popover: popover.clone(),
+        content,
+        history: RefCell::new(history),
+        history_size: config.history_size,
+        format: config.format.unwrap_or_else(|| DEFAULT_FORMAT.to_string()),
+        format_empty: config
+            .format_empty
+            .unwrap_or_else(|| DEFAULT_FORMAT_EMPTY.get().to_string()),
+        copy_command: config.copy_command,
+        history_path,
+    });
+    ui.render_label();
+
+    let results = Arc::new(Broadcaster::<Option<String>>::new());
+    let picking = Rc::new(Cell::new(false));
+    // The subscription lives as long as the label and holds the only strong
+    // reference to the module state; the gestures hold weak ones.
+    attach_subscription(&label, results.subscribe(), {
+        let ui = Rc::clone(&ui);
+        let picking = Rc::clone(&picking);
+        move |label, picked| {
+            picking.set(false);
+            label.remove_css_class("picking");
+            if let Some(hex) = picked {
+                ui.select(hex);
+            }
+        }
+    });
+
+    let primary = GestureClick::builder().button(1).build();
+    primary.connect_released({
+        let label = label.downgrade();
+        let command = config.command;
+        let copy_command = ui.copy_command.clone();
+        move |_, _, _, _| {
+            if picking.replace(true) {
+                return;
+            }
+            if let Some(label) = label.upgrade() {
+                label.add_css_class("picking");
+            }
+            start_pick(command.clone(), copy_command.clone(), Arc::clone(&results));
+        }
+    });
+    label.add_controller(primary);
+
+    let toggle_popover = {
+        let ui = Rc::downgrade(&ui);
+        move || {
+            let Some(ui) = ui.upgrade() else {
+                return;
+            };
+            if ui.popover.is_visible() {
+                ui.popover.popdown();
+            } else {
+                ui.refresh_popover();
+                ui.popover.popup();
+            }
+        }
+    };
+    {
+        let toggle_popover = toggle_popover.clone();
+        touch::attach_long_press(&label, move |_, _| toggle_popover());
+    }
+    let secondary = GestureClick::builder().button(3).build();
+    secondary.connect_pressed(move |_, _, _, _| toggle_popover());
+    label.add_controller(secondary);
+
+    label
+}
+
+impl ColorPickerUi {
+    fn render_label(&self) {
+        let history = self.history.borrow();
+        let text = match history.first() {
+            Some(hex) => render_format(&self.format, hex),
+            None => self.format_empty.clone(),
+        };
+        self.label.set_visible(!text.trim().is_empty());
+        self.label.set_markup(&text);
+        if history.is_empty() {
+            self.label.add_css_class("colorpicker-empty");
+        } else {
+            self.label.remove_css_class("colorpicker-empty");
+        }
+    }
+
+    /// Makes `hex` the current color and saves the history.
+    fn select(self: &Rc<Self>, hex: String) {
+        push_history(&mut self.history.borrow_mut(), hex, self.history_size);
+        if let Some(path) = &self.history_path {
+            if let Err(err) = output_cache::store(path, &*self.history.borrow()) {
+                eprintln!("vibar/colorpicker: failed to save history: {err}");
+            }
+        }
+        self.render_label();
+        if self.popover.is_visible() {
+            self.refresh_popover();
+        }
+    }
+
+    fn clear(self: &Rc<Self>) {
+        self.history.borrow_mut().clear();
+        if let Some(path) = &self.history_path {
+            let _ = std::fs::remove_file(path);
+        }
+        self.render_label();
+        self.refresh_popover();
+    }
+
+    fn refresh_popover(self: &Rc<Self>) {
+        let content = &self.content;
+        while let Some(child) = content.first_child() {
+            content.remove(&child);
+        }
+
+        let header = GtkBox::new(Orientation::Horizontal, 6);
+        header.add_css_class("colorpicker-header");
+        let title = Label::new(Some("Picked colors"));
+        title.add_css_class("colorpicker-title");
+        title.set_xalign(0.0);
+        title.set_hexpand(true);
+        header.append(&title);
+        let clear = Button::with_label("Clear");
+        clear.add_css_class("colorpicker-control-button");
+        clear.set_sensitive(!self.history.borrow().is_empty());
+        clear.connect_clicked({
+            let ui = Rc::downgrade(self);
+            move |_| {
+                if let Some(ui) = ui.upgrade() {
+                    ui.clear();
+                }
+            }
+        });
+        header.append(&clear);
+        content.append(&header);
+
+        let history = self.history.borrow().clone();
+        if history.is_empty() {
+            let empty = Label::new(Some("No colors picked"));
+            empty.add_css_class("colorpicker-empty-label");
+            content.append(&empty);
+            return;
+        }
+        for hex in history {
+            content.append(&self.build_entry(hex));
+        }
+    }
+
+    /// A history row; clicking copies the color again and makes it current.
+    fn build_entry(self: &Rc<Self>, hex: String) -> Button {
+        let text = Label::new(None);
+        text.set_markup(&render_format(DEFAULT_FORMAT, &hex));
+        text.set_xalign(0.0);
+        let entry = Button::new();
+        entry.set_child(Some(&text));
+        entry.add_css_class("colorpicker-entry");
+        entry.set_focusable(false);
+        entry.connect_clicked({
+            let ui = Rc::downgrade(self);
+            move |_| {
+                let Some(ui) = ui.upgrade() else {
+                    return;
+                };
+                let copy_command = ui.copy_command.clone();
+                let copied = hex.clone();
+                std::thread::spawn(move || copy_to_clipboard(&copy_command, &copied));
+                ui.select(hex.clone());
+                ui.popover.popdown();
+            }
+        });
+        entry
+    }
+}
+
+/// Runs the picker off the GTK thread and copies its color before
+/// reporting it; `None` when the pick was cancelled or failed.
+fn start_pick(command: String, copy_command: String, results: Arc<Broadcaster<Option<String>>>) {
+    std::thread::spawn(move || {
+        let picked = match Command::new("sh")
+            .arg("-c")
+            .arg(&command)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+        {
+            Ok(output) => parse_picked_color(&String::from_utf8_lossy(&output.stdout)),
+            Err(err) => {
+                eprintln!("vibar/colorpicker: failed to run '{command}': {err}");
+                None
+            }
+        };
+        if let Some(hex) = &picked {
+            copy_to_clipboard(&copy_command, hex);
+        }
+        results.broadcast(picked);
+    });
+}
+
+fn copy_to_clipboard(copy_command: &str, hex: &str) {
+    if copy_command.trim().is_empty() {
+        return;
+    }
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(copy_command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match child {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(hex.as_bytes());
+            }
+            let _ = child.wait();
+        }
+        Err(err) => eprintln!("vibar/colorpicker: failed to run '{copy_command}': {err}"),
+    }
+}
+
+/// First `#rrggbb` in the picker output (a bare `rrggbb` word also counts),
+/// lowercased.
+fn parse_picked_color(output: &str) -> Option<String> {
+    let is_hex6 = |value: &str| value.len() == 6 && value.chars().all(|c| c.is_ascii_hexdigit());
+    let prefixed = output.match_indices('#').find_map(|(index, _)| {
+        let digits = output.get(index + 1..index + 7)?;
+        let next = output[index + 7..].chars().next();
+        (is_hex6(digits) && !next.is_some_and(|c| c.is_ascii_hexdigit())).then_some(digits)
+    });
+    let digits = prefixed.or_else(|| output.split_whitespace().find(|word| is_hex6(word)))?;
+    Some(format!("#{}", digits.to_ascii_lowercase()))
+}
+
+fn push_history(history: &mut Vec<String>, hex: String, history_size: usize) {
+    history.retain(|entry| *entry != hex);
+    history.insert(0, hex);
+    history.truncate(history_size.max(1));
+}
+
+/// `{swatch}` is inserted after escaping, since it is markup itself; `hex`
+/// has already been validated.
+fn render_format(format: &str, hex: &str) -> String {
+    render_markup_template(format, &[("{hex}", hex)]).replace(
+        "{swatch}",
+        &format!("<span foreground=\"{hex}\">\u{25a0}</span>"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map};
+
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'colorpicker'"));
+    }
+
+    #[test]
+    fn parse_config_applies_defaults_and_aliases() {
+        let parsed =
+            parse_config(&ModuleConfig::new(MODULE_TYPE, Map::new())).expect("config should parse");
+        assert_eq!(parsed.command, DEFAULT_COMMAND);
+        assert_eq!(parsed.copy_command, DEFAULT_COPY_COMMAND);
+        assert_eq!(parsed.history_size, DEFAULT_HISTORY_SIZE);
+
+        let Value::Object(map) = json!({ "copy_command": "", "history_size": 3 }) else {
+            panic!("config should be an object");
+        };
+        let parsed =
+            parse_config(&ModuleConfig::new(MODULE_TYPE, map)).expect("config should parse");
+        assert_eq!(parsed.copy_command, "");
+        assert_eq!(parsed.history_size, 3);
+    }
+
+    #[test]
+    fn parse_picked_color_finds_hex() {
+        assert_eq!(parse_picked_color("#A1B2C3\n"), Some("#a1b2c3".to_string()));
+        assert_eq!(
+            parse_picked_color("picked: rgb(1, 2, 3) #00ff7f"),
+            Some("#00ff7f".to_string())
+        );
+        assert_eq!(parse_picked_color("ff0000"), Some("#ff0000".to_string()));
+        assert_eq!(parse_picked_color("#ff00007f"), None);
+        assert_eq!(parse_picked_color(""), None);
+        assert_eq!(parse_picked_color("#12345"), None);
+    }
+
+    #[test]
+    fn push_history_moves_repeats_to_front_and_caps() {
+        let mut history = vec!["#000000".to_string(), "#ffffff".to_string()];
+        push_history(&mut history, "#ffffff".to_string(), 2);
+        assert_eq!(history, ["#ffffff", "#000000"]);
+        push_history(&mut history, "#ff0000".to_string(), 2);
+        assert_eq!(history, ["#ff0000", "#ffffff"]);
+    }
+
+    #[test]
+    fn render_format_inserts_swatch_markup() {
+        assert_eq!(
+            render_format("{swatch} {hex}", "#ff0000"),
+            "<span foreground=\"#ff0000\">\u{25a0}</span> #ff0000"
+        );
+    }
+}
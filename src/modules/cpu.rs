@@ -1,6 +1,6 @@
 use std::fs;
 use std::sync::{Arc, OnceLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use gtk::prelude::*;
 use gtk::{Label, Widget};
@@ -10,15 +10,20 @@ use serde_json::Value;
 use crate::modules::broadcaster::{
     attach_subscription, BackendRegistry, Broadcaster, Subscription,
 };
+use crate::modules::text::{attach_text_subscription, visible_block, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
 use crate::modules::{
-    escape_markup_text, render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel,
+    escape_markup_text, reaches_threshold, render_markup_template, state_store, ModuleBuildContext,
+    ModuleConfig, ModuleLabel,
 };
 
 use super::ModuleFactory;
 
 const MIN_CPU_INTERVAL_SECS: u32 = 1;
 const DEFAULT_CPU_INTERVAL_SECS: u32 = 5;
+const MIN_CPU_SAMPLE_INTERVAL_MS: u64 = 100;
 const DEFAULT_CPU_FORMAT: &str = "{used_percentage}%";
+const CPU_HIGH_CLASS: &str = "cpu-high";
 const CPU_USAGE_CLASSES: [&str; 5] = [
     "usage-low",
     "usage-medium",
@@ -38,26 +43,88 @@ pub(crate) struct CpuConfig {
     pub(crate) on_click: Option<String>,
     #[serde(default = "default_cpu_interval")]
     pub(crate) interval_secs: u32,
+    /// How often `/proc/stat` is read; defaults to `interval_secs`.
+    #[serde(rename = "sample-interval-ms", alias = "sample_interval_ms", default)]
+    pub(crate) sample_interval_ms: Option<u64>,
+    /// Time constant of the moving average over samples; `0` shows raw
+    /// per-sample usage.
+    #[serde(rename = "smoothing-ms", alias = "smoothing_ms", default)]
+    pub(crate) smoothing_ms: u64,
+    /// Usage percentage from which the label carries `.cpu-high`.
+    #[serde(rename = "high-threshold", alias = "high_threshold", default)]
+    pub(crate) high_threshold: Option<u8>,
     #[serde(default)]
     pub(crate) class: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy)]
-struct CpuSnapshot {
+pub(super) struct CpuSnapshot {
     idle: u64,
     total: u64,
 }
 
+/// Aggregate and per-core counters from one `/proc/stat` read.
+#[derive(Debug, Clone)]
+struct CpuStat {
+    total: CpuSnapshot,
+    cores: Vec<CpuSnapshot>,
+}
+
+/// Values behind the format placeholders.
+#[derive(Debug, Clone, Default)]
+struct CpuValues {
+    used_percentage: f64,
+    core_percentages: Vec<f64>,
+    /// 1, 5, and 15 minute load averages.
+    load: Option<[f64; 3]>,
+    max_frequency_ghz: Option<f64>,
+}
+
 #[derive(Debug, Clone)]
 struct CpuUpdate {
     text: String,
+    tooltip: String,
     usage_class: &'static str,
+    used_percentage: Option<u16>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct CpuSharedKey {
     format: String,
+    tooltip_format: Option<String>,
     interval_secs: u32,
+    sample_interval_ms: u64,
+    smoothing_ms: u64,
+}
+
+/// Exponential moving average weighted by the time between samples, so the
+/// smoothing does not depend on the sampling rate.
+#[derive(Debug)]
+struct UsageSmoother {
+    time_constant: Duration,
+    value: Option<f64>,
+}
+
+impl UsageSmoother {
+    fn new(time_constant: Duration) -> Self {
+        Self {
+            time_constant,
+            value: None,
+        }
+    }
+
+    fn push(&mut self, sample: f64, elapsed: Duration) -> f64 {
+        let value = match self.value {
+            Some(previous) if !self.time_constant.is_zero() => {
+                let weight =
+                    1.0 - (-elapsed.as_secs_f64() / self.time_constant.as_secs_f64()).exp();
+                previous + weight * (sample - previous)
+            }
+            _ => sample,
+        };
+        self.value = Some(value);
+        value
+    }
 }
 
 pub(crate) struct CpuFactory;
@@ -69,14 +136,41 @@ impl ModuleFactory for CpuFactory {
         MODULE_TYPE
     }
 
-    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_cpu_module(
+            parsed,
+            context.tooltip_format.clone(),
+            context.published_namespace(MODULE_TYPE),
+        )
+        .upcast())
+    }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
         let parsed = parse_config(config)?;
         let format = parsed
             .format
             .unwrap_or_else(|| DEFAULT_CPU_FORMAT.to_string());
-        let click_command = parsed.click.or(parsed.on_click);
-
-        Ok(build_cpu_module(format, click_command, parsed.interval_secs, parsed.class).upcast())
+        let interval_secs = normalized_cpu_interval(parsed.interval_secs);
+        let subscription = subscribe_shared_cpu(
+            format,
+            None,
+            interval_secs,
+            normalized_sample_interval_ms(parsed.sample_interval_ms, interval_secs),
+            parsed.smoothing_ms,
+        );
+        attach_text_subscription(subscription, sink, |update| {
+            if let Some(used_percentage) = update.used_percentage {
+                publish_usage(MODULE_TYPE, used_percentage);
+            }
+            visible_block(&update.text)
+                .map(|block| block.urgent(update.usage_class == "usage-critical"))
+        });
+        Ok(())
     }
 }
 
@@ -100,16 +194,33 @@ pub(crate) fn normalized_cpu_interval(interval_secs: u32) -> u32 {
     interval_secs.max(MIN_CPU_INTERVAL_SECS)
 }
 
+/// Sampling never runs slower than the display interval.
+fn normalized_sample_interval_ms(sample_interval_ms: Option<u64>, interval_secs: u32) -> u64 {
+    let display_interval_ms = u64::from(interval_secs) * 1000;
+    sample_interval_ms
+        .unwrap_or(display_interval_ms)
+        .clamp(MIN_CPU_SAMPLE_INTERVAL_MS, display_interval_ms)
+}
+
 fn cpu_registry() -> &'static BackendRegistry<CpuSharedKey, Broadcaster<CpuUpdate>> {
     static REGISTRY: OnceLock<BackendRegistry<CpuSharedKey, Broadcaster<CpuUpdate>>> =
         OnceLock::new();
     REGISTRY.get_or_init(BackendRegistry::new)
 }
 
-fn subscribe_shared_cpu(format: String, interval_secs: u32) -> Subscription<CpuUpdate> {
+fn subscribe_shared_cpu(
+    format: String,
+    tooltip_format: Option<String>,
+    interval_secs: u32,
+    sample_interval_ms: u64,
+    smoothing_ms: u64,
+) -> Subscription<CpuUpdate> {
     let key = CpuSharedKey {
-        format: format.clone(),
+        format,
+        tooltip_format,
         interval_secs,
+        sample_interval_ms,
+        smoothing_ms,
     };
 
     let (broadcaster, start_worker) = cpu_registry().get_or_create(key.clone(), Broadcaster::new);
@@ -125,27 +236,60 @@ fn subscribe_shared_cpu(format: String, interval_secs: u32) -> Subscription<CpuU
 
 fn start_cpu_worker(key: CpuSharedKey, broadcaster: Arc<Broadcaster<CpuUpdate>>) {
     let interval = Duration::from_secs(u64::from(key.interval_secs));
+    let sample_interval = Duration::from_millis(key.sample_interval_ms);
+    let smoothing = Duration::from_millis(key.smoothing_ms);
     std::thread::spawn(move || {
-        let mut previous: Option<CpuSnapshot> = None;
+        let mut previous: Option<(CpuStat, Instant)> = None;
+        let mut smoother = UsageSmoother::new(smoothing);
+        let mut core_smoothers: Vec<UsageSmoother> = Vec::new();
+        let mut next_display = Instant::now();
 
         loop {
-            let update = match read_cpu_snapshot() {
+            let now = Instant::now();
+            let update = match read_cpu_stat() {
                 Ok(current) => {
-                    let Some(prev) = previous else {
-                        previous = Some(current);
+                    let Some((prev, sampled_at)) = previous.replace((current.clone(), now)) else {
                         std::thread::sleep(Duration::from_millis(100));
                         continue;
                     };
-                    let usage = cpu_usage_between(prev, current);
-                    previous = Some(current);
+                    let elapsed = now - sampled_at;
+                    let usage =
+                        smoother.push(cpu_usage_between(prev.total, current.total), elapsed);
+                    core_smoothers
+                        .resize_with(current.cores.len(), || UsageSmoother::new(smoothing));
+                    let core_percentages = core_smoothers
+                        .iter_mut()
+                        .zip(prev.cores.iter().zip(&current.cores))
+                        .map(|(smoother, (prev, current))| {
+                            smoother.push(cpu_usage_between(*prev, *current), elapsed)
+                        })
+                        .collect();
+                    if now < next_display {
+                        std::thread::sleep(sample_interval);
+                        continue;
+                    }
+                    let values = CpuValues {
+                        used_percentage: usage,
+                        core_percentages,
+                        load: read_load_average().ok(),
+                        max_frequency_ghz: read_max_frequency_ghz(),
+                    };
                     CpuUpdate {
-                        text: render_format(&key.format, usage),
+                        text: render_format(&key.format, &values),
+                        tooltip: key
+                            .tooltip_format
+                            .as_deref()
+                            .map(|format| render_format(format, &values))
+                            .unwrap_or_default(),
                         usage_class: usage_css_class(usage),
+                        used_percentage: Some(percentage(usage)),
                     }
                 }
                 Err(err) => CpuUpdate {
                     text: escape_markup_text(&format!("cpu error: {err}")),
+                    tooltip: String::new(),
                     usage_class: "usage-unknown",
+                    used_percentage: None,
                 },
             };
 
@@ -154,21 +298,26 @@ fn start_cpu_worker(key: CpuSharedKey, broadcaster: Arc<Broadcaster<CpuUpdate>>)
                 cpu_registry().remove(&key, &broadcaster);
                 return;
             }
-            std::thread::sleep(interval);
+            next_display = now + interval;
+            std::thread::sleep(sample_interval);
         }
     });
 }
 
 pub(crate) fn build_cpu_module(
-    format: String,
-    click_command: Option<String>,
-    interval_secs: u32,
-    class: Option<String>,
+    config: CpuConfig,
+    tooltip_format: Option<String>,
+    namespace: String,
 ) -> Label {
     let label = ModuleLabel::new("cpu")
-        .with_css_classes(class.as_deref())
-        .with_click_command(click_command)
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(config.click.or(config.on_click))
         .into_label();
+    let format = config
+        .format
+        .unwrap_or_else(|| DEFAULT_CPU_FORMAT.to_string());
+    let interval_secs = config.interval_secs;
+    let high_threshold = config.high_threshold;
 
     let effective_interval_secs = normalized_cpu_interval(interval_secs);
     if effective_interval_secs != interval_secs {
@@ -178,35 +327,80 @@ pub(crate) fn build_cpu_module(
         );
     }
 
-    let subscription = subscribe_shared_cpu(format, effective_interval_secs);
-
-    attach_subscription(&label, subscription, |label, update| {
+    let tooltip = tooltip_format
+        .is_some()
+        .then(|| ModuleTooltip::attach(&label));
+    let subscription = subscribe_shared_cpu(
+        format,
+        tooltip_format,
+        effective_interval_secs,
+        normalized_sample_interval_ms(config.sample_interval_ms, effective_interval_secs),
+        config.smoothing_ms,
+    );
+
+    attach_subscription(&label, subscription, move |label, update| {
+        if let Some(used_percentage) = update.used_percentage {
+            publish_usage(&namespace, used_percentage);
+        }
         let visible = !update.text.trim().is_empty();
         label.set_visible(visible);
         if visible {
             label.set_markup(&update.text);
         }
+        if let Some(tooltip) = &tooltip {
+            tooltip.set_markup(&update.tooltip);
+        }
         for class_name in CPU_USAGE_CLASSES {
             label.remove_css_class(class_name);
         }
         label.add_css_class(update.usage_class);
+        if reaches_threshold(update.used_percentage, high_threshold) {
+            label.add_css_class(CPU_HIGH_CLASS);
+        } else {
+            label.remove_css_class(CPU_HIGH_CLASS);
+        }
     });
 
     label
 }
 
-fn read_cpu_snapshot() -> Result<CpuSnapshot, String> {
+pub(super) fn read_cpu_snapshot() -> Result<CpuSnapshot, String> {
     let stat = fs::read_to_string("/proc/stat")
         .map_err(|err| format!("failed to read /proc/stat: {err}"))?;
     parse_proc_stat_cpu_line(&stat)
 }
 
+fn read_cpu_stat() -> Result<CpuStat, String> {
+    let stat = fs::read_to_string("/proc/stat")
+        .map_err(|err| format!("failed to read /proc/stat: {err}"))?;
+    parse_proc_stat(&stat)
+}
+
 fn parse_proc_stat_cpu_line(stat: &str) -> Result<CpuSnapshot, String> {
     let line = stat
         .lines()
         .find(|line| line.starts_with("cpu "))
         .ok_or_else(|| "missing aggregate cpu line in /proc/stat".to_string())?;
+    parse_cpu_values(line)
+}
+
+/// The aggregate line plus every `cpuN` line, in `/proc/stat` order.
+fn parse_proc_stat(stat: &str) -> Result<CpuStat, String> {
+    let cores = stat
+        .lines()
+        .filter(|line| {
+            line.strip_prefix("cpu")
+                .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .map(parse_cpu_values)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(CpuStat {
+        total: parse_proc_stat_cpu_line(stat)?,
+        cores,
+    })
+}
 
+fn parse_cpu_values(line: &str) -> Result<CpuSnapshot, String> {
     let values: Vec<u64> = line
         .split_whitespace()
         .skip(1)
@@ -218,7 +412,7 @@ fn parse_proc_stat_cpu_line(stat: &str) -> Result<CpuSnapshot, String> {
         .collect::<Result<Vec<_>, _>>()?;
 
     if values.len() < 4 {
-        return Err("aggregate cpu line in /proc/stat has fewer than 4 columns".to_string());
+        return Err("cpu line in /proc/stat has fewer than 4 columns".to_string());
     }
 
     let idle = values[3] + values.get(4).copied().unwrap_or(0);
@@ -227,7 +421,49 @@ fn parse_proc_stat_cpu_line(stat: &str) -> Result<CpuSnapshot, String> {
     Ok(CpuSnapshot { idle, total })
 }
 
-fn cpu_usage_between(previous: CpuSnapshot, current: CpuSnapshot) -> f64 {
+fn read_load_average() -> Result<[f64; 3], String> {
+    let loadavg = fs::read_to_string("/proc/loadavg")
+        .map_err(|err| format!("failed to read /proc/loadavg: {err}"))?;
+    parse_load_average(&loadavg)
+}
+
+fn parse_load_average(loadavg: &str) -> Result<[f64; 3], String> {
+    let mut fields = loadavg.split_whitespace().map(str::parse::<f64>);
+    let mut load = [0.0; 3];
+    for value in &mut load {
+        *value = fields
+            .next()
+            .and_then(Result::ok)
+            .ok_or_else(|| format!("malformed /proc/loadavg: '{}'", loadavg.trim()))?;
+    }
+    Ok(load)
+}
+
+/// Highest current core frequency from cpufreq, in GHz; `None` without
+/// cpufreq.
+fn read_max_frequency_ghz() -> Option<f64> {
+    let entries = fs::read_dir("/sys/devices/system/cpu").ok()?;
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix("cpu"))
+                .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit()))
+        })
+        .filter_map(|entry| {
+            fs::read_to_string(entry.path().join("cpufreq/scaling_cur_freq"))
+                .ok()?
+                .trim()
+                .parse::<u64>()
+                .ok()
+        })
+        .max()
+        .map(|khz| khz as f64 / 1_000_000.0)
+}
+
+pub(super) fn cpu_usage_between(previous: CpuSnapshot, current: CpuSnapshot) -> f64 {
     let delta_total = current.total.saturating_sub(previous.total);
     if delta_total == 0 {
         return 0.0;
@@ -237,20 +473,59 @@ fn cpu_usage_between(previous: CpuSnapshot, current: CpuSnapshot) -> f64 {
     ((delta_total.saturating_sub(delta_idle)) as f64 / delta_total as f64) * 100.0
 }
 
-fn render_format(format: &str, used_percentage: f64) -> String {
-    let used_percentage = used_percentage.clamp(0.0, 100.0) as u16;
+fn percentage(usage: f64) -> u16 {
+    usage.clamp(0.0, 100.0) as u16
+}
+
+fn render_format(format: &str, values: &CpuValues) -> String {
+    let used_percentage = percentage(values.used_percentage);
     let idle_percentage = 100u16.saturating_sub(used_percentage);
+    let load = |index: usize| {
+        values
+            .load
+            .map(|load| format!("{:.2}", load[index]))
+            .unwrap_or_default()
+    };
 
-    render_markup_template(
-        format,
+    let mut replacements = vec![
+        ("{used_percentage}".to_string(), used_percentage.to_string()),
+        ("{idle_percentage}".to_string(), idle_percentage.to_string()),
+        ("{load1}".to_string(), load(0)),
+        ("{load5}".to_string(), load(1)),
+        ("{load15}".to_string(), load(2)),
+        (
+            "{max_frequency}".to_string(),
+            values
+                .max_frequency_ghz
+                .map(|ghz| format!("{ghz:.1}"))
+                .unwrap_or_default(),
+        ),
+    ];
+    for (index, usage) in values.core_percentages.iter().enumerate() {
+        replacements.push((format!("{{usage{index}}}"), percentage(*usage).to_string()));
+    }
+    let replacements = replacements
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect::<Vec<_>>();
+
+    render_markup_template(format, &replacements)
+}
+
+fn publish_usage(namespace: &str, used_percentage: u16) {
+    state_store::publish(
+        namespace,
         &[
-            ("{used_percentage}", &used_percentage.to_string()),
-            ("{idle_percentage}", &idle_percentage.to_string()),
+            ("used_percentage", used_percentage.to_string()),
+            (
+                "idle_percentage",
+                100u16.saturating_sub(used_percentage).to_string(),
+            ),
         ],
-    )
+    );
 }
 
-fn usage_css_class(used_percentage: f64) -> &'static str {
+pub(super) fn usage_css_class(used_percentage: f64) -> &'static str {
     if used_percentage < 30.0 {
         "usage-low"
     } else if used_percentage < 60.0 {
@@ -282,6 +557,33 @@ mod tests {
         assert_eq!(normalized_cpu_interval(10), 10);
     }
 
+    #[test]
+    fn normalized_sample_interval_stays_within_display_interval() {
+        assert_eq!(normalized_sample_interval_ms(None, 5), 5000);
+        assert_eq!(normalized_sample_interval_ms(Some(500), 5), 500);
+        assert_eq!(normalized_sample_interval_ms(Some(10), 5), 100);
+        assert_eq!(normalized_sample_interval_ms(Some(9000), 5), 5000);
+    }
+
+    #[test]
+    fn usage_smoother_weights_samples_by_elapsed_time() {
+        let mut raw = UsageSmoother::new(Duration::ZERO);
+        assert_eq!(raw.push(10.0, Duration::from_secs(1)), 10.0);
+        assert_eq!(raw.push(90.0, Duration::from_secs(1)), 90.0);
+
+        let mut smoothed = UsageSmoother::new(Duration::from_secs(1));
+        assert_eq!(smoothed.push(0.0, Duration::from_millis(250)), 0.0);
+        let once = smoothed.push(100.0, Duration::from_secs(1));
+        assert_eq!(format!("{once:.1}"), "63.2");
+        let mut stepped = UsageSmoother::new(Duration::from_secs(1));
+        stepped.push(0.0, Duration::from_millis(250));
+        let mut value = 0.0;
+        for _ in 0..4 {
+            value = stepped.push(100.0, Duration::from_millis(250));
+        }
+        assert_eq!(format!("{value:.1}"), "63.2");
+    }
+
     #[test]
     fn parse_proc_stat_cpu_line_parses_totals() {
         let stat = "cpu  100 20 30 400 50 0 0 0 0 0\ncpu0 1 2 3 4 5 6 7 8 9 10\n";
@@ -304,18 +606,60 @@ mod tests {
         assert_eq!(format!("{usage:.0}"), "60");
     }
 
+    fn usage(used_percentage: f64) -> CpuValues {
+        CpuValues {
+            used_percentage,
+            ..CpuValues::default()
+        }
+    }
+
     #[test]
     fn render_format_replaces_placeholders() {
-        let text = render_format("{used_percentage}% {idle_percentage}%", 62.4);
+        let text = render_format("{used_percentage}% {idle_percentage}%", &usage(62.4));
         assert_eq!(text, "62% 38%");
     }
 
     #[test]
     fn render_format_truncates_percentage() {
-        let text = render_format("{used_percentage}% {idle_percentage}%", 62.9);
+        let text = render_format("{used_percentage}% {idle_percentage}%", &usage(62.9));
         assert_eq!(text, "62% 38%");
     }
 
+    #[test]
+    fn render_format_replaces_core_load_and_frequency_placeholders() {
+        let mut values = usage(50.0);
+        values.core_percentages = (0..11).map(f64::from).collect();
+        values.load = Some([0.5, 1.25, 2.0]);
+        values.max_frequency_ghz = Some(3.456);
+        let text = render_format(
+            "{usage0} {usage1} {usage10} {load1} {load5} {load15} {max_frequency}GHz",
+            &values,
+        );
+        assert_eq!(text, "0 1 10 0.50 1.25 2.00 3.5GHz");
+
+        let text = render_format("{load1}|{max_frequency}", &usage(0.0));
+        assert_eq!(text, "|");
+    }
+
+    #[test]
+    fn parse_proc_stat_reads_cores() {
+        let stat = "cpu  100 20 30 400 50 0 0 0 0 0\ncpu0 1 2 3 4 5 6 7 8 9 10\ncpu1 10 0 0 90 0\nintr 1 2\n";
+        let parsed = parse_proc_stat(stat).expect("stat should parse");
+        assert_eq!(parsed.total.total, 600);
+        assert_eq!(parsed.cores.len(), 2);
+        assert_eq!(parsed.cores[0].idle, 9);
+        assert_eq!(parsed.cores[1].total, 100);
+    }
+
+    #[test]
+    fn parse_load_average_reads_three_values() {
+        assert_eq!(
+            parse_load_average("0.52 0.58 0.59 1/467 12345\n"),
+            Ok([0.52, 0.58, 0.59])
+        );
+        assert!(parse_load_average("0.52").is_err());
+    }
+
     #[test]
     fn usage_css_class_matches_thresholds() {
         assert_eq!(usage_css_class(0.0), "usage-low");
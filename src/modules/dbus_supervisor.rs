@@ -0,0 +1,155 @@
+//! Session bus supervision shared by the D-Bus based modules.
+//!
+//! Signal listeners run through [`spawn_listener`], which reconnects with
+//! backoff when the bus goes away (for example a session bus restart)
+//! instead of letting the listener thread end silently. Connection health is
+//! published so widgets can show a `.disconnected` state, and a generation
+//! counter lets long-lived connections notice that they must be reopened.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use gtk::prelude::*;
+use gtk::Widget;
+use zbus::blocking::Connection;
+
+use crate::modules::broadcaster::{attach_subscription, Broadcaster};
+
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+const DISCONNECTED_CLASS: &str = "disconnected";
+
+/// Why a listener returned.
+pub(crate) enum ListenerExit {
+    /// Nobody needs the events anymore; the listener is not restarted.
+    Stopped,
+    /// The connection or subscription failed; reconnect and listen again.
+    Lost(String),
+}
+
+struct SessionHealth {
+    /// `None` until the first connection attempt finishes.
+    connected: Mutex<Option<bool>>,
+    generation: AtomicU64,
+    updates: Broadcaster<bool>,
+}
+
+fn session_health() -> &'static SessionHealth {
+    static HEALTH: OnceLock<SessionHealth> = OnceLock::new();
+    HEALTH.get_or_init(|| SessionHealth {
+        connected: Mutex::new(None),
+        generation: AtomicU64::new(0),
+        updates: Broadcaster::new(),
+    })
+}
+
+/// Bumped every time the session bus comes back after a disconnect.
+/// Connections opened under an older generation are dead.
+pub(crate) fn session_generation() -> u64 {
+    session_health().generation.load(Ordering::Acquire)
+}
+
+/// Runs `listen` on its own thread with a fresh session connection, and
+/// again after every [`ListenerExit::Lost`], waiting with exponential
+/// backoff between attempts.
+pub(crate) fn spawn_listener(
+    owner: &'static str,
+    mut listen: impl FnMut(&Connection) -> ListenerExit + Send + 'static,
+) {
+    thread::spawn(move || {
+        let mut failures = 0u32;
+        loop {
+            match Connection::session() {
+                Ok(connection) => {
+                    let generation = report_connected();
+                    let started = Instant::now();
+                    match listen(&connection) {
+                        ListenerExit::Stopped => return,
+                        ListenerExit::Lost(reason) => {
+                            report_lost(owner, generation, &reason);
+                        }
+                    }
+                    // A listener that ran for a while was healthy; start the
+                    // backoff over instead of waiting the maximum.
+                    if started.elapsed() >= RECONNECT_MAX_DELAY {
+                        failures = 0;
+                    }
+                }
+                Err(err) => {
+                    report_lost(owner, session_generation(), &err.to_string());
+                }
+            }
+            thread::sleep(reconnect_delay(failures));
+            failures = failures.saturating_add(1);
+        }
+    });
+}
+
+fn report_connected() -> u64 {
+    let health = session_health();
+    let mut connected = health.connected.lock().expect("dbus health mutex poisoned");
+    match *connected {
+        Some(true) => {}
+        Some(false) => {
+            health.generation.fetch_add(1, Ordering::AcqRel);
+            eprintln!("vibar: session D-Bus connection restored");
+            *connected = Some(true);
+            health.updates.broadcast(true);
+        }
+        None => {
+            *connected = Some(true);
+            health.updates.broadcast(true);
+        }
+    }
+    health.generation.load(Ordering::Acquire)
+}
+
+/// Ignored when the bus already came back since `generation`, so a slow
+/// listener does not flag a fresh connection as lost.
+fn report_lost(owner: &str, generation: u64, reason: &str) {
+    let health = session_health();
+    let mut connected = health.connected.lock().expect("dbus health mutex poisoned");
+    if generation != health.generation.load(Ordering::Acquire) || *connected == Some(false) {
+        return;
+    }
+    eprintln!("vibar: lost session D-Bus connection ({owner}: {reason}); reconnecting");
+    *connected = Some(false);
+    health.updates.broadcast(false);
+}
+
+/// Toggles `.disconnected` on `widget` while the session bus is unreachable.
+pub(crate) fn attach_disconnected_class<W>(widget: &W)
+where
+    W: IsA<Widget> + Clone + 'static,
+{
+    let subscription = session_health().updates.subscribe();
+    attach_subscription(widget, subscription, |widget, connected| {
+        if connected {
+            widget.remove_css_class(DISCONNECTED_CLASS);
+        } else {
+            widget.add_css_class(DISCONNECTED_CLASS);
+        }
+    });
+}
+
+fn reconnect_delay(failures: u32) -> Duration {
+    RECONNECT_INITIAL_DELAY
+        .saturating_mul(2u32.saturating_pow(failures))
+        .min(RECONNECT_MAX_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_delay_doubles_up_to_the_cap() {
+        assert_eq!(reconnect_delay(0), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(1), Duration::from_secs(2));
+        assert_eq!(reconnect_delay(4), Duration::from_secs(16));
+        assert_eq!(reconnect_delay(5), RECONNECT_MAX_DELAY);
+        assert_eq!(reconnect_delay(u32::MAX), RECONNECT_MAX_DELAY);
+    }
+}
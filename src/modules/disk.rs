@@ -1,4 +1,5 @@
 use std::ffi::CString;
+use std::fs;
 use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
@@ -10,6 +11,8 @@ use serde_json::Value;
 use crate::modules::broadcaster::{
     attach_subscription, BackendRegistry, Broadcaster, Subscription,
 };
+use crate::modules::text::{attach_text_subscription, visible_block, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
 use crate::modules::{
     escape_markup_text, render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel,
 };
@@ -20,6 +23,14 @@ const MIN_DISK_INTERVAL_SECS: u32 = 1;
 const DEFAULT_DISK_INTERVAL_SECS: u32 = 30;
 const DEFAULT_DISK_PATH: &str = "/";
 const DEFAULT_DISK_FORMAT: &str = "{free}";
+const DEFAULT_DISK_SEPARATOR: &str = " ";
+const DEFAULT_DISK_THRESHOLD_HYSTERESIS: u8 = 2;
+const DISK_STATE_CLASSES: [&str; 4] = [
+    "disk-normal",
+    "disk-warning",
+    "disk-critical",
+    "disk-unknown",
+];
 pub(crate) const MODULE_TYPE: &str = "disk";
 
 #[derive(Debug, Deserialize, Clone)]
@@ -34,6 +45,26 @@ pub(crate) struct DiskConfig {
     pub(crate) interval_secs: u32,
     #[serde(default)]
     pub(crate) path: Option<String>,
+    /// Watches several paths instead of `path`, each rendered with `format`.
+    #[serde(default)]
+    pub(crate) paths: Option<Vec<String>>,
+    /// Joins the rendered `paths` entries.
+    #[serde(default)]
+    pub(crate) separator: Option<String>,
+    #[serde(rename = "warning-threshold", alias = "warning_threshold", default)]
+    pub(crate) warning_threshold: Option<u8>,
+    #[serde(rename = "critical-threshold", alias = "critical_threshold", default)]
+    pub(crate) critical_threshold: Option<u8>,
+    #[serde(
+        rename = "threshold-hysteresis",
+        alias = "threshold_hysteresis",
+        default = "default_disk_threshold_hysteresis"
+    )]
+    pub(crate) threshold_hysteresis: u8,
+    #[serde(rename = "on-warning", alias = "on_warning", default)]
+    pub(crate) on_warning: Option<String>,
+    #[serde(rename = "on-critical", alias = "on_critical", default)]
+    pub(crate) on_critical: Option<String>,
     #[serde(default)]
     pub(crate) class: Option<String>,
 }
@@ -41,21 +72,73 @@ pub(crate) struct DiskConfig {
 #[derive(Debug, Clone)]
 struct DiskStatus {
     path: String,
+    /// Source device and filesystem type from /proc/mounts; empty when the
+    /// mount is not found.
+    device: String,
+    fstype: String,
     free_bytes: u64,
     used_bytes: u64,
     total_bytes: u64,
 }
 
+/// Ordered by severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum DiskState {
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl DiskState {
+    fn css_class(self) -> &'static str {
+        match self {
+            Self::Normal => "disk-normal",
+            Self::Warning => "disk-warning",
+            Self::Critical => "disk-critical",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct DiskUpdate {
     text: String,
+    tooltip: String,
+    state_class: &'static str,
+}
+
+#[derive(Debug, Clone)]
+struct DiskRuntimeConfig {
+    paths: Vec<String>,
+    format: String,
+    separator: String,
+    tooltip_format: Option<String>,
+    interval_secs: u32,
+    thresholds: DiskThresholds,
+    on_warning: Option<String>,
+    on_critical: Option<String>,
+    click_command: Option<String>,
+    class: Option<String>,
+}
+
+/// Free-space percentages that drive the disk state. A state is entered at or
+/// below its threshold and only left once free space recovers by `hysteresis`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct DiskThresholds {
+    warning: Option<u8>,
+    critical: Option<u8>,
+    hysteresis: u8,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct DiskSharedKey {
-    path: String,
+    paths: Vec<String>,
     format: String,
+    separator: String,
+    tooltip_format: Option<String>,
     interval_secs: u32,
+    thresholds: DiskThresholds,
+    on_warning: Option<String>,
+    on_critical: Option<String>,
 }
 
 pub(crate) struct DiskFactory;
@@ -67,29 +150,70 @@ impl ModuleFactory for DiskFactory {
         MODULE_TYPE
     }
 
-    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
         let parsed = parse_config(config)?;
-        let path = parsed.path.unwrap_or_else(|| DEFAULT_DISK_PATH.to_string());
-        let format = parsed
-            .format
-            .unwrap_or_else(|| DEFAULT_DISK_FORMAT.to_string());
-        let click_command = parsed.click.or(parsed.on_click);
-
-        Ok(build_disk_module(
-            path,
-            format,
-            click_command,
-            parsed.interval_secs,
-            parsed.class,
-        )
-        .upcast())
+        Ok(build_disk_module(runtime_config(parsed, context.tooltip_format.clone())?).upcast())
+    }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        let config = runtime_config(parse_config(config)?, None)?;
+        let config = DiskRuntimeConfig {
+            interval_secs: normalized_disk_interval(config.interval_secs),
+            ..config
+        };
+        attach_text_subscription(subscribe_shared_disk(&config), sink, |update| {
+            visible_block(&update.text)
+                .map(|block| block.urgent(update.state_class == "disk-critical"))
+        });
+        Ok(())
     }
 }
 
+fn runtime_config(
+    parsed: DiskConfig,
+    tooltip_format: Option<String>,
+) -> Result<DiskRuntimeConfig, String> {
+    let paths = match (parsed.path, parsed.paths) {
+        (Some(_), Some(_)) => return Err("set either `path` or `paths`, not both".to_string()),
+        (_, Some(paths)) if paths.is_empty() => return Err("`paths` must not be empty".to_string()),
+        (_, Some(paths)) => paths,
+        (path, None) => vec![path.unwrap_or_else(|| DEFAULT_DISK_PATH.to_string())],
+    };
+
+    Ok(DiskRuntimeConfig {
+        paths,
+        format: parsed
+            .format
+            .unwrap_or_else(|| DEFAULT_DISK_FORMAT.to_string()),
+        separator: parsed
+            .separator
+            .unwrap_or_else(|| DEFAULT_DISK_SEPARATOR.to_string()),
+        tooltip_format,
+        interval_secs: parsed.interval_secs,
+        thresholds: DiskThresholds {
+            warning: parsed.warning_threshold,
+            critical: parsed.critical_threshold,
+            hysteresis: parsed.threshold_hysteresis,
+        },
+        on_warning: parsed.on_warning,
+        on_critical: parsed.on_critical,
+        click_command: parsed.click.or(parsed.on_click),
+        class: parsed.class,
+    })
+}
+
 fn default_disk_interval() -> u32 {
     DEFAULT_DISK_INTERVAL_SECS
 }
 
+fn default_disk_threshold_hysteresis() -> u8 {
+    DEFAULT_DISK_THRESHOLD_HYSTERESIS
+}
+
 pub(crate) fn parse_config(module: &ModuleConfig) -> Result<DiskConfig, String> {
     if module.module_type != MODULE_TYPE {
         return Err(format!(
@@ -112,71 +236,175 @@ fn disk_registry() -> &'static BackendRegistry<DiskSharedKey, Broadcaster<DiskUp
     REGISTRY.get_or_init(BackendRegistry::new)
 }
 
-fn subscribe_shared_disk(
-    path: String,
-    format: String,
-    interval_secs: u32,
-) -> Subscription<DiskUpdate> {
+fn subscribe_shared_disk(config: &DiskRuntimeConfig) -> Subscription<DiskUpdate> {
     let key = DiskSharedKey {
-        path,
-        format,
-        interval_secs,
+        paths: config.paths.clone(),
+        format: config.format.clone(),
+        separator: config.separator.clone(),
+        tooltip_format: config.tooltip_format.clone(),
+        interval_secs: config.interval_secs,
+        thresholds: config.thresholds,
+        on_warning: config.on_warning.clone(),
+        on_critical: config.on_critical.clone(),
     };
 
     let (broadcaster, start_worker) = disk_registry().get_or_create(key.clone(), Broadcaster::new);
     let receiver = broadcaster.subscribe();
 
     if start_worker {
-        start_disk_worker(key, broadcaster);
+        start_disk_worker(key, config.clone(), broadcaster);
     }
 
     receiver
 }
 
-fn start_disk_worker(key: DiskSharedKey, broadcaster: Arc<Broadcaster<DiskUpdate>>) {
-    let interval = Duration::from_secs(u64::from(key.interval_secs));
-    std::thread::spawn(move || loop {
-        let text = match read_disk_status(&key.path) {
-            Ok(status) => render_format(&key.format, &status),
-            Err(err) => escape_markup_text(&format!("disk error: {err}")),
-        };
-        broadcaster.broadcast(DiskUpdate { text });
-        if broadcaster.subscriber_count() == 0 {
-            disk_registry().remove(&key, &broadcaster);
-            return;
+fn start_disk_worker(
+    key: DiskSharedKey,
+    config: DiskRuntimeConfig,
+    broadcaster: Arc<Broadcaster<DiskUpdate>>,
+) {
+    let interval = Duration::from_secs(u64::from(config.interval_secs));
+    std::thread::spawn(move || {
+        // Each path keeps its own state, so hooks fire per path.
+        let mut states = vec![DiskState::Normal; config.paths.len()];
+        loop {
+            let mounts = read_mounts();
+            let mut texts = Vec::new();
+            let mut tooltips = Vec::new();
+            let mut failed = false;
+            for (path, state) in config.paths.iter().zip(&mut states) {
+                match read_disk_status(path) {
+                    Ok(mut status) => {
+                        if let Some(mount) = mount_for(&mounts, path) {
+                            status.device = mount.device.clone();
+                            status.fstype = mount.fstype.clone();
+                        }
+                        let next_state =
+                            next_disk_state(*state, percentage_free(&status), config.thresholds);
+                        if next_state != *state {
+                            run_threshold_hook(next_state, path, &config);
+                            *state = next_state;
+                        }
+                        texts.push(render_format(&config.format, &status));
+                        if let Some(format) = &config.tooltip_format {
+                            tooltips.push(render_format(format, &status));
+                        }
+                    }
+                    Err(err) => {
+                        failed = true;
+                        texts.push(escape_markup_text(&format!("disk error: {err}")));
+                    }
+                }
+            }
+            let state_class = if failed {
+                "disk-unknown"
+            } else {
+                states
+                    .iter()
+                    .max()
+                    .copied()
+                    .unwrap_or(DiskState::Normal)
+                    .css_class()
+            };
+            let update = DiskUpdate {
+                text: join_entries(&texts, &config.separator),
+                tooltip: join_entries(&tooltips, "\n"),
+                state_class,
+            };
+            broadcaster.broadcast(update);
+            if broadcaster.subscriber_count() == 0 {
+                disk_registry().remove(&key, &broadcaster);
+                return;
+            }
+            std::thread::sleep(interval);
         }
-        std::thread::sleep(interval);
     });
 }
 
-pub(crate) fn build_disk_module(
-    path: String,
-    format: String,
-    click_command: Option<String>,
-    interval_secs: u32,
-    class: Option<String>,
-) -> Label {
+fn run_threshold_hook(state: DiskState, path: &str, config: &DiskRuntimeConfig) {
+    let command = match state {
+        DiskState::Normal => None,
+        DiskState::Warning => config.on_warning.as_deref(),
+        DiskState::Critical => config.on_critical.as_deref(),
+    };
+    let Some(command) = command else {
+        return;
+    };
+
+    if let Err(err) = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("VIBAR_DISK_PATH", path)
+        .spawn()
+    {
+        eprintln!("disk: failed to run threshold command '{command}': {err}");
+    }
+}
+
+/// Computes the next disk state from the current free-space percentage.
+///
+/// Escalation happens as soon as free space is at/below a threshold; recovery
+/// to a less severe state requires free space to exceed the threshold plus
+/// `hysteresis`, so hooks are not re-run while hovering around the boundary.
+fn next_disk_state(current: DiskState, free_pct: f64, thresholds: DiskThresholds) -> DiskState {
+    let below = |threshold: Option<u8>, held: bool| {
+        threshold.is_some_and(|value| {
+            let limit = if held {
+                f64::from(value) + f64::from(thresholds.hysteresis)
+            } else {
+                f64::from(value)
+            };
+            free_pct <= limit
+        })
+    };
+
+    if below(thresholds.critical, current == DiskState::Critical) {
+        return DiskState::Critical;
+    }
+    if below(thresholds.warning, current != DiskState::Normal) {
+        return DiskState::Warning;
+    }
+    DiskState::Normal
+}
+
+fn build_disk_module(config: DiskRuntimeConfig) -> Label {
     let label = ModuleLabel::new("disk")
-        .with_css_classes(class.as_deref())
-        .with_click_command(click_command)
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(config.click_command.clone())
         .into_label();
 
-    let effective_interval_secs = normalized_disk_interval(interval_secs);
-    if effective_interval_secs != interval_secs {
+    let effective_interval_secs = normalized_disk_interval(config.interval_secs);
+    if effective_interval_secs != config.interval_secs {
         eprintln!(
             "disk interval_secs={} is too low; clamping to {} second",
-            interval_secs, effective_interval_secs
+            config.interval_secs, effective_interval_secs
         );
     }
 
-    let subscription = subscribe_shared_disk(path, format, effective_interval_secs);
+    let config = DiskRuntimeConfig {
+        interval_secs: effective_interval_secs,
+        ..config
+    };
 
-    attach_subscription(&label, subscription, |label, update| {
+    let tooltip = config
+        .tooltip_format
+        .is_some()
+        .then(|| ModuleTooltip::attach(&label));
+    let subscription = subscribe_shared_disk(&config);
+
+    attach_subscription(&label, subscription, move |label, update| {
         let visible = !update.text.trim().is_empty();
         label.set_visible(visible);
         if visible {
             label.set_markup(&update.text);
         }
+        if let Some(tooltip) = &tooltip {
+            tooltip.set_markup(&update.tooltip);
+        }
+        for class_name in DISK_STATE_CLASSES {
+            label.remove_css_class(class_name);
+        }
+        label.add_css_class(update.state_class);
     });
 
     label
@@ -207,18 +435,104 @@ fn read_disk_status(path: &str) -> Result<DiskStatus, String> {
     let used_bytes = total_bytes.saturating_sub(stat.f_bfree.saturating_mul(block));
     Ok(DiskStatus {
         path: path.to_string(),
+        device: String::new(),
+        fstype: String::new(),
         free_bytes,
         used_bytes,
         total_bytes,
     })
 }
 
-fn render_format(format: &str, status: &DiskStatus) -> String {
-    let free_pct = if status.total_bytes == 0 {
+/// One `/proc/mounts` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MountEntry {
+    device: String,
+    mountpoint: String,
+    fstype: String,
+}
+
+fn read_mounts() -> Vec<MountEntry> {
+    fs::read_to_string("/proc/mounts")
+        .map(|mounts| parse_mounts(&mounts))
+        .unwrap_or_default()
+}
+
+fn parse_mounts(mounts: &str) -> Vec<MountEntry> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some(MountEntry {
+                device: unescape_mount_field(fields.next()?),
+                mountpoint: unescape_mount_field(fields.next()?),
+                fstype: unescape_mount_field(fields.next()?),
+            })
+        })
+        .collect()
+}
+
+/// Undoes the octal escapes (`\040` for a space) the kernel writes in
+/// /proc/mounts.
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'\\')
+            .then(|| field.get(index + 1..index + 4))
+            .flatten()
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+        match escaped {
+            Some(byte) => {
+                unescaped.push(byte);
+                index += 4;
+            }
+            None => {
+                unescaped.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+/// The mount holding `path`: the longest matching mountpoint, and the last
+/// one mounted when several share it.
+fn mount_for<'a>(mounts: &'a [MountEntry], path: &str) -> Option<&'a MountEntry> {
+    mounts
+        .iter()
+        .filter(|mount| is_under_mountpoint(path, &mount.mountpoint))
+        .max_by_key(|mount| mount.mountpoint.len())
+}
+
+fn is_under_mountpoint(path: &str, mountpoint: &str) -> bool {
+    if mountpoint == "/" {
+        return path.starts_with('/');
+    }
+    path.strip_prefix(mountpoint)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Joins rendered entries, skipping ones that rendered empty.
+fn join_entries(entries: &[String], separator: &str) -> String {
+    entries
+        .iter()
+        .filter(|entry| !entry.trim().is_empty())
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn percentage_free(status: &DiskStatus) -> f64 {
+    if status.total_bytes == 0 {
         0.0
     } else {
         (status.free_bytes as f64 / status.total_bytes as f64) * 100.0
-    };
+    }
+}
+
+fn render_format(format: &str, status: &DiskStatus) -> String {
+    let free_pct = percentage_free(status);
     let used_pct = if status.total_bytes == 0 {
         0.0
     } else {
@@ -229,6 +543,8 @@ fn render_format(format: &str, status: &DiskStatus) -> String {
         format,
         &[
             ("{path}", &status.path),
+            ("{device}", &status.device),
+            ("{fstype}", &status.fstype),
             ("{free}", &format_bytes(status.free_bytes)),
             ("{used}", &format_bytes(status.used_bytes)),
             ("{total}", &format_bytes(status.total_bytes)),
@@ -295,11 +611,122 @@ mod tests {
     fn render_format_replaces_placeholders() {
         let status = DiskStatus {
             path: "/".to_string(),
+            device: "/dev/nvme0n1p2".to_string(),
+            fstype: "btrfs".to_string(),
             free_bytes: 600,
             used_bytes: 400,
             total_bytes: 1000,
         };
         let text = render_format("{free} {path} {percentage_used}", &status);
         assert_eq!(text, "600B / 40");
+        let text = render_format("{device} {fstype}", &status);
+        assert_eq!(text, "/dev/nvme0n1p2 btrfs");
+    }
+
+    #[test]
+    fn runtime_config_reads_paths() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_str("{\"paths\":[\"/\",\"/home\"],\"separator\":\" | \"}")
+                .expect("module config map should parse"),
+        );
+        let config = runtime_config(parse_config(&module).expect("config should parse"), None)
+            .expect("paths should be accepted");
+        assert_eq!(config.paths, ["/", "/home"]);
+        assert_eq!(config.separator, " | ");
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_str("{\"path\":\"/\",\"paths\":[\"/home\"]}")
+                .expect("module config map should parse"),
+        );
+        let parsed = parse_config(&module).expect("config should parse");
+        assert!(runtime_config(parsed, None).is_err());
+    }
+
+    #[test]
+    fn mount_for_picks_the_longest_mountpoint() {
+        let mounts = parse_mounts(
+            "/dev/sda2 / ext4 rw 0 0\n\
+             /dev/sda3 /home ext4 rw 0 0\n\
+             /dev/sdb1 /home/my\\040media vfat rw 0 0\n\
+             tmpfs /home tmpfs rw 0 0\n",
+        );
+        assert_eq!(mounts[2].mountpoint, "/home/my media");
+
+        let mount = mount_for(&mounts, "/home/user").expect("/home should match");
+        assert_eq!(
+            (mount.device.as_str(), mount.fstype.as_str()),
+            ("tmpfs", "tmpfs")
+        );
+        let mount = mount_for(&mounts, "/homework").expect("/ should match");
+        assert_eq!(mount.device, "/dev/sda2");
+        let mount = mount_for(&mounts, "/home/my media/x").expect("media should match");
+        assert_eq!(mount.fstype, "vfat");
+    }
+
+    #[test]
+    fn join_entries_skips_empty_entries() {
+        let entries = ["a".to_string(), " ".to_string(), "b".to_string()];
+        assert_eq!(join_entries(&entries, " | "), "a | b");
+    }
+
+    #[test]
+    fn next_disk_state_escalates_at_thresholds() {
+        let thresholds = DiskThresholds {
+            warning: Some(20),
+            critical: Some(10),
+            hysteresis: 2,
+        };
+        assert_eq!(
+            next_disk_state(DiskState::Normal, 25.0, thresholds),
+            DiskState::Normal
+        );
+        assert_eq!(
+            next_disk_state(DiskState::Normal, 20.0, thresholds),
+            DiskState::Warning
+        );
+        assert_eq!(
+            next_disk_state(DiskState::Normal, 5.0, thresholds),
+            DiskState::Critical
+        );
+    }
+
+    #[test]
+    fn next_disk_state_applies_hysteresis_on_recovery() {
+        let thresholds = DiskThresholds {
+            warning: Some(20),
+            critical: Some(10),
+            hysteresis: 2,
+        };
+        assert_eq!(
+            next_disk_state(DiskState::Critical, 11.5, thresholds),
+            DiskState::Critical
+        );
+        assert_eq!(
+            next_disk_state(DiskState::Critical, 12.5, thresholds),
+            DiskState::Warning
+        );
+        assert_eq!(
+            next_disk_state(DiskState::Warning, 21.0, thresholds),
+            DiskState::Warning
+        );
+        assert_eq!(
+            next_disk_state(DiskState::Warning, 22.5, thresholds),
+            DiskState::Normal
+        );
+    }
+
+    #[test]
+    fn next_disk_state_without_thresholds_stays_normal() {
+        let thresholds = DiskThresholds {
+            warning: None,
+            critical: None,
+            hysteresis: 2,
+        };
+        assert_eq!(
+            next_disk_state(DiskState::Normal, 0.0, thresholds),
+            DiskState::Normal
+        );
     }
 }
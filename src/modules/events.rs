@@ -0,0 +1,293 @@
+//! Cross-module events and the top-level `reactions` that respond to them.
+//!
+//! Module backends call [`emit`] from their worker threads; events are
+//! delivered on the GTK thread, where matching reactions run a command or add
+//! a CSS class to other modules.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use gtk::glib::{self, IOCondition};
+use gtk::prelude::*;
+use gtk::Widget;
+use serde::Deserialize;
+
+use crate::modules::broadcaster::{drain_pipe, Broadcaster, Subscription};
+
+pub(crate) const BATTERY_CRITICAL: &str = "battery-critical";
+pub(crate) const SINK_CHANGED: &str = "sink-changed";
+pub(crate) const WORKSPACE_SWITCHED: &str = "workspace-switched";
+const KNOWN_EVENTS: [&str; 3] = [BATTERY_CRITICAL, SINK_CHANGED, WORKSPACE_SWITCHED];
+/// Repeats of an event within this window are dropped. Modules with
+/// different formats run separate workers on the same device, and each of
+/// them sees the same change.
+const REPEAT_WINDOW: Duration = Duration::from_secs(2);
+
+/// One published event; `value` is the event's subject (battery capacity,
+/// sink name, workspace name).
+#[derive(Debug, Clone)]
+pub(crate) struct Event {
+    name: &'static str,
+    value: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct ReactionConfig {
+    pub(crate) on: String,
+    #[serde(default)]
+    pub(crate) run: Option<String>,
+    #[serde(rename = "set-class", alias = "set_class", default)]
+    pub(crate) set_class: Option<String>,
+    /// CSS class selecting the modules `set-class` applies to.
+    #[serde(default)]
+    pub(crate) target: Option<String>,
+    /// Removes the class again after this long; kept until reload otherwise.
+    #[serde(rename = "duration-ms", alias = "duration_ms", default)]
+    pub(crate) duration_ms: Option<u32>,
+}
+
+impl ReactionConfig {
+    fn validate(&self) -> Result<(), String> {
+        if !KNOWN_EVENTS.contains(&self.on.as_str()) {
+            return Err(format!(
+                "unknown event '{}' (expected one of {})",
+                self.on,
+                KNOWN_EVENTS.join(", ")
+            ));
+        }
+        if self.run.is_none() && self.set_class.is_none() {
+            return Err("needs `run` or `set-class`".to_string());
+        }
+        if self.set_class.is_some() && self.target.as_deref().is_none_or(str::is_empty) {
+            return Err("`set-class` needs a `target` class".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct ReactionState {
+    reactions: Vec<ReactionConfig>,
+    subscription: Option<Subscription<Event>>,
+}
+
+thread_local! {
+    static STATE: RefCell<ReactionState> = RefCell::new(ReactionState::default());
+}
+
+/// Last value emitted per event name.
+#[derive(Debug, Default)]
+struct RecentEvents {
+    last: HashMap<&'static str, (String, Instant)>,
+}
+
+impl RecentEvents {
+    /// Records the event; `true` when it repeats the previous value of the
+    /// same event within [`REPEAT_WINDOW`].
+    fn is_repeat(&mut self, name: &'static str, value: &str, now: Instant) -> bool {
+        let repeat = self.last.get(name).is_some_and(|(last_value, at)| {
+            last_value == value && now.saturating_duration_since(*at) < REPEAT_WINDOW
+        });
+        if !repeat {
+            self.last.insert(name, (value.to_string(), now));
+        }
+        repeat
+    }
+}
+
+fn recent_events() -> &'static Mutex<RecentEvents> {
+    static RECENT: OnceLock<Mutex<RecentEvents>> = OnceLock::new();
+    RECENT.get_or_init(Mutex::default)
+}
+
+fn event_bus() -> &'static Broadcaster<Event> {
+    static BUS: OnceLock<Broadcaster<Event>> = OnceLock::new();
+    BUS.get_or_init(Broadcaster::new)
+}
+
+/// Publishes an event. A no-op until some reaction is configured, so the bus
+/// never replays a stale event to its first subscriber.
+pub(crate) fn emit(name: &'static str, value: impl Into<String>) {
+    let bus = event_bus();
+    if bus.subscriber_count() == 0 {
+        return;
+    }
+    let value = value.into();
+    if recent_events()
+        .lock()
+        .expect("recent events mutex poisoned")
+        .is_repeat(name, &value, Instant::now())
+    {
+        return;
+    }
+    bus.broadcast(Event { name, value });
+}
+
+/// Applies the top-level `reactions` config; invalid entries are skipped
+/// with a warning. Must run on the GTK thread.
+pub(crate) fn configure(reactions: &[ReactionConfig]) {
+    let reactions = reactions
+        .iter()
+        .filter(|reaction| match reaction.validate() {
+            Ok(()) => true,
+            Err(err) => {
+                eprintln!("vibar: ignoring reaction on '{}': {err}", reaction.on);
+                false
+            }
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let subscribe = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.reactions = reactions;
+        !state.reactions.is_empty() && state.subscription.is_none()
+    });
+    if subscribe {
+        start_delivery();
+    }
+}
+
+/// Subscribes once for the process lifetime; later `configure` calls only
+/// swap the reaction list.
+fn start_delivery() {
+    let subscription = event_bus().subscribe();
+    let fd = subscription.notify_fd;
+    STATE.with(|state| state.borrow_mut().subscription = Some(subscription));
+
+    glib::unix_fd_add_local(fd, IOCondition::IN, move |_, _| {
+        drain_pipe(fd);
+        let (events, reactions) = STATE.with(|state| {
+            let state = state.borrow();
+            let events = state
+                .subscription
+                .as_ref()
+                .map(|subscription| subscription.receiver.try_iter().collect::<Vec<_>>())
+                .unwrap_or_default();
+            (events, state.reactions.clone())
+        });
+        for event in &events {
+            for reaction in reactions
+                .iter()
+                .filter(|reaction| reaction.on == event.name)
+            {
+                apply_reaction(reaction, event);
+            }
+        }
+        glib::ControlFlow::Continue
+    });
+}
+
+fn apply_reaction(reaction: &ReactionConfig, event: &Event) {
+    if let Some(command) = reaction.run.as_deref() {
+        if let Err(err) = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("VIBAR_EVENT", event.name)
+            .env("VIBAR_EVENT_VALUE", &event.value)
+            .spawn()
+        {
+            eprintln!("vibar: failed to run reaction command '{command}': {err}");
+        }
+    }
+
+    let (Some(class_name), Some(target)) =
+        (reaction.set_class.as_deref(), reaction.target.as_deref())
+    else {
+        return;
+    };
+    for widget in widgets_with_class(target) {
+        widget.add_css_class(class_name);
+        if let Some(duration_ms) = reaction.duration_ms {
+            let widget = widget.downgrade();
+            let class_name = class_name.to_string();
+            glib::timeout_add_local_once(
+                Duration::from_millis(u64::from(duration_ms)),
+                move || {
+                    if let Some(widget) = widget.upgrade() {
+                        widget.remove_css_class(&class_name);
+                    }
+                },
+            );
+        }
+    }
+}
+
+/// Every widget in every bar window (popovers included) carrying `class_name`.
+fn widgets_with_class(class_name: &str) -> Vec<Widget> {
+    let mut found = Vec::new();
+    for window in gtk::Window::list_toplevels() {
+        collect_widgets_with_class(&window, class_name, &mut found);
+    }
+    found
+}
+
+fn collect_widgets_with_class(widget: &Widget, class_name: &str, found: &mut Vec<Widget>) {
+    if widget.has_css_class(class_name) {
+        found.push(widget.clone());
+    }
+    let mut child = widget.first_child();
+    while let Some(current) = child {
+        collect_widgets_with_class(&current, class_name, found);
+        child = current.next_sibling();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn reaction(value: serde_json::Value) -> ReactionConfig {
+        serde_json::from_value(value).expect("reaction should parse")
+    }
+
+    #[test]
+    fn validate_accepts_run_and_targeted_set_class() {
+        assert!(
+            reaction(json!({ "on": "battery-critical", "run": "notify-send low" }))
+                .validate()
+                .is_ok()
+        );
+        assert!(reaction(json!({
+            "on": "workspace-switched",
+            "set-class": "flash",
+            "target": "clock",
+            "duration-ms": 300
+        }))
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn recent_events_drop_repeats_within_the_window() {
+        let mut recent = RecentEvents::default();
+        let start = Instant::now();
+        assert!(!recent.is_repeat(WORKSPACE_SWITCHED, "1", start));
+        assert!(recent.is_repeat(WORKSPACE_SWITCHED, "1", start));
+        // Switching away and back is not a repeat.
+        assert!(!recent.is_repeat(WORKSPACE_SWITCHED, "2", start));
+        assert!(!recent.is_repeat(WORKSPACE_SWITCHED, "1", start));
+        assert!(!recent.is_repeat(SINK_CHANGED, "1", start));
+        assert!(!recent.is_repeat(WORKSPACE_SWITCHED, "1", start + REPEAT_WINDOW));
+    }
+
+    #[test]
+    fn validate_rejects_incomplete_reactions() {
+        assert!(reaction(json!({ "on": "lid-closed", "run": "true" }))
+            .validate()
+            .is_err());
+        assert!(reaction(json!({ "on": "sink-changed" }))
+            .validate()
+            .is_err());
+        assert!(
+            reaction(json!({ "on": "sink-changed", "set_class": "flash" }))
+                .validate()
+                .is_err()
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::process::Command;
 use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
@@ -6,22 +7,36 @@ use std::time::Duration;
 
 use gtk::glib::ControlFlow;
 use gtk::prelude::*;
-use gtk::{Align, Label, Widget};
-use serde::Deserialize;
+use gtk::{Align, Widget};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::modules::backend_errors;
 use crate::modules::broadcaster::{
     attach_subscription, BackendRegistry, Broadcaster, Subscription,
 };
+use crate::modules::json_path::{value_to_text, JsonPath};
+use crate::modules::output_cache;
+use crate::modules::redact;
+use crate::modules::scroll_text::{ModuleText, ScrollTextMode};
+use crate::modules::text::{attach_text_subscription, TextBlock, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
 use crate::modules::{
-    apply_css_classes, attach_primary_click_command, escape_markup_text, render_markup_template,
-    ModuleBuildContext, ModuleConfig,
+    attach_primary_click_command, escape_markup_text, render_markup_template, ModuleBuildContext,
+    ModuleConfig,
 };
 
 use super::ModuleFactory;
 
 const MIN_EXEC_INTERVAL_SECS: u32 = 1;
+const DEFAULT_EXEC_INTERVAL_SECS: u32 = 5;
+/// Without a poll period the worker still wakes up this often to notice
+/// that every subscriber is gone.
+const UNSCHEDULED_IDLE_CHECK: Duration = Duration::from_secs(5);
 pub(crate) const MODULE_TYPE: &str = "exec";
+const STALE_CLASS: &str = "stale";
+/// Output class that maps to the i3bar `urgent` flag in `--stdout` mode.
+const URGENT_CLASS: &str = "urgent";
 
 #[derive(Debug, Deserialize, Clone)]
 pub(crate) struct ExecConfig {
@@ -32,16 +47,134 @@ pub(crate) struct ExecConfig {
     pub(crate) click: Option<String>,
     #[serde(rename = "on-click", default)]
     pub(crate) on_click: Option<String>,
-    #[serde(default = "default_exec_interval")]
-    pub(crate) interval_secs: u32,
+    #[serde(
+        rename = "interval_secs",
+        alias = "interval",
+        default = "default_exec_interval"
+    )]
+    pub(crate) interval: ExecInterval,
     #[serde(default)]
     pub(crate) signal: Option<i32>,
     #[serde(default)]
     pub(crate) class: Option<String>,
+    #[serde(rename = "max-width", alias = "max_width", default)]
+    pub(crate) max_width: Option<u32>,
+    #[serde(rename = "scroll-text", alias = "scroll_text", default)]
+    pub(crate) scroll_text: ScrollTextMode,
+    #[serde(rename = "json-path", alias = "json_path", default)]
+    pub(crate) json_path: Option<ExecJsonPathConfig>,
+    #[serde(default)]
+    pub(crate) cache: bool,
+    #[serde(default)]
+    pub(crate) redact: bool,
+}
+
+/// The command to run; `redact` masks it in its output and error text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ExecCommand {
+    pub(crate) line: String,
+    pub(crate) redact: bool,
+}
+
+impl ExecCommand {
+    /// Names the command in logs and `vibar inspect` without revealing it,
+    /// whether or not it is redacted.
+    fn label(&self) -> String {
+        redact::command_label(&self.line)
+    }
+
+    /// `text` as it may be shown or logged.
+    fn sanitize(&self, text: &str) -> String {
+        if self.redact {
+            redact::mask_command(text, &self.line)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// `interval_secs`: seconds between runs, or a mode that disables polling.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub(crate) enum ExecInterval {
+    Secs(u32),
+    Mode(ExecIntervalMode),
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ExecIntervalMode {
+    /// Run at startup, then only on `signal`.
+    Once,
+    /// Run only on `signal`.
+    Signal,
+}
+
+impl ExecInterval {
+    /// Time between runs; `None` when the command only reruns on `signal`.
+    fn period(self) -> Option<Duration> {
+        match self {
+            Self::Secs(secs) => Some(Duration::from_secs(u64::from(secs))),
+            Self::Mode(_) => None,
+        }
+    }
+
+    fn runs_at_startup(self) -> bool {
+        self != Self::Mode(ExecIntervalMode::Signal)
+    }
+}
+
+/// `json-path`: a single path for the displayed text, or a map of paths.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub(crate) enum ExecJsonPathConfig {
+    Text(String),
+    Fields {
+        #[serde(default)]
+        text: Option<String>,
+        #[serde(default)]
+        class: Option<String>,
+        #[serde(default)]
+        tooltip: Option<String>,
+    },
+}
+
+/// Parsed `json-path` expressions applied to JSON command output.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ExecJsonPaths {
+    text: JsonPath,
+    class: Option<JsonPath>,
+    tooltip: Option<JsonPath>,
 }
 
-fn default_exec_interval() -> u32 {
-    5
+const DEFAULT_JSON_TEXT_PATH: &str = "$.text";
+
+impl ExecJsonPathConfig {
+    pub(crate) fn parse(&self) -> Result<ExecJsonPaths, String> {
+        let parse = |path: &str| {
+            JsonPath::parse(path).map_err(|err| format!("invalid {MODULE_TYPE} json-path: {err}"))
+        };
+        match self {
+            Self::Text(text) => Ok(ExecJsonPaths {
+                text: parse(text)?,
+                class: None,
+                tooltip: None,
+            }),
+            Self::Fields {
+                text,
+                class,
+                tooltip,
+            } => Ok(ExecJsonPaths {
+                text: parse(text.as_deref().unwrap_or(DEFAULT_JSON_TEXT_PATH))?,
+                class: class.as_deref().map(parse).transpose()?,
+                tooltip: tooltip.as_deref().map(parse).transpose()?,
+            }),
+        }
+    }
+}
+
+fn default_exec_interval() -> ExecInterval {
+    ExecInterval::Secs(DEFAULT_EXEC_INTERVAL_SECS)
 }
 
 fn default_exec_format() -> String {
@@ -57,19 +190,74 @@ impl ModuleFactory for ExecFactory {
         MODULE_TYPE
     }
 
-    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
         let parsed = parse_config(config)?;
         let click_command = parsed.click.or(parsed.on_click);
         let signal = normalize_exec_signal(parsed.signal)?;
+        let json_paths = parsed
+            .json_path
+            .as_ref()
+            .map(ExecJsonPathConfig::parse)
+            .transpose()?;
+        let text = ModuleText::new(
+            MODULE_TYPE,
+            parsed.max_width,
+            parsed.scroll_text,
+            context.scale(),
+            &["module", "exec"],
+            parsed.class.as_deref(),
+        )?;
         Ok(build_exec_module(
-            parsed.command,
-            parsed.format,
+            ExecSharedKey {
+                command: ExecCommand {
+                    line: parsed.command,
+                    redact: parsed.redact,
+                },
+                format: parsed.format,
+                tooltip_format: context.tooltip_format.clone(),
+                interval: parsed.interval,
+                json_paths,
+                cache: parsed.cache,
+            },
             click_command,
-            parsed.interval_secs,
             signal,
-            parsed.class,
-        )
-        .upcast())
+            text,
+        ))
+    }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        let parsed = parse_config(config)?;
+        let signal = normalize_exec_signal(parsed.signal)?;
+        let json_paths = parsed
+            .json_path
+            .as_ref()
+            .map(ExecJsonPathConfig::parse)
+            .transpose()?;
+        let subscription = subscribe_shared_exec_output(
+            ExecSharedKey {
+                command: ExecCommand {
+                    line: parsed.command,
+                    redact: parsed.redact,
+                },
+                format: parsed.format,
+                tooltip_format: None,
+                interval: normalized_exec_interval(parsed.interval),
+                json_paths,
+                cache: parsed.cache,
+            },
+            signal,
+        );
+        attach_text_subscription(subscription, sink, |rendered| {
+            let urgent = rendered.classes.iter().any(|class| class == URGENT_CLASS);
+            rendered
+                .visible
+                .then(|| TextBlock::new(rendered.text).urgent(urgent))
+        });
+        Ok(())
     }
 }
 
@@ -81,59 +269,87 @@ pub(crate) fn parse_config(module: &ModuleConfig) -> Result<ExecConfig, String>
         ));
     }
 
-    serde_json::from_value(Value::Object(module.config.clone()))
-        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+    if module.config.contains_key("interval_secs") && module.config.contains_key("interval") {
+        return Err(format!(
+            "invalid {MODULE_TYPE} module config: `interval_secs` and `interval` are the same option; set only one"
+        ));
+    }
+    let config: ExecConfig = serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))?;
+    if config.interval == ExecInterval::Mode(ExecIntervalMode::Signal) && config.signal.is_none() {
+        return Err(format!(
+            "invalid {MODULE_TYPE} module config: `interval_secs: \"signal\"` requires `signal`"
+        ));
+    }
+    Ok(config)
 }
 
-pub(crate) fn build_exec_module(
-    command: String,
-    format: String,
+fn build_exec_module(
+    mut key: ExecSharedKey,
     click_command: Option<String>,
-    interval_secs: u32,
     signal: Option<i32>,
-    class: Option<String>,
-) -> Label {
-    let label = Label::new(None);
-    label.set_halign(Align::Start);
-    label.set_xalign(0.0);
-    label.add_css_class("module");
-    label.add_css_class("exec");
-    let effective_interval_secs = normalized_exec_interval(interval_secs);
-
-    if effective_interval_secs != interval_secs {
-        eprintln!(
-            "exec interval_secs={} is too low; clamping to {} second",
-            interval_secs, effective_interval_secs
-        );
+    text: ModuleText,
+) -> Widget {
+    if let ModuleText::Label(label) = &text {
+        label.set_halign(Align::Start);
+        label.set_xalign(0.0);
+    }
+    let widget = text.widget();
+    let interval = key.interval;
+    key.interval = normalized_exec_interval(interval);
+
+    if let (ExecInterval::Secs(requested), ExecInterval::Secs(effective)) = (interval, key.interval)
+    {
+        if requested != effective {
+            eprintln!(
+                "exec interval_secs={} is too low; clamping to {} second",
+                requested, effective
+            );
+        }
     }
 
-    apply_css_classes(&label, class.as_deref());
-
-    attach_primary_click_command(&label, click_command);
+    attach_primary_click_command(&widget, click_command);
 
-    let subscription =
-        subscribe_shared_exec_output(command, format, effective_interval_secs, signal);
+    let tooltip = key
+        .tooltip_format
+        .is_some()
+        .then(|| ModuleTooltip::attach(&widget));
+    let subscription = subscribe_shared_exec_output(key, signal);
 
-    attach_subscription(&label, subscription, {
+    attach_subscription(&widget, subscription, {
         let mut active_dynamic_classes: Vec<String> = Vec::new();
-        move |label, rendered| {
-            label.set_markup(&rendered.text);
-            label.set_visible(rendered.visible);
+        move |widget, rendered| {
+            text.set_markup(&rendered.text);
+            widget.set_visible(rendered.visible);
+            match &tooltip {
+                Some(tooltip) => {
+                    tooltip.set_markup(rendered.tooltip.as_deref().unwrap_or_default())
+                }
+                None => widget.set_tooltip_text(rendered.tooltip.as_deref()),
+            }
             for class_name in &active_dynamic_classes {
-                label.remove_css_class(class_name);
+                widget.remove_css_class(class_name);
             }
             for class_name in &rendered.classes {
-                label.add_css_class(class_name);
+                widget.add_css_class(class_name);
             }
             active_dynamic_classes = rendered.classes;
+            if rendered.stale {
+                widget.add_css_class(STALE_CLASS);
+            } else {
+                widget.remove_css_class(STALE_CLASS);
+            }
         }
     });
 
-    label
+    widget
 }
 
-pub(crate) fn normalized_exec_interval(interval_secs: u32) -> u32 {
-    interval_secs.max(MIN_EXEC_INTERVAL_SECS)
+pub(crate) fn normalized_exec_interval(interval: ExecInterval) -> ExecInterval {
+    match interval {
+        ExecInterval::Secs(secs) => ExecInterval::Secs(secs.max(MIN_EXEC_INTERVAL_SECS)),
+        mode => mode,
+    }
 }
 
 pub(crate) fn normalize_exec_signal(signal: Option<i32>) -> Result<Option<i32>, String> {
@@ -160,16 +376,36 @@ fn exec_signal_to_signum(signal: i32) -> Result<i32, String> {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct ExecSharedKey {
-    command: String,
+    command: ExecCommand,
     format: String,
-    interval_secs: u32,
+    tooltip_format: Option<String>,
+    interval: ExecInterval,
+    json_paths: Option<ExecJsonPaths>,
+    cache: bool,
+}
+
+impl ExecSharedKey {
+    /// Cache entries ignore `interval_secs` so retuning the poll rate keeps
+    /// the cached output.
+    fn cache_path(&self) -> Option<PathBuf> {
+        output_cache::cache_path(
+            MODULE_TYPE,
+            &(&self.command.line, &self.format, &self.json_paths),
+        )
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 struct ExecRenderedOutput {
     text: String,
     classes: Vec<String>,
+    /// Plain text from the JSON `tooltip` path, or the rendered
+    /// `tooltip-format` markup when that is set.
+    tooltip: Option<String>,
     visible: bool,
+    /// Set on output restored from the on-disk cache until the first run.
+    #[serde(skip)]
+    stale: bool,
 }
 
 /// Shared exec backend wrapping Broadcaster with signal-based refresh support.
@@ -212,17 +448,9 @@ fn exec_registry() -> &'static BackendRegistry<ExecSharedKey, SharedExecBackend>
 }
 
 fn subscribe_shared_exec_output(
-    command: String,
-    format: String,
-    interval_secs: u32,
+    key: ExecSharedKey,
     signal: Option<i32>,
 ) -> Subscription<ExecRenderedOutput> {
-    let key = ExecSharedKey {
-        command,
-        format,
-        interval_secs,
-    };
-
     let (backend, start_worker) =
         exec_registry().get_or_create(key.clone(), SharedExecBackend::new);
 
@@ -243,18 +471,61 @@ fn start_shared_exec_worker(key: ExecSharedKey, backend: Arc<SharedExecBackend>)
     let (refresh_sender, refresh_receiver) = std::sync::mpsc::channel::<()>();
     backend.set_refresh_sender(refresh_sender);
 
-    std::thread::spawn(move || loop {
-        backend
-            .broadcaster
-            .broadcast(run_exec_command(&key.command, &key.format));
-        if backend.broadcaster.subscriber_count() == 0 {
-            exec_registry().remove(&key, &backend);
-            unregister_exec_backend_signals(&backend);
-            return;
-        }
-        match refresh_receiver.recv_timeout(Duration::from_secs(u64::from(key.interval_secs))) {
-            Ok(()) | Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
-            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+    let cache_path = if key.cache { key.cache_path() } else { None };
+    let mut cached = cache_path
+        .as_deref()
+        .and_then(output_cache::load::<ExecRenderedOutput>);
+    if let Some(cached) = &cached {
+        backend.broadcaster.broadcast(ExecRenderedOutput {
+            stale: true,
+            ..cached.clone()
+        });
+    } else if !key.interval.runs_at_startup() {
+        // Hidden until the first signal.
+        backend.broadcaster.broadcast(ExecRenderedOutput::default());
+    }
+
+    std::thread::spawn(move || {
+        let error_backend = format!("{MODULE_TYPE} {}", key.command.label());
+        let mut run_now = key.interval.runs_at_startup();
+        loop {
+            if run_now {
+                let (output, failure) = run_exec_command(
+                    &key.command,
+                    &key.format,
+                    key.tooltip_format.as_deref(),
+                    key.json_paths.as_ref(),
+                );
+                match failure {
+                    Some(failure) => backend_errors::report(&error_backend, failure),
+                    None => backend_errors::clear(&error_backend),
+                }
+                if let Some(path) = &cache_path {
+                    if cached.as_ref() != Some(&output) {
+                        if let Err(err) = output_cache::store(path, &output) {
+                            eprintln!("vibar/exec: failed to cache output: {err}");
+                        }
+                        cached = Some(output.clone());
+                    }
+                }
+                backend.broadcaster.broadcast(output);
+            }
+            if backend.broadcaster.subscriber_count() == 0 {
+                exec_registry().remove(&key, &backend);
+                unregister_exec_backend_signals(&backend);
+                backend_errors::clear(&error_backend);
+                return;
+            }
+            let period = key.interval.period();
+            run_now = match refresh_receiver.recv_timeout(period.unwrap_or(UNSCHEDULED_IDLE_CHECK))
+            {
+                Ok(()) => true,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => period.is_some(),
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                    backend_errors::clear(&error_backend);
+                    return;
+                }
+            };
         }
     });
 }
@@ -402,30 +673,102 @@ fn drain_exec_signal_pipe(read_fd: i32) {
     }
 }
 
-fn run_exec_command(command: &str, format: &str) -> ExecRenderedOutput {
-    match Command::new("sh").arg("-c").arg(command).output() {
+/// Runs `command` once and renders its output with `format`; a
+/// `tooltip_format` renders the same output a second time into `tooltip`.
+/// Also returns the failure to log if it could not start; a non-zero exit
+/// status is not a failure, since status checks exit 1 on purpose.
+fn run_exec_command(
+    command: &ExecCommand,
+    format: &str,
+    tooltip_format: Option<&str>,
+    json_paths: Option<&ExecJsonPaths>,
+) -> (ExecRenderedOutput, Option<String>) {
+    match Command::new("sh").arg("-c").arg(&command.line).output() {
         Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-            if !stdout.trim().is_empty() {
-                parse_exec_output(&stdout, format)
-            } else if !stderr.trim().is_empty() {
-                apply_exec_format(
-                    stderr.trim().to_string(),
-                    Vec::new(),
-                    HashMap::new(),
-                    format,
-                )
-            } else {
-                ExecRenderedOutput::default()
-            }
+            // Scripts may echo their arguments, and shell errors quote the
+            // command line.
+            let stdout = command.sanitize(&String::from_utf8_lossy(&output.stdout));
+            let stderr = command.sanitize(String::from_utf8_lossy(&output.stderr).trim());
+
+            let render = |format: &str| {
+                if !stdout.trim().is_empty() {
+                    match json_paths {
+                        Some(json_paths) => {
+                            parse_json_path_exec_output(&stdout, format, json_paths)
+                        }
+                        None => parse_exec_output(&stdout, format),
+                    }
+                } else if !stderr.is_empty() {
+                    apply_exec_format(stderr.clone(), Vec::new(), HashMap::new(), format)
+                } else {
+                    ExecRenderedOutput::default()
+                }
+            };
+            let rendered = render(format);
+            let rendered = match tooltip_format {
+                Some(tooltip_format) if rendered.visible => ExecRenderedOutput {
+                    tooltip: Some(render(tooltip_format).text),
+                    ..rendered
+                },
+                _ => rendered,
+            };
+            (rendered, None)
+        }
+        Err(err) => {
+            let err = command.sanitize(&err.to_string());
+            (
+                exec_error_output(&format!("exec error: {err}")),
+                Some(format!("failed to start: {err}")),
+            )
         }
-        Err(err) => ExecRenderedOutput {
-            text: escape_markup_text(&format!("exec error: {err}")),
-            classes: Vec::new(),
-            visible: true,
-        },
+    }
+}
+
+fn exec_error_output(message: &str) -> ExecRenderedOutput {
+    ExecRenderedOutput {
+        text: escape_markup_text(message),
+        classes: Vec::new(),
+        tooltip: None,
+        visible: true,
+        stale: false,
+    }
+}
+
+/// Renders output through `json-path`: the text path fills `{text}`, the
+/// optional class/tooltip paths set CSS classes and the tooltip, and
+/// top-level scalars stay available as `{property}`.
+fn parse_json_path_exec_output(
+    raw: &str,
+    format: &str,
+    json_paths: &ExecJsonPaths,
+) -> ExecRenderedOutput {
+    let value = match serde_json::from_str::<Value>(raw.trim()) {
+        Ok(value) => value,
+        Err(err) => return exec_error_output(&format!("exec json-path: invalid JSON: {err}")),
+    };
+
+    let text = json_paths
+        .text
+        .select(&value)
+        .map(value_to_text)
+        .unwrap_or_default();
+    let classes = json_paths
+        .class
+        .as_ref()
+        .and_then(|path| path.select(&value))
+        .map(parse_json_classes)
+        .unwrap_or_default();
+    let tooltip = json_paths
+        .tooltip
+        .as_ref()
+        .and_then(|path| path.select(&value))
+        .map(value_to_text)
+        .filter(|tooltip| !tooltip.is_empty());
+    let vars = parse_json_format_vars(&value);
+
+    ExecRenderedOutput {
+        tooltip,
+        ..apply_exec_format(text, classes, vars, format)
     }
 }
 
@@ -533,7 +876,9 @@ fn apply_exec_format(
     ExecRenderedOutput {
         text: rendered,
         classes,
+        tooltip: None,
         visible,
+        stale: false,
     }
 }
 
@@ -585,6 +930,31 @@ mod tests {
         assert_eq!(on_click_cfg.on_click.as_deref(), Some("bar"));
     }
 
+    #[test]
+    fn parse_config_supports_scroll_text_keys() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "command": "echo ok",
+                "max_width": 30,
+                "scroll_text": "hover"
+            }))
+            .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.max_width, Some(30));
+        assert_eq!(cfg.scroll_text, ScrollTextMode::Hover);
+
+        let default_module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({ "command": "echo ok" }))
+                .expect("module config map should parse"),
+        );
+        let default_cfg = parse_config(&default_module).expect("config should parse");
+        assert_eq!(default_cfg.max_width, None);
+        assert_eq!(default_cfg.scroll_text, ScrollTextMode::Off);
+    }
+
     #[test]
     fn parse_config_supports_signal_field() {
         let module = ModuleConfig::new(
@@ -599,6 +969,84 @@ mod tests {
         assert_eq!(cfg.signal, Some(8));
     }
 
+    #[test]
+    fn parse_config_supports_interval_modes() {
+        let parse = |value: Value| {
+            parse_config(&ModuleConfig::new(
+                MODULE_TYPE,
+                serde_json::from_value(value).expect("module config map should parse"),
+            ))
+        };
+
+        let cfg = parse(json!({ "command": "uname -r", "interval": "once" }))
+            .expect("once interval should parse");
+        assert_eq!(cfg.interval, ExecInterval::Mode(ExecIntervalMode::Once));
+        assert!(cfg.interval.runs_at_startup());
+        assert_eq!(cfg.interval.period(), None);
+
+        let cfg = parse(json!({ "command": "date", "interval_secs": "signal", "signal": 3 }))
+            .expect("signal interval should parse");
+        assert!(!cfg.interval.runs_at_startup());
+
+        let err = parse(json!({ "command": "date", "interval_secs": "signal" }))
+            .expect_err("signal interval without signal should fail");
+        assert!(err.contains("requires `signal`"));
+
+        let err = parse(json!({ "command": "date", "interval": "once", "interval_secs": 5 }))
+            .expect_err("both interval keys should fail");
+        assert!(err.contains("`interval_secs` and `interval` are the same option"));
+
+        let cfg = parse(json!({ "command": "date", "interval_secs": 0 }))
+            .expect("numeric interval should parse");
+        assert_eq!(
+            normalized_exec_interval(cfg.interval),
+            ExecInterval::Secs(MIN_EXEC_INTERVAL_SECS)
+        );
+    }
+
+    #[test]
+    fn parse_config_supports_cache_flag() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({ "command": "echo ok", "cache": true }))
+                .expect("module config map should parse"),
+        );
+        assert!(parse_config(&module).expect("config should parse").cache);
+
+        let default_module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({ "command": "echo ok" }))
+                .expect("module config map should parse"),
+        );
+        assert!(
+            !parse_config(&default_module)
+                .expect("config should parse")
+                .cache
+        );
+    }
+
+    #[test]
+    fn cached_output_round_trips_without_stale_flag() {
+        let output = ExecRenderedOutput {
+            text: "42".to_string(),
+            classes: vec!["ok".to_string()],
+            tooltip: Some("tip".to_string()),
+            visible: true,
+            stale: true,
+        };
+        let raw = serde_json::to_string(&output).expect("output should serialize");
+        assert!(!raw.contains("stale"));
+        let restored: ExecRenderedOutput =
+            serde_json::from_str(&raw).expect("output should deserialize");
+        assert_eq!(
+            restored,
+            ExecRenderedOutput {
+                stale: false,
+                ..output
+            }
+        );
+    }
+
     #[test]
     fn normalize_exec_signal_accepts_none() {
         assert_eq!(
@@ -629,9 +1077,22 @@ mod tests {
         assert!(err.contains("`signal` must be <="));
     }
 
+    fn command(line: &str) -> ExecCommand {
+        ExecCommand {
+            line: line.to_string(),
+            redact: false,
+        }
+    }
+
     #[test]
     fn run_exec_command_prefers_stdout() {
-        let output = run_exec_command("printf 'out'; printf 'err' >&2", "{text}");
+        let (output, failure) = run_exec_command(
+            &command("printf 'out'; printf 'err' >&2"),
+            "{text}",
+            None,
+            None,
+        );
+        assert!(failure.is_none());
         assert_eq!(output.text, "out");
         assert!(output.classes.is_empty());
         assert!(output.visible);
@@ -639,7 +1100,7 @@ mod tests {
 
     #[test]
     fn run_exec_command_falls_back_to_stderr() {
-        let output = run_exec_command("printf 'err-only' >&2", "{text}");
+        let (output, _) = run_exec_command(&command("printf 'err-only' >&2"), "{text}", None, None);
         assert_eq!(output.text, "err-only");
         assert!(output.classes.is_empty());
         assert!(output.visible);
@@ -647,12 +1108,67 @@ mod tests {
 
     #[test]
     fn run_exec_command_hides_when_output_is_empty() {
-        let output = run_exec_command("printf ''", "{text}");
+        let (output, _) = run_exec_command(&command("printf ''"), "{text}", None, None);
         assert_eq!(output.text, "");
         assert!(output.classes.is_empty());
         assert!(!output.visible);
     }
 
+    #[test]
+    fn run_exec_command_renders_tooltip_format_from_same_output() {
+        let (output, _) = run_exec_command(
+            &command(r#"printf '{"text":"42","alt":"<b>"}'"#),
+            "{text}",
+            Some("{alt}: {text}"),
+            None,
+        );
+        assert_eq!(output.text, "42");
+        assert_eq!(output.tooltip.as_deref(), Some("&lt;b&gt;: 42"));
+
+        let (hidden, _) = run_exec_command(&command("printf ''"), "{text}", Some("tip"), None);
+        assert_eq!(hidden.tooltip, None);
+    }
+
+    #[test]
+    fn run_exec_command_masks_redacted_failing_command() {
+        let line = "echo \"bad token s3cr3t-token\" >&2; exit 3 # s3cr3t-token";
+        let (output, failure) = run_exec_command(&command(line), "{text}", None, None);
+        assert_eq!(output.text, "bad token s3cr3t-token");
+        assert!(failure.is_none());
+
+        let redacted = ExecCommand {
+            redact: true,
+            ..command(line)
+        };
+        let (output, failure) = run_exec_command(&redacted, "{text}", None, None);
+        assert_eq!(output.text, "bad token &lt;redacted&gt;");
+        assert!(failure.is_none());
+
+        let redacted = ExecCommand {
+            redact: true,
+            ..command("echo \"token s3cr3t-token\"; exit 1")
+        };
+        let (output, failure) = run_exec_command(&redacted, "{text}", Some("tip {text}"), None);
+        assert_eq!(output.text, "token &lt;redacted&gt;");
+        assert_eq!(
+            output.tooltip.as_deref(),
+            Some("tip token &lt;redacted&gt;")
+        );
+        assert!(failure.is_none());
+    }
+
+    #[test]
+    fn exec_command_label_never_contains_the_command() {
+        let line = "curl -H 'Authorization: s3cr3t' https://example.com";
+        let plain = command(line);
+        let redacted = ExecCommand {
+            redact: true,
+            ..command(line)
+        };
+        assert!(!plain.label().contains("s3cr3t"));
+        assert_eq!(plain.label(), redacted.label());
+    }
+
     #[test]
     fn parse_exec_output_supports_i3blocks_style_class_line() {
         let output = parse_exec_output("42%\n\nmedium", "{text}");
@@ -712,6 +1228,77 @@ mod tests {
         assert!(!output.visible);
     }
 
+    #[test]
+    fn parse_config_supports_json_path_forms() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "command": "weather --json",
+                "json-path": "$.data.temp"
+            }))
+            .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("json-path string should parse");
+        let paths = cfg
+            .json_path
+            .expect("json-path should be set")
+            .parse()
+            .expect("json-path should be valid");
+        assert_eq!(
+            paths.text,
+            JsonPath::parse("$.data.temp").expect("valid path")
+        );
+        assert!(paths.class.is_none() && paths.tooltip.is_none());
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "command": "weather --json",
+                "json_path": { "class": "$.state" }
+            }))
+            .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("json-path map should parse");
+        let paths = cfg
+            .json_path
+            .expect("json-path should be set")
+            .parse()
+            .expect("json-path should be valid");
+        assert_eq!(paths.text, JsonPath::parse("$.text").expect("valid path"));
+        assert!(paths.class.is_some());
+
+        let invalid = ExecJsonPathConfig::Text("$.a[".to_string());
+        assert!(invalid.parse().is_err());
+    }
+
+    #[test]
+    fn parse_json_path_exec_output_maps_text_class_and_tooltip() {
+        let paths = ExecJsonPathConfig::Fields {
+            text: Some("$.data.temp".to_string()),
+            class: Some("$.data.states".to_string()),
+            tooltip: Some("$.data.summary".to_string()),
+        }
+        .parse()
+        .expect("json-path should be valid");
+        let output = parse_json_path_exec_output(
+            r#"{"city":"Oslo","data":{"temp":-3.5,"states":["cold","snow"],"summary":"Light snow"}}"#,
+            "{city} {text}°",
+            &paths,
+        );
+        assert_eq!(output.text, "Oslo -3.5°");
+        assert_eq!(output.classes, vec!["cold", "snow"]);
+        assert_eq!(output.tooltip.as_deref(), Some("Light snow"));
+        assert!(output.visible);
+
+        let missing = parse_json_path_exec_output(r#"{"data":{}}"#, "{text}", &paths);
+        assert!(!missing.visible);
+        assert!(missing.tooltip.is_none());
+
+        let not_json = parse_json_path_exec_output("plain text", "{text}", &paths);
+        assert!(not_json.text.contains("invalid JSON"));
+        assert!(not_json.visible);
+    }
+
     #[test]
     fn shared_exec_backend_broadcasts_to_all_subscribers() {
         let broadcaster = Broadcaster::new();
@@ -721,7 +1308,9 @@ mod tests {
         broadcaster.broadcast(ExecRenderedOutput {
             text: "42".to_string(),
             classes: vec!["ok".to_string()],
+            tooltip: None,
             visible: true,
+            stale: false,
         });
 
         assert_eq!(
@@ -732,7 +1321,9 @@ mod tests {
             ExecRenderedOutput {
                 text: "42".to_string(),
                 classes: vec!["ok".to_string()],
+                tooltip: None,
                 visible: true,
+                stale: false,
             }
         );
         assert_eq!(
@@ -743,7 +1334,9 @@ mod tests {
             ExecRenderedOutput {
                 text: "42".to_string(),
                 classes: vec!["ok".to_string()],
+                tooltip: None,
                 visible: true,
+                stale: false,
             }
         );
     }
@@ -754,7 +1347,9 @@ mod tests {
         broadcaster.broadcast(ExecRenderedOutput {
             text: "latest".to_string(),
             classes: vec!["cached".to_string()],
+            tooltip: None,
             visible: true,
+            stale: false,
         });
 
         let sub = broadcaster.subscribe();
@@ -766,7 +1361,9 @@ mod tests {
             ExecRenderedOutput {
                 text: "latest".to_string(),
                 classes: vec!["cached".to_string()],
+                tooltip: None,
                 visible: true,
+                stale: false,
             }
         );
     }
@@ -785,7 +1382,9 @@ mod tests {
         broadcaster.broadcast(ExecRenderedOutput {
             text: "x".to_string(),
             classes: Vec::new(),
+            tooltip: None,
             visible: true,
+            stale: false,
         });
 
         assert_eq!(broadcaster.subscriber_count(), 1);
@@ -0,0 +1,35 @@
+//! Blocking readiness wait for the udev and netlink sockets that backend
+//! workers watch between refreshes.
+
+/// Waits up to `timeout_millis` for `fd` to become readable; `Ok(false)` on
+/// timeout.
+pub(crate) fn wait_for_readable_fd(fd: i32, timeout_millis: u64) -> Result<bool, String> {
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+
+    let timeout_millis = timeout_millis.min(i32::MAX as u64) as i32;
+
+    loop {
+        // SAFETY: we pass a valid pointer to one pollfd entry and a correct count.
+        let rc = unsafe { libc::poll(&mut pollfd, 1, timeout_millis) };
+        if rc > 0 {
+            if (pollfd.revents & libc::POLLIN) != 0 {
+                return Ok(true);
+            }
+            return Err(format!("unexpected poll events: {}", pollfd.revents));
+        }
+
+        if rc == 0 {
+            return Ok(false);
+        }
+
+        let err = std::io::Error::last_os_error();
+        if err.kind() == std::io::ErrorKind::Interrupted {
+            continue;
+        }
+        return Err(format!("poll failed: {err}"));
+    }
+}
@@ -0,0 +1,265 @@
+//! Focus mode: hides the modules listed in the top-level `focus.hide`
+//! config and optionally turns on notification do-not-disturb.
+//!
+//! Every built module registers its widget here. While focus mode is on,
+//! listed widgets are kept hidden, and the visibility a module asks for in
+//! the meantime is remembered and restored when focus mode ends. The
+//! `focus` module shows and toggles the state; `vibar focus` does the same
+//! from the command line.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use gtk::glib::WeakRef;
+use gtk::prelude::*;
+use gtk::{GestureClick, Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::icons::Glyph;
+use crate::modules::notifications::{self, NotificationsBackendKind};
+use crate::modules::{ModuleBuildContext, ModuleConfig, ModuleFactory, ModuleLabel};
+
+const DEFAULT_FORMAT: Glyph = Glyph::new("\u{f06e}", "◎");
+const DEFAULT_FORMAT_ACTIVE: Glyph = Glyph::new("\u{f070}", "◉");
+const NAME_KEY: &str = "name";
+pub(crate) const MODULE_TYPE: &str = "focus";
+
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq)]
+pub(crate) struct FocusConfig {
+    /// Module types or `name`s hidden while focus mode is on.
+    #[serde(default)]
+    pub(crate) hide: Vec<String>,
+    /// Notification backend whose do-not-disturb follows focus mode.
+    #[serde(default)]
+    pub(crate) dnd: Option<NotificationsBackendKind>,
+}
+
+impl FocusConfig {
+    fn hides(&self, module_type: &str, name: Option<&str>) -> bool {
+        self.hide
+            .iter()
+            .any(|entry| entry == module_type || Some(entry.as_str()) == name)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct FocusModuleConfig {
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(rename = "format-active", alias = "format_active", default)]
+    format_active: Option<String>,
+    #[serde(default)]
+    class: Option<String>,
+}
+
+type FocusListener = Rc<dyn Fn(&Widget)>;
+
+#[derive(Default)]
+struct FocusState {
+    config: FocusConfig,
+    active: bool,
+    listeners: Vec<(WeakRef<Widget>, FocusListener)>,
+}
+
+thread_local! {
+    static STATE: RefCell<FocusState> = RefCell::new(FocusState::default());
+}
+
+/// Applies the top-level `focus` config. Widgets are re-evaluated right
+/// away, so a reload can change the hidden set while focus mode is on.
+pub(crate) fn configure(config: &FocusConfig) {
+    let changed = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let changed = state.config != *config;
+        state.config = config.clone();
+        changed
+    });
+    if changed {
+        notify_listeners();
+    }
+}
+
+pub(crate) fn is_active() -> bool {
+    STATE.with(|state| state.borrow().active)
+}
+
+/// Turns focus mode on or off; `None` toggles it.
+pub(crate) fn set_active(active: Option<bool>) {
+    let (active, dnd) = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let active = active.unwrap_or(!state.active);
+        let changed = state.active != active;
+        state.active = active;
+        (active, state.config.dnd.filter(|_| changed))
+    });
+    if let Some(kind) = dnd {
+        notifications::set_dnd(kind, active);
+    }
+    notify_listeners();
+}
+
+fn hides(module_type: &str, name: Option<&str>) -> bool {
+    STATE.with(|state| {
+        let state = state.borrow();
+        state.active && state.config.hides(module_type, name)
+    })
+}
+
+/// Runs `listener` for `widget` now and on every focus change, for as long
+/// as the widget lives.
+fn watch(widget: &Widget, listener: impl Fn(&Widget) + 'static) {
+    listener(widget);
+    STATE.with(|state| {
+        state
+            .borrow_mut()
+            .listeners
+            .push((widget.downgrade(), Rc::new(listener)));
+    });
+}
+
+fn notify_listeners() {
+    // Listeners read the state themselves, so it must not stay borrowed.
+    let listeners = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state
+            .listeners
+            .retain(|(widget, _)| widget.upgrade().is_some());
+        state
+            .listeners
+            .iter()
+            .filter_map(|(widget, listener)| Some((widget.upgrade()?, listener.clone())))
+            .collect::<Vec<_>>()
+    });
+    for (widget, listener) in listeners {
+        listener(&widget);
+    }
+}
+
+/// Lets focus mode hide the module `widget` was built for.
+pub(crate) fn register_module(widget: &Widget, config: &ModuleConfig) {
+    let module_type = config.module_type.clone();
+    let name = config
+        .config
+        .get(NAME_KEY)
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    // What the module itself wants; focus mode only ever hides on top.
+    let wanted = Rc::new(Cell::new(widget.is_visible()));
+    let forcing = Rc::new(Cell::new(false));
+
+    {
+        let module_type = module_type.clone();
+        let name = name.clone();
+        let wanted = wanted.clone();
+        let forcing = forcing.clone();
+        widget.connect_visible_notify(move |widget| {
+            if forcing.get() {
+                return;
+            }
+            wanted.set(widget.is_visible());
+            if widget.is_visible() && hides(&module_type, name.as_deref()) {
+                forcing.set(true);
+                widget.set_visible(false);
+                forcing.set(false);
+            }
+        });
+    }
+    watch(widget, move |widget| {
+        let visible = wanted.get() && !hides(&module_type, name.as_deref());
+        if widget.is_visible() != visible {
+            forcing.set(true);
+            widget.set_visible(visible);
+            forcing.set(false);
+        }
+    });
+}
+
+pub(crate) struct FocusFactory;
+
+pub(crate) const FACTORY: FocusFactory = FocusFactory;
+
+impl ModuleFactory for FocusFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_focus_module(parsed).upcast())
+    }
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<FocusModuleConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn build_focus_module(config: FocusModuleConfig) -> Label {
+    let label = ModuleLabel::new(MODULE_TYPE)
+        .with_css_classes(config.class.as_deref())
+        .into_label();
+    label.add_css_class("clickable");
+
+    let click = GestureClick::builder().button(1).build();
+    click.connect_released(|_, _, _, _| set_active(None));
+    label.add_controller(click);
+
+    let format = config
+        .format
+        .unwrap_or_else(|| DEFAULT_FORMAT.get().to_string());
+    let format_active = config
+        .format_active
+        .unwrap_or_else(|| DEFAULT_FORMAT_ACTIVE.get().to_string());
+    watch(label.upcast_ref(), move |widget| {
+        let Some(label) = widget.downcast_ref::<Label>() else {
+            return;
+        };
+        let active = is_active();
+        label.set_markup(if active { &format_active } else { &format });
+        if active {
+            label.add_css_class("focus-active");
+        } else {
+            label.remove_css_class("focus-active");
+        }
+    });
+
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn focus_config_matches_types_and_names() {
+        let config: FocusConfig = serde_json::from_value(json!({
+            "hide": ["playerctl", "work-mail"],
+            "dnd": "mako"
+        }))
+        .expect("focus config should parse");
+
+        assert!(config.hides("playerctl", None));
+        assert!(config.hides("exec", Some("work-mail")));
+        assert!(!config.hides("exec", Some("weather")));
+        assert!(!config.hides("clock", None));
+        assert_eq!(config.dnd, Some(NotificationsBackendKind::Mako));
+    }
+
+    #[test]
+    fn focus_config_defaults_to_hiding_nothing() {
+        let config: FocusConfig =
+            serde_json::from_value(json!({})).expect("focus config should parse");
+        assert!(config.hide.is_empty());
+        assert_eq!(config.dnd, None);
+    }
+}
@@ -0,0 +1,663 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::cpu::usage_css_class;
+use crate::modules::memory::format_bytes;
+use crate::modules::temperature::{find_hwmon_input, numbered_entries, read_temperature_reading};
+use crate::modules::text::{attach_text_subscription, visible_block, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
+use crate::modules::{
+    escape_markup_text, render_markup_template, state_store, ModuleBuildContext, ModuleConfig,
+    ModuleLabel,
+};
+
+use super::ModuleFactory;
+
+const MIN_GPU_INTERVAL_SECS: u32 = 1;
+const DEFAULT_GPU_INTERVAL_SECS: u32 = 5;
+const DEFAULT_GPU_FORMAT: &str = "{usage}%";
+const DRM_ROOT: &str = "/sys/class/drm";
+const AMD_PCI_VENDOR: &str = "0x1002";
+const NVIDIA_PCI_VENDOR: &str = "0x10de";
+const NVIDIA_SMI_QUERY: &str =
+    "--query-gpu=utilization.gpu,memory.used,memory.total,temperature.gpu";
+const GPU_USAGE_CLASSES: [&str; 5] = [
+    "usage-low",
+    "usage-medium",
+    "usage-high",
+    "usage-critical",
+    "usage-unknown",
+];
+const GPU_VENDOR_CLASSES: [&str; 2] = ["gpu-amd", "gpu-nvidia"];
+pub(crate) const MODULE_TYPE: &str = "gpu";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct GpuConfig {
+    #[serde(default)]
+    pub(crate) format: Option<String>,
+    #[serde(default)]
+    pub(crate) vendor: GpuVendor,
+    /// DRM card name, for example `card1`; defaults to the first AMD or
+    /// NVIDIA card.
+    #[serde(default)]
+    pub(crate) card: Option<String>,
+    #[serde(default = "default_gpu_interval")]
+    pub(crate) interval_secs: u32,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum GpuVendor {
+    #[default]
+    Auto,
+    Amd,
+    Nvidia,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GpuKind {
+    Amd,
+    Nvidia,
+}
+
+impl GpuKind {
+    fn from_pci_vendor(vendor: &str) -> Option<Self> {
+        match vendor.trim() {
+            AMD_PCI_VENDOR => Some(Self::Amd),
+            NVIDIA_PCI_VENDOR => Some(Self::Nvidia),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Amd => "AMD",
+            Self::Nvidia => "NVIDIA",
+        }
+    }
+
+    fn css_class(self) -> &'static str {
+        match self {
+            Self::Amd => "gpu-amd",
+            Self::Nvidia => "gpu-nvidia",
+        }
+    }
+
+    fn matches(self, vendor: GpuVendor) -> bool {
+        match vendor {
+            GpuVendor::Auto => true,
+            GpuVendor::Amd => self == Self::Amd,
+            GpuVendor::Nvidia => self == Self::Nvidia,
+        }
+    }
+}
+
+/// A detected GPU. NVIDIA cards without a DRM node (no `nvidia-drm`) have
+/// neither `card` nor `device_dir`, and `nvidia-smi` picks its first GPU.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GpuDevice {
+    kind: GpuKind,
+    card: Option<String>,
+    device_dir: Option<PathBuf>,
+}
+
+impl GpuDevice {
+    /// PCI address (`0000:01:00.0`), which `nvidia-smi --id` accepts.
+    fn pci_address(&self) -> Option<String> {
+        let device = fs::canonicalize(self.device_dir.as_ref()?).ok()?;
+        Some(device.file_name()?.to_str()?.to_string())
+    }
+}
+
+/// One reading; values the driver does not report are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct GpuStats {
+    usage: Option<u8>,
+    vram_used_bytes: Option<u64>,
+    vram_total_bytes: Option<u64>,
+    temperature_c: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct GpuUpdate {
+    text: String,
+    tooltip: String,
+    usage_class: &'static str,
+    vendor_class: Option<&'static str>,
+    published: state_store::PublishedValues,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct GpuSharedKey {
+    format: String,
+    tooltip_format: Option<String>,
+    vendor: GpuVendor,
+    card: Option<String>,
+    interval_secs: u32,
+}
+
+pub(crate) struct GpuFactory;
+
+pub(crate) const FACTORY: GpuFactory = GpuFactory;
+
+impl ModuleFactory for GpuFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_gpu_module(
+            parsed,
+            context.tooltip_format.clone(),
+            context.published_namespace(MODULE_TYPE),
+        )
+        .upcast())
+    }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        let parsed = parse_config(config)?;
+        let subscription = subscribe_shared_gpu(shared_key(&parsed, None));
+        attach_text_subscription(subscription, sink, |update| {
+            state_store::publish(MODULE_TYPE, &update.published);
+            visible_block(&update.text)
+                .map(|block| block.urgent(update.usage_class == "usage-critical"))
+        });
+        Ok(())
+    }
+}
+
+fn default_gpu_interval() -> u32 {
+    DEFAULT_GPU_INTERVAL_SECS
+}
+
+pub(crate) fn parse_config(module: &ModuleConfig) -> Result<GpuConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+pub(crate) fn normalized_gpu_interval(interval_secs: u32) -> u32 {
+    interval_secs.max(MIN_GPU_INTERVAL_SECS)
+}
+
+fn shared_key(config: &GpuConfig, tooltip_format: Option<String>) -> GpuSharedKey {
+    GpuSharedKey {
+        format: config
+            .format
+            .clone()
+            .unwrap_or_else(|| DEFAULT_GPU_FORMAT.to_string()),
+        tooltip_format,
+        vendor: config.vendor,
+        card: config.card.clone(),
+        interval_secs: normalized_gpu_interval(config.interval_secs),
+    }
+}
+
+fn gpu_registry() -> &'static BackendRegistry<GpuSharedKey, Broadcaster<GpuUpdate>> {
+    static REGISTRY: OnceLock<BackendRegistry<GpuSharedKey, Broadcaster<GpuUpdate>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_gpu(key: GpuSharedKey) -> Subscription<GpuUpdate> {
+    let (broadcaster, start_worker) = gpu_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_gpu_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_gpu_worker(key: GpuSharedKey, broadcaster: Arc<Broadcaster<GpuUpdate>>) {
+    let interval = Duration::from_secs(u64::from(key.interval_secs));
+    std::thread::spawn(move || loop {
+        let reading = detect_gpu(Path::new(DRM_ROOT), key.vendor, key.card.as_deref())
+            .and_then(|device| read_gpu_stats(&device).map(|stats| (device, stats)));
+        let update = match reading {
+            Ok((device, stats)) => GpuUpdate {
+                text: render_format(&key.format, &device, &stats),
+                tooltip: key
+                    .tooltip_format
+                    .as_deref()
+                    .map(|format| render_format(format, &device, &stats))
+                    .unwrap_or_default(),
+                usage_class: stats
+                    .usage
+                    .map(|usage| usage_css_class(f64::from(usage)))
+                    .unwrap_or("usage-unknown"),
+                vendor_class: Some(device.kind.css_class()),
+                published: stats_values(&stats),
+            },
+            Err(err) => GpuUpdate {
+                text: escape_markup_text(&format!("gpu error: {err}")),
+                tooltip: String::new(),
+                usage_class: "usage-unknown",
+                vendor_class: None,
+                published: Vec::new(),
+            },
+        };
+
+        broadcaster.broadcast(update);
+        if broadcaster.subscriber_count() == 0 {
+            gpu_registry().remove(&key, &broadcaster);
+            return;
+        }
+        std::thread::sleep(interval);
+    });
+}
+
+fn build_gpu_module(config: GpuConfig, tooltip_format: Option<String>, namespace: String) -> Label {
+    let key = shared_key(&config, tooltip_format);
+    let label = ModuleLabel::new("gpu")
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(config.click.or(config.on_click))
+        .into_label();
+
+    if key.interval_secs != config.interval_secs {
+        eprintln!(
+            "gpu interval_secs={} is too low; clamping to {} second",
+            config.interval_secs, key.interval_secs
+        );
+    }
+
+    let tooltip = key
+        .tooltip_format
+        .is_some()
+        .then(|| ModuleTooltip::attach(&label));
+    let subscription = subscribe_shared_gpu(key);
+
+    attach_subscription(&label, subscription, move |label, update| {
+        state_store::publish(&namespace, &update.published);
+        let visible = !update.text.trim().is_empty();
+        label.set_visible(visible);
+        if visible {
+            label.set_markup(&update.text);
+        }
+        if let Some(tooltip) = &tooltip {
+            tooltip.set_markup(&update.tooltip);
+        }
+        for class_name in GPU_USAGE_CLASSES.iter().chain(&GPU_VENDOR_CLASSES) {
+            label.remove_css_class(class_name);
+        }
+        label.add_css_class(update.usage_class);
+        if let Some(vendor_class) = update.vendor_class {
+            label.add_css_class(vendor_class);
+        }
+    });
+
+    label
+}
+
+/// The first `cardN` under `drm_root` from a supported vendor, narrowed by
+/// `vendor` and `card`.
+fn detect_gpu(drm_root: &Path, vendor: GpuVendor, card: Option<&str>) -> Result<GpuDevice, String> {
+    for card_dir in numbered_entries(drm_root, "card", "") {
+        let name = card_dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_string();
+        if card.is_some_and(|card| card != name) {
+            continue;
+        }
+        let device_dir = card_dir.join("device");
+        let Some(kind) = fs::read_to_string(device_dir.join("vendor"))
+            .ok()
+            .and_then(|vendor| GpuKind::from_pci_vendor(&vendor))
+        else {
+            continue;
+        };
+        if kind.matches(vendor) {
+            return Ok(GpuDevice {
+                kind,
+                card: Some(name),
+                device_dir: Some(device_dir),
+            });
+        }
+    }
+
+    if vendor == GpuVendor::Nvidia && card.is_none() {
+        return Ok(GpuDevice {
+            kind: GpuKind::Nvidia,
+            card: None,
+            device_dir: None,
+        });
+    }
+    Err(match card {
+        Some(card) => format!("no supported GPU at {}/{card}", drm_root.display()),
+        None => format!("no AMD or NVIDIA GPU under {}", drm_root.display()),
+    })
+}
+
+fn read_gpu_stats(device: &GpuDevice) -> Result<GpuStats, String> {
+    // Querying a runtime-suspended card (the idle dGPU of a hybrid laptop)
+    // wakes it up, so it reads as no stats until something else resumes it.
+    if device
+        .device_dir
+        .as_deref()
+        .is_some_and(is_runtime_suspended)
+    {
+        return Ok(GpuStats::default());
+    }
+    match (device.kind, &device.device_dir) {
+        (GpuKind::Amd, Some(device_dir)) => read_amd_stats(device_dir),
+        (GpuKind::Amd, None) => Err("AMD GPU without a DRM device".to_string()),
+        (GpuKind::Nvidia, _) => read_nvidia_stats(device.pci_address().as_deref()),
+    }
+}
+
+fn is_runtime_suspended(device_dir: &Path) -> bool {
+    fs::read_to_string(device_dir.join("power/runtime_status"))
+        .is_ok_and(|status| status.trim() == "suspended")
+}
+
+/// amdgpu sysfs: busy percentage, VRAM in bytes, and the hwmon `edge`
+/// sensor (or the first one).
+fn read_amd_stats(device_dir: &Path) -> Result<GpuStats, String> {
+    let read_number = |file: &str| -> Result<u64, String> {
+        let path = device_dir.join(file);
+        let raw = fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        raw.trim()
+            .parse()
+            .map_err(|err| format!("failed to parse '{}' as integer: {err}", raw.trim()))
+    };
+    let hwmon_dir = device_dir.join("hwmon");
+    let temperature_c = find_hwmon_input(&hwmon_dir, None, Some("edge"))
+        .or_else(|_| find_hwmon_input(&hwmon_dir, None, None))
+        .and_then(|(path, _)| read_temperature_reading(&path.to_string_lossy()))
+        .ok()
+        .map(|reading| reading.celsius);
+
+    Ok(GpuStats {
+        usage: Some(read_number("gpu_busy_percent")?.min(100) as u8),
+        vram_used_bytes: read_number("mem_info_vram_used").ok(),
+        vram_total_bytes: read_number("mem_info_vram_total").ok(),
+        temperature_c,
+    })
+}
+
+fn read_nvidia_stats(pci_address: Option<&str>) -> Result<GpuStats, String> {
+    let mut command = Command::new("nvidia-smi");
+    command.args([NVIDIA_SMI_QUERY, "--format=csv,noheader,nounits"]);
+    command.arg(format!("--id={}", pci_address.unwrap_or("0")));
+    let output = command
+        .output()
+        .map_err(|err| format!("failed to run nvidia-smi: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "nvidia-smi failed: {}",
+            String::from_utf8_lossy(&output.stdout).trim()
+        ));
+    }
+    parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parses one CSV line of [`NVIDIA_SMI_QUERY`]: usage %, used and total
+/// memory in MiB, temperature in °C. Unsupported fields read `[N/A]`.
+fn parse_nvidia_smi(output: &str) -> Result<GpuStats, String> {
+    let line = output
+        .lines()
+        .next()
+        .filter(|line| !line.trim().is_empty())
+        .ok_or_else(|| "nvidia-smi printed nothing".to_string())?;
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    if fields.len() != 4 {
+        return Err(format!("unexpected nvidia-smi output: '{line}'"));
+    }
+    let mib = |field: &str| field.parse::<u64>().ok().map(|mib| mib * 1024 * 1024);
+
+    Ok(GpuStats {
+        usage: fields[0].parse::<u8>().ok().map(|usage| usage.min(100)),
+        vram_used_bytes: mib(fields[1]),
+        vram_total_bytes: mib(fields[2]),
+        temperature_c: fields[3].parse().ok(),
+    })
+}
+
+fn render_format(format: &str, device: &GpuDevice, stats: &GpuStats) -> String {
+    let vram_percentage = stats
+        .vram_used_bytes
+        .zip(stats.vram_total_bytes.filter(|total| *total > 0))
+        .map(|(used, total)| ((used as f64 / total as f64) * 100.0).round().to_string());
+
+    render_markup_template(
+        format,
+        &[
+            (
+                "{usage}",
+                &stats
+                    .usage
+                    .map(|usage| usage.to_string())
+                    .unwrap_or_default(),
+            ),
+            (
+                "{vram_used}",
+                &stats.vram_used_bytes.map(format_bytes).unwrap_or_default(),
+            ),
+            (
+                "{vram_total}",
+                &stats.vram_total_bytes.map(format_bytes).unwrap_or_default(),
+            ),
+            ("{vram_percentage}", &vram_percentage.unwrap_or_default()),
+            (
+                "{temperature}",
+                &stats
+                    .temperature_c
+                    .map(|celsius| (celsius.round() as i32).to_string())
+                    .unwrap_or_default(),
+            ),
+            ("{vendor}", device.kind.name()),
+            ("{card}", device.card.as_deref().unwrap_or_default()),
+        ],
+    )
+}
+
+fn stats_values(stats: &GpuStats) -> state_store::PublishedValues {
+    let mut values = Vec::new();
+    if let Some(usage) = stats.usage {
+        values.push(("usage", usage.to_string()));
+    }
+    if let Some(celsius) = stats.temperature_c {
+        values.push(("temperature", (celsius.round() as i32).to_string()));
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use serde_json::Map;
+
+    use super::*;
+
+    fn test_root(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time should move forward")
+            .as_nanos();
+        env::temp_dir().join(format!("vibar-gpu-test-{name}-{nanos}"))
+    }
+
+    fn card(root: &Path, name: &str, vendor: &str, files: &[(&str, &str)]) -> PathBuf {
+        let device = root.join(name).join("device");
+        fs::create_dir_all(&device).expect("test card dir should create");
+        fs::write(device.join("vendor"), format!("{vendor}\n")).expect("vendor should write");
+        for (file, value) in files {
+            let path = device.join(file);
+            fs::create_dir_all(path.parent().expect("file has a parent"))
+                .expect("test dir should create");
+            fs::write(path, value).expect("test file should write");
+        }
+        device
+    }
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'gpu'"));
+    }
+
+    #[test]
+    fn parse_config_reads_vendor() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(serde_json::json!({ "vendor": "nvidia", "card": "card1" }))
+                .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.vendor, GpuVendor::Nvidia);
+        assert_eq!(cfg.card.as_deref(), Some("card1"));
+
+        let cfg =
+            parse_config(&ModuleConfig::new(MODULE_TYPE, Map::new())).expect("config should parse");
+        assert_eq!(cfg.vendor, GpuVendor::Auto);
+        assert_eq!(normalized_gpu_interval(0), 1);
+    }
+
+    #[test]
+    fn detect_gpu_picks_first_supported_card() {
+        let root = test_root("detect");
+        card(&root, "card0", "0x8086", &[]);
+        card(&root, "card1", NVIDIA_PCI_VENDOR, &[]);
+        card(&root, "card2", AMD_PCI_VENDOR, &[]);
+        fs::create_dir_all(root.join("card1-DP-1")).expect("connector dir should create");
+
+        let device = detect_gpu(&root, GpuVendor::Auto, None).expect("gpu found");
+        assert_eq!(device.kind, GpuKind::Nvidia);
+        assert_eq!(device.card.as_deref(), Some("card1"));
+
+        let device = detect_gpu(&root, GpuVendor::Amd, None).expect("amd found");
+        assert_eq!(device.card.as_deref(), Some("card2"));
+
+        assert!(detect_gpu(&root, GpuVendor::Auto, Some("card0")).is_err());
+        let empty = root.join("empty");
+        assert!(detect_gpu(&empty, GpuVendor::Auto, None).is_err());
+        let device = detect_gpu(&empty, GpuVendor::Nvidia, None).expect("nvidia-smi fallback");
+        assert_eq!(device.device_dir, None);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn read_gpu_stats_leaves_suspended_cards_asleep() {
+        let root = test_root("suspended");
+        card(
+            &root,
+            "card1",
+            NVIDIA_PCI_VENDOR,
+            &[("power/runtime_status", "suspended\n")],
+        );
+
+        let device = detect_gpu(&root, GpuVendor::Auto, None).expect("gpu found");
+        assert_eq!(read_gpu_stats(&device), Ok(GpuStats::default()));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn read_amd_stats_reads_sysfs() {
+        let root = test_root("amd");
+        let device = card(
+            &root,
+            "card0",
+            AMD_PCI_VENDOR,
+            &[
+                ("gpu_busy_percent", "37\n"),
+                ("mem_info_vram_used", "1073741824\n"),
+                ("mem_info_vram_total", "8589934592\n"),
+                ("hwmon/hwmon3/temp1_input", "48000\n"),
+                ("hwmon/hwmon3/temp1_label", "edge\n"),
+                ("hwmon/hwmon3/temp2_input", "61000\n"),
+                ("hwmon/hwmon3/temp2_label", "junction\n"),
+            ],
+        );
+
+        let stats = read_amd_stats(&device).expect("stats should read");
+        assert_eq!(
+            stats,
+            GpuStats {
+                usage: Some(37),
+                vram_used_bytes: Some(1 << 30),
+                vram_total_bytes: Some(8 << 30),
+                temperature_c: Some(48.0),
+            }
+        );
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn parse_nvidia_smi_reads_csv_line() {
+        let stats = parse_nvidia_smi("12, 2048, 8192, 55\n").expect("line should parse");
+        assert_eq!(stats.usage, Some(12));
+        assert_eq!(stats.vram_used_bytes, Some(2048 * 1024 * 1024));
+        assert_eq!(stats.temperature_c, Some(55.0));
+
+        let stats = parse_nvidia_smi("[N/A], 512, 4096, [N/A]").expect("line should parse");
+        assert_eq!(stats.usage, None);
+        assert_eq!(stats.temperature_c, None);
+        assert!(parse_nvidia_smi("").is_err());
+        assert!(parse_nvidia_smi("No devices were found").is_err());
+    }
+
+    #[test]
+    fn render_format_replaces_placeholders() {
+        let device = GpuDevice {
+            kind: GpuKind::Amd,
+            card: Some("card0".to_string()),
+            device_dir: None,
+        };
+        let stats = GpuStats {
+            usage: Some(37),
+            vram_used_bytes: Some(2 << 30),
+            vram_total_bytes: Some(8 << 30),
+            temperature_c: Some(47.6),
+        };
+        assert_eq!(
+            render_format(
+                "{vendor} {card}: {usage}% {vram_used}/{vram_total} ({vram_percentage}%) {temperature}°C",
+                &device,
+                &stats
+            ),
+            "AMD card0: 37% 2G/8G (25%) 48°C"
+        );
+        assert_eq!(
+            render_format("{usage}{temperature}", &device, &GpuStats::default()),
+            ""
+        );
+    }
+}
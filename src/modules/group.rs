@@ -6,8 +6,10 @@ use serde::de::Deserializer;
 use serde::Deserialize;
 use serde_json::Value;
 
+use crate::modules::broadcaster::attach_subscription;
 use crate::modules::{
-    apply_css_classes, build_module, ModuleBuildContext, ModuleConfig, ModuleFactory,
+    apply_css_classes, build_module, popovers, state_store, ModuleBuildContext, ModuleConfig,
+    ModuleFactory,
 };
 
 #[derive(Debug, Deserialize, Clone)]
@@ -20,6 +22,8 @@ pub(crate) struct GroupConfig {
     pub(crate) spacing: i32,
     #[serde(default, deserialize_with = "deserialize_drawer")]
     pub(crate) drawer: Option<GroupDrawerConfig>,
+    #[serde(default)]
+    pub(crate) header: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -81,7 +85,7 @@ pub(crate) fn parse_config(module: &ModuleConfig) -> Result<GroupConfig, String>
 
 fn build_group_module(config: GroupConfig, context: &ModuleBuildContext) -> Result<GtkBox, String> {
     let spacing = normalized_spacing(config.spacing);
-    let container = GtkBox::new(Orientation::Horizontal, spacing);
+    let container = GtkBox::new(context.orientation(), spacing);
     container.add_css_class("module");
     container.add_css_class("group");
     container.set_focusable(false);
@@ -89,11 +93,15 @@ fn build_group_module(config: GroupConfig, context: &ModuleBuildContext) -> Resu
 
     apply_css_classes(&container, config.class.as_deref());
 
+    if let Some(header) = config.header {
+        container.append(&build_group_header(header));
+    }
+
     let drawer_enabled = config.drawer.is_some();
     let child_orientation = if config.drawer.is_some() {
         Orientation::Vertical
     } else {
-        Orientation::Horizontal
+        context.orientation()
     };
     let child_container = GtkBox::new(child_orientation, spacing);
     child_container.add_css_class("group-content");
@@ -132,6 +140,7 @@ fn build_group_module(config: GroupConfig, context: &ModuleBuildContext) -> Resu
         popover.add_css_class("group-popover");
         popover.set_child(Some(&child_container));
         toggle.set_popover(Some(&popover));
+        popovers::track(&popover, MODULE_TYPE);
 
         let open_label = drawer.label_open;
         let closed_label = drawer.label_closed;
@@ -159,6 +168,22 @@ fn build_group_module(config: GroupConfig, context: &ModuleBuildContext) -> Resu
     Ok(container)
 }
 
+fn build_group_header(template: String) -> Label {
+    let label = Label::new(None);
+    label.add_css_class("group-header");
+    label.set_markup(&state_store::render_bound_template(&template));
+
+    attach_subscription(
+        &label,
+        state_store::subscribe_changes(),
+        move |label, ()| {
+            label.set_markup(&state_store::render_bound_template(&template));
+        },
+    );
+
+    label
+}
+
 fn default_spacing() -> i32 {
     6
 }
@@ -239,6 +264,20 @@ mod tests {
         assert!(drawer.start_open);
     }
 
+    #[test]
+    fn parse_config_supports_header() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "modules": [{ "type": "clock" }],
+                "header": "{battery.percent}%"
+            }))
+            .expect("group config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("group config should parse");
+        assert_eq!(cfg.header.as_deref(), Some("{battery.percent}%"));
+    }
+
     #[test]
     fn parse_config_supports_boolean_drawer() {
         let module = ModuleConfig::new(
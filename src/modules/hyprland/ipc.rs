@@ -0,0 +1,409 @@
+//! Hyprland IPC: one-shot requests on `.socket.sock` and a single shared
+//! event stream read from `.socket2.sock`.
+
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::ops::Deref;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::modules::backend_errors;
+use crate::modules::broadcaster::BackendRegistry;
+
+/// Name under which event stream failures are reported.
+const ERROR_BACKEND: &str = "hyprland";
+const SIGNATURE_ENV: &str = "HYPRLAND_INSTANCE_SIGNATURE";
+const REQUEST_SOCKET: &str = ".socket.sock";
+const EVENT_SOCKET: &str = ".socket2.sock";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(500);
+const STREAM_END_RETRY_DELAY: Duration = Duration::from_millis(200);
+const EVENT_IDLE_TIMEOUT: Duration = Duration::from_millis(500);
+/// How long an event read blocks before the worker re-checks subscribers.
+const EVENT_READ_TIMEOUT: Duration = Duration::from_millis(500);
+const EVENT_COALESCE_WINDOW: Duration = Duration::from_millis(40);
+
+/// One `name>>data` line of the event socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HyprlandEvent {
+    pub(crate) name: String,
+    pub(crate) data: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct Monitor {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) focused: bool,
+    #[serde(rename = "activeWorkspace")]
+    pub(crate) active_workspace: WorkspaceRef,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct WorkspaceRef {
+    pub(crate) id: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SharedEventsKey;
+
+/// Receiving end of [`subscribe_shared_events`]. Dropping it tells the
+/// event worker the subscriber is gone even while no events arrive.
+pub(crate) struct EventSubscription {
+    receiver: std::sync::mpsc::Receiver<HyprlandEvent>,
+    _alive: Arc<()>,
+}
+
+impl Deref for EventSubscription {
+    type Target = std::sync::mpsc::Receiver<HyprlandEvent>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.receiver
+    }
+}
+
+struct EventSubscriber {
+    sender: std::sync::mpsc::Sender<HyprlandEvent>,
+    alive: Weak<()>,
+}
+
+struct EventFanout {
+    subscribers: Mutex<Vec<EventSubscriber>>,
+}
+
+impl EventFanout {
+    fn new() -> Self {
+        Self {
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn subscribe(&self) -> EventSubscription {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let alive = Arc::new(());
+        self.subscribers
+            .lock()
+            .expect("hyprland event fanout mutex poisoned")
+            .push(EventSubscriber {
+                sender,
+                alive: Arc::downgrade(&alive),
+            });
+        EventSubscription {
+            receiver,
+            _alive: alive,
+        }
+    }
+
+    fn broadcast(&self, event: HyprlandEvent) {
+        self.subscribers
+            .lock()
+            .expect("hyprland event fanout mutex poisoned")
+            .retain(|subscriber| subscriber.sender.send(event.clone()).is_ok());
+    }
+
+    /// Prunes dropped subscriptions first, so the count falls to zero
+    /// without waiting for the next event.
+    fn subscriber_count(&self) -> usize {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .expect("hyprland event fanout mutex poisoned");
+        subscribers.retain(|subscriber| subscriber.alive.strong_count() > 0);
+        subscribers.len()
+    }
+}
+
+fn shared_events_registry() -> &'static BackendRegistry<SharedEventsKey, EventFanout> {
+    static REGISTRY: OnceLock<BackendRegistry<SharedEventsKey, EventFanout>> = OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+pub(crate) fn subscribe_shared_events() -> EventSubscription {
+    let key = SharedEventsKey;
+    let (fanout, start_worker) =
+        shared_events_registry().get_or_create(key.clone(), EventFanout::new);
+    let receiver = fanout.subscribe();
+
+    if start_worker {
+        start_shared_events_worker(key, fanout);
+    }
+
+    receiver
+}
+
+fn start_shared_events_worker(key: SharedEventsKey, fanout: Arc<EventFanout>) {
+    std::thread::spawn(move || {
+        let should_stop = || {
+            if fanout.subscriber_count() == 0 {
+                shared_events_registry().remove(&key, &fanout);
+                return true;
+            }
+            false
+        };
+
+        loop {
+            if should_stop() {
+                return;
+            }
+
+            let stream = match socket_path(EVENT_SOCKET).and_then(|path| {
+                UnixStream::connect(&path)
+                    .map_err(|err| format!("failed to connect to {}: {err}", path.display()))
+            }) {
+                Ok(stream) => stream,
+                Err(err) => {
+                    backend_errors::report(ERROR_BACKEND, err);
+                    std::thread::sleep(CONNECT_RETRY_DELAY);
+                    continue;
+                }
+            };
+            if let Err(err) = stream.set_read_timeout(Some(EVENT_READ_TIMEOUT)) {
+                backend_errors::report(
+                    ERROR_BACKEND,
+                    format!("failed to set event stream timeout: {err}"),
+                );
+                std::thread::sleep(CONNECT_RETRY_DELAY);
+                continue;
+            }
+            backend_errors::clear(ERROR_BACKEND);
+
+            // A quiet compositor sends nothing, so reads time out to let the
+            // worker notice that every subscriber is gone. Partial lines stay
+            // in `line` until the rest arrives.
+            let mut reader = BufReader::new(stream);
+            let mut line = Vec::new();
+            loop {
+                if should_stop() {
+                    return;
+                }
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(0) => break,
+                    Ok(_) if line.ends_with(b"\n") => {
+                        let text = String::from_utf8_lossy(&line);
+                        let text = text.trim_end_matches('\n');
+                        debug_log(&format!("event={text}"));
+                        if let Some(event) = parse_event_line(text) {
+                            fanout.broadcast(event);
+                        }
+                        line.clear();
+                    }
+                    Ok(_) => {}
+                    Err(err)
+                        if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                    Err(err) => {
+                        backend_errors::report(
+                            ERROR_BACKEND,
+                            format!("event stream read failed: {err}"),
+                        );
+                        break;
+                    }
+                }
+            }
+
+            debug_log("event stream ended, reconnecting");
+            std::thread::sleep(STREAM_END_RETRY_DELAY);
+        }
+    });
+}
+
+/// Waits for one of the `relevant` events, then collects the rest of the
+/// burst (a workspace switch emits several events at once). Returns the
+/// relevant events in order; empty when nothing arrived before the idle
+/// timeout.
+pub(crate) fn recv_relevant_events_coalesced(
+    events: &std::sync::mpsc::Receiver<HyprlandEvent>,
+    relevant: &[&str],
+) -> Result<Vec<HyprlandEvent>, std::sync::mpsc::RecvTimeoutError> {
+    let is_relevant = |event: &HyprlandEvent| relevant.contains(&event.name.as_str());
+    let first = loop {
+        match events.recv_timeout(EVENT_IDLE_TIMEOUT) {
+            Ok(event) if is_relevant(&event) => break event,
+            Ok(_) => continue,
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => return Ok(Vec::new()),
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(std::sync::mpsc::RecvTimeoutError::Disconnected)
+            }
+        }
+    };
+
+    let mut burst = vec![first];
+    let deadline = Instant::now() + EVENT_COALESCE_WINDOW;
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        let remaining = deadline.saturating_duration_since(now);
+        match events.recv_timeout(remaining) {
+            Ok(event) if is_relevant(&event) => burst.push(event),
+            Ok(_) => {}
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => break,
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(std::sync::mpsc::RecvTimeoutError::Disconnected)
+            }
+        }
+    }
+
+    Ok(burst)
+}
+
+/// Runs `j/<command>` (for example `workspaces`) and decodes the JSON reply.
+pub(crate) fn request<T: DeserializeOwned>(command: &str) -> Result<T, String> {
+    let response = send_request(&format!("j/{command}"))?;
+    serde_json::from_str(&response).map_err(|err| format!("invalid reply to '{command}': {err}"))
+}
+
+pub(crate) fn query_monitors() -> Result<Vec<Monitor>, String> {
+    request("monitors")
+}
+
+/// Runs each dispatcher (for example `workspace 3`) in order on a helper
+/// thread so button clicks never block the GTK main loop.
+pub(crate) fn dispatch(dispatchers: Vec<String>) {
+    std::thread::spawn(move || {
+        for dispatcher in dispatchers {
+            match send_request(&format!("dispatch {dispatcher}")) {
+                Ok(reply) if reply.trim() == "ok" => {}
+                Ok(reply) => eprintln!(
+                    "vibar/hyprland: dispatch '{dispatcher}' failed: {}",
+                    reply.trim()
+                ),
+                Err(err) => eprintln!("vibar/hyprland: dispatch '{dispatcher}' failed: {err}"),
+            }
+        }
+    });
+}
+
+fn send_request(request: &str) -> Result<String, String> {
+    let path = socket_path(REQUEST_SOCKET)?;
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|err| format!("failed to connect to {}: {err}", path.display()))?;
+    stream
+        .set_read_timeout(Some(REQUEST_TIMEOUT))
+        .and_then(|()| stream.write_all(request.as_bytes()))
+        .map_err(|err| format!("failed to send '{request}': {err}"))?;
+
+    // Hyprland closes the connection after the reply.
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .map_err(|err| format!("failed to read reply to '{request}': {err}"))?;
+    debug_log(&format!("request={request} reply_len={}", response.len()));
+    Ok(response)
+}
+
+fn socket_path(socket: &str) -> Result<PathBuf, String> {
+    let signature = std::env::var(SIGNATURE_ENV)
+        .ok()
+        .filter(|signature| !signature.is_empty())
+        .ok_or_else(|| format!("${SIGNATURE_ENV} is not set (is Hyprland running?)"))?;
+    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").ok();
+    let candidates = socket_dir_candidates(&signature, runtime_dir.as_deref());
+    let dir = candidates
+        .iter()
+        .find(|dir| dir.join(socket).exists())
+        .or(candidates.first())
+        .expect("socket dir candidates should not be empty");
+    Ok(dir.join(socket))
+}
+
+/// Hyprland 0.40+ keeps its sockets under `$XDG_RUNTIME_DIR/hypr`; older
+/// releases used `/tmp/hypr`.
+fn socket_dir_candidates(signature: &str, runtime_dir: Option<&str>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(runtime_dir) = runtime_dir.filter(|dir| !dir.is_empty()) {
+        dirs.push(Path::new(runtime_dir).join("hypr").join(signature));
+    }
+    dirs.push(Path::new("/tmp/hypr").join(signature));
+    dirs
+}
+
+fn parse_event_line(line: &str) -> Option<HyprlandEvent> {
+    let (name, data) = line.split_once(">>")?;
+    (!name.is_empty()).then(|| HyprlandEvent {
+        name: name.to_string(),
+        data: data.to_string(),
+    })
+}
+
+fn debug_log(message: &str) {
+    if debug_enabled() {
+        eprintln!("vibar/hyprland: {message}");
+    }
+}
+
+fn debug_enabled() -> bool {
+    std::env::var("VIBAR_DEBUG_HYPRLAND_IPC")
+        .map(|v| matches!(v.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fanout_counts_dropped_subscriptions_without_events() {
+        let fanout = EventFanout::new();
+        let first = fanout.subscribe();
+        let second = fanout.subscribe();
+        assert_eq!(fanout.subscriber_count(), 2);
+        drop(first);
+        assert_eq!(fanout.subscriber_count(), 1);
+        drop(second);
+        assert_eq!(fanout.subscriber_count(), 0);
+    }
+
+    #[test]
+    fn parse_event_line_splits_name_and_data() {
+        assert_eq!(
+            parse_event_line("workspacev2>>3,three"),
+            Some(HyprlandEvent {
+                name: "workspacev2".to_string(),
+                data: "3,three".to_string(),
+            })
+        );
+        // Only the first separator splits; titles may contain `>>`.
+        assert_eq!(
+            parse_event_line("windowtitlev2>>abc,a >> b").map(|event| event.data),
+            Some("abc,a >> b".to_string())
+        );
+        assert_eq!(
+            parse_event_line("submap>>").map(|event| event.data),
+            Some(String::new())
+        );
+        assert_eq!(parse_event_line("garbage"), None);
+    }
+
+    #[test]
+    fn socket_dir_candidates_prefer_runtime_dir() {
+        assert_eq!(
+            socket_dir_candidates("sig", Some("/run/user/1000")),
+            vec![
+                PathBuf::from("/run/user/1000/hypr/sig"),
+                PathBuf::from("/tmp/hypr/sig"),
+            ]
+        );
+        assert_eq!(
+            socket_dir_candidates("sig", None),
+            vec![PathBuf::from("/tmp/hypr/sig")]
+        );
+    }
+
+    #[test]
+    fn monitor_reply_decodes() {
+        let monitors: Vec<Monitor> = serde_json::from_str(
+            r#"[{"id":0,"name":"eDP-1","focused":true,"width":1920,
+                 "activeWorkspace":{"id":2,"name":"2"}}]"#,
+        )
+        .expect("monitor reply should decode");
+        assert_eq!(monitors[0].name, "eDP-1");
+        assert!(monitors[0].focused);
+        assert_eq!(monitors[0].active_workspace.id, 2);
+    }
+}
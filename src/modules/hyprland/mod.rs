@@ -0,0 +1,4 @@
+pub(crate) mod ipc;
+pub(crate) mod submap;
+pub(crate) mod window;
+pub(crate) mod workspaces;
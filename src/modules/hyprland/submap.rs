@@ -0,0 +1,162 @@
+use std::sync::{Arc, OnceLock};
+
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde_json::Value;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::hyprland::ipc::{recv_relevant_events_coalesced, subscribe_shared_events};
+use crate::modules::sway::mode::ModeConfig;
+use crate::modules::{
+    render_markup_template, ModuleBuildContext, ModuleConfig, ModuleFactory, ModuleLabel,
+};
+
+const SUBMAP_EVENT: &str = "submap";
+
+#[derive(Debug, Clone)]
+struct SubmapUpdate {
+    text: String,
+    visible: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SubmapSharedKey {
+    format: String,
+}
+
+pub(crate) struct HyprlandSubmapFactory;
+
+pub(crate) const FACTORY: HyprlandSubmapFactory = HyprlandSubmapFactory;
+pub(crate) const MODULE_TYPE: &str = "hyprland/submap";
+
+impl ModuleFactory for HyprlandSubmapFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        let click_command = parsed.click.or(parsed.on_click);
+        Ok(build_submap_module(parsed.format, click_command, parsed.class).upcast())
+    }
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<ModeConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn submap_registry() -> &'static BackendRegistry<SubmapSharedKey, Broadcaster<SubmapUpdate>> {
+    static REGISTRY: OnceLock<BackendRegistry<SubmapSharedKey, Broadcaster<SubmapUpdate>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_submap(format: String) -> Subscription<SubmapUpdate> {
+    let key = SubmapSharedKey { format };
+
+    let (broadcaster, start_worker) =
+        submap_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_submap_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+/// Hyprland has no query for the active submap in all releases, so the
+/// state comes from `submap>>name` events only and starts at the default.
+fn start_submap_worker(key: SubmapSharedKey, broadcaster: Arc<Broadcaster<SubmapUpdate>>) {
+    std::thread::spawn(move || {
+        broadcaster.broadcast(render_submap(&key.format, ""));
+        let events = subscribe_shared_events();
+
+        loop {
+            if broadcaster.subscriber_count() == 0 {
+                submap_registry().remove(&key, &broadcaster);
+                return;
+            }
+
+            match recv_relevant_events_coalesced(&events, &[SUBMAP_EVENT]) {
+                Ok(burst) => {
+                    if let Some(event) = burst.last() {
+                        broadcaster.broadcast(render_submap(&key.format, &event.data));
+                    }
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            }
+        }
+    });
+}
+
+/// An empty name means the default submap, which hides the module.
+fn render_submap(format: &str, submap: &str) -> SubmapUpdate {
+    if submap.is_empty() || submap == "default" {
+        return SubmapUpdate {
+            text: String::new(),
+            visible: false,
+        };
+    }
+
+    let rendered = render_markup_template(format, &[("{}", submap)]);
+    SubmapUpdate {
+        visible: !rendered.trim().is_empty(),
+        text: rendered,
+    }
+}
+
+fn build_submap_module(
+    format: String,
+    click_command: Option<String>,
+    class: Option<String>,
+) -> Label {
+    let label = ModuleLabel::new("hyprland-submap")
+        .with_css_classes(class.as_deref())
+        .with_click_command(click_command)
+        .into_label();
+
+    attach_subscription(&label, subscribe_shared_submap(format), |label, update| {
+        label.set_visible(update.visible);
+        if update.visible {
+            label.set_markup(&update.text);
+        }
+    });
+
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("sway/mode", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'hyprland/submap'"));
+    }
+
+    #[test]
+    fn render_submap_hides_default_and_escapes_name() {
+        assert!(!render_submap("{}", "").visible);
+        assert!(!render_submap("{}", "default").visible);
+
+        let update = render_submap("<b>{}</b>", "resize & move");
+        assert!(update.visible);
+        assert_eq!(update.text, "<b>resize &amp; move</b>");
+    }
+}
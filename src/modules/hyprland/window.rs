@@ -0,0 +1,247 @@
+use std::sync::{Arc, OnceLock};
+
+use gtk::prelude::*;
+use gtk::Widget;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::hyprland::ipc::{
+    query_monitors, recv_relevant_events_coalesced, request, subscribe_shared_events,
+};
+use crate::modules::scroll_text::ModuleText;
+use crate::modules::sway::window::WindowConfig;
+use crate::modules::{
+    attach_primary_click_command, escape_markup_text, render_markup_template, ModuleBuildContext,
+    ModuleConfig, ModuleFactory,
+};
+
+const RELEVANT_EVENTS: &[&str] = &[
+    "activewindow",
+    "windowtitle",
+    "closewindow",
+    "movewindow",
+    "workspace",
+    "focusedmon",
+];
+
+#[derive(Debug, Clone)]
+struct WindowUpdate {
+    title: String,
+    output: Option<String>,
+    visible: bool,
+}
+
+/// `j/activewindow` replies `{}` when nothing is focused.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct HyprActiveWindow {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    class: String,
+    #[serde(default)]
+    monitor: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct WindowSharedKey {
+    format: String,
+}
+
+pub(crate) struct HyprlandWindowFactory;
+
+pub(crate) const FACTORY: HyprlandWindowFactory = HyprlandWindowFactory;
+pub(crate) const MODULE_TYPE: &str = "hyprland/window";
+
+impl ModuleFactory for HyprlandWindowFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        let click_command = parsed.click.or(parsed.on_click);
+        let text = ModuleText::new(
+            MODULE_TYPE,
+            Some(parsed.max_width),
+            parsed.scroll_text,
+            context.scale(),
+            &["module", "hyprland-window"],
+            parsed.class.as_deref(),
+        )?;
+        Ok(build_window_module(
+            context.monitor_connector.clone(),
+            parsed.format,
+            click_command,
+            text,
+        ))
+    }
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<WindowConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn window_registry() -> &'static BackendRegistry<WindowSharedKey, Broadcaster<WindowUpdate>> {
+    static REGISTRY: OnceLock<BackendRegistry<WindowSharedKey, Broadcaster<WindowUpdate>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_window(format: String) -> Subscription<WindowUpdate> {
+    let key = WindowSharedKey { format };
+
+    let (broadcaster, start_worker) =
+        window_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_window_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_window_worker(key: WindowSharedKey, broadcaster: Arc<Broadcaster<WindowUpdate>>) {
+    std::thread::spawn(move || {
+        broadcaster.broadcast(query_active_window(&key.format));
+        let events = subscribe_shared_events();
+
+        loop {
+            if broadcaster.subscriber_count() == 0 {
+                window_registry().remove(&key, &broadcaster);
+                return;
+            }
+
+            match recv_relevant_events_coalesced(&events, RELEVANT_EVENTS) {
+                Ok(burst) if !burst.is_empty() => {
+                    broadcaster.broadcast(query_active_window(&key.format));
+                }
+                Ok(_) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            }
+        }
+    });
+}
+
+fn query_active_window(format: &str) -> WindowUpdate {
+    let (Ok(window), Ok(monitors)) = (
+        request::<HyprActiveWindow>("activewindow"),
+        query_monitors(),
+    ) else {
+        return WindowUpdate {
+            title: escape_markup_text("hyprland?"),
+            output: None,
+            visible: true,
+        };
+    };
+
+    let output = window.monitor.and_then(|id| {
+        monitors
+            .into_iter()
+            .find(|monitor| monitor.id == id)
+            .map(|monitor| monitor.name)
+    });
+    if window.title.is_empty() {
+        return WindowUpdate {
+            title: String::new(),
+            output,
+            visible: false,
+        };
+    }
+
+    let rendered = render_markup_template(
+        format,
+        &[
+            ("{}", &window.title),
+            ("{title}", &window.title),
+            ("{class}", &window.class),
+        ],
+    );
+    let visible = !rendered.trim().is_empty();
+    WindowUpdate {
+        title: rendered,
+        output,
+        visible,
+    }
+}
+
+fn build_window_module(
+    output_filter: Option<String>,
+    format: String,
+    click_command: Option<String>,
+    text: ModuleText,
+) -> Widget {
+    let widget = text.widget();
+    attach_primary_click_command(&widget, click_command);
+
+    attach_subscription(
+        &widget,
+        subscribe_shared_window(format),
+        move |widget, update| {
+            let belongs_to_output = match (output_filter.as_deref(), update.output.as_deref()) {
+                (Some(expected), Some(current)) => expected == current,
+                (Some(_), None) => false,
+                (None, _) => true,
+            };
+
+            if !belongs_to_output || !update.visible {
+                widget.set_visible(false);
+                return;
+            }
+
+            widget.set_visible(true);
+            text.set_markup(&update.title);
+        },
+    );
+
+    widget
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("sway/window", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'hyprland/window'"));
+    }
+
+    #[test]
+    fn parse_config_matches_sway_window_defaults() {
+        let module = ModuleConfig::new(MODULE_TYPE, Map::new());
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.format, "{}");
+        assert_eq!(cfg.max_width, 80);
+    }
+
+    #[test]
+    fn active_window_reply_tolerates_empty_object() {
+        let empty: HyprActiveWindow =
+            serde_json::from_str("{}").expect("empty reply should decode");
+        assert!(empty.title.is_empty());
+        assert_eq!(empty.monitor, None);
+
+        let window: HyprActiveWindow = serde_json::from_str(
+            r#"{"address":"0x5","class":"kitty","title":"~","monitor":1,"workspace":{"id":2,"name":"2"}}"#,
+        )
+        .expect("window reply should decode");
+        assert_eq!(window.class, "kitty");
+        assert_eq!(window.monitor, Some(1));
+    }
+}
@@ -0,0 +1,517 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::rc::Rc;
+use std::sync::{Arc, OnceLock};
+
+use gtk::gdk;
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, GestureClick, Label, Orientation, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::hyprland::ipc::{
+    dispatch, query_monitors, recv_relevant_events_coalesced, request, subscribe_shared_events,
+    HyprlandEvent, WorkspaceRef,
+};
+use crate::modules::sway::workspaces::{
+    group_workspaces_by_output, next_free_workspace_number, WorkspaceConfig, WorkspaceInfo,
+};
+use crate::modules::touch::{self, SwipeDirection};
+use crate::modules::workspace_strip;
+use crate::modules::{
+    apply_css_classes, events, render_markup_template, ModuleBuildContext, ModuleConfig,
+    ModuleFactory,
+};
+
+const DEFAULT_OUTPUT_LABEL_FORMAT: &str = "{output}";
+const URGENT_EVENT: &str = "urgent";
+/// Hyprland also sends `...v2` variants of most of these; the original
+/// names exist in every release.
+const RELEVANT_EVENTS: &[&str] = &[
+    "workspace",
+    "focusedmon",
+    "createworkspace",
+    "destroyworkspace",
+    "moveworkspace",
+    "renameworkspace",
+    "monitoradded",
+    "monitorremoved",
+    URGENT_EVENT,
+];
+
+#[derive(Debug, Clone)]
+struct WorkspacesUpdate {
+    workspaces: Vec<WorkspaceInfo>,
+    focused_workspace: Option<String>,
+    /// False while Hyprland cannot be queried.
+    connected: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct HyprWorkspace {
+    id: i64,
+    name: String,
+    monitor: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct HyprClient {
+    address: String,
+    workspace: WorkspaceRef,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct WorkspacesSharedKey;
+
+pub(crate) struct HyprlandWorkspaceFactory;
+
+pub(crate) const FACTORY: HyprlandWorkspaceFactory = HyprlandWorkspaceFactory;
+pub(crate) const MODULE_TYPE: &str = "hyprland/workspaces";
+
+impl ModuleFactory for HyprlandWorkspaceFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        let output_format = parsed.group_by_output.then(|| {
+            parsed
+                .output_format
+                .clone()
+                .unwrap_or_else(|| DEFAULT_OUTPUT_LABEL_FORMAT.to_string())
+        });
+        Ok(build_workspaces_module(context.monitor.clone(), parsed, output_format).upcast())
+    }
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<WorkspaceConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn workspaces_registry(
+) -> &'static BackendRegistry<WorkspacesSharedKey, Broadcaster<WorkspacesUpdate>> {
+    static REGISTRY: OnceLock<BackendRegistry<WorkspacesSharedKey, Broadcaster<WorkspacesUpdate>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_workspaces() -> Subscription<WorkspacesUpdate> {
+    let (broadcaster, start_worker) =
+        workspaces_registry().get_or_create(WorkspacesSharedKey, Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_workspaces_worker(broadcaster);
+    }
+
+    receiver
+}
+
+fn start_workspaces_worker(broadcaster: Arc<Broadcaster<WorkspacesUpdate>>) {
+    std::thread::spawn(move || {
+        // Workspace ids with an urgent window that have not been shown since.
+        let mut urgent = BTreeSet::new();
+        let update = query_workspaces(&mut urgent);
+        let mut focused_workspace = update.focused_workspace.clone();
+        broadcaster.broadcast(update);
+        let hypr_events = subscribe_shared_events();
+
+        loop {
+            if broadcaster.subscriber_count() == 0 {
+                workspaces_registry().remove(&WorkspacesSharedKey, &broadcaster);
+                return;
+            }
+
+            match recv_relevant_events_coalesced(&hypr_events, RELEVANT_EVENTS) {
+                Ok(burst) if !burst.is_empty() => {
+                    mark_urgent_workspaces(&burst, &mut urgent);
+                    let update = query_workspaces(&mut urgent);
+                    if let Some(name) = update.focused_workspace.as_ref() {
+                        if focused_workspace.as_ref() != Some(name) {
+                            events::emit(events::WORKSPACE_SWITCHED, name.clone());
+                        }
+                        focused_workspace = Some(name.clone());
+                    }
+                    broadcaster.broadcast(update);
+                }
+                Ok(_) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            }
+        }
+    });
+}
+
+/// `urgent` events carry a window address; resolve it to its workspace.
+fn mark_urgent_workspaces(burst: &[HyprlandEvent], urgent: &mut BTreeSet<i64>) {
+    let addresses: Vec<&str> = burst
+        .iter()
+        .filter(|event| event.name == URGENT_EVENT)
+        .map(|event| event.data.trim_start_matches("0x"))
+        .collect();
+    if addresses.is_empty() {
+        return;
+    }
+    let Ok(clients) = request::<Vec<HyprClient>>("clients") else {
+        return;
+    };
+    for client in clients {
+        if addresses.contains(&client.address.trim_start_matches("0x")) {
+            urgent.insert(client.workspace.id);
+        }
+    }
+}
+
+fn query_workspaces(urgent: &mut BTreeSet<i64>) -> WorkspacesUpdate {
+    let (Ok(workspaces), Ok(monitors)) = (
+        request::<Vec<HyprWorkspace>>("workspaces"),
+        query_monitors(),
+    ) else {
+        return WorkspacesUpdate {
+            workspaces: Vec::new(),
+            focused_workspace: None,
+            connected: false,
+        };
+    };
+
+    let active_ids: Vec<i64> = monitors
+        .iter()
+        .map(|monitor| monitor.active_workspace.id)
+        .collect();
+    let focused_id = monitors
+        .iter()
+        .find(|monitor| monitor.focused)
+        .map(|monitor| monitor.active_workspace.id);
+    // Urgency ends once the workspace is shown, or when it is gone.
+    urgent.retain(|id| {
+        !active_ids.contains(id) && workspaces.iter().any(|workspace| workspace.id == *id)
+    });
+
+    let monitor_order = |name: &str| {
+        monitors
+            .iter()
+            .position(|monitor| monitor.name == name)
+            .unwrap_or(usize::MAX)
+    };
+    let mut workspaces = workspaces;
+    workspaces.retain(|workspace| !is_special_workspace(&workspace.name));
+    workspaces.sort_by_key(|workspace| {
+        (
+            monitor_order(&workspace.monitor),
+            workspace_number(workspace.id),
+        )
+    });
+
+    let infos = workspaces
+        .iter()
+        .map(|workspace| WorkspaceInfo {
+            name: workspace.name.clone(),
+            num: workspace_number(workspace.id),
+            output: workspace.monitor.clone(),
+            focused: focused_id == Some(workspace.id),
+            visible: active_ids.contains(&workspace.id),
+            urgent: urgent.contains(&workspace.id),
+        })
+        .collect::<Vec<_>>();
+    let focused_workspace = infos
+        .iter()
+        .find(|workspace| workspace.focused)
+        .map(|workspace| workspace.name.clone());
+
+    WorkspacesUpdate {
+        workspaces: infos,
+        focused_workspace,
+        connected: true,
+    }
+}
+
+/// Special (scratchpad) workspaces are toggled, not switched to.
+fn is_special_workspace(name: &str) -> bool {
+    name.starts_with("special:")
+}
+
+/// Named workspaces get negative ids in Hyprland; report them like sway's
+/// named workspaces (`-1`) so they sort first and take no number slot.
+fn workspace_number(id: i64) -> i32 {
+    i32::try_from(id).ok().filter(|id| *id > 0).unwrap_or(-1)
+}
+
+/// Dispatcher argument selecting `ws`: numbered workspaces by id, named
+/// ones by name.
+fn workspace_selector(ws: &WorkspaceInfo) -> String {
+    if ws.num > 0 {
+        ws.num.to_string()
+    } else {
+        format!("name:{}", ws.name)
+    }
+}
+
+fn build_workspaces_module(
+    monitor: Option<gdk::Monitor>,
+    config: WorkspaceConfig,
+    output_format: Option<String>,
+) -> GtkBox {
+    let container = GtkBox::new(Orientation::Horizontal, 4);
+    container.add_css_class("module");
+    container.add_css_class("workspaces");
+    container.add_css_class("hyprland-workspaces");
+    apply_css_classes(&container, config.class.as_deref());
+    let strip = workspace_strip::attach_scrollable_strip(&container, config.max_width);
+
+    // Resolved lazily: connectors of freshly plugged monitors may not be
+    // known yet when the bar is built.
+    let output = Rc::new(RefCell::new(None::<String>));
+    let resolve_output = {
+        let output = Rc::clone(&output);
+        move || {
+            if output.borrow().is_none() {
+                *output.borrow_mut() = monitor
+                    .as_ref()
+                    .and_then(|monitor| monitor.connector())
+                    .map(|connector| connector.to_string());
+            }
+            output.borrow().clone()
+        }
+    };
+
+    let button_class = config.button_class.clone();
+    let format_button = config.format_button.clone();
+    let max_visible = config.max_visible;
+    attach_subscription(
+        &container,
+        subscribe_shared_workspaces(),
+        move |container, update| {
+            while let Some(child) = strip.first_child() {
+                strip.remove(&child);
+            }
+            if !update.connected {
+                let fallback = Label::new(Some("hyprland?"));
+                fallback.add_css_class("workspace-status");
+                strip.append(&fallback);
+                container.set_visible(true);
+                return;
+            }
+
+            let output = if let Some(output_format) = output_format.as_deref() {
+                render_grouped(
+                    &strip,
+                    &update,
+                    output_format,
+                    button_class.as_deref(),
+                    max_visible,
+                );
+                None
+            } else {
+                let Some(output) = resolve_output() else {
+                    container.set_visible(false);
+                    return;
+                };
+                let workspaces = update
+                    .workspaces
+                    .iter()
+                    .filter(|ws| ws.output == output)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                append_buttons(&strip, &workspaces, button_class.as_deref(), max_visible);
+                Some(output)
+            };
+            append_new_workspace_button(
+                &strip,
+                &update,
+                format_button.as_deref(),
+                button_class.as_deref(),
+                output.as_deref(),
+            );
+            container.set_visible(true);
+        },
+    );
+
+    touch::attach_horizontal_swipe(&container, |direction| {
+        let target = match direction {
+            SwipeDirection::Left => "m+1",
+            SwipeDirection::Right => "m-1",
+        };
+        dispatch(vec![format!("workspace {target}")]);
+    });
+
+    if config.middle_click_back_and_forth {
+        // Workspace buttons only claim the primary button, so a middle click
+        // anywhere on the strip reaches this gesture.
+        let click = GestureClick::builder().button(2).build();
+        click.connect_released(|_, _, _, _| {
+            dispatch(vec!["workspace previous".to_string()]);
+        });
+        container.add_controller(click);
+    }
+
+    container
+}
+
+fn render_grouped(
+    container: &GtkBox,
+    update: &WorkspacesUpdate,
+    output_format: &str,
+    button_class: Option<&str>,
+    max_visible: Option<usize>,
+) {
+    for (output, workspaces) in group_workspaces_by_output(&update.workspaces) {
+        let group = GtkBox::new(Orientation::Horizontal, 4);
+        group.add_css_class("workspace-group");
+
+        let header = Label::new(None);
+        header.add_css_class("workspace-output-label");
+        let header_text = render_markup_template(output_format, &[("{output}", &output)]);
+        header.set_visible(!header_text.trim().is_empty());
+        header.set_markup(&header_text);
+        group.append(&header);
+
+        append_buttons(&group, &workspaces, button_class, max_visible);
+        container.append(&group);
+    }
+}
+
+fn append_buttons(
+    parent: &GtkBox,
+    workspaces: &[WorkspaceInfo],
+    button_class: Option<&str>,
+    max_visible: Option<usize>,
+) {
+    let focused = workspaces
+        .iter()
+        .find(|ws| ws.focused)
+        .map(|ws| ws.name.as_str());
+    workspace_strip::append_workspace_buttons(
+        parent,
+        workspaces,
+        focused,
+        max_visible,
+        button_class,
+        MODULE_TYPE,
+        |ws| build_workspace_button(ws, button_class),
+    );
+}
+
+fn build_workspace_button(ws: &WorkspaceInfo, button_class: Option<&str>) -> Button {
+    let button = Button::with_label(&ws.name);
+    button.add_css_class("menu-button");
+    apply_css_classes(&button, button_class);
+    button.set_focusable(false);
+
+    if ws.focused {
+        button.add_css_class("active");
+        button.add_css_class("workspace-active");
+    }
+    if ws.urgent {
+        button.add_css_class("urgent");
+        button.add_css_class("workspace-urgent");
+    }
+
+    let selector = workspace_selector(ws);
+    button.connect_clicked(move |_| dispatch(vec![format!("workspace {selector}")]));
+
+    button
+}
+
+/// Appends the `format-button` button, if configured. With an `output` the
+/// click focuses that monitor first so the new workspace opens on this bar.
+fn append_new_workspace_button(
+    container: &GtkBox,
+    update: &WorkspacesUpdate,
+    format_button: Option<&str>,
+    button_class: Option<&str>,
+    output: Option<&str>,
+) {
+    let Some(format_button) = format_button else {
+        return;
+    };
+
+    let num = next_free_workspace_number(&update.workspaces);
+    let label = Label::new(None);
+    label.set_markup(&render_markup_template(
+        format_button,
+        &[("{num}", &num.to_string())],
+    ));
+
+    let button = Button::new();
+    button.set_child(Some(&label));
+    button.add_css_class("menu-button");
+    button.add_css_class("workspace-new");
+    apply_css_classes(&button, button_class);
+    button.set_focusable(false);
+
+    let mut dispatchers = Vec::new();
+    if let Some(output) = output {
+        dispatchers.push(format!("focusmonitor {output}"));
+    }
+    dispatchers.push(format!("workspace {num}"));
+    button.connect_clicked(move |_| dispatch(dispatchers.clone()));
+
+    container.append(&button);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map};
+
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("sway/workspaces", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'hyprland/workspaces'"));
+    }
+
+    #[test]
+    fn parse_config_accepts_sway_workspace_keys() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "button-class": "pill",
+                "group-by-output": true,
+                "format-button": "+",
+                "middle_click_back_and_forth": true
+            }))
+            .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.button_class.as_deref(), Some("pill"));
+        assert!(cfg.group_by_output);
+        assert_eq!(cfg.format_button.as_deref(), Some("+"));
+        assert!(cfg.middle_click_back_and_forth);
+    }
+
+    #[test]
+    fn named_workspaces_select_by_name() {
+        let numbered = WorkspaceInfo {
+            name: "3".to_string(),
+            num: workspace_number(3),
+            output: "eDP-1".to_string(),
+            focused: false,
+            visible: false,
+            urgent: false,
+        };
+        let named = WorkspaceInfo {
+            name: "mail".to_string(),
+            num: workspace_number(-1337),
+            ..numbered.clone()
+        };
+        assert_eq!(workspace_selector(&numbered), "3");
+        assert_eq!(named.num, -1);
+        assert_eq!(workspace_selector(&named), "name:mail");
+        assert!(is_special_workspace("special:scratch"));
+        assert!(!is_special_workspace("mail"));
+    }
+}
@@ -0,0 +1,503 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, GestureClick, Label, Orientation, Widget};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::{
+    apply_css_classes, backend_errors, escape_markup_text, ModuleBuildContext, ModuleConfig,
+};
+
+use super::ModuleFactory;
+
+pub(crate) const MODULE_TYPE: &str = "i3status";
+const RESTART_DELAY_SECS: u64 = 5;
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct I3StatusConfig {
+    pub(crate) command: String,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+    #[serde(rename = "block-class", alias = "block_class", default)]
+    pub(crate) block_class: Option<String>,
+    #[serde(
+        rename = "show-colors",
+        alias = "show_colors",
+        default = "default_show_colors"
+    )]
+    pub(crate) show_colors: bool,
+}
+
+/// First line of the i3bar protocol.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+struct I3BarHeader {
+    version: u32,
+    #[serde(default)]
+    click_events: bool,
+}
+
+/// One block of an i3bar status line. Unsupported fields (`border`,
+/// `min_width`, `align`, `separator`, ...) are ignored.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, Default)]
+struct I3Block {
+    #[serde(default)]
+    full_text: String,
+    #[serde(default)]
+    short_text: Option<String>,
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    background: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    instance: Option<String>,
+    #[serde(default)]
+    urgent: bool,
+    #[serde(default)]
+    markup: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct I3StatusUpdate {
+    blocks: Vec<I3Block>,
+    click_events: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct I3StatusSharedKey {
+    command: String,
+}
+
+/// Shared backend: the status broadcaster plus the child's stdin, which
+/// receives click events when the header enabled them.
+struct SharedI3StatusBackend {
+    broadcaster: Broadcaster<I3StatusUpdate>,
+    clicks: Mutex<Option<ClickWriter>>,
+}
+
+struct ClickWriter {
+    stdin: ChildStdin,
+    /// Whether the opening `[` of the click event array has been written.
+    started: bool,
+}
+
+impl SharedI3StatusBackend {
+    fn new() -> Self {
+        Self {
+            broadcaster: Broadcaster::new(),
+            clicks: Mutex::new(None),
+        }
+    }
+
+    fn set_click_writer(&self, stdin: Option<ChildStdin>) {
+        *self
+            .clicks
+            .lock()
+            .expect("i3status click writer mutex poisoned") = stdin.map(|stdin| ClickWriter {
+            stdin,
+            started: false,
+        });
+    }
+
+    fn send_click(&self, event: &Value) {
+        let mut clicks = self
+            .clicks
+            .lock()
+            .expect("i3status click writer mutex poisoned");
+        let Some(writer) = clicks.as_mut() else {
+            return;
+        };
+
+        let prefix = if writer.started { "," } else { "[\n" };
+        if let Err(err) = writeln!(writer.stdin, "{prefix}{event}") {
+            eprintln!("vibar/i3status: failed to forward click event: {err}");
+            *clicks = None;
+            return;
+        }
+        writer.started = true;
+    }
+}
+
+pub(crate) struct I3StatusFactory;
+
+pub(crate) const FACTORY: I3StatusFactory = I3StatusFactory;
+
+impl ModuleFactory for I3StatusFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_i3status_module(parsed).upcast())
+    }
+}
+
+fn default_show_colors() -> bool {
+    true
+}
+
+pub(crate) fn parse_config(module: &ModuleConfig) -> Result<I3StatusConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn i3status_registry() -> &'static BackendRegistry<I3StatusSharedKey, SharedI3StatusBackend> {
+    static REGISTRY: OnceLock<BackendRegistry<I3StatusSharedKey, SharedI3StatusBackend>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_i3status(
+    command: String,
+) -> (Subscription<I3StatusUpdate>, Arc<SharedI3StatusBackend>) {
+    let key = I3StatusSharedKey { command };
+
+    let (backend, start_worker) =
+        i3status_registry().get_or_create(key.clone(), SharedI3StatusBackend::new);
+    let receiver = backend.broadcaster.subscribe();
+
+    if start_worker {
+        start_i3status_worker(key, Arc::clone(&backend));
+    }
+
+    (receiver, backend)
+}
+
+fn start_i3status_worker(key: I3StatusSharedKey, backend: Arc<SharedI3StatusBackend>) {
+    std::thread::spawn(move || loop {
+        let error = match run_status_command(&key.command, &backend) {
+            Ok(()) => format!("'{}' exited", key.command),
+            Err(err) => err,
+        };
+        backend.set_click_writer(None);
+        if backend.broadcaster.subscriber_count() == 0 {
+            i3status_registry().remove(&key, &backend);
+            return;
+        }
+
+        backend_errors::report(
+            MODULE_TYPE,
+            format!("{error}; restarting in {RESTART_DELAY_SECS}s"),
+        );
+        backend.broadcaster.broadcast(I3StatusUpdate {
+            blocks: Vec::new(),
+            click_events: false,
+            error: Some(error),
+        });
+        std::thread::sleep(Duration::from_secs(RESTART_DELAY_SECS));
+    });
+}
+
+/// Runs the command until it exits, its output breaks the protocol, or all
+/// subscribers are gone.
+fn run_status_command(command: &str, backend: &SharedI3StatusBackend) -> Result<(), String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to start '{command}': {err}"))?;
+    let result = read_status_stream(&mut child, backend);
+    let _ = child.kill();
+    let _ = child.wait();
+    result
+}
+
+fn read_status_stream(child: &mut Child, backend: &SharedI3StatusBackend) -> Result<(), String> {
+    let stdout = child.stdout.take().expect("child stdout is piped");
+    let mut lines = BufReader::new(stdout).lines();
+
+    let Some(header) = lines.next() else {
+        return Ok(());
+    };
+    let header = header.map_err(|err| format!("failed to read status output: {err}"))?;
+    let header = parse_header(&header)?;
+    backend_errors::clear(MODULE_TYPE);
+    backend.set_click_writer(if header.click_events {
+        child.stdin.take()
+    } else {
+        None
+    });
+
+    for line in lines {
+        let line = line.map_err(|err| format!("failed to read status output: {err}"))?;
+        let Some(blocks) = parse_status_line(&line)? else {
+            continue;
+        };
+        backend.broadcaster.broadcast(I3StatusUpdate {
+            blocks,
+            click_events: header.click_events,
+            error: None,
+        });
+        if backend.broadcaster.subscriber_count() == 0 {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+fn parse_header(line: &str) -> Result<I3BarHeader, String> {
+    let header: I3BarHeader = serde_json::from_str(line.trim())
+        .map_err(|err| format!("invalid i3bar protocol header '{}': {err}", line.trim()))?;
+    if header.version != 1 {
+        return Err(format!(
+            "unsupported i3bar protocol version {}",
+            header.version
+        ));
+    }
+    Ok(header)
+}
+
+/// Parses one line of the infinite status array. Returns `None` for the
+/// opening `[` and blank lines.
+fn parse_status_line(line: &str) -> Result<Option<Vec<I3Block>>, String> {
+    let line = line.trim();
+    let line = line.strip_prefix(',').unwrap_or(line).trim_start();
+    if line.is_empty() || line == "[" {
+        return Ok(None);
+    }
+    let line = line.strip_suffix(',').unwrap_or(line);
+
+    serde_json::from_str(line)
+        .map(Some)
+        .map_err(|err| format!("invalid i3bar status line: {err}"))
+}
+
+fn block_markup(block: &I3Block, show_colors: bool) -> String {
+    let text = if block.markup.as_deref() == Some("pango") {
+        block.full_text.clone()
+    } else {
+        escape_markup_text(&block.full_text)
+    };
+    if !show_colors {
+        return text;
+    }
+
+    let mut attributes = String::new();
+    for (name, value) in [
+        ("foreground", &block.color),
+        ("background", &block.background),
+    ] {
+        if let Some(value) = value.as_deref().filter(|value| is_color_value(value)) {
+            attributes.push_str(&format!(" {name}=\"{value}\""));
+        }
+    }
+    if attributes.is_empty() {
+        text
+    } else {
+        format!("<span{attributes}>{text}</span>")
+    }
+}
+
+/// i3bar colors are `#RRGGBB` or `#RRGGBBAA`.
+fn is_color_value(value: &str) -> bool {
+    value
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn click_event_json(block: &I3Block, button: u32, x: f64, y: f64) -> Value {
+    let mut event = json!({
+        "button": button,
+        "x": x.round() as i64,
+        "y": y.round() as i64,
+        "relative_x": x.round() as i64,
+        "relative_y": y.round() as i64,
+    });
+    if let Some(name) = &block.name {
+        event["name"] = Value::from(name.as_str());
+    }
+    if let Some(instance) = &block.instance {
+        event["instance"] = Value::from(instance.as_str());
+    }
+    event
+}
+
+fn build_i3status_module(config: I3StatusConfig) -> GtkBox {
+    let container = GtkBox::new(Orientation::Horizontal, 0);
+    container.add_css_class("module");
+    container.add_css_class("i3status");
+    apply_css_classes(&container, config.class.as_deref());
+
+    let (subscription, backend) = subscribe_shared_i3status(config.command.clone());
+
+    attach_subscription(&container, subscription, move |container, update| {
+        while let Some(child) = container.first_child() {
+            container.remove(&child);
+        }
+
+        if let Some(error) = update.error {
+            let label = Label::new(Some("i3status?"));
+            label.add_css_class("i3status-status");
+            label.set_tooltip_text(Some(&error));
+            container.append(&label);
+            return;
+        }
+
+        for block in update.blocks {
+            let label = build_block_label(&block, &config);
+            if update.click_events {
+                attach_click_forwarding(&label, block, Arc::clone(&backend));
+            }
+            container.append(&label);
+        }
+    });
+
+    container
+}
+
+fn build_block_label(block: &I3Block, config: &I3StatusConfig) -> Label {
+    let label = Label::new(None);
+    label.set_markup(&block_markup(block, config.show_colors));
+    label.add_css_class("i3status-block");
+    apply_css_classes(&label, config.block_class.as_deref());
+    if block.urgent {
+        label.add_css_class("urgent");
+    }
+    if let Some(short_text) = block.short_text.as_deref().filter(|text| !text.is_empty()) {
+        label.set_tooltip_text(Some(short_text));
+    }
+    label.set_visible(!block.full_text.trim().is_empty());
+    label
+}
+
+fn attach_click_forwarding(label: &Label, block: I3Block, backend: Arc<SharedI3StatusBackend>) {
+    label.add_css_class("clickable");
+    let click = GestureClick::builder().button(0).build();
+    click.connect_pressed(move |gesture, _, x, y| {
+        backend.send_click(&click_event_json(&block, gesture.current_button(), x, y));
+    });
+    label.add_controller(click);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("exec", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'i3status'"));
+    }
+
+    #[test]
+    fn parse_config_requires_command_and_reads_aliases() {
+        let module = ModuleConfig::new(MODULE_TYPE, Map::new());
+        assert!(parse_config(&module).is_err());
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_str(
+                r#"{"command":"i3status","block_class":"pill","show_colors":false}"#,
+            )
+            .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.command, "i3status");
+        assert_eq!(cfg.block_class.as_deref(), Some("pill"));
+        assert!(!cfg.show_colors);
+    }
+
+    #[test]
+    fn parse_header_reads_click_events() {
+        assert_eq!(
+            parse_header(r#"{"version":1,"click_events":true}"#),
+            Ok(I3BarHeader {
+                version: 1,
+                click_events: true
+            })
+        );
+        assert!(
+            !parse_header(r#"{ "version": 1 }"#)
+                .expect("header should parse")
+                .click_events
+        );
+        assert!(parse_header("[").is_err());
+        assert!(parse_header(r#"{"version":2}"#).is_err());
+    }
+
+    #[test]
+    fn parse_status_line_handles_array_framing() {
+        assert_eq!(parse_status_line("["), Ok(None));
+        assert_eq!(parse_status_line(""), Ok(None));
+
+        let first = parse_status_line(r#"[{"full_text":"a","name":"disk"}]"#)
+            .expect("line should parse")
+            .expect("line has blocks");
+        assert_eq!(first[0].full_text, "a");
+        assert_eq!(first[0].name.as_deref(), Some("disk"));
+
+        let next = parse_status_line(r#",[{"full_text":"b","urgent":true}]"#)
+            .expect("line should parse")
+            .expect("line has blocks");
+        assert!(next[0].urgent);
+
+        let trailing = parse_status_line(r#"[{"full_text":"c"}],"#)
+            .expect("line should parse")
+            .expect("line has blocks");
+        assert_eq!(trailing[0].full_text, "c");
+
+        assert!(parse_status_line("[{").is_err());
+    }
+
+    #[test]
+    fn block_markup_escapes_plain_text_and_applies_colors() {
+        let block = I3Block {
+            full_text: "a < b".to_string(),
+            color: Some("#ff0000".to_string()),
+            background: Some("red\" weight=\"bold".to_string()),
+            ..I3Block::default()
+        };
+        assert_eq!(
+            block_markup(&block, true),
+            "<span foreground=\"#ff0000\">a &lt; b</span>"
+        );
+        assert_eq!(block_markup(&block, false), "a &lt; b");
+
+        let pango = I3Block {
+            full_text: "<b>x</b>".to_string(),
+            markup: Some("pango".to_string()),
+            ..I3Block::default()
+        };
+        assert_eq!(block_markup(&pango, true), "<b>x</b>");
+    }
+
+    #[test]
+    fn click_event_json_includes_block_identity() {
+        let block = I3Block {
+            name: Some("volume".to_string()),
+            instance: Some("master".to_string()),
+            ..I3Block::default()
+        };
+        let event = click_event_json(&block, 3, 4.4, 10.6);
+        assert_eq!(event["name"], "volume");
+        assert_eq!(event["instance"], "master");
+        assert_eq!(event["button"], 3);
+        assert_eq!(event["x"], 4);
+        assert_eq!(event["y"], 11);
+
+        let anonymous = click_event_json(&I3Block::default(), 1, 0.0, 0.0);
+        assert!(anonymous.get("name").is_none());
+    }
+}
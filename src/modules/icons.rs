@@ -0,0 +1,58 @@
+//! Default icon glyphs with plain Unicode fallbacks for setups without a
+//! Nerd Font. The choice is process-wide and applied when module configs are
+//! parsed, so it only affects defaults; explicit config values always win.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static NERD_FONTS: AtomicBool = AtomicBool::new(true);
+
+pub(crate) fn set_nerd_fonts(enabled: bool) {
+    NERD_FONTS.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn nerd_fonts_enabled() -> bool {
+    NERD_FONTS.load(Ordering::Relaxed)
+}
+
+/// A default glyph in both its Nerd Font and plain Unicode form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Glyph {
+    nerd: &'static str,
+    plain: &'static str,
+}
+
+impl Glyph {
+    pub(crate) const fn new(nerd: &'static str, plain: &'static str) -> Self {
+        Self { nerd, plain }
+    }
+
+    pub(crate) fn get(self) -> &'static str {
+        self.pick(nerd_fonts_enabled())
+    }
+
+    fn pick(self, nerd_fonts: bool) -> &'static str {
+        if nerd_fonts {
+            self.nerd
+        } else {
+            self.plain
+        }
+    }
+}
+
+/// Returns whichever default icon list matches the active glyph set.
+pub(crate) fn default_icon_list(nerd: &[&str], plain: &[&str]) -> Vec<String> {
+    let icons = if nerd_fonts_enabled() { nerd } else { plain };
+    icons.iter().map(|icon| icon.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyph_pick_selects_variant() {
+        let glyph = Glyph::new("\u{f04b}", "▶");
+        assert_eq!(glyph.pick(true), "\u{f04b}");
+        assert_eq!(glyph.pick(false), "▶");
+    }
+}
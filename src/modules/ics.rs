@@ -0,0 +1,611 @@
+//! Minimal iCalendar (`.ics`) reader shared by the `clock` calendar popover
+//! and the `next-event` module.
+//!
+//! Reads `VEVENT` start/end, `SUMMARY`, simple `RRULE`s (`FREQ` with
+//! `INTERVAL`, `COUNT`, `UNTIL`; other rule parts are ignored) and
+//! `EXDATE`. Times with `TZID` are taken as local time.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+use crate::config::resolve_config_relative_path;
+use crate::modules::broadcaster::{BackendRegistry, Broadcaster, Subscription};
+
+/// Upper bound on generated occurrences per event, against runaway rules.
+const MAX_OCCURRENCES: u32 = 100_000;
+/// How often calendar files are checked for changes.
+const CALENDAR_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CalendarEvent {
+    pub(crate) summary: String,
+    /// Local start time; midnight for all-day events.
+    start: NaiveDateTime,
+    pub(crate) all_day: bool,
+    /// Number of days each occurrence touches (at least 1).
+    days: i64,
+    recurrence: Option<Recurrence>,
+    excluded: BTreeSet<NaiveDate>,
+}
+
+impl CalendarEvent {
+    pub(crate) fn start_time(&self) -> NaiveTime {
+        self.start.time()
+    }
+
+    /// Start dates of the occurrences that touch `from..=to`.
+    fn occurrences_touching(&self, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        let earliest_start = from - chrono::Duration::days(self.days - 1);
+        let first = self.start.date();
+        let Some(recurrence) = self.recurrence.as_ref() else {
+            return (first >= earliest_start && first <= to)
+                .then_some(first)
+                .into_iter()
+                .collect();
+        };
+
+        let mut dates = Vec::new();
+        let mut produced = 0;
+        for step in 0..MAX_OCCURRENCES {
+            let Some(date) = nth_occurrence(first, recurrence, step) else {
+                // Skipped dates such as the 31st in a 30-day month.
+                continue;
+            };
+            if date > to || recurrence.until.is_some_and(|until| date > until) {
+                break;
+            }
+            if recurrence.count.is_some_and(|count| produced >= count) {
+                break;
+            }
+            produced += 1;
+            if date >= earliest_start && !self.excluded.contains(&date) {
+                dates.push(date);
+            }
+        }
+        dates
+    }
+}
+
+fn nth_occurrence(first: NaiveDate, recurrence: &Recurrence, step: u32) -> Option<NaiveDate> {
+    let offset = i64::from(step) * i64::from(recurrence.interval);
+    match recurrence.frequency {
+        Frequency::Daily => first.checked_add_signed(chrono::Duration::days(offset)),
+        Frequency::Weekly => first.checked_add_signed(chrono::Duration::weeks(offset)),
+        Frequency::Monthly => {
+            let months = i64::from(first.month0()) + offset;
+            let year = first.year() + i32::try_from(months / 12).ok()?;
+            NaiveDate::from_ymd_opt(year, u32::try_from(months % 12).ok()? + 1, first.day())
+        }
+        Frequency::Yearly => NaiveDate::from_ymd_opt(
+            first.year() + i32::try_from(offset).ok()?,
+            first.month(),
+            first.day(),
+        ),
+    }
+}
+
+/// Events from all configured calendars.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct CalendarEvents {
+    events: Vec<CalendarEvent>,
+}
+
+impl CalendarEvents {
+    /// Days in `from..=to` that have at least one event.
+    pub(crate) fn days_with_events(&self, from: NaiveDate, to: NaiveDate) -> BTreeSet<NaiveDate> {
+        let mut days = BTreeSet::new();
+        for event in &self.events {
+            for start in event.occurrences_touching(from, to) {
+                for offset in 0..event.days {
+                    let day = start + chrono::Duration::days(offset);
+                    if day >= from && day <= to {
+                        days.insert(day);
+                    }
+                }
+            }
+        }
+        days
+    }
+
+    /// Events on `day`: all-day events first, then by start time.
+    pub(crate) fn events_on(&self, day: NaiveDate) -> Vec<&CalendarEvent> {
+        let mut events = self
+            .events
+            .iter()
+            .filter(|event| !event.occurrences_touching(day, day).is_empty())
+            .collect::<Vec<_>>();
+        events.sort_by_key(|event| (!event.all_day, event.start.time(), event.summary.clone()));
+        events
+    }
+
+    /// The earliest occurrence starting after `now` and no later than
+    /// `until`; all-day events only count when `include_all_day` is set.
+    pub(crate) fn next_after(
+        &self,
+        now: NaiveDateTime,
+        until: NaiveDateTime,
+        include_all_day: bool,
+    ) -> Option<(NaiveDateTime, &CalendarEvent)> {
+        self.events
+            .iter()
+            .filter(|event| include_all_day || !event.all_day)
+            .flat_map(|event| {
+                event
+                    .occurrences_touching(now.date(), until.date())
+                    .into_iter()
+                    .map(move |date| (date.and_time(event.start.time()), event))
+            })
+            .filter(|(start, _)| *start > now && *start <= until)
+            .min_by(|(a_start, a), (b_start, b)| (a_start, &a.summary).cmp(&(b_start, &b.summary)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CalendarEventsKey {
+    paths: Vec<PathBuf>,
+}
+
+fn calendar_events_registry(
+) -> &'static BackendRegistry<CalendarEventsKey, Broadcaster<Arc<CalendarEvents>>> {
+    static REGISTRY: OnceLock<
+        BackendRegistry<CalendarEventsKey, Broadcaster<Arc<CalendarEvents>>>,
+    > = OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+/// Events from the `.ics` files behind `paths` (`~/` is expanded, relative
+/// paths resolve against the config file's directory), re-read whenever one
+/// of them changes. Modules with the same path list share one worker.
+pub(crate) fn subscribe_calendar_events(
+    paths: &[String],
+    config_source: Option<&Path>,
+) -> Subscription<Arc<CalendarEvents>> {
+    let key = CalendarEventsKey {
+        paths: paths
+            .iter()
+            .map(|path| resolve_config_relative_path(path, config_source))
+            .collect(),
+    };
+    let (broadcaster, start_worker) =
+        calendar_events_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_calendar_events_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_calendar_events_worker(
+    key: CalendarEventsKey,
+    broadcaster: Arc<Broadcaster<Arc<CalendarEvents>>>,
+) {
+    std::thread::spawn(move || {
+        let mut last_fingerprint = None;
+        loop {
+            let files = calendar_files(&key.paths);
+            let fingerprint = files_fingerprint(&files);
+            if last_fingerprint.as_ref() != Some(&fingerprint) {
+                broadcaster.broadcast(Arc::new(load_calendar_files(&files)));
+                last_fingerprint = Some(fingerprint);
+            }
+            if broadcaster.subscriber_count() == 0 {
+                calendar_events_registry().remove(&key, &broadcaster);
+                return;
+            }
+            std::thread::sleep(CALENDAR_POLL_INTERVAL);
+        }
+    });
+}
+
+/// `.ics` files behind the configured paths; directories (such as vdirsyncer
+/// or khal calendars) contribute the `.ics` files directly inside them.
+fn calendar_files(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(path) else {
+            continue;
+        };
+        let mut dir_files = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "ics"))
+            .collect::<Vec<_>>();
+        dir_files.sort();
+        files.extend(dir_files);
+    }
+    files
+}
+
+/// Changes whenever a calendar file is added, removed, or modified.
+fn files_fingerprint(files: &[PathBuf]) -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+    files
+        .iter()
+        .map(|path| {
+            let metadata = fs::metadata(path).ok();
+            (
+                path.clone(),
+                metadata.as_ref().and_then(|meta| meta.modified().ok()),
+                metadata.map(|meta| meta.len()).unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+fn load_calendar_files(files: &[PathBuf]) -> CalendarEvents {
+    let mut events = Vec::new();
+    for path in files {
+        match fs::read_to_string(path) {
+            Ok(content) => events.extend(parse_ics(&content)),
+            Err(err) => eprintln!("Failed to read calendar {}: {err}", path.display()),
+        }
+    }
+    CalendarEvents { events }
+}
+
+#[derive(Default)]
+struct EventBuilder {
+    uid: Option<String>,
+    summary: String,
+    start: Option<(NaiveDateTime, bool)>,
+    end: Option<(NaiveDateTime, bool)>,
+    recurrence: Option<Recurrence>,
+    excluded: BTreeSet<NaiveDate>,
+    recurrence_id: Option<NaiveDate>,
+    cancelled: bool,
+}
+
+fn parse_ics(content: &str) -> Vec<CalendarEvent> {
+    let mut masters = Vec::new();
+    // Moved or edited occurrences replace the master's occurrence on their
+    // original date.
+    let mut overridden = HashMap::<String, Vec<NaiveDate>>::new();
+    let mut current: Option<EventBuilder> = None;
+    let mut nested_depth = 0usize;
+
+    for line in unfold_lines(content) {
+        let Some((name, params, value)) = split_property(&line) else {
+            continue;
+        };
+        match (name.as_str(), value) {
+            ("BEGIN", component) if component.eq_ignore_ascii_case("VEVENT") => {
+                current = Some(EventBuilder::default());
+                nested_depth = 0;
+            }
+            ("BEGIN", _) if current.is_some() => nested_depth += 1,
+            ("END", component) if component.eq_ignore_ascii_case("VEVENT") => {
+                let Some(builder) = current.take() else {
+                    continue;
+                };
+                if let (Some(uid), Some(date)) = (builder.uid.clone(), builder.recurrence_id) {
+                    overridden.entry(uid).or_default().push(date);
+                }
+                if let Some(event) = builder.build() {
+                    masters.push(event);
+                }
+            }
+            ("END", _) if current.is_some() => nested_depth = nested_depth.saturating_sub(1),
+            (_, value) if nested_depth == 0 => {
+                if let Some(builder) = current.as_mut() {
+                    builder.apply(&name, &params, value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    masters
+        .into_iter()
+        .map(|(uid, mut event)| {
+            if event.recurrence.is_some() {
+                if let Some(dates) = uid.and_then(|uid| overridden.get(&uid)) {
+                    event.excluded.extend(dates);
+                }
+            }
+            event
+        })
+        .collect()
+}
+
+impl EventBuilder {
+    fn apply(&mut self, name: &str, params: &str, value: &str) {
+        match name {
+            "UID" => self.uid = Some(value.to_string()),
+            "SUMMARY" => self.summary = unescape_text(value),
+            "DTSTART" => self.start = parse_date_time(params, value),
+            "DTEND" => self.end = parse_date_time(params, value),
+            "RRULE" => self.recurrence = parse_rrule(value),
+            "EXDATE" => self.excluded.extend(
+                value
+                    .split(',')
+                    .filter_map(|item| parse_date_time(params, item))
+                    .map(|(start, _)| start.date()),
+            ),
+            "RECURRENCE-ID" => {
+                self.recurrence_id = parse_date_time(params, value).map(|(start, _)| start.date())
+            }
+            "STATUS" => self.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+
+    fn build(self) -> Option<(Option<String>, CalendarEvent)> {
+        if self.cancelled {
+            return None;
+        }
+        let (start, all_day) = self.start?;
+        let days = match self.end {
+            Some((end, _)) if all_day => (end.date() - start.date()).num_days().max(1),
+            // A timed event ending at midnight does not touch the next day.
+            Some((end, _)) if end > start => {
+                let last = end - chrono::Duration::seconds(1);
+                (last.date() - start.date()).num_days() + 1
+            }
+            _ => 1,
+        };
+        Some((
+            self.uid,
+            CalendarEvent {
+                summary: self.summary,
+                start,
+                all_day,
+                days,
+                recurrence: self.recurrence,
+                excluded: self.excluded,
+            },
+        ))
+    }
+}
+
+/// Joins folded continuation lines (RFC 5545 3.1).
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.lines() {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+/// Splits `NAME;PARAMS:VALUE`, ignoring `:` inside quoted parameter values.
+fn split_property(line: &str) -> Option<(String, String, &str)> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(index, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(index),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+    Some((
+        name.to_ascii_uppercase(),
+        params.to_ascii_uppercase(),
+        value,
+    ))
+}
+
+/// Returns the local start time and whether the value is a plain date.
+fn parse_date_time(params: &str, value: &str) -> Option<(NaiveDateTime, bool)> {
+    let value = value.trim();
+    let date_only = params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME");
+    if date_only || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some((date.and_time(NaiveTime::MIN), true));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        let local = Utc.from_utc_datetime(&naive).with_timezone(&Local);
+        return Some((local.naive_local(), false));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some((naive, false))
+}
+
+fn parse_rrule(value: &str) -> Option<Recurrence> {
+    let mut recurrence = Recurrence {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+    };
+    let mut frequency = None;
+    for part in value.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Some(Frequency::Daily),
+                    "WEEKLY" => Some(Frequency::Weekly),
+                    "MONTHLY" => Some(Frequency::Monthly),
+                    "YEARLY" => Some(Frequency::Yearly),
+                    _ => None,
+                }
+            }
+            "INTERVAL" => recurrence.interval = value.parse().ok().filter(|n| *n > 0)?,
+            "COUNT" => recurrence.count = value.parse().ok(),
+            "UNTIL" => recurrence.until = parse_date_time("", value).map(|(end, _)| end.date()),
+            _ => {}
+        }
+    }
+    recurrence.frequency = frequency?;
+    Some(recurrence)
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push(' '),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("valid date")
+    }
+
+    fn events(content: &str) -> CalendarEvents {
+        CalendarEvents {
+            events: parse_ics(content),
+        }
+    }
+
+    #[test]
+    fn parse_ics_reads_timed_and_all_day_events() {
+        let calendar = events(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Dentist\\, downtown\r\n\
+             DTSTART:20261016T093000\r\n\
+             DTEND:20261016T103000\r\n\
+             BEGIN:VALARM\r\n\
+             SUMMARY:reminder\r\n\
+             END:VALARM\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Conference with a very long\r\n  name\r\n\
+             DTSTART;VALUE=DATE:20261015\r\n\
+             DTEND;VALUE=DATE:20261018\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             SUMMARY:Cancelled\r\n\
+             STATUS:CANCELLED\r\n\
+             DTSTART;VALUE=DATE:20261016\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        );
+
+        let on_day = calendar.events_on(date(2026, 10, 16));
+        let summaries = on_day
+            .iter()
+            .map(|event| event.summary.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summaries,
+            ["Conference with a very long name", "Dentist, downtown"]
+        );
+        assert_eq!(
+            on_day[1].start_time(),
+            NaiveTime::from_hms_opt(9, 30, 0).unwrap()
+        );
+        assert_eq!(
+            calendar.days_with_events(date(2026, 10, 1), date(2026, 10, 31)),
+            BTreeSet::from([date(2026, 10, 15), date(2026, 10, 16), date(2026, 10, 17)])
+        );
+    }
+
+    #[test]
+    fn next_after_finds_earliest_upcoming_occurrence() {
+        let calendar = events(
+            "BEGIN:VEVENT\n\
+             SUMMARY:Standup\n\
+             DTSTART:20261001T090000\n\
+             RRULE:FREQ=DAILY\n\
+             END:VEVENT\n\
+             BEGIN:VEVENT\n\
+             SUMMARY:Holiday\n\
+             DTSTART;VALUE=DATE:20261017\n\
+             END:VEVENT\n",
+        );
+        let at = |day, hour, minute| date(2026, 10, day).and_hms_opt(hour, minute, 0).unwrap();
+
+        let (start, event) = calendar
+            .next_after(at(16, 9, 0), at(17, 12, 0), false)
+            .expect("standup tomorrow");
+        assert_eq!((start, event.summary.as_str()), (at(17, 9, 0), "Standup"));
+
+        let (start, event) = calendar
+            .next_after(at(16, 9, 0), at(17, 12, 0), true)
+            .expect("holiday at midnight");
+        assert_eq!((start, event.summary.as_str()), (at(17, 0, 0), "Holiday"));
+
+        assert!(calendar
+            .next_after(at(16, 9, 0), at(16, 23, 0), false)
+            .is_none());
+    }
+
+    #[test]
+    fn recurring_events_respect_rules_and_exceptions() {
+        let calendar = events(
+            "BEGIN:VEVENT\n\
+             UID:standup\n\
+             SUMMARY:Standup\n\
+             DTSTART:20261001T090000\n\
+             RRULE:FREQ=WEEKLY;INTERVAL=2;COUNT=3\n\
+             EXDATE:20261015T090000\n\
+             END:VEVENT\n\
+             BEGIN:VEVENT\n\
+             UID:standup\n\
+             RECURRENCE-ID:20261029T090000\n\
+             SUMMARY:Standup (moved)\n\
+             DTSTART:20261030T090000\n\
+             END:VEVENT\n\
+             BEGIN:VEVENT\n\
+             SUMMARY:Rent\n\
+             DTSTART;VALUE=DATE:20260131\n\
+             RRULE:FREQ=MONTHLY;UNTIL=20260601\n\
+             END:VEVENT\n",
+        );
+
+        assert_eq!(
+            calendar.days_with_events(date(2026, 10, 1), date(2026, 11, 30)),
+            BTreeSet::from([date(2026, 10, 1), date(2026, 10, 30)])
+        );
+        assert_eq!(
+            calendar.days_with_events(date(2026, 1, 1), date(2026, 12, 31)),
+            BTreeSet::from([
+                date(2026, 1, 31),
+                date(2026, 3, 31),
+                date(2026, 5, 31),
+                date(2026, 10, 1),
+                date(2026, 10, 30),
+            ])
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use gtk::prelude::*;
+use gtk::{GestureClick, Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::{render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel};
+
+use super::ModuleFactory;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_COUNTDOWN_SECS: u32 = 60;
+const DEFAULT_IDLE_COUNTDOWN_FORMAT: &str = "󰒲 {seconds}s";
+const DEFAULT_MARKER_FILENAME: &str = "vibar-idle-warning";
+pub(crate) const MODULE_TYPE: &str = "idle-countdown";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct IdleCountdownConfig {
+    #[serde(default)]
+    pub(crate) marker: Option<String>,
+    #[serde(
+        rename = "countdown-secs",
+        alias = "countdown_secs",
+        default = "default_countdown_secs"
+    )]
+    pub(crate) countdown_secs: u32,
+    #[serde(default = "default_idle_countdown_format")]
+    pub(crate) format: String,
+    #[serde(rename = "format-idle", alias = "format_idle", default)]
+    pub(crate) format_idle: String,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct IdleCountdownUpdate {
+    text: String,
+    /// The countdown ran out while the marker is still present.
+    idle: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct IdleCountdownSharedKey {
+    marker: PathBuf,
+    countdown_secs: u32,
+    format: String,
+    format_idle: String,
+}
+
+pub(crate) struct IdleCountdownFactory;
+
+pub(crate) const FACTORY: IdleCountdownFactory = IdleCountdownFactory;
+
+impl ModuleFactory for IdleCountdownFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_idle_countdown_module(parsed).upcast())
+    }
+}
+
+fn default_countdown_secs() -> u32 {
+    DEFAULT_COUNTDOWN_SECS
+}
+
+fn default_idle_countdown_format() -> String {
+    DEFAULT_IDLE_COUNTDOWN_FORMAT.to_string()
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<IdleCountdownConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn resolve_marker_path(marker: Option<&str>) -> PathBuf {
+    if let Some(marker) = marker {
+        if let Some(stripped) = marker.strip_prefix("~/") {
+            if let Ok(home) = env::var("HOME") {
+                return PathBuf::from(home).join(stripped);
+            }
+        }
+        return PathBuf::from(marker);
+    }
+
+    if let Ok(runtime_dir) = env::var("XDG_RUNTIME_DIR") {
+        return PathBuf::from(runtime_dir).join(DEFAULT_MARKER_FILENAME);
+    }
+    env::temp_dir().join(DEFAULT_MARKER_FILENAME)
+}
+
+fn idle_countdown_registry(
+) -> &'static BackendRegistry<IdleCountdownSharedKey, Broadcaster<IdleCountdownUpdate>> {
+    static REGISTRY: OnceLock<
+        BackendRegistry<IdleCountdownSharedKey, Broadcaster<IdleCountdownUpdate>>,
+    > = OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_idle_countdown(
+    key: IdleCountdownSharedKey,
+) -> Subscription<IdleCountdownUpdate> {
+    let (broadcaster, start_worker) =
+        idle_countdown_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_idle_countdown_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_idle_countdown_worker(
+    key: IdleCountdownSharedKey,
+    broadcaster: Arc<Broadcaster<IdleCountdownUpdate>>,
+) {
+    std::thread::spawn(move || loop {
+        let update = fs::metadata(&key.marker)
+            .and_then(|metadata| metadata.modified())
+            .map(|marked_at| {
+                let remaining = remaining_secs(marked_at, SystemTime::now(), key.countdown_secs);
+                if remaining == 0 {
+                    IdleCountdownUpdate {
+                        text: render_format(&key.format_idle, remaining),
+                        idle: true,
+                    }
+                } else {
+                    IdleCountdownUpdate {
+                        text: render_format(&key.format, remaining),
+                        idle: false,
+                    }
+                }
+            })
+            .unwrap_or(IdleCountdownUpdate {
+                text: String::new(),
+                idle: false,
+            });
+        broadcaster.broadcast(update);
+        if broadcaster.subscriber_count() == 0 {
+            idle_countdown_registry().remove(&key, &broadcaster);
+            return;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    });
+}
+
+fn build_idle_countdown_module(config: IdleCountdownConfig) -> Label {
+    let click_command = config.click.or(config.on_click);
+    let label = ModuleLabel::new("idle-countdown")
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(click_command.clone())
+        .into_label();
+    label.set_visible(false);
+
+    let marker = resolve_marker_path(config.marker.as_deref());
+
+    if click_command.is_none() {
+        // The click itself is seat activity, so swayidle has already restarted
+        // its timers; dropping the marker just hides the stale countdown.
+        label.add_css_class("clickable");
+        let click = GestureClick::builder().button(1).build();
+        click.connect_pressed({
+            let marker = marker.clone();
+            let label = label.clone();
+            move |_, _, _, _| {
+                let _ = fs::remove_file(&marker);
+                label.set_visible(false);
+            }
+        });
+        label.add_controller(click);
+    }
+
+    let subscription = subscribe_shared_idle_countdown(IdleCountdownSharedKey {
+        marker,
+        countdown_secs: config.countdown_secs,
+        format: config.format,
+        format_idle: config.format_idle,
+    });
+
+    attach_subscription(&label, subscription, |label, update| {
+        let visible = !update.text.trim().is_empty();
+        label.set_visible(visible);
+        if visible {
+            label.set_markup(&update.text);
+        }
+        if update.idle {
+            label.add_css_class("idle");
+        } else {
+            label.remove_css_class("idle");
+        }
+    });
+
+    label
+}
+
+fn remaining_secs(marked_at: SystemTime, now: SystemTime, countdown_secs: u32) -> u64 {
+    let elapsed = now
+        .duration_since(marked_at)
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+    u64::from(countdown_secs).saturating_sub(elapsed)
+}
+
+fn render_format(format: &str, remaining_secs: u64) -> String {
+    let seconds = remaining_secs.to_string();
+    let time = format!("{}:{:02}", remaining_secs / 60, remaining_secs % 60);
+    render_markup_template(format, &[("{seconds}", &seconds), ("{time}", &time)])
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'idle-countdown'"));
+    }
+
+    #[test]
+    fn parse_config_uses_defaults() {
+        let module = ModuleConfig::new(MODULE_TYPE, Map::new());
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.countdown_secs, DEFAULT_COUNTDOWN_SECS);
+        assert_eq!(cfg.format, DEFAULT_IDLE_COUNTDOWN_FORMAT);
+        assert!(cfg.format_idle.is_empty());
+        assert!(cfg.marker.is_none());
+    }
+
+    #[test]
+    fn remaining_secs_counts_down_and_saturates() {
+        let marked_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let now = marked_at + Duration::from_secs(15);
+        assert_eq!(remaining_secs(marked_at, now, 60), 45);
+        assert_eq!(remaining_secs(marked_at, now, 10), 0);
+        assert_eq!(remaining_secs(now, marked_at, 60), 60);
+    }
+
+    #[test]
+    fn render_format_replaces_placeholders() {
+        assert_eq!(render_format("{seconds}s / {time}", 75), "75s / 1:15");
+    }
+}
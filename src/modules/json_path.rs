@@ -0,0 +1,134 @@
+//! Minimal JSONPath subset for picking values out of command output:
+//! `$.data.temp`, `$.items[0].name`, `$["key.with.dots"]`. The leading `$`
+//! is optional.
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    pub(crate) fn parse(path: &str) -> Result<Self, String> {
+        let invalid = |reason: &str| format!("invalid json path '{path}': {reason}");
+        let mut rest = path.trim();
+        rest = rest.strip_prefix('$').unwrap_or(rest);
+        let mut segments = Vec::new();
+
+        while !rest.is_empty() {
+            if let Some(after_dot) = rest.strip_prefix('.') {
+                let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+                let key = &after_dot[..end];
+                if key.is_empty() {
+                    return Err(invalid("empty key after '.'"));
+                }
+                segments.push(Segment::Key(key.to_string()));
+                rest = &after_dot[end..];
+            } else if let Some(after_bracket) = rest.strip_prefix('[') {
+                let end = after_bracket
+                    .find(']')
+                    .ok_or_else(|| invalid("missing ']'"))?;
+                let inner = after_bracket[..end].trim();
+                let quoted = inner
+                    .strip_prefix('"')
+                    .and_then(|inner| inner.strip_suffix('"'))
+                    .or_else(|| {
+                        inner
+                            .strip_prefix('\'')
+                            .and_then(|inner| inner.strip_suffix('\''))
+                    });
+                let segment = match quoted {
+                    Some(key) => Segment::Key(key.to_string()),
+                    None => Segment::Index(
+                        inner
+                            .parse()
+                            .map_err(|_| invalid("index must be a non-negative integer"))?,
+                    ),
+                };
+                segments.push(segment);
+                rest = &after_bracket[end + 1..];
+            } else if segments.is_empty() && !path.trim().starts_with('$') {
+                // Bare `data.temp` reads like `$.data.temp`.
+                let end = rest.find(['.', '[']).unwrap_or(rest.len());
+                segments.push(Segment::Key(rest[..end].to_string()));
+                rest = &rest[end..];
+            } else {
+                return Err(invalid("expected '.' or '['"));
+            }
+        }
+
+        Ok(Self { segments })
+    }
+
+    pub(crate) fn select<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.segments
+            .iter()
+            .try_fold(value, |current, segment| match segment {
+                Segment::Key(key) => current.get(key),
+                Segment::Index(index) => current.get(index),
+            })
+    }
+}
+
+/// Display text for a selected value: strings unquoted, `null` as empty,
+/// objects/arrays as compact JSON.
+pub(crate) fn value_to_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn select(path: &str, value: &Value) -> Option<Value> {
+        JsonPath::parse(path)
+            .expect("path should parse")
+            .select(value)
+            .cloned()
+    }
+
+    #[test]
+    fn select_walks_keys_and_indices() {
+        let value = json!({
+            "data": { "temp": 21.5, "items": [{ "name": "a" }, { "name": "b" }] },
+            "key.with.dots": true
+        });
+        assert_eq!(select("$.data.temp", &value), Some(json!(21.5)));
+        assert_eq!(select("data.temp", &value), Some(json!(21.5)));
+        assert_eq!(select("$.data.items[1].name", &value), Some(json!("b")));
+        assert_eq!(select("$[\"key.with.dots\"]", &value), Some(json!(true)));
+        assert_eq!(select("$['data']['temp']", &value), Some(json!(21.5)));
+        assert_eq!(select("$", &value), Some(value.clone()));
+        assert_eq!(select("$.data.missing", &value), None);
+        assert_eq!(select("$.data.items[5]", &value), None);
+    }
+
+    #[test]
+    fn parse_rejects_malformed_paths() {
+        assert!(JsonPath::parse("$.").is_err());
+        assert!(JsonPath::parse("$.a[").is_err());
+        assert!(JsonPath::parse("$.a[-1]").is_err());
+        assert!(JsonPath::parse("$a").is_err());
+    }
+
+    #[test]
+    fn value_to_text_formats_scalars_and_containers() {
+        assert_eq!(value_to_text(&json!("hi")), "hi");
+        assert_eq!(value_to_text(&json!(3)), "3");
+        assert_eq!(value_to_text(&json!(null)), "");
+        assert_eq!(value_to_text(&json!([1, 2])), "[1,2]");
+    }
+}
@@ -10,8 +10,11 @@ use serde_json::Value;
 use crate::modules::broadcaster::{
     attach_subscription, BackendRegistry, Broadcaster, Subscription,
 };
+use crate::modules::text::{attach_text_subscription, visible_block, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
 use crate::modules::{
-    escape_markup_text, render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel,
+    escape_markup_text, reaches_threshold, render_markup_template, state_store, ModuleBuildContext,
+    ModuleConfig, ModuleLabel,
 };
 
 use super::ModuleFactory;
@@ -19,16 +22,31 @@ use super::ModuleFactory;
 const MIN_MEMORY_INTERVAL_SECS: u32 = 1;
 const DEFAULT_MEMORY_INTERVAL_SECS: u32 = 5;
 const DEFAULT_MEMORY_FORMAT: &str = "{used_percentage}%";
+const MEMORY_WARNING_CLASS: &str = "memory-warning";
+const SWAP_WARNING_CLASS: &str = "memory-swap-warning";
 pub(crate) const MODULE_TYPE: &str = "memory";
 
 #[derive(Debug, Deserialize, Clone)]
 pub(crate) struct MemoryConfig {
     #[serde(default)]
     pub(crate) format: Option<String>,
+    /// Used instead of `format` while swap is in use.
+    #[serde(rename = "format-swap", alias = "format_swap", default)]
+    pub(crate) format_swap: Option<String>,
     #[serde(default)]
     pub(crate) click: Option<String>,
     #[serde(rename = "on-click", default)]
     pub(crate) on_click: Option<String>,
+    /// Used percentage from which the label carries `.memory-warning`.
+    #[serde(rename = "warning-threshold", alias = "warning_threshold", default)]
+    pub(crate) warning_threshold: Option<u8>,
+    /// Swap percentage from which the label carries `.memory-swap-warning`.
+    #[serde(
+        rename = "swap-warning-threshold",
+        alias = "swap_warning_threshold",
+        default
+    )]
+    pub(crate) swap_warning_threshold: Option<u8>,
     #[serde(default = "default_memory_interval")]
     pub(crate) interval_secs: u32,
     #[serde(default)]
@@ -36,21 +54,32 @@ pub(crate) struct MemoryConfig {
 }
 
 #[derive(Debug, Clone)]
-struct MemoryStatus {
-    total_bytes: u64,
-    used_bytes: u64,
+pub(super) struct MemoryStatus {
+    pub(super) total_bytes: u64,
+    pub(super) used_bytes: u64,
     free_bytes: u64,
     available_bytes: u64,
+    swap_total_bytes: u64,
+    swap_used_bytes: u64,
+    /// Compressed size of the data in all zram devices.
+    zram_compressed_bytes: u64,
 }
 
 #[derive(Debug, Clone)]
 struct MemoryUpdate {
     text: String,
+    tooltip: String,
+    /// `None` when /proc/meminfo could not be read.
+    used_percentage: Option<u16>,
+    swap_percentage: Option<u16>,
+    published: state_store::PublishedValues,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct MemorySharedKey {
     format: String,
+    format_swap: Option<String>,
+    tooltip_format: Option<String>,
     interval_secs: u32,
 }
 
@@ -63,14 +92,36 @@ impl ModuleFactory for MemoryFactory {
         MODULE_TYPE
     }
 
-    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_memory_module(
+            parsed,
+            context.tooltip_format.clone(),
+            context.published_namespace(MODULE_TYPE),
+        )
+        .upcast())
+    }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
         let parsed = parse_config(config)?;
         let format = parsed
             .format
             .unwrap_or_else(|| DEFAULT_MEMORY_FORMAT.to_string());
-        let click_command = parsed.click.or(parsed.on_click);
-
-        Ok(build_memory_module(format, click_command, parsed.interval_secs, parsed.class).upcast())
+        let subscription = subscribe_shared_memory(
+            format,
+            parsed.format_swap,
+            None,
+            normalized_memory_interval(parsed.interval_secs),
+        );
+        attach_text_subscription(subscription, sink, |update| {
+            state_store::publish(MODULE_TYPE, &update.published);
+            visible_block(&update.text)
+        });
+        Ok(())
     }
 }
 
@@ -100,9 +151,16 @@ fn memory_registry() -> &'static BackendRegistry<MemorySharedKey, Broadcaster<Me
     REGISTRY.get_or_init(BackendRegistry::new)
 }
 
-fn subscribe_shared_memory(format: String, interval_secs: u32) -> Subscription<MemoryUpdate> {
+fn subscribe_shared_memory(
+    format: String,
+    format_swap: Option<String>,
+    tooltip_format: Option<String>,
+    interval_secs: u32,
+) -> Subscription<MemoryUpdate> {
     let key = MemorySharedKey {
-        format: format.clone(),
+        format,
+        format_swap,
+        tooltip_format,
         interval_secs,
     };
 
@@ -120,11 +178,36 @@ fn subscribe_shared_memory(format: String, interval_secs: u32) -> Subscription<M
 fn start_memory_worker(key: MemorySharedKey, broadcaster: Arc<Broadcaster<MemoryUpdate>>) {
     let interval = Duration::from_secs(u64::from(key.interval_secs));
     std::thread::spawn(move || loop {
-        let text = match read_memory_status() {
-            Ok(status) => render_format(&key.format, &status),
-            Err(err) => escape_markup_text(&format!("memory error: {err}")),
+        let update = match read_memory_status() {
+            Ok(status) => {
+                let format = match &key.format_swap {
+                    Some(format_swap) if status.swap_used_bytes > 0 => format_swap,
+                    _ => &key.format,
+                };
+                MemoryUpdate {
+                    text: render_format(format, &status),
+                    tooltip: key
+                        .tooltip_format
+                        .as_deref()
+                        .map(|format| render_format(format, &status))
+                        .unwrap_or_default(),
+                    used_percentage: Some(percentage(status.used_bytes, status.total_bytes) as u16),
+                    swap_percentage: Some(percentage(
+                        status.swap_used_bytes,
+                        status.swap_total_bytes,
+                    ) as u16),
+                    published: status_values(&status),
+                }
+            }
+            Err(err) => MemoryUpdate {
+                text: escape_markup_text(&format!("memory error: {err}")),
+                tooltip: String::new(),
+                used_percentage: None,
+                swap_percentage: None,
+                published: Vec::new(),
+            },
         };
-        broadcaster.broadcast(MemoryUpdate { text });
+        broadcaster.broadcast(update);
         if broadcaster.subscriber_count() == 0 {
             memory_registry().remove(&key, &broadcaster);
             return;
@@ -134,15 +217,20 @@ fn start_memory_worker(key: MemorySharedKey, broadcaster: Arc<Broadcaster<Memory
 }
 
 pub(crate) fn build_memory_module(
-    format: String,
-    click_command: Option<String>,
-    interval_secs: u32,
-    class: Option<String>,
+    config: MemoryConfig,
+    tooltip_format: Option<String>,
+    namespace: String,
 ) -> Label {
     let label = ModuleLabel::new("memory")
-        .with_css_classes(class.as_deref())
-        .with_click_command(click_command)
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(config.click.or(config.on_click))
         .into_label();
+    let format = config
+        .format
+        .unwrap_or_else(|| DEFAULT_MEMORY_FORMAT.to_string());
+    let interval_secs = config.interval_secs;
+    let warning_threshold = config.warning_threshold;
+    let swap_warning_threshold = config.swap_warning_threshold;
 
     let effective_interval_secs = normalized_memory_interval(interval_secs);
     if effective_interval_secs != interval_secs {
@@ -152,38 +240,77 @@ pub(crate) fn build_memory_module(
         );
     }
 
-    let subscription = subscribe_shared_memory(format, effective_interval_secs);
+    let tooltip = tooltip_format
+        .is_some()
+        .then(|| ModuleTooltip::attach(&label));
+    let subscription = subscribe_shared_memory(
+        format,
+        config.format_swap,
+        tooltip_format,
+        effective_interval_secs,
+    );
 
-    attach_subscription(&label, subscription, |label, update| {
+    attach_subscription(&label, subscription, move |label, update| {
+        state_store::publish(&namespace, &update.published);
         let visible = !update.text.trim().is_empty();
         label.set_visible(visible);
         if visible {
             label.set_markup(&update.text);
         }
+        if let Some(tooltip) = &tooltip {
+            tooltip.set_markup(&update.tooltip);
+        }
+        for (class_name, value, threshold) in [
+            (
+                MEMORY_WARNING_CLASS,
+                update.used_percentage,
+                warning_threshold,
+            ),
+            (
+                SWAP_WARNING_CLASS,
+                update.swap_percentage,
+                swap_warning_threshold,
+            ),
+        ] {
+            if reaches_threshold(value, threshold) {
+                label.add_css_class(class_name);
+            } else {
+                label.remove_css_class(class_name);
+            }
+        }
     });
 
     label
 }
 
-fn read_memory_status() -> Result<MemoryStatus, String> {
+pub(super) fn read_memory_status() -> Result<MemoryStatus, String> {
     let meminfo = fs::read_to_string("/proc/meminfo")
         .map_err(|err| format!("failed to read /proc/meminfo: {err}"))?;
-    parse_meminfo(&meminfo)
+    let mut status = parse_meminfo(&meminfo)?;
+    status.zram_compressed_bytes = read_zram_compressed_bytes();
+    Ok(status)
 }
 
 fn parse_meminfo(meminfo: &str) -> Result<MemoryStatus, String> {
     let mut total_kib: Option<u64> = None;
     let mut available_kib: Option<u64> = None;
+    let mut swap_total_kib: Option<u64> = None;
+    let mut swap_free_kib: Option<u64> = None;
 
     for line in meminfo.lines() {
-        if total_kib.is_none() && line.starts_with("MemTotal:") {
-            total_kib = parse_meminfo_line_value_kib(line);
-        } else if available_kib.is_none() && line.starts_with("MemAvailable:") {
-            available_kib = parse_meminfo_line_value_kib(line);
-        }
-
-        if total_kib.is_some() && available_kib.is_some() {
-            break;
+        let field = if line.starts_with("MemTotal:") {
+            &mut total_kib
+        } else if line.starts_with("MemAvailable:") {
+            &mut available_kib
+        } else if line.starts_with("SwapTotal:") {
+            &mut swap_total_kib
+        } else if line.starts_with("SwapFree:") {
+            &mut swap_free_kib
+        } else {
+            continue;
+        };
+        if field.is_none() {
+            *field = parse_meminfo_line_value_kib(line);
         }
     }
 
@@ -195,36 +322,69 @@ fn parse_meminfo(meminfo: &str) -> Result<MemoryStatus, String> {
         .saturating_mul(1024);
     let available_bytes = available_bytes.min(total_bytes);
     let used_bytes = total_bytes.saturating_sub(available_bytes);
+    // Swap lines are missing on kernels built without swap support.
+    let swap_total_bytes = swap_total_kib.unwrap_or(0).saturating_mul(1024);
+    let swap_free_bytes = swap_free_kib.unwrap_or(0).saturating_mul(1024);
 
     Ok(MemoryStatus {
         total_bytes,
         used_bytes,
         free_bytes: total_bytes.saturating_sub(used_bytes),
         available_bytes,
+        swap_total_bytes,
+        swap_used_bytes: swap_total_bytes.saturating_sub(swap_free_bytes),
+        zram_compressed_bytes: 0,
     })
 }
 
+/// Sum over `/sys/block/zram*`; `0` without zram devices.
+fn read_zram_compressed_bytes() -> u64 {
+    let Ok(entries) = fs::read_dir("/sys/block") else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("zram"))
+        .filter_map(|entry| fs::read_to_string(entry.path().join("mm_stat")).ok())
+        .filter_map(|mm_stat| parse_zram_mm_stat(&mm_stat))
+        .sum()
+}
+
+/// `compr_data_size`, the second column of a zram `mm_stat` file.
+fn parse_zram_mm_stat(mm_stat: &str) -> Option<u64> {
+    mm_stat.split_whitespace().nth(1)?.parse().ok()
+}
+
 fn parse_meminfo_line_value_kib(line: &str) -> Option<u64> {
     line.split_whitespace().nth(1)?.parse::<u64>().ok()
 }
 
-fn render_format(format: &str, status: &MemoryStatus) -> String {
-    let total = status.total_bytes as f64;
-    let used_pct = if status.total_bytes == 0 {
-        0.0
-    } else {
-        (status.used_bytes as f64 / total) * 100.0
-    };
-    let free_pct = if status.total_bytes == 0 {
-        0.0
-    } else {
-        (status.free_bytes as f64 / total) * 100.0
-    };
-    let available_pct = if status.total_bytes == 0 {
+fn status_values(status: &MemoryStatus) -> state_store::PublishedValues {
+    let used_pct = percentage(status.used_bytes, status.total_bytes);
+    let swap_pct = percentage(status.swap_used_bytes, status.swap_total_bytes);
+    vec![
+        ("used", format_bytes(status.used_bytes)),
+        ("total", format_bytes(status.total_bytes)),
+        ("used_percentage", format!("{used_pct:.0}")),
+        ("swap_used", format_bytes(status.swap_used_bytes)),
+        ("swap_percentage", format!("{swap_pct:.0}")),
+    ]
+}
+
+/// `part` as a percentage of `total`; `0` when `total` is zero.
+fn percentage(part: u64, total: u64) -> f64 {
+    if total == 0 {
         0.0
     } else {
-        (status.available_bytes as f64 / total) * 100.0
-    };
+        (part as f64 / total as f64) * 100.0
+    }
+}
+
+fn render_format(format: &str, status: &MemoryStatus) -> String {
+    let used_pct = percentage(status.used_bytes, status.total_bytes);
+    let free_pct = percentage(status.free_bytes, status.total_bytes);
+    let available_pct = percentage(status.available_bytes, status.total_bytes);
+    let swap_pct = percentage(status.swap_used_bytes, status.swap_total_bytes);
 
     render_markup_template(
         format,
@@ -236,11 +396,18 @@ fn render_format(format: &str, status: &MemoryStatus) -> String {
             ("{used_percentage}", &format!("{used_pct:.0}")),
             ("{free_percentage}", &format!("{free_pct:.0}")),
             ("{available_percentage}", &format!("{available_pct:.0}")),
+            ("{swapUsed}", &format_bytes(status.swap_used_bytes)),
+            ("{swapTotal}", &format_bytes(status.swap_total_bytes)),
+            ("{swapPercentage}", &format!("{swap_pct:.0}")),
+            (
+                "{zramCompressed}",
+                &format_bytes(status.zram_compressed_bytes),
+            ),
         ],
     )
 }
 
-fn format_bytes(bytes: u64) -> String {
+pub(super) fn format_bytes(bytes: u64) -> String {
     const UNITS: [&str; 6] = ["B", "K", "M", "G", "T", "P"];
 
     let mut value = bytes as f64;
@@ -287,6 +454,25 @@ mod tests {
         assert_eq!(status.total_bytes, 8_000_000 * 1024);
         assert_eq!(status.available_bytes, 2_000_000 * 1024);
         assert_eq!(status.used_bytes, 6_000_000 * 1024);
+        assert_eq!(status.swap_total_bytes, 0);
+    }
+
+    #[test]
+    fn parse_meminfo_reads_swap() {
+        let meminfo = "MemTotal:       8000000 kB\nMemAvailable:   2000000 kB\n\
+                       SwapTotal:      4000000 kB\nSwapFree:       3000000 kB\n";
+        let status = parse_meminfo(meminfo).expect("meminfo should parse");
+
+        assert_eq!(status.swap_total_bytes, 4_000_000 * 1024);
+        assert_eq!(status.swap_used_bytes, 1_000_000 * 1024);
+    }
+
+    #[test]
+    fn parse_zram_mm_stat_reads_compressed_size() {
+        let mm_stat =
+            "  8192000  2048000  2500000        0  2500000      12       0       0       0\n";
+        assert_eq!(parse_zram_mm_stat(mm_stat), Some(2_048_000));
+        assert_eq!(parse_zram_mm_stat(""), None);
     }
 
     #[test]
@@ -296,8 +482,16 @@ mod tests {
             used_bytes: 700,
             free_bytes: 300,
             available_bytes: 200,
+            swap_total_bytes: 2048,
+            swap_used_bytes: 512,
+            zram_compressed_bytes: 100,
         };
         let text = render_format("{used_percentage} {used} {available}", &status);
         assert_eq!(text, "70 700B 200B");
+        let text = render_format(
+            "{swapUsed}/{swapTotal} {swapPercentage}% {zramCompressed}",
+            &status,
+        );
+        assert_eq!(text, "512B/2K 25% 100B");
     }
 }
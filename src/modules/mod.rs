@@ -1,31 +1,114 @@
+pub(crate) mod animation;
+pub(crate) mod backend_errors;
+pub(crate) mod background;
 pub(crate) mod backlight;
 pub(crate) mod battery;
+pub(crate) mod bluetooth;
 pub(crate) mod broadcaster;
+pub(crate) mod class_map;
 pub(crate) mod clock;
+pub(crate) mod colorpicker;
 pub(crate) mod cpu;
+pub(crate) mod dbus_supervisor;
 pub(crate) mod disk;
+pub(crate) mod events;
 pub(crate) mod exec;
+pub(crate) mod fd_wait;
+pub(crate) mod focus;
+pub(crate) mod gpu;
 pub(crate) mod group;
+pub(crate) mod hyprland;
+pub(crate) mod i3status;
+pub(crate) mod icons;
+pub(crate) mod ics;
+pub(crate) mod idle_countdown;
+pub(crate) mod json_path;
 pub(crate) mod memory;
+pub(crate) mod network;
+pub(crate) mod next_event;
+pub(crate) mod notifications;
+pub(crate) mod output_cache;
 pub(crate) mod playerctl;
+pub(crate) mod popover_surface;
+pub(crate) mod popovers;
 pub(crate) mod pulseaudio;
+pub(crate) mod redact;
+pub(crate) mod reuse;
+pub(crate) mod runtime_stats;
+pub(crate) mod scale;
+pub(crate) mod scroll_text;
+pub(crate) mod self_monitor;
+pub(crate) mod spacer;
+pub(crate) mod state_store;
+pub(crate) mod stats;
 pub(crate) mod sway;
 pub(crate) mod temperature;
+pub(crate) mod text;
+pub(crate) mod tooltip;
+pub(crate) mod touch;
 pub(crate) mod tray;
+pub(crate) mod value_animation;
+pub(crate) mod wait_for;
+pub(crate) mod window;
+pub(crate) mod workspace_strip;
+pub(crate) mod worldclock;
+
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::rc::Rc;
 
 use gtk::gdk;
 use gtk::prelude::*;
-use gtk::{GestureClick, Label, Widget};
+use gtk::{
+    EventControllerScroll, EventControllerScrollFlags, GestureClick, Label, PropagationPhase,
+    Widget,
+};
 use serde::Deserialize;
 use serde_json::{Map, Value};
 
+use self::text::TextSink;
+
 #[derive(Debug, Clone, Default)]
 pub(crate) struct ModuleBuildContext {
     pub(crate) monitor_connector: Option<String>,
     pub(crate) monitor: Option<gdk::Monitor>,
+    /// The module's `tooltip-format`, for factories that support it.
+    pub(crate) tooltip_format: Option<String>,
+    /// Whether the bar runs along a left or right screen edge.
+    pub(crate) vertical: bool,
+    /// Config file the bar was loaded from; relative paths in module
+    /// options resolve against its directory.
+    pub(crate) config_source: Option<PathBuf>,
+    /// Hands out the namespaces of the bar's modules in `state_store`.
+    pub(crate) state_namespaces: state_store::InstanceNamespaces,
+    /// The module's own `state_store` namespace; set by `build_module`.
+    pub(crate) state_namespace: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+impl ModuleBuildContext {
+    /// Direction module containers lay out their children in.
+    pub(crate) fn orientation(&self) -> gtk::Orientation {
+        if self.vertical {
+            gtk::Orientation::Vertical
+        } else {
+            gtk::Orientation::Horizontal
+        }
+    }
+
+    /// Namespace the module publishes its `state_store` values under.
+    pub(crate) fn published_namespace(&self, module_type: &str) -> String {
+        self.state_namespace
+            .clone()
+            .unwrap_or_else(|| module_type.to_string())
+    }
+
+    /// Output scale of the bar's monitor (fractional when GDK reports it).
+    pub(crate) fn scale(&self) -> f64 {
+        scale::monitor_scale(self.monitor.as_ref())
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 pub(crate) struct ModuleConfig {
     #[serde(rename = "type")]
     pub(crate) module_type: String,
@@ -33,6 +116,8 @@ pub(crate) struct ModuleConfig {
     pub(crate) config: Map<String, Value>,
 }
 
+const OVERRIDES_KEY: &str = "overrides";
+
 impl ModuleConfig {
     pub(crate) fn new(module_type: impl Into<String>, config: Map<String, Value>) -> Self {
         Self {
@@ -40,29 +125,100 @@ impl ModuleConfig {
             config,
         }
     }
+
+    /// Returns this config with the `overrides` entry for `connector` merged
+    /// on top (shallow, per top-level field) and the `overrides` key removed.
+    pub(crate) fn resolved_for_output(&self, connector: Option<&str>) -> Result<Self, String> {
+        let mut config = self.config.clone();
+        let Some(overrides) = config.remove(OVERRIDES_KEY) else {
+            return Ok(self.clone());
+        };
+        let Value::Object(mut overrides) = overrides else {
+            return Err(format!("field `{OVERRIDES_KEY}` must be an object"));
+        };
+
+        if let Some(output_override) = connector.and_then(|name| overrides.remove(name)) {
+            let Value::Object(fields) = output_override else {
+                return Err(format!(
+                    "field `{OVERRIDES_KEY}.{}` must be an object",
+                    connector.unwrap_or_default()
+                ));
+            };
+            for (key, value) in fields {
+                if key == "type" {
+                    return Err(format!(
+                        "field `{OVERRIDES_KEY}` cannot change module `type`"
+                    ));
+                }
+                config.insert(key, value);
+            }
+        }
+
+        Ok(Self::new(self.module_type.clone(), config))
+    }
 }
 
 pub(crate) trait ModuleFactory {
     fn module_type(&self) -> &'static str;
     fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String>;
+
+    /// Whether `init` renders `context.tooltip_format`; other modules
+    /// reject the option.
+    fn supports_tooltip_format(&self) -> bool {
+        false
+    }
+
+    /// Whether the module reads `on-scroll-up` / `on-scroll-down` itself
+    /// instead of getting the shared handling from `build_module`.
+    fn handles_scroll_commands(&self) -> bool {
+        false
+    }
+
+    /// Headless rendering for `vibar --stdout`.
+    fn init_text(&self, _config: &ModuleConfig, _sink: TextSink) -> Result<(), String> {
+        Err("not supported in --stdout mode".to_string())
+    }
 }
 
 const FACTORIES: &[&dyn ModuleFactory] = &[
     &backlight::FACTORY,
     &battery::FACTORY,
+    &bluetooth::FACTORY,
     &exec::FACTORY,
+    &focus::FACTORY,
     &cpu::FACTORY,
+    &gpu::FACTORY,
     &disk::FACTORY,
     &memory::FACTORY,
+    &network::FACTORY,
+    &next_event::FACTORY,
+    &notifications::FACTORY,
     &playerctl::FACTORY,
     &group::FACTORY,
+    &hyprland::submap::FACTORY,
+    &hyprland::window::FACTORY,
+    &hyprland::workspaces::FACTORY,
+    &i3status::FACTORY,
+    &idle_countdown::FACTORY,
     &pulseaudio::FACTORY,
+    &pulseaudio::mic_meter::FACTORY,
+    &self_monitor::FACTORY,
+    &spacer::SPACER_FACTORY,
+    &spacer::EXPAND_FACTORY,
+    &stats::FACTORY,
+    &sway::input_toggle::FACTORY,
+    &sway::language::FACTORY,
+    &sway::marks::FACTORY,
     &sway::mode::FACTORY,
     &sway::window::FACTORY,
     &sway::workspaces::FACTORY,
+    &sway::xwayland::FACTORY,
     &temperature::FACTORY,
     &clock::FACTORY,
+    &colorpicker::FACTORY,
     &tray::FACTORY,
+    &window::FACTORY,
+    &worldclock::FACTORY,
 ];
 
 pub(crate) fn build_module(
@@ -73,8 +229,200 @@ pub(crate) fn build_module(
         .iter()
         .find(|factory| factory.module_type() == config.module_type)
         .ok_or_else(|| format!("unknown module type '{}'", config.module_type))?;
+    let mut resolved = config.resolved_for_output(context.monitor_connector.as_deref())?;
+    let background = background::take_background(&mut resolved.config)?;
+    let tooltip_format = tooltip::take_tooltip_format(&mut resolved.config)?;
+    if tooltip_format.is_some() && !factory.supports_tooltip_format() {
+        return Err(format!(
+            "module '{}' does not support `tooltip-format`",
+            config.module_type
+        ));
+    }
+    let scroll_commands = if factory.handles_scroll_commands() {
+        ScrollCommands::default()
+    } else {
+        take_scroll_commands(&mut resolved.config)?
+    };
+    let click_commands = take_click_commands(&mut resolved.config)?;
+    let value_animation = value_animation::take_value_animation(&mut resolved.config)?;
+    let class_map = class_map::take_class_map(&mut resolved.config)?;
+    let context = &ModuleBuildContext {
+        tooltip_format,
+        state_namespace: Some(context.state_namespaces.next(config)),
+        ..context.clone()
+    };
+    let widget = match wait_for::take_wait_for(&mut resolved.config)? {
+        None => {
+            let widget = factory.init(&resolved, context)?;
+            value_animation::attach(&widget, value_animation);
+            class_map::attach(factory.module_type(), &widget, class_map)?;
+            widget
+        }
+        Some(wait_for) => {
+            let context = context.clone();
+            wait_for::build_deferred(factory.module_type(), wait_for, move || {
+                let widget = factory.init(&resolved, &context)?;
+                value_animation::attach(&widget, value_animation);
+                class_map::attach(factory.module_type(), &widget, class_map)?;
+                Ok(widget)
+            })
+        }
+    };
+
+    attach_scroll_commands(&widget, scroll_commands);
+    attach_click_commands(&widget, click_commands);
+
+    let widget = match background {
+        Some(style) => background::wrap(factory.module_type(), &widget, &style),
+        None => widget,
+    };
+    focus::register_module(&widget, config);
+    Ok(widget)
+}
+
+/// Starts a module's headless output for `vibar --stdout`. Per-output
+/// `overrides` do not apply and `wait-for` conditions, `background`,
+/// `tooltip-format`, `class-map`, and scroll and middle/double-click
+/// commands are ignored.
+pub(crate) fn build_text_module(config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+    let factory = FACTORIES
+        .iter()
+        .find(|factory| factory.module_type() == config.module_type)
+        .ok_or_else(|| format!("unknown module type '{}'", config.module_type))?;
+    let mut resolved = config.resolved_for_output(None)?;
+    wait_for::take_wait_for(&mut resolved.config)?;
+    background::take_background(&mut resolved.config)?;
+    tooltip::take_tooltip_format(&mut resolved.config)?;
+    if !factory.handles_scroll_commands() {
+        take_scroll_commands(&mut resolved.config)?;
+    }
+    take_click_commands(&mut resolved.config)?;
+    value_animation::take_value_animation(&mut resolved.config)?;
+    class_map::take_class_map(&mut resolved.config)?;
+    factory.init_text(&resolved, sink)
+}
+
+/// The common `on-scroll-up` / `on-scroll-down` shell commands.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ScrollCommands {
+    pub(crate) up: Option<String>,
+    pub(crate) down: Option<String>,
+}
+
+/// Removes the scroll command keys from `config`.
+pub(crate) fn take_scroll_commands(
+    config: &mut Map<String, Value>,
+) -> Result<ScrollCommands, String> {
+    Ok(ScrollCommands {
+        up: take_command(config, "on-scroll-up", "on_scroll_up")?,
+        down: take_command(config, "on-scroll-down", "on_scroll_down")?,
+    })
+}
+
+/// The common `on-middle-click` / `on-double-click` shell commands.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ClickCommands {
+    pub(crate) middle: Option<String>,
+    pub(crate) double: Option<String>,
+    /// The entry's `click` / `on-click`, taken over from the module while a
+    /// double-click command is set so the two can be told apart.
+    pub(crate) single: Option<String>,
+}
+
+/// Removes the middle/double-click command keys from `config`, plus the
+/// left-click command when a double-click command is set.
+pub(crate) fn take_click_commands(
+    config: &mut Map<String, Value>,
+) -> Result<ClickCommands, String> {
+    let middle = take_command(config, "on-middle-click", "on_middle_click")?;
+    let double = take_command(config, "on-double-click", "on_double_click")?;
+    let single = if double.is_some() {
+        take_command(config, "click", "on-click")?
+    } else {
+        None
+    };
+    Ok(ClickCommands {
+        middle,
+        double,
+        single,
+    })
+}
+
+fn take_command(
+    config: &mut Map<String, Value>,
+    key: &str,
+    alias: &str,
+) -> Result<Option<String>, String> {
+    let value = config.remove(key);
+    let alias_value = config.remove(alias);
+    match value.or(alias_value) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(command)) => Ok(Some(command)),
+        Some(_) => Err(format!("`{key}` must be a string")),
+    }
+}
+
+/// Runs the scroll commands on vertical scrolling. The controller runs in
+/// the capture phase, so a configured direction takes precedence over the
+/// module's own scroll handling; the other direction is left to it.
+pub(crate) fn attach_scroll_commands(widget: &impl IsA<Widget>, commands: ScrollCommands) {
+    if commands.up.is_none() && commands.down.is_none() {
+        return;
+    }
 
-    factory.init(config, context)
+    let scroll = EventControllerScroll::new(
+        EventControllerScrollFlags::VERTICAL | EventControllerScrollFlags::DISCRETE,
+    );
+    scroll.set_propagation_phase(PropagationPhase::Capture);
+    scroll.connect_scroll(move |_, _, dy| {
+        let command = if dy < 0.0 {
+            commands.up.as_deref()
+        } else if dy > 0.0 {
+            commands.down.as_deref()
+        } else {
+            None
+        };
+        let Some(command) = command else {
+            return gtk::glib::Propagation::Proceed;
+        };
+        spawn_shell_command(command);
+        gtk::glib::Propagation::Stop
+    });
+    widget.add_controller(scroll);
+}
+
+/// Middle click runs `commands.middle`. With a double-click command, left
+/// clicks are counted: a second click within the double-click time runs
+/// `commands.double`, and `commands.single` runs only once that time has
+/// passed after a lone click.
+pub(crate) fn attach_click_commands(widget: &impl IsA<Widget>, commands: ClickCommands) {
+    attach_click_command(widget, 2, commands.middle);
+
+    let Some(double) = commands.double else {
+        return;
+    };
+    widget.add_css_class("clickable");
+    let single = commands.single;
+    let presses = Rc::new(Cell::new(0));
+    let click = GestureClick::builder().button(1).build();
+    click.connect_pressed({
+        let presses = Rc::clone(&presses);
+        move |_, n_press, _, _| {
+            presses.set(n_press);
+            if n_press == 2 {
+                spawn_shell_command(&double);
+            }
+        }
+    });
+    // Emitted once the double-click time runs out without another press.
+    click.connect_stopped(move |_| {
+        if presses.replace(0) == 1 {
+            if let Some(single) = &single {
+                spawn_shell_command(single);
+            }
+        }
+    });
+    widget.add_controller(click);
 }
 
 pub(crate) fn attach_primary_click_command(widget: &impl IsA<Widget>, command: Option<String>) {
@@ -84,7 +432,11 @@ pub(crate) fn attach_primary_click_command(widget: &impl IsA<Widget>, command: O
     attach_click_command(widget, 1, command);
 }
 
+/// Right click, or a touch long-press, runs `command`.
 pub(crate) fn attach_secondary_click_command(widget: &impl IsA<Widget>, command: Option<String>) {
+    if let Some(command) = command.clone() {
+        touch::attach_long_press(widget, move |_, _| spawn_shell_command(&command));
+    }
     attach_click_command(widget, 3, command);
 }
 
@@ -94,15 +446,17 @@ fn attach_click_command(widget: &impl IsA<Widget>, button: u32, command: Option<
     };
 
     let click = GestureClick::builder().button(button).build();
-    click.connect_pressed(move |_, _, _, _| {
-        let _ = std::process::Command::new("sh")
-            .arg("-c")
-            .arg(command.as_str())
-            .spawn();
-    });
+    click.connect_pressed(move |_, _, _, _| spawn_shell_command(&command));
     widget.add_controller(click);
 }
 
+fn spawn_shell_command(command: &str) {
+    let _ = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .spawn();
+}
+
 pub(crate) fn apply_css_classes(widget: &impl IsA<Widget>, classes: Option<&str>) {
     let Some(classes) = classes else {
         return;
@@ -129,12 +483,24 @@ pub(crate) fn icon_for_percentage(format_icons: &[String], percent: u8) -> &str
     if format_icons.is_empty() {
         return "";
     }
-    if format_icons.len() == 1 {
-        return &format_icons[0];
+    &format_icons[icon_index_for_percentage(format_icons.len(), percent)]
+}
+
+/// Index into an icon list of `len` entries for `percent` (0 when empty).
+pub(crate) fn icon_index_for_percentage(len: usize, percent: u8) -> usize {
+    if len <= 1 {
+        return 0;
     }
     let clamped = percent.min(100) as usize;
-    let index = (clamped * (format_icons.len() - 1)) / 100;
-    &format_icons[index]
+    (clamped * (len - 1)) / 100
+}
+
+/// Whether a percentage reached a configured threshold; false while either
+/// is unknown.
+pub(crate) fn reaches_threshold(percentage: Option<u16>, threshold: Option<u8>) -> bool {
+    percentage
+        .zip(threshold)
+        .is_some_and(|(percentage, threshold)| percentage >= u16::from(threshold))
 }
 
 /// Builder that consolidates repeated label setup across modules.
@@ -187,6 +553,121 @@ mod tests {
         assert!(err.contains("unknown module type 'does-not-exist'"));
     }
 
+    #[test]
+    fn build_module_rejects_invalid_wait_for() {
+        let module: ModuleConfig = serde_json::from_value(serde_json::json!({
+            "type": "clock",
+            "wait-for": "later"
+        }))
+        .expect("module config should parse");
+        let err = build_module(&module, &ModuleBuildContext::default())
+            .expect_err("invalid wait-for should fail");
+        assert!(err.contains("invalid `wait-for` condition"));
+    }
+
+    #[test]
+    fn build_module_rejects_non_string_scroll_command() {
+        let module: ModuleConfig = serde_json::from_value(serde_json::json!({
+            "type": "clock",
+            "on-scroll-up": ["brightnessctl", "set", "+5%"]
+        }))
+        .expect("module config should parse");
+        let err = build_module(&module, &ModuleBuildContext::default())
+            .expect_err("non-string scroll command should fail");
+        assert!(err.contains("`on-scroll-up` must be a string"));
+    }
+
+    #[test]
+    fn take_scroll_commands_accepts_both_spellings() {
+        let mut config: Map<String, Value> = serde_json::from_value(serde_json::json!({
+            "format": "{}",
+            "on-scroll-up": "up",
+            "on_scroll_down": "down"
+        }))
+        .expect("config map should parse");
+        let commands = take_scroll_commands(&mut config).expect("commands should parse");
+        assert_eq!(commands.up.as_deref(), Some("up"));
+        assert_eq!(commands.down.as_deref(), Some("down"));
+        assert_eq!(config.len(), 1);
+    }
+
+    #[test]
+    fn take_click_commands_takes_left_click_only_with_double_click() {
+        let mut config: Map<String, Value> = serde_json::from_value(serde_json::json!({
+            "on-click": "single",
+            "on_middle_click": "middle"
+        }))
+        .expect("config map should parse");
+        let commands = take_click_commands(&mut config).expect("commands should parse");
+        assert_eq!(commands.middle.as_deref(), Some("middle"));
+        assert_eq!(commands.single, None);
+        assert!(config.contains_key("on-click"));
+
+        config.insert("on-double-click".to_string(), Value::from("double"));
+        let commands = take_click_commands(&mut config).expect("commands should parse");
+        assert_eq!(commands.double.as_deref(), Some("double"));
+        assert_eq!(commands.single.as_deref(), Some("single"));
+        assert!(config.is_empty());
+    }
+
+    #[test]
+    fn resolved_for_output_merges_matching_override() {
+        let module: ModuleConfig = serde_json::from_value(serde_json::json!({
+            "type": "clock",
+            "format": "{}",
+            "class": "base",
+            "overrides": {
+                "DP-1": { "format": "<b>{}</b>" },
+                "eDP-1": { "class": "laptop" }
+            }
+        }))
+        .expect("module config should parse");
+
+        let resolved = module
+            .resolved_for_output(Some("DP-1"))
+            .expect("overrides should resolve");
+        assert_eq!(
+            resolved.config.get("format"),
+            Some(&Value::from("<b>{}</b>"))
+        );
+        assert_eq!(resolved.config.get("class"), Some(&Value::from("base")));
+        assert!(!resolved.config.contains_key("overrides"));
+
+        let unmatched = module
+            .resolved_for_output(None)
+            .expect("overrides should resolve");
+        assert_eq!(unmatched.config.get("format"), Some(&Value::from("{}")));
+        assert!(!unmatched.config.contains_key("overrides"));
+    }
+
+    #[test]
+    fn resolved_for_output_rejects_invalid_overrides() {
+        let module: ModuleConfig = serde_json::from_value(serde_json::json!({
+            "type": "clock",
+            "overrides": { "DP-1": { "type": "cpu" } }
+        }))
+        .expect("module config should parse");
+        let err = module
+            .resolved_for_output(Some("DP-1"))
+            .expect_err("type override should fail");
+        assert!(err.contains("cannot change module `type`"));
+
+        let module: ModuleConfig = serde_json::from_value(serde_json::json!({
+            "type": "clock",
+            "overrides": ["DP-1"]
+        }))
+        .expect("module config should parse");
+        assert!(module.resolved_for_output(Some("DP-1")).is_err());
+    }
+
+    #[test]
+    fn reaches_threshold_needs_a_threshold() {
+        assert!(!reaches_threshold(Some(95), None));
+        assert!(reaches_threshold(Some(80), Some(80)));
+        assert!(!reaches_threshold(Some(79), Some(80)));
+        assert!(!reaches_threshold(None, Some(0)));
+    }
+
     #[test]
     fn icon_for_percentage_maps_range() {
         let icons = vec!["low".to_string(), "mid".to_string(), "high".to_string()];
@@ -0,0 +1,411 @@
+//! Interface state from sysfs/procfs, addresses via `getifaddrs`, and a
+//! netlink route socket that wakes the worker on link/address/route changes.
+
+use std::ffi::CStr;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::os::unix::io::RawFd;
+use std::path::Path;
+use std::time::Instant;
+
+use super::nm::NmClient;
+
+const SYS_CLASS_NET: &str = "/sys/class/net";
+const PROC_NET_ROUTE: &str = "/proc/net/route";
+const PROC_NET_DEV: &str = "/proc/net/dev";
+const PROC_NET_WIRELESS: &str = "/proc/net/wireless";
+/// Full scale of the `/proc/net/wireless` link quality column.
+const WIRELESS_QUALITY_MAX: f64 = 70.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ConnectionKind {
+    Disconnected,
+    Ethernet,
+    Wifi,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(super) struct WifiInfo {
+    pub(super) essid: Option<String>,
+    pub(super) signal_strength: Option<u8>,
+    pub(super) frequency_mhz: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct NetworkStatus {
+    pub(super) kind: ConnectionKind,
+    pub(super) ifname: String,
+    pub(super) address: Option<(IpAddr, u8)>,
+    pub(super) gateway: Option<Ipv4Addr>,
+    pub(super) wifi: WifiInfo,
+    /// Bytes per second since the previous sample.
+    pub(super) rx_rate: f64,
+    pub(super) tx_rate: f64,
+}
+
+/// Previous `/proc/net/dev` counters for the rate computation.
+#[derive(Default)]
+pub(super) struct BandwidthSampler {
+    last: Option<(String, u64, u64, Instant)>,
+}
+
+impl BandwidthSampler {
+    /// Returns `(rx, tx)` bytes per second; zero on the first sample and after
+    /// the interface changes or its counters reset.
+    fn sample(&mut self, ifname: &str, rx_bytes: u64, tx_bytes: u64) -> (f64, f64) {
+        let now = Instant::now();
+        let rates = match self.last.as_ref() {
+            Some((last_ifname, last_rx, last_tx, at))
+                if last_ifname == ifname && rx_bytes >= *last_rx && tx_bytes >= *last_tx =>
+            {
+                let elapsed = now.duration_since(*at).as_secs_f64();
+                if elapsed > 0.0 {
+                    (
+                        (rx_bytes - last_rx) as f64 / elapsed,
+                        (tx_bytes - last_tx) as f64 / elapsed,
+                    )
+                } else {
+                    (0.0, 0.0)
+                }
+            }
+            _ => (0.0, 0.0),
+        };
+        self.last = Some((ifname.to_string(), rx_bytes, tx_bytes, now));
+        rates
+    }
+}
+
+/// Reads the state of `interface`, or of the interface carrying the default
+/// route when unset. `nm` is the worker's NetworkManager connection.
+pub(super) fn read_network_status(
+    interface: Option<&str>,
+    sampler: &mut BandwidthSampler,
+    nm: &mut NmClient,
+) -> NetworkStatus {
+    let routes = fs::read_to_string(PROC_NET_ROUTE)
+        .map(|table| parse_default_routes(&table))
+        .unwrap_or_default();
+    let ifname = match interface {
+        Some(interface) => Some(interface.to_string()),
+        None => routes.first().map(|(ifname, _)| ifname.clone()),
+    };
+
+    let Some(ifname) = ifname.filter(|ifname| interface_is_up(ifname)) else {
+        return NetworkStatus {
+            kind: ConnectionKind::Disconnected,
+            ifname: interface.unwrap_or_default().to_string(),
+            address: None,
+            gateway: None,
+            wifi: WifiInfo::default(),
+            rx_rate: 0.0,
+            tx_rate: 0.0,
+        };
+    };
+
+    let (rx_rate, tx_rate) = fs::read_to_string(PROC_NET_DEV)
+        .ok()
+        .and_then(|dev| parse_interface_counters(&dev, &ifname))
+        .map(|(rx, tx)| sampler.sample(&ifname, rx, tx))
+        .unwrap_or_default();
+    let gateway = routes
+        .iter()
+        .find(|(route_ifname, _)| *route_ifname == ifname)
+        .map(|(_, gateway)| *gateway);
+
+    let (kind, wifi) = if is_wireless(&ifname) {
+        let mut wifi = nm.query_wifi_info(&ifname).unwrap_or_default();
+        if wifi.signal_strength.is_none() {
+            wifi.signal_strength = fs::read_to_string(PROC_NET_WIRELESS)
+                .ok()
+                .and_then(|table| parse_wireless_quality(&table, &ifname));
+        }
+        (ConnectionKind::Wifi, wifi)
+    } else {
+        (ConnectionKind::Ethernet, WifiInfo::default())
+    };
+
+    NetworkStatus {
+        kind,
+        address: interface_address(&ifname),
+        ifname,
+        gateway,
+        wifi,
+        rx_rate,
+        tx_rate,
+    }
+}
+
+fn interface_is_up(ifname: &str) -> bool {
+    let dir = Path::new(SYS_CLASS_NET).join(ifname);
+    match fs::read_to_string(dir.join("operstate")) {
+        Ok(state) if state.trim() == "up" => true,
+        // Tunnels and some virtual links never report `up`; trust the carrier.
+        Ok(state) if state.trim() == "unknown" => {
+            fs::read_to_string(dir.join("carrier")).is_ok_and(|carrier| carrier.trim() == "1")
+        }
+        _ => false,
+    }
+}
+
+fn is_wireless(ifname: &str) -> bool {
+    let dir = Path::new(SYS_CLASS_NET).join(ifname);
+    dir.join("wireless").exists() || dir.join("phy80211").exists()
+}
+
+/// `(interface, gateway)` for every IPv4 default route, lowest metric first.
+fn parse_default_routes(table: &str) -> Vec<(String, Ipv4Addr)> {
+    let mut routes = table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let (ifname, destination, gateway, metric) = (
+                fields.first()?,
+                fields.get(1)?,
+                fields.get(2)?,
+                fields.get(6)?,
+            );
+            if *destination != "00000000" {
+                return None;
+            }
+            // The kernel prints addresses as native-endian hex of the
+            // network-order bytes.
+            let gateway = u32::from_str_radix(gateway, 16).ok()?;
+            Some((
+                metric.parse::<u32>().ok()?,
+                ifname.to_string(),
+                Ipv4Addr::from(gateway.to_le_bytes()),
+            ))
+        })
+        .collect::<Vec<_>>();
+    routes.sort_by_key(|(metric, _, _)| *metric);
+    routes
+        .into_iter()
+        .map(|(_, ifname, gateway)| (ifname, gateway))
+        .collect()
+}
+
+/// `(rx_bytes, tx_bytes)` for `ifname` from `/proc/net/dev`.
+fn parse_interface_counters(dev: &str, ifname: &str) -> Option<(u64, u64)> {
+    dev.lines().skip(2).find_map(|line| {
+        let (name, counters) = line.split_once(':')?;
+        if name.trim() != ifname {
+            return None;
+        }
+        let counters = counters.split_whitespace().collect::<Vec<_>>();
+        Some((
+            counters.first()?.parse().ok()?,
+            counters.get(8)?.parse().ok()?,
+        ))
+    })
+}
+
+/// Link quality of `ifname` from `/proc/net/wireless`, as a percentage.
+fn parse_wireless_quality(table: &str, ifname: &str) -> Option<u8> {
+    table.lines().skip(2).find_map(|line| {
+        let (name, fields) = line.split_once(':')?;
+        if name.trim() != ifname {
+            return None;
+        }
+        let quality = fields
+            .split_whitespace()
+            .nth(1)?
+            .trim_end_matches('.')
+            .parse::<f64>()
+            .ok()?;
+        Some(
+            (quality / WIRELESS_QUALITY_MAX * 100.0)
+                .round()
+                .clamp(0.0, 100.0) as u8,
+        )
+    })
+}
+
+/// First IPv4 address of `ifname` with its prefix length, else the first
+/// non-link-local IPv6 address.
+fn interface_address(ifname: &str) -> Option<(IpAddr, u8)> {
+    let mut addrs: *mut libc::ifaddrs = std::ptr::null_mut();
+    // SAFETY: `addrs` receives a list that is released with `freeifaddrs`.
+    if unsafe { libc::getifaddrs(&mut addrs) } != 0 {
+        return None;
+    }
+
+    let mut ipv4 = None;
+    let mut ipv6 = None;
+    let mut cursor = addrs;
+    while !cursor.is_null() {
+        // SAFETY: `cursor` is a node of the list returned by `getifaddrs`.
+        let entry = unsafe { &*cursor };
+        cursor = entry.ifa_next;
+        if entry.ifa_addr.is_null() || entry.ifa_name.is_null() {
+            continue;
+        }
+        // SAFETY: `ifa_name` is a NUL-terminated string owned by the list.
+        if unsafe { CStr::from_ptr(entry.ifa_name) }.to_bytes() != ifname.as_bytes() {
+            continue;
+        }
+
+        // SAFETY: `ifa_addr` is non-null and points at a sockaddr whose
+        // family selects the concrete type; the netmask has the same family.
+        match i32::from(unsafe { (*entry.ifa_addr).sa_family }) {
+            libc::AF_INET if ipv4.is_none() => {
+                let addr = unsafe { &*(entry.ifa_addr as *const libc::sockaddr_in) };
+                let prefix = if entry.ifa_netmask.is_null() {
+                    32
+                } else {
+                    let mask = unsafe { &*(entry.ifa_netmask as *const libc::sockaddr_in) };
+                    mask.sin_addr.s_addr.count_ones() as u8
+                };
+                let ip = Ipv4Addr::from(addr.sin_addr.s_addr.to_ne_bytes());
+                ipv4 = Some((IpAddr::V4(ip), prefix));
+            }
+            libc::AF_INET6 if ipv6.is_none() => {
+                let addr = unsafe { &*(entry.ifa_addr as *const libc::sockaddr_in6) };
+                let ip = Ipv6Addr::from(addr.sin6_addr.s6_addr);
+                if ip.segments()[0] & 0xffc0 == 0xfe80 {
+                    continue;
+                }
+                let prefix = if entry.ifa_netmask.is_null() {
+                    128
+                } else {
+                    let mask = unsafe { &*(entry.ifa_netmask as *const libc::sockaddr_in6) };
+                    mask.sin6_addr
+                        .s6_addr
+                        .iter()
+                        .map(|byte| byte.count_ones())
+                        .sum::<u32>() as u8
+                };
+                ipv6 = Some((IpAddr::V6(ip), prefix));
+            }
+            _ => {}
+        }
+    }
+    // SAFETY: `addrs` came from a successful `getifaddrs` call.
+    unsafe { libc::freeifaddrs(addrs) };
+
+    ipv4.or(ipv6)
+}
+
+/// Route netlink socket subscribed to link, address, and route changes.
+/// Messages are only used as a wakeup; the state is re-read afterwards.
+pub(super) struct LinkMonitor {
+    fd: RawFd,
+}
+
+impl LinkMonitor {
+    pub(super) fn new() -> Result<Self, String> {
+        // SAFETY: plain socket(2) call; the fd is owned by the returned value.
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
+                libc::NETLINK_ROUTE,
+            )
+        };
+        if fd < 0 {
+            return Err(format!(
+                "failed to open netlink socket: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        let monitor = Self { fd };
+
+        // SAFETY: all-zero is a valid `sockaddr_nl`.
+        let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_groups = (libc::RTMGRP_LINK
+            | libc::RTMGRP_IPV4_IFADDR
+            | libc::RTMGRP_IPV6_IFADDR
+            | libc::RTMGRP_IPV4_ROUTE) as u32;
+        // SAFETY: `addr` is a valid `sockaddr_nl` and the length matches it.
+        let rc = unsafe {
+            libc::bind(
+                monitor.fd,
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if rc < 0 {
+            return Err(format!(
+                "failed to bind netlink socket: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(monitor)
+    }
+
+    pub(super) fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    /// Discards pending messages; returns whether there were any.
+    pub(super) fn drain_events(&self) -> bool {
+        let mut buf = [0u8; 8192];
+        let mut received = false;
+        loop {
+            // SAFETY: `buf` is a writable buffer of the given length.
+            let rc =
+                unsafe { libc::recv(self.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+            if rc <= 0 {
+                return received;
+            }
+            received = true;
+        }
+    }
+}
+
+impl Drop for LinkMonitor {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_default_routes_orders_by_metric() {
+        let table =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+                     wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0\n\
+                     wlan0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\t0\t0\t0\n\
+                     eth0\t00000000\t0100000A\t0003\t0\t0\t100\t00000000\t0\t0\t0\n";
+
+        assert_eq!(
+            parse_default_routes(table),
+            vec![
+                ("eth0".to_string(), Ipv4Addr::new(10, 0, 0, 1)),
+                ("wlan0".to_string(), Ipv4Addr::new(192, 168, 1, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_interface_counters_reads_rx_and_tx_bytes() {
+        let dev = "Inter-|   Receive                                                |  Transmit\n \
+                   face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed\n    \
+                   lo:  1000      10    0    0    0     0          0         0     1000      10    0    0    0     0       0          0\n  \
+                   wlan0: 52000    400    0    0    0     0          0         0    13000     120    0    0    0     0       0          0\n";
+
+        assert_eq!(parse_interface_counters(dev, "wlan0"), Some((52000, 13000)));
+        assert_eq!(parse_interface_counters(dev, "eth0"), None);
+    }
+
+    #[test]
+    fn parse_wireless_quality_scales_to_percent() {
+        let table = "Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE\n \
+                     face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22\n \
+                     wlan0: 0000   49.  -61.  -256        0      0      0      0     12        0\n";
+
+        assert_eq!(parse_wireless_quality(table, "wlan0"), Some(70));
+    }
+
+    #[test]
+    fn bandwidth_sampler_resets_on_interface_change() {
+        let mut sampler = BandwidthSampler::default();
+        assert_eq!(sampler.sample("eth0", 100, 100), (0.0, 0.0));
+        assert_eq!(sampler.sample("wlan0", 500, 500), (0.0, 0.0));
+        assert_eq!(sampler.sample("wlan0", 400, 600), (0.0, 0.0));
+    }
+}
@@ -0,0 +1,406 @@
+mod backend;
+mod nm;
+
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::fd_wait::wait_for_readable_fd;
+use crate::modules::text::{attach_text_subscription, visible_block, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
+use crate::modules::{render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel};
+
+use self::backend::{
+    read_network_status, BandwidthSampler, ConnectionKind, LinkMonitor, NetworkStatus,
+};
+use self::nm::NmClient;
+use super::ModuleFactory;
+
+const MIN_NETWORK_INTERVAL_SECS: u32 = 1;
+const DEFAULT_NETWORK_INTERVAL_SECS: u32 = 5;
+const DEFAULT_FORMAT_WIFI: &str = "{essid} {signalStrength}%";
+const DEFAULT_FORMAT_ETHERNET: &str = "{ifname} {ipaddr}";
+const DEFAULT_FORMAT_DISCONNECTED: &str = "disconnected";
+/// Netlink bursts (address + route + link) arrive together; settle first.
+const LINK_EVENT_DEBOUNCE: Duration = Duration::from_millis(100);
+const NETWORK_STATE_CLASSES: [&str; 3] =
+    ["network-wifi", "network-ethernet", "network-disconnected"];
+pub(crate) const MODULE_TYPE: &str = "network";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct NetworkConfig {
+    #[serde(default)]
+    pub(crate) format: Option<String>,
+    #[serde(rename = "format-wifi", alias = "format_wifi", default)]
+    pub(crate) format_wifi: Option<String>,
+    #[serde(rename = "format-ethernet", alias = "format_ethernet", default)]
+    pub(crate) format_ethernet: Option<String>,
+    #[serde(rename = "format-disconnected", alias = "format_disconnected", default)]
+    pub(crate) format_disconnected: Option<String>,
+    /// Interface to show; the one carrying the default route when unset.
+    #[serde(default)]
+    pub(crate) interface: Option<String>,
+    #[serde(default = "default_network_interval")]
+    pub(crate) interval_secs: u32,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct NetworkSharedKey {
+    interface: Option<String>,
+    format_wifi: String,
+    format_ethernet: String,
+    format_disconnected: String,
+    tooltip_format: Option<String>,
+    interval_secs: u32,
+}
+
+#[derive(Debug, Clone)]
+struct NetworkUpdate {
+    text: String,
+    tooltip: String,
+    state_class: &'static str,
+}
+
+pub(crate) struct NetworkFactory;
+
+pub(crate) const FACTORY: NetworkFactory = NetworkFactory;
+
+impl ModuleFactory for NetworkFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_network_module(parsed, context.tooltip_format.clone()).upcast())
+    }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        let parsed = parse_config(config)?;
+        let subscription = subscribe_shared_network(shared_key(&parsed, None));
+        attach_text_subscription(subscription, sink, |update| visible_block(&update.text));
+        Ok(())
+    }
+}
+
+fn default_network_interval() -> u32 {
+    DEFAULT_NETWORK_INTERVAL_SECS
+}
+
+pub(crate) fn parse_config(module: &ModuleConfig) -> Result<NetworkConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+pub(crate) fn normalized_network_interval(interval_secs: u32) -> u32 {
+    interval_secs.max(MIN_NETWORK_INTERVAL_SECS)
+}
+
+/// Per-state formats fall back to `format`, then to the built-in default.
+fn shared_key(config: &NetworkConfig, tooltip_format: Option<String>) -> NetworkSharedKey {
+    let state_format = |format: &Option<String>, default: &str| {
+        format
+            .clone()
+            .or_else(|| config.format.clone())
+            .unwrap_or_else(|| default.to_string())
+    };
+    NetworkSharedKey {
+        interface: config.interface.clone(),
+        format_wifi: state_format(&config.format_wifi, DEFAULT_FORMAT_WIFI),
+        format_ethernet: state_format(&config.format_ethernet, DEFAULT_FORMAT_ETHERNET),
+        format_disconnected: state_format(&config.format_disconnected, DEFAULT_FORMAT_DISCONNECTED),
+        tooltip_format,
+        interval_secs: normalized_network_interval(config.interval_secs),
+    }
+}
+
+fn network_registry() -> &'static BackendRegistry<NetworkSharedKey, Broadcaster<NetworkUpdate>> {
+    static REGISTRY: OnceLock<BackendRegistry<NetworkSharedKey, Broadcaster<NetworkUpdate>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_network(key: NetworkSharedKey) -> Subscription<NetworkUpdate> {
+    let (broadcaster, start_worker) =
+        network_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_network_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_network_worker(key: NetworkSharedKey, broadcaster: Arc<Broadcaster<NetworkUpdate>>) {
+    std::thread::spawn(move || {
+        let resync_interval = Duration::from_secs(u64::from(key.interval_secs));
+        let mut link_monitor = match LinkMonitor::new() {
+            Ok(monitor) => Some(monitor),
+            Err(err) => {
+                eprintln!("network netlink listener unavailable, using polling only: {err}");
+                None
+            }
+        };
+        let mut sampler = BandwidthSampler::default();
+        let mut nm = NmClient::default();
+
+        loop {
+            let status = read_network_status(key.interface.as_deref(), &mut sampler, &mut nm);
+            broadcaster.broadcast(build_update(&key, &status));
+            let last_refresh = Instant::now();
+
+            if broadcaster.subscriber_count() == 0 {
+                network_registry().remove(&key, &broadcaster);
+                return;
+            }
+
+            // Sleep until the next bandwidth sample, or less when the link,
+            // an address, or a route changes.
+            loop {
+                let remaining = resync_interval.saturating_sub(last_refresh.elapsed());
+                if remaining.is_zero() {
+                    break;
+                }
+                let Some(monitor) = link_monitor.as_ref() else {
+                    std::thread::sleep(remaining);
+                    break;
+                };
+                match wait_for_readable_fd(monitor.fd(), remaining.as_millis() as u64) {
+                    Ok(true) => {
+                        std::thread::sleep(LINK_EVENT_DEBOUNCE);
+                        if monitor.drain_events() {
+                            break;
+                        }
+                    }
+                    Ok(false) => break,
+                    Err(err) => {
+                        eprintln!("network netlink wait failed, listener stopped: {err}");
+                        link_monitor = None;
+                    }
+                }
+            }
+        }
+    });
+}
+
+fn build_update(key: &NetworkSharedKey, status: &NetworkStatus) -> NetworkUpdate {
+    let (format, state_class) = match status.kind {
+        ConnectionKind::Wifi => (&key.format_wifi, "network-wifi"),
+        ConnectionKind::Ethernet => (&key.format_ethernet, "network-ethernet"),
+        ConnectionKind::Disconnected => (&key.format_disconnected, "network-disconnected"),
+    };
+    NetworkUpdate {
+        text: render_format(format, status),
+        tooltip: key
+            .tooltip_format
+            .as_deref()
+            .map(|format| render_format(format, status))
+            .unwrap_or_default(),
+        state_class,
+    }
+}
+
+fn build_network_module(config: NetworkConfig, tooltip_format: Option<String>) -> Label {
+    let click_command = config.click.clone().or_else(|| config.on_click.clone());
+    let label = ModuleLabel::new(MODULE_TYPE)
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(click_command)
+        .into_label();
+
+    let tooltip = tooltip_format
+        .is_some()
+        .then(|| ModuleTooltip::attach(&label));
+    let key = shared_key(&config, tooltip_format);
+    if key.interval_secs != config.interval_secs {
+        eprintln!(
+            "network interval_secs={} is too low; clamping to {} second",
+            config.interval_secs, key.interval_secs
+        );
+    }
+
+    attach_subscription(
+        &label,
+        subscribe_shared_network(key),
+        move |label, update| {
+            let visible = !update.text.trim().is_empty();
+            label.set_visible(visible);
+            if visible {
+                label.set_markup(&update.text);
+            }
+            for class_name in NETWORK_STATE_CLASSES {
+                label.remove_css_class(class_name);
+            }
+            label.add_css_class(update.state_class);
+            if let Some(tooltip) = &tooltip {
+                tooltip.set_markup(&update.tooltip);
+            }
+        },
+    );
+
+    label
+}
+
+fn render_format(format: &str, status: &NetworkStatus) -> String {
+    let (ipaddr, cidr) = status
+        .address
+        .map(|(ip, prefix)| (ip.to_string(), prefix.to_string()))
+        .unwrap_or_default();
+    let gwaddr = status
+        .gateway
+        .map(|gateway| gateway.to_string())
+        .unwrap_or_default();
+    let essid = status.wifi.essid.clone().unwrap_or_default();
+    let signal_strength = status
+        .wifi
+        .signal_strength
+        .map(|strength| strength.to_string())
+        .unwrap_or_default();
+    let frequency = status
+        .wifi
+        .frequency_mhz
+        .map(|mhz| format!("{:.1}", f64::from(mhz) / 1000.0))
+        .unwrap_or_default();
+
+    render_markup_template(
+        format,
+        &[
+            ("{ifname}", &status.ifname),
+            ("{ipaddr}", &ipaddr),
+            ("{cidr}", &cidr),
+            ("{gwaddr}", &gwaddr),
+            ("{essid}", &essid),
+            ("{signalStrength}", &signal_strength),
+            ("{frequency}", &frequency),
+            (
+                "{bandwidthUpBits}",
+                &format_rate(status.tx_rate * 8.0, "b/s"),
+            ),
+            (
+                "{bandwidthDownBits}",
+                &format_rate(status.rx_rate * 8.0, "b/s"),
+            ),
+            ("{bandwidthUpBytes}", &format_rate(status.tx_rate, "B/s")),
+            ("{bandwidthDownBytes}", &format_rate(status.rx_rate, "B/s")),
+        ],
+    )
+}
+
+/// SI-prefixed rate such as `850b/s`, `1.2Mb/s`, or `35kB/s`.
+fn format_rate(per_second: f64, unit: &str) -> String {
+    const PREFIXES: [&str; 5] = ["", "k", "M", "G", "T"];
+
+    let mut value = per_second.max(0.0);
+    let mut prefix_index = 0usize;
+    while value >= 1000.0 && prefix_index < PREFIXES.len() - 1 {
+        value /= 1000.0;
+        prefix_index += 1;
+    }
+
+    if prefix_index == 0 || value >= 10.0 {
+        format!("{value:.0}{}{unit}", PREFIXES[prefix_index])
+    } else {
+        format!("{value:.1}{}{unit}", PREFIXES[prefix_index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use serde_json::{json, Map};
+
+    use super::backend::WifiInfo;
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'network'"));
+    }
+
+    #[test]
+    fn shared_key_falls_back_to_format_then_defaults() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "format": "{ifname}",
+                "format-wifi": "{essid}",
+                "interval_secs": 0
+            }))
+            .expect("module config map should parse"),
+        );
+        let key = shared_key(&parse_config(&module).expect("config should parse"), None);
+
+        assert_eq!(key.format_wifi, "{essid}");
+        assert_eq!(key.format_ethernet, "{ifname}");
+        assert_eq!(key.format_disconnected, "{ifname}");
+        assert_eq!(key.interval_secs, 1);
+
+        let defaults = shared_key(
+            &parse_config(&ModuleConfig::new(MODULE_TYPE, Map::new()))
+                .expect("empty config should parse"),
+            None,
+        );
+        assert_eq!(defaults.format_disconnected, DEFAULT_FORMAT_DISCONNECTED);
+    }
+
+    #[test]
+    fn render_format_replaces_placeholders() {
+        let status = NetworkStatus {
+            kind: ConnectionKind::Wifi,
+            ifname: "wlan0".to_string(),
+            address: Some((IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20)), 24)),
+            gateway: Some(Ipv4Addr::new(192, 168, 1, 1)),
+            wifi: WifiInfo {
+                essid: Some("Home & Co".to_string()),
+                signal_strength: Some(72),
+                frequency_mhz: Some(5180),
+            },
+            rx_rate: 1_500_000.0,
+            tx_rate: 120.0,
+        };
+
+        assert_eq!(
+            render_format(
+                "{essid} {signalStrength}% {frequency}GHz {ipaddr}/{cidr} via {gwaddr} \
+                 {bandwidthDownBits} {bandwidthUpBytes}",
+                &status
+            ),
+            "Home &amp; Co 72% 5.2GHz 192.168.1.20/24 via 192.168.1.1 12Mb/s 120B/s"
+        );
+    }
+
+    #[test]
+    fn format_rate_uses_si_prefixes() {
+        assert_eq!(format_rate(0.0, "b/s"), "0b/s");
+        assert_eq!(format_rate(999.0, "b/s"), "999b/s");
+        assert_eq!(format_rate(1_300.0, "B/s"), "1.3kB/s");
+        assert_eq!(format_rate(48_000_000.0, "b/s"), "48Mb/s");
+    }
+}
@@ -0,0 +1,72 @@
+//! SSID, signal strength, and frequency of the active access point from
+//! NetworkManager over the system bus.
+
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::OwnedObjectPath;
+
+use super::backend::WifiInfo;
+
+const NM_SERVICE: &str = "org.freedesktop.NetworkManager";
+const NM_PATH: &str = "/org/freedesktop/NetworkManager";
+const NM_INTERFACE: &str = "org.freedesktop.NetworkManager";
+const NM_WIRELESS_INTERFACE: &str = "org.freedesktop.NetworkManager.Device.Wireless";
+const NM_ACCESS_POINT_INTERFACE: &str = "org.freedesktop.NetworkManager.AccessPoint";
+
+/// System-bus connection held by the network worker across refreshes.
+/// Opened on first use and dropped when the bus goes away, so the next
+/// refresh reconnects.
+#[derive(Default)]
+pub(super) struct NmClient {
+    connection: Option<Connection>,
+}
+
+impl NmClient {
+    /// `None` when NetworkManager is not running or does not manage `ifname`;
+    /// the caller falls back to `/proc/net/wireless` for the signal.
+    pub(super) fn query_wifi_info(&mut self, ifname: &str) -> Option<WifiInfo> {
+        if self.connection.is_none() {
+            self.connection = Connection::system().ok();
+        }
+        let connection = self.connection.as_ref()?;
+        let manager = Proxy::new(connection, NM_SERVICE, NM_PATH, NM_INTERFACE).ok()?;
+        let device: OwnedObjectPath = match manager.call("GetDeviceByIpIface", &(ifname,)) {
+            Ok(device) => device,
+            Err(zbus::Error::InputOutput(_)) => {
+                self.connection = None;
+                return None;
+            }
+            Err(_) => return None,
+        };
+
+        let wireless = Proxy::new(
+            connection,
+            NM_SERVICE,
+            device.as_str(),
+            NM_WIRELESS_INTERFACE,
+        )
+        .ok()?;
+        let access_point = wireless
+            .get_property::<OwnedObjectPath>("ActiveAccessPoint")
+            .ok()?;
+        if access_point.as_str() == "/" {
+            return Some(WifiInfo::default());
+        }
+
+        let access_point = Proxy::new(
+            connection,
+            NM_SERVICE,
+            access_point.as_str(),
+            NM_ACCESS_POINT_INTERFACE,
+        )
+        .ok()?;
+        Some(WifiInfo {
+            essid: access_point
+                .get_property::<Vec<u8>>("Ssid")
+                .ok()
+                .map(|ssid| String::from_utf8_lossy(&ssid).into_owned())
+                .filter(|ssid| !ssid.is_empty()),
+            signal_strength: access_point.get_property::<u8>("Strength").ok(),
+            frequency_mhz: access_point.get_property::<u32>("Frequency").ok(),
+        })
+    }
+}
@@ -0,0 +1,247 @@
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use chrono::{Local, NaiveDateTime};
+use gtk::glib::ControlFlow;
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::attach_subscription;
+use crate::modules::icons::Glyph;
+use crate::modules::ics::{subscribe_calendar_events, CalendarEvents};
+use crate::modules::{render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel};
+
+use super::ModuleFactory;
+
+/// The countdown has minute resolution, so a few refreshes per minute keep
+/// it accurate without a per-second timer.
+const REFRESH_INTERVAL_SECS: u32 = 15;
+const DEFAULT_FORMAT: Glyph =
+    Glyph::new("\u{f073} {title} in {countdown}", "{title} in {countdown}");
+const DEFAULT_URGENT_MINUTES: u32 = 10;
+const DEFAULT_LOOKAHEAD_HOURS: u32 = 24;
+const URGENT_CLASS: &str = "urgent";
+pub(crate) const MODULE_TYPE: &str = "next-event";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct NextEventConfig {
+    /// `.ics` files, or directories whose `.ics` files are read.
+    pub(crate) ics: Vec<String>,
+    #[serde(default)]
+    pub(crate) format: Option<String>,
+    #[serde(
+        rename = "urgent-minutes",
+        alias = "urgent_minutes",
+        default = "default_urgent_minutes"
+    )]
+    pub(crate) urgent_minutes: u32,
+    #[serde(
+        rename = "lookahead-hours",
+        alias = "lookahead_hours",
+        default = "default_lookahead_hours"
+    )]
+    pub(crate) lookahead_hours: u32,
+    #[serde(rename = "include-all-day", alias = "include_all_day", default)]
+    pub(crate) include_all_day: bool,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+/// Settings applied to the shared event list on every refresh.
+#[derive(Debug, Clone)]
+struct NextEventView {
+    format: String,
+    urgent_minutes: u32,
+    lookahead_hours: u32,
+    include_all_day: bool,
+}
+
+pub(crate) struct NextEventFactory;
+
+pub(crate) const FACTORY: NextEventFactory = NextEventFactory;
+
+impl ModuleFactory for NextEventFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_next_event_module(parsed, context.config_source.as_deref()).upcast())
+    }
+}
+
+fn default_urgent_minutes() -> u32 {
+    DEFAULT_URGENT_MINUTES
+}
+
+fn default_lookahead_hours() -> u32 {
+    DEFAULT_LOOKAHEAD_HOURS
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<NextEventConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    let parsed: NextEventConfig = serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))?;
+    if parsed.ics.is_empty() {
+        return Err(format!("{MODULE_TYPE} 'ics' must list at least one path"));
+    }
+    Ok(parsed)
+}
+
+fn build_next_event_module(config: NextEventConfig, config_source: Option<&Path>) -> Label {
+    let label = ModuleLabel::new(MODULE_TYPE)
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(config.click.or(config.on_click))
+        .into_label();
+    label.set_visible(false);
+
+    let view = NextEventView {
+        format: config
+            .format
+            .unwrap_or_else(|| DEFAULT_FORMAT.get().to_string()),
+        urgent_minutes: config.urgent_minutes,
+        lookahead_hours: config.lookahead_hours,
+        include_all_day: config.include_all_day,
+    };
+    let latest = Rc::new(RefCell::new(Arc::new(CalendarEvents::default())));
+    let update = {
+        let latest = Rc::clone(&latest);
+        move |label: &Label| {
+            let (text, urgent) = render_next_event(&view, &latest.borrow(), now());
+            let visible = !text.trim().is_empty();
+            label.set_visible(visible);
+            if visible {
+                label.set_markup(&text);
+            }
+            if urgent {
+                label.add_css_class(URGENT_CLASS);
+            } else {
+                label.remove_css_class(URGENT_CLASS);
+            }
+        }
+    };
+
+    attach_subscription(
+        &label,
+        subscribe_calendar_events(&config.ics, config_source),
+        {
+            let update = update.clone();
+            move |label, events| {
+                *latest.borrow_mut() = events;
+                update(label);
+            }
+        },
+    );
+
+    let label_weak = label.downgrade();
+    gtk::glib::timeout_add_seconds_local(REFRESH_INTERVAL_SECS, move || {
+        let Some(label) = label_weak.upgrade() else {
+            return ControlFlow::Break;
+        };
+        update(&label);
+        ControlFlow::Continue
+    });
+
+    label
+}
+
+fn now() -> NaiveDateTime {
+    Local::now().naive_local()
+}
+
+/// Rendered markup (empty without an upcoming event) and whether the event
+/// starts within `urgent_minutes`.
+fn render_next_event(
+    view: &NextEventView,
+    events: &CalendarEvents,
+    now: NaiveDateTime,
+) -> (String, bool) {
+    let until = now + chrono::Duration::hours(i64::from(view.lookahead_hours));
+    let Some((start, event)) = events.next_after(now, until, view.include_all_day) else {
+        return (String::new(), false);
+    };
+
+    let secs_until = (start - now).num_seconds().max(0);
+    let minutes = (secs_until + 59) / 60;
+    let time = if event.all_day {
+        "all day".to_string()
+    } else {
+        start.format("%H:%M").to_string()
+    };
+    let text = render_markup_template(
+        &view.format,
+        &[
+            ("{title}", &event.summary),
+            ("{time}", &time),
+            ("{countdown}", &format_countdown(minutes)),
+            ("{minutes}", &minutes.to_string()),
+        ],
+    );
+    (text, minutes <= i64::from(view.urgent_minutes))
+}
+
+/// `45m`, `2h 05m`, or `3d 4h`; rounded up to whole minutes.
+fn format_countdown(minutes: i64) -> String {
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes:02}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map};
+
+    use super::*;
+
+    fn config(value: Value) -> Result<NextEventConfig, String> {
+        let Value::Object(map) = value else {
+            panic!("config should be an object");
+        };
+        parse_config(&ModuleConfig::new(MODULE_TYPE, map))
+    }
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'next-event'"));
+    }
+
+    #[test]
+    fn parse_config_requires_ics_and_uses_defaults() {
+        assert!(config(json!({})).is_err());
+        assert!(config(json!({ "ics": [] })).is_err());
+
+        let parsed = config(json!({ "ics": ["~/cal.ics"] })).expect("config should parse");
+        assert_eq!(parsed.urgent_minutes, DEFAULT_URGENT_MINUTES);
+        assert_eq!(parsed.lookahead_hours, DEFAULT_LOOKAHEAD_HOURS);
+        assert!(!parsed.include_all_day);
+    }
+
+    #[test]
+    fn format_countdown_picks_largest_units() {
+        assert_eq!(format_countdown(7), "7m");
+        assert_eq!(format_countdown(125), "2h 05m");
+        assert_eq!(format_countdown(3 * 1440 + 4 * 60 + 59), "3d 4h");
+    }
+}
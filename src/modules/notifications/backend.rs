@@ -0,0 +1,142 @@
+//! Notification state shared by every notifications module, served by
+//! vibar itself or read from a running mako or dunst.
+
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+
+use crate::modules::broadcaster::Broadcaster;
+
+use super::{daemon, external, NotificationsBackendKind};
+
+pub(super) const NOTIFICATIONS_SERVICE: &str = "org.freedesktop.Notifications";
+pub(super) const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+pub(super) const NOTIFICATIONS_INTERFACE: &str = "org.freedesktop.Notifications";
+/// Entries kept per snapshot; modules list fewer through `max-items`.
+pub(super) const HISTORY_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) enum Urgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    /// The spec's `urgency` hint byte.
+    pub(super) fn from_level(level: u8) -> Self {
+        match level {
+            0 => Self::Low,
+            2 => Self::Critical,
+            _ => Self::Normal,
+        }
+    }
+
+    /// dunst reports urgency by name.
+    pub(super) fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "low" => Self::Low,
+            "critical" => Self::Critical,
+            _ => Self::Normal,
+        }
+    }
+
+    pub(super) fn css_class(self) -> &'static str {
+        match self {
+            Self::Low => "urgency-low",
+            Self::Normal => "urgency-normal",
+            Self::Critical => "urgency-critical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct NotificationEntry {
+    pub(super) id: u32,
+    pub(super) app_name: String,
+    pub(super) summary: String,
+    pub(super) body: String,
+    pub(super) urgency: Urgency,
+    /// Whether the popover offers a dismiss button for the entry.
+    pub(super) dismissable: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct NotificationsSnapshot {
+    /// `false` while the daemon cannot be served or reached.
+    pub(super) available: bool,
+    pub(super) dnd: bool,
+    pub(super) unread: usize,
+    /// Newest first.
+    pub(super) entries: Vec<NotificationEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum NotificationAction {
+    ToggleDnd,
+    SetDnd(bool),
+    Dismiss(u32),
+    Clear,
+    /// Only the built-in daemon tracks read state.
+    MarkRead,
+}
+
+pub(super) fn run_backend(
+    kind: NotificationsBackendKind,
+    broadcaster: &Arc<Broadcaster<NotificationsSnapshot>>,
+) {
+    let (wake_tx, wake_rx) = mpsc::channel();
+    register_wake(kind, wake_tx.clone());
+    match kind {
+        NotificationsBackendKind::Daemon => daemon::run(broadcaster, wake_tx, &wake_rx),
+        NotificationsBackendKind::Mako => external::run(
+            external::MAKO_ERROR_BACKEND,
+            external::read_mako,
+            broadcaster,
+            &wake_rx,
+        ),
+        NotificationsBackendKind::Dunst => external::run(
+            external::DUNST_ERROR_BACKEND,
+            external::read_dunst,
+            broadcaster,
+            &wake_rx,
+        ),
+    }
+}
+
+/// Runs `action` off the GTK thread, then refreshes the shared snapshot.
+pub(super) fn perform(kind: NotificationsBackendKind, action: NotificationAction) {
+    thread::spawn(move || {
+        let result = match kind {
+            NotificationsBackendKind::Daemon => daemon::perform(action),
+            NotificationsBackendKind::Mako => external::perform_mako(action),
+            NotificationsBackendKind::Dunst => external::perform_dunst(action),
+        };
+        if let Err(err) = result {
+            eprintln!("notifications: {err}");
+        }
+        wake(kind);
+    });
+}
+
+fn wake_senders() -> &'static Mutex<HashMap<NotificationsBackendKind, mpsc::Sender<()>>> {
+    static SENDERS: OnceLock<Mutex<HashMap<NotificationsBackendKind, mpsc::Sender<()>>>> =
+        OnceLock::new();
+    SENDERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn register_wake(kind: NotificationsBackendKind, wake_tx: mpsc::Sender<()>) {
+    if let Ok(mut senders) = wake_senders().lock() {
+        senders.insert(kind, wake_tx);
+    }
+}
+
+fn wake(kind: NotificationsBackendKind) {
+    if let Ok(senders) = wake_senders().lock() {
+        if let Some(wake_tx) = senders.get(&kind) {
+            let _ = wake_tx.send(());
+        }
+    }
+}
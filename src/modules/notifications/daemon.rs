@@ -0,0 +1,376 @@
+//! A minimal `org.freedesktop.Notifications` server. Notifications are
+//! only collected for the bar; vibar never shows popups of its own.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::Duration;
+
+use zbus::blocking::connection::Builder as ConnectionBuilder;
+use zbus::blocking::Connection;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::OwnedValue;
+
+use crate::modules::backend_errors;
+use crate::modules::broadcaster::Broadcaster;
+use crate::modules::dbus_supervisor;
+
+use super::backend::{
+    NotificationAction, NotificationEntry, NotificationsSnapshot, Urgency, HISTORY_LIMIT,
+    NOTIFICATIONS_INTERFACE, NOTIFICATIONS_PATH, NOTIFICATIONS_SERVICE,
+};
+
+const ERROR_BACKEND: &str = "notifications/daemon";
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+/// How often the worker checks for subscribers and session bus restarts
+/// while no notifications arrive.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const SPEC_VERSION: &str = "1.2";
+/// `NotificationClosed` reasons from the spec.
+const CLOSED_DISMISSED: u32 = 2;
+const CLOSED_BY_CALL: u32 = 3;
+
+#[derive(Debug, Default)]
+struct DaemonState {
+    last_id: u32,
+    /// Newest first, each with its read flag.
+    entries: VecDeque<(NotificationEntry, bool)>,
+    dnd: bool,
+}
+
+impl DaemonState {
+    /// Stores a notification and returns its id. Replacing an existing one
+    /// keeps its id and moves it to the front; during do-not-disturb only
+    /// critical notifications count as unread.
+    fn notify(
+        &mut self,
+        replaces_id: u32,
+        app_name: String,
+        summary: String,
+        body: String,
+        urgency: Urgency,
+    ) -> u32 {
+        let replaced = (replaces_id != 0)
+            .then(|| {
+                self.entries
+                    .iter()
+                    .position(|(entry, _)| entry.id == replaces_id)
+            })
+            .flatten();
+        let id = match replaced {
+            Some(index) => {
+                self.entries.remove(index);
+                replaces_id
+            }
+            None => {
+                self.last_id = self.last_id.wrapping_add(1).max(1);
+                self.last_id
+            }
+        };
+
+        let read = self.dnd && urgency != Urgency::Critical;
+        self.entries.push_front((
+            NotificationEntry {
+                id,
+                app_name,
+                summary,
+                body,
+                urgency,
+                dismissable: true,
+            },
+            read,
+        ));
+        self.entries.truncate(HISTORY_LIMIT);
+        id
+    }
+
+    fn close(&mut self, id: u32) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|(entry, _)| entry.id != id);
+        self.entries.len() != before
+    }
+
+    fn clear(&mut self) -> Vec<u32> {
+        self.entries.drain(..).map(|(entry, _)| entry.id).collect()
+    }
+
+    fn mark_read(&mut self) {
+        for (_, read) in &mut self.entries {
+            *read = true;
+        }
+    }
+
+    fn snapshot(&self) -> NotificationsSnapshot {
+        NotificationsSnapshot {
+            available: true,
+            dnd: self.dnd,
+            unread: self.entries.iter().filter(|(_, read)| !read).count(),
+            entries: self
+                .entries
+                .iter()
+                .map(|(entry, _)| entry.clone())
+                .collect(),
+        }
+    }
+}
+
+/// History outlives the worker so it survives bar reloads and bus restarts.
+fn state() -> MutexGuard<'static, DaemonState> {
+    static STATE: OnceLock<Mutex<DaemonState>> = OnceLock::new();
+    STATE
+        .get_or_init(|| Mutex::new(DaemonState::default()))
+        .lock()
+        .expect("notifications state mutex poisoned")
+}
+
+/// The connection that owns the bus name, for signals sent on behalf of
+/// popover actions.
+fn served_connection() -> &'static Mutex<Option<Connection>> {
+    static CONNECTION: OnceLock<Mutex<Option<Connection>>> = OnceLock::new();
+    CONNECTION.get_or_init(|| Mutex::new(None))
+}
+
+struct NotificationServer {
+    changed: mpsc::Sender<()>,
+}
+
+#[zbus::interface(name = "org.freedesktop.Notifications")]
+impl NotificationServer {
+    fn get_capabilities(&self) -> Vec<String> {
+        vec!["body".to_string(), "body-markup".to_string()]
+    }
+
+    // The argument list is fixed by the spec.
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: String,
+        replaces_id: u32,
+        _app_icon: String,
+        summary: String,
+        body: String,
+        _actions: Vec<String>,
+        hints: HashMap<String, OwnedValue>,
+        _expire_timeout: i32,
+    ) -> u32 {
+        let urgency = hints
+            .get("urgency")
+            .and_then(|value| value.try_clone().ok())
+            .and_then(|value| u8::try_from(value).ok())
+            .map(Urgency::from_level)
+            .unwrap_or_default();
+        let id = state().notify(replaces_id, app_name, summary, body, urgency);
+        let _ = self.changed.send(());
+        id
+    }
+
+    async fn close_notification(
+        &self,
+        id: u32,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> zbus::fdo::Result<()> {
+        let closed = state().close(id);
+        if closed {
+            let _ = self.changed.send(());
+            Self::notification_closed(&emitter, id, CLOSED_BY_CALL).await?;
+        }
+        Ok(())
+    }
+
+    fn get_server_information(&self) -> (String, String, String, String) {
+        (
+            "vibar".to_string(),
+            "vibar".to_string(),
+            env!("CARGO_PKG_VERSION").to_string(),
+            SPEC_VERSION.to_string(),
+        )
+    }
+
+    #[zbus(signal)]
+    async fn notification_closed(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        reason: u32,
+    ) -> zbus::Result<()>;
+}
+
+pub(super) fn run(
+    broadcaster: &Arc<Broadcaster<NotificationsSnapshot>>,
+    wake_tx: mpsc::Sender<()>,
+    wake_rx: &mpsc::Receiver<()>,
+) {
+    loop {
+        let generation = dbus_supervisor::session_generation();
+        let connection = match serve(wake_tx.clone()) {
+            Ok(connection) => {
+                backend_errors::clear(ERROR_BACKEND);
+                connection
+            }
+            Err(err) => {
+                backend_errors::report(ERROR_BACKEND, err);
+                broadcaster.broadcast(NotificationsSnapshot::default());
+                let _ = wake_rx.recv_timeout(RETRY_DELAY);
+                if broadcaster.subscriber_count() == 0 {
+                    return;
+                }
+                continue;
+            }
+        };
+        *served_connection()
+            .lock()
+            .expect("notifications connection mutex poisoned") = Some(connection);
+
+        let mut last = None;
+        let stopped = loop {
+            let snapshot = state().snapshot();
+            if last.as_ref() != Some(&snapshot) {
+                broadcaster.broadcast(snapshot.clone());
+                last = Some(snapshot);
+            }
+            let _ = wake_rx.recv_timeout(IDLE_CHECK_INTERVAL);
+            while wake_rx.try_recv().is_ok() {}
+            if broadcaster.subscriber_count() == 0 {
+                break true;
+            }
+            if dbus_supervisor::session_generation() != generation {
+                break false;
+            }
+        };
+
+        // Dropping the connection releases the bus name.
+        *served_connection()
+            .lock()
+            .expect("notifications connection mutex poisoned") = None;
+        if stopped {
+            return;
+        }
+    }
+}
+
+fn serve(changed: mpsc::Sender<()>) -> Result<Connection, String> {
+    ConnectionBuilder::session()
+        .and_then(|builder| builder.name(NOTIFICATIONS_SERVICE))
+        .and_then(|builder| builder.serve_at(NOTIFICATIONS_PATH, NotificationServer { changed }))
+        .and_then(|builder| builder.build())
+        .map_err(|err| {
+            format!(
+                "failed to serve {NOTIFICATIONS_SERVICE} (is another notification daemon running?): {err}"
+            )
+        })
+}
+
+pub(super) fn perform(action: NotificationAction) -> Result<(), String> {
+    let closed = {
+        let mut state = state();
+        match action {
+            NotificationAction::ToggleDnd => {
+                state.dnd = !state.dnd;
+                Vec::new()
+            }
+            NotificationAction::SetDnd(dnd) => {
+                state.dnd = dnd;
+                Vec::new()
+            }
+            NotificationAction::Dismiss(id) => {
+                if state.close(id) {
+                    vec![id]
+                } else {
+                    Vec::new()
+                }
+            }
+            NotificationAction::Clear => state.clear(),
+            NotificationAction::MarkRead => {
+                state.mark_read();
+                Vec::new()
+            }
+        }
+    };
+
+    let connection = served_connection()
+        .lock()
+        .expect("notifications connection mutex poisoned");
+    let Some(connection) = connection.as_ref() else {
+        return Ok(());
+    };
+    for id in closed {
+        connection
+            .emit_signal(
+                None::<&str>,
+                NOTIFICATIONS_PATH,
+                NOTIFICATIONS_INTERFACE,
+                "NotificationClosed",
+                &(id, CLOSED_DISMISSED),
+            )
+            .map_err(|err| format!("failed to emit NotificationClosed: {err}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notify(state: &mut DaemonState, replaces_id: u32, summary: &str, urgency: Urgency) -> u32 {
+        state.notify(
+            replaces_id,
+            "app".to_string(),
+            summary.to_string(),
+            String::new(),
+            urgency,
+        )
+    }
+
+    #[test]
+    fn notify_assigns_ids_and_replaces_in_place() {
+        let mut state = DaemonState::default();
+        assert_eq!(notify(&mut state, 0, "first", Urgency::Normal), 1);
+        assert_eq!(notify(&mut state, 0, "second", Urgency::Normal), 2);
+        assert_eq!(notify(&mut state, 1, "first again", Urgency::Normal), 1);
+        // Unknown ids get a fresh one, as the spec requires.
+        assert_eq!(notify(&mut state, 42, "third", Urgency::Normal), 3);
+
+        let snapshot = state.snapshot();
+        let summaries = snapshot
+            .entries
+            .iter()
+            .map(|entry| entry.summary.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(summaries, ["third", "first again", "second"]);
+        assert_eq!(snapshot.unread, 3);
+
+        assert!(state.close(2));
+        assert!(!state.close(2));
+        assert_eq!(state.clear(), vec![3, 1]);
+        assert!(state.snapshot().entries.is_empty());
+    }
+
+    #[test]
+    fn dnd_only_counts_critical_notifications_as_unread() {
+        let mut state = DaemonState {
+            dnd: true,
+            ..DaemonState::default()
+        };
+        notify(&mut state, 0, "quiet", Urgency::Normal);
+        notify(&mut state, 0, "loud", Urgency::Critical);
+        assert_eq!(state.snapshot().unread, 1);
+
+        state.mark_read();
+        let snapshot = state.snapshot();
+        assert_eq!(snapshot.unread, 0);
+        assert_eq!(snapshot.entries.len(), 2);
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut state = DaemonState::default();
+        for _ in 0..HISTORY_LIMIT + 5 {
+            notify(&mut state, 0, "spam", Urgency::Low);
+        }
+        let snapshot = state.snapshot();
+        assert_eq!(snapshot.entries.len(), HISTORY_LIMIT);
+        assert_eq!(
+            snapshot.entries.first().map(|entry| entry.id),
+            Some(u32::try_from(HISTORY_LIMIT + 5).unwrap())
+        );
+    }
+}
@@ -0,0 +1,385 @@
+//! Notification state of a running mako or dunst, read over their D-Bus
+//! control interfaces. Neither signals new notifications, so both are
+//! polled.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use zbus::blocking::fdo::DBusProxy;
+use zbus::blocking::{Connection, Proxy};
+use zbus::names::BusName;
+use zbus::zvariant::{OwnedValue, Value};
+
+use crate::modules::backend_errors;
+use crate::modules::broadcaster::Broadcaster;
+
+use super::backend::{
+    NotificationAction, NotificationEntry, NotificationsSnapshot, Urgency, HISTORY_LIMIT,
+    NOTIFICATIONS_INTERFACE, NOTIFICATIONS_PATH, NOTIFICATIONS_SERVICE,
+};
+
+pub(super) const MAKO_ERROR_BACKEND: &str = "notifications/mako";
+pub(super) const DUNST_ERROR_BACKEND: &str = "notifications/dunst";
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+const MAKO_PATH: &str = "/fr/emersion/Mako";
+const MAKO_INTERFACE: &str = "fr.emersion.Mako";
+/// The mode `makoctl mode -t do-not-disturb` toggles in mako's docs.
+const MAKO_DND_MODE: &str = "do-not-disturb";
+const DUNST_INTERFACE: &str = "org.dunstproject.cmd0";
+
+type Dict = HashMap<String, OwnedValue>;
+
+pub(super) fn run(
+    error_backend: &'static str,
+    read: fn(&Connection) -> Result<NotificationsSnapshot, String>,
+    broadcaster: &Arc<Broadcaster<NotificationsSnapshot>>,
+    wake_rx: &mpsc::Receiver<()>,
+) {
+    let mut connection = None;
+    let mut last = None;
+    loop {
+        let snapshot = match poll(&mut connection, read) {
+            Ok(snapshot) => {
+                backend_errors::clear(error_backend);
+                snapshot
+            }
+            Err(err) => {
+                backend_errors::report(error_backend, err);
+                NotificationsSnapshot::default()
+            }
+        };
+        if last.as_ref() != Some(&snapshot) {
+            broadcaster.broadcast(snapshot.clone());
+            last = Some(snapshot);
+        }
+
+        let _ = wake_rx.recv_timeout(POLL_INTERVAL);
+        while wake_rx.try_recv().is_ok() {}
+        if broadcaster.subscriber_count() == 0 {
+            return;
+        }
+    }
+}
+
+/// Reuses the session connection between polls; any failure drops it so
+/// the next poll reconnects.
+fn poll(
+    connection: &mut Option<Connection>,
+    read: fn(&Connection) -> Result<NotificationsSnapshot, String>,
+) -> Result<NotificationsSnapshot, String> {
+    let current = match connection.take() {
+        Some(current) => current,
+        None => Connection::session()
+            .map_err(|err| format!("failed to connect to session dbus: {err}"))?,
+    };
+    // Calling an unowned name would D-Bus-activate whichever daemon is
+    // installed, which may not be the configured one.
+    if !daemon_running(&current)? {
+        *connection = Some(current);
+        return Err(format!("no daemon owns {NOTIFICATIONS_SERVICE}"));
+    }
+    let snapshot = read(&current)?;
+    *connection = Some(current);
+    Ok(snapshot)
+}
+
+fn daemon_running(connection: &Connection) -> Result<bool, String> {
+    let proxy =
+        DBusProxy::new(connection).map_err(|err| format!("failed to create dbus proxy: {err}"))?;
+    let name = BusName::try_from(NOTIFICATIONS_SERVICE)
+        .map_err(|err| format!("invalid bus name {NOTIFICATIONS_SERVICE}: {err}"))?;
+    proxy
+        .name_has_owner(name)
+        .map_err(|err| format!("failed to look up {NOTIFICATIONS_SERVICE}: {err}"))
+}
+
+pub(super) fn read_mako(connection: &Connection) -> Result<NotificationsSnapshot, String> {
+    let proxy = mako_proxy(connection)?;
+    let current: Vec<Dict> = proxy
+        .call("ListNotifications", &())
+        .map_err(|err| format!("failed to list mako notifications: {err}"))?;
+    // History needs mako 1.7 and modes 1.8; older releases just show less.
+    let history: Vec<Dict> = proxy.call("ListHistory", &()).unwrap_or_default();
+    let modes: Vec<String> = proxy.call("ListModes", &()).unwrap_or_default();
+    Ok(mako_snapshot(&current, &history, &modes))
+}
+
+pub(super) fn read_dunst(connection: &Connection) -> Result<NotificationsSnapshot, String> {
+    let properties: Dict = Proxy::new(
+        connection,
+        NOTIFICATIONS_SERVICE,
+        NOTIFICATIONS_PATH,
+        PROPERTIES_INTERFACE,
+    )
+    .and_then(|proxy| proxy.call("GetAll", &(DUNST_INTERFACE,)))
+    .map_err(|err| format!("failed to read dunst properties: {err}"))?;
+    let history: Vec<Dict> = dunst_proxy(connection)?
+        .call("NotificationListHistory", &())
+        .map_err(|err| format!("failed to list dunst history: {err}"))?;
+    Ok(dunst_snapshot(&properties, &history))
+}
+
+pub(super) fn perform_mako(action: NotificationAction) -> Result<(), String> {
+    let connection = session()?;
+    let proxy = mako_proxy(&connection)?;
+    match action {
+        NotificationAction::ToggleDnd | NotificationAction::SetDnd(_) => {
+            let mut modes: Vec<String> = proxy
+                .call("ListModes", &())
+                .map_err(|err| format!("failed to list mako modes: {err}"))?;
+            let dnd = match action {
+                NotificationAction::SetDnd(dnd) => dnd,
+                _ => !modes.iter().any(|mode| mode == MAKO_DND_MODE),
+            };
+            modes.retain(|mode| mode != MAKO_DND_MODE);
+            if dnd {
+                modes.push(MAKO_DND_MODE.to_string());
+            }
+            proxy
+                .call::<_, _, ()>("SetModes", &(modes,))
+                .map_err(|err| format!("failed to set mako modes: {err}"))
+        }
+        NotificationAction::Dismiss(id) => close_notification(&connection, id),
+        NotificationAction::Clear => {
+            let current: Vec<Dict> = proxy
+                .call("ListNotifications", &())
+                .map_err(|err| format!("failed to list mako notifications: {err}"))?;
+            current
+                .iter()
+                .filter_map(entry_id)
+                .try_for_each(|id| close_notification(&connection, id))
+        }
+        NotificationAction::MarkRead => Ok(()),
+    }
+}
+
+pub(super) fn perform_dunst(action: NotificationAction) -> Result<(), String> {
+    let connection = session()?;
+    let proxy = dunst_proxy(&connection)?;
+    match action {
+        NotificationAction::ToggleDnd | NotificationAction::SetDnd(_) => {
+            let properties = Proxy::new(
+                &connection,
+                NOTIFICATIONS_SERVICE,
+                NOTIFICATIONS_PATH,
+                PROPERTIES_INTERFACE,
+            )
+            .map_err(|err| format!("failed to create dunst properties proxy: {err}"))?;
+            let paused = match action {
+                NotificationAction::SetDnd(dnd) => dnd,
+                _ => {
+                    let paused: OwnedValue =
+                        properties
+                            .call("Get", &(DUNST_INTERFACE, "paused"))
+                            .map_err(|err| format!("failed to read dunst paused state: {err}"))?;
+                    !bool::try_from(paused).unwrap_or(false)
+                }
+            };
+            properties
+                .call::<_, _, ()>("Set", &(DUNST_INTERFACE, "paused", Value::from(paused)))
+                .map_err(|err| format!("failed to set dunst paused state: {err}"))
+        }
+        NotificationAction::Dismiss(id) => proxy
+            .call::<_, _, ()>("NotificationRemoveFromHistory", &(id,))
+            .map_err(|err| format!("failed to remove dunst notification {id}: {err}")),
+        NotificationAction::Clear => proxy
+            .call::<_, _, ()>("NotificationCloseAll", &())
+            .and_then(|()| proxy.call::<_, _, ()>("NotificationClearHistory", &()))
+            .map_err(|err| format!("failed to clear dunst notifications: {err}")),
+        NotificationAction::MarkRead => Ok(()),
+    }
+}
+
+fn session() -> Result<Connection, String> {
+    Connection::session().map_err(|err| format!("failed to connect to session dbus: {err}"))
+}
+
+fn mako_proxy(connection: &Connection) -> Result<Proxy<'static>, String> {
+    Proxy::new(connection, NOTIFICATIONS_SERVICE, MAKO_PATH, MAKO_INTERFACE)
+        .map_err(|err| format!("failed to create mako proxy: {err}"))
+}
+
+fn dunst_proxy(connection: &Connection) -> Result<Proxy<'static>, String> {
+    Proxy::new(
+        connection,
+        NOTIFICATIONS_SERVICE,
+        NOTIFICATIONS_PATH,
+        DUNST_INTERFACE,
+    )
+    .map_err(|err| format!("failed to create dunst proxy: {err}"))
+}
+
+fn close_notification(connection: &Connection, id: u32) -> Result<(), String> {
+    Proxy::new(
+        connection,
+        NOTIFICATIONS_SERVICE,
+        NOTIFICATIONS_PATH,
+        NOTIFICATIONS_INTERFACE,
+    )
+    .and_then(|proxy| proxy.call::<_, _, ()>("CloseNotification", &(id,)))
+    .map_err(|err| format!("failed to close notification {id}: {err}"))
+}
+
+/// Shown notifications count as unread; history entries cannot be
+/// dismissed through mako's interface.
+fn mako_snapshot(current: &[Dict], history: &[Dict], modes: &[String]) -> NotificationsSnapshot {
+    let mut entries = current
+        .iter()
+        .filter_map(|dict| entry_from_dict(dict, "app-name", true))
+        .collect::<Vec<_>>();
+    let unread = entries.len();
+    for entry in history
+        .iter()
+        .filter_map(|dict| entry_from_dict(dict, "app-name", false))
+    {
+        if !entries.iter().any(|known| known.id == entry.id) {
+            entries.push(entry);
+        }
+    }
+    entries.sort_by_key(|entry| Reverse(entry.id));
+    entries.truncate(HISTORY_LIMIT);
+
+    NotificationsSnapshot {
+        available: true,
+        dnd: modes.iter().any(|mode| mode == MAKO_DND_MODE),
+        unread,
+        entries,
+    }
+}
+
+/// dunst only lists its history, so unread counts the shown and the
+/// waiting (paused) notifications it reports.
+fn dunst_snapshot(properties: &Dict, history: &[Dict]) -> NotificationsSnapshot {
+    let length = |key: &str| property::<u32>(properties, key).unwrap_or(0) as usize;
+    let mut entries = history
+        .iter()
+        .filter_map(|dict| entry_from_dict(dict, "appname", true))
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|entry| Reverse(entry.id));
+    entries.truncate(HISTORY_LIMIT);
+
+    NotificationsSnapshot {
+        available: true,
+        dnd: property::<bool>(properties, "paused").unwrap_or(false),
+        unread: length("displayedLength") + length("waitingLength"),
+        entries,
+    }
+}
+
+fn entry_from_dict(
+    dict: &Dict,
+    app_name_key: &str,
+    dismissable: bool,
+) -> Option<NotificationEntry> {
+    let text = |key: &str| property::<String>(dict, key).unwrap_or_default();
+    Some(NotificationEntry {
+        id: entry_id(dict)?,
+        app_name: text(app_name_key),
+        summary: text("summary"),
+        body: text("body"),
+        urgency: property::<u8>(dict, "urgency")
+            .map(Urgency::from_level)
+            .or_else(|| property::<String>(dict, "urgency").map(|name| Urgency::from_name(&name)))
+            .unwrap_or_default(),
+        dismissable,
+    })
+}
+
+/// mako sends ids as `u`, dunst as `i`.
+fn entry_id(dict: &Dict) -> Option<u32> {
+    property::<u32>(dict, "id")
+        .or_else(|| property::<i32>(dict, "id").and_then(|id| u32::try_from(id).ok()))
+}
+
+fn property<T>(dict: &Dict, key: &str) -> Option<T>
+where
+    T: TryFrom<OwnedValue>,
+{
+    let value = dict.get(key)?.try_clone().ok()?;
+    T::try_from(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use zbus::zvariant::Str;
+
+    use super::*;
+
+    fn dict(entries: Vec<(&str, OwnedValue)>) -> Dict {
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect()
+    }
+
+    fn text(value: &str) -> OwnedValue {
+        OwnedValue::from(Str::from(value.to_string()))
+    }
+
+    #[test]
+    fn mako_snapshot_merges_current_and_history() {
+        let current = vec![dict(vec![
+            ("id", OwnedValue::from(7_u32)),
+            ("app-name", text("mail")),
+            ("summary", text("New mail")),
+            ("urgency", OwnedValue::from(2_u8)),
+        ])];
+        let history = vec![
+            dict(vec![
+                ("id", OwnedValue::from(7_u32)),
+                ("summary", text("New mail")),
+            ]),
+            dict(vec![
+                ("id", OwnedValue::from(3_u32)),
+                ("app-name", text("chat")),
+                ("summary", text("Ping")),
+            ]),
+        ];
+        let snapshot = mako_snapshot(&current, &history, &["do-not-disturb".to_string()]);
+
+        assert!(snapshot.dnd);
+        assert_eq!(snapshot.unread, 1);
+        let entries = snapshot
+            .entries
+            .iter()
+            .map(|entry| (entry.id, entry.app_name.as_str(), entry.dismissable))
+            .collect::<Vec<_>>();
+        assert_eq!(entries, [(7, "mail", true), (3, "chat", false)]);
+        assert_eq!(snapshot.entries[0].urgency, Urgency::Critical);
+    }
+
+    #[test]
+    fn dunst_snapshot_reads_lengths_and_named_urgency() {
+        let properties = dict(vec![
+            ("paused", OwnedValue::from(true)),
+            ("displayedLength", OwnedValue::from(1_u32)),
+            ("waitingLength", OwnedValue::from(2_u32)),
+        ]);
+        let history = vec![dict(vec![
+            ("id", OwnedValue::from(12_i32)),
+            ("appname", text("backup")),
+            ("summary", text("Done")),
+            ("body", text("All files copied")),
+            ("urgency", text("LOW")),
+        ])];
+        let snapshot = dunst_snapshot(&properties, &history);
+
+        assert!(snapshot.dnd);
+        assert_eq!(snapshot.unread, 3);
+        assert_eq!(
+            snapshot.entries,
+            vec![NotificationEntry {
+                id: 12,
+                app_name: "backup".to_string(),
+                summary: "Done".to_string(),
+                body: "All files copied".to_string(),
+                urgency: Urgency::Low,
+                dismissable: true,
+            }]
+        );
+    }
+}
@@ -0,0 +1,321 @@
+mod backend;
+mod daemon;
+mod external;
+mod schedule;
+mod ui;
+
+use std::sync::{Arc, OnceLock};
+
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::icons::Glyph;
+use crate::modules::{render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel};
+
+use self::backend::{run_backend, NotificationsSnapshot};
+use self::schedule::{subscribe_dnd_schedule, DndScheduleConfig};
+use self::ui::{build_ui, refresh_ui};
+use super::ModuleFactory;
+
+const DEFAULT_FORMAT: Glyph = Glyph::new("\u{f0f3}", "🔔");
+const DEFAULT_FORMAT_UNREAD: Glyph = Glyph::new("\u{f0f3} {count}", "🔔 {count}");
+const DEFAULT_FORMAT_DND: Glyph = Glyph::new("\u{f1f6}", "🔕");
+const DEFAULT_MAX_ITEMS: usize = 10;
+const NOTIFICATIONS_STATE_CLASSES: [&str; 3] = [
+    "notifications-unread",
+    "notifications-dnd",
+    "notifications-unavailable",
+];
+pub(crate) const MODULE_TYPE: &str = "notifications";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct NotificationsConfig {
+    #[serde(default)]
+    pub(crate) backend: NotificationsBackendKind,
+    #[serde(default)]
+    pub(crate) format: Option<String>,
+    #[serde(rename = "format-unread", alias = "format_unread", default)]
+    pub(crate) format_unread: Option<String>,
+    #[serde(rename = "format-dnd", alias = "format_dnd", default)]
+    pub(crate) format_dnd: Option<String>,
+    #[serde(
+        rename = "max-items",
+        alias = "max_items",
+        default = "default_max_items"
+    )]
+    pub(crate) max_items: usize,
+    #[serde(rename = "dnd-schedule", alias = "dnd_schedule", default)]
+    pub(crate) dnd_schedule: DndScheduleConfig,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum NotificationsBackendKind {
+    /// vibar owns `org.freedesktop.Notifications` itself.
+    #[default]
+    Daemon,
+    Mako,
+    Dunst,
+}
+
+/// Formats for each state, with fallbacks already applied.
+#[derive(Debug, Clone)]
+struct NotificationsFormats {
+    idle: String,
+    unread: String,
+    dnd: String,
+}
+
+/// One backend per kind, shared by every module using it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct NotificationsSharedKey {
+    backend: NotificationsBackendKind,
+}
+
+pub(crate) struct NotificationsFactory;
+
+pub(crate) const FACTORY: NotificationsFactory = NotificationsFactory;
+
+impl ModuleFactory for NotificationsFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_notifications_module(parsed).upcast())
+    }
+}
+
+fn default_max_items() -> usize {
+    DEFAULT_MAX_ITEMS
+}
+
+pub(crate) fn parse_config(module: &ModuleConfig) -> Result<NotificationsConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn resolve_formats(config: &NotificationsConfig) -> NotificationsFormats {
+    NotificationsFormats {
+        idle: config
+            .format
+            .clone()
+            .unwrap_or_else(|| DEFAULT_FORMAT.get().to_string()),
+        unread: config
+            .format_unread
+            .clone()
+            .or_else(|| config.format.clone())
+            .unwrap_or_else(|| DEFAULT_FORMAT_UNREAD.get().to_string()),
+        dnd: config
+            .format_dnd
+            .clone()
+            .unwrap_or_else(|| DEFAULT_FORMAT_DND.get().to_string()),
+    }
+}
+
+fn notifications_registry(
+) -> &'static BackendRegistry<NotificationsSharedKey, Broadcaster<NotificationsSnapshot>> {
+    static REGISTRY: OnceLock<
+        BackendRegistry<NotificationsSharedKey, Broadcaster<NotificationsSnapshot>>,
+    > = OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_notifications(
+    backend: NotificationsBackendKind,
+) -> Subscription<NotificationsSnapshot> {
+    let key = NotificationsSharedKey { backend };
+    let (broadcaster, start_worker) =
+        notifications_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_notifications_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_notifications_worker(
+    key: NotificationsSharedKey,
+    broadcaster: Arc<Broadcaster<NotificationsSnapshot>>,
+) {
+    std::thread::spawn(move || {
+        run_backend(key.backend, &broadcaster);
+        notifications_registry().remove(&key, &broadcaster);
+    });
+}
+
+/// Turns do-not-disturb on or off on `kind`, for callers outside the
+/// module (focus mode).
+pub(crate) fn set_dnd(kind: NotificationsBackendKind, enabled: bool) {
+    backend::perform(kind, backend::NotificationAction::SetDnd(enabled));
+}
+
+fn build_notifications_module(config: NotificationsConfig) -> Label {
+    let label = ModuleLabel::new(MODULE_TYPE)
+        .with_css_classes(config.class.as_deref())
+        .into_label();
+    let backend = config.backend;
+    let max_items = config.max_items;
+    let ui = build_ui(&label, backend);
+    let formats = resolve_formats(&config);
+
+    if !config.dnd_schedule.is_empty() {
+        attach_subscription(
+            &label,
+            subscribe_dnd_schedule(backend, config.dnd_schedule.clone()),
+            |_, ()| {},
+        );
+    }
+
+    attach_subscription(
+        &label,
+        subscribe_shared_notifications(backend),
+        move |label, snapshot| {
+            let text = render_format(&formats, &snapshot);
+            let visible = !text.trim().is_empty();
+            label.set_visible(visible);
+            if visible {
+                label.set_markup(&text);
+            }
+            for class_name in NOTIFICATIONS_STATE_CLASSES {
+                label.remove_css_class(class_name);
+            }
+            for class_name in state_classes(&snapshot) {
+                label.add_css_class(class_name);
+            }
+            refresh_ui(&ui, backend, &snapshot, max_items);
+        },
+    );
+
+    label
+}
+
+fn render_format(formats: &NotificationsFormats, snapshot: &NotificationsSnapshot) -> String {
+    let format = if snapshot.dnd {
+        &formats.dnd
+    } else if snapshot.unread > 0 {
+        &formats.unread
+    } else {
+        &formats.idle
+    };
+    render_markup_template(
+        format,
+        &[
+            ("{count}", &snapshot.unread.to_string()),
+            ("{total}", &snapshot.entries.len().to_string()),
+        ],
+    )
+}
+
+fn state_classes(snapshot: &NotificationsSnapshot) -> Vec<&'static str> {
+    [
+        (snapshot.unread > 0, "notifications-unread"),
+        (snapshot.dnd, "notifications-dnd"),
+        (!snapshot.available, "notifications-unavailable"),
+    ]
+    .into_iter()
+    .filter_map(|(active, class_name)| active.then_some(class_name))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map};
+
+    use super::*;
+
+    fn config(value: Value) -> NotificationsConfig {
+        let Value::Object(map) = value else {
+            panic!("config should be an object");
+        };
+        parse_config(&ModuleConfig::new(MODULE_TYPE, map)).expect("config should parse")
+    }
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'notifications'"));
+    }
+
+    #[test]
+    fn parse_config_reads_backend_and_limits() {
+        let parsed = config(json!({ "backend": "dunst", "max_items": 3 }));
+        assert_eq!(parsed.backend, NotificationsBackendKind::Dunst);
+        assert_eq!(parsed.max_items, 3);
+
+        let parsed = config(json!({}));
+        assert_eq!(parsed.backend, NotificationsBackendKind::Daemon);
+        assert_eq!(parsed.max_items, DEFAULT_MAX_ITEMS);
+    }
+
+    #[test]
+    fn parse_config_reads_dnd_schedule() {
+        let parsed = config(json!({
+            "dnd-schedule": {
+                "windows": [{ "from": "22:00", "to": "07:00" }],
+                "while": "pgrep -x wf-recorder"
+            }
+        }));
+        assert_eq!(parsed.dnd_schedule.windows.len(), 1);
+        assert_eq!(parsed.dnd_schedule.windows[0].from, 22 * 60);
+        assert_eq!(parsed.dnd_schedule.windows[0].to, 7 * 60);
+        assert_eq!(
+            parsed.dnd_schedule.while_command.as_deref(),
+            Some("pgrep -x wf-recorder")
+        );
+        assert!(config(json!({})).dnd_schedule.is_empty());
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            json!({ "dnd_schedule": { "windows": [{ "from": "9am", "to": "17:00" }] } })
+                .as_object()
+                .cloned()
+                .expect("config should be an object"),
+        );
+        let err = parse_config(&module).expect_err("bad time should fail");
+        assert!(err.contains("expected HH:MM"));
+    }
+
+    #[test]
+    fn render_format_picks_state_format() {
+        let formats = resolve_formats(&config(json!({
+            "format": "idle {total}",
+            "format-unread": "{count} new",
+            "format-dnd": "dnd {count}"
+        })));
+        let mut snapshot = NotificationsSnapshot {
+            available: true,
+            ..NotificationsSnapshot::default()
+        };
+        assert_eq!(render_format(&formats, &snapshot), "idle 0");
+
+        snapshot.unread = 2;
+        assert_eq!(render_format(&formats, &snapshot), "2 new");
+        assert_eq!(state_classes(&snapshot), ["notifications-unread"]);
+
+        snapshot.dnd = true;
+        assert_eq!(render_format(&formats, &snapshot), "dnd 2");
+        assert_eq!(
+            state_classes(&snapshot),
+            ["notifications-unread", "notifications-dnd"]
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use chrono::{Local, Timelike};
+use serde::{Deserialize, Deserializer};
+
+use crate::modules::broadcaster::{BackendRegistry, Broadcaster, Subscription};
+
+use super::{set_dnd, NotificationsBackendKind};
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+/// How often the worker notices modules that went away, between the
+/// once-a-minute schedule checks.
+const SUBSCRIBER_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// `dnd-schedule` field: do-not-disturb turns on while the local time is in
+/// one of `windows`, or while the `while` command exits 0.
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq, Hash)]
+pub(crate) struct DndScheduleConfig {
+    #[serde(default)]
+    pub(crate) windows: Vec<DndWindow>,
+    #[serde(rename = "while", default)]
+    pub(crate) while_command: Option<String>,
+}
+
+/// Time of day range in minutes after midnight; `to` before `from` spans
+/// midnight.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct DndWindow {
+    #[serde(deserialize_with = "deserialize_clock_time")]
+    pub(crate) from: u16,
+    #[serde(deserialize_with = "deserialize_clock_time")]
+    pub(crate) to: u16,
+}
+
+/// One schedule worker per backend. Modules with different schedules on the
+/// same backend are merged: do-not-disturb is on while any of them applies.
+#[derive(Default)]
+struct ScheduleWorker {
+    entries: Mutex<Vec<ScheduleEntry>>,
+}
+
+/// A distinct schedule and the modules that set it.
+struct ScheduleEntry {
+    schedule: DndScheduleConfig,
+    broadcaster: Broadcaster<()>,
+}
+
+impl ScheduleWorker {
+    fn subscribe(&self, schedule: DndScheduleConfig) -> Subscription<()> {
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("dnd schedule entries mutex poisoned");
+        if let Some(entry) = entries.iter().find(|entry| entry.schedule == schedule) {
+            return entry.broadcaster.subscribe();
+        }
+        let broadcaster = Broadcaster::new();
+        let subscription = broadcaster.subscribe();
+        entries.push(ScheduleEntry {
+            schedule,
+            broadcaster,
+        });
+        subscription
+    }
+
+    /// Schedules that still have a module, dropping the rest.
+    fn live_schedules(&self) -> Vec<DndScheduleConfig> {
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("dnd schedule entries mutex poisoned");
+        entries.retain(|entry| {
+            entry.broadcaster.prune_closed();
+            entry.broadcaster.subscriber_count() > 0
+        });
+        entries.iter().map(|entry| entry.schedule.clone()).collect()
+    }
+}
+
+impl DndScheduleConfig {
+    pub(super) fn is_empty(&self) -> bool {
+        self.windows.is_empty() && self.while_command.is_none()
+    }
+
+    fn is_active(&self, minute: u16) -> bool {
+        in_windows(&self.windows, minute)
+            || self.while_command.as_deref().is_some_and(command_succeeds)
+    }
+}
+
+impl DndWindow {
+    fn contains(self, minute: u16) -> bool {
+        if self.from <= self.to {
+            (self.from..self.to).contains(&minute)
+        } else {
+            minute >= self.from || minute < self.to
+        }
+    }
+}
+
+fn deserialize_clock_time<'de, D>(deserializer: D) -> Result<u16, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    parse_clock_time(&value).map_err(serde::de::Error::custom)
+}
+
+fn parse_clock_time(value: &str) -> Result<u16, String> {
+    let invalid = || format!("invalid time '{value}', expected HH:MM");
+    let (hours, minutes) = value.trim().split_once(':').ok_or_else(invalid)?;
+    let hours: u16 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u16 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 24 || minutes > 59 || (hours == 24 && minutes != 0) {
+        return Err(invalid());
+    }
+    Ok((hours * 60 + minutes) % MINUTES_PER_DAY)
+}
+
+/// Whether the windows alone put `minute` in do-not-disturb.
+fn in_windows(windows: &[DndWindow], minute: u16) -> bool {
+    windows.iter().any(|window| window.contains(minute))
+}
+
+/// The do-not-disturb state to apply when the schedule moves from
+/// `previous` to `scheduled`. Only boundaries change anything, so a manual
+/// toggle holds until the next one; at startup an inactive schedule leaves
+/// the current state alone.
+fn transition(previous: Option<bool>, scheduled: bool) -> Option<bool> {
+    match previous {
+        Some(previous) if previous == scheduled => None,
+        None if !scheduled => None,
+        _ => Some(scheduled),
+    }
+}
+
+fn command_succeeds(command: &str) -> bool {
+    Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+fn schedule_registry() -> &'static BackendRegistry<NotificationsBackendKind, ScheduleWorker> {
+    static REGISTRY: OnceLock<BackendRegistry<NotificationsBackendKind, ScheduleWorker>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+/// Keeps `schedule` applied to `backend` while the subscription lives.
+pub(super) fn subscribe_dnd_schedule(
+    backend: NotificationsBackendKind,
+    schedule: DndScheduleConfig,
+) -> Subscription<()> {
+    let (worker, start_worker) =
+        schedule_registry().get_or_create(backend, ScheduleWorker::default);
+    let receiver = worker.subscribe(schedule);
+
+    if start_worker {
+        start_schedule_worker(backend, worker);
+    }
+
+    receiver
+}
+
+fn start_schedule_worker(backend: NotificationsBackendKind, worker: Arc<ScheduleWorker>) {
+    std::thread::spawn(move || {
+        let mut previous = None;
+        let mut applied = Vec::new();
+        let mut next_check = Instant::now();
+        loop {
+            let schedules = worker.live_schedules();
+            if schedules.is_empty() {
+                schedule_registry().remove(&backend, &worker);
+                return;
+            }
+
+            if schedules != applied || Instant::now() >= next_check {
+                let now = Local::now();
+                let minute = (now.hour() * 60 + now.minute()) as u16;
+                let scheduled = schedules.iter().any(|schedule| schedule.is_active(minute));
+                if let Some(enabled) = transition(previous, scheduled) {
+                    set_dnd(backend, enabled);
+                }
+                previous = Some(scheduled);
+                applied = schedules;
+
+                // Check again just after the next minute starts.
+                let wait = 60 - u64::from(now.second().min(59));
+                next_check = Instant::now() + Duration::from_secs(wait);
+            }
+
+            std::thread::sleep(SUBSCRIBER_CHECK_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn parse_clock_time_accepts_hours_and_minutes() {
+        assert_eq!(parse_clock_time("07:30"), Ok(450));
+        assert_eq!(parse_clock_time("0:00"), Ok(0));
+        assert_eq!(parse_clock_time("24:00"), Ok(0));
+        assert!(parse_clock_time("7").is_err());
+        assert!(parse_clock_time("25:00").is_err());
+        assert!(parse_clock_time("12:60").is_err());
+    }
+
+    #[test]
+    fn windows_span_midnight() {
+        let schedule: DndScheduleConfig = serde_json::from_value(json!({
+            "windows": [{ "from": "22:00", "to": "07:00" }, { "from": "12:00", "to": "13:00" }]
+        }))
+        .expect("schedule should parse");
+
+        assert!(in_windows(&schedule.windows, 23 * 60));
+        assert!(in_windows(&schedule.windows, 6 * 60 + 59));
+        assert!(!in_windows(&schedule.windows, 7 * 60));
+        assert!(in_windows(&schedule.windows, 12 * 60 + 30));
+        assert!(!in_windows(&schedule.windows, 13 * 60));
+        assert!(!in_windows(&schedule.windows, 21 * 60 + 59));
+    }
+
+    #[test]
+    fn worker_merges_schedules_until_their_modules_go_away() {
+        let night: DndScheduleConfig =
+            serde_json::from_value(json!({ "windows": [{ "from": "22:00", "to": "07:00" }] }))
+                .expect("schedule should parse");
+        let lunch: DndScheduleConfig =
+            serde_json::from_value(json!({ "windows": [{ "from": "12:00", "to": "13:00" }] }))
+                .expect("schedule should parse");
+        let worker = ScheduleWorker::default();
+
+        let _night_a = worker.subscribe(night.clone());
+        let _night_b = worker.subscribe(night.clone());
+        let lunch_sub = worker.subscribe(lunch.clone());
+        assert_eq!(worker.live_schedules(), vec![night.clone(), lunch]);
+
+        drop(lunch_sub);
+        assert_eq!(worker.live_schedules(), vec![night]);
+    }
+
+    #[test]
+    fn transition_only_acts_on_boundaries() {
+        assert_eq!(transition(None, true), Some(true));
+        assert_eq!(transition(None, false), None);
+        assert_eq!(transition(Some(true), true), None);
+        assert_eq!(transition(Some(true), false), Some(false));
+        assert_eq!(transition(Some(false), true), Some(true));
+    }
+}
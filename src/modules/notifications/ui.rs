@@ -0,0 +1,170 @@
+use gtk::prelude::*;
+use gtk::{pango, Box as GtkBox, Button, GestureClick, Label, Orientation, Popover, PositionType};
+
+use super::backend::{perform, NotificationAction, NotificationEntry, NotificationsSnapshot};
+use super::{NotificationsBackendKind, MODULE_TYPE};
+use crate::modules::{popovers, touch};
+
+const BODY_MAX_WIDTH_CHARS: i32 = 40;
+
+#[derive(Clone)]
+pub(super) struct NotificationsUi {
+    content: GtkBox,
+}
+
+/// Left click toggles do-not-disturb; right click or a long press opens
+/// the list, which marks everything read.
+pub(super) fn build_ui(label: &Label, kind: NotificationsBackendKind) -> NotificationsUi {
+    label.add_css_class("clickable");
+
+    let popover = Popover::new();
+    popover.add_css_class("notifications-popover");
+    popover.set_autohide(true);
+    popover.set_has_arrow(true);
+    popover.set_position(PositionType::Top);
+    popover.set_parent(label);
+    popovers::track(&popover, MODULE_TYPE);
+    popover.connect_show(move |_| perform(kind, NotificationAction::MarkRead));
+
+    let content = GtkBox::new(Orientation::Vertical, 4);
+    content.add_css_class("notifications-content");
+    popover.set_child(Some(&content));
+
+    let toggle_popover = move || {
+        if popover.is_visible() {
+            popover.popdown();
+        } else {
+            popover.popup();
+        }
+    };
+    {
+        let toggle_popover = toggle_popover.clone();
+        touch::attach_long_press(label, move |_, _| toggle_popover());
+    }
+    let secondary = GestureClick::builder().button(3).build();
+    secondary.connect_pressed(move |_, _, _, _| toggle_popover());
+    label.add_controller(secondary);
+
+    let primary = GestureClick::builder().button(1).build();
+    primary.connect_released(move |_, _, _, _| perform(kind, NotificationAction::ToggleDnd));
+    label.add_controller(primary);
+
+    NotificationsUi { content }
+}
+
+/// Rebuilds the popover rows; actions only send the request and the next
+/// snapshot shows their result.
+pub(super) fn refresh_ui(
+    ui: &NotificationsUi,
+    kind: NotificationsBackendKind,
+    snapshot: &NotificationsSnapshot,
+    max_items: usize,
+) {
+    let content = &ui.content;
+    while let Some(child) = content.first_child() {
+        content.remove(&child);
+    }
+
+    content.append(&build_header_row(kind, snapshot));
+    if !snapshot.available {
+        content.append(&build_empty_label("Notification daemon unavailable"));
+        return;
+    }
+    if snapshot.entries.is_empty() {
+        content.append(&build_empty_label("No notifications"));
+        return;
+    }
+    for entry in snapshot.entries.iter().take(max_items) {
+        content.append(&build_entry_row(kind, entry));
+    }
+}
+
+fn build_header_row(kind: NotificationsBackendKind, snapshot: &NotificationsSnapshot) -> GtkBox {
+    let row = GtkBox::new(Orientation::Horizontal, 6);
+    row.add_css_class("notifications-header");
+
+    let title = Label::new(Some("Notifications"));
+    title.add_css_class("notifications-title");
+    title.set_xalign(0.0);
+    title.set_hexpand(true);
+    row.append(&title);
+
+    let dnd_button = Button::with_label("Do not disturb");
+    dnd_button.add_css_class("notifications-control-button");
+    if snapshot.dnd {
+        dnd_button.add_css_class("active");
+    }
+    dnd_button.set_sensitive(snapshot.available);
+    dnd_button.connect_clicked(move |button| {
+        button.set_sensitive(false);
+        perform(kind, NotificationAction::ToggleDnd);
+    });
+    row.append(&dnd_button);
+
+    let clear_button = Button::with_label("Clear");
+    clear_button.add_css_class("notifications-control-button");
+    clear_button.set_sensitive(snapshot.available && !snapshot.entries.is_empty());
+    clear_button.connect_clicked(move |button| {
+        button.set_sensitive(false);
+        perform(kind, NotificationAction::Clear);
+    });
+    row.append(&clear_button);
+    row
+}
+
+fn build_entry_row(kind: NotificationsBackendKind, entry: &NotificationEntry) -> GtkBox {
+    let row = GtkBox::new(Orientation::Horizontal, 6);
+    row.add_css_class("notifications-entry");
+    row.add_css_class(entry.urgency.css_class());
+
+    let text = GtkBox::new(Orientation::Vertical, 2);
+    text.set_hexpand(true);
+    if !entry.app_name.is_empty() {
+        let app = Label::new(Some(&entry.app_name));
+        app.add_css_class("notifications-entry-app");
+        app.set_xalign(0.0);
+        text.append(&app);
+    }
+    let summary = Label::new(Some(&entry.summary));
+    summary.add_css_class("notifications-entry-summary");
+    summary.set_xalign(0.0);
+    summary.set_wrap(true);
+    summary.set_max_width_chars(BODY_MAX_WIDTH_CHARS);
+    text.append(&summary);
+    if !entry.body.is_empty() {
+        let body = Label::new(None);
+        body.add_css_class("notifications-entry-body");
+        // Bodies may carry the spec's small markup subset; anything pango
+        // rejects (links, images) is shown as plain text.
+        if pango::parse_markup(&entry.body, '\0').is_ok() {
+            body.set_markup(&entry.body);
+        } else {
+            body.set_text(&entry.body);
+        }
+        body.set_xalign(0.0);
+        body.set_wrap(true);
+        body.set_max_width_chars(BODY_MAX_WIDTH_CHARS);
+        text.append(&body);
+    }
+    row.append(&text);
+
+    if entry.dismissable {
+        let dismiss = Button::with_label("×");
+        dismiss.add_css_class("notifications-dismiss");
+        dismiss.set_valign(gtk::Align::Start);
+        let id = entry.id;
+        dismiss.connect_clicked(move |button| {
+            button.set_sensitive(false);
+            perform(kind, NotificationAction::Dismiss(id));
+        });
+        row.append(&dismiss);
+    }
+    row
+}
+
+fn build_empty_label(text: &str) -> Label {
+    let label = Label::new(Some(text));
+    label.add_css_class("notifications-empty");
+    label.set_xalign(0.0);
+    label
+}
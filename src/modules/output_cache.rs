@@ -0,0 +1,115 @@
+//! On-disk cache of the last rendered output of polled modules.
+//!
+//! Entries live as one JSON file per key under
+//! `$XDG_STATE_HOME/vibar/output-cache` (falling back to
+//! `~/.local/state/vibar/output-cache`), so a restarted bar can show the
+//! previous value until the first real update arrives.
+
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+const CACHE_DIR_NAME: &str = "output-cache";
+
+/// Returns the cache file for `key` inside `namespace`, or `None` when no
+/// state directory can be resolved.
+pub(crate) fn cache_path(namespace: &str, key: &impl Hash) -> Option<PathBuf> {
+    let dir = state_dir()?.join(CACHE_DIR_NAME).join(namespace);
+    Some(dir.join(format!("{:016x}.json", stable_hash(key))))
+}
+
+pub(crate) fn load<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let raw = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+/// Writes `value` via a temporary file and rename so readers never see a
+/// partially written entry.
+pub(crate) fn store<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| format!("invalid cache path {}", path.display()))?;
+    fs::create_dir_all(parent)
+        .map_err(|err| format!("failed to create {}: {err}", parent.display()))?;
+    let raw = serde_json::to_string(value).map_err(|err| err.to_string())?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, raw)
+        .map_err(|err| format!("failed to write {}: {err}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path).map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+fn state_dir() -> Option<PathBuf> {
+    if let Ok(state_home) = env::var("XDG_STATE_HOME") {
+        if !state_home.is_empty() {
+            return Some(PathBuf::from(state_home).join("vibar"));
+        }
+    }
+    env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".local/state/vibar"))
+}
+
+/// FNV-1a, so cache file names stay the same across builds (unlike
+/// `DefaultHasher`, whose output is not guaranteed to be stable).
+pub(crate) fn stable_hash(key: &impl Hash) -> u64 {
+    let mut hasher = FnvHasher::default();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_hash_is_deterministic_and_key_sensitive() {
+        assert_eq!(stable_hash(&"echo hi"), stable_hash(&"echo hi"));
+        assert_ne!(stable_hash(&"echo hi"), stable_hash(&"echo ho"));
+    }
+
+    #[test]
+    fn store_then_load_round_trips() {
+        let path = env::temp_dir()
+            .join(format!("vibar-output-cache-test-{}", std::process::id()))
+            .join("entry.json");
+        store(&path, &vec!["a".to_string(), "b".to_string()]).expect("store should succeed");
+        assert_eq!(
+            load::<Vec<String>>(&path),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+        let _ = fs::remove_dir_all(path.parent().expect("path has parent"));
+    }
+
+    #[test]
+    fn load_returns_none_for_missing_file() {
+        assert_eq!(
+            load::<String>(Path::new("/nonexistent/vibar/output-cache.json")),
+            None
+        );
+    }
+}
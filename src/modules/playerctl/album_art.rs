@@ -0,0 +1,221 @@
+//! Album art for the active player.
+//!
+//! `file://` art is used in place. Remote art is downloaded with `curl` into
+//! `$XDG_CACHE_HOME/vibar/album-art` (falling back to `~/.cache`), one file
+//! per URL, and the directory is pruned oldest-first to the configured size
+//! after every download.
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::modules::output_cache::stable_hash;
+
+const CACHE_DIR_NAME: &str = "album-art";
+const DOWNLOAD_TIMEOUT_SECS: &str = "10";
+
+/// Returns a local path for `art_url` if the art is available right now.
+/// Remote art that is not cached yet is fetched in the background, and
+/// `ready_tx` is notified once it lands so the caller can republish.
+pub(super) fn resolve_art_path(
+    art_url: &str,
+    cache_limit_bytes: u64,
+    ready_tx: &mpsc::Sender<()>,
+) -> Option<String> {
+    if art_url.starts_with("file://") {
+        let (path, _) = gtk::glib::filename_from_uri(art_url).ok()?;
+        return path.is_file().then(|| path.to_string_lossy().into_owned());
+    }
+    if !is_remote_url(art_url) {
+        return None;
+    }
+
+    let dir = cache_dir()?;
+    let path = dir.join(cache_file_name(art_url));
+    if path.is_file() {
+        touch(&path);
+        return Some(path.to_string_lossy().into_owned());
+    }
+
+    // A failed URL is not retried on every metadata refresh; successful ones
+    // are forgotten so art evicted by pruning can be fetched again.
+    let first_attempt = attempted_urls()
+        .lock()
+        .map(|mut attempted| attempted.insert(art_url.to_string()))
+        .unwrap_or(false);
+    if first_attempt {
+        let art_url = art_url.to_string();
+        let ready_tx = ready_tx.clone();
+        std::thread::spawn(move || match download(&art_url, &path) {
+            Ok(()) => {
+                if let Ok(mut attempted) = attempted_urls().lock() {
+                    attempted.remove(&art_url);
+                }
+                prune_cache(&dir, cache_limit_bytes, &path);
+                let _ = ready_tx.send(());
+            }
+            Err(err) => eprintln!("playerctl: {err}"),
+        });
+    }
+    None
+}
+
+fn is_remote_url(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+fn attempted_urls() -> &'static Mutex<HashSet<String>> {
+    static ATTEMPTED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    ATTEMPTED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    let cache_home = env::var("XDG_CACHE_HOME")
+        .ok()
+        .filter(|cache_home| !cache_home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".cache"))
+        })?;
+    Some(cache_home.join("vibar").join(CACHE_DIR_NAME))
+}
+
+fn cache_file_name(art_url: &str) -> String {
+    format!("{:016x}", stable_hash(&art_url))
+}
+
+/// Bumps the mtime so pruning treats recently shown art as fresh.
+fn touch(path: &Path) {
+    if let Ok(file) = fs::File::options().append(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// Downloads via a temporary file and rename so a half-written file is
+/// never picked up as cached art.
+fn download(art_url: &str, path: &Path) -> Result<(), String> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| format!("invalid album art path {}", path.display()))?;
+    fs::create_dir_all(parent)
+        .map_err(|err| format!("failed to create {}: {err}", parent.display()))?;
+
+    let tmp_path = path.with_extension("part");
+    let status = Command::new("curl")
+        .args(["-fsSL", "--max-time", DOWNLOAD_TIMEOUT_SECS, "-o"])
+        .arg(&tmp_path)
+        .arg(art_url)
+        .status()
+        .map_err(|err| format!("failed to run curl for album art: {err}"))?;
+    if !status.success() {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(format!("failed to download album art {art_url}: {status}"));
+    }
+    fs::rename(&tmp_path, path).map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+fn prune_cache(dir: &Path, limit_bytes: u64, keep: &Path) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    let entries = read_dir
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let metadata = entry
+                .metadata()
+                .ok()
+                .filter(|metadata| metadata.is_file())?;
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            Some((entry.path(), metadata.len(), modified))
+        })
+        .collect::<Vec<_>>();
+
+    for path in entries_to_evict(entries, limit_bytes, keep) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Picks the least recently used files to drop until the rest fit in
+/// `limit_bytes`. `keep` (the art just fetched) is never evicted, even when
+/// it alone exceeds the limit.
+fn entries_to_evict(
+    mut entries: Vec<(PathBuf, u64, SystemTime)>,
+    limit_bytes: u64,
+    keep: &Path,
+) -> Vec<PathBuf> {
+    let mut total = entries.iter().map(|(_, len, _)| len).sum::<u64>();
+    entries.sort_by_key(|(_, _, modified)| *modified);
+
+    let mut evicted = Vec::new();
+    for (path, len, _) in entries {
+        if total <= limit_bytes {
+            break;
+        }
+        if path == keep {
+            continue;
+        }
+        total = total.saturating_sub(len);
+        evicted.push(path);
+    }
+    evicted
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn entry(name: &str, len: u64, age_secs: u64) -> (PathBuf, u64, SystemTime) {
+        (
+            PathBuf::from(name),
+            len,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_000 - age_secs),
+        )
+    }
+
+    #[test]
+    fn entries_to_evict_drops_oldest_until_under_limit() {
+        let entries = vec![
+            entry("new", 40, 1),
+            entry("old", 40, 100),
+            entry("mid", 40, 50),
+        ];
+        assert_eq!(
+            entries_to_evict(entries.clone(), 80, Path::new("new")),
+            vec![PathBuf::from("old")]
+        );
+        assert_eq!(
+            entries_to_evict(entries.clone(), 200, Path::new("new")),
+            Vec::<PathBuf>::new()
+        );
+        // The fresh download survives even when it is the oldest entry.
+        assert_eq!(
+            entries_to_evict(entries, 0, Path::new("old")),
+            vec![PathBuf::from("mid"), PathBuf::from("new")]
+        );
+    }
+
+    #[test]
+    fn cache_file_name_is_stable_per_url() {
+        let first = cache_file_name("https://example.com/a.jpg");
+        assert_eq!(first, cache_file_name("https://example.com/a.jpg"));
+        assert_ne!(first, cache_file_name("https://example.com/b.jpg"));
+        assert_eq!(first.len(), 16);
+    }
+
+    #[test]
+    fn only_http_urls_are_downloaded() {
+        assert!(is_remote_url("https://i.scdn.co/image/abc"));
+        assert!(is_remote_url("http://localhost/cover.png"));
+        assert!(!is_remote_url("file:///tmp/cover.png"));
+        assert!(!is_remote_url("data:image/png;base64,AAAA"));
+    }
+}
@@ -10,11 +10,12 @@ use zbus::zvariant::{ObjectPath, OwnedValue};
 use zbus::MatchRule;
 
 use crate::modules::broadcaster::Broadcaster;
+use crate::modules::dbus_supervisor::{self, ListenerExit};
 
+use super::album_art::resolve_art_path;
 use super::model::{
     matches_player_filter, metadata_artist, metadata_i64, metadata_object_path_string,
-    metadata_string, normalize_status, select_active_player, short_player_name, status_icon_for,
-    BackendUpdate, PlayerctlMetadata,
+    metadata_string, normalize_status, short_player_name, BackendUpdate, PlayerctlMetadata,
 };
 
 pub(super) const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
@@ -34,6 +35,18 @@ pub(super) fn call_player_method(bus_name: &str, method: &str) -> Result<(), Str
     Ok(())
 }
 
+/// Relative seek; MPRIS clamps the result to the track.
+pub(super) fn call_seek(bus_name: &str, offset_micros: i64) -> Result<(), String> {
+    let connection =
+        Connection::session().map_err(|err| format!("failed to connect to D-Bus: {err}"))?;
+    let proxy = Proxy::new(&connection, bus_name, MPRIS_PATH, MPRIS_PLAYER_INTERFACE)
+        .map_err(|err| format!("failed to create player proxy for {bus_name}: {err}"))?;
+    proxy
+        .call_method("Seek", &(offset_micros,))
+        .map_err(|err| format!("failed to call Seek on {bus_name}: {err}"))?;
+    Ok(())
+}
+
 pub(super) fn call_set_position(
     bus_name: &str,
     track_id: &str,
@@ -51,16 +64,33 @@ pub(super) fn call_set_position(
     Ok(())
 }
 
+/// Sets the MPRIS `Volume` property; players without volume control
+/// reject the write.
+pub(super) fn call_set_volume(bus_name: &str, volume: f64) -> Result<(), String> {
+    let connection =
+        Connection::session().map_err(|err| format!("failed to connect to D-Bus: {err}"))?;
+    let proxy = Proxy::new(&connection, bus_name, MPRIS_PATH, MPRIS_PLAYER_INTERFACE)
+        .map_err(|err| format!("failed to create player proxy for {bus_name}: {err}"))?;
+    proxy
+        .set_property("Volume", volume)
+        .map_err(|err| format!("failed to set Volume on {bus_name}: {err}"))?;
+    Ok(())
+}
+
+/// `art_cache_limit_bytes` enables album art lookups for the active player;
+/// downloads that finish later trigger another snapshot.
 pub(super) fn run_event_backend(
     broadcaster: &Arc<Broadcaster<BackendUpdate>>,
     player_filter: Option<String>,
+    art_cache_limit_bytes: Option<u64>,
 ) {
     let (trigger_tx, trigger_rx) = mpsc::channel::<()>();
+    let art = art_cache_limit_bytes.map(|limit_bytes| (limit_bytes, trigger_tx.clone()));
 
     start_name_owner_listener(trigger_tx.clone());
     start_properties_listener(trigger_tx);
 
-    publish_snapshot(broadcaster, player_filter.as_deref());
+    publish_snapshot(broadcaster, player_filter.as_deref(), art.as_ref());
 
     while let Ok(_) | Err(RecvTimeoutError::Timeout) =
         trigger_rx.recv_timeout(Duration::from_millis(500))
@@ -68,13 +98,29 @@ pub(super) fn run_event_backend(
         if broadcaster.subscriber_count() == 0 {
             return;
         }
-        publish_snapshot(broadcaster, player_filter.as_deref());
+        publish_snapshot(broadcaster, player_filter.as_deref(), art.as_ref());
     }
 }
 
-fn publish_snapshot(broadcaster: &Broadcaster<BackendUpdate>, player_filter: Option<&str>) {
-    let update = match query_active_player_metadata(player_filter) {
-        Ok(snapshot) => BackendUpdate::Snapshot(snapshot),
+fn publish_snapshot(
+    broadcaster: &Broadcaster<BackendUpdate>,
+    player_filter: Option<&str>,
+    art: Option<&(u64, mpsc::Sender<()>)>,
+) {
+    let update = match query_players(player_filter) {
+        Ok(mut players) => {
+            // Art is resolved for every player, since each module may show
+            // a different one.
+            if let Some((limit_bytes, ready_tx)) = art {
+                for metadata in &mut players {
+                    metadata.art_path = metadata
+                        .art_url
+                        .as_deref()
+                        .and_then(|art_url| resolve_art_path(art_url, *limit_bytes, ready_tx));
+                }
+            }
+            BackendUpdate::Snapshot(players)
+        }
         Err(err) => BackendUpdate::Error(err),
     };
 
@@ -82,19 +128,23 @@ fn publish_snapshot(broadcaster: &Broadcaster<BackendUpdate>, player_filter: Opt
 }
 
 fn start_name_owner_listener(trigger_tx: std::sync::mpsc::Sender<()>) {
-    std::thread::spawn(move || {
-        let Ok(connection) = Connection::session() else {
-            eprintln!("playerctl: failed to open session bus for NameOwnerChanged listener");
-            return;
-        };
-        let Ok(proxy) = DBusProxy::new(&connection) else {
-            eprintln!("playerctl: failed to create DBus proxy for NameOwnerChanged listener");
-            return;
+    dbus_supervisor::spawn_listener("playerctl", move |connection| {
+        let proxy = match DBusProxy::new(connection) {
+            Ok(proxy) => proxy,
+            Err(err) => return ListenerExit::Lost(format!("failed to create DBus proxy: {err}")),
         };
-        let Ok(mut signals) = proxy.receive_name_owner_changed() else {
-            eprintln!("playerctl: failed to subscribe to NameOwnerChanged");
-            return;
+        let mut signals = match proxy.receive_name_owner_changed() {
+            Ok(signals) => signals,
+            Err(err) => {
+                return ListenerExit::Lost(format!(
+                    "failed to subscribe to NameOwnerChanged: {err}"
+                ))
+            }
         };
+        // Players may have come or gone while the bus was unreachable.
+        if trigger_tx.send(()).is_err() {
+            return ListenerExit::Stopped;
+        }
 
         for signal in &mut signals {
             let is_mpris = signal
@@ -103,19 +153,15 @@ fn start_name_owner_listener(trigger_tx: std::sync::mpsc::Sender<()>) {
                 .map(|args| args.name().starts_with(MPRIS_PREFIX))
                 .unwrap_or(false);
             if is_mpris && trigger_tx.send(()).is_err() {
-                return;
+                return ListenerExit::Stopped;
             }
         }
+        ListenerExit::Lost("NameOwnerChanged stream ended".to_string())
     });
 }
 
 fn start_properties_listener(trigger_tx: std::sync::mpsc::Sender<()>) {
-    std::thread::spawn(move || {
-        let Ok(connection) = Connection::session() else {
-            eprintln!("playerctl: failed to open session bus for PropertiesChanged listener");
-            return;
-        };
-
+    dbus_supervisor::spawn_listener("playerctl", move |connection| {
         let rule = match MatchRule::builder()
             .msg_type(MessageType::Signal)
             .interface(DBUS_PROPERTIES_INTERFACE)
@@ -126,13 +172,17 @@ fn start_properties_listener(trigger_tx: std::sync::mpsc::Sender<()>) {
             Ok(rule) => rule,
             Err(err) => {
                 eprintln!("playerctl: failed to build PropertiesChanged match rule: {err}");
-                return;
+                return ListenerExit::Stopped;
             }
         };
 
-        let Ok(iterator) = MessageIterator::for_match_rule(rule, &connection, Some(256)) else {
-            eprintln!("playerctl: failed to subscribe to PropertiesChanged");
-            return;
+        let iterator = match MessageIterator::for_match_rule(rule, connection, Some(256)) {
+            Ok(iterator) => iterator,
+            Err(err) => {
+                return ListenerExit::Lost(format!(
+                    "failed to subscribe to PropertiesChanged: {err}"
+                ))
+            }
         };
 
         for message in iterator {
@@ -141,9 +191,10 @@ fn start_properties_listener(trigger_tx: std::sync::mpsc::Sender<()>) {
             };
 
             if is_mpris_properties_changed(&message) && trigger_tx.send(()).is_err() {
-                return;
+                return ListenerExit::Stopped;
             }
         }
+        ListenerExit::Lost("PropertiesChanged stream ended".to_string())
     });
 }
 
@@ -159,9 +210,7 @@ fn is_mpris_properties_changed(message: &zbus::Message) -> bool {
     interface_name == MPRIS_PLAYER_INTERFACE || interface_name == MPRIS_ROOT_INTERFACE
 }
 
-fn query_active_player_metadata(
-    player_filter: Option<&str>,
-) -> Result<Option<PlayerctlMetadata>, String> {
+fn query_players(player_filter: Option<&str>) -> Result<Vec<PlayerctlMetadata>, String> {
     let connection =
         Connection::session().map_err(|err| format!("failed to connect to D-Bus: {err}"))?;
     let proxy =
@@ -181,18 +230,10 @@ fn query_active_player_metadata(
         players.retain(|name| matches_player_filter(name, filter));
     }
 
-    if players.is_empty() {
-        return Ok(None);
-    }
-
-    let mut candidates = Vec::new();
-    for bus_name in players {
-        if let Ok(metadata) = read_player_metadata(&connection, &bus_name) {
-            candidates.push(metadata);
-        }
-    }
-
-    Ok(select_active_player(candidates))
+    Ok(players
+        .iter()
+        .filter_map(|bus_name| read_player_metadata(&connection, bus_name).ok())
+        .collect())
 }
 
 fn read_player_metadata(
@@ -219,7 +260,6 @@ fn read_player_metadata(
         .unwrap_or_else(|| short_player_name(bus_name));
 
     Ok(PlayerctlMetadata {
-        status_icon: status_icon_for(&status),
         status,
         player,
         artist: metadata_artist(&metadata).unwrap_or_default(),
@@ -244,5 +284,8 @@ fn read_player_metadata(
             .unwrap_or(false),
         track_id: metadata_object_path_string(&metadata, "mpris:trackid"),
         bus_name: bus_name.to_string(),
+        art_url: metadata_string(&metadata, "mpris:artUrl"),
+        art_path: None,
+        volume: player_proxy.get_property::<f64>("Volume").ok(),
     })
 }
@@ -1,8 +1,21 @@
 use serde::Deserialize;
 
+use crate::modules::icons::Glyph;
+use crate::modules::scroll_text::{normalize_width_chars, ScrollTextMode};
+
 const DEFAULT_PLAYERCTL_INTERVAL_SECS: u32 = 1;
 const DEFAULT_PLAYERCTL_FORMAT: &str = "{status_icon} {title}";
 const DEFAULT_NO_PLAYER_TEXT: &str = "No media";
+const DEFAULT_ALBUM_ART_SIZE: u32 = 16;
+const DEFAULT_ALBUM_ART_POPOVER_SIZE: u32 = 96;
+const DEFAULT_ALBUM_ART_CACHE_SIZE_MB: u64 = 50;
+const DEFAULT_VOLUME_SCROLL_STEP: f64 = 5.0;
+const DEFAULT_MAX_VOLUME: u32 = 100;
+const MAX_VOLUME_LIMIT: u32 = 150;
+const STATUS_ICON_PLAYING: Glyph = Glyph::new("", "▶");
+const STATUS_ICON_PAUSED: Glyph = Glyph::new("", "⏸");
+const STATUS_ICON_STOPPED: Glyph = Glyph::new("", "⏹");
+const STATUS_ICON_UNKNOWN: Glyph = Glyph::new("", "♪");
 
 #[derive(Debug, Deserialize, Clone)]
 pub(super) struct PlayerctlConfig {
@@ -32,8 +45,29 @@ pub(super) struct PlayerctlConfig {
     pub(super) controls: PlayerctlControlsConfig,
     #[serde(rename = "max-width", alias = "max_width", default)]
     pub(super) max_width: Option<u32>,
+    #[serde(
+        rename = "scroll-text",
+        alias = "scroll_text",
+        alias = "marquee",
+        default
+    )]
+    pub(super) marquee: ScrollTextMode,
+    #[serde(rename = "status-icons", alias = "status_icons", default)]
+    pub(super) status_icons: PlayerctlStatusIcons,
+    #[serde(rename = "album-art", alias = "album_art", default)]
+    pub(super) album_art: PlayerctlAlbumArtConfig,
     #[serde(default)]
-    pub(super) marquee: PlayerctlMarqueeMode,
+    pub(super) volume: PlayerctlVolumeConfig,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub(super) struct PlayerctlStatusIcons {
+    #[serde(default = "default_status_icon_playing")]
+    pub(super) playing: String,
+    #[serde(default = "default_status_icon_paused")]
+    pub(super) paused: String,
+    #[serde(default = "default_status_icon_stopped")]
+    pub(super) stopped: String,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -50,6 +84,46 @@ pub(super) struct PlayerctlControlsConfig {
     pub(super) show_seek: bool,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub(super) struct PlayerctlAlbumArtConfig {
+    #[serde(default)]
+    pub(super) enabled: bool,
+    #[serde(default = "default_album_art_size")]
+    pub(super) size: u32,
+    #[serde(
+        rename = "popover-size",
+        alias = "popover_size",
+        default = "default_album_art_popover_size"
+    )]
+    pub(super) popover_size: u32,
+    #[serde(
+        rename = "cache-size-mb",
+        alias = "cache_size_mb",
+        default = "default_album_art_cache_size_mb"
+    )]
+    pub(super) cache_size_mb: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub(super) struct PlayerctlVolumeConfig {
+    #[serde(default)]
+    pub(super) enabled: bool,
+    #[serde(default = "default_volume_scroll")]
+    pub(super) scroll: bool,
+    #[serde(
+        rename = "scroll-step",
+        alias = "scroll_step",
+        default = "default_volume_scroll_step"
+    )]
+    pub(super) scroll_step: f64,
+    #[serde(
+        rename = "max-volume",
+        alias = "max_volume",
+        default = "default_max_volume"
+    )]
+    pub(super) max_volume: u32,
+}
+
 #[derive(Debug, Deserialize, Clone, Copy, Default)]
 #[serde(rename_all = "kebab-case")]
 pub(super) enum PlayerctlControlsOpenMode {
@@ -58,18 +132,6 @@ pub(super) enum PlayerctlControlsOpenMode {
     LeftClick,
 }
 
-#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
-#[serde(rename_all = "kebab-case")]
-pub(super) enum PlayerctlMarqueeMode {
-    #[default]
-    Off,
-    #[serde(alias = "on-hover", alias = "on_hover", alias = "hover_only")]
-    Hover,
-    #[serde(alias = "while-open", alias = "while_open", alias = "on-open")]
-    Open,
-    Always,
-}
-
 #[derive(Debug, Clone)]
 pub(super) struct PlayerctlViewConfig {
     pub(super) format: String,
@@ -84,7 +146,16 @@ pub(super) struct PlayerctlViewConfig {
     pub(super) controls_open: PlayerctlControlsOpenMode,
     pub(super) controls_show_seek: bool,
     pub(super) max_width: Option<u32>,
-    pub(super) marquee: PlayerctlMarqueeMode,
+    pub(super) marquee: ScrollTextMode,
+    pub(super) status_icons: PlayerctlStatusIcons,
+    /// Cache size limit for album art; `None` when album art is disabled.
+    pub(super) album_art_cache_bytes: Option<u64>,
+    pub(super) album_art_size: u32,
+    pub(super) album_art_popover_size: u32,
+    /// Upper volume bound in percent; `None` when volume control is off.
+    pub(super) volume_max: Option<u32>,
+    /// Percent per scroll step; `None` leaves scrolling to player cycling.
+    pub(super) volume_scroll_step: Option<f64>,
 }
 
 impl PlayerctlConfig {
@@ -105,6 +176,63 @@ impl PlayerctlConfig {
             controls_show_seek: self.controls.show_seek,
             max_width: self.max_width.and_then(normalize_width_chars),
             marquee: self.marquee,
+            status_icons: self.status_icons,
+            album_art_cache_bytes: self
+                .album_art
+                .enabled
+                .then(|| self.album_art.cache_size_mb.saturating_mul(1024 * 1024)),
+            album_art_size: self.album_art.size.max(1),
+            album_art_popover_size: self.album_art.popover_size.max(1),
+            volume_max: self
+                .volume
+                .enabled
+                .then(|| self.volume.max_volume.clamp(1, MAX_VOLUME_LIMIT)),
+            volume_scroll_step: (self.volume.enabled && self.volume.scroll)
+                .then_some(self.volume.scroll_step)
+                .filter(|step| step.is_finite() && *step > 0.0),
+        }
+    }
+}
+
+impl PlayerctlStatusIcons {
+    pub(super) fn icon_for(&self, status: &str) -> &str {
+        match status {
+            "playing" => &self.playing,
+            "paused" => &self.paused,
+            "stopped" => &self.stopped,
+            _ => STATUS_ICON_UNKNOWN.get(),
+        }
+    }
+}
+
+impl Default for PlayerctlStatusIcons {
+    fn default() -> Self {
+        Self {
+            playing: default_status_icon_playing(),
+            paused: default_status_icon_paused(),
+            stopped: default_status_icon_stopped(),
+        }
+    }
+}
+
+impl Default for PlayerctlAlbumArtConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            size: default_album_art_size(),
+            popover_size: default_album_art_popover_size(),
+            cache_size_mb: default_album_art_cache_size_mb(),
+        }
+    }
+}
+
+impl Default for PlayerctlVolumeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scroll: default_volume_scroll(),
+            scroll_step: default_volume_scroll_step(),
+            max_volume: default_max_volume(),
         }
     }
 }
@@ -123,14 +251,6 @@ pub(super) fn default_playerctl_interval() -> u32 {
     DEFAULT_PLAYERCTL_INTERVAL_SECS
 }
 
-pub(super) fn normalize_width_chars(value: u32) -> Option<u32> {
-    if value == 0 {
-        return None;
-    }
-
-    Some(value)
-}
-
 fn default_no_player_text() -> String {
     DEFAULT_NO_PLAYER_TEXT.to_string()
 }
@@ -143,6 +263,42 @@ fn default_show_seek() -> bool {
     true
 }
 
+fn default_album_art_size() -> u32 {
+    DEFAULT_ALBUM_ART_SIZE
+}
+
+fn default_album_art_popover_size() -> u32 {
+    DEFAULT_ALBUM_ART_POPOVER_SIZE
+}
+
+fn default_album_art_cache_size_mb() -> u64 {
+    DEFAULT_ALBUM_ART_CACHE_SIZE_MB
+}
+
+fn default_volume_scroll() -> bool {
+    true
+}
+
+fn default_volume_scroll_step() -> f64 {
+    DEFAULT_VOLUME_SCROLL_STEP
+}
+
+fn default_max_volume() -> u32 {
+    DEFAULT_MAX_VOLUME
+}
+
+fn default_status_icon_playing() -> String {
+    STATUS_ICON_PLAYING.get().to_string()
+}
+
+fn default_status_icon_paused() -> String {
+    STATUS_ICON_PAUSED.get().to_string()
+}
+
+fn default_status_icon_stopped() -> String {
+    STATUS_ICON_STOPPED.get().to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::{json, Map};
@@ -243,25 +399,19 @@ mod tests {
         assert_eq!(snake_cfg.max_width, Some(24));
     }
 
-    #[test]
-    fn normalize_width_chars_rejects_zero() {
-        assert_eq!(normalize_width_chars(0), None);
-        assert_eq!(normalize_width_chars(1), Some(1));
-    }
-
     #[test]
     fn parse_config_defaults_marquee_to_off() {
         let module = ModuleConfig::new(super::super::MODULE_TYPE, Map::new());
         let cfg = super::super::parse_config(&module).expect("config should parse");
-        assert!(matches!(cfg.marquee, PlayerctlMarqueeMode::Off));
+        assert!(matches!(cfg.marquee, ScrollTextMode::Off));
     }
 
     #[test]
-    fn parse_config_supports_marquee_modes() {
+    fn parse_config_supports_scroll_text_modes() {
         let hover = ModuleConfig::new(
             super::super::MODULE_TYPE,
             serde_json::from_value(json!({
-                "marquee": "hover"
+                "scroll-text": "hover"
             }))
             .expect("playerctl config map should parse"),
         );
@@ -275,7 +425,7 @@ mod tests {
         let always = ModuleConfig::new(
             super::super::MODULE_TYPE,
             serde_json::from_value(json!({
-                "marquee": "always"
+                "scroll-text": "always"
             }))
             .expect("playerctl config map should parse"),
         );
@@ -284,8 +434,94 @@ mod tests {
         let open_cfg = super::super::parse_config(&open).expect("config should parse");
         let always_cfg = super::super::parse_config(&always).expect("config should parse");
 
-        assert!(matches!(hover_cfg.marquee, PlayerctlMarqueeMode::Hover));
-        assert!(matches!(open_cfg.marquee, PlayerctlMarqueeMode::Open));
-        assert!(matches!(always_cfg.marquee, PlayerctlMarqueeMode::Always));
+        assert!(matches!(hover_cfg.marquee, ScrollTextMode::Hover));
+        assert!(matches!(open_cfg.marquee, ScrollTextMode::Open));
+        assert!(matches!(always_cfg.marquee, ScrollTextMode::Always));
+    }
+
+    #[test]
+    fn parse_config_supports_partial_status_icons() {
+        let module = ModuleConfig::new(
+            super::super::MODULE_TYPE,
+            serde_json::from_value(json!({
+                "status-icons": { "playing": ">", "paused": "||" }
+            }))
+            .expect("playerctl config map should parse"),
+        );
+        let cfg = super::super::parse_config(&module).expect("config should parse");
+
+        assert_eq!(cfg.status_icons.icon_for("playing"), ">");
+        assert_eq!(cfg.status_icons.icon_for("paused"), "||");
+        assert_eq!(
+            cfg.status_icons.icon_for("stopped"),
+            default_status_icon_stopped()
+        );
+    }
+
+    #[test]
+    fn parse_config_supports_album_art_keys() {
+        let module = ModuleConfig::new(super::super::MODULE_TYPE, Map::new());
+        let view = super::super::parse_config(&module)
+            .expect("config should parse")
+            .into_view();
+        assert_eq!(view.album_art_cache_bytes, None);
+        assert_eq!(view.album_art_size, DEFAULT_ALBUM_ART_SIZE);
+
+        let module = ModuleConfig::new(
+            super::super::MODULE_TYPE,
+            serde_json::from_value(json!({
+                "album_art": {
+                    "enabled": true,
+                    "popover-size": 128,
+                    "cache_size_mb": 2
+                }
+            }))
+            .expect("playerctl config map should parse"),
+        );
+        let view = super::super::parse_config(&module)
+            .expect("config should parse")
+            .into_view();
+        assert_eq!(view.album_art_cache_bytes, Some(2 * 1024 * 1024));
+        assert_eq!(view.album_art_popover_size, 128);
+    }
+
+    #[test]
+    fn parse_config_supports_volume_keys() {
+        let module = ModuleConfig::new(super::super::MODULE_TYPE, Map::new());
+        let view = super::super::parse_config(&module)
+            .expect("config should parse")
+            .into_view();
+        assert_eq!(view.volume_max, None);
+        assert_eq!(view.volume_scroll_step, None);
+
+        let module = ModuleConfig::new(
+            super::super::MODULE_TYPE,
+            serde_json::from_value(json!({
+                "volume": {
+                    "enabled": true,
+                    "scroll-step": 2.5,
+                    "max_volume": 400
+                }
+            }))
+            .expect("playerctl config map should parse"),
+        );
+        let view = super::super::parse_config(&module)
+            .expect("config should parse")
+            .into_view();
+        assert_eq!(view.volume_max, Some(MAX_VOLUME_LIMIT));
+        assert_eq!(view.volume_scroll_step, Some(2.5));
+
+        let module = ModuleConfig::new(
+            super::super::MODULE_TYPE,
+            serde_json::from_value(json!({
+                "volume": { "enabled": true, "scroll": false }
+            }))
+            .expect("playerctl config map should parse"),
+        );
+        let view = super::super::parse_config(&module)
+            .expect("config should parse")
+            .into_view();
+        assert_eq!(view.volume_max, Some(DEFAULT_MAX_VOLUME));
+        assert_eq!(view.volume_scroll_step, None);
     }
 }
@@ -1,34 +1,39 @@
+mod album_art;
 mod backend;
 mod config;
 mod model;
 mod ui;
 
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::{Arc, OnceLock};
 
 use gtk::prelude::*;
-use gtk::{Label, Overlay, Widget};
+use gtk::{EventControllerScroll, EventControllerScrollFlags, Label, Overlay, Widget};
 use serde_json::Value;
 
 use crate::modules::broadcaster::{
     attach_subscription, BackendRegistry, Broadcaster, Subscription,
 };
+use crate::modules::dbus_supervisor;
+use crate::modules::scroll_text::{ScrollText, ScrollTextMode};
+use crate::modules::tooltip::ModuleTooltip;
 use crate::modules::{
     apply_css_classes, attach_primary_click_command, escape_markup_text, ModuleBuildContext,
     ModuleConfig,
 };
 
 use super::ModuleFactory;
-use backend::run_event_backend;
-use config::{
-    default_playerctl_interval, PlayerctlConfig, PlayerctlMarqueeMode, PlayerctlViewConfig,
-};
+use backend::{call_set_volume, run_event_backend};
+use config::{default_playerctl_interval, PlayerctlConfig, PlayerctlViewConfig};
 use model::{
-    render_format, render_markup_format, should_show_metadata, status_css_class, BackendUpdate,
+    cycle_player, render_format, render_markup_format, select_player, should_show_metadata,
+    status_css_class, stepped_volume, BackendUpdate, PlayerctlMetadata,
 };
 use ui::{
-    build_carousel_ui, build_controls_ui, build_playerctl_tooltip, install_carousel_animation,
-    install_carousel_hover_tracking, install_carousel_open_tracking, install_controls_open_gesture,
-    refresh_controls_ui, set_playerctl_text, sync_controls_width, wire_controls_actions,
+    build_bar_art, build_controls_ui, connect_player_switcher, install_controls_open_gesture,
+    refresh_controls_ui, refresh_player_switcher, set_bar_art, set_playerctl_text,
+    sync_controls_width, wire_controls_actions,
 };
 
 const PLAYERCTL_STATE_CLASSES: [&str; 4] = [
@@ -42,6 +47,7 @@ pub(crate) const MODULE_TYPE: &str = "playerctl";
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct PlayerctlSharedKey {
     player: Option<String>,
+    album_art_cache_bytes: Option<u64>,
 }
 
 pub(crate) struct PlayerctlFactory;
@@ -53,9 +59,18 @@ impl ModuleFactory for PlayerctlFactory {
         MODULE_TYPE
     }
 
-    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
         let parsed = parse_config(config)?;
-        Ok(build_playerctl_module(parsed.into_view()).upcast())
+        Ok(build_playerctl_module(
+            parsed.into_view(),
+            context.scale(),
+            context.tooltip_format.clone(),
+        )
+        .upcast())
     }
 }
 
@@ -78,9 +93,13 @@ fn playerctl_registry() -> &'static BackendRegistry<PlayerctlSharedKey, Broadcas
     REGISTRY.get_or_init(BackendRegistry::new)
 }
 
-fn subscribe_shared_playerctl(player: Option<String>) -> Subscription<BackendUpdate> {
+fn subscribe_shared_playerctl(
+    player: Option<String>,
+    album_art_cache_bytes: Option<u64>,
+) -> Subscription<BackendUpdate> {
     let key = PlayerctlSharedKey {
         player: player.clone(),
+        album_art_cache_bytes,
     };
 
     let (broadcaster, start_worker) =
@@ -100,12 +119,16 @@ fn start_playerctl_worker(
     player: Option<String>,
 ) {
     std::thread::spawn(move || {
-        run_event_backend(&broadcaster, player);
+        run_event_backend(&broadcaster, player, key.album_art_cache_bytes);
         playerctl_registry().remove(&key, &broadcaster);
     });
 }
 
-fn build_playerctl_module(config: PlayerctlViewConfig) -> Overlay {
+fn build_playerctl_module(
+    config: PlayerctlViewConfig,
+    scale: f64,
+    tooltip_format: Option<String>,
+) -> Overlay {
     let root = Overlay::new();
     root.add_css_class("module");
     root.add_css_class("playerctl");
@@ -113,6 +136,7 @@ fn build_playerctl_module(config: PlayerctlViewConfig) -> Overlay {
     root.set_focus_on_click(false);
 
     apply_css_classes(&root, config.class.as_deref());
+    dbus_supervisor::attach_disconnected_class(&root);
     root.set_tooltip_text(None);
 
     let label = Label::new(None);
@@ -123,26 +147,47 @@ fn build_playerctl_module(config: PlayerctlViewConfig) -> Overlay {
 
     let carousel = config.max_width.map(|max_width| {
         root.add_css_class("playerctl-max-width");
-        build_carousel_ui(&root, max_width, config.class.as_deref(), config.marquee)
+        let carousel = ScrollText::new(
+            &root,
+            max_width,
+            config.marquee,
+            scale,
+            &["module", "playerctl"],
+            config.class.as_deref(),
+        );
+        carousel.area().add_css_class("playerctl-carousel");
+        carousel
     });
-    if let Some(carousel) = &carousel {
-        root.set_child(Some(&carousel.area));
-    } else {
+    if carousel.is_none() {
         root.set_child(Some(&label));
     }
+    let art_ui = config
+        .album_art_cache_bytes
+        .map(|_| build_bar_art(&root, config.album_art_size));
 
     if !config.controls_enabled {
         attach_primary_click_command(&root, config.click_command.clone());
     }
 
     let controls_ui = if config.controls_enabled {
-        let controls_ui = build_controls_ui(&root, config.controls_show_seek);
+        let controls_ui = build_controls_ui(
+            &root,
+            config.controls_show_seek,
+            config
+                .album_art_cache_bytes
+                .map(|_| config.album_art_popover_size),
+            config.volume_max,
+        );
         install_controls_open_gesture(&root, &controls_ui.popover, config.controls_open);
         Some(controls_ui)
     } else {
         None
     };
-    let tooltip_ui = build_playerctl_tooltip(&root, controls_ui.as_ref().map(|ui| &ui.popover));
+    // Without `tooltip-format` the tooltip only repeats truncated carousel text.
+    let tooltip = ModuleTooltip::attach(&root).with_css_class("playerctl-tooltip-popover");
+    if let Some(controls) = &controls_ui {
+        tooltip.suppress_while(&controls.popover);
+    }
 
     if config.interval_secs != default_playerctl_interval() {
         eprintln!(
@@ -151,61 +196,105 @@ fn build_playerctl_module(config: PlayerctlViewConfig) -> Overlay {
         );
     }
 
-    let subscription = subscribe_shared_playerctl(config.player.clone());
+    let subscription =
+        subscribe_shared_playerctl(config.player.clone(), config.album_art_cache_bytes);
 
-    attach_subscription(&root, subscription, {
+    let pinned_player = Rc::new(RefCell::new(None::<String>));
+    let last_update = Rc::new(RefCell::new(BackendUpdate::Snapshot(Vec::new())));
+    // Volume set by scrolling, until the player reports it back; lets quick
+    // scroll steps build on each other instead of on a stale reading.
+    let pending_volume = Rc::new(RefCell::new(None::<(String, f64)>));
+    let render = Rc::new({
         let label = label.clone();
         let format = config.format.clone();
+        let status_icons = config.status_icons.clone();
         let no_player_text = config.no_player_text.clone();
         let hide_when_idle = config.hide_when_idle;
         let show_when_paused = config.show_when_paused;
         let controls_ui = controls_ui.clone();
         let carousel = carousel.clone();
-        let tooltip_ui = tooltip_ui.clone();
-        move |root, update| {
-            let (plain_text, markup_text, visibility, state_class) = match update {
-                BackendUpdate::Snapshot(Some(metadata)) => {
-                    let plain_text = render_format(&format, &metadata);
-                    let markup_text = render_markup_format(&format, &metadata);
-                    if let Some(controls) = &controls_ui {
-                        refresh_controls_ui(controls, Some(&metadata), "");
+        let tooltip = tooltip.clone();
+        let art_ui = art_ui.clone();
+        let pinned_player = pinned_player.clone();
+        move |root: &Overlay, update: &BackendUpdate| {
+            let (players, selected) = match update {
+                BackendUpdate::Snapshot(players) => (
+                    players.as_slice(),
+                    select_sticky_player(players, &pinned_player),
+                ),
+                BackendUpdate::Error(_) => (&[][..], None),
+            };
+            if let Some(art_ui) = &art_ui {
+                set_bar_art(
+                    art_ui,
+                    selected.and_then(|metadata| metadata.art_path.as_deref()),
+                );
+            }
+            if players.len() > 1 {
+                root.add_css_class("multiple-players");
+            } else {
+                root.remove_css_class("multiple-players");
+            }
+            if let Some(controls) = &controls_ui {
+                refresh_player_switcher(controls, players, selected);
+            }
+
+            let (plain_text, markup_text, tooltip_markup, visibility, state_class) =
+                match (update, selected) {
+                    (BackendUpdate::Error(err), _) => {
+                        let plain_text = format!("playerctl error: {err}");
+                        let markup_text = escape_markup_text(&plain_text);
+                        if let Some(controls) = &controls_ui {
+                            refresh_controls_ui(controls, None, &plain_text);
+                        }
+                        (
+                            plain_text,
+                            markup_text.clone(),
+                            markup_text,
+                            true,
+                            "no-player",
+                        )
                     }
-                    (
-                        plain_text,
-                        markup_text,
-                        should_show_metadata(Some(&metadata), hide_when_idle, show_when_paused),
-                        status_css_class(&metadata.status),
-                    )
-                }
-                BackendUpdate::Snapshot(None) => {
-                    let plain_text = no_player_text.clone();
-                    let markup_text = escape_markup_text(&plain_text);
-                    if let Some(controls) = &controls_ui {
-                        refresh_controls_ui(controls, None, &plain_text);
+                    (_, Some(metadata)) => {
+                        let status_icon = status_icons.icon_for(&metadata.status);
+                        let plain_text = render_format(&format, metadata, status_icon);
+                        let markup_text = render_markup_format(&format, metadata, status_icon);
+                        let tooltip_markup = match &tooltip_format {
+                            Some(tooltip_format) => {
+                                render_markup_format(tooltip_format, metadata, status_icon)
+                            }
+                            None => markup_text.clone(),
+                        };
+                        if let Some(controls) = &controls_ui {
+                            refresh_controls_ui(controls, Some(metadata), "");
+                        }
+                        (
+                            plain_text,
+                            markup_text,
+                            tooltip_markup,
+                            should_show_metadata(Some(metadata), hide_when_idle, show_when_paused),
+                            status_css_class(&metadata.status),
+                        )
                     }
-                    (
-                        plain_text,
-                        markup_text,
-                        should_show_metadata(None, hide_when_idle, show_when_paused),
-                        "no-player",
-                    )
-                }
-                BackendUpdate::Error(err) => {
-                    let plain_text = format!("playerctl error: {err}");
-                    let markup_text = escape_markup_text(&plain_text);
-                    if let Some(controls) = &controls_ui {
-                        refresh_controls_ui(controls, None, &plain_text);
+                    (_, None) => {
+                        let plain_text = no_player_text.clone();
+                        let markup_text = escape_markup_text(&plain_text);
+                        if let Some(controls) = &controls_ui {
+                            refresh_controls_ui(controls, None, &plain_text);
+                        }
+                        (
+                            plain_text,
+                            markup_text.clone(),
+                            markup_text,
+                            should_show_metadata(None, hide_when_idle, show_when_paused),
+                            "no-player",
+                        )
                     }
-                    (plain_text, markup_text, true, "no-player")
-                }
-            };
-            set_playerctl_text(
-                &label,
-                &tooltip_ui,
-                carousel.as_ref(),
-                &plain_text,
-                &markup_text,
-            );
+                };
+            let truncated =
+                set_playerctl_text(&label, carousel.as_ref(), &plain_text, &markup_text);
+            tooltip.set_enabled(tooltip_format.is_some() || truncated);
+            tooltip.set_markup(&tooltip_markup);
             if let Some(controls) = &controls_ui {
                 let width = root
                     .width_request()
@@ -219,16 +308,111 @@ fn build_playerctl_module(config: PlayerctlViewConfig) -> Overlay {
         }
     });
 
-    if let Some(carousel) = &carousel {
-        if matches!(carousel.marquee, PlayerctlMarqueeMode::Hover) {
-            install_carousel_hover_tracking(&root, carousel);
+    attach_subscription(&root, subscription, {
+        let render = render.clone();
+        let last_update = last_update.clone();
+        let pending_volume = pending_volume.clone();
+        move |root, update| {
+            *pending_volume.borrow_mut() = None;
+            render(root, &update);
+            *last_update.borrow_mut() = update;
         }
-        if matches!(carousel.marquee, PlayerctlMarqueeMode::Open) {
-            if let Some(controls) = &controls_ui {
-                install_carousel_open_tracking(&controls.popover, carousel);
+    });
+
+    // Steps the shown player's volume; returns whether it has one.
+    let adjust_volume = {
+        let last_update = last_update.clone();
+        let pinned_player = pinned_player.clone();
+        let volume_max = config.volume_max.unwrap_or_default();
+        move |step_percent: f64, increase: bool| {
+            let last_update = last_update.borrow();
+            let BackendUpdate::Snapshot(players) = &*last_update else {
+                return false;
+            };
+            let Some(metadata) = select_player(players, pinned_player.borrow().as_deref()) else {
+                return false;
+            };
+            let pending = pending_volume
+                .borrow()
+                .as_ref()
+                .filter(|(bus_name, _)| *bus_name == metadata.bus_name)
+                .map(|(_, volume)| *volume);
+            let Some(current) = pending.or(metadata.volume) else {
+                return false;
+            };
+            let volume = stepped_volume(current, step_percent, increase, volume_max);
+            *pending_volume.borrow_mut() = Some((metadata.bus_name.clone(), volume));
+            let bus_name = metadata.bus_name.clone();
+            std::thread::spawn(move || {
+                let _ = call_set_volume(&bus_name, volume);
+            });
+            true
+        }
+    };
+
+    // Pins the player `step` places away from the shown one; returns
+    // whether there was another player to switch to.
+    let cycle = Rc::new(move |root: &Overlay, step: isize| {
+        let last_update = last_update.borrow();
+        let BackendUpdate::Snapshot(players) = &*last_update else {
+            return false;
+        };
+        if players.len() < 2 {
+            return false;
+        }
+        let shown = select_player(players, pinned_player.borrow().as_deref())
+            .map(|metadata| metadata.bus_name.clone());
+        let Some(shown) = shown else {
+            return false;
+        };
+        *pinned_player.borrow_mut() = cycle_player(players, &shown, step);
+        render(root, &last_update);
+        true
+    });
+
+    let scroll = EventControllerScroll::new(
+        EventControllerScrollFlags::VERTICAL | EventControllerScrollFlags::DISCRETE,
+    );
+    {
+        let cycle = cycle.clone();
+        let volume_scroll_step = config.volume_scroll_step;
+        scroll.connect_scroll(move |controller, _, dy| {
+            if dy == 0.0 {
+                return gtk::glib::Propagation::Proceed;
             }
+            let handled = match volume_scroll_step {
+                Some(step) => adjust_volume(step, dy < 0.0),
+                None => controller
+                    .widget()
+                    .and_downcast::<Overlay>()
+                    .is_some_and(|root| cycle(&root, if dy > 0.0 { 1 } else { -1 })),
+            };
+            if handled {
+                gtk::glib::Propagation::Stop
+            } else {
+                gtk::glib::Propagation::Proceed
+            }
+        });
+    }
+    root.add_controller(scroll);
+
+    if let Some(controls) = &controls_ui {
+        let root = root.clone();
+        connect_player_switcher(controls, move |step| {
+            cycle(&root, step);
+        });
+    }
+
+    if let Some(carousel) = &carousel {
+        match carousel.mode() {
+            ScrollTextMode::Hover => carousel.track_hover(&root),
+            ScrollTextMode::Open => {
+                if let Some(controls) = &controls_ui {
+                    carousel.track_open(&controls.popover);
+                }
+            }
+            ScrollTextMode::Off | ScrollTextMode::Always => {}
         }
-        install_carousel_animation(carousel.clone());
     }
 
     if let Some(controls) = controls_ui {
@@ -238,6 +422,20 @@ fn build_playerctl_module(config: PlayerctlViewConfig) -> Overlay {
     root
 }
 
+/// The pinned player while it exists; a pinned player that went away is
+/// forgotten, so selection falls back to the active player.
+fn select_sticky_player<'a>(
+    players: &'a [PlayerctlMetadata],
+    pinned_player: &RefCell<Option<String>>,
+) -> Option<&'a PlayerctlMetadata> {
+    let mut pinned = pinned_player.borrow_mut();
+    let selected = select_player(players, pinned.as_deref());
+    if selected.map(|metadata| &metadata.bus_name) != pinned.as_ref() {
+        *pinned = None;
+    }
+    selected
+}
+
 fn apply_state_class(widget: &impl IsA<Widget>, active_class: &str) {
     for class_name in PLAYERCTL_STATE_CLASSES {
         widget.remove_css_class(class_name);
@@ -5,14 +5,14 @@ use zbus::zvariant::{ObjectPath, OwnedValue};
 
 #[derive(Debug, Clone)]
 pub(super) enum BackendUpdate {
-    Snapshot(Option<PlayerctlMetadata>),
+    /// Every matching player, sorted by bus name.
+    Snapshot(Vec<PlayerctlMetadata>),
     Error(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub(super) struct PlayerctlMetadata {
     pub(super) status: String,
-    pub(super) status_icon: &'static str,
     pub(super) player: String,
     pub(super) artist: String,
     pub(super) album: String,
@@ -26,18 +26,51 @@ pub(super) struct PlayerctlMetadata {
     pub(super) can_seek: bool,
     pub(super) track_id: Option<String>,
     pub(super) bus_name: String,
+    /// `mpris:artUrl` as reported by the player.
+    pub(super) art_url: Option<String>,
+    /// Local file for `art_url`, once resolved or downloaded.
+    pub(super) art_path: Option<String>,
+    /// MPRIS `Volume`, where 1.0 is full volume; `None` when unsupported.
+    pub(super) volume: Option<f64>,
 }
 
-pub(super) fn select_active_player(
-    candidates: Vec<PlayerctlMetadata>,
-) -> Option<PlayerctlMetadata> {
-    candidates.into_iter().min_by(|a, b| {
+pub(super) fn select_active_player(candidates: &[PlayerctlMetadata]) -> Option<&PlayerctlMetadata> {
+    candidates.iter().min_by(|a, b| {
         active_rank(&a.status)
             .cmp(&active_rank(&b.status))
             .then(a.bus_name.cmp(&b.bus_name))
     })
 }
 
+/// The pinned player while it is still present, otherwise the active one.
+pub(super) fn select_player<'a>(
+    players: &'a [PlayerctlMetadata],
+    pinned: Option<&str>,
+) -> Option<&'a PlayerctlMetadata> {
+    pinned
+        .and_then(|bus_name| players.iter().find(|player| player.bus_name == bus_name))
+        .or_else(|| select_active_player(players))
+}
+
+/// Bus name of the player `step` places away from `current`, wrapping
+/// around `players`.
+pub(super) fn cycle_player(
+    players: &[PlayerctlMetadata],
+    current: &str,
+    step: isize,
+) -> Option<String> {
+    let count = isize::try_from(players.len())
+        .ok()
+        .filter(|count| *count > 0)?;
+    let index = players
+        .iter()
+        .position(|player| player.bus_name == current)
+        .and_then(|index| isize::try_from(index).ok())
+        .unwrap_or(0);
+    let next = usize::try_from((index + step).rem_euclid(count)).ok()?;
+    players.get(next).map(|player| player.bus_name.clone())
+}
+
 pub(super) fn matches_player_filter(bus_name: &str, filter: &str) -> bool {
     bus_name == filter
         || bus_name
@@ -56,15 +89,6 @@ pub(super) fn normalize_status(status: &str) -> String {
     status.to_ascii_lowercase()
 }
 
-pub(super) fn status_icon_for(status: &str) -> &'static str {
-    match status {
-        "playing" => "",
-        "paused" => "",
-        "stopped" => "",
-        _ => "",
-    }
-}
-
 pub(super) fn metadata_string(metadata: &HashMap<String, OwnedValue>, key: &str) -> Option<String> {
     metadata
         .get(key)
@@ -117,26 +141,63 @@ pub(super) fn metadata_object_path_string(
         .filter(|path| !path.is_empty())
 }
 
-pub(super) fn render_format(format: &str, metadata: &PlayerctlMetadata) -> String {
+/// `{volume}` / `{player_volume}` as a whole percent, empty when the player has no volume.
+pub(super) fn volume_percent_text(volume: Option<f64>) -> String {
+    volume
+        .map(|volume| format!("{}", (volume * 100.0).round().max(0.0) as u32))
+        .unwrap_or_default()
+}
+
+/// Volume after one scroll step of `step_percent`, clamped to
+/// `0..=max_percent`.
+pub(super) fn stepped_volume(
+    current: f64,
+    step_percent: f64,
+    increase: bool,
+    max_percent: u32,
+) -> f64 {
+    let step = step_percent / 100.0;
+    let next = if increase {
+        current + step
+    } else {
+        current - step
+    };
+    next.clamp(0.0, f64::from(max_percent) / 100.0)
+}
+
+pub(super) fn render_format(
+    format: &str,
+    metadata: &PlayerctlMetadata,
+    status_icon: &str,
+) -> String {
     format
         .replace("{status}", &metadata.status)
-        .replace("{status_icon}", metadata.status_icon)
+        .replace("{status_icon}", status_icon)
         .replace("{player}", &metadata.player)
         .replace("{artist}", &metadata.artist)
         .replace("{album}", &metadata.album)
         .replace("{title}", &metadata.title)
+        .replace("{volume}", &volume_percent_text(metadata.volume))
+        .replace("{player_volume}", &volume_percent_text(metadata.volume))
 }
 
-pub(super) fn render_markup_format(format: &str, metadata: &PlayerctlMetadata) -> String {
+pub(super) fn render_markup_format(
+    format: &str,
+    metadata: &PlayerctlMetadata,
+    status_icon: &str,
+) -> String {
+    let volume = volume_percent_text(metadata.volume);
     render_markup_template(
         format,
         &[
             ("{status}", &metadata.status),
-            ("{status_icon}", metadata.status_icon),
+            ("{status_icon}", status_icon),
             ("{player}", &metadata.player),
             ("{artist}", &metadata.artist),
             ("{album}", &metadata.album),
             ("{title}", &metadata.title),
+            ("{volume}", &volume),
+            ("{player_volume}", &volume),
         ],
     )
 }
@@ -180,6 +241,15 @@ pub(super) fn metadata_seek_ratio(metadata: &PlayerctlMetadata) -> Option<f64> {
     Some(position as f64 / length as f64)
 }
 
+/// Track position under a pointer at `x` on a slider `width` pixels wide.
+pub(super) fn seek_position_at(x: f64, width: f64, length_micros: i64) -> Option<i64> {
+    if width <= 0.0 || length_micros <= 0 {
+        return None;
+    }
+    let ratio = (x / width).clamp(0.0, 1.0);
+    Some((length_micros as f64 * ratio).round() as i64)
+}
+
 pub(super) fn format_timestamp_micros(value: Option<i64>) -> String {
     let Some(micros) = value else {
         return "00:00".to_string();
@@ -221,10 +291,9 @@ mod tests {
 
     #[test]
     fn select_active_player_prefers_playing_then_name() {
-        let chosen = select_active_player(vec![
+        let candidates = [
             PlayerctlMetadata {
                 status: "paused".to_string(),
-                status_icon: "",
                 player: "vlc".to_string(),
                 artist: String::new(),
                 album: String::new(),
@@ -238,10 +307,12 @@ mod tests {
                 can_seek: false,
                 track_id: None,
                 bus_name: "org.mpris.MediaPlayer2.vlc".to_string(),
+                art_url: None,
+                art_path: None,
+                volume: None,
             },
             PlayerctlMetadata {
                 status: "playing".to_string(),
-                status_icon: "",
                 player: "spotify".to_string(),
                 artist: String::new(),
                 album: String::new(),
@@ -255,9 +326,12 @@ mod tests {
                 can_seek: false,
                 track_id: None,
                 bus_name: "org.mpris.MediaPlayer2.spotify".to_string(),
+                art_url: None,
+                art_path: None,
+                volume: None,
             },
-        ])
-        .expect("one player should be selected");
+        ];
+        let chosen = select_active_player(&candidates).expect("one player should be selected");
 
         assert_eq!(chosen.status, "playing");
         assert_eq!(chosen.bus_name, "org.mpris.MediaPlayer2.spotify");
@@ -267,7 +341,6 @@ mod tests {
     fn render_format_replaces_placeholders() {
         let metadata = PlayerctlMetadata {
             status: "paused".to_string(),
-            status_icon: "",
             player: "spotify".to_string(),
             artist: "Boards of Canada".to_string(),
             album: "Music Has the Right to Children".to_string(),
@@ -281,11 +354,15 @@ mod tests {
             can_seek: false,
             track_id: None,
             bus_name: "org.mpris.MediaPlayer2.spotify".to_string(),
+            art_url: None,
+            art_path: None,
+            volume: None,
         };
 
         let text = render_format(
             "{status_icon} {artist} - {title} ({player}) [{status}]",
             &metadata,
+            "",
         );
         assert_eq!(text, " Boards of Canada - Roygbiv (spotify) [paused]");
     }
@@ -294,7 +371,6 @@ mod tests {
     fn render_markup_format_escapes_placeholder_values() {
         let metadata = PlayerctlMetadata {
             status: "paused".to_string(),
-            status_icon: "",
             player: "spotify".to_string(),
             artist: "Boards <Canada>".to_string(),
             album: "Music Has the Right to Children".to_string(),
@@ -308,11 +384,15 @@ mod tests {
             can_seek: false,
             track_id: None,
             bus_name: "org.mpris.MediaPlayer2.spotify".to_string(),
+            art_url: None,
+            art_path: None,
+            volume: None,
         };
 
         let text = render_markup_format(
             "<span style=\"italic\">{artist} - {title}</span>",
             &metadata,
+            "",
         );
         assert_eq!(
             text,
@@ -324,7 +404,6 @@ mod tests {
     fn should_show_metadata_respects_visibility_settings() {
         let playing = PlayerctlMetadata {
             status: "playing".to_string(),
-            status_icon: "",
             player: String::new(),
             artist: String::new(),
             album: String::new(),
@@ -338,6 +417,9 @@ mod tests {
             can_seek: false,
             track_id: None,
             bus_name: String::new(),
+            art_url: None,
+            art_path: None,
+            volume: None,
         };
         let paused = PlayerctlMetadata {
             status: "paused".to_string(),
@@ -368,7 +450,6 @@ mod tests {
     fn metadata_seek_ratio_handles_expected_cases() {
         let metadata = PlayerctlMetadata {
             status: "playing".to_string(),
-            status_icon: "",
             player: String::new(),
             artist: String::new(),
             album: String::new(),
@@ -382,6 +463,9 @@ mod tests {
             can_seek: true,
             track_id: Some("/org/mpris/MediaPlayer2/track/1".to_string()),
             bus_name: String::new(),
+            art_url: None,
+            art_path: None,
+            volume: None,
         };
         assert_eq!(metadata_seek_ratio(&metadata), Some(0.25));
 
@@ -398,6 +482,18 @@ mod tests {
         assert_eq!(metadata_seek_ratio(&missing_position), None);
     }
 
+    #[test]
+    fn seek_position_at_maps_pointer_to_track_time() {
+        assert_eq!(seek_position_at(50.0, 200.0, 240_000_000), Some(60_000_000));
+        assert_eq!(seek_position_at(-10.0, 200.0, 240_000_000), Some(0));
+        assert_eq!(
+            seek_position_at(250.0, 200.0, 240_000_000),
+            Some(240_000_000)
+        );
+        assert_eq!(seek_position_at(50.0, 0.0, 240_000_000), None);
+        assert_eq!(seek_position_at(50.0, 200.0, 0), None);
+    }
+
     #[test]
     fn format_timestamp_micros_formats_mm_ss() {
         assert_eq!(format_timestamp_micros(None), "00:00");
@@ -405,4 +501,82 @@ mod tests {
         assert_eq!(format_timestamp_micros(Some(5_000_000)), "00:05");
         assert_eq!(format_timestamp_micros(Some(65_000_000)), "01:05");
     }
+
+    fn player(bus_name: &str, status: &str) -> PlayerctlMetadata {
+        PlayerctlMetadata {
+            status: status.to_string(),
+            player: short_player_name(bus_name),
+            artist: String::new(),
+            album: String::new(),
+            title: String::new(),
+            position_micros: None,
+            length_micros: None,
+            can_go_previous: false,
+            can_go_next: false,
+            can_play: false,
+            can_pause: false,
+            can_seek: false,
+            track_id: None,
+            bus_name: bus_name.to_string(),
+            art_url: None,
+            art_path: None,
+            volume: None,
+        }
+    }
+
+    #[test]
+    fn select_player_keeps_pinned_player_while_present() {
+        let players = [
+            player("org.mpris.MediaPlayer2.mpv", "paused"),
+            player("org.mpris.MediaPlayer2.spotify", "playing"),
+        ];
+        let pinned = select_player(&players, Some("org.mpris.MediaPlayer2.mpv"));
+        assert_eq!(pinned.map(|p| p.player.as_str()), Some("mpv"));
+
+        let gone = select_player(&players, Some("org.mpris.MediaPlayer2.vlc"));
+        assert_eq!(gone.map(|p| p.player.as_str()), Some("spotify"));
+    }
+
+    #[test]
+    fn cycle_player_wraps_in_both_directions() {
+        let players = [
+            player("org.mpris.MediaPlayer2.a", "playing"),
+            player("org.mpris.MediaPlayer2.b", "paused"),
+            player("org.mpris.MediaPlayer2.c", "stopped"),
+        ];
+        assert_eq!(
+            cycle_player(&players, "org.mpris.MediaPlayer2.c", 1).as_deref(),
+            Some("org.mpris.MediaPlayer2.a")
+        );
+        assert_eq!(
+            cycle_player(&players, "org.mpris.MediaPlayer2.a", -1).as_deref(),
+            Some("org.mpris.MediaPlayer2.c")
+        );
+        assert_eq!(cycle_player(&[], "org.mpris.MediaPlayer2.a", 1), None);
+    }
+
+    #[test]
+    fn stepped_volume_clamps_to_range() {
+        assert!((stepped_volume(0.5, 5.0, true, 100) - 0.55).abs() < 1e-9);
+        assert!((stepped_volume(0.5, 5.0, false, 100) - 0.45).abs() < 1e-9);
+        assert_eq!(stepped_volume(0.98, 5.0, true, 100), 1.0);
+        assert_eq!(stepped_volume(0.02, 5.0, false, 100), 0.0);
+        assert!((stepped_volume(0.98, 5.0, true, 120) - 1.03).abs() < 1e-9);
+    }
+
+    #[test]
+    fn render_format_shows_volume_percent_when_known() {
+        let metadata = PlayerctlMetadata {
+            volume: Some(0.426),
+            ..player("org.mpris.MediaPlayer2.mpv", "playing")
+        };
+        assert_eq!(
+            render_format("{player} {volume}%", &metadata, ""),
+            "mpv 43%"
+        );
+
+        let unsupported = player("org.mpris.MediaPlayer2.mpv", "playing");
+        assert_eq!(render_format("[{volume}]", &unsupported, ""), "[]");
+        assert_eq!(render_format("{player_volume}%", &metadata, ""), "43%");
+    }
 }
@@ -1,21 +1,48 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use gtk::glib::ControlFlow;
+use gtk::gdk::{Key, ModifierType, Texture};
+use gtk::glib::{self, Propagation};
 use gtk::prelude::*;
 use gtk::{
-    Box as GtkBox, Button, DrawingArea, EventControllerMotion, GestureClick, Grid, Label,
-    Orientation, Overlay, Popover, PositionType, Scale, Widget,
+    Box as GtkBox, Button, EventControllerKey, EventControllerMotion, GestureClick, Grid, Image,
+    Label, Orientation, Overlay, Popover, PositionType, PropagationPhase, Scale, Widget,
 };
 
-use crate::modules::apply_css_classes;
+use crate::modules::icons::Glyph;
+use crate::modules::popovers;
+use crate::modules::scroll_text::ScrollText;
 
-use super::backend::{call_player_method, call_set_position};
-use super::config::{PlayerctlControlsOpenMode, PlayerctlMarqueeMode};
-use super::model::{format_timestamp_micros, metadata_seek_ratio, PlayerctlMetadata};
+use super::backend::{call_player_method, call_seek, call_set_position, call_set_volume};
+use super::config::PlayerctlControlsOpenMode;
+use super::model::{
+    format_timestamp_micros, metadata_seek_ratio, seek_position_at, volume_percent_text,
+    PlayerctlMetadata,
+};
+use super::MODULE_TYPE;
+
+const CONTROL_ICON_PREVIOUS: Glyph = Glyph::new("", "⏮");
+const CONTROL_ICON_PLAY: Glyph = Glyph::new("", "▶");
+const CONTROL_ICON_PAUSE: Glyph = Glyph::new("", "⏸");
+const PLAYER_CYCLE_PREVIOUS: &str = "‹";
+const PLAYER_CYCLE_NEXT: &str = "›";
+const CONTROL_ICON_NEXT: Glyph = Glyph::new("", "⏭");
+const KEYBOARD_SEEK_STEP_MICROS: i64 = 5_000_000;
+/// Gap between the bar album art and the label.
+const ART_SPACING_PX: i32 = 4;
+
+/// What a key press in the controls popover does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ControlsKeyAction {
+    PlayPause,
+    Previous,
+    Next,
+    SeekBy(i64),
+    Close,
+}
 
 #[derive(Clone)]
 pub(super) struct PlayerctlControlsUi {
@@ -37,363 +64,142 @@ pub(super) struct PlayerctlControlsUi {
     seek_update_hold_until: Arc<std::sync::Mutex<Option<Instant>>>,
     current_metadata: Arc<std::sync::Mutex<Option<PlayerctlMetadata>>>,
     show_seek: bool,
+    art: Option<ArtImage>,
+    player_switcher: GtkBox,
+    player_switcher_label: Label,
+    player_previous_button: Button,
+    player_next_button: Button,
+    volume: Option<VolumeSlider>,
 }
 
+/// Popover slider for the MPRIS `Volume` property.
 #[derive(Clone)]
-pub(super) struct PlayerctlCarouselUi {
-    root: Overlay,
-    width_limit_px: i32,
-    pub(super) area: DrawingArea,
-    pub(super) marquee: PlayerctlMarqueeMode,
-    state: Rc<RefCell<PlayerctlCarouselState>>,
+struct VolumeSlider {
+    row: GtkBox,
+    scale: Scale,
+    value_label: Label,
+    suppress_callback: Rc<Cell<bool>>,
+    /// Backend updates leave the slider alone until this passes, so the
+    /// player echoing an older value doesn't yank it while dragging.
+    update_hold_until: Rc<Cell<Option<Instant>>>,
 }
 
+/// An image that only reloads its texture when the art path changes.
 #[derive(Clone)]
-pub(super) struct PlayerctlTooltipUi {
-    label: Label,
-    show_on_hover: Arc<AtomicBool>,
-}
-
-#[derive(Debug)]
-struct PlayerctlCarouselState {
-    full_text: String,
-    full_markup: String,
-    layout: Option<gtk::pango::Layout>,
-    content_width_px: f64,
-    viewport_width_px: i32,
-    text_height_px: i32,
-    offset_px: f64,
-    last_tick: Instant,
-    hover_active: bool,
-    open_active: bool,
-    hold_until: Option<Instant>,
-    waiting_restart: bool,
+struct ArtImage {
+    image: Image,
+    /// Last requested art path; a load that finishes after a newer request
+    /// is dropped.
+    path: Rc<RefCell<Option<String>>>,
 }
 
-pub(super) fn build_carousel_ui(
-    root: &Overlay,
-    max_width_chars: u32,
-    extra_classes: Option<&str>,
-    marquee: PlayerctlMarqueeMode,
-) -> PlayerctlCarouselUi {
-    let area = DrawingArea::new();
-    area.add_css_class("playerctl-carousel");
-    area.set_overflow(gtk::Overflow::Hidden);
-    area.set_focusable(false);
-    area.set_can_target(false);
-    area.set_hexpand(false);
-    area.set_halign(gtk::Align::Start);
-    area.set_vexpand(false);
-    area.set_valign(gtk::Align::Center);
-
-    let width_limit_px = width_px_for_widget(&area, max_width_chars);
-    let viewport_width_px = 1;
-    let viewport_height_px = fixed_height_px_from_label_probe(extra_classes);
-    area.set_content_width(viewport_width_px);
-    area.set_content_height(viewport_height_px);
-    area.set_size_request(viewport_width_px, -1);
-
-    root.set_overflow(gtk::Overflow::Hidden);
-    root.set_size_request(viewport_width_px, -1);
-    root.set_hexpand(false);
-    root.set_halign(gtk::Align::Start);
-    root.set_valign(gtk::Align::Center);
-
-    let state = Rc::new(RefCell::new(PlayerctlCarouselState {
-        full_text: String::new(),
-        full_markup: String::new(),
-        layout: None,
-        content_width_px: 0.0,
-        viewport_width_px,
-        text_height_px: 0,
-        offset_px: 0.0,
-        last_tick: Instant::now(),
-        hover_active: false,
-        open_active: false,
-        hold_until: None,
-        waiting_restart: false,
-    }));
-
-    area.set_draw_func({
-        let state = state.clone();
-        move |area, context, width, height| {
-            let state = state.borrow();
-            let Some(layout) = state.layout.as_ref() else {
-                return;
-            };
-            let y = ((height - state.text_height_px).max(0) as f64) / 2.0;
-            let show_overflow_hint = should_show_overflow_hint(&state, marquee);
-            let hint_width_px = if show_overflow_hint {
-                overflow_hint_width_px(area)
-            } else {
-                0
-            };
-            let text_clip_width_px = (width - hint_width_px).max(1);
-
-            context.save().ok();
-            context.rectangle(0.0, 0.0, f64::from(text_clip_width_px), f64::from(height));
-            context.clip();
-
-            render_layout_at(area, context, -state.offset_px, y, layout);
-
-            if state.content_width_px > area.allocated_width() as f64 {
-                let next_x = -state.offset_px + state.content_width_px + carousel_gap_px();
-                if next_x < area.allocated_width() as f64 {
-                    render_layout_at(area, context, next_x, y, layout);
-                }
-            }
-            context.restore().ok();
-
-            if show_overflow_hint {
-                render_overflow_hint(area, context, y);
-            }
-        }
-    });
-
-    PlayerctlCarouselUi {
-        root: root.clone(),
-        width_limit_px,
-        area,
-        marquee,
-        state,
-    }
+/// Album art overlaid at the start of the bar module; the text is shifted
+/// right while art is shown.
+#[derive(Clone)]
+pub(super) struct PlayerctlArtUi {
+    art: ArtImage,
+    content: Option<Widget>,
+    size: i32,
 }
 
+/// Shows `markup_text` on the bar and returns whether the carousel had to
+/// truncate it.
 pub(super) fn set_playerctl_text(
     label: &Label,
-    tooltip_ui: &PlayerctlTooltipUi,
-    carousel: Option<&PlayerctlCarouselUi>,
+    scroll_text: Option<&ScrollText>,
     plain_text: &str,
     markup_text: &str,
-) {
-    tooltip_ui.label.set_markup(markup_text);
-    tooltip_ui.show_on_hover.store(false, Ordering::Relaxed);
-
-    if let Some(carousel) = carousel {
-        let should_reset = {
-            let state = carousel.state.borrow();
-            state.full_text != plain_text || state.full_markup != markup_text
-        };
-
-        if should_reset {
-            reset_carousel_state(carousel, plain_text, markup_text);
-            carousel.area.queue_draw();
-        }
-
-        let is_truncated = {
-            let state = carousel.state.borrow();
-            state.content_width_px > state.viewport_width_px as f64
-        };
-        tooltip_ui
-            .show_on_hover
-            .store(is_truncated, Ordering::Relaxed);
+) -> bool {
+    if let Some(scroll_text) = scroll_text {
+        scroll_text.set_text(plain_text, markup_text)
     } else {
         label.set_markup(markup_text);
+        false
     }
 }
 
-pub(super) fn build_playerctl_tooltip(
-    root: &Overlay,
-    controls_popover: Option<&Popover>,
-) -> PlayerctlTooltipUi {
-    let popover = Popover::new();
-    popover.add_css_class("playerctl-tooltip-popover");
-    popover.set_has_arrow(true);
-    popover.set_position(PositionType::Top);
-    popover.set_autohide(false);
-    popover.set_parent(root);
-
-    let label = Label::new(None);
-    label.add_css_class("playerctl-tooltip-label");
-    label.set_wrap(false);
-    label.set_single_line_mode(true);
-    label.set_xalign(0.0);
-    popover.set_child(Some(&label));
-    let show_on_hover = Arc::new(AtomicBool::new(false));
-
-    let tooltip_suppressed = Arc::new(AtomicBool::new(false));
-    if let Some(controls_popover) = controls_popover {
-        let tooltip_suppressed_on_show = tooltip_suppressed.clone();
-        let tooltip_popover = popover.clone();
-        controls_popover.connect_show(move |_| {
-            tooltip_suppressed_on_show.store(true, Ordering::Relaxed);
-            tooltip_popover.popdown();
-        });
-        let tooltip_suppressed_on_hide = tooltip_suppressed.clone();
-        controls_popover.connect_hide(move |_| {
-            tooltip_suppressed_on_hide.store(false, Ordering::Relaxed);
-        });
-    }
-
-    let motion = EventControllerMotion::new();
-    {
-        let popover = popover.clone();
-        let label = label.clone();
-        let tooltip_suppressed = tooltip_suppressed.clone();
-        let show_on_hover = show_on_hover.clone();
-        motion.connect_enter(move |_, _, _| {
-            if !tooltip_suppressed.load(Ordering::Relaxed)
-                && show_on_hover.load(Ordering::Relaxed)
-                && !label.text().is_empty()
-            {
-                popover.popup();
-            }
-        });
-    }
-    {
-        let popover = popover.clone();
-        motion.connect_leave(move |_| {
-            popover.popdown();
-        });
+impl ArtImage {
+    fn new(css_class: &str, size: u32) -> Self {
+        let image = Image::new();
+        image.add_css_class(css_class);
+        image.set_pixel_size(i32::try_from(size).unwrap_or(i32::MAX));
+        image.set_can_target(false);
+        image.set_visible(false);
+        Self {
+            image,
+            path: Rc::new(RefCell::new(None)),
+        }
     }
-    root.add_controller(motion);
 
-    PlayerctlTooltipUi {
-        label,
-        show_on_hover,
-    }
-}
+    /// Reads the art file asynchronously when `path` changed; the previous
+    /// art stays up meanwhile. `on_shown` gets whether art is shown once the
+    /// image was updated.
+    fn set_path(&self, path: Option<&str>, on_shown: impl Fn(bool) + 'static) {
+        if self.path.borrow().as_deref() == path {
+            return;
+        }
+        *self.path.borrow_mut() = path.map(str::to_string);
+        let Some(path) = path.map(str::to_string) else {
+            self.show(None);
+            on_shown(false);
+            return;
+        };
 
-pub(super) fn install_carousel_hover_tracking(root: &Overlay, carousel: &PlayerctlCarouselUi) {
-    let motion = EventControllerMotion::new();
-    {
-        let state = carousel.state.clone();
-        motion.connect_enter(move |_, _, _| {
-            if let Ok(mut state) = state.try_borrow_mut() {
-                state.hover_active = true;
-                state.last_tick = Instant::now();
+        let art = self.clone();
+        glib::spawn_future_local(async move {
+            let loaded = gtk::gio::File::for_path(&path).load_bytes_future().await;
+            if art.path.borrow().as_deref() != Some(path.as_str()) {
+                return;
             }
+            let texture = loaded
+                .and_then(|(bytes, _)| Texture::from_bytes(&bytes))
+                .map_err(|err| eprintln!("playerctl: failed to load album art {path}: {err}"))
+                .ok();
+            art.show(texture.as_ref());
+            on_shown(texture.is_some());
         });
     }
-    {
-        let state = carousel.state.clone();
-        let area = carousel.area.clone();
-        motion.connect_leave(move |_| {
-            if let Ok(mut state) = state.try_borrow_mut() {
-                state.hover_active = false;
-                state.offset_px = 0.0;
-                state.hold_until = Some(Instant::now() + Duration::from_millis(350));
-                state.waiting_restart = false;
-            }
-            area.queue_draw();
-        });
+
+    fn show(&self, texture: Option<&Texture>) {
+        self.image.set_paintable(texture);
+        self.image.set_visible(texture.is_some());
     }
-    root.add_controller(motion);
 }
 
-pub(super) fn install_carousel_open_tracking(popover: &Popover, carousel: &PlayerctlCarouselUi) {
-    {
-        let state = carousel.state.clone();
-        popover.connect_show(move |_| {
-            if let Ok(mut state) = state.try_borrow_mut() {
-                state.open_active = true;
-                state.last_tick = Instant::now();
-            }
-        });
-    }
-    {
-        let state = carousel.state.clone();
-        let area = carousel.area.clone();
-        popover.connect_hide(move |_| {
-            if let Ok(mut state) = state.try_borrow_mut() {
-                state.open_active = false;
-                state.offset_px = 0.0;
-                state.hold_until = Some(Instant::now() + Duration::from_millis(350));
-                state.waiting_restart = false;
-            }
-            area.queue_draw();
-        });
+/// Must run after the label or scroll text is set as the root's child.
+pub(super) fn build_bar_art(root: &Overlay, size: u32) -> PlayerctlArtUi {
+    let art = ArtImage::new("playerctl-art", size);
+    art.image.set_halign(gtk::Align::Start);
+    art.image.set_valign(gtk::Align::Center);
+    root.add_overlay(&art.image);
+    PlayerctlArtUi {
+        art,
+        content: root.child(),
+        size: i32::try_from(size).unwrap_or(i32::MAX),
     }
 }
 
-pub(super) fn install_carousel_animation(carousel: PlayerctlCarouselUi) {
-    const SPEED_PX_PER_SEC: f64 = 48.0;
-    const END_HOLD_MS: u64 = 700;
-    const RESTART_HOLD_MS: u64 = 700;
-
-    let area_weak = carousel.area.downgrade();
-    gtk::glib::timeout_add_local(Duration::from_millis(24), move || {
-        let Some(area) = area_weak.upgrade() else {
-            return ControlFlow::Break;
-        };
-
-        if !area.is_mapped() {
-            return ControlFlow::Continue;
-        }
-        if matches!(carousel.marquee, PlayerctlMarqueeMode::Off) {
-            return ControlFlow::Continue;
-        }
-        let now = Instant::now();
-        let mut should_redraw = false;
-        let mut should_return_early = false;
-
-        {
-            let mut state = carousel.state.borrow_mut();
-            let elapsed_secs = now.saturating_duration_since(state.last_tick).as_secs_f64();
-            state.last_tick = now;
-
-            if matches!(carousel.marquee, PlayerctlMarqueeMode::Hover) && !state.hover_active {
-                should_return_early = true;
-            }
-            if matches!(carousel.marquee, PlayerctlMarqueeMode::Open) && !state.open_active {
-                should_return_early = true;
-            }
-
-            if !should_return_early
-                && (state.full_text.is_empty()
-                    || state.content_width_px <= state.viewport_width_px as f64)
-            {
-                if state.offset_px != 0.0 {
-                    state.offset_px = 0.0;
-                    should_redraw = true;
-                }
-                state.hold_until = None;
-                state.waiting_restart = false;
-                should_return_early = true;
-            }
-
-            if !should_return_early {
-                if let Some(hold_until) = state.hold_until {
-                    if now < hold_until {
-                        should_return_early = true;
-                    } else {
-                        state.hold_until = None;
-                        if state.waiting_restart {
-                            state.offset_px = 0.0;
-                            state.waiting_restart = false;
-                            state.hold_until = Some(now + Duration::from_millis(RESTART_HOLD_MS));
-                            should_redraw = true;
-                            should_return_early = true;
-                        }
-                    }
-                }
-            }
-
-            if !should_return_early {
-                state.offset_px += SPEED_PX_PER_SEC * elapsed_secs;
-                let loop_distance = state.content_width_px + carousel_gap_px();
-                if state.offset_px >= loop_distance {
-                    state.offset_px = loop_distance;
-                    state.waiting_restart = true;
-                    state.hold_until = Some(now + Duration::from_millis(END_HOLD_MS));
-                }
-                should_redraw = true;
-            }
-        }
-
-        if should_redraw {
-            area.queue_draw();
-        }
-
-        if should_return_early {
-            return ControlFlow::Continue;
+pub(super) fn set_bar_art(art_ui: &PlayerctlArtUi, path: Option<&str>) {
+    let content = art_ui.content.clone();
+    let size = art_ui.size;
+    art_ui.art.set_path(path, move |shown| {
+        if let Some(content) = &content {
+            let margin = if shown {
+                size.saturating_add(ART_SPACING_PX)
+            } else {
+                0
+            };
+            content.set_margin_start(margin);
         }
-
-        ControlFlow::Continue
     });
 }
 
-pub(super) fn build_controls_ui(root: &Overlay, show_seek: bool) -> PlayerctlControlsUi {
+pub(super) fn build_controls_ui(
+    root: &Overlay,
+    show_seek: bool,
+    art_size: Option<u32>,
+    volume_max: Option<u32>,
+) -> PlayerctlControlsUi {
     root.add_css_class("clickable");
     root.add_css_class("playerctl-controls-enabled");
 
@@ -403,6 +209,8 @@ pub(super) fn build_controls_ui(root: &Overlay, show_seek: bool) -> PlayerctlCon
     popover.set_has_arrow(true);
     popover.set_position(PositionType::Top);
     popover.set_parent(root);
+    popovers::track(&popover, MODULE_TYPE);
+    popovers::accept_keyboard(&popover);
     {
         let root = root.clone();
         let popover_for_callback = popover.clone();
@@ -418,20 +226,43 @@ pub(super) fn build_controls_ui(root: &Overlay, show_seek: bool) -> PlayerctlCon
     content.set_hexpand(true);
     popover.set_child(Some(&content));
 
+    let art = art_size.map(|size| {
+        let art = ArtImage::new("playerctl-controls-art", size);
+        art.image.set_halign(gtk::Align::Center);
+        content.append(&art.image);
+        art
+    });
+
+    let player_switcher = GtkBox::new(Orientation::Horizontal, 6);
+    player_switcher.add_css_class("playerctl-player-switcher");
+    player_switcher.set_visible(false);
+    let player_previous_button = Button::with_label(PLAYER_CYCLE_PREVIOUS);
+    player_previous_button.add_css_class("playerctl-player-cycle");
+    player_switcher.append(&player_previous_button);
+    let player_switcher_label = Label::new(None);
+    player_switcher_label.add_css_class("playerctl-player-switcher-label");
+    player_switcher_label.set_hexpand(true);
+    player_switcher_label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+    player_switcher.append(&player_switcher_label);
+    let player_next_button = Button::with_label(PLAYER_CYCLE_NEXT);
+    player_next_button.add_css_class("playerctl-player-cycle");
+    player_switcher.append(&player_next_button);
+    content.append(&player_switcher);
+
     let buttons_row = GtkBox::new(Orientation::Horizontal, 6);
     buttons_row.add_css_class("playerctl-controls-row");
     buttons_row.set_halign(gtk::Align::Center);
     content.append(&buttons_row);
 
-    let previous_button = Button::with_label("");
+    let previous_button = Button::with_label(CONTROL_ICON_PREVIOUS.get());
     previous_button.add_css_class("playerctl-control-button");
     buttons_row.append(&previous_button);
 
-    let play_pause_button = Button::with_label("");
+    let play_pause_button = Button::with_label(CONTROL_ICON_PLAY.get());
     play_pause_button.add_css_class("playerctl-control-button");
     buttons_row.append(&play_pause_button);
 
-    let next_button = Button::with_label("");
+    let next_button = Button::with_label(CONTROL_ICON_NEXT.get());
     next_button.add_css_class("playerctl-control-button");
     buttons_row.append(&next_button);
 
@@ -487,6 +318,12 @@ pub(super) fn build_controls_ui(root: &Overlay, show_seek: bool) -> PlayerctlCon
     seek_time_widget.set_visible(show_seek);
     content.append(&seek_time_widget);
 
+    let volume = volume_max.map(|max_percent| {
+        let volume = build_volume_slider(max_percent);
+        content.append(&volume.row);
+        volume
+    });
+
     let suppress_seek_callback = Arc::new(AtomicBool::new(false));
     let seek_update_hold_until = Arc::new(std::sync::Mutex::new(None));
     let current_metadata = Arc::new(std::sync::Mutex::new(None));
@@ -509,6 +346,7 @@ pub(super) fn build_controls_ui(root: &Overlay, show_seek: bool) -> PlayerctlCon
         });
     }
     seek_scale.add_controller(press_gesture);
+    install_seek_preview(&seek_scale, current_metadata.clone());
 
     PlayerctlControlsUi {
         popover,
@@ -529,9 +367,154 @@ pub(super) fn build_controls_ui(root: &Overlay, show_seek: bool) -> PlayerctlCon
         seek_update_hold_until,
         current_metadata,
         show_seek,
+        art,
+        player_switcher,
+        player_switcher_label,
+        player_previous_button,
+        player_next_button,
+        volume,
     }
 }
 
+fn build_volume_slider(max_percent: u32) -> VolumeSlider {
+    let row = GtkBox::new(Orientation::Horizontal, 8);
+    row.add_css_class("playerctl-volume-row");
+    let key = Label::new(Some("Volume"));
+    key.add_css_class("playerctl-volume-label");
+    row.append(&key);
+
+    let scale = Scale::with_range(
+        Orientation::Horizontal,
+        0.0,
+        f64::from(max_percent) / 100.0,
+        0.01,
+    );
+    scale.add_css_class("playerctl-volume-scale");
+    scale.set_draw_value(false);
+    scale.set_hexpand(true);
+    scale.set_sensitive(false);
+    row.append(&scale);
+
+    let value_label = Label::new(None);
+    value_label.add_css_class("playerctl-volume-value");
+    value_label.set_xalign(1.0);
+    row.append(&value_label);
+
+    VolumeSlider {
+        row,
+        scale,
+        value_label,
+        suppress_callback: Rc::new(Cell::new(false)),
+        update_hold_until: Rc::new(Cell::new(None)),
+    }
+}
+
+/// Insensitive for players that don't report a volume.
+fn refresh_volume_slider(volume: &VolumeSlider, metadata: Option<&PlayerctlMetadata>) {
+    let value = metadata.and_then(|metadata| metadata.volume);
+    volume.scale.set_sensitive(value.is_some());
+    if volume
+        .update_hold_until
+        .get()
+        .is_some_and(|until| Instant::now() < until)
+    {
+        return;
+    }
+    volume.update_hold_until.set(None);
+
+    volume.value_label.set_text(&volume_percent_text(value));
+    volume.suppress_callback.set(true);
+    volume.scale.set_value(value.unwrap_or(0.0));
+    volume.suppress_callback.set(false);
+}
+
+/// The arrows run `cycle` with -1 or 1 to show the previous or next player.
+pub(super) fn connect_player_switcher(
+    controls_ui: &PlayerctlControlsUi,
+    cycle: impl Fn(isize) + Clone + 'static,
+) {
+    {
+        let cycle = cycle.clone();
+        controls_ui
+            .player_previous_button
+            .connect_clicked(move |_| cycle(-1));
+    }
+    controls_ui
+        .player_next_button
+        .connect_clicked(move |_| cycle(1));
+}
+
+/// Shows the switcher with the selected player's name and position while
+/// more than one player is available.
+pub(super) fn refresh_player_switcher(
+    controls_ui: &PlayerctlControlsUi,
+    players: &[PlayerctlMetadata],
+    selected: Option<&PlayerctlMetadata>,
+) {
+    let position = selected.and_then(|selected| {
+        players
+            .iter()
+            .position(|player| player.bus_name == selected.bus_name)
+            .map(|index| (index, selected))
+    });
+    let Some((index, selected)) = position.filter(|_| players.len() > 1) else {
+        controls_ui.player_switcher.set_visible(false);
+        return;
+    };
+    controls_ui.player_switcher_label.set_text(&format!(
+        "{} ({}/{})",
+        selected.player,
+        index + 1,
+        players.len()
+    ));
+    controls_ui.player_switcher.set_visible(true);
+}
+
+/// Small time label that follows the pointer over the seek slider (also
+/// while dragging), showing where a click or release would seek to.
+fn install_seek_preview(
+    seek_scale: &Scale,
+    current_metadata: Arc<std::sync::Mutex<Option<PlayerctlMetadata>>>,
+) {
+    let preview_label = Label::new(None);
+    preview_label.add_css_class("playerctl-seek-preview-label");
+
+    let preview = Popover::new();
+    preview.add_css_class("playerctl-seek-preview");
+    preview.set_autohide(false);
+    preview.set_has_arrow(false);
+    preview.set_can_target(false);
+    preview.set_position(PositionType::Top);
+    preview.set_child(Some(&preview_label));
+    preview.set_parent(seek_scale);
+
+    let motion = EventControllerMotion::new();
+    {
+        let preview = preview.clone();
+        let scale = seek_scale.clone();
+        motion.connect_motion(move |_, x, _| {
+            let length_micros = current_metadata
+                .lock()
+                .ok()
+                .and_then(|slot| slot.as_ref().and_then(|metadata| metadata.length_micros));
+            let position = length_micros
+                .and_then(|length| seek_position_at(x, f64::from(scale.width()), length));
+            let Some(position) = position.filter(|_| scale.is_sensitive()) else {
+                preview.popdown();
+                return;
+            };
+
+            preview_label.set_text(&format_timestamp_micros(Some(position)));
+            preview.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, 0, 1, 1)));
+            if !preview.is_visible() {
+                preview.popup();
+            }
+        });
+    }
+    motion.connect_leave(move |_| preview.popdown());
+    seek_scale.add_controller(motion);
+}
+
 pub(super) fn install_controls_open_gesture(
     root: &Overlay,
     popover: &Popover,
@@ -554,6 +537,8 @@ pub(super) fn install_controls_open_gesture(
 }
 
 pub(super) fn wire_controls_actions(controls_ui: PlayerctlControlsUi) {
+    install_controls_key_controller(&controls_ui);
+
     let current_metadata_for_previous = controls_ui.current_metadata.clone();
     controls_ui.previous_button.connect_clicked(move |_| {
         let bus_name = current_metadata_for_previous
@@ -631,6 +616,97 @@ pub(super) fn wire_controls_actions(controls_ui: PlayerctlControlsUi) {
             let _ = call_set_position(&bus_name, &track_id, target_position);
         });
     });
+
+    if let Some(volume) = controls_ui.volume {
+        let current_metadata_for_volume = controls_ui.current_metadata.clone();
+        let VolumeSlider {
+            scale,
+            value_label,
+            suppress_callback,
+            update_hold_until,
+            ..
+        } = volume;
+        scale.connect_value_changed(move |scale| {
+            if suppress_callback.get() {
+                return;
+            }
+            update_hold_until.set(Some(Instant::now() + Duration::from_millis(700)));
+
+            let bus_name = current_metadata_for_volume
+                .lock()
+                .ok()
+                .and_then(|slot| slot.as_ref().map(|metadata| metadata.bus_name.clone()));
+            let Some(bus_name) = bus_name else {
+                return;
+            };
+            let value = scale.value().max(0.0);
+            value_label.set_text(&volume_percent_text(Some(value)));
+            std::thread::spawn(move || {
+                let _ = call_set_volume(&bus_name, value);
+            });
+        });
+    }
+}
+
+/// Space toggles playback, Left/Right seek, Shift+Left/Right skip tracks and
+/// Escape closes. Handled in the capture phase so a focused button doesn't
+/// also react to Space.
+fn install_controls_key_controller(controls_ui: &PlayerctlControlsUi) {
+    {
+        let play_pause_button = controls_ui.play_pause_button.clone();
+        controls_ui.popover.connect_show(move |_| {
+            play_pause_button.grab_focus();
+        });
+    }
+
+    let keys = EventControllerKey::new();
+    keys.set_propagation_phase(PropagationPhase::Capture);
+    let controls = controls_ui.clone();
+    keys.connect_key_pressed(move |_, key, _, modifiers| {
+        let Some(action) = controls_key_action(key, modifiers) else {
+            return Propagation::Proceed;
+        };
+        match action {
+            ControlsKeyAction::PlayPause => emit_if_sensitive(&controls.play_pause_button),
+            ControlsKeyAction::Previous => emit_if_sensitive(&controls.previous_button),
+            ControlsKeyAction::Next => emit_if_sensitive(&controls.next_button),
+            ControlsKeyAction::SeekBy(offset_micros) => {
+                let bus_name = controls
+                    .current_metadata
+                    .lock()
+                    .ok()
+                    .and_then(|slot| slot.as_ref().filter(|metadata| metadata.can_seek).cloned())
+                    .map(|metadata| metadata.bus_name);
+                if let Some(bus_name) = bus_name {
+                    std::thread::spawn(move || {
+                        let _ = call_seek(&bus_name, offset_micros);
+                    });
+                }
+            }
+            ControlsKeyAction::Close => controls.popover.popdown(),
+        }
+        Propagation::Stop
+    });
+    controls_ui.popover.add_controller(keys);
+}
+
+fn controls_key_action(key: Key, modifiers: ModifierType) -> Option<ControlsKeyAction> {
+    let shift = modifiers.contains(ModifierType::SHIFT_MASK);
+    match key {
+        Key::space | Key::KP_Space => Some(ControlsKeyAction::PlayPause),
+        Key::Left | Key::KP_Left if shift => Some(ControlsKeyAction::Previous),
+        Key::Right | Key::KP_Right if shift => Some(ControlsKeyAction::Next),
+        Key::Left | Key::KP_Left => Some(ControlsKeyAction::SeekBy(-KEYBOARD_SEEK_STEP_MICROS)),
+        Key::Right | Key::KP_Right => Some(ControlsKeyAction::SeekBy(KEYBOARD_SEEK_STEP_MICROS)),
+        Key::Escape => Some(ControlsKeyAction::Close),
+        _ => None,
+    }
+}
+
+fn emit_if_sensitive(button: &Button) {
+    if button.is_sensitive() {
+        button.emit_clicked();
+    }
 }
 
 pub(super) fn refresh_controls_ui(
@@ -641,6 +717,15 @@ pub(super) fn refresh_controls_ui(
     if let Ok(mut slot) = controls_ui.current_metadata.lock() {
         *slot = metadata.cloned();
     }
+    if let Some(art) = &controls_ui.art {
+        art.set_path(
+            metadata.and_then(|metadata| metadata.art_path.as_deref()),
+            |_| {},
+        );
+    }
+    if let Some(volume) = &controls_ui.volume {
+        refresh_volume_slider(volume, metadata);
+    }
 
     let Some(metadata) = metadata else {
         controls_ui
@@ -652,7 +737,9 @@ pub(super) fn refresh_controls_ui(
         controls_ui.metadata_title_value.set_text("—");
         controls_ui.previous_button.set_sensitive(false);
         controls_ui.play_pause_button.set_sensitive(false);
-        controls_ui.play_pause_button.set_label("");
+        controls_ui
+            .play_pause_button
+            .set_label(CONTROL_ICON_PLAY.get());
         controls_ui.next_button.set_sensitive(false);
         controls_ui.seek_scale.set_sensitive(false);
         controls_ui.seek_widget.set_visible(controls_ui.show_seek);
@@ -674,9 +761,9 @@ pub(super) fn refresh_controls_ui(
         .play_pause_button
         .set_sensitive(can_toggle_playback);
     let toggle_icon = if metadata.status == "playing" {
-        ""
+        CONTROL_ICON_PAUSE.get()
     } else {
-        ""
+        CONTROL_ICON_PLAY.get()
     };
     controls_ui.play_pause_button.set_label(toggle_icon);
     controls_ui
@@ -742,93 +829,6 @@ pub(super) fn sync_controls_width(controls_ui: &PlayerctlControlsUi, module_widt
     }
 }
 
-fn reset_carousel_state(carousel: &PlayerctlCarouselUi, plain_text: &str, markup_text: &str) {
-    let layout = carousel.area.create_pango_layout(None);
-    match gtk::pango::parse_markup(markup_text, '\0') {
-        Ok((attrs, text, _)) => {
-            layout.set_text(&text);
-            layout.set_attributes(Some(&attrs));
-        }
-        Err(_) => {
-            layout.set_text(plain_text);
-            layout.set_attributes(None);
-        }
-    }
-    let (text_width_px, text_height_px) = layout.pixel_size();
-    let content_width_px = text_width_px.max(1);
-    let viewport_width_px = content_width_px.min(carousel.width_limit_px);
-
-    let mut state = carousel.state.borrow_mut();
-    state.full_text = plain_text.to_string();
-    state.full_markup = markup_text.to_string();
-    state.layout = Some(layout);
-    state.content_width_px = content_width_px as f64;
-    state.viewport_width_px = viewport_width_px;
-    state.text_height_px = text_height_px.max(1);
-    state.offset_px = 0.0;
-    state.last_tick = Instant::now();
-    state.hold_until = Some(Instant::now() + Duration::from_millis(900));
-    state.waiting_restart = false;
-
-    carousel.area.set_content_width(viewport_width_px);
-    carousel.area.set_size_request(viewport_width_px, -1);
-    carousel.root.set_size_request(viewport_width_px, -1);
-}
-
-fn width_px_for_widget(widget: &impl IsA<Widget>, width_chars: u32) -> i32 {
-    let sample = "M".repeat(width_chars as usize);
-    let layout = widget.create_pango_layout(Some(sample.as_str()));
-    let (pixel_width, _) = layout.pixel_size();
-    pixel_width.max(1)
-}
-
-fn fixed_height_px_from_label_probe(extra_classes: Option<&str>) -> i32 {
-    let probe = Label::new(Some("Mg"));
-    probe.add_css_class("module");
-    probe.add_css_class("playerctl");
-    apply_css_classes(&probe, extra_classes);
-    probe.set_wrap(false);
-    probe.set_single_line_mode(true);
-
-    let (_, natural, _, _) = probe.measure(Orientation::Vertical, -1);
-    natural.max(1)
-}
-
-fn carousel_gap_px() -> f64 {
-    42.0
-}
-
-fn should_show_overflow_hint(
-    state: &PlayerctlCarouselState,
-    marquee: PlayerctlMarqueeMode,
-) -> bool {
-    let is_overflowing = state.content_width_px > state.viewport_width_px as f64;
-    if !is_overflowing {
-        return false;
-    }
-
-    match marquee {
-        PlayerctlMarqueeMode::Off => true,
-        PlayerctlMarqueeMode::Hover => !state.hover_active,
-        PlayerctlMarqueeMode::Open => !state.open_active,
-        PlayerctlMarqueeMode::Always => false,
-    }
-}
-
-fn overflow_hint_width_px(area: &DrawingArea) -> i32 {
-    let layout = area.create_pango_layout(Some("…"));
-    let (width, _) = layout.pixel_size();
-    width.max(1) + 4
-}
-
-fn render_overflow_hint(area: &DrawingArea, context: &gtk::cairo::Context, text_y: f64) {
-    let hint = "…";
-    let layout = area.create_pango_layout(Some(hint));
-    let (hint_width, _) = layout.pixel_size();
-    let x = f64::from((area.allocated_width() - hint_width - 1).max(0));
-    render_layout_at(area, context, x, text_y, &layout);
-}
-
 fn build_controls_metadata_labels(key: &str) -> (Label, Label) {
     let key_label = Label::new(Some(key));
     key_label.add_css_class("playerctl-controls-metadata-key");
@@ -857,13 +857,38 @@ fn non_empty_or_dash(text: &str) -> &str {
     }
 }
 
-#[allow(deprecated)]
-fn render_layout_at(
-    area: &DrawingArea,
-    context: &gtk::cairo::Context,
-    x: f64,
-    y: f64,
-    layout: &gtk::pango::Layout,
-) {
-    gtk::render_layout(&area.style_context(), context, x, y, layout);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn controls_key_action_maps_media_keys() {
+        let none = ModifierType::empty();
+        let shift = ModifierType::SHIFT_MASK;
+        assert_eq!(
+            controls_key_action(Key::space, none),
+            Some(ControlsKeyAction::PlayPause)
+        );
+        assert_eq!(
+            controls_key_action(Key::Left, none),
+            Some(ControlsKeyAction::SeekBy(-KEYBOARD_SEEK_STEP_MICROS))
+        );
+        assert_eq!(
+            controls_key_action(Key::Right, none),
+            Some(ControlsKeyAction::SeekBy(KEYBOARD_SEEK_STEP_MICROS))
+        );
+        assert_eq!(
+            controls_key_action(Key::Left, shift),
+            Some(ControlsKeyAction::Previous)
+        );
+        assert_eq!(
+            controls_key_action(Key::Right, shift),
+            Some(ControlsKeyAction::Next)
+        );
+        assert_eq!(
+            controls_key_action(Key::Escape, none),
+            Some(ControlsKeyAction::Close)
+        );
+        assert_eq!(controls_key_action(Key::Tab, none), None);
+    }
 }
@@ -0,0 +1,223 @@
+//! Layer-shell surfaces for module popovers (`popovers.layer`). While a
+//! tracked popover is shown, its content moves into a borderless layer-shell
+//! window next to the module, so it is not limited to the bar surface and
+//! can sit above fullscreen windows. The popover itself stays open but
+//! invisible, so module code keeps using `popup`, `popdown`, and
+//! `is_visible` unchanged.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{EventControllerKey, Popover, Widget};
+use gtk4_layer_shell::{Edge, KeyboardMode, Layer, LayerShell};
+
+use super::popovers::{self, PopoverLayer};
+
+const SURFACE_NAMESPACE: &str = "vibar-popover";
+const SURFACE_CLASS: &str = "vibar-popover-surface";
+
+/// A popover whose content currently lives in `window`.
+struct HostedPopover {
+    window: gtk::Window,
+    child: Widget,
+    autohide: bool,
+    has_arrow: bool,
+}
+
+/// Hosts `popover` in its own surface whenever `popovers.layer` is set at
+/// the time it opens.
+pub(super) fn attach(popover: &Popover) {
+    let hosted: Rc<RefCell<Option<HostedPopover>>> = Rc::default();
+    popover.connect_show({
+        let hosted = Rc::clone(&hosted);
+        move |popover| {
+            let Some(layer) = popovers::surface_layer() else {
+                return;
+            };
+            if hosted.borrow().is_none() {
+                *hosted.borrow_mut() = host(popover, layer);
+            }
+        }
+    });
+    popover.connect_hide(move |popover| {
+        let restored = hosted.borrow_mut().take();
+        if let Some(restored) = restored {
+            restore(popover, restored);
+        }
+    });
+}
+
+fn host(popover: &Popover, layer: PopoverLayer) -> Option<HostedPopover> {
+    let child = popover.child()?;
+    let anchor = popover.parent()?;
+    let bar = anchor.root()?.downcast::<gtk::Window>().ok()?;
+    // Offscreen renders (`vibar screenshot`) have no layer surface to follow,
+    // and popovers inside a hosted one (group drawers) can open from it.
+    if !bar.is_layer_window() || bar.has_css_class(SURFACE_CLASS) {
+        return None;
+    }
+
+    let window = gtk::Window::builder().decorated(false).build();
+    window.add_css_class(SURFACE_CLASS);
+    for class_name in popover.css_classes() {
+        window.add_css_class(&class_name);
+    }
+    window.init_layer_shell();
+    window.set_namespace(Some(SURFACE_NAMESPACE));
+    window.set_layer(match layer {
+        PopoverLayer::Top => Layer::Top,
+        PopoverLayer::Overlay => Layer::Overlay,
+    });
+    window.set_keyboard_mode(KeyboardMode::OnDemand);
+    if let Some(monitor) = bar.monitor() {
+        window.set_monitor(Some(&monitor));
+    }
+    place_next_to(&window, &bar, &anchor);
+
+    let hosted = HostedPopover {
+        window: window.clone(),
+        child: child.clone(),
+        autohide: popover.is_autohide(),
+        has_arrow: popover.has_arrow(),
+    };
+    // An autohiding popover would close on the first click into the surface.
+    popover.set_autohide(false);
+    popover.set_has_arrow(false);
+    popover.set_opacity(0.0);
+    popover.set_can_target(false);
+    popover.set_child(None::<&Widget>);
+    window.set_child(Some(&child));
+
+    let popdown = {
+        let popover = popover.downgrade();
+        move || {
+            if let Some(popover) = popover.upgrade() {
+                popover.popdown();
+            }
+        }
+    };
+    // Focus moving elsewhere closes it, like autohide, except to a popover
+    // opened from inside it.
+    window.connect_is_active_notify({
+        let popdown = popdown.clone();
+        move |window| {
+            let nested_open = window
+                .child()
+                .is_some_and(|child| popovers::is_open_inside(&child));
+            if !window.is_active() && !nested_open {
+                popdown();
+            }
+        }
+    });
+    let keys = EventControllerKey::new();
+    keys.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk::gdk::Key::Escape {
+            popdown();
+            return gtk::glib::Propagation::Stop;
+        }
+        gtk::glib::Propagation::Proceed
+    });
+    window.add_controller(keys);
+
+    window.present();
+    Some(hosted)
+}
+
+fn restore(popover: &Popover, hosted: HostedPopover) {
+    hosted.window.set_child(None::<&Widget>);
+    hosted.window.destroy();
+    popover.set_child(Some(&hosted.child));
+    popover.set_autohide(hosted.autohide);
+    popover.set_has_arrow(hosted.has_arrow);
+    popover.set_opacity(1.0);
+    popover.set_can_target(true);
+}
+
+/// Anchors `window` to the bar's edge, lined up with `anchor` and opening
+/// toward the middle of the bar.
+fn place_next_to(window: &gtk::Window, bar: &gtk::Window, anchor: &Widget) {
+    let Some(bounds) = anchor.compute_bounds(bar) else {
+        return;
+    };
+    let horizontal = bar.is_anchor(Edge::Left) && bar.is_anchor(Edge::Right);
+    let (edge, thickness) = if horizontal {
+        let edge = if bar.is_anchor(Edge::Top) {
+            Edge::Top
+        } else {
+            Edge::Bottom
+        };
+        (edge, bar.height())
+    } else {
+        let edge = if bar.is_anchor(Edge::Left) {
+            Edge::Left
+        } else {
+            Edge::Right
+        };
+        (edge, bar.width())
+    };
+    window.set_anchor(edge, true);
+    // Surfaces are placed outside exclusive zones; without one the bar
+    // would cover the popover.
+    let edge_margin = if bar.exclusive_zone() > 0 {
+        0
+    } else {
+        bar.margin(edge) + thickness
+    };
+    window.set_margin(edge, edge_margin);
+
+    let (start_edge, end_edge, start, size, length) = if horizontal {
+        (
+            Edge::Left,
+            Edge::Right,
+            bounds.x(),
+            bounds.width(),
+            bar.width(),
+        )
+    } else {
+        (
+            Edge::Top,
+            Edge::Bottom,
+            bounds.y(),
+            bounds.height(),
+            bar.height(),
+        )
+    };
+    let (start, size) = (start.round() as i32, size.round() as i32);
+    let (side, offset) = along_bar_offset(start, size, length);
+    let side = if side == BarSide::Start {
+        start_edge
+    } else {
+        end_edge
+    };
+    window.set_anchor(side, true);
+    window.set_margin(side, bar.margin(side) + offset);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BarSide {
+    Start,
+    End,
+}
+
+/// Which end of a bar of `length` the surface anchors to, and its distance
+/// from that end, for a module at `start` spanning `size`.
+fn along_bar_offset(start: i32, size: i32, length: i32) -> (BarSide, i32) {
+    if start + size / 2 <= length / 2 {
+        (BarSide::Start, start.max(0))
+    } else {
+        (BarSide::End, (length - start - size).max(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn along_bar_offset_opens_toward_the_middle() {
+        assert_eq!(along_bar_offset(10, 40, 1000), (BarSide::Start, 10));
+        assert_eq!(along_bar_offset(900, 60, 1000), (BarSide::End, 40));
+        assert_eq!(along_bar_offset(980, 40, 1000), (BarSide::End, 0));
+    }
+}
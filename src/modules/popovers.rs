@@ -0,0 +1,194 @@
+//! Shared popover manager: opening one module popover closes the others.
+//!
+//! Popovers register with the module type that owns them. Types listed in
+//! the top-level `popovers.exceptions` config neither close nor get closed,
+//! and `popovers.single-open=false` turns the behavior off entirely.
+//! Popovers are never closed by one nested inside them (group drawers).
+//! With `popovers.layer` set, tracked popovers open in their own layer-shell
+//! surface (see `popover_surface`).
+
+use std::cell::RefCell;
+
+use gtk::glib::WeakRef;
+use gtk::prelude::*;
+use gtk::Popover;
+use gtk4_layer_shell::{KeyboardMode, LayerShell};
+use serde::Deserialize;
+
+use super::popover_surface;
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct PopoverConfig {
+    #[serde(
+        rename = "single-open",
+        alias = "single_open",
+        default = "default_single_open"
+    )]
+    pub(crate) single_open: bool,
+    #[serde(default)]
+    pub(crate) exceptions: Vec<String>,
+    /// Layer for separate popover surfaces; unset keeps popovers on the bar
+    /// surface.
+    #[serde(default)]
+    pub(crate) layer: Option<PopoverLayer>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum PopoverLayer {
+    Top,
+    /// Above fullscreen windows.
+    Overlay,
+}
+
+impl Default for PopoverConfig {
+    fn default() -> Self {
+        Self {
+            single_open: default_single_open(),
+            exceptions: Vec::new(),
+            layer: None,
+        }
+    }
+}
+
+fn default_single_open() -> bool {
+    true
+}
+
+#[derive(Default)]
+struct PopoverManager {
+    config: PopoverConfig,
+    open: Vec<(&'static str, WeakRef<Popover>)>,
+}
+
+impl PopoverManager {
+    fn is_exempt(&self, owner: &str) -> bool {
+        self.config
+            .exceptions
+            .iter()
+            .any(|exception| exception == owner)
+    }
+}
+
+thread_local! {
+    static MANAGER: RefCell<PopoverManager> = RefCell::new(PopoverManager::default());
+}
+
+/// Applies the top-level `popovers` config; takes effect on the next popup.
+pub(crate) fn configure(config: &PopoverConfig) {
+    MANAGER.with(|manager| manager.borrow_mut().config = config.clone());
+}
+
+/// Layer for popover surfaces from the current config.
+pub(super) fn surface_layer() -> Option<PopoverLayer> {
+    MANAGER.with(|manager| manager.borrow().config.layer)
+}
+
+/// Whether a tracked popover opened from inside `widget` is shown.
+pub(super) fn is_open_inside(widget: &gtk::Widget) -> bool {
+    MANAGER.with(|manager| {
+        manager
+            .borrow()
+            .open
+            .iter()
+            .filter_map(|(_, weak)| weak.upgrade())
+            .any(|open| open.is_visible() && open.is_ancestor(widget))
+    })
+}
+
+/// Tracks `popover` so showing it closes other tracked popovers.
+/// `owner` is the module type, matched against `popovers.exceptions`.
+pub(crate) fn track(popover: &Popover, owner: &'static str) {
+    popover_surface::attach(popover);
+    popover.connect_show(move |popover| {
+        let to_close = MANAGER.with(|manager| {
+            let mut manager = manager.borrow_mut();
+            manager.open.retain(|(_, weak)| {
+                weak.upgrade()
+                    .is_some_and(|open| open.is_visible() && &open != popover)
+            });
+            manager.open.push((owner, popover.downgrade()));
+            if !manager.config.single_open || manager.is_exempt(owner) {
+                return Vec::new();
+            }
+            manager
+                .open
+                .iter()
+                .filter(|(open_owner, _)| !manager.is_exempt(open_owner))
+                .filter_map(|(_, weak)| weak.upgrade())
+                // A drawer stays open while a popover inside it is shown.
+                .filter(|open| open != popover && !popover.is_ancestor(open))
+                .collect::<Vec<_>>()
+        });
+        // Popdown outside the borrow: hide handlers re-enter the manager.
+        for open in to_close {
+            open.popdown();
+        }
+    });
+    popover.connect_hide(|popover| {
+        MANAGER.with(|manager| {
+            manager
+                .borrow_mut()
+                .open
+                .retain(|(_, weak)| weak.upgrade().is_some_and(|open| &open != popover));
+        });
+    });
+}
+
+/// Bar windows take no keyboard input. While `popover` is shown, its bar
+/// switches to on-demand keyboard focus so key controllers inside the
+/// popover receive events.
+pub(crate) fn accept_keyboard(popover: &Popover) {
+    popover.connect_show(|popover| set_bar_keyboard_mode(popover, KeyboardMode::OnDemand));
+    popover.connect_hide(|popover| set_bar_keyboard_mode(popover, KeyboardMode::None));
+}
+
+fn set_bar_keyboard_mode(popover: &Popover, mode: KeyboardMode) {
+    let Some(window) = popover
+        .root()
+        .and_then(|root| root.downcast::<gtk::Window>().ok())
+    else {
+        return;
+    };
+    if window.is_layer_window() {
+        window.set_keyboard_mode(mode);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn popover_config_defaults_to_single_open_without_exceptions() {
+        let config: PopoverConfig = serde_json::from_str("{}").expect("config should parse");
+        assert_eq!(config, PopoverConfig::default());
+        assert!(config.single_open);
+        assert!(config.exceptions.is_empty());
+        assert!(config.layer.is_none());
+    }
+
+    #[test]
+    fn popover_config_accepts_aliases_and_exceptions() {
+        let config: PopoverConfig =
+            serde_json::from_str(r#"{"single_open":false,"exceptions":["group"]}"#)
+                .expect("config should parse");
+        assert!(!config.single_open);
+        assert_eq!(config.exceptions, vec!["group".to_string()]);
+
+        let manager = PopoverManager {
+            config,
+            open: Vec::new(),
+        };
+        assert!(manager.is_exempt("group"));
+        assert!(!manager.is_exempt("tray"));
+    }
+
+    #[test]
+    fn popover_config_reads_surface_layer() {
+        let config: PopoverConfig =
+            serde_json::from_str(r#"{"layer":"overlay"}"#).expect("config should parse");
+        assert_eq!(config.layer, Some(PopoverLayer::Overlay));
+        assert!(serde_json::from_str::<PopoverConfig>(r#"{"layer":"bottom"}"#).is_err());
+    }
+}
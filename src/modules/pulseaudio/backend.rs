@@ -1,4 +1,4 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{BTreeMap, HashSet};
 use std::sync::mpsc::{Receiver, TryRecvError};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
@@ -14,31 +14,32 @@ use pulse::proplist::{properties, Proplist};
 use pulse::volume::Volume;
 
 use crate::modules::broadcaster::Broadcaster;
-use crate::modules::escape_markup_text;
+use crate::modules::{backend_errors, escape_markup_text, events};
 
-use super::config::PulseAudioConfig;
+use super::config::{PulseAudioConfig, MAX_VOLUME_LIMIT};
 use super::format::{classify_icon_kind_by_priority, IconKind};
+use super::state_cache::{CachedSink, EntityChange, PendingChanges, PulseCache};
 use super::{
-    normalized_scroll_step, render_format, AudioControlsState, PulseState, SinkDeviceEntry,
-    SinkInputEntry, SinkPortEntry, UiUpdate, WorkerCommand, MAINLOOP_IDLE_SLEEP_MILLIS,
-    SESSION_RECONNECT_DELAY_SECS,
+    is_overamplified, normalized_scroll_step, render_format, render_tooltip_format,
+    AudioControlsState, PulseState, SinkDeviceEntry, SinkInputEntry, SinkPortEntry, UiUpdate,
+    WorkerCommand, MAINLOOP_IDLE_SLEEP_MILLIS, MODULE_TYPE, SESSION_RECONNECT_DELAY_SECS,
 };
 
 #[derive(Debug, Clone)]
-struct ServerDefaults {
-    sink_name: String,
-    source_name: Option<String>,
+pub(super) struct ServerDefaults {
+    pub(super) sink_name: String,
+    pub(super) source_name: Option<String>,
 }
 
 #[derive(Debug, Clone)]
-struct SinkSnapshot {
-    volume: u32,
-    muted: bool,
-    bluetooth: bool,
-    icon_kind: IconKind,
-    channels: pulse::volume::ChannelVolumes,
-    ports: Vec<SinkPortEntry>,
-    active_port_name: Option<String>,
+pub(super) struct SinkSnapshot {
+    pub(super) volume: u32,
+    pub(super) muted: bool,
+    pub(super) bluetooth: bool,
+    pub(super) icon_kind: IconKind,
+    pub(super) channels: pulse::volume::ChannelVolumes,
+    pub(super) ports: Vec<SinkPortEntry>,
+    pub(super) active_port_name: Option<String>,
 }
 
 pub(super) fn run_native_loop(
@@ -46,15 +47,20 @@ pub(super) fn run_native_loop(
     worker_rx: Receiver<WorkerCommand>,
     config: PulseAudioConfig,
 ) {
+    // Starred sinks outlive reconnects to the audio server.
+    let mut preferred_sinks = config.preferred_sinks.clone();
     loop {
         if broadcaster.subscriber_count() == 0 {
             return;
         }
-        match run_native_session(broadcaster, &worker_rx, &config) {
+        match run_native_session(broadcaster, &worker_rx, &config, &mut preferred_sinks) {
             Ok(()) => return,
             Err(err) => {
+                backend_errors::report(MODULE_TYPE, err.clone());
                 broadcaster.broadcast(UiUpdate {
                     label_text: escape_markup_text(&format!("audio error: {err}")),
+                    tooltip_text: String::new(),
+                    overamplified: false,
                     controls: None,
                 });
                 std::thread::sleep(Duration::from_secs(SESSION_RECONNECT_DELAY_SECS));
@@ -67,31 +73,18 @@ fn run_native_session(
     broadcaster: &Broadcaster<UiUpdate>,
     worker_rx: &Receiver<WorkerCommand>,
     config: &PulseAudioConfig,
+    preferred_sinks: &mut Vec<String>,
 ) -> Result<(), String> {
-    let mut proplist =
-        Proplist::new().ok_or_else(|| "failed to create pulseaudio proplist".to_string())?;
-    proplist
-        .set_str(properties::APPLICATION_NAME, "vibar")
-        .map_err(|err| format!("failed to set pulseaudio app name: {err:?}"))?;
+    let (mut mainloop, mut context) = connect_native_context("vibar-pulseaudio")?;
 
-    let mut mainloop =
-        Mainloop::new().ok_or_else(|| "failed to create pulseaudio mainloop".to_string())?;
-    let mut context = Context::new_with_proplist(&mainloop, "vibar-pulseaudio", &proplist)
-        .ok_or_else(|| "failed to create pulseaudio context".to_string())?;
-
-    context
-        .connect(None, ContextFlagSet::NOFLAGS, None)
-        .map_err(|err| format!("failed to connect pulseaudio context: {err:?}"))?;
-
-    wait_for_context_ready(&mut mainloop, &context)?;
-
-    let dirty = Arc::new(AtomicBool::new(true));
+    let pending = Arc::new(Mutex::new(PendingChanges::everything()));
     context.set_subscribe_callback(Some(Box::new({
-        let dirty = Arc::clone(&dirty);
-        move |facility, operation, _| {
-            if is_relevant_pulse_event(facility, operation) {
-                dirty.store(true, Ordering::SeqCst);
-            }
+        let pending = Arc::clone(&pending);
+        move |facility, operation, index| {
+            pending
+                .lock()
+                .expect("pulseaudio pending changes mutex poisoned")
+                .record(facility, operation, index);
         }
     })));
 
@@ -104,8 +97,13 @@ fn run_native_session(
         |_| {},
     );
     wait_for_operation(&mut mainloop, &mut subscribe_op)?;
+    backend_errors::clear(MODULE_TYPE);
 
+    let mut cache: Option<PulseCache> = None;
+    let mut last_update: Option<UiUpdate> = None;
     let mut last_defaults: Option<ServerDefaults> = None;
+    let mut last_sinks: Vec<SinkDeviceEntry> = Vec::new();
+    let mut previously_available: Option<HashSet<String>> = None;
 
     loop {
         loop {
@@ -118,15 +116,14 @@ fn run_native_session(
                             &defaults.sink_name,
                             step,
                             increase,
+                            config.max_volume,
                         );
                     }
-                    dirty.store(true, Ordering::SeqCst);
                 }
                 Ok(WorkerCommand::SetSinkMute { muted }) => {
                     if let Some(defaults) = last_defaults.as_ref() {
                         let _ = set_sink_mute(&context, &mut mainloop, &defaults.sink_name, muted);
                     }
-                    dirty.store(true, Ordering::SeqCst);
                 }
                 Ok(WorkerCommand::SetSinkVolumePercent { percent }) => {
                     if let Some(defaults) = last_defaults.as_ref() {
@@ -134,29 +131,41 @@ fn run_native_session(
                             &context,
                             &mut mainloop,
                             &defaults.sink_name,
-                            percent,
+                            percent.min(config.max_volume),
                         );
                     }
-                    dirty.store(true, Ordering::SeqCst);
                 }
                 Ok(WorkerCommand::SetSinkInputMute { index, muted }) => {
                     let _ = set_sink_input_mute(&context, &mut mainloop, index, muted);
-                    dirty.store(true, Ordering::SeqCst);
                 }
                 Ok(WorkerCommand::SetSinkInputVolumePercent { index, percent }) => {
-                    let _ = set_sink_input_volume_percent(&context, &mut mainloop, index, percent);
-                    dirty.store(true, Ordering::SeqCst);
+                    let _ = set_sink_input_volume_percent(
+                        &context,
+                        &mut mainloop,
+                        index,
+                        percent.min(config.max_volume),
+                    );
                 }
                 Ok(WorkerCommand::SetDefaultSink { sink_name }) => {
                     let _ = set_default_sink(&mut context, &mut mainloop, &sink_name);
-                    dirty.store(true, Ordering::SeqCst);
                 }
                 Ok(WorkerCommand::SetSinkPort {
                     sink_name,
                     port_name,
                 }) => {
                     let _ = set_sink_port(&context, &mut mainloop, &sink_name, &port_name);
-                    dirty.store(true, Ordering::SeqCst);
+                }
+                Ok(WorkerCommand::TogglePreferredSink { sink_name }) => {
+                    let description = last_sinks
+                        .iter()
+                        .find(|sink| sink.name == sink_name)
+                        .map(|sink| sink.description.as_str())
+                        .unwrap_or(sink_name.as_str());
+                    toggle_preferred_sink(preferred_sinks, &sink_name, description);
+                    pending
+                        .lock()
+                        .expect("pulseaudio pending changes mutex poisoned")
+                        .rebroadcast = true;
                 }
                 Err(TryRecvError::Empty) => break,
                 Err(TryRecvError::Disconnected) => {
@@ -166,21 +175,77 @@ fn run_native_session(
             }
         }
 
-        if dirty.swap(false, Ordering::SeqCst) {
-            match query_current_state(&context, &mut mainloop) {
-                Ok((state, defaults, controls_state)) => {
+        // Commands need no refresh of their own; the server reports what
+        // they changed like any other event.
+        let changes = std::mem::take(
+            &mut *pending
+                .lock()
+                .expect("pulseaudio pending changes mutex poisoned"),
+        );
+        if !changes.is_empty() {
+            let update = match refresh_current_state(&context, &mut mainloop, &mut cache, &changes)
+            {
+                Ok((state, defaults, mut controls_state)) => {
+                    for sink in &mut controls_state.sinks {
+                        sink.preferred =
+                            preferred_rank(preferred_sinks, &sink.name, &sink.description)
+                                .is_some();
+                    }
+                    if config.auto_switch {
+                        let target = previously_available.as_ref().and_then(|available| {
+                            auto_switch_target(
+                                preferred_sinks,
+                                &controls_state.sinks,
+                                available,
+                                &defaults.sink_name,
+                            )
+                        });
+                        if let Some(target) = target {
+                            let target = target.to_string();
+                            let _ = set_default_sink(&mut context, &mut mainloop, &target);
+                        }
+                    }
+                    previously_available = Some(
+                        controls_state
+                            .sinks
+                            .iter()
+                            .filter(|sink| sink.available)
+                            .map(|sink| sink.name.clone())
+                            .collect(),
+                    );
+                    last_sinks = controls_state.sinks.clone();
+
+                    let previous_sink = last_defaults.as_ref().map(|d| d.sink_name.as_str());
+                    if default_sink_changed(previous_sink, &defaults.sink_name) {
+                        events::emit(events::SINK_CHANGED, defaults.sink_name.clone());
+                        if let Some(command) = config.on_sink_change.as_deref() {
+                            run_sink_change_hook(
+                                command,
+                                previous_sink.unwrap_or_default(),
+                                &controls_state,
+                            );
+                        }
+                    }
                     last_defaults = Some(defaults);
-                    broadcaster.broadcast(UiUpdate {
+                    UiUpdate {
                         label_text: render_format(config, &state),
+                        tooltip_text: render_tooltip_format(config, &state),
+                        overamplified: is_overamplified(state.volume),
                         controls: Some(controls_state),
-                    });
-                }
-                Err(err) => {
-                    broadcaster.broadcast(UiUpdate {
-                        label_text: escape_markup_text(&format!("audio error: {err}")),
-                        controls: None,
-                    });
+                    }
                 }
+                Err(err) => UiUpdate {
+                    label_text: escape_markup_text(&format!("audio error: {err}")),
+                    tooltip_text: String::new(),
+                    overamplified: false,
+                    controls: None,
+                },
+            };
+            // Events often change nothing shown, e.g. another stream's
+            // volume; skipping those keeps the popover from rebuilding.
+            if last_update.as_ref() != Some(&update) {
+                broadcaster.broadcast(update.clone());
+                last_update = Some(update);
             }
         }
 
@@ -211,6 +276,27 @@ fn run_native_session(
     }
 }
 
+/// Creates a mainloop + context pair and blocks until the context is ready.
+pub(super) fn connect_native_context(context_name: &str) -> Result<(Mainloop, Context), String> {
+    let mut proplist =
+        Proplist::new().ok_or_else(|| "failed to create pulseaudio proplist".to_string())?;
+    proplist
+        .set_str(properties::APPLICATION_NAME, "vibar")
+        .map_err(|err| format!("failed to set pulseaudio app name: {err:?}"))?;
+
+    let mut mainloop =
+        Mainloop::new().ok_or_else(|| "failed to create pulseaudio mainloop".to_string())?;
+    let mut context = Context::new_with_proplist(&mainloop, context_name, &proplist)
+        .ok_or_else(|| "failed to create pulseaudio context".to_string())?;
+
+    context
+        .connect(None, ContextFlagSet::NOFLAGS, None)
+        .map_err(|err| format!("failed to connect pulseaudio context: {err:?}"))?;
+
+    wait_for_context_ready(&mut mainloop, &context)?;
+    Ok((mainloop, context))
+}
+
 fn wait_for_context_ready(mainloop: &mut Mainloop, context: &Context) -> Result<(), String> {
     loop {
         match context.get_state() {
@@ -229,7 +315,7 @@ fn wait_for_context_ready(mainloop: &mut Mainloop, context: &Context) -> Result<
     }
 }
 
-fn wait_for_operation<ClosureProto: ?Sized>(
+pub(super) fn wait_for_operation<ClosureProto: ?Sized>(
     mainloop: &mut Mainloop,
     operation: &mut pulse::operation::Operation<ClosureProto>,
 ) -> Result<(), String> {
@@ -252,6 +338,74 @@ fn iterate_mainloop_blocking(mainloop: &mut Mainloop) -> Result<(), String> {
     }
 }
 
+/// Returns whether the default sink switched since the previous query.
+///
+/// The first successful query after (re)connecting has no previous sink and
+/// never counts as a change, so the hook does not fire on startup.
+pub(super) fn default_sink_changed(previous: Option<&str>, current: &str) -> bool {
+    previous.is_some_and(|previous| previous != current)
+}
+
+/// Position of a sink in the preferred list, matched by name or description.
+pub(super) fn preferred_rank(preferred: &[String], name: &str, description: &str) -> Option<usize> {
+    preferred
+        .iter()
+        .position(|entry| entry == name || entry == description)
+}
+
+/// Removes the sink from the preferred list, or appends it (lowest priority)
+/// when it is not listed yet.
+pub(super) fn toggle_preferred_sink(preferred: &mut Vec<String>, name: &str, description: &str) {
+    let before = preferred.len();
+    preferred.retain(|entry| entry != name && entry != description);
+    if preferred.len() == before {
+        preferred.push(name.to_string());
+    }
+}
+
+/// Returns the sink to switch to: the highest-priority available preferred
+/// sink, but only when it just became available and is not already default.
+///
+/// Requiring a fresh arrival keeps a manual switch away from a still-connected
+/// favorite in place.
+pub(super) fn auto_switch_target<'a>(
+    preferred: &[String],
+    sinks: &'a [SinkDeviceEntry],
+    previously_available: &HashSet<String>,
+    current_default: &str,
+) -> Option<&'a str> {
+    let (_, best) = sinks
+        .iter()
+        .filter(|sink| sink.available)
+        .filter_map(|sink| {
+            preferred_rank(preferred, &sink.name, &sink.description).map(|rank| (rank, sink))
+        })
+        .min_by_key(|(rank, _)| *rank)?;
+
+    (best.name != current_default && !previously_available.contains(&best.name))
+        .then_some(best.name.as_str())
+}
+
+fn run_sink_change_hook(command: &str, previous_sink: &str, controls: &AudioControlsState) {
+    let description = controls
+        .sinks
+        .iter()
+        .find(|sink| sink.name == controls.sink_name)
+        .map(|sink| sink.description.as_str())
+        .unwrap_or(controls.sink_name.as_str());
+
+    if let Err(err) = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("VIBAR_SINK_NAME", &controls.sink_name)
+        .env("VIBAR_SINK_DESCRIPTION", description)
+        .env("VIBAR_PREVIOUS_SINK_NAME", previous_sink)
+        .spawn()
+    {
+        eprintln!("pulseaudio: failed to run on-sink-change command '{command}': {err}");
+    }
+}
+
 pub(super) fn is_relevant_pulse_event(
     facility: Option<Facility>,
     operation: Option<pulse::context::subscribe::Operation>,
@@ -268,43 +422,89 @@ pub(super) fn is_relevant_pulse_event(
     relevant_facility && relevant_operation
 }
 
-fn query_current_state(
+/// Updates `cache` for `changes` and returns the resulting state. A failed
+/// refresh drops the cache, so the next event re-queries everything.
+fn refresh_current_state(
     context: &Context,
     mainloop: &mut Mainloop,
+    cache: &mut Option<PulseCache>,
+    changes: &PendingChanges,
 ) -> Result<(PulseState, ServerDefaults, AudioControlsState), String> {
+    let result = refresh_cache(context, mainloop, cache, changes).and_then(|cache| {
+        let (state, controls) = cache.current_state()?;
+        Ok((state, cache.defaults.clone(), controls))
+    });
+    if result.is_err() {
+        *cache = None;
+    }
+    result
+}
+
+/// Re-queries only the entities named in `changes`, or everything when
+/// there is no cache yet.
+fn refresh_cache<'a>(
+    context: &Context,
+    mainloop: &mut Mainloop,
+    cache: &'a mut Option<PulseCache>,
+    changes: &PendingChanges,
+) -> Result<&'a PulseCache, String> {
+    if changes.full || cache.is_none() {
+        return Ok(cache.insert(query_full_cache(context, mainloop)?));
+    }
+    let cache = cache.as_mut().expect("pulseaudio cache is present");
+
+    if changes.server {
+        cache.defaults = query_server_defaults(context, mainloop)?;
+    }
+    if changes.all_sinks {
+        cache.sinks = query_sinks(context, mainloop)?;
+    } else {
+        for (&index, &change) in &changes.sinks {
+            let sink = match change {
+                EntityChange::Updated => query_sink(context, mainloop, index)?,
+                EntityChange::Removed => None,
+            };
+            cache.apply_sink(index, sink);
+        }
+    }
+    for (&index, &change) in &changes.sink_inputs {
+        let input = match change {
+            EntityChange::Updated => query_sink_input(context, mainloop, index)?,
+            EntityChange::Removed => None,
+        };
+        cache.apply_sink_input(index, input);
+    }
+    if changes.server || changes.source {
+        cache.source_muted = query_default_source_muted(context, mainloop, &cache.defaults)?;
+    }
+    Ok(cache)
+}
+
+fn query_full_cache(context: &Context, mainloop: &mut Mainloop) -> Result<PulseCache, String> {
     let defaults = query_server_defaults(context, mainloop)?;
-    let sinks = query_sinks(context, mainloop, &defaults.sink_name)?;
-    let sink_info = query_sink_info(context, mainloop, &defaults.sink_name)?;
+    let sinks = query_sinks(context, mainloop)?;
     let sink_inputs = query_sink_inputs(context, mainloop)?;
+    let source_muted = query_default_source_muted(context, mainloop, &defaults)?;
+    Ok(PulseCache {
+        defaults,
+        sinks,
+        sink_inputs,
+        source_muted,
+    })
+}
 
-    let source_muted = match defaults.source_name.as_ref() {
-        Some(source_name) => query_source_muted(context, mainloop, source_name)?,
-        None => false,
-    };
+fn query_default_source_muted(
+    context: &Context,
+    mainloop: &mut Mainloop,
+    defaults: &ServerDefaults,
+) -> Result<bool, String> {
+    match defaults.source_name.as_ref() {
+        Some(source_name) => query_source_muted(context, mainloop, source_name),
+        None => Ok(false),
+    }
+}
 
-    Ok((
-        PulseState {
-            volume: sink_info.volume,
-            muted: sink_info.muted,
-            source_muted,
-            bluetooth: sink_info.bluetooth,
-            icon_kind: sink_info.icon_kind,
-        },
-        defaults.clone(),
-        AudioControlsState {
-            sink_name: defaults.sink_name.clone(),
-            sinks,
-            selected_sink_name: defaults.sink_name.clone(),
-            sink_volume: sink_info.volume,
-            sink_muted: sink_info.muted,
-            sink_ports: sink_info.ports,
-            active_sink_port: sink_info.active_port_name,
-            sink_inputs,
-        },
-    ))
-}
-
-fn query_server_defaults(
+pub(super) fn query_server_defaults(
     context: &Context,
     mainloop: &mut Mainloop,
 ) -> Result<ServerDefaults, String> {
@@ -371,7 +571,7 @@ fn query_sink_info(
     result
 }
 
-fn query_source_muted(
+pub(super) fn query_source_muted(
     context: &Context,
     mainloop: &mut Mainloop,
     source_name: &str,
@@ -458,28 +658,27 @@ fn sink_ports_from_info(info: &SinkInfo) -> Vec<SinkPortEntry> {
 fn query_sinks(
     context: &Context,
     mainloop: &mut Mainloop,
-    default_sink_name: &str,
-) -> Result<Vec<SinkDeviceEntry>, String> {
-    let slot = Arc::new(Mutex::new(None::<Result<Vec<SinkDeviceEntry>, String>>));
-    let items = Arc::new(Mutex::new(Vec::<SinkDeviceEntry>::new()));
+) -> Result<BTreeMap<u32, CachedSink>, String> {
+    let slot = Arc::new(Mutex::new(
+        None::<Result<BTreeMap<u32, CachedSink>, String>>,
+    ));
+    let items = Arc::new(Mutex::new(BTreeMap::<u32, CachedSink>::new()));
     let mut op = context.introspect().get_sink_info_list({
         let slot = Arc::clone(&slot);
         let items = Arc::clone(&items);
-        let default_sink_name = default_sink_name.to_string();
         move |result| match result {
             ListResult::Item(info) => {
-                if let Some(snapshot) = sink_device_from_info(info, &default_sink_name) {
+                if let Some(sink) = cached_sink_from_info(info) {
                     items
                         .lock()
                         .expect("sink list mutex poisoned")
-                        .push(snapshot);
+                        .insert(info.index, sink);
                 }
             }
             ListResult::End => {
                 let mut guard = slot.lock().expect("sink list result mutex poisoned");
                 if guard.is_none() {
-                    let mut values = items.lock().expect("sink list mutex poisoned").clone();
-                    values.sort_by(|a, b| a.description.cmp(&b.description));
+                    let values = items.lock().expect("sink list mutex poisoned").clone();
                     *guard = Some(Ok(values));
                 }
             }
@@ -498,7 +697,51 @@ fn query_sinks(
     result
 }
 
-fn sink_device_from_info(info: &SinkInfo, default_sink_name: &str) -> Option<SinkDeviceEntry> {
+/// One sink by index; `Ok(None)` when it is already gone.
+fn query_sink(
+    context: &Context,
+    mainloop: &mut Mainloop,
+    index: u32,
+) -> Result<Option<CachedSink>, String> {
+    let slot = Arc::new(Mutex::new(None::<Result<Option<CachedSink>, String>>));
+    let mut op = context.introspect().get_sink_info_by_index(index, {
+        let slot = Arc::clone(&slot);
+        move |result| {
+            let mut guard = slot.lock().expect("sink info mutex poisoned");
+            match result {
+                ListResult::Item(info) => {
+                    *guard = Some(Ok(cached_sink_from_info(info)));
+                }
+                ListResult::End => {
+                    if guard.is_none() {
+                        *guard = Some(Ok(None));
+                    }
+                }
+                // The server also reports a missing index as an error.
+                ListResult::Error => {
+                    *guard = Some(Ok(None));
+                }
+            }
+        }
+    });
+    wait_for_operation(mainloop, &mut op)?;
+
+    let result = slot
+        .lock()
+        .expect("sink info mutex poisoned")
+        .clone()
+        .unwrap_or_else(|| Err("pulseaudio sink info query returned no data".to_string()));
+    result
+}
+
+fn cached_sink_from_info(info: &SinkInfo) -> Option<CachedSink> {
+    Some(CachedSink {
+        device: sink_device_from_info(info)?,
+        snapshot: snapshot_from_sink_info(info),
+    })
+}
+
+fn sink_device_from_info(info: &SinkInfo) -> Option<SinkDeviceEntry> {
     let name = info.name.as_ref().map(|value| value.to_string())?;
     let description = info
         .description
@@ -507,10 +750,11 @@ fn sink_device_from_info(info: &SinkInfo, default_sink_name: &str) -> Option<Sin
         .unwrap_or_else(|| name.clone());
     let available = sink_is_available(info);
     Some(SinkDeviceEntry {
-        is_default: name == default_sink_name,
         name,
         description,
         available,
+        is_default: false,
+        preferred: false,
     })
 }
 
@@ -526,9 +770,11 @@ fn sink_is_available(info: &SinkInfo) -> bool {
 fn query_sink_inputs(
     context: &Context,
     mainloop: &mut Mainloop,
-) -> Result<Vec<SinkInputEntry>, String> {
-    let slot = Arc::new(Mutex::new(None::<Result<Vec<SinkInputEntry>, String>>));
-    let items = Arc::new(Mutex::new(Vec::<SinkInputEntry>::new()));
+) -> Result<BTreeMap<u32, SinkInputEntry>, String> {
+    let slot = Arc::new(Mutex::new(
+        None::<Result<BTreeMap<u32, SinkInputEntry>, String>>,
+    ));
+    let items = Arc::new(Mutex::new(BTreeMap::<u32, SinkInputEntry>::new()));
     let mut op = context.introspect().get_sink_input_info_list({
         let slot = Arc::clone(&slot);
         let items = Arc::clone(&items);
@@ -538,17 +784,16 @@ fn query_sink_inputs(
                     items
                         .lock()
                         .expect("sink input list mutex poisoned")
-                        .push(snapshot);
+                        .insert(info.index, snapshot);
                 }
             }
             ListResult::End => {
                 let mut guard = slot.lock().expect("sink input result mutex poisoned");
                 if guard.is_none() {
-                    let mut values = items
+                    let values = items
                         .lock()
                         .expect("sink input list mutex poisoned")
                         .clone();
-                    values.sort_by(|a, b| a.name.cmp(&b.name));
                     *guard = Some(Ok(values));
                 }
             }
@@ -567,6 +812,43 @@ fn query_sink_inputs(
     result
 }
 
+/// One sink input by index; `Ok(None)` when it is gone or has no volume.
+fn query_sink_input(
+    context: &Context,
+    mainloop: &mut Mainloop,
+    index: u32,
+) -> Result<Option<SinkInputEntry>, String> {
+    let slot = Arc::new(Mutex::new(None::<Result<Option<SinkInputEntry>, String>>));
+    let mut op = context.introspect().get_sink_input_info(index, {
+        let slot = Arc::clone(&slot);
+        move |result| {
+            let mut guard = slot.lock().expect("sink input info mutex poisoned");
+            match result {
+                ListResult::Item(info) => {
+                    *guard = Some(Ok(sink_input_from_info(info)));
+                }
+                ListResult::End => {
+                    if guard.is_none() {
+                        *guard = Some(Ok(None));
+                    }
+                }
+                // The server also reports a missing index as an error.
+                ListResult::Error => {
+                    *guard = Some(Ok(None));
+                }
+            }
+        }
+    });
+    wait_for_operation(mainloop, &mut op)?;
+
+    let result = slot
+        .lock()
+        .expect("sink input info mutex poisoned")
+        .clone()
+        .unwrap_or_else(|| Err("pulseaudio sink input info query returned no data".to_string()));
+    result
+}
+
 fn sink_input_from_info(info: &SinkInputInfo) -> Option<SinkInputEntry> {
     if !info.has_volume {
         return None;
@@ -599,20 +881,21 @@ fn apply_volume_step(
     sink_name: &str,
     step: f64,
     increase: bool,
+    max_percent: u32,
 ) -> Result<(), String> {
     let sink_info = query_sink_info(context, mainloop, sink_name)?;
-    let mut current = sink_info.channels;
-
-    let delta = percent_to_volume_delta(step);
-    if increase {
-        let _ = current.increase(delta);
-    } else {
-        let _ = current.decrease(delta);
-    }
+    let stepped = stepped_volume(
+        sink_info.channels,
+        percent_to_volume_delta(step),
+        increase,
+        percent_to_volume_absolute(max_percent),
+    );
 
     let mut introspector = context.introspect();
-    let mut op = introspector.set_sink_volume_by_name(sink_name, &current, None);
-    wait_for_operation(mainloop, &mut op)?;
+    if let Some(volumes) = stepped {
+        let mut op = introspector.set_sink_volume_by_name(sink_name, &volumes, None);
+        wait_for_operation(mainloop, &mut op)?;
+    }
 
     if sink_info.muted {
         let mut mute_op = introspector.set_sink_mute_by_name(sink_name, false, None);
@@ -622,6 +905,26 @@ fn apply_volume_step(
     Ok(())
 }
 
+/// `current` moved by one scroll step. `None` when an increase starts at or
+/// above `limit` (for example after another client raised the volume), so a
+/// scroll up never lowers the volume down to the limit.
+pub(super) fn stepped_volume(
+    mut current: pulse::volume::ChannelVolumes,
+    delta: Volume,
+    increase: bool,
+    limit: Volume,
+) -> Option<pulse::volume::ChannelVolumes> {
+    if increase {
+        if current.max() >= limit {
+            return None;
+        }
+        let _ = current.inc_clamp(delta, limit);
+    } else {
+        let _ = current.decrease(delta);
+    }
+    Some(current)
+}
+
 fn query_sink_channel_volumes(
     context: &Context,
     mainloop: &mut Mainloop,
@@ -775,7 +1078,7 @@ pub(super) fn percent_to_volume_delta(step: f64) -> Volume {
 }
 
 fn percent_to_volume_absolute(percent: u32) -> Volume {
-    let bounded = percent.min(150);
+    let bounded = percent.min(MAX_VOLUME_LIMIT);
     let raw = ((bounded as f64 / 100.0) * f64::from(Volume::NORMAL.0)).round() as u32;
     Volume(raw)
 }
@@ -1,34 +1,55 @@
 use serde::Deserialize;
 use serde_json::Value;
 
+use crate::modules::icons::Glyph;
 use crate::modules::ModuleConfig;
 
 use super::MODULE_TYPE;
 
 pub(super) const DEFAULT_SCROLL_STEP: f64 = 1.0;
+pub(super) const DEFAULT_MAX_VOLUME: u32 = 100;
+pub(super) const MAX_VOLUME_LIMIT: u32 = 150;
 pub(super) const DEFAULT_FORMAT: &str = "{volume}% {icon}  {format_source}";
-pub(super) const DEFAULT_FORMAT_BLUETOOTH: &str = "{volume}% {icon} {format_source}";
-pub(super) const DEFAULT_FORMAT_BLUETOOTH_MUTED: &str = " {icon} {format_source}";
-pub(super) const DEFAULT_FORMAT_MUTED: &str = " {format_source}";
-pub(super) const DEFAULT_FORMAT_SOURCE: &str = "";
-pub(super) const DEFAULT_FORMAT_SOURCE_MUTED: &str = "";
+pub(super) const DEFAULT_FORMAT_BLUETOOTH: Glyph = Glyph::new(
+    "{volume}% {icon} {format_source}",
+    "{volume}% {icon} ᛒ {format_source}",
+);
+pub(super) const DEFAULT_FORMAT_BLUETOOTH_MUTED: Glyph =
+    Glyph::new(" {icon} {format_source}", "🔇 {icon} ᛒ {format_source}");
+pub(super) const DEFAULT_FORMAT_MUTED: Glyph =
+    Glyph::new(" {format_source}", "🔇 {format_source}");
+pub(super) const DEFAULT_FORMAT_SOURCE: Glyph = Glyph::new("", "🎤");
+pub(super) const DEFAULT_FORMAT_SOURCE_MUTED: Glyph = Glyph::new("", "");
 pub(super) const DEFAULT_CONTROLS_ENABLED: bool = false;
-pub(super) const ICON_VOLUME_LOW: &str = "";
-pub(super) const ICON_VOLUME_MEDIUM: &str = "";
-pub(super) const ICON_VOLUME_HIGH: &str = "";
-pub(super) const ICON_HEADPHONE: &str = "";
-pub(super) const ICON_HANDS_FREE: &str = "";
-pub(super) const ICON_HEADSET: &str = "";
-pub(super) const ICON_PHONE: &str = "";
-pub(super) const ICON_PORTABLE: &str = "";
-pub(super) const ICON_CAR: &str = "";
+/// From the freedesktop sound theme, installed on most desktops.
+pub(super) const DEFAULT_TEST_SOUND: &str =
+    "/usr/share/sounds/freedesktop/stereo/audio-test-signal.oga";
+pub(super) const ICON_VOLUME_LOW: Glyph = Glyph::new("", "🔈");
+pub(super) const ICON_VOLUME_MEDIUM: Glyph = Glyph::new("", "🔉");
+pub(super) const ICON_VOLUME_HIGH: Glyph = Glyph::new("", "🔊");
+pub(super) const ICON_HEADPHONE: Glyph = Glyph::new("", "🎧");
+pub(super) const ICON_HANDS_FREE: Glyph = Glyph::new("", "🎧");
+pub(super) const ICON_HEADSET: Glyph = Glyph::new("", "🎧");
+pub(super) const ICON_PHONE: Glyph = Glyph::new("", "📱");
+pub(super) const ICON_PORTABLE: Glyph = Glyph::new("", "📱");
+pub(super) const ICON_CAR: Glyph = Glyph::new("", "🚗");
+pub(super) const ICON_TEST_SOUND: Glyph = Glyph::new("󰝚", "♪");
 
 #[derive(Debug, Deserialize, Clone)]
 pub(crate) struct PulseAudioConfig {
     #[serde(rename = "scroll-step", default = "default_scroll_step")]
     pub(crate) scroll_step: f64,
+    #[serde(
+        rename = "max-volume",
+        alias = "max_volume",
+        default = "default_max_volume"
+    )]
+    pub(crate) max_volume: u32,
     #[serde(default)]
     pub(crate) format: Option<String>,
+    /// Set from the module's `tooltip-format`, not read from this config.
+    #[serde(skip)]
+    pub(crate) tooltip_format: Option<String>,
     #[serde(rename = "format-bluetooth", default)]
     pub(crate) format_bluetooth: Option<String>,
     #[serde(rename = "format-bluetooth-muted", default)]
@@ -49,6 +70,12 @@ pub(crate) struct PulseAudioConfig {
     pub(crate) right_click: Option<String>,
     #[serde(rename = "on-right-click", default)]
     pub(crate) on_right_click: Option<String>,
+    #[serde(rename = "on-sink-change", alias = "on_sink_change", default)]
+    pub(crate) on_sink_change: Option<String>,
+    #[serde(rename = "preferred-sinks", alias = "preferred_sinks", default)]
+    pub(crate) preferred_sinks: Vec<String>,
+    #[serde(rename = "auto-switch", alias = "auto_switch", default)]
+    pub(crate) auto_switch: bool,
     #[serde(default)]
     pub(crate) controls: PulseAudioControlsConfig,
     #[serde(default)]
@@ -61,6 +88,19 @@ pub(crate) struct PulseAudioControlsConfig {
     pub(crate) enabled: bool,
     #[serde(default)]
     pub(crate) open: PulseAudioControlsOpenMode,
+    #[serde(rename = "test-sound", alias = "test_sound", default)]
+    pub(crate) test_sound: Option<String>,
+}
+
+impl PulseAudioControlsConfig {
+    /// Sound file for the per-device test buttons; `""` hides them.
+    pub(crate) fn test_sound_path(&self) -> Option<&str> {
+        match self.test_sound.as_deref() {
+            None => Some(DEFAULT_TEST_SOUND),
+            Some("") => None,
+            Some(path) => Some(path),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
@@ -111,15 +151,19 @@ fn default_scroll_step() -> f64 {
     DEFAULT_SCROLL_STEP
 }
 
+fn default_max_volume() -> u32 {
+    DEFAULT_MAX_VOLUME
+}
+
 fn default_controls_enabled() -> bool {
     DEFAULT_CONTROLS_ENABLED
 }
 
 fn default_volume_icons() -> Vec<String> {
     vec![
-        ICON_VOLUME_LOW.to_string(),
-        ICON_VOLUME_MEDIUM.to_string(),
-        ICON_VOLUME_HIGH.to_string(),
+        ICON_VOLUME_LOW.get().to_string(),
+        ICON_VOLUME_MEDIUM.get().to_string(),
+        ICON_VOLUME_HIGH.get().to_string(),
     ]
 }
 
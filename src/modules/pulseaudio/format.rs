@@ -60,28 +60,44 @@ impl PulseAudioFormatIcons {
             IconKind::Headphone => self
                 .headphone
                 .as_deref()
-                .unwrap_or(ICON_HEADPHONE)
+                .unwrap_or(ICON_HEADPHONE.get())
                 .to_string(),
             IconKind::Speaker => self
                 .speaker
                 .as_deref()
-                .unwrap_or(ICON_VOLUME_HIGH)
+                .unwrap_or(ICON_VOLUME_HIGH.get())
+                .to_string(),
+            IconKind::Hdmi => self
+                .hdmi
+                .as_deref()
+                .unwrap_or(ICON_VOLUME_HIGH.get())
                 .to_string(),
-            IconKind::Hdmi => self.hdmi.as_deref().unwrap_or(ICON_VOLUME_HIGH).to_string(),
             IconKind::HandsFree => self
                 .hands_free
                 .as_deref()
-                .unwrap_or(ICON_HANDS_FREE)
+                .unwrap_or(ICON_HANDS_FREE.get())
+                .to_string(),
+            IconKind::Headset => self
+                .headset
+                .as_deref()
+                .unwrap_or(ICON_HEADSET.get())
+                .to_string(),
+            IconKind::Phone => self
+                .phone
+                .as_deref()
+                .unwrap_or(ICON_PHONE.get())
                 .to_string(),
-            IconKind::Headset => self.headset.as_deref().unwrap_or(ICON_HEADSET).to_string(),
-            IconKind::Phone => self.phone.as_deref().unwrap_or(ICON_PHONE).to_string(),
             IconKind::Portable => self
                 .portable
                 .as_deref()
-                .unwrap_or(ICON_PORTABLE)
+                .unwrap_or(ICON_PORTABLE.get())
+                .to_string(),
+            IconKind::Car => self.car.as_deref().unwrap_or(ICON_CAR.get()).to_string(),
+            IconKind::Hifi => self
+                .hifi
+                .as_deref()
+                .unwrap_or(ICON_VOLUME_HIGH.get())
                 .to_string(),
-            IconKind::Car => self.car.as_deref().unwrap_or(ICON_CAR).to_string(),
-            IconKind::Hifi => self.hifi.as_deref().unwrap_or(ICON_VOLUME_HIGH).to_string(),
             IconKind::Default => volume_icon_from_list(&self.default, volume),
         }
     }
@@ -90,11 +106,11 @@ impl PulseAudioFormatIcons {
 pub(super) fn volume_icon_from_list(icons: &[String], volume: u32) -> String {
     if icons.is_empty() {
         return if volume == 0 {
-            ICON_VOLUME_LOW.to_string()
+            ICON_VOLUME_LOW.get().to_string()
         } else if volume < 67 {
-            ICON_VOLUME_MEDIUM.to_string()
+            ICON_VOLUME_MEDIUM.get().to_string()
         } else {
-            ICON_VOLUME_HIGH.to_string()
+            ICON_VOLUME_HIGH.get().to_string()
         };
     }
 
@@ -0,0 +1,447 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use gtk::prelude::*;
+use gtk::{DrawingArea, Widget};
+use libpulse_binding as pulse;
+use pulse::context::subscribe::{Facility, InterestMaskSet};
+use pulse::context::{Context, State as ContextState};
+use pulse::def::BufferAttr;
+use pulse::mainloop::standard::IterateResult;
+use pulse::sample::{Format, Spec};
+use pulse::stream::{FlagSet as StreamFlagSet, PeekResult, State as StreamState, Stream};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::scale::snap_to_device_px;
+use crate::modules::{
+    apply_css_classes, attach_primary_click_command, ModuleBuildContext, ModuleConfig,
+    ModuleFactory,
+};
+
+use super::backend::{
+    connect_native_context, query_server_defaults, query_source_muted, wait_for_operation,
+};
+use super::{MAINLOOP_IDLE_SLEEP_MILLIS, SESSION_RECONNECT_DELAY_SECS};
+
+const DEFAULT_METER_WIDTH: i32 = 40;
+const DEFAULT_METER_HEIGHT: i32 = 8;
+const MIN_METER_SIZE: i32 = 2;
+/// With `PEAK_DETECT` the server delivers one peak value per sample, so the
+/// sample rate doubles as the meter refresh rate.
+const METER_SAMPLE_RATE: u32 = 25;
+const METER_TRACK_ALPHA: f64 = 0.25;
+pub(crate) const MODULE_TYPE: &str = "pulseaudio/mic-meter";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct MicMeterConfig {
+    #[serde(default)]
+    pub(crate) source: Option<String>,
+    #[serde(default = "default_meter_width")]
+    pub(crate) width: i32,
+    #[serde(default = "default_meter_height")]
+    pub(crate) height: i32,
+    #[serde(
+        rename = "hide-when-muted",
+        alias = "hide_when_muted",
+        default = "default_hide_when_muted"
+    )]
+    pub(crate) hide_when_muted: bool,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MicMeterUpdate {
+    level: f32,
+    active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MicMeterSharedKey {
+    source: Option<String>,
+}
+
+pub(crate) struct MicMeterFactory;
+
+pub(crate) const FACTORY: MicMeterFactory = MicMeterFactory;
+
+impl ModuleFactory for MicMeterFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_mic_meter_module(parsed, context.scale()).upcast())
+    }
+}
+
+fn default_meter_width() -> i32 {
+    DEFAULT_METER_WIDTH
+}
+
+fn default_meter_height() -> i32 {
+    DEFAULT_METER_HEIGHT
+}
+
+fn default_hide_when_muted() -> bool {
+    true
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<MicMeterConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn mic_meter_registry() -> &'static BackendRegistry<MicMeterSharedKey, Broadcaster<MicMeterUpdate>>
+{
+    static REGISTRY: OnceLock<BackendRegistry<MicMeterSharedKey, Broadcaster<MicMeterUpdate>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_mic_meter(source: Option<String>) -> Subscription<MicMeterUpdate> {
+    let key = MicMeterSharedKey { source };
+
+    let (broadcaster, start_worker) =
+        mic_meter_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_mic_meter_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_mic_meter_worker(key: MicMeterSharedKey, broadcaster: Arc<Broadcaster<MicMeterUpdate>>) {
+    std::thread::spawn(move || {
+        loop {
+            if broadcaster.subscriber_count() == 0 {
+                break;
+            }
+            match run_meter_session(&broadcaster, key.source.as_deref()) {
+                Ok(()) => break,
+                Err(_) => {
+                    broadcaster.broadcast(MicMeterUpdate {
+                        level: 0.0,
+                        active: false,
+                    });
+                    std::thread::sleep(Duration::from_secs(SESSION_RECONNECT_DELAY_SECS));
+                }
+            }
+        }
+        mic_meter_registry().remove(&key, &broadcaster);
+    });
+}
+
+fn run_meter_session(
+    broadcaster: &Broadcaster<MicMeterUpdate>,
+    configured_source: Option<&str>,
+) -> Result<(), String> {
+    let (mut mainloop, mut context) = connect_native_context("vibar-mic-meter")?;
+
+    let dirty = Arc::new(AtomicBool::new(true));
+    context.set_subscribe_callback(Some(Box::new({
+        let dirty = Arc::clone(&dirty);
+        move |facility, operation, _| {
+            if matches!(facility, Some(Facility::Source | Facility::Server)) && operation.is_some()
+            {
+                dirty.store(true, Ordering::SeqCst);
+            }
+        }
+    })));
+
+    let mut subscribe_op =
+        context.subscribe(InterestMaskSet::SOURCE | InterestMaskSet::SERVER, |_| {});
+    wait_for_operation(&mut mainloop, &mut subscribe_op)?;
+
+    let mut stream: Option<Stream> = None;
+    let mut stream_source: Option<String> = None;
+
+    loop {
+        if dirty.swap(false, Ordering::SeqCst) {
+            let source_name = match configured_source {
+                Some(name) => Some(name.to_string()),
+                None => query_server_defaults(&context, &mut mainloop)
+                    .ok()
+                    .and_then(|defaults| defaults.source_name),
+            };
+            let muted = match source_name.as_deref() {
+                Some(name) => query_source_muted(&context, &mut mainloop, name).unwrap_or(true),
+                None => true,
+            };
+
+            if muted || source_name != stream_source {
+                if let Some(mut previous) = stream.take() {
+                    let _ = previous.disconnect();
+                }
+                stream_source = None;
+            }
+            if !muted && stream.is_none() {
+                if let Some(name) = source_name {
+                    stream = Some(open_peak_stream(
+                        &mut context,
+                        "vibar-mic-meter",
+                        &name,
+                        None,
+                    )?);
+                    stream_source = Some(name);
+                }
+            }
+
+            broadcaster.broadcast(MicMeterUpdate {
+                level: 0.0,
+                active: stream.is_some(),
+            });
+        }
+
+        if let Some(active) = stream.as_mut() {
+            match active.get_state() {
+                StreamState::Ready => {
+                    if let Some(level) = drain_peak(active)? {
+                        broadcaster.broadcast(MicMeterUpdate {
+                            level,
+                            active: true,
+                        });
+                    }
+                }
+                StreamState::Failed | StreamState::Terminated => {
+                    // Source vanished (for example an unplugged USB mic); re-resolve.
+                    stream = None;
+                    stream_source = None;
+                    dirty.store(true, Ordering::SeqCst);
+                }
+                _ => {}
+            }
+        }
+
+        match mainloop.iterate(false) {
+            IterateResult::Success(_) => {}
+            IterateResult::Quit(_) => return Err("pulseaudio mainloop quit".to_string()),
+            IterateResult::Err(err) => {
+                return Err(format!("pulseaudio mainloop iteration failed: {err:?}"));
+            }
+        }
+
+        match context.get_state() {
+            ContextState::Ready => {}
+            ContextState::Failed => {
+                return Err(format!("pulseaudio context failed: {:?}", context.errno()));
+            }
+            ContextState::Terminated => {
+                return Err("pulseaudio context terminated".to_string());
+            }
+            _ => {}
+        }
+
+        std::thread::sleep(Duration::from_millis(MAINLOOP_IDLE_SLEEP_MILLIS));
+
+        if broadcaster.subscriber_count() == 0 {
+            if let Some(mut active) = stream.take() {
+                let _ = active.disconnect();
+            }
+            return Ok(());
+        }
+    }
+}
+
+/// Opens a record stream that delivers one peak value per sample from
+/// `source_name`. With `monitor_stream` set, only that sink input is
+/// measured; `source_name` must then be the monitor of its sink.
+pub(super) fn open_peak_stream(
+    context: &mut Context,
+    stream_name: &str,
+    source_name: &str,
+    monitor_stream: Option<u32>,
+) -> Result<Stream, String> {
+    let spec = Spec {
+        format: Format::FLOAT32NE,
+        channels: 1,
+        rate: METER_SAMPLE_RATE,
+    };
+    let mut stream = Stream::new(context, stream_name, &spec, None)
+        .ok_or_else(|| "failed to create pulseaudio record stream".to_string())?;
+    if let Some(index) = monitor_stream {
+        stream
+            .set_monitor_stream(index)
+            .map_err(|err| format!("failed to monitor sink input {index}: {err:?}"))?;
+    }
+
+    let attr = BufferAttr {
+        maxlength: u32::MAX,
+        tlength: u32::MAX,
+        prebuf: u32::MAX,
+        minreq: u32::MAX,
+        fragsize: std::mem::size_of::<f32>() as u32,
+    };
+    stream
+        .connect_record(
+            Some(source_name),
+            Some(&attr),
+            StreamFlagSet::PEAK_DETECT | StreamFlagSet::ADJUST_LATENCY | StreamFlagSet::DONT_MOVE,
+        )
+        .map_err(|err| format!("failed to connect record stream to {source_name}: {err:?}"))?;
+
+    Ok(stream)
+}
+
+pub(super) fn drain_peak(stream: &mut Stream) -> Result<Option<f32>, String> {
+    let mut peak: Option<f32> = None;
+    loop {
+        let fragment = stream
+            .peek()
+            .map_err(|err| format!("failed to read peak level: {err:?}"))?;
+        match fragment {
+            PeekResult::Empty => return Ok(peak),
+            PeekResult::Hole(_) => {}
+            PeekResult::Data(data) => {
+                let value = peak_from_samples(data);
+                peak = Some(peak.map_or(value, |current| current.max(value)));
+            }
+        }
+        stream
+            .discard()
+            .map_err(|err| format!("failed to drop peak fragment: {err:?}"))?;
+    }
+}
+
+/// Returns the largest absolute native-endian `f32` sample, clamped to `0..=1`.
+fn peak_from_samples(data: &[u8]) -> f32 {
+    data.chunks_exact(std::mem::size_of::<f32>())
+        .map(|chunk| {
+            let bytes: [u8; 4] = chunk.try_into().expect("chunk size should match f32");
+            f32::from_ne_bytes(bytes).abs()
+        })
+        .filter(|value| value.is_finite())
+        .fold(0.0_f32, f32::max)
+        .min(1.0)
+}
+
+fn build_mic_meter_module(config: MicMeterConfig, scale: f64) -> DrawingArea {
+    let area = DrawingArea::new();
+    area.add_css_class("module");
+    area.add_css_class("pulseaudio-mic-meter");
+    apply_css_classes(&area, config.class.as_deref());
+    attach_primary_click_command(&area, config.click.or(config.on_click));
+
+    area.set_content_width(config.width.max(MIN_METER_SIZE));
+    area.set_content_height(config.height.max(MIN_METER_SIZE));
+    area.set_valign(gtk::Align::Center);
+    area.set_focusable(false);
+
+    let level = Rc::new(Cell::new(0.0_f32));
+    area.set_draw_func({
+        let level = Rc::clone(&level);
+        move |area, context, width, height| {
+            draw_level_bar(area, context, width, height, level.get(), scale);
+        }
+    });
+
+    let subscription = subscribe_shared_mic_meter(config.source);
+    let hide_when_muted = config.hide_when_muted;
+
+    attach_subscription(&area, subscription, move |area, update| {
+        area.set_visible(update.active || !hide_when_muted);
+        if update.active {
+            area.remove_css_class("muted");
+        } else {
+            area.add_css_class("muted");
+        }
+        if (level.get() - update.level).abs() > f32::EPSILON {
+            level.set(update.level);
+            area.queue_draw();
+        }
+    });
+
+    area
+}
+
+/// Paints a horizontal level bar: a faint track in the widget's CSS color
+/// with the filled part (`level` in `0..=1`) at full opacity.
+pub(super) fn draw_level_bar(
+    area: &DrawingArea,
+    context: &gtk::cairo::Context,
+    width: i32,
+    height: i32,
+    level: f32,
+    scale: f64,
+) {
+    let color = area.color();
+    let (red, green, blue, alpha) = (
+        f64::from(color.red()),
+        f64::from(color.green()),
+        f64::from(color.blue()),
+        f64::from(color.alpha()),
+    );
+    let width = f64::from(width);
+    let height = f64::from(height);
+
+    context.set_source_rgba(red, green, blue, alpha * METER_TRACK_ALPHA);
+    context.rectangle(0.0, 0.0, width, height);
+    let _ = context.fill();
+
+    let filled = snap_to_device_px(width * f64::from(level.clamp(0.0, 1.0)), scale);
+    context.set_source_rgba(red, green, blue, alpha);
+    context.rectangle(0.0, 0.0, filled, height);
+    let _ = context.fill();
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'pulseaudio/mic-meter'"));
+    }
+
+    #[test]
+    fn parse_config_uses_defaults() {
+        let module = ModuleConfig::new(MODULE_TYPE, Map::new());
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.width, DEFAULT_METER_WIDTH);
+        assert_eq!(cfg.height, DEFAULT_METER_HEIGHT);
+        assert!(cfg.hide_when_muted);
+        assert!(cfg.source.is_none());
+    }
+
+    #[test]
+    fn peak_from_samples_returns_largest_magnitude() {
+        let bytes = [0.1_f32, -0.6, 0.3]
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect::<Vec<_>>();
+        assert!((peak_from_samples(&bytes) - 0.6).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn peak_from_samples_clamps_and_ignores_partial_samples() {
+        let mut bytes = 2.5_f32.to_ne_bytes().to_vec();
+        bytes.push(0xff);
+        assert_eq!(peak_from_samples(&bytes), 1.0);
+        assert_eq!(peak_from_samples(&[]), 0.0);
+    }
+}
@@ -10,6 +10,10 @@ use pulse::context::subscribe::Facility;
 use crate::modules::broadcaster::{
     attach_subscription, BackendRegistry, Broadcaster, Subscription,
 };
+use crate::modules::icons::Glyph;
+use crate::modules::text::{attach_text_subscription, visible_block, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
+use crate::modules::touch;
 use crate::modules::{
     apply_css_classes, attach_primary_click_command, attach_secondary_click_command,
     render_markup_template, ModuleBuildContext, ModuleConfig,
@@ -20,15 +24,21 @@ use super::ModuleFactory;
 mod backend;
 mod config;
 mod format;
+pub(crate) mod mic_meter;
+mod state_cache;
+mod stream_meter;
 mod ui;
 
 use self::backend::run_native_loop;
 #[cfg(test)]
-use self::backend::{is_relevant_pulse_event, percent_to_volume_delta};
+use self::backend::{
+    auto_switch_target, default_sink_changed, is_relevant_pulse_event, percent_to_volume_delta,
+    stepped_volume, toggle_preferred_sink,
+};
 use self::config::{
     parse_config, PulseAudioConfig, PulseAudioControlsOpenMode, PulseAudioFormatIcons,
     DEFAULT_FORMAT, DEFAULT_FORMAT_BLUETOOTH, DEFAULT_FORMAT_BLUETOOTH_MUTED, DEFAULT_FORMAT_MUTED,
-    DEFAULT_FORMAT_SOURCE, DEFAULT_FORMAT_SOURCE_MUTED,
+    DEFAULT_FORMAT_SOURCE, DEFAULT_FORMAT_SOURCE_MUTED, DEFAULT_SCROLL_STEP, MAX_VOLUME_LIMIT,
 };
 #[cfg(test)]
 use self::format::classify_icon_kind_by_priority;
@@ -39,8 +49,8 @@ use self::ui::{build_controls_ui, refresh_controls_ui};
 
 const MAINLOOP_IDLE_SLEEP_MILLIS: u64 = 10;
 const SESSION_RECONNECT_DELAY_SECS: u64 = 2;
-const ICON_MUTED: &str = "";
-const CONTROLS_UI_MAX_PERCENT: f64 = 150.0;
+const ICON_MUTED: Glyph = Glyph::new("", "🔇");
+const NORMAL_VOLUME_PERCENT: u32 = 100;
 pub(crate) const MODULE_TYPE: &str = "pulseaudio";
 
 #[derive(Debug, Clone)]
@@ -52,7 +62,7 @@ struct PulseState {
     icon_kind: IconKind,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 struct AudioControlsState {
     sink_name: String,
     sinks: Vec<SinkDeviceEntry>,
@@ -64,22 +74,23 @@ struct AudioControlsState {
     sink_inputs: Vec<SinkInputEntry>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 struct SinkPortEntry {
     name: String,
     description: String,
     available: pulse::def::PortAvailable,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 struct SinkDeviceEntry {
     name: String,
     description: String,
     available: bool,
     is_default: bool,
+    preferred: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 struct SinkInputEntry {
     index: u32,
     name: String,
@@ -114,23 +125,34 @@ enum WorkerCommand {
         sink_name: String,
         port_name: String,
     },
+    TogglePreferredSink {
+        sink_name: String,
+    },
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 struct UiUpdate {
     label_text: String,
+    /// Empty without `tooltip-format`.
+    tooltip_text: String,
+    overamplified: bool,
     controls: Option<AudioControlsState>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct PulseSharedKey {
     format: Option<String>,
+    tooltip_format: Option<String>,
     format_bluetooth: Option<String>,
     format_bluetooth_muted: Option<String>,
     format_muted: Option<String>,
     format_source: Option<String>,
     format_source_muted: Option<String>,
     format_icons: PulseAudioFormatIcons,
+    max_volume: u32,
+    on_sink_change: Option<String>,
+    preferred_sinks: Vec<String>,
+    auto_switch: bool,
 }
 
 struct SharedPulseState {
@@ -149,12 +171,17 @@ fn subscribe_shared_pulse(
 ) -> (Subscription<UiUpdate>, Sender<WorkerCommand>) {
     let key = PulseSharedKey {
         format: config.format.clone(),
+        tooltip_format: config.tooltip_format.clone(),
         format_bluetooth: config.format_bluetooth.clone(),
         format_bluetooth_muted: config.format_bluetooth_muted.clone(),
         format_muted: config.format_muted.clone(),
         format_source: config.format_source.clone(),
         format_source_muted: config.format_source_muted.clone(),
         format_icons: config.format_icons.clone(),
+        max_volume: config.max_volume,
+        on_sink_change: config.on_sink_change.clone(),
+        preferred_sinks: config.preferred_sinks.clone(),
+        auto_switch: config.auto_switch,
     };
 
     let render_config = config.clone();
@@ -208,16 +235,31 @@ impl ModuleFactory for PulseAudioFactory {
         MODULE_TYPE
     }
 
-    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
-        let parsed = parse_config(config)?;
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let mut parsed = parse_config(config)?;
+        parsed.tooltip_format = context.tooltip_format.clone();
         let click_command = parsed.click.clone().or(parsed.on_click.clone());
         let right_click_command = parsed.right_click.clone().or(parsed.on_right_click.clone());
         Ok(build_pulseaudio_module(parsed, click_command, right_click_command).upcast())
     }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        let mut parsed = parse_config(config)?;
+        parsed.max_volume = normalized_max_volume(parsed.max_volume);
+        let (ui_subscription, _worker_tx) = subscribe_shared_pulse(&parsed);
+        attach_text_subscription(ui_subscription, sink, |update| {
+            visible_block(&update.label_text)
+        });
+        Ok(())
+    }
 }
 
 fn build_pulseaudio_module(
-    config: PulseAudioConfig,
+    mut config: PulseAudioConfig,
     click_command: Option<String>,
     right_click_command: Option<String>,
 ) -> Label {
@@ -227,10 +269,40 @@ fn build_pulseaudio_module(
 
     apply_css_classes(&label, config.class.as_deref());
 
+    let max_volume = normalized_max_volume(config.max_volume);
+    if max_volume != config.max_volume {
+        eprintln!(
+            "pulseaudio max-volume={} is out of range; clamping to {}",
+            config.max_volume, max_volume
+        );
+        config.max_volume = max_volume;
+    }
+
     let (ui_subscription, worker_tx) = subscribe_shared_pulse(&config);
 
+    let scroll_step = normalized_scroll_step(config.scroll_step);
+    if (scroll_step - config.scroll_step).abs() > f64::EPSILON {
+        eprintln!(
+            "pulseaudio scroll-step={} is too low; clamping to {}",
+            config.scroll_step, scroll_step
+        );
+    }
+
     let controls_ui = if config.controls.enabled {
-        let controls_ui = build_controls_ui(&label, worker_tx.clone(), config.controls.open);
+        // Keyboard volume keys work even when scrolling is disabled.
+        let keyboard_step = if scroll_step > 0.0 {
+            scroll_step
+        } else {
+            DEFAULT_SCROLL_STEP
+        };
+        let controls_ui = build_controls_ui(
+            &label,
+            worker_tx.clone(),
+            config.controls.open,
+            config.max_volume,
+            keyboard_step,
+            config.controls.test_sound_path().map(ToOwned::to_owned),
+        );
         if matches!(config.controls.open, PulseAudioControlsOpenMode::LeftClick)
             && click_command.is_some()
         {
@@ -252,13 +324,6 @@ fn build_pulseaudio_module(
         attach_secondary_click_command(&label, right_click_command);
     }
 
-    let scroll_step = normalized_scroll_step(config.scroll_step);
-    if (scroll_step - config.scroll_step).abs() > f64::EPSILON {
-        eprintln!(
-            "pulseaudio scroll-step={} is too low; clamping to {}",
-            config.scroll_step, scroll_step
-        );
-    }
     if scroll_step > 0.0 {
         let scroll = EventControllerScroll::new(
             EventControllerScrollFlags::VERTICAL | EventControllerScrollFlags::DISCRETE,
@@ -282,8 +347,24 @@ fn build_pulseaudio_module(
             gtk::glib::Propagation::Proceed
         });
         label.add_controller(scroll);
+
+        let drag_tx = worker_tx.clone();
+        touch::attach_vertical_drag_steps(&label, move |increase| {
+            let _ = drag_tx.send(WorkerCommand::VolumeStep {
+                increase,
+                step: scroll_step,
+            });
+        });
     }
 
+    let tooltip = config.tooltip_format.is_some().then(|| {
+        let tooltip = ModuleTooltip::attach(&label);
+        if let Some(controls_ui) = controls_ui.as_ref() {
+            tooltip.suppress_while(&controls_ui.popover);
+        }
+        tooltip
+    });
+
     attach_subscription(&label, ui_subscription, {
         let controls_ui = controls_ui.clone();
         move |label, update| {
@@ -292,6 +373,14 @@ fn build_pulseaudio_module(
             if visible {
                 label.set_markup(&update.label_text);
             }
+            if let Some(tooltip) = &tooltip {
+                tooltip.set_markup(&update.tooltip_text);
+            }
+            if update.overamplified {
+                label.add_css_class("overamplified");
+            } else {
+                label.remove_css_class("overamplified");
+            }
             if let Some(state) = update.controls.as_ref() {
                 if let Some(controls_ui) = controls_ui.as_ref() {
                     refresh_controls_ui(controls_ui, state, worker_tx.clone());
@@ -311,38 +400,61 @@ pub(crate) fn normalized_scroll_step(step: f64) -> f64 {
     }
 }
 
+fn normalized_max_volume(max_volume: u32) -> u32 {
+    max_volume.clamp(1, MAX_VOLUME_LIMIT)
+}
+
+fn is_overamplified(volume: u32) -> bool {
+    volume > NORMAL_VOLUME_PERCENT
+}
+
 fn render_format(config: &PulseAudioConfig, state: &PulseState) -> String {
-    let format = if state.muted {
+    render_template(config, state, select_format(config, state))
+}
+
+fn render_tooltip_format(config: &PulseAudioConfig, state: &PulseState) -> String {
+    config
+        .tooltip_format
+        .as_deref()
+        .map(|format| render_template(config, state, format))
+        .unwrap_or_default()
+}
+
+/// `format` or the muted / bluetooth variant for the current state.
+fn select_format<'a>(config: &'a PulseAudioConfig, state: &PulseState) -> &'a str {
+    if state.muted {
         if state.bluetooth {
             config
                 .format_bluetooth_muted
                 .as_deref()
-                .unwrap_or(DEFAULT_FORMAT_BLUETOOTH_MUTED)
+                .unwrap_or(DEFAULT_FORMAT_BLUETOOTH_MUTED.get())
         } else {
             config
                 .format_muted
                 .as_deref()
-                .unwrap_or(DEFAULT_FORMAT_MUTED)
+                .unwrap_or(DEFAULT_FORMAT_MUTED.get())
         }
     } else if state.bluetooth {
         config
             .format_bluetooth
             .as_deref()
-            .unwrap_or(DEFAULT_FORMAT_BLUETOOTH)
+            .unwrap_or(DEFAULT_FORMAT_BLUETOOTH.get())
     } else {
         config.format.as_deref().unwrap_or(DEFAULT_FORMAT)
-    };
+    }
+}
 
+fn render_template(config: &PulseAudioConfig, state: &PulseState, format: &str) -> String {
     let source = if state.source_muted {
         config
             .format_source_muted
             .as_deref()
-            .unwrap_or(DEFAULT_FORMAT_SOURCE_MUTED)
+            .unwrap_or(DEFAULT_FORMAT_SOURCE_MUTED.get())
     } else {
         config
             .format_source
             .as_deref()
-            .unwrap_or(DEFAULT_FORMAT_SOURCE)
+            .unwrap_or(DEFAULT_FORMAT_SOURCE.get())
     };
 
     let icon = config.format_icons.icon_for(state.icon_kind, state.volume);
@@ -359,6 +471,8 @@ fn render_format(config: &PulseAudioConfig, state: &PulseState) -> String {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use serde_json::{json, Map};
 
     use super::*;
@@ -393,6 +507,33 @@ mod tests {
         assert_eq!(text, " ");
     }
 
+    #[test]
+    fn normalized_max_volume_clamps_to_supported_range() {
+        assert_eq!(normalized_max_volume(0), 1);
+        assert_eq!(normalized_max_volume(100), 100);
+        assert_eq!(normalized_max_volume(150), 150);
+        assert_eq!(normalized_max_volume(200), 150);
+    }
+
+    #[test]
+    fn is_overamplified_only_above_normal_volume() {
+        assert!(!is_overamplified(100));
+        assert!(is_overamplified(101));
+    }
+
+    #[test]
+    fn default_sink_changed_ignores_initial_query() {
+        assert!(!default_sink_changed(None, "alsa_output.speakers"));
+        assert!(!default_sink_changed(
+            Some("alsa_output.speakers"),
+            "alsa_output.speakers"
+        ));
+        assert!(default_sink_changed(
+            Some("alsa_output.speakers"),
+            "bluez_output.headphones"
+        ));
+    }
+
     #[test]
     fn normalized_scroll_step_disables_zero_and_negative() {
         assert_eq!(normalized_scroll_step(0.0), 0.0);
@@ -411,6 +552,131 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn parse_config_reads_controls_test_sound() {
+        let module = ModuleConfig::new(MODULE_TYPE, Map::new());
+        let config = parse_config(&module).expect("config should parse");
+        assert_eq!(
+            config.controls.test_sound_path(),
+            Some(config::DEFAULT_TEST_SOUND)
+        );
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            Map::from_iter([(
+                "controls".to_string(),
+                json!({ "test_sound": "/tmp/ping.wav" }),
+            )]),
+        );
+        let config = parse_config(&module).expect("config should parse");
+        assert_eq!(config.controls.test_sound_path(), Some("/tmp/ping.wav"));
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            Map::from_iter([("controls".to_string(), json!({ "test-sound": "" }))]),
+        );
+        let config = parse_config(&module).expect("config should parse");
+        assert_eq!(config.controls.test_sound_path(), None);
+    }
+
+    #[test]
+    fn parse_config_reads_max_volume_with_default() {
+        let module = ModuleConfig::new(MODULE_TYPE, Map::new());
+        let config = parse_config(&module).expect("config should parse");
+        assert_eq!(config.max_volume, 100);
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            Map::from_iter([("max_volume".to_string(), json!(150))]),
+        );
+        let config = parse_config(&module).expect("config should parse");
+        assert_eq!(config.max_volume, 150);
+    }
+
+    #[test]
+    fn parse_config_reads_preferred_sinks_and_auto_switch() {
+        let module = ModuleConfig::new(MODULE_TYPE, Map::new());
+        let config = parse_config(&module).expect("config should parse");
+        assert!(config.preferred_sinks.is_empty());
+        assert!(!config.auto_switch);
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            Map::from_iter([
+                (
+                    "preferred_sinks".to_string(),
+                    json!(["headset", "Speakers"]),
+                ),
+                ("auto_switch".to_string(), json!(true)),
+            ]),
+        );
+        let config = parse_config(&module).expect("config should parse");
+        assert_eq!(config.preferred_sinks, vec!["headset", "Speakers"]);
+        assert!(config.auto_switch);
+    }
+
+    fn sink(name: &str, description: &str, available: bool) -> SinkDeviceEntry {
+        SinkDeviceEntry {
+            name: name.to_string(),
+            description: description.to_string(),
+            available,
+            is_default: false,
+            preferred: false,
+        }
+    }
+
+    #[test]
+    fn auto_switch_target_picks_newly_available_preferred_sink() {
+        let preferred = vec!["headset".to_string(), "Speakers".to_string()];
+        let sinks = vec![
+            sink("builtin", "Built-in Audio", true),
+            sink("usb-speakers", "Speakers", true),
+            sink("headset", "Headset", true),
+        ];
+
+        let previously = HashSet::from(["builtin".to_string(), "usb-speakers".to_string()]);
+        assert_eq!(
+            auto_switch_target(&preferred, &sinks, &previously, "usb-speakers"),
+            Some("headset")
+        );
+
+        // The best preferred sink was already around: respect the user's choice.
+        let previously = HashSet::from(["headset".to_string()]);
+        assert_eq!(
+            auto_switch_target(&preferred, &sinks, &previously, "builtin"),
+            None
+        );
+
+        // Already the default.
+        assert_eq!(
+            auto_switch_target(&preferred, &sinks, &HashSet::new(), "headset"),
+            None
+        );
+    }
+
+    #[test]
+    fn auto_switch_target_skips_unavailable_sinks() {
+        let preferred = vec!["headset".to_string(), "Speakers".to_string()];
+        let sinks = vec![
+            sink("headset", "Headset", false),
+            sink("usb-speakers", "Speakers", true),
+        ];
+        assert_eq!(
+            auto_switch_target(&preferred, &sinks, &HashSet::new(), "builtin"),
+            Some("usb-speakers")
+        );
+    }
+
+    #[test]
+    fn toggle_preferred_sink_adds_and_removes_by_name_or_description() {
+        let mut preferred = vec!["Speakers".to_string()];
+        toggle_preferred_sink(&mut preferred, "headset", "Headset");
+        assert_eq!(preferred, vec!["Speakers", "headset"]);
+
+        toggle_preferred_sink(&mut preferred, "usb-speakers", "Speakers");
+        assert_eq!(preferred, vec!["headset"]);
+    }
+
     #[test]
     fn parse_config_supports_right_click_aliases() {
         let right_click_module = ModuleConfig::new(
@@ -471,6 +737,24 @@ mod tests {
         assert!(delta.0 > 0);
     }
 
+    #[test]
+    fn stepped_volume_never_lowers_volume_above_limit() {
+        use pulse::volume::{ChannelVolumes, Volume};
+
+        let limit = Volume(Volume::NORMAL.0);
+        let delta = Volume(Volume::NORMAL.0 / 20);
+        let mut volumes = ChannelVolumes::default();
+
+        volumes.set(2, Volume(Volume::NORMAL.0 * 3 / 2));
+        assert_eq!(stepped_volume(volumes, delta, true, limit), None);
+        let lowered = stepped_volume(volumes, delta, false, limit).expect("decrease applies");
+        assert!(lowered.max() < volumes.max());
+
+        volumes.set(2, Volume(Volume::NORMAL.0 - 1));
+        let raised = stepped_volume(volumes, delta, true, limit).expect("increase applies");
+        assert_eq!(raised.max(), limit);
+    }
+
     #[test]
     fn classify_icon_kind_matches_priority_order() {
         assert_eq!(
@@ -0,0 +1,246 @@
+//! The worker's copy of the audio server state. Subscription events name the
+//! entity they touch, so a refresh re-queries only that sink, stream, or
+//! server setting instead of the whole server.
+
+use std::collections::BTreeMap;
+
+use libpulse_binding as pulse;
+use pulse::context::subscribe::{Facility, Operation};
+
+use super::backend::{is_relevant_pulse_event, ServerDefaults, SinkSnapshot};
+use super::{AudioControlsState, PulseState, SinkDeviceEntry, SinkInputEntry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum EntityChange {
+    Updated,
+    Removed,
+}
+
+/// Work collected from subscription events between two refreshes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct PendingChanges {
+    /// Re-query everything, as after connecting or a failed refresh.
+    pub(super) full: bool,
+    /// Default sink and source names.
+    pub(super) server: bool,
+    /// The whole sink list; card profile changes add and drop sinks.
+    pub(super) all_sinks: bool,
+    pub(super) sinks: BTreeMap<u32, EntityChange>,
+    pub(super) sink_inputs: BTreeMap<u32, EntityChange>,
+    /// Mute state of the default source.
+    pub(super) source: bool,
+    /// Nothing to query, but state derived in the worker changed (starred
+    /// sinks).
+    pub(super) rebroadcast: bool,
+}
+
+impl PendingChanges {
+    pub(super) fn everything() -> Self {
+        Self {
+            full: true,
+            ..Self::default()
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Notes one subscription event; later events for an index replace
+    /// earlier ones.
+    pub(super) fn record(
+        &mut self,
+        facility: Option<Facility>,
+        operation: Option<Operation>,
+        index: u32,
+    ) {
+        if !is_relevant_pulse_event(facility, operation) {
+            return;
+        }
+        let change = if operation == Some(Operation::Removed) {
+            EntityChange::Removed
+        } else {
+            EntityChange::Updated
+        };
+        match facility {
+            Some(Facility::Sink) => {
+                self.sinks.insert(index, change);
+            }
+            Some(Facility::SinkInput) => {
+                self.sink_inputs.insert(index, change);
+            }
+            Some(Facility::Server) => self.server = true,
+            Some(Facility::Source) => self.source = true,
+            Some(Facility::Card) => self.all_sinks = true,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct CachedSink {
+    /// `is_default` is filled in by [`PulseCache::current_state`].
+    pub(super) device: SinkDeviceEntry,
+    pub(super) snapshot: SinkSnapshot,
+}
+
+/// Sinks and sink inputs are keyed by server index.
+#[derive(Debug, Clone)]
+pub(super) struct PulseCache {
+    pub(super) defaults: ServerDefaults,
+    pub(super) sinks: BTreeMap<u32, CachedSink>,
+    pub(super) sink_inputs: BTreeMap<u32, SinkInputEntry>,
+    pub(super) source_muted: bool,
+}
+
+impl PulseCache {
+    /// Stores a re-queried sink; `None` drops it.
+    pub(super) fn apply_sink(&mut self, index: u32, sink: Option<CachedSink>) {
+        match sink {
+            Some(sink) => {
+                self.sinks.insert(index, sink);
+            }
+            None => {
+                self.sinks.remove(&index);
+            }
+        }
+    }
+
+    /// Stores a re-queried sink input; `None` drops it.
+    pub(super) fn apply_sink_input(&mut self, index: u32, input: Option<SinkInputEntry>) {
+        match input {
+            Some(input) => {
+                self.sink_inputs.insert(index, input);
+            }
+            None => {
+                self.sink_inputs.remove(&index);
+            }
+        }
+    }
+
+    /// Module and controls state for the default sink.
+    pub(super) fn current_state(&self) -> Result<(PulseState, AudioControlsState), String> {
+        let default_name = self.defaults.sink_name.as_str();
+        let default_sink = self
+            .sinks
+            .values()
+            .find(|sink| sink.device.name == default_name)
+            .ok_or_else(|| "pulseaudio sink info not found".to_string())?;
+        let snapshot = &default_sink.snapshot;
+
+        let mut sinks: Vec<SinkDeviceEntry> = self
+            .sinks
+            .values()
+            .map(|sink| SinkDeviceEntry {
+                is_default: sink.device.name == default_name,
+                ..sink.device.clone()
+            })
+            .collect();
+        sinks.sort_by(|a, b| a.description.cmp(&b.description));
+        let mut sink_inputs: Vec<SinkInputEntry> = self.sink_inputs.values().cloned().collect();
+        sink_inputs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok((
+            PulseState {
+                volume: snapshot.volume,
+                muted: snapshot.muted,
+                source_muted: self.source_muted,
+                bluetooth: snapshot.bluetooth,
+                icon_kind: snapshot.icon_kind,
+            },
+            AudioControlsState {
+                sink_name: default_name.to_string(),
+                sinks,
+                selected_sink_name: default_name.to_string(),
+                sink_volume: snapshot.volume,
+                sink_muted: snapshot.muted,
+                sink_ports: snapshot.ports.clone(),
+                active_sink_port: snapshot.active_port_name.clone(),
+                sink_inputs,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::format::IconKind;
+    use super::*;
+
+    fn sink(name: &str, description: &str, volume: u32) -> CachedSink {
+        CachedSink {
+            device: SinkDeviceEntry {
+                name: name.to_string(),
+                description: description.to_string(),
+                available: true,
+                is_default: false,
+                preferred: false,
+            },
+            snapshot: SinkSnapshot {
+                volume,
+                muted: false,
+                bluetooth: false,
+                icon_kind: IconKind::Default,
+                channels: pulse::volume::ChannelVolumes::default(),
+                ports: Vec::new(),
+                active_port_name: None,
+            },
+        }
+    }
+
+    fn input(index: u32, name: &str) -> SinkInputEntry {
+        SinkInputEntry {
+            index,
+            name: name.to_string(),
+            volume: 100,
+            muted: false,
+        }
+    }
+
+    #[test]
+    fn record_tracks_entities_by_index() {
+        let mut changes = PendingChanges::default();
+        changes.record(Some(Facility::Sink), Some(Operation::Changed), 3);
+        changes.record(Some(Facility::SinkInput), Some(Operation::New), 7);
+        changes.record(Some(Facility::SinkInput), Some(Operation::Removed), 7);
+        changes.record(Some(Facility::Client), Some(Operation::New), 1);
+
+        assert_eq!(changes.sinks.get(&3), Some(&EntityChange::Updated));
+        assert_eq!(changes.sink_inputs.get(&7), Some(&EntityChange::Removed));
+        assert!(!changes.full && !changes.server && !changes.all_sinks);
+
+        changes.record(Some(Facility::Card), Some(Operation::Changed), 0);
+        changes.record(Some(Facility::Server), Some(Operation::Changed), 0);
+        assert!(changes.all_sinks && changes.server);
+        assert!(PendingChanges::default().is_empty());
+        assert!(!PendingChanges::everything().is_empty());
+    }
+
+    #[test]
+    fn current_state_follows_the_default_sink() {
+        let mut cache = PulseCache {
+            defaults: ServerDefaults {
+                sink_name: "hdmi".to_string(),
+                source_name: None,
+            },
+            sinks: BTreeMap::from([(1, sink("speakers", "Speakers", 40))]),
+            sink_inputs: BTreeMap::from([(9, input(9, "mpv")), (4, input(4, "firefox"))]),
+            source_muted: true,
+        };
+        assert!(cache.current_state().is_err());
+
+        cache.apply_sink(2, Some(sink("hdmi", "HDMI", 70)));
+        let (state, controls) = cache.current_state().expect("default sink is cached");
+        assert_eq!(state.volume, 70);
+        assert!(state.source_muted);
+        assert_eq!(controls.sinks[0].name, "hdmi");
+        assert!(controls.sinks[0].is_default);
+        assert!(!controls.sinks[1].is_default);
+        assert_eq!(controls.sink_inputs[0].name, "firefox");
+
+        cache.apply_sink_input(4, None);
+        cache.apply_sink(2, None);
+        assert!(cache.current_state().is_err());
+        assert_eq!(cache.sink_inputs.len(), 1);
+    }
+}
@@ -0,0 +1,349 @@
+//! Live per-stream levels for the controls popover. One record stream with
+//! `PEAK_DETECT` is opened on the sink monitor for each sink input, and only
+//! while a controls popover is mapped, so the bar costs nothing when closed.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use gtk::glib::{self, IOCondition};
+use gtk::prelude::*;
+use gtk::Popover;
+use libpulse_binding as pulse;
+use pulse::callbacks::ListResult;
+use pulse::context::subscribe::{Facility, InterestMaskSet};
+use pulse::context::{Context, State as ContextState};
+use pulse::mainloop::standard::{IterateResult, Mainloop};
+use pulse::stream::{State as StreamState, Stream};
+
+use crate::modules::broadcaster::{drain_pipe, BackendRegistry, Broadcaster, Subscription};
+
+use super::backend::{connect_native_context, wait_for_operation};
+use super::mic_meter::{drain_peak, open_peak_stream};
+use super::{MAINLOOP_IDLE_SLEEP_MILLIS, SESSION_RECONNECT_DELAY_SECS};
+
+/// Corked or silent streams stop delivering samples; drop their bar to zero
+/// instead of freezing it at the last peak.
+const STALE_LEVEL_AFTER: Duration = Duration::from_millis(250);
+
+/// Peak level in `0..=1` per sink input index.
+pub(super) type StreamLevels = HashMap<u32, f32>;
+
+struct MonitoredStream {
+    monitor_source: u32,
+    stream: Stream,
+    last_data: Instant,
+}
+
+fn stream_meter_registry() -> &'static BackendRegistry<(), Broadcaster<StreamLevels>> {
+    static REGISTRY: OnceLock<BackendRegistry<(), Broadcaster<StreamLevels>>> = OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_stream_meters() -> Subscription<StreamLevels> {
+    let (broadcaster, start_worker) = stream_meter_registry().get_or_create((), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_stream_meter_worker(broadcaster);
+    }
+
+    receiver
+}
+
+/// Fd watch plus the subscription it drains while a popover is mapped.
+type ActiveMeter = (glib::SourceId, Subscription<StreamLevels>);
+
+/// Calls `apply` with fresh levels while `popover` is mapped. The
+/// subscription is dropped on unmap, which stops the worker once no
+/// popover is open.
+pub(super) fn attach_while_mapped(popover: &Popover, apply: impl FnMut(StreamLevels) + 'static) {
+    let apply = Rc::new(RefCell::new(apply));
+    let active: Rc<RefCell<Option<ActiveMeter>>> = Rc::new(RefCell::new(None));
+
+    {
+        let active = Rc::clone(&active);
+        popover.connect_map(move |_| {
+            if active.borrow().is_some() {
+                return;
+            }
+            let subscription = subscribe_stream_meters();
+            let fd = subscription.notify_fd;
+            let source_id = glib::unix_fd_add_local(fd, IOCondition::IN, {
+                let active = Rc::clone(&active);
+                let apply = Rc::clone(&apply);
+                move |_, _| {
+                    drain_pipe(fd);
+                    if let Some((_, subscription)) = active.borrow().as_ref() {
+                        while let Ok(levels) = subscription.receiver.try_recv() {
+                            (apply.borrow_mut())(levels);
+                        }
+                    }
+                    glib::ControlFlow::Continue
+                }
+            });
+            *active.borrow_mut() = Some((source_id, subscription));
+        });
+    }
+
+    popover.connect_unmap(move |_| {
+        if let Some((source_id, _subscription)) = active.borrow_mut().take() {
+            source_id.remove();
+        }
+    });
+}
+
+fn start_stream_meter_worker(broadcaster: Arc<Broadcaster<StreamLevels>>) {
+    std::thread::spawn(move || {
+        loop {
+            if broadcaster.subscriber_count() == 0 {
+                break;
+            }
+            match run_stream_meter_session(&broadcaster) {
+                Ok(()) => break,
+                Err(_) => {
+                    broadcaster.broadcast(StreamLevels::new());
+                    std::thread::sleep(Duration::from_secs(SESSION_RECONNECT_DELAY_SECS));
+                }
+            }
+        }
+        stream_meter_registry().remove(&(), &broadcaster);
+    });
+}
+
+fn run_stream_meter_session(broadcaster: &Broadcaster<StreamLevels>) -> Result<(), String> {
+    let (mut mainloop, mut context) = connect_native_context("vibar-stream-meter")?;
+
+    let dirty = Arc::new(AtomicBool::new(true));
+    context.set_subscribe_callback(Some(Box::new({
+        let dirty = Arc::clone(&dirty);
+        move |facility, operation, _| {
+            if matches!(facility, Some(Facility::SinkInput | Facility::Sink)) && operation.is_some()
+            {
+                dirty.store(true, Ordering::SeqCst);
+            }
+        }
+    })));
+
+    let mut subscribe_op =
+        context.subscribe(InterestMaskSet::SINK_INPUT | InterestMaskSet::SINK, |_| {});
+    wait_for_operation(&mut mainloop, &mut subscribe_op)?;
+
+    let mut streams: HashMap<u32, MonitoredStream> = HashMap::new();
+    let mut levels = StreamLevels::new();
+
+    loop {
+        let mut changed = false;
+
+        if dirty.swap(false, Ordering::SeqCst) {
+            let targets = monitor_targets(
+                &query_sink_input_sinks(&context, &mut mainloop)?,
+                &query_sink_monitors(&context, &mut mainloop)?,
+            );
+            streams.retain(|index, monitored| {
+                let keep = targets.get(index) == Some(&monitored.monitor_source);
+                if !keep {
+                    let _ = monitored.stream.disconnect();
+                }
+                keep
+            });
+            for (&index, &monitor_source) in &targets {
+                if streams.contains_key(&index) {
+                    continue;
+                }
+                // A stream that vanished between the query and here just
+                // fails to connect; the next sink input event retries.
+                if let Ok(stream) = open_peak_stream(
+                    &mut context,
+                    "vibar-stream-meter",
+                    &monitor_source.to_string(),
+                    Some(index),
+                ) {
+                    streams.insert(
+                        index,
+                        MonitoredStream {
+                            monitor_source,
+                            stream,
+                            last_data: Instant::now(),
+                        },
+                    );
+                }
+            }
+            let before = levels.len();
+            levels.retain(|index, _| streams.contains_key(index));
+            changed |= levels.len() != before;
+        }
+
+        let mut failed = Vec::new();
+        for (&index, monitored) in &mut streams {
+            match monitored.stream.get_state() {
+                StreamState::Ready => {
+                    let level = match drain_peak(&mut monitored.stream)? {
+                        Some(level) => {
+                            monitored.last_data = Instant::now();
+                            level
+                        }
+                        None if monitored.last_data.elapsed() >= STALE_LEVEL_AFTER => 0.0,
+                        None => continue,
+                    };
+                    if levels
+                        .insert(index, level)
+                        .is_none_or(|previous| (previous - level).abs() > f32::EPSILON)
+                    {
+                        changed = true;
+                    }
+                }
+                StreamState::Failed | StreamState::Terminated => failed.push(index),
+                _ => {}
+            }
+        }
+        if !failed.is_empty() {
+            for index in failed {
+                streams.remove(&index);
+            }
+            dirty.store(true, Ordering::SeqCst);
+        }
+
+        if changed {
+            broadcaster.broadcast(levels.clone());
+        }
+
+        match mainloop.iterate(false) {
+            IterateResult::Success(_) => {}
+            IterateResult::Quit(_) => return Err("pulseaudio mainloop quit".to_string()),
+            IterateResult::Err(err) => {
+                return Err(format!("pulseaudio mainloop iteration failed: {err:?}"));
+            }
+        }
+
+        match context.get_state() {
+            ContextState::Ready => {}
+            ContextState::Failed => {
+                return Err(format!("pulseaudio context failed: {:?}", context.errno()));
+            }
+            ContextState::Terminated => {
+                return Err("pulseaudio context terminated".to_string());
+            }
+            _ => {}
+        }
+
+        std::thread::sleep(Duration::from_millis(MAINLOOP_IDLE_SLEEP_MILLIS));
+
+        if broadcaster.subscriber_count() == 0 {
+            for (_, mut monitored) in streams.drain() {
+                let _ = monitored.stream.disconnect();
+            }
+            return Ok(());
+        }
+    }
+}
+
+/// Maps each sink input to the monitor source of the sink it plays on.
+/// Inputs whose sink is unknown (mid-move) are skipped until the next event.
+fn monitor_targets(
+    input_sinks: &[(u32, u32)],
+    sink_monitors: &HashMap<u32, u32>,
+) -> HashMap<u32, u32> {
+    input_sinks
+        .iter()
+        .filter_map(|&(input, sink)| sink_monitors.get(&sink).map(|&monitor| (input, monitor)))
+        .collect()
+}
+
+/// `(sink input index, sink index)` for every playback stream.
+fn query_sink_input_sinks(
+    context: &Context,
+    mainloop: &mut Mainloop,
+) -> Result<Vec<(u32, u32)>, String> {
+    let slot = Arc::new(Mutex::new(None::<Result<Vec<(u32, u32)>, String>>));
+    let items = Arc::new(Mutex::new(Vec::new()));
+    let mut op = context.introspect().get_sink_input_info_list({
+        let slot = Arc::clone(&slot);
+        let items = Arc::clone(&items);
+        move |result| match result {
+            ListResult::Item(info) => {
+                items
+                    .lock()
+                    .expect("stream meter input list mutex poisoned")
+                    .push((info.index, info.sink));
+            }
+            ListResult::End => {
+                let mut guard = slot.lock().expect("stream meter result mutex poisoned");
+                if guard.is_none() {
+                    *guard = Some(Ok(items
+                        .lock()
+                        .expect("stream meter input list mutex poisoned")
+                        .clone()));
+                }
+            }
+            ListResult::Error => {
+                *slot.lock().expect("stream meter result mutex poisoned") =
+                    Some(Err("pulseaudio sink input list query failed".to_string()));
+            }
+        }
+    });
+    wait_for_operation(mainloop, &mut op)?;
+    let result = slot
+        .lock()
+        .expect("stream meter result mutex poisoned")
+        .clone()
+        .unwrap_or_else(|| Err("pulseaudio sink input list query returned no data".to_string()));
+    result
+}
+
+/// Monitor source index per sink index.
+fn query_sink_monitors(
+    context: &Context,
+    mainloop: &mut Mainloop,
+) -> Result<HashMap<u32, u32>, String> {
+    let slot = Arc::new(Mutex::new(None::<Result<HashMap<u32, u32>, String>>));
+    let items = Arc::new(Mutex::new(HashMap::new()));
+    let mut op = context.introspect().get_sink_info_list({
+        let slot = Arc::clone(&slot);
+        let items = Arc::clone(&items);
+        move |result| match result {
+            ListResult::Item(info) => {
+                items
+                    .lock()
+                    .expect("stream meter sink list mutex poisoned")
+                    .insert(info.index, info.monitor_source);
+            }
+            ListResult::End => {
+                let mut guard = slot.lock().expect("stream meter result mutex poisoned");
+                if guard.is_none() {
+                    *guard = Some(Ok(items
+                        .lock()
+                        .expect("stream meter sink list mutex poisoned")
+                        .clone()));
+                }
+            }
+            ListResult::Error => {
+                *slot.lock().expect("stream meter result mutex poisoned") =
+                    Some(Err("pulseaudio sink list query failed".to_string()));
+            }
+        }
+    });
+    wait_for_operation(mainloop, &mut op)?;
+    let result = slot
+        .lock()
+        .expect("stream meter result mutex poisoned")
+        .clone()
+        .unwrap_or_else(|| Err("pulseaudio sink list query returned no data".to_string()));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monitor_targets_pairs_inputs_with_their_sink_monitor() {
+        let sink_monitors = HashMap::from([(1, 10), (2, 20)]);
+        let targets = monitor_targets(&[(100, 1), (101, 2), (102, 3)], &sink_monitors);
+
+        assert_eq!(targets, HashMap::from([(100, 10), (101, 20)]));
+    }
+}
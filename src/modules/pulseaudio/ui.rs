@@ -1,17 +1,31 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc, Arc};
 
+use gtk::gdk::{Key, ModifierType};
+use gtk::glib::Propagation;
 use gtk::prelude::*;
-use gtk::{Box as GtkBox, Button, GestureClick, Label, Orientation, Popover, PositionType, Scale};
+use gtk::{
+    Box as GtkBox, Button, DrawingArea, EventControllerKey, GestureClick, Label, Orientation,
+    Popover, PositionType, PropagationPhase, Scale,
+};
 use libpulse_binding as pulse;
 
-use super::config::{PulseAudioControlsOpenMode, ICON_VOLUME_HIGH};
-use super::{AudioControlsState, WorkerCommand, CONTROLS_UI_MAX_PERCENT, ICON_MUTED};
+use super::config::{PulseAudioControlsOpenMode, ICON_TEST_SOUND, ICON_VOLUME_HIGH};
+use super::mic_meter::draw_level_bar;
+use super::stream_meter;
+use super::{AudioControlsState, WorkerCommand, ICON_MUTED, MODULE_TYPE};
+use crate::modules::{popovers, touch};
+
+const KEYBOARD_SELECTED_CLASS: &str = "keyboard-selected";
+const STREAM_METER_HEIGHT: i32 = 3;
 
 #[derive(Clone)]
 pub(super) struct PulseAudioControlsUi {
+    pub(super) popover: Popover,
+    sink_row: GtkBox,
     sink_mute_button: Button,
     sink_volume_scale: Scale,
     sink_volume_percent_label: Label,
@@ -20,11 +34,29 @@ pub(super) struct PulseAudioControlsUi {
     sink_inputs_box: GtkBox,
     suppress_sink_scale_callback: Arc<AtomicBool>,
     sink_muted_state: Arc<AtomicBool>,
-    sink_input_rows: RefCell<HashMap<u32, SinkInputRowUi>>,
+    sink_input_rows: Rc<RefCell<HashMap<u32, SinkInputRowUi>>>,
+    /// Stream indices in display order.
+    sink_input_order: Rc<RefCell<Vec<u32>>>,
+    /// Row that keyboard volume/mute keys act on: `None` is the main
+    /// volume row, otherwise a stream index.
+    keyboard_target: Rc<Cell<Option<u32>>>,
+    max_percent: f64,
+    /// Played by each output device's test button; `None` hides the buttons.
+    test_sound: Option<Rc<str>>,
+}
+
+/// What a key press in the controls popover does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ControlsKeyAction {
+    VolumeStep { increase: bool },
+    ToggleMute,
+    CycleTarget { backward: bool },
 }
 
 #[derive(Clone)]
 struct SinkInputRowUi {
+    /// Holds `row` with the level bar underneath.
+    container: GtkBox,
     row: GtkBox,
     mute_button: Button,
     name_label: Label,
@@ -33,13 +65,20 @@ struct SinkInputRowUi {
     muted_state: Arc<AtomicBool>,
     suppress_scale_callback: Arc<AtomicBool>,
     drag_active: Arc<AtomicBool>,
+    max_percent: f64,
+    meter: DrawingArea,
+    meter_level: Rc<Cell<f32>>,
 }
 
 pub(super) fn build_controls_ui(
     label: &Label,
     worker_tx: mpsc::Sender<WorkerCommand>,
     open_mode: PulseAudioControlsOpenMode,
+    max_volume: u32,
+    keyboard_step: f64,
+    test_sound: Option<String>,
 ) -> PulseAudioControlsUi {
+    let max_percent = f64::from(max_volume);
     label.add_css_class("clickable");
     label.add_css_class("pulseaudio-controls-enabled");
 
@@ -49,6 +88,8 @@ pub(super) fn build_controls_ui(
     popover.set_has_arrow(true);
     popover.set_position(PositionType::Top);
     popover.set_parent(label);
+    popovers::track(&popover, MODULE_TYPE);
+    popovers::accept_keyboard(&popover);
 
     let content = GtkBox::new(Orientation::Vertical, 6);
     content.add_css_class("pulseaudio-controls-content");
@@ -59,12 +100,11 @@ pub(super) fn build_controls_ui(
     sink_row.add_css_class("pulseaudio-controls-sink-row");
     content.append(&sink_row);
 
-    let sink_mute_button = Button::with_label(ICON_VOLUME_HIGH);
+    let sink_mute_button = Button::with_label(ICON_VOLUME_HIGH.get());
     sink_mute_button.add_css_class("pulseaudio-control-button");
     sink_row.append(&sink_mute_button);
 
-    let sink_volume_scale =
-        Scale::with_range(Orientation::Horizontal, 0.0, CONTROLS_UI_MAX_PERCENT, 1.0);
+    let sink_volume_scale = Scale::with_range(Orientation::Horizontal, 0.0, max_percent, 1.0);
     sink_volume_scale.add_css_class("pulseaudio-volume-scale");
     sink_volume_scale.set_hexpand(true);
     sink_volume_scale.set_draw_value(false);
@@ -96,7 +136,7 @@ pub(super) fn build_controls_ui(
         let suppress = suppress_sink_scale_callback.clone();
         let percent_label = sink_volume_percent_label.clone();
         sink_volume_scale.connect_value_changed(move |scale| {
-            let percent = scale.value().round().clamp(0.0, CONTROLS_UI_MAX_PERCENT) as u32;
+            let percent = scale.value().round().clamp(0.0, max_percent) as u32;
             percent_label.set_text(&format!("{percent}%"));
             if suppress.load(Ordering::Relaxed) {
                 return;
@@ -114,7 +154,9 @@ pub(super) fn build_controls_ui(
         });
     }
 
-    PulseAudioControlsUi {
+    let controls_ui = PulseAudioControlsUi {
+        popover,
+        sink_row,
         sink_mute_button,
         sink_volume_scale,
         sink_volume_percent_label,
@@ -123,7 +165,132 @@ pub(super) fn build_controls_ui(
         sink_inputs_box: inputs_box,
         suppress_sink_scale_callback,
         sink_muted_state,
-        sink_input_rows: RefCell::new(HashMap::new()),
+        sink_input_rows: Rc::new(RefCell::new(HashMap::new())),
+        sink_input_order: Rc::new(RefCell::new(Vec::new())),
+        keyboard_target: Rc::new(Cell::new(None)),
+        max_percent,
+        test_sound: test_sound.map(Rc::from),
+    };
+    install_controls_key_controller(&controls_ui, keyboard_step);
+    {
+        let rows = Rc::clone(&controls_ui.sink_input_rows);
+        stream_meter::attach_while_mapped(&controls_ui.popover, move |levels| {
+            for (index, row) in rows.borrow().iter() {
+                set_stream_meter_level(row, levels.get(index).copied().unwrap_or(0.0));
+            }
+        });
+    }
+    controls_ui
+}
+
+/// Up/Down change the selected row's volume by `step`, M toggles its mute
+/// and Tab/Shift+Tab move the selection between the main volume row and
+/// the program streams. Handled in the capture phase so the focused widget
+/// doesn't also move focus or its own slider.
+fn install_controls_key_controller(controls_ui: &PulseAudioControlsUi, step: f64) {
+    {
+        let controls_ui = controls_ui.clone();
+        controls_ui.popover.clone().connect_show(move |_| {
+            controls_ui.sink_mute_button.grab_focus();
+            set_keyboard_target(&controls_ui, None);
+        });
+    }
+
+    let keys = EventControllerKey::new();
+    keys.set_propagation_phase(PropagationPhase::Capture);
+    let controls = controls_ui.clone();
+    keys.connect_key_pressed(move |_, key, _, modifiers| {
+        let Some(action) = controls_key_action(key, modifiers) else {
+            return Propagation::Proceed;
+        };
+        let target = controls.keyboard_target.get();
+        match action {
+            ControlsKeyAction::VolumeStep { increase } => {
+                let delta = if increase { step } else { -step };
+                let adjust = |scale: &Scale, max_percent: f64| {
+                    scale.set_value((scale.value() + delta).clamp(0.0, max_percent));
+                };
+                match target {
+                    None => adjust(&controls.sink_volume_scale, controls.max_percent),
+                    Some(index) => {
+                        if let Some(row) = controls.sink_input_rows.borrow().get(&index) {
+                            adjust(&row.scale, row.max_percent);
+                        }
+                    }
+                }
+            }
+            ControlsKeyAction::ToggleMute => match target {
+                None => controls.sink_mute_button.emit_clicked(),
+                Some(index) => {
+                    let mute_button = controls
+                        .sink_input_rows
+                        .borrow()
+                        .get(&index)
+                        .map(|row| row.mute_button.clone());
+                    if let Some(mute_button) = mute_button {
+                        mute_button.emit_clicked();
+                    }
+                }
+            },
+            ControlsKeyAction::CycleTarget { backward } => {
+                let next = {
+                    let order = controls.sink_input_order.borrow();
+                    next_keyboard_target(target, &order, backward)
+                };
+                set_keyboard_target(&controls, next);
+            }
+        }
+        Propagation::Stop
+    });
+    controls_ui.popover.add_controller(keys);
+}
+
+fn controls_key_action(key: Key, modifiers: ModifierType) -> Option<ControlsKeyAction> {
+    match key {
+        Key::Up | Key::KP_Up => Some(ControlsKeyAction::VolumeStep { increase: true }),
+        Key::Down | Key::KP_Down => Some(ControlsKeyAction::VolumeStep { increase: false }),
+        Key::m | Key::M => Some(ControlsKeyAction::ToggleMute),
+        Key::ISO_Left_Tab => Some(ControlsKeyAction::CycleTarget { backward: true }),
+        Key::Tab | Key::KP_Tab => Some(ControlsKeyAction::CycleTarget {
+            backward: modifiers.contains(ModifierType::SHIFT_MASK),
+        }),
+        _ => None,
+    }
+}
+
+/// Cycles main volume row → streams in display order → main volume row.
+/// A target whose stream went away restarts from the main row.
+fn next_keyboard_target(current: Option<u32>, streams: &[u32], backward: bool) -> Option<u32> {
+    let targets = std::iter::once(None)
+        .chain(streams.iter().copied().map(Some))
+        .collect::<Vec<_>>();
+    let position = targets
+        .iter()
+        .position(|target| *target == current)
+        .unwrap_or(0);
+    let next = if backward {
+        (position + targets.len() - 1) % targets.len()
+    } else {
+        (position + 1) % targets.len()
+    };
+    targets[next]
+}
+
+fn set_keyboard_target(controls_ui: &PulseAudioControlsUi, target: Option<u32>) {
+    controls_ui.keyboard_target.set(target);
+    if target.is_none() {
+        controls_ui.sink_row.add_css_class(KEYBOARD_SELECTED_CLASS);
+    } else {
+        controls_ui
+            .sink_row
+            .remove_css_class(KEYBOARD_SELECTED_CLASS);
+    }
+    for (index, row) in controls_ui.sink_input_rows.borrow().iter() {
+        if target == Some(*index) {
+            row.row.add_css_class(KEYBOARD_SELECTED_CLASS);
+        } else {
+            row.row.remove_css_class(KEYBOARD_SELECTED_CLASS);
+        }
     }
 }
 
@@ -143,15 +310,22 @@ fn install_controls_open_gesture(
         PulseAudioControlsOpenMode::LeftClick => 1,
         PulseAudioControlsOpenMode::RightClick => 3,
     };
-    let click = GestureClick::builder().button(button).build();
-    let popover = popover.clone();
-    click.connect_pressed(move |_, _, _, _| {
-        if popover.is_visible() {
-            popover.popdown();
-        } else {
-            popover.popup();
+    let toggle_popover = {
+        let popover = popover.clone();
+        move || {
+            if popover.is_visible() {
+                popover.popdown();
+            } else {
+                popover.popup();
+            }
         }
-    });
+    };
+    if button == 3 {
+        let toggle_popover = toggle_popover.clone();
+        touch::attach_long_press(label, move |_, _| toggle_popover());
+    }
+    let click = GestureClick::builder().button(button).build();
+    click.connect_pressed(move |_, _, _, _| toggle_popover());
     label.add_controller(click);
 }
 
@@ -161,9 +335,9 @@ pub(super) fn refresh_controls_ui(
     worker_tx: mpsc::Sender<WorkerCommand>,
 ) {
     controls_ui.sink_mute_button.set_label(if state.sink_muted {
-        ICON_MUTED
+        ICON_MUTED.get()
     } else {
-        ICON_VOLUME_HIGH
+        ICON_VOLUME_HIGH.get()
     });
     controls_ui
         .sink_mute_button
@@ -176,7 +350,7 @@ pub(super) fn refresh_controls_ui(
         .store(true, Ordering::Relaxed);
     controls_ui
         .sink_volume_scale
-        .set_value((state.sink_volume as f64).min(CONTROLS_UI_MAX_PERCENT));
+        .set_value((state.sink_volume as f64).min(controls_ui.max_percent));
     controls_ui
         .suppress_sink_scale_callback
         .store(false, Ordering::Relaxed);
@@ -213,6 +387,7 @@ pub(super) fn refresh_controls_ui(
             if !sink.available {
                 button.set_sensitive(false);
             }
+            button.set_hexpand(true);
             let worker_tx_for_sink = worker_tx.clone();
             let sink_name = sink.name.clone();
             button.connect_clicked(move |_| {
@@ -220,7 +395,38 @@ pub(super) fn refresh_controls_ui(
                     sink_name: sink_name.clone(),
                 });
             });
-            controls_ui.sinks_box.append(&button);
+
+            let favorite_button = Button::with_label(if sink.preferred { "★" } else { "☆" });
+            favorite_button.add_css_class("pulseaudio-control-button");
+            favorite_button.add_css_class("pulseaudio-sink-favorite");
+            if sink.preferred {
+                favorite_button.add_css_class("active");
+            }
+            favorite_button.set_tooltip_text(Some(if sink.preferred {
+                "Remove from preferred outputs"
+            } else {
+                "Add to preferred outputs"
+            }));
+            let worker_tx_for_favorite = worker_tx.clone();
+            let sink_name = sink.name.clone();
+            favorite_button.connect_clicked(move |_| {
+                let _ = worker_tx_for_favorite.send(WorkerCommand::TogglePreferredSink {
+                    sink_name: sink_name.clone(),
+                });
+            });
+
+            let row = GtkBox::new(Orientation::Horizontal, 4);
+            row.add_css_class("pulseaudio-controls-device-row");
+            row.append(&button);
+            if let Some(test_sound) = &controls_ui.test_sound {
+                row.append(&build_test_sound_button(
+                    &sink.name,
+                    sink.available,
+                    Rc::clone(test_sound),
+                ));
+            }
+            row.append(&favorite_button);
+            controls_ui.sinks_box.append(&row);
         }
     }
 
@@ -256,6 +462,27 @@ pub(super) fn refresh_controls_ui(
     sync_sink_input_rows(controls_ui, state, worker_tx);
 }
 
+/// Plays `test_sound` on `sink_name` only, so the device can be told apart
+/// by ear.
+fn build_test_sound_button(sink_name: &str, available: bool, test_sound: Rc<str>) -> Button {
+    let button = Button::with_label(ICON_TEST_SOUND.get());
+    button.add_css_class("pulseaudio-control-button");
+    button.add_css_class("pulseaudio-sink-test");
+    button.set_tooltip_text(Some("Play a test sound on this device"));
+    button.set_sensitive(available);
+    let sink_name = sink_name.to_string();
+    button.connect_clicked(move |_| {
+        let result = std::process::Command::new("paplay")
+            .arg(format!("--device={sink_name}"))
+            .arg(&*test_sound)
+            .spawn();
+        if let Err(err) = result {
+            eprintln!("pulseaudio test sound failed to start paplay: {err}");
+        }
+    });
+    button
+}
+
 fn clear_box_children(container: &GtkBox) {
     while let Some(child) = container.first_child() {
         container.remove(&child);
@@ -278,10 +505,20 @@ fn sync_sink_input_rows(
         if wanted.contains(index) {
             true
         } else {
-            controls_ui.sink_inputs_box.remove(&row.row);
+            controls_ui.sink_inputs_box.remove(&row.container);
             false
         }
     });
+    *controls_ui.sink_input_order.borrow_mut() =
+        state.sink_inputs.iter().map(|input| input.index).collect();
+    if controls_ui
+        .keyboard_target
+        .get()
+        .is_some_and(|index| !wanted.contains(&index))
+    {
+        controls_ui.keyboard_target.set(None);
+        controls_ui.sink_row.add_css_class(KEYBOARD_SELECTED_CLASS);
+    }
 
     if state.sink_inputs.is_empty() {
         if controls_ui.sink_inputs_box.first_child().is_none() {
@@ -294,21 +531,27 @@ fn sync_sink_input_rows(
     }
 
     for input in &state.sink_inputs {
-        let row = rows
-            .entry(input.index)
-            .or_insert_with(|| build_sink_input_row(input.index, worker_tx.clone()));
+        let row = rows.entry(input.index).or_insert_with(|| {
+            build_sink_input_row(input.index, worker_tx.clone(), controls_ui.max_percent)
+        });
         update_sink_input_row(row, input);
-        if row.row.parent().is_none() {
-            controls_ui.sink_inputs_box.append(&row.row);
+        if row.container.parent().is_none() {
+            controls_ui.sink_inputs_box.append(&row.container);
         }
     }
 }
 
-fn build_sink_input_row(index: u32, worker_tx: mpsc::Sender<WorkerCommand>) -> SinkInputRowUi {
+fn build_sink_input_row(
+    index: u32,
+    worker_tx: mpsc::Sender<WorkerCommand>,
+    max_percent: f64,
+) -> SinkInputRowUi {
+    let container = GtkBox::new(Orientation::Vertical, 2);
     let row = GtkBox::new(Orientation::Horizontal, 6);
     row.add_css_class("pulseaudio-controls-input-row");
+    container.append(&row);
 
-    let mute_button = Button::with_label(ICON_VOLUME_HIGH);
+    let mute_button = Button::with_label(ICON_VOLUME_HIGH.get());
     mute_button.add_css_class("pulseaudio-control-button");
     row.append(&mute_button);
 
@@ -318,7 +561,7 @@ fn build_sink_input_row(index: u32, worker_tx: mpsc::Sender<WorkerCommand>) -> S
     name_label.set_xalign(0.0);
     row.append(&name_label);
 
-    let scale = Scale::with_range(Orientation::Horizontal, 0.0, CONTROLS_UI_MAX_PERCENT, 1.0);
+    let scale = Scale::with_range(Orientation::Horizontal, 0.0, max_percent, 1.0);
     scale.add_css_class("pulseaudio-volume-scale");
     scale.set_draw_value(false);
     scale.set_width_request(120);
@@ -328,6 +571,21 @@ fn build_sink_input_row(index: u32, worker_tx: mpsc::Sender<WorkerCommand>) -> S
     percent_label.add_css_class("pulseaudio-volume-percent");
     row.append(&percent_label);
 
+    let meter = DrawingArea::new();
+    meter.add_css_class("pulseaudio-stream-meter");
+    meter.set_content_height(STREAM_METER_HEIGHT);
+    meter.set_hexpand(true);
+    meter.set_can_target(false);
+    let meter_level = Rc::new(Cell::new(0.0_f32));
+    meter.set_draw_func({
+        let meter_level = Rc::clone(&meter_level);
+        move |area, context, width, height| {
+            let scale = f64::from(area.scale_factor());
+            draw_level_bar(area, context, width, height, meter_level.get(), scale);
+        }
+    });
+    container.append(&meter);
+
     let muted_state = Arc::new(AtomicBool::new(false));
     let drag_active = Arc::new(AtomicBool::new(false));
     let drag_gesture = GestureClick::new();
@@ -351,7 +609,7 @@ fn build_sink_input_row(index: u32, worker_tx: mpsc::Sender<WorkerCommand>) -> S
         let suppress_scale_callback = suppress_scale_callback.clone();
         let percent_label = percent_label.clone();
         scale.connect_value_changed(move |scale| {
-            let percent = scale.value().round().clamp(0.0, CONTROLS_UI_MAX_PERCENT) as u32;
+            let percent = scale.value().round().clamp(0.0, max_percent) as u32;
             percent_label.set_text(&format!("{percent}%"));
             if suppress_scale_callback.load(Ordering::Relaxed) {
                 return;
@@ -373,6 +631,7 @@ fn build_sink_input_row(index: u32, worker_tx: mpsc::Sender<WorkerCommand>) -> S
     }
 
     SinkInputRowUi {
+        container,
         row,
         mute_button,
         name_label,
@@ -381,14 +640,24 @@ fn build_sink_input_row(index: u32, worker_tx: mpsc::Sender<WorkerCommand>) -> S
         muted_state,
         suppress_scale_callback,
         drag_active,
+        max_percent,
+        meter,
+        meter_level,
+    }
+}
+
+fn set_stream_meter_level(row: &SinkInputRowUi, level: f32) {
+    if (row.meter_level.get() - level).abs() > f32::EPSILON {
+        row.meter_level.set(level);
+        row.meter.queue_draw();
     }
 }
 
 fn update_sink_input_row(row: &SinkInputRowUi, input: &super::SinkInputEntry) {
     row.mute_button.set_label(if input.muted {
-        ICON_MUTED
+        ICON_MUTED.get()
     } else {
-        ICON_VOLUME_HIGH
+        ICON_VOLUME_HIGH.get()
     });
     row.muted_state.store(input.muted, Ordering::Relaxed);
     row.name_label.set_text(&input.name);
@@ -396,10 +665,54 @@ fn update_sink_input_row(row: &SinkInputRowUi, input: &super::SinkInputEntry) {
         row.percent_label.set_text(&format!("{}%", input.volume));
         row.suppress_scale_callback.store(true, Ordering::Relaxed);
         row.scale
-            .set_value((input.volume as f64).min(CONTROLS_UI_MAX_PERCENT));
+            .set_value((input.volume as f64).min(row.max_percent));
         row.suppress_scale_callback.store(false, Ordering::Relaxed);
     }
 
     row.mute_button
         .set_tooltip_text(Some(&format!("Mute {}", input.name)));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn controls_key_action_maps_volume_mute_and_tab() {
+        let none = ModifierType::empty();
+        assert_eq!(
+            controls_key_action(Key::Up, none),
+            Some(ControlsKeyAction::VolumeStep { increase: true })
+        );
+        assert_eq!(
+            controls_key_action(Key::Down, none),
+            Some(ControlsKeyAction::VolumeStep { increase: false })
+        );
+        assert_eq!(
+            controls_key_action(Key::m, none),
+            Some(ControlsKeyAction::ToggleMute)
+        );
+        assert_eq!(
+            controls_key_action(Key::Tab, none),
+            Some(ControlsKeyAction::CycleTarget { backward: false })
+        );
+        assert_eq!(
+            controls_key_action(Key::ISO_Left_Tab, ModifierType::SHIFT_MASK),
+            Some(ControlsKeyAction::CycleTarget { backward: true })
+        );
+        assert_eq!(controls_key_action(Key::Escape, none), None);
+    }
+
+    #[test]
+    fn next_keyboard_target_cycles_through_streams() {
+        let streams = [7, 3];
+        assert_eq!(next_keyboard_target(None, &streams, false), Some(7));
+        assert_eq!(next_keyboard_target(Some(7), &streams, false), Some(3));
+        assert_eq!(next_keyboard_target(Some(3), &streams, false), None);
+        assert_eq!(next_keyboard_target(None, &streams, true), Some(3));
+        assert_eq!(next_keyboard_target(Some(7), &streams, true), None);
+
+        assert_eq!(next_keyboard_target(Some(42), &streams, false), Some(7));
+        assert_eq!(next_keyboard_target(None, &[], false), None);
+    }
+}
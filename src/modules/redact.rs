@@ -0,0 +1,119 @@
+//! Masking of configured shell commands that carry tokens or passwords
+//! (`redact: true`), so they stay out of module error text, logs, and
+//! `vibar inspect`.
+
+use super::output_cache::stable_hash;
+
+const REDACTED: &str = "<redacted>";
+/// Shorter command words (flags, short paths) are left alone so masking
+/// does not shred unrelated text. `NAME=value` values are always masked;
+/// shorter ones only where they stand alone as a word.
+const MIN_SECRET_LEN: usize = 6;
+
+/// Names a command in messages. The short hash tells several commands
+/// apart without revealing them.
+pub(crate) fn command_label(command: &str) -> String {
+    format!("<redacted {:04x}>", stable_hash(&command) & 0xffff)
+}
+
+/// Replaces `command`, its arguments, and `NAME=value` values in `text`.
+/// The program name is kept so errors still say what failed.
+pub(crate) fn mask_command(text: &str, command: &str) -> String {
+    let (mut secrets, short_values) = secret_fragments(command);
+    secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+    let text = secrets.into_iter().fold(text.to_string(), |text, secret| {
+        text.replace(secret, REDACTED)
+    });
+    short_values
+        .into_iter()
+        .fold(text, |text, value| replace_word(&text, value))
+}
+
+/// Fragments to mask anywhere, plus assignment values too short for that.
+fn secret_fragments(command: &str) -> (Vec<&str>, Vec<&str>) {
+    let mut fragments = vec![command.trim()];
+    let mut short_values = Vec::new();
+    let mut program_seen = false;
+    for word in command.split_whitespace() {
+        let word = word.trim_matches(|c| c == '\'' || c == '"');
+        let value = word.split_once('=').map(|(_, value)| value);
+        if value.is_none() && !program_seen {
+            program_seen = true;
+            continue;
+        }
+        fragments.push(word);
+        match value {
+            Some(value) if value.len() < MIN_SECRET_LEN && !value.is_empty() => {
+                short_values.push(value);
+            }
+            value => fragments.extend(value),
+        }
+    }
+    fragments.retain(|fragment| fragment.len() >= MIN_SECRET_LEN);
+    (fragments, short_values)
+}
+
+/// Replaces `word` where it is not part of a longer alphanumeric run.
+fn replace_word(text: &str, word: &str) -> String {
+    let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    let mut masked = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(word) {
+        let end = start + word.len();
+        let before = rest[..start].chars().next_back();
+        let after = rest[end..].chars().next();
+        masked.push_str(&rest[..start]);
+        if is_word_char(before) || is_word_char(after) {
+            masked.push_str(word);
+        } else {
+            masked.push_str(REDACTED);
+        }
+        rest = &rest[end..];
+    }
+    masked.push_str(rest);
+    masked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_command_hides_arguments_and_assignments() {
+        let command = "API_KEY=s3cr3t-key weather --token 'abcdef123456' -v";
+        assert_eq!(
+            mask_command("weather: 401 for token abcdef123456 (-v)", command),
+            "weather: 401 for token <redacted> (-v)"
+        );
+        assert_eq!(
+            mask_command("sh: 1: API_KEY=s3cr3t-key: denied", command),
+            "sh: 1: <redacted>: denied"
+        );
+        assert_eq!(mask_command(command, command), "<redacted>");
+        assert_eq!(mask_command("weather failed", command), "weather failed");
+    }
+
+    #[test]
+    fn mask_command_hides_short_assignment_values_as_words() {
+        let command = "PIN=4711 unlock --pin=42 -v";
+        assert_eq!(
+            mask_command("unlock: wrong pin 4711 (42 left, code 14711)", command),
+            "unlock: wrong pin <redacted> (<redacted> left, code 14711)"
+        );
+        assert_eq!(
+            mask_command("sh: PIN=4711: not found (-v)", command),
+            "sh: <redacted>: not found (-v)"
+        );
+    }
+
+    #[test]
+    fn command_label_does_not_contain_the_command() {
+        let label = command_label("curl -s https://example.com/?token=abcdef");
+        assert!(label.starts_with("<redacted "));
+        assert!(!label.contains("curl"));
+        assert_ne!(
+            label,
+            command_label("curl -s https://example.com/?token=ghijkl")
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Carries module widgets over a config reload.
+//!
+//! A module is identified by its `type` plus its optional `name`; modules
+//! sharing both are told apart by their order on the bar. When the reloaded
+//! bar for the same output has a module with the same identity and an
+//! unchanged config, the old widget moves into the new bar instead of being
+//! rebuilt, keeping its backend subscriptions and widget state.
+
+use std::collections::HashMap;
+
+use super::ModuleConfig;
+
+const NAME_KEY: &str = "name";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ModuleIdentity {
+    module_type: String,
+    name: Option<String>,
+    /// Position among earlier modules with the same type and name.
+    ordinal: usize,
+}
+
+/// Assigns identities in bar order (left, center, right).
+#[derive(Debug, Default)]
+struct IdentityCounter {
+    seen: HashMap<(String, Option<String>), usize>,
+}
+
+impl IdentityCounter {
+    fn next(&mut self, module: &ModuleConfig) -> ModuleIdentity {
+        let name = module
+            .config
+            .get(NAME_KEY)
+            .and_then(|value| value.as_str())
+            .map(str::to_string);
+        let ordinal = self
+            .seen
+            .entry((module.module_type.clone(), name.clone()))
+            .or_default();
+        let identity = ModuleIdentity {
+            module_type: module.module_type.clone(),
+            name,
+            ordinal: *ordinal,
+        };
+        *ordinal += 1;
+        identity
+    }
+}
+
+/// A built module as placed on a bar.
+#[derive(Debug, Clone)]
+pub(crate) struct PlacedModule<W> {
+    identity: ModuleIdentity,
+    config: ModuleConfig,
+    widget: W,
+}
+
+/// The modules of one bar while it is built: identities are assigned in
+/// the order modules are placed, and matching widgets come from `pool`.
+#[derive(Debug)]
+pub(crate) struct BarModules<W> {
+    counter: IdentityCounter,
+    pool: ModulePool<W>,
+    placed: Vec<PlacedModule<W>>,
+}
+
+impl<W: Clone> BarModules<W> {
+    pub(crate) fn new(pool: ModulePool<W>) -> Self {
+        Self {
+            counter: IdentityCounter::default(),
+            pool,
+            placed: Vec::new(),
+        }
+    }
+
+    /// Identifies the next module and returns its previous widget when it
+    /// can be reused. Pass the widget actually placed to [`Self::record`].
+    pub(crate) fn next(&mut self, config: &ModuleConfig) -> (ModuleIdentity, Option<W>) {
+        let identity = self.counter.next(config);
+        let reused = self.pool.take(&identity, config);
+        (identity, reused)
+    }
+
+    pub(crate) fn record(&mut self, identity: ModuleIdentity, config: &ModuleConfig, widget: &W) {
+        self.placed.push(PlacedModule {
+            identity,
+            config: config.clone(),
+            widget: widget.clone(),
+        });
+    }
+
+    pub(crate) fn into_placed(self) -> Vec<PlacedModule<W>> {
+        self.placed
+    }
+}
+
+/// Widgets of a bar being replaced, available to its successor.
+#[derive(Debug)]
+pub(crate) struct ModulePool<W> {
+    modules: HashMap<ModuleIdentity, (ModuleConfig, W)>,
+}
+
+impl<W> Default for ModulePool<W> {
+    fn default() -> Self {
+        Self {
+            modules: HashMap::new(),
+        }
+    }
+}
+
+impl<W> ModulePool<W> {
+    pub(crate) fn new(placed: Vec<PlacedModule<W>>) -> Self {
+        Self {
+            modules: placed
+                .into_iter()
+                .map(|module| (module.identity, (module.config, module.widget)))
+                .collect(),
+        }
+    }
+
+    /// Takes the widget built for `identity` when it was built from the same
+    /// config. Each widget is handed out at most once.
+    fn take(&mut self, identity: &ModuleIdentity, config: &ModuleConfig) -> Option<W> {
+        match self.modules.get(identity) {
+            Some((old_config, _)) if old_config == config => {
+                self.modules.remove(identity).map(|(_, widget)| widget)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map, Value};
+
+    use super::*;
+
+    fn module(value: Value) -> ModuleConfig {
+        serde_json::from_value(value).expect("module config should parse")
+    }
+
+    #[test]
+    fn identities_use_name_then_order() {
+        let mut counter = IdentityCounter::default();
+        let first = counter.next(&ModuleConfig::new("clock", Map::new()));
+        let second = counter.next(&ModuleConfig::new("clock", Map::new()));
+        let named = counter.next(&module(json!({ "type": "clock", "name": "utc" })));
+        assert_eq!(first.ordinal, 0);
+        assert_eq!(second.ordinal, 1);
+        assert_eq!(named.ordinal, 0);
+        assert_eq!(named.name.as_deref(), Some("utc"));
+        assert_ne!(first, named);
+    }
+
+    #[test]
+    fn reloaded_bar_reuses_only_unchanged_modules() {
+        let cpu = module(json!({ "type": "cpu", "interval_secs": 1 }));
+        let clock = module(json!({ "type": "clock", "name": "local" }));
+        let mut old = BarModules::new(ModulePool::default());
+        for (config, widget) in [(&cpu, "cpu-widget"), (&clock, "clock-widget")] {
+            let (identity, reused) = old.next(config);
+            assert_eq!(reused, None);
+            old.record(identity, config, &widget);
+        }
+
+        // Reordered, with the clock's format changed and a second cpu added.
+        let changed_clock = module(json!({ "type": "clock", "name": "local", "format": "%H" }));
+        let mut new = BarModules::new(ModulePool::new(old.into_placed()));
+        assert_eq!(new.next(&changed_clock).1, None);
+        assert_eq!(new.next(&cpu).1, Some("cpu-widget"));
+        assert_eq!(new.next(&cpu).1, None);
+    }
+}
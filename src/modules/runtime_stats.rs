@@ -0,0 +1,84 @@
+//! Process-wide counters describing vibar's own backend/UI activity.
+//!
+//! Recorded by [`crate::modules::broadcaster`] and read by diagnostics
+//! consumers such as the metrics textfile exporter.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+static BACKENDS_STARTED: AtomicU64 = AtomicU64::new(0);
+static BACKENDS_STOPPED: AtomicU64 = AtomicU64::new(0);
+static BROADCASTS: AtomicU64 = AtomicU64::new(0);
+static UI_UPDATES: AtomicU64 = AtomicU64::new(0);
+static UI_UPDATE_NANOS: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RuntimeStats {
+    pub(crate) backends_started: u64,
+    pub(crate) backends_stopped: u64,
+    pub(crate) broadcasts: u64,
+    pub(crate) ui_updates: u64,
+    pub(crate) ui_update_time: Duration,
+}
+
+impl RuntimeStats {
+    pub(crate) fn active_backends(&self) -> u64 {
+        self.backends_started.saturating_sub(self.backends_stopped)
+    }
+}
+
+pub(crate) fn record_backend_started() {
+    BACKENDS_STARTED.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn record_backend_stopped() {
+    BACKENDS_STOPPED.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn record_broadcast() {
+    BROADCASTS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn record_ui_update(elapsed: Duration) {
+    UI_UPDATES.fetch_add(1, Ordering::Relaxed);
+    UI_UPDATE_NANOS.fetch_add(
+        u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX),
+        Ordering::Relaxed,
+    );
+}
+
+pub(crate) fn snapshot() -> RuntimeStats {
+    RuntimeStats {
+        backends_started: BACKENDS_STARTED.load(Ordering::Relaxed),
+        backends_stopped: BACKENDS_STOPPED.load(Ordering::Relaxed),
+        broadcasts: BROADCASTS.load(Ordering::Relaxed),
+        ui_updates: UI_UPDATES.load(Ordering::Relaxed),
+        ui_update_time: Duration::from_nanos(UI_UPDATE_NANOS.load(Ordering::Relaxed)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn active_backends_never_underflows() {
+        let stats = RuntimeStats {
+            backends_started: 1,
+            backends_stopped: 3,
+            broadcasts: 0,
+            ui_updates: 0,
+            ui_update_time: Duration::ZERO,
+        };
+        assert_eq!(stats.active_backends(), 0);
+    }
+
+    #[test]
+    fn record_ui_update_accumulates_time() {
+        let before = snapshot();
+        record_ui_update(Duration::from_millis(2));
+        let after = snapshot();
+        assert!(after.ui_updates > before.ui_updates);
+        assert!(after.ui_update_time >= before.ui_update_time + Duration::from_millis(2));
+    }
+}
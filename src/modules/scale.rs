@@ -0,0 +1,90 @@
+//! Device-pixel helpers for fractional output scales (1.25x, 1.5x, ...).
+//!
+//! GTK lays widgets out in logical pixels, but text and custom drawing are
+//! rasterized at the output scale. Rounding Pango measurements to whole
+//! logical pixels can drop a fraction of a device pixel and clip the last
+//! glyph, so measurements are rounded up at device resolution instead.
+
+use gtk::gdk;
+use gtk::prelude::*;
+
+/// Output scale for `monitor`: the fractional `scale` property when GDK
+/// provides it (GTK 4.14+), otherwise the integer scale factor.
+pub(crate) fn monitor_scale(monitor: Option<&gdk::Monitor>) -> f64 {
+    let Some(monitor) = monitor else {
+        return 1.0;
+    };
+    let scale = if monitor.find_property("scale").is_some() {
+        monitor.property::<f64>("scale")
+    } else {
+        f64::from(monitor.scale_factor())
+    };
+    normalized_scale(scale)
+}
+
+fn normalized_scale(scale: f64) -> f64 {
+    if scale.is_finite() && scale > 0.0 {
+        scale
+    } else {
+        1.0
+    }
+}
+
+/// Converts Pango units to logical pixels without rounding.
+pub(crate) fn pango_units_to_px(units: i32) -> f64 {
+    f64::from(units) / f64::from(gtk::pango::SCALE)
+}
+
+/// Smallest whole logical size that still covers `logical_px` once rounded
+/// up to whole device pixels at `scale`.
+pub(crate) fn ceil_logical_px(logical_px: f64, scale: f64) -> i32 {
+    let scale = normalized_scale(scale);
+    let device_px = (logical_px * scale - 1e-6).ceil();
+    (device_px / scale - 1e-6).ceil().max(0.0) as i32
+}
+
+/// Snaps a logical coordinate to the nearest device pixel at `scale`.
+pub(crate) fn snap_to_device_px(logical_px: f64, scale: f64) -> f64 {
+    let scale = normalized_scale(scale);
+    (logical_px * scale).round() / scale
+}
+
+/// Logical `(width, height)` of `layout`, rounded up at device resolution.
+pub(crate) fn layout_size_px(layout: &gtk::pango::Layout, scale: f64) -> (i32, i32) {
+    let (width, height) = layout.size();
+    (
+        ceil_logical_px(pango_units_to_px(width), scale),
+        ceil_logical_px(pango_units_to_px(height), scale),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ceil_logical_px_covers_fractional_device_pixels() {
+        assert_eq!(ceil_logical_px(100.0, 1.0), 100);
+        assert_eq!(ceil_logical_px(100.2, 1.0), 101);
+        // 100.2 logical px at 1.5x = 150.3 device px -> 151 device px -> 100.67 logical.
+        assert_eq!(ceil_logical_px(100.2, 1.5), 101);
+        // 99.9 logical at 1.25x = 124.875 -> 125 device px -> exactly 100 logical.
+        assert_eq!(ceil_logical_px(99.9, 1.25), 100);
+        assert_eq!(ceil_logical_px(0.0, 1.5), 0);
+    }
+
+    #[test]
+    fn snap_to_device_px_rounds_at_scale() {
+        assert_eq!(snap_to_device_px(3.3, 1.0), 3.0);
+        assert_eq!(snap_to_device_px(3.3, 1.5), 5.0 / 1.5);
+        assert_eq!(snap_to_device_px(3.3, 2.0), 3.5);
+    }
+
+    #[test]
+    fn invalid_scale_falls_back_to_one() {
+        assert_eq!(normalized_scale(0.0), 1.0);
+        assert_eq!(normalized_scale(f64::NAN), 1.0);
+        assert_eq!(normalized_scale(1.25), 1.25);
+        assert_eq!(monitor_scale(None), 1.0);
+    }
+}
@@ -0,0 +1,632 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use gtk::prelude::*;
+use gtk::{DrawingArea, EventControllerMotion, Label, Orientation, Overlay, Popover, Widget};
+use serde::Deserialize;
+
+use crate::modules::animation::{ease_out_cubic, AnimationStep, FrameAnimation, Tween};
+use crate::modules::apply_css_classes;
+use crate::modules::scale::{ceil_logical_px, layout_size_px, snap_to_device_px};
+
+const SCROLL_SPEED_PX_PER_SEC: f64 = 48.0;
+const SCROLL_START_HOLD: Duration = Duration::from_millis(900);
+const SCROLL_END_HOLD: Duration = Duration::from_millis(700);
+const SCROLL_RESTART_HOLD: Duration = Duration::from_millis(700);
+const SCROLL_STOP_HOLD: Duration = Duration::from_millis(350);
+const SCROLL_RETURN_DURATION: Duration = Duration::from_millis(250);
+
+/// When overflowing text scrolls.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ScrollTextMode {
+    #[default]
+    Off,
+    #[serde(alias = "on-hover", alias = "on_hover", alias = "hover_only")]
+    Hover,
+    #[serde(alias = "while-open", alias = "while_open", alias = "on-open")]
+    Open,
+    Always,
+}
+
+/// Width-capped text viewport that scrolls overflowing text by pixel offset.
+///
+/// The text is drawn into a `DrawingArea` set as the child of the given
+/// `Overlay`. Short text shrinks the viewport to its content width; long text
+/// is clipped at `max-width` with a `…` cue and, depending on the mode,
+/// scrolls on a frame-clock animation.
+#[derive(Clone)]
+pub(crate) struct ScrollText {
+    root: gtk::glib::WeakRef<Overlay>,
+    width_limit_px: i32,
+    scale: f64,
+    area: DrawingArea,
+    mode: ScrollTextMode,
+    state: Rc<RefCell<ScrollTextState>>,
+    animation: FrameAnimation,
+}
+
+#[derive(Debug)]
+struct ScrollTextState {
+    full_text: String,
+    full_markup: String,
+    layout: Option<gtk::pango::Layout>,
+    content_width_px: f64,
+    viewport_width_px: i32,
+    text_height_px: i32,
+    offset_px: f64,
+    hover_active: bool,
+    open_active: bool,
+    hold_remaining: Option<Duration>,
+    waiting_restart: bool,
+    return_tween: Option<Tween>,
+}
+
+/// `max-width` of `0` disables width capping.
+pub(crate) fn normalize_width_chars(value: u32) -> Option<u32> {
+    if value == 0 {
+        return None;
+    }
+
+    Some(value)
+}
+
+impl ScrollText {
+    /// Builds the viewport inside `root`. `probe_classes` are the module's
+    /// base CSS classes, used to measure a fixed line height; `scale` is the
+    /// output scale used to round text measurements to device pixels.
+    pub(crate) fn new(
+        root: &Overlay,
+        max_width_chars: u32,
+        mode: ScrollTextMode,
+        scale: f64,
+        probe_classes: &[&str],
+        extra_classes: Option<&str>,
+    ) -> Self {
+        let area = DrawingArea::new();
+        area.add_css_class("scroll-text");
+        area.set_overflow(gtk::Overflow::Hidden);
+        area.set_focusable(false);
+        area.set_can_target(false);
+        area.set_hexpand(false);
+        area.set_halign(gtk::Align::Start);
+        area.set_vexpand(false);
+        area.set_valign(gtk::Align::Center);
+
+        let width_limit_px = width_px_for_widget(&area, max_width_chars, scale);
+        let viewport_width_px = 1;
+        let viewport_height_px = fixed_height_px_from_label_probe(probe_classes, extra_classes);
+        area.set_content_width(viewport_width_px);
+        area.set_content_height(viewport_height_px);
+        area.set_size_request(viewport_width_px, -1);
+
+        root.set_overflow(gtk::Overflow::Hidden);
+        root.set_size_request(viewport_width_px, -1);
+        root.set_hexpand(false);
+        root.set_halign(gtk::Align::Start);
+        root.set_valign(gtk::Align::Center);
+        root.set_child(Some(&area));
+
+        let state = Rc::new(RefCell::new(ScrollTextState {
+            full_text: String::new(),
+            full_markup: String::new(),
+            layout: None,
+            content_width_px: 0.0,
+            viewport_width_px,
+            text_height_px: 0,
+            offset_px: 0.0,
+            hover_active: false,
+            open_active: false,
+            hold_remaining: None,
+            waiting_restart: false,
+            return_tween: None,
+        }));
+
+        let animation = FrameAnimation::new(&area, {
+            let state = state.clone();
+            let area = area.downgrade();
+            move |elapsed| {
+                let Some(area) = area.upgrade() else {
+                    return AnimationStep::Idle;
+                };
+                let Ok(mut state) = state.try_borrow_mut() else {
+                    return AnimationStep::Running;
+                };
+                let (should_redraw, step) = advance_scroll(&mut state, mode, elapsed);
+                drop(state);
+                if should_redraw {
+                    area.queue_draw();
+                }
+                step
+            }
+        });
+
+        area.set_draw_func({
+            let state = state.clone();
+            move |area, context, width, height| {
+                let state = state.borrow();
+                let Some(layout) = state.layout.as_ref() else {
+                    return;
+                };
+                let y =
+                    snap_to_device_px(((height - state.text_height_px).max(0) as f64) / 2.0, scale);
+                let show_overflow_hint = should_show_overflow_hint(&state, mode);
+                let hint_width_px = if show_overflow_hint {
+                    overflow_hint_width_px(area, scale)
+                } else {
+                    0
+                };
+                let text_clip_width_px = (width - hint_width_px).max(1);
+
+                context.save().ok();
+                context.rectangle(0.0, 0.0, f64::from(text_clip_width_px), f64::from(height));
+                context.clip();
+
+                render_layout_at(area, context, -state.offset_px, y, layout);
+
+                if state.content_width_px > area.allocated_width() as f64 {
+                    let next_x = -state.offset_px + state.content_width_px + scroll_gap_px();
+                    if next_x < area.allocated_width() as f64 {
+                        render_layout_at(area, context, next_x, y, layout);
+                    }
+                }
+                context.restore().ok();
+
+                if show_overflow_hint {
+                    render_overflow_hint(area, context, y, scale);
+                }
+            }
+        });
+
+        Self {
+            root: root.downgrade(),
+            width_limit_px,
+            scale,
+            area,
+            mode,
+            state,
+            animation,
+        }
+    }
+
+    pub(crate) fn mode(&self) -> ScrollTextMode {
+        self.mode
+    }
+
+    pub(crate) fn area(&self) -> &DrawingArea {
+        &self.area
+    }
+
+    /// Updates the text, restarting the scroll if it changed. Returns whether
+    /// the text overflows the viewport.
+    pub(crate) fn set_text(&self, plain_text: &str, markup_text: &str) -> bool {
+        let should_reset = {
+            let state = self.state.borrow();
+            state.full_text != plain_text || state.full_markup != markup_text
+        };
+
+        if should_reset {
+            self.reset(plain_text, markup_text);
+            self.area.queue_draw();
+            self.animation.wake();
+        }
+
+        let state = self.state.borrow();
+        state.content_width_px > state.viewport_width_px as f64
+    }
+
+    /// Scrolls while the pointer is over `widget` (`hover` mode).
+    pub(crate) fn track_hover(&self, widget: &impl IsA<Widget>) {
+        let motion = EventControllerMotion::new();
+        {
+            let scroll = self.clone();
+            motion.connect_enter(move |_, _, _| {
+                scroll.set_active(|state| state.hover_active = true);
+            });
+        }
+        {
+            let scroll = self.clone();
+            motion.connect_leave(move |_| {
+                scroll.set_active(|state| {
+                    state.hover_active = false;
+                    stop_scroll(state);
+                });
+            });
+        }
+        widget.add_controller(motion);
+    }
+
+    /// Scrolls while `popover` is shown (`open` mode).
+    pub(crate) fn track_open(&self, popover: &Popover) {
+        {
+            let scroll = self.clone();
+            popover.connect_show(move |_| {
+                scroll.set_active(|state| state.open_active = true);
+            });
+        }
+        {
+            let scroll = self.clone();
+            popover.connect_hide(move |_| {
+                scroll.set_active(|state| {
+                    state.open_active = false;
+                    stop_scroll(state);
+                });
+            });
+        }
+    }
+
+    fn set_active(&self, update: impl FnOnce(&mut ScrollTextState)) {
+        if let Ok(mut state) = self.state.try_borrow_mut() {
+            update(&mut state);
+        }
+        self.animation.wake();
+    }
+
+    fn reset(&self, plain_text: &str, markup_text: &str) {
+        let layout = self.area.create_pango_layout(None);
+        match gtk::pango::parse_markup(markup_text, '\0') {
+            Ok((attrs, text, _)) => {
+                layout.set_text(&text);
+                layout.set_attributes(Some(&attrs));
+            }
+            Err(_) => {
+                layout.set_text(plain_text);
+                layout.set_attributes(None);
+            }
+        }
+        let (text_width_px, text_height_px) = layout_size_px(&layout, self.scale);
+        let content_width_px = text_width_px.max(1);
+        let viewport_width_px = content_width_px.min(self.width_limit_px);
+
+        let mut state = self.state.borrow_mut();
+        state.full_text = plain_text.to_string();
+        state.full_markup = markup_text.to_string();
+        state.layout = Some(layout);
+        state.content_width_px = content_width_px as f64;
+        state.viewport_width_px = viewport_width_px;
+        state.text_height_px = text_height_px.max(1);
+        state.offset_px = 0.0;
+        state.hold_remaining = Some(SCROLL_START_HOLD);
+        state.waiting_restart = false;
+        state.return_tween = None;
+
+        self.area.set_content_width(viewport_width_px);
+        self.area.set_size_request(viewport_width_px, -1);
+        if let Some(root) = self.root.upgrade() {
+            root.set_size_request(viewport_width_px, -1);
+        }
+    }
+}
+
+/// Text widget for label-style modules: a plain `Label` (ellipsized at
+/// `max-width`), or a [`ScrollText`] viewport when `scroll-text` is enabled.
+#[derive(Clone)]
+pub(crate) enum ModuleText {
+    Label(Label),
+    Scroll(Overlay, ScrollText),
+}
+
+impl ModuleText {
+    /// Builds the widget with `base_classes` plus the user `class`. Modules
+    /// without a popover only support `hover` and `always` scrolling.
+    pub(crate) fn new(
+        module_type: &str,
+        max_width: Option<u32>,
+        mode: ScrollTextMode,
+        scale: f64,
+        base_classes: &[&str],
+        extra_classes: Option<&str>,
+    ) -> Result<Self, String> {
+        let max_width = max_width.and_then(normalize_width_chars);
+        if mode == ScrollTextMode::Off {
+            let label = Label::new(None);
+            for class_name in base_classes {
+                label.add_css_class(class_name);
+            }
+            apply_css_classes(&label, extra_classes);
+            if let Some(max_width) = max_width {
+                label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+                label.set_max_width_chars(max_width as i32);
+            }
+            return Ok(Self::Label(label));
+        }
+
+        if mode == ScrollTextMode::Open {
+            return Err(format!(
+                "{module_type} scroll-text 'open' needs a popover; use 'hover' or 'always'"
+            ));
+        }
+        let Some(max_width) = max_width else {
+            return Err(format!("{module_type} scroll-text requires max-width"));
+        };
+
+        let root = Overlay::new();
+        for class_name in base_classes {
+            root.add_css_class(class_name);
+        }
+        apply_css_classes(&root, extra_classes);
+        let scroll = ScrollText::new(&root, max_width, mode, scale, base_classes, extra_classes);
+        if mode == ScrollTextMode::Hover {
+            scroll.track_hover(&root);
+        }
+        Ok(Self::Scroll(root, scroll))
+    }
+
+    pub(crate) fn widget(&self) -> Widget {
+        match self {
+            Self::Label(label) => label.clone().upcast(),
+            Self::Scroll(root, _) => root.clone().upcast(),
+        }
+    }
+
+    pub(crate) fn set_markup(&self, markup: &str) {
+        match self {
+            Self::Label(label) => label.set_markup(markup),
+            Self::Scroll(_, scroll) => {
+                let plain = gtk::pango::parse_markup(markup, '\0')
+                    .map(|(_, text, _)| text.to_string())
+                    .unwrap_or_else(|_| markup.to_string());
+                scroll.set_text(&plain, markup);
+            }
+        }
+    }
+}
+
+/// Eases the text back to its start position after hover/open ends.
+fn stop_scroll(state: &mut ScrollTextState) {
+    state.return_tween = (state.offset_px != 0.0)
+        .then(|| Tween::new(state.offset_px, 0.0, SCROLL_RETURN_DURATION, ease_out_cubic));
+    state.hold_remaining = Some(SCROLL_STOP_HOLD);
+    state.waiting_restart = false;
+}
+
+/// Advances the scroll by one frame. Returns whether a redraw is needed and
+/// whether the animation should keep ticking.
+fn advance_scroll(
+    state: &mut ScrollTextState,
+    mode: ScrollTextMode,
+    elapsed: Duration,
+) -> (bool, AnimationStep) {
+    if let Some(tween) = state.return_tween.as_mut() {
+        state.offset_px = tween.advance(elapsed);
+        if tween.is_finished() {
+            state.return_tween = None;
+        }
+        return (true, AnimationStep::Running);
+    }
+
+    let active = match mode {
+        ScrollTextMode::Off => false,
+        ScrollTextMode::Hover => state.hover_active,
+        ScrollTextMode::Open => state.open_active,
+        ScrollTextMode::Always => true,
+    };
+    if !active {
+        return (false, AnimationStep::Idle);
+    }
+
+    if state.full_text.is_empty() || state.content_width_px <= state.viewport_width_px as f64 {
+        let should_redraw = state.offset_px != 0.0;
+        state.offset_px = 0.0;
+        state.hold_remaining = None;
+        state.waiting_restart = false;
+        return (should_redraw, AnimationStep::Idle);
+    }
+
+    if let Some(remaining) = state.hold_remaining {
+        if remaining > elapsed {
+            state.hold_remaining = Some(remaining - elapsed);
+            return (false, AnimationStep::Running);
+        }
+        state.hold_remaining = None;
+        if state.waiting_restart {
+            state.offset_px = 0.0;
+            state.waiting_restart = false;
+            state.hold_remaining = Some(SCROLL_RESTART_HOLD);
+            return (true, AnimationStep::Running);
+        }
+    }
+
+    state.offset_px += SCROLL_SPEED_PX_PER_SEC * elapsed.as_secs_f64();
+    let loop_distance = state.content_width_px + scroll_gap_px();
+    if state.offset_px >= loop_distance {
+        state.offset_px = loop_distance;
+        state.waiting_restart = true;
+        state.hold_remaining = Some(SCROLL_END_HOLD);
+    }
+    (true, AnimationStep::Running)
+}
+
+fn width_px_for_widget(widget: &impl IsA<Widget>, width_chars: u32, scale: f64) -> i32 {
+    let sample = "M".repeat(width_chars as usize);
+    let layout = widget.create_pango_layout(Some(sample.as_str()));
+    let (pixel_width, _) = layout_size_px(&layout, scale);
+    pixel_width.max(1)
+}
+
+fn fixed_height_px_from_label_probe(probe_classes: &[&str], extra_classes: Option<&str>) -> i32 {
+    let probe = Label::new(Some("Mg"));
+    for class_name in probe_classes {
+        probe.add_css_class(class_name);
+    }
+    apply_css_classes(&probe, extra_classes);
+    probe.set_wrap(false);
+    probe.set_single_line_mode(true);
+
+    let (_, natural, _, _) = probe.measure(Orientation::Vertical, -1);
+    natural.max(1)
+}
+
+fn scroll_gap_px() -> f64 {
+    42.0
+}
+
+fn should_show_overflow_hint(state: &ScrollTextState, mode: ScrollTextMode) -> bool {
+    let is_overflowing = state.content_width_px > state.viewport_width_px as f64;
+    if !is_overflowing {
+        return false;
+    }
+
+    match mode {
+        ScrollTextMode::Off => true,
+        ScrollTextMode::Hover => !state.hover_active,
+        ScrollTextMode::Open => !state.open_active,
+        ScrollTextMode::Always => false,
+    }
+}
+
+fn overflow_hint_width_px(area: &DrawingArea, scale: f64) -> i32 {
+    let layout = area.create_pango_layout(Some("…"));
+    let (width, _) = layout_size_px(&layout, scale);
+    width.max(1) + ceil_logical_px(4.0, scale)
+}
+
+fn render_overflow_hint(
+    area: &DrawingArea,
+    context: &gtk::cairo::Context,
+    text_y: f64,
+    scale: f64,
+) {
+    let hint = "…";
+    let layout = area.create_pango_layout(Some(hint));
+    let (hint_width, _) = layout_size_px(&layout, scale);
+    let x = f64::from((area.allocated_width() - hint_width - 1).max(0));
+    render_layout_at(area, context, x, text_y, &layout);
+}
+
+#[allow(deprecated)]
+fn render_layout_at(
+    area: &DrawingArea,
+    context: &gtk::cairo::Context,
+    x: f64,
+    y: f64,
+    layout: &gtk::pango::Layout,
+) {
+    gtk::render_layout(&area.style_context(), context, x, y, layout);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overflowing_state() -> ScrollTextState {
+        ScrollTextState {
+            full_text: "a long title".to_string(),
+            full_markup: "a long title".to_string(),
+            layout: None,
+            content_width_px: 200.0,
+            viewport_width_px: 100,
+            text_height_px: 10,
+            offset_px: 0.0,
+            hover_active: false,
+            open_active: false,
+            hold_remaining: None,
+            waiting_restart: false,
+            return_tween: None,
+        }
+    }
+
+    #[test]
+    fn normalize_width_chars_rejects_zero() {
+        assert_eq!(normalize_width_chars(0), None);
+        assert_eq!(normalize_width_chars(1), Some(1));
+    }
+
+    #[test]
+    fn mode_parses_aliases() {
+        let parse = |value: &str| {
+            serde_json::from_value::<ScrollTextMode>(serde_json::Value::String(value.to_string()))
+                .expect("mode should parse")
+        };
+        assert_eq!(parse("off"), ScrollTextMode::Off);
+        assert_eq!(parse("on-hover"), ScrollTextMode::Hover);
+        assert_eq!(parse("while_open"), ScrollTextMode::Open);
+        assert_eq!(parse("always"), ScrollTextMode::Always);
+    }
+
+    #[test]
+    fn scroll_goes_idle_when_inactive_or_text_fits() {
+        let mut state = overflowing_state();
+        let step = advance_scroll(&mut state, ScrollTextMode::Hover, Duration::from_millis(16));
+        assert_eq!(step, (false, AnimationStep::Idle));
+
+        state.content_width_px = 50.0;
+        let step = advance_scroll(
+            &mut state,
+            ScrollTextMode::Always,
+            Duration::from_millis(16),
+        );
+        assert_eq!(step, (false, AnimationStep::Idle));
+    }
+
+    #[test]
+    fn scroll_advances_by_frame_delta_after_hold() {
+        let mut state = overflowing_state();
+        state.hold_remaining = Some(Duration::from_millis(100));
+
+        let (redraw, _) = advance_scroll(
+            &mut state,
+            ScrollTextMode::Always,
+            Duration::from_millis(60),
+        );
+        assert!(!redraw);
+        assert_eq!(state.offset_px, 0.0);
+
+        advance_scroll(
+            &mut state,
+            ScrollTextMode::Always,
+            Duration::from_millis(500),
+        );
+        assert!(state.hold_remaining.is_none());
+        assert!((state.offset_px - SCROLL_SPEED_PX_PER_SEC * 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn scroll_holds_at_loop_end_then_restarts() {
+        let mut state = overflowing_state();
+        advance_scroll(&mut state, ScrollTextMode::Always, Duration::from_secs(60));
+        assert_eq!(state.offset_px, 200.0 + scroll_gap_px());
+        assert!(state.waiting_restart);
+
+        advance_scroll(&mut state, ScrollTextMode::Always, SCROLL_END_HOLD);
+        assert_eq!(state.offset_px, 0.0);
+        assert!(!state.waiting_restart);
+        assert_eq!(state.hold_remaining, Some(SCROLL_RESTART_HOLD));
+    }
+
+    #[test]
+    fn stopped_scroll_eases_back_to_start() {
+        let mut state = overflowing_state();
+        state.offset_px = 80.0;
+        stop_scroll(&mut state);
+
+        let (redraw, step) = advance_scroll(
+            &mut state,
+            ScrollTextMode::Hover,
+            Duration::from_millis(100),
+        );
+        assert!(redraw);
+        assert_eq!(step, AnimationStep::Running);
+        assert!(state.offset_px > 0.0 && state.offset_px < 80.0);
+
+        advance_scroll(&mut state, ScrollTextMode::Hover, SCROLL_RETURN_DURATION);
+        assert_eq!(state.offset_px, 0.0);
+        assert!(state.return_tween.is_none());
+        assert_eq!(
+            advance_scroll(&mut state, ScrollTextMode::Hover, Duration::ZERO),
+            (false, AnimationStep::Idle)
+        );
+    }
+
+    #[test]
+    fn overflow_hint_hidden_while_scrolling() {
+        let mut state = overflowing_state();
+        assert!(should_show_overflow_hint(&state, ScrollTextMode::Off));
+        assert!(!should_show_overflow_hint(&state, ScrollTextMode::Always));
+        state.hover_active = true;
+        assert!(!should_show_overflow_hint(&state, ScrollTextMode::Hover));
+        state.content_width_px = 10.0;
+        assert!(!should_show_overflow_hint(&state, ScrollTextMode::Off));
+    }
+}
@@ -0,0 +1,307 @@
+use std::fs;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::memory::format_bytes;
+use crate::modules::{
+    escape_markup_text, render_markup_template, runtime_stats, ModuleBuildContext, ModuleConfig,
+    ModuleLabel,
+};
+
+use super::ModuleFactory;
+
+const MIN_SELF_INTERVAL_SECS: u32 = 1;
+const DEFAULT_SELF_INTERVAL_SECS: u32 = 5;
+const DEFAULT_SELF_FORMAT: &str = "vibar {cpu}% {rss}";
+const PROC_SELF_STAT: &str = "/proc/self/stat";
+const PROC_SELF_STATUS: &str = "/proc/self/status";
+pub(crate) const MODULE_TYPE: &str = "self";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct SelfMonitorConfig {
+    #[serde(default = "default_self_format")]
+    pub(crate) format: String,
+    #[serde(default = "default_self_interval")]
+    pub(crate) interval_secs: u32,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProcessStatus {
+    rss_bytes: u64,
+    threads: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ProcessSample {
+    cpu_ticks: u64,
+    taken_at: Instant,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SelfSharedKey {
+    format: String,
+    interval_secs: u32,
+}
+
+pub(crate) struct SelfMonitorFactory;
+
+pub(crate) const FACTORY: SelfMonitorFactory = SelfMonitorFactory;
+
+impl ModuleFactory for SelfMonitorFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_self_module(parsed).upcast())
+    }
+}
+
+fn default_self_format() -> String {
+    DEFAULT_SELF_FORMAT.to_string()
+}
+
+fn default_self_interval() -> u32 {
+    DEFAULT_SELF_INTERVAL_SECS
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<SelfMonitorConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn self_registry() -> &'static BackendRegistry<SelfSharedKey, Broadcaster<String>> {
+    static REGISTRY: OnceLock<BackendRegistry<SelfSharedKey, Broadcaster<String>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_self(format: String, interval_secs: u32) -> Subscription<String> {
+    let key = SelfSharedKey {
+        format,
+        interval_secs,
+    };
+
+    let (broadcaster, start_worker) = self_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_self_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_self_worker(key: SelfSharedKey, broadcaster: Arc<Broadcaster<String>>) {
+    let interval = Duration::from_secs(u64::from(key.interval_secs));
+    let ticks_per_sec = clock_ticks_per_sec();
+    std::thread::spawn(move || {
+        let mut previous: Option<ProcessSample> = None;
+
+        loop {
+            let text = match read_process_sample().and_then(|sample| {
+                let status = read_process_status()?;
+                Ok((sample, status))
+            }) {
+                Ok((sample, status)) => {
+                    let cpu = previous
+                        .map(|prev| cpu_percentage_between(prev, sample, ticks_per_sec))
+                        .unwrap_or(0.0);
+                    previous = Some(sample);
+                    render_format(
+                        &key.format,
+                        cpu,
+                        status,
+                        runtime_stats::snapshot().active_backends(),
+                    )
+                }
+                Err(err) => escape_markup_text(&format!("self error: {err}")),
+            };
+
+            broadcaster.broadcast(text);
+            if broadcaster.subscriber_count() == 0 {
+                self_registry().remove(&key, &broadcaster);
+                return;
+            }
+            std::thread::sleep(interval);
+        }
+    });
+}
+
+fn build_self_module(config: SelfMonitorConfig) -> Label {
+    let click_command = config.click.or(config.on_click);
+    let label = ModuleLabel::new("self")
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(click_command)
+        .into_label();
+
+    let interval_secs = config.interval_secs.max(MIN_SELF_INTERVAL_SECS);
+    let subscription = subscribe_shared_self(config.format, interval_secs);
+
+    attach_subscription(&label, subscription, |label, text| {
+        let visible = !text.trim().is_empty();
+        label.set_visible(visible);
+        if visible {
+            label.set_markup(&text);
+        }
+    });
+
+    label
+}
+
+fn clock_ticks_per_sec() -> u64 {
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    u64::try_from(ticks)
+        .ok()
+        .filter(|ticks| *ticks > 0)
+        .unwrap_or(100)
+}
+
+fn read_process_sample() -> Result<ProcessSample, String> {
+    let content = fs::read_to_string(PROC_SELF_STAT)
+        .map_err(|err| format!("failed to read {PROC_SELF_STAT}: {err}"))?;
+    let cpu_ticks = parse_stat_cpu_ticks(&content)
+        .ok_or_else(|| format!("failed to parse {PROC_SELF_STAT}"))?;
+    Ok(ProcessSample {
+        cpu_ticks,
+        taken_at: Instant::now(),
+    })
+}
+
+fn read_process_status() -> Result<ProcessStatus, String> {
+    let content = fs::read_to_string(PROC_SELF_STATUS)
+        .map_err(|err| format!("failed to read {PROC_SELF_STATUS}: {err}"))?;
+    parse_status(&content).ok_or_else(|| format!("failed to parse {PROC_SELF_STATUS}"))
+}
+
+/// Returns `utime + stime` from `/proc/<pid>/stat`. Fields are counted after
+/// the parenthesized command name, which may itself contain spaces.
+fn parse_stat_cpu_ticks(content: &str) -> Option<u64> {
+    let (_, after_comm) = content.rsplit_once(')')?;
+    let mut fields = after_comm.split_whitespace().skip(11);
+    let utime = fields.next()?.parse::<u64>().ok()?;
+    let stime = fields.next()?.parse::<u64>().ok()?;
+    Some(utime + stime)
+}
+
+fn parse_status(content: &str) -> Option<ProcessStatus> {
+    let mut rss_kib = None;
+    let mut threads = None;
+    for line in content.lines() {
+        if let Some(value) = line.strip_prefix("VmRSS:") {
+            rss_kib = value.split_whitespace().next()?.parse::<u64>().ok();
+        } else if let Some(value) = line.strip_prefix("Threads:") {
+            threads = value.trim().parse::<u64>().ok();
+        }
+    }
+    Some(ProcessStatus {
+        rss_bytes: rss_kib? * 1024,
+        threads: threads?,
+    })
+}
+
+/// CPU usage relative to a single core (like `top`), so multi-threaded
+/// bursts can exceed 100%.
+fn cpu_percentage_between(prev: ProcessSample, current: ProcessSample, ticks_per_sec: u64) -> f64 {
+    let elapsed = current.taken_at.duration_since(prev.taken_at).as_secs_f64();
+    if elapsed <= 0.0 || ticks_per_sec == 0 {
+        return 0.0;
+    }
+    let ticks = current.cpu_ticks.saturating_sub(prev.cpu_ticks) as f64;
+    (ticks / ticks_per_sec as f64) / elapsed * 100.0
+}
+
+fn render_format(format: &str, cpu: f64, status: ProcessStatus, workers: u64) -> String {
+    render_markup_template(
+        format,
+        &[
+            ("{cpu}", &format!("{cpu:.1}")),
+            ("{rss}", &format_bytes(status.rss_bytes)),
+            ("{threads}", &status.threads.to_string()),
+            ("{workers}", &workers.to_string()),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("cpu", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'self'"));
+    }
+
+    #[test]
+    fn parse_stat_cpu_ticks_handles_spaces_in_command_name() {
+        let stat = "1234 (vibar (main) x) S 1 1234 1234 0 -1 4194560 100 0 0 0 250 50 0 0 20 0 12";
+        assert_eq!(parse_stat_cpu_ticks(stat), Some(300));
+    }
+
+    #[test]
+    fn parse_status_reads_rss_and_threads() {
+        let status = "Name:\tvibar\nVmRSS:\t   40960 kB\nThreads:\t14\n";
+        assert_eq!(
+            parse_status(status),
+            Some(ProcessStatus {
+                rss_bytes: 40960 * 1024,
+                threads: 14,
+            })
+        );
+    }
+
+    #[test]
+    fn cpu_percentage_between_uses_elapsed_time() {
+        let start = Instant::now();
+        let prev = ProcessSample {
+            cpu_ticks: 100,
+            taken_at: start,
+        };
+        let current = ProcessSample {
+            cpu_ticks: 150,
+            taken_at: start + Duration::from_secs(2),
+        };
+        let usage = cpu_percentage_between(prev, current, 100);
+        assert!((usage - 25.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn render_format_replaces_placeholders() {
+        let text = render_format(
+            "{cpu}% {rss} {threads}t {workers}w",
+            1.24,
+            ProcessStatus {
+                rss_bytes: 40 * 1024 * 1024,
+                threads: 14,
+            },
+            6,
+        );
+        assert_eq!(text, "1.2% 40M 14t 6w");
+    }
+}
@@ -0,0 +1,163 @@
+//! Layout pseudo-modules: `spacer` inserts a fixed gap and `expand` takes
+//! all free space in its area, pushing the modules after it to the far edge.
+
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Orientation, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::text::TextSink;
+use crate::modules::{apply_css_classes, ModuleBuildContext, ModuleConfig, ModuleFactory};
+
+pub(crate) const SPACER_MODULE_TYPE: &str = "spacer";
+pub(crate) const EXPAND_MODULE_TYPE: &str = "expand";
+const DEFAULT_SPACER_WIDTH: i32 = 8;
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct SpacerConfig {
+    #[serde(default = "default_spacer_width")]
+    pub(crate) width: i32,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct ExpandConfig {
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+pub(crate) struct SpacerFactory;
+pub(crate) struct ExpandFactory;
+
+pub(crate) const SPACER_FACTORY: SpacerFactory = SpacerFactory;
+pub(crate) const EXPAND_FACTORY: ExpandFactory = ExpandFactory;
+
+impl ModuleFactory for SpacerFactory {
+    fn module_type(&self) -> &'static str {
+        SPACER_MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_spacer_config(config)?;
+        let widget = build_gap(SPACER_MODULE_TYPE, parsed.class.as_deref());
+        widget.set_size_request(parsed.width, -1);
+        Ok(widget.upcast())
+    }
+
+    /// Status lines have no notion of gaps; the spacer emits no block.
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        parse_spacer_config(config)?;
+        sink(None);
+        Ok(())
+    }
+}
+
+impl ModuleFactory for ExpandFactory {
+    fn module_type(&self) -> &'static str {
+        EXPAND_MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_expand_config(config)?;
+        let widget = build_gap(EXPAND_MODULE_TYPE, parsed.class.as_deref());
+        // Expansion propagates to the area box, so the area itself grows
+        // towards the center and this widget takes the extra width.
+        widget.set_hexpand(true);
+        Ok(widget.upcast())
+    }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        parse_expand_config(config)?;
+        sink(None);
+        Ok(())
+    }
+}
+
+fn default_spacer_width() -> i32 {
+    DEFAULT_SPACER_WIDTH
+}
+
+fn parse_spacer_config(module: &ModuleConfig) -> Result<SpacerConfig, String> {
+    let config: SpacerConfig = parse_typed_config(module, SPACER_MODULE_TYPE)?;
+    if config.width < 0 {
+        return Err(format!(
+            "invalid {SPACER_MODULE_TYPE} module config: field `width` must not be negative"
+        ));
+    }
+    Ok(config)
+}
+
+fn parse_expand_config(module: &ModuleConfig) -> Result<ExpandConfig, String> {
+    parse_typed_config(module, EXPAND_MODULE_TYPE)
+}
+
+fn parse_typed_config<T: serde::de::DeserializeOwned>(
+    module: &ModuleConfig,
+    module_type: &str,
+) -> Result<T, String> {
+    if module.module_type != module_type {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            module_type, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {module_type} module config: {err}"))
+}
+
+/// Empty box; carries no `.module` class so module padding and colors
+/// don't apply.
+fn build_gap(class_name: &str, extra_classes: Option<&str>) -> GtkBox {
+    let widget = GtkBox::new(Orientation::Horizontal, 0);
+    widget.add_css_class(class_name);
+    widget.set_focusable(false);
+    widget.set_focus_on_click(false);
+    widget.set_can_target(false);
+    apply_css_classes(&widget, extra_classes);
+    widget
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map};
+
+    use super::*;
+
+    fn module(value: Value) -> ModuleConfig {
+        serde_json::from_value(value).expect("module config should parse")
+    }
+
+    #[test]
+    fn parse_spacer_config_defaults_and_validates_width() {
+        let parsed = parse_spacer_config(&ModuleConfig::new(SPACER_MODULE_TYPE, Map::new()))
+            .expect("defaults should parse");
+        assert_eq!(parsed.width, DEFAULT_SPACER_WIDTH);
+        assert!(parsed.class.is_none());
+
+        let parsed = parse_spacer_config(&module(json!({
+            "type": "spacer",
+            "width": 24,
+            "class": "wide"
+        })))
+        .expect("config should parse");
+        assert_eq!(parsed.width, 24);
+        assert_eq!(parsed.class.as_deref(), Some("wide"));
+
+        let err = parse_spacer_config(&module(json!({ "type": "spacer", "width": -4 })))
+            .expect_err("negative width should fail");
+        assert!(err.contains("must not be negative"));
+    }
+
+    #[test]
+    fn parse_expand_config_checks_module_type() {
+        let parsed = parse_expand_config(&module(json!({ "type": "expand", "class": "push" })))
+            .expect("config should parse");
+        assert_eq!(parsed.class.as_deref(), Some("push"));
+
+        let err = parse_expand_config(&ModuleConfig::new(SPACER_MODULE_TYPE, Map::new()))
+            .expect_err("wrong module type should fail");
+        assert!(err.contains("expected module type 'expand'"));
+    }
+}
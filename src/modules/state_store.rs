@@ -0,0 +1,265 @@
+//! Process-wide store of published module placeholder values.
+//!
+//! Modules publish their latest values under their instance namespace (for
+//! example `cpu.used_percentage`, or `cpu#2.used_percentage` for a second
+//! `cpu` with a different config). The `group` header references them as
+//! `{cpu.used_percentage}` via [`render_bound_template`].
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Mutex, OnceLock};
+
+use serde_json::Value;
+
+use crate::modules::broadcaster::{Broadcaster, Subscription};
+use crate::modules::{escape_markup_text, ModuleConfig};
+
+const NAME_KEY: &str = "name";
+const GROUP_MODULE_TYPE: &str = "group";
+const GROUP_MODULES_KEYS: [&str; 2] = ["modules", "children"];
+
+/// `key -> value` pairs a shared worker sends along with its update. Each
+/// subscribing module publishes them under its own instance namespace, so
+/// instances with the same config still share one worker.
+pub(crate) type PublishedValues = Vec<(&'static str, String)>;
+
+/// Instances seen so far, per `(type, name)`.
+type InstanceCounts = HashMap<(String, Option<String>), usize>;
+
+/// Hands out instance namespaces in bar order. One per bar, shared by its
+/// modules and their `group` children, so two instances of a module type
+/// never publish over each other.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct InstanceNamespaces {
+    seen: Rc<RefCell<InstanceCounts>>,
+}
+
+impl InstanceNamespaces {
+    /// Namespace of the next module built on the bar.
+    pub(crate) fn next(&self, module: &ModuleConfig) -> String {
+        let name = module
+            .config
+            .get(NAME_KEY)
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let mut seen = self.seen.borrow_mut();
+        let ordinal = seen
+            .entry((module.module_type.clone(), name.clone()))
+            .or_default();
+        let namespace = instance_namespace(&module.module_type, name.as_deref(), *ordinal);
+        *ordinal += 1;
+        namespace
+    }
+
+    /// Counts a module kept from the previous bar, and its `group`
+    /// children, so modules built after it get the same namespaces as on a
+    /// fresh build.
+    pub(crate) fn skip(&self, module: &ModuleConfig) {
+        self.next(module);
+        if module.module_type != GROUP_MODULE_TYPE {
+            return;
+        }
+        let children = GROUP_MODULES_KEYS
+            .iter()
+            .find_map(|key| module.config.get(*key))
+            .and_then(Value::as_array);
+        for child in children.into_iter().flatten() {
+            if let Ok(child) = serde_json::from_value::<ModuleConfig>(child.clone()) {
+                self.skip(&child);
+            }
+        }
+    }
+}
+
+/// `<type>` for the first instance, `<type>#<n>` for the n-th; a `name`
+/// replaces the number (`<type>#<name>`, then `<type>#<name>#<n>`).
+fn instance_namespace(module_type: &str, name: Option<&str>, ordinal: usize) -> String {
+    let base = match name {
+        Some(name) => format!("{module_type}#{name}"),
+        None => module_type.to_string(),
+    };
+    if ordinal == 0 {
+        base
+    } else {
+        format!("{base}#{}", ordinal + 1)
+    }
+}
+
+struct StateStore {
+    values: Mutex<HashMap<String, String>>,
+    changes: Broadcaster<()>,
+}
+
+fn state_store() -> &'static StateStore {
+    static STORE: OnceLock<StateStore> = OnceLock::new();
+    STORE.get_or_init(|| StateStore {
+        values: Mutex::new(HashMap::new()),
+        changes: Broadcaster::new(),
+    })
+}
+
+/// Publishes `namespace.key` values; subscribers are notified only when at
+/// least one value actually changed.
+pub(crate) fn publish(namespace: &str, entries: &[(&str, String)]) {
+    let store = state_store();
+    let changed = {
+        let mut values = store.values.lock().expect("state store mutex poisoned");
+        let mut changed = false;
+        for (key, value) in entries {
+            let full_key = format!("{namespace}.{key}");
+            if values.get(&full_key) != Some(value) {
+                values.insert(full_key, value.clone());
+                changed = true;
+            }
+        }
+        changed
+    };
+
+    if changed {
+        store.changes.broadcast(());
+    }
+}
+
+pub(crate) fn subscribe_changes() -> Subscription<()> {
+    state_store().changes.subscribe()
+}
+
+/// Returns all published `namespace.key` values, sorted by key.
+pub(crate) fn snapshot() -> Vec<(String, String)> {
+    let mut entries = state_store()
+        .values
+        .lock()
+        .expect("state store mutex poisoned")
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect::<Vec<_>>();
+    entries.sort();
+    entries
+}
+
+/// Replaces `{namespace.key}` bindings with published values (markup-escaped).
+/// Bindings without a published value render empty; other `{...}` text is
+/// left untouched.
+pub(crate) fn render_bound_template(template: &str) -> String {
+    let values = state_store()
+        .values
+        .lock()
+        .expect("state store mutex poisoned");
+    render_bindings(template, &values)
+}
+
+fn render_bindings(template: &str, values: &HashMap<String, String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) if is_binding_key(&after[..end]) => {
+                if let Some(value) = values.get(&after[..end]) {
+                    rendered.push_str(&escape_markup_text(value));
+                }
+                rest = &after[end + 1..];
+            }
+            _ => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+fn is_binding_key(key: &str) -> bool {
+    let Some((namespace, field)) = key.split_once('.') else {
+        return false;
+    };
+    !namespace.is_empty()
+        && !field.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '/' | '.' | '#'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn render_bindings_replaces_known_keys() {
+        let values = values(&[("battery.percent", "81"), ("cpu.used_percentage", "12")]);
+        assert_eq!(
+            render_bindings("{battery.percent}% {cpu.used_percentage}%", &values),
+            "81% 12%"
+        );
+    }
+
+    #[test]
+    fn render_bindings_blanks_missing_keys_and_keeps_plain_placeholders() {
+        let values = values(&[]);
+        assert_eq!(
+            render_bindings("{} {icon} {memory.used}|{", &values),
+            "{} {icon} |{"
+        );
+    }
+
+    #[test]
+    fn render_bindings_escapes_values() {
+        let values = values(&[("battery.status", "<Full>")]);
+        assert_eq!(render_bindings("{battery.status}", &values), "&lt;Full&gt;");
+    }
+
+    #[test]
+    fn instance_namespaces_number_repeated_modules() {
+        let module = |value: serde_json::Value| {
+            serde_json::from_value::<ModuleConfig>(value).expect("module config should parse")
+        };
+        let namespaces = InstanceNamespaces::default();
+        let cpu = module(serde_json::json!({ "type": "cpu" }));
+        assert_eq!(namespaces.next(&cpu), "cpu");
+        assert_eq!(namespaces.next(&cpu), "cpu#2");
+        let gpu = module(serde_json::json!({ "type": "temperature", "name": "gpu" }));
+        assert_eq!(namespaces.next(&gpu), "temperature#gpu");
+        assert_eq!(namespaces.next(&gpu), "temperature#gpu#2");
+
+        let group = module(serde_json::json!({
+            "type": "group",
+            "modules": [{ "type": "cpu" }, { "type": "temperature" }]
+        }));
+        namespaces.skip(&group);
+        assert_eq!(namespaces.next(&cpu), "cpu#4");
+        assert_eq!(
+            namespaces.next(&module(serde_json::json!({ "type": "temperature" }))),
+            "temperature#2"
+        );
+    }
+
+    #[test]
+    fn instances_publish_separately() {
+        publish("state-store-instances", &[("value", "1".to_string())]);
+        publish("state-store-instances#2", &[("value", "2".to_string())]);
+        assert_eq!(
+            render_bound_template("{state-store-instances.value} {state-store-instances#2.value}"),
+            "1 2"
+        );
+    }
+
+    #[test]
+    fn publish_updates_rendered_values() {
+        publish("state-store-test", &[("value", "1".to_string())]);
+        assert_eq!(render_bound_template("{state-store-test.value}"), "1");
+        publish("state-store-test", &[("value", "2".to_string())]);
+        assert_eq!(render_bound_template("{state-store-test.value}"), "2");
+    }
+}
@@ -0,0 +1,425 @@
+use std::fs;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::{render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel};
+
+use super::cpu::{cpu_usage_between, read_cpu_snapshot, CpuSnapshot};
+use super::memory::{format_bytes, read_memory_status};
+use super::temperature::{read_temperature_reading, resolve_temperature_sensor_path};
+use super::ModuleFactory;
+
+const MIN_STATS_INTERVAL_SECS: u32 = 1;
+const DEFAULT_STATS_INTERVAL_SECS: u32 = 5;
+const DEFAULT_STATS_FORMAT: &str = "C:{cpu}% M:{mem}% {temp}°C";
+const UNAVAILABLE_VALUE: &str = "?";
+const NET_DEV_PATH: &str = "/proc/net/dev";
+pub(crate) const MODULE_TYPE: &str = "stats";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct StatsConfig {
+    #[serde(default = "default_stats_format")]
+    pub(crate) format: String,
+    #[serde(default = "default_stats_interval")]
+    pub(crate) interval_secs: u32,
+    #[serde(rename = "path", alias = "hwmon-path", alias = "hwmon_path", default)]
+    pub(crate) sensor_path: Option<String>,
+    #[serde(rename = "thermal-zone", alias = "thermal_zone", default)]
+    pub(crate) thermal_zone: Option<u32>,
+    #[serde(default)]
+    pub(crate) interface: Option<String>,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct StatsUpdate {
+    text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct StatsSharedKey {
+    format: String,
+    interval_secs: u32,
+    sensor_path: String,
+    interface: Option<String>,
+}
+
+/// Which metric sources the format references; unused sources are not read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StatsSources {
+    cpu: bool,
+    memory: bool,
+    temperature: bool,
+    network: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct NetCounters {
+    rx_bytes: u64,
+    tx_bytes: u64,
+}
+
+#[derive(Debug, Default)]
+struct StatsValues {
+    cpu: Option<u16>,
+    mem: Option<u16>,
+    mem_used: Option<String>,
+    temp: Option<i32>,
+    net_down: Option<String>,
+    net_up: Option<String>,
+}
+
+pub(crate) struct StatsFactory;
+
+pub(crate) const FACTORY: StatsFactory = StatsFactory;
+
+impl ModuleFactory for StatsFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_stats_module(parsed).upcast())
+    }
+}
+
+fn default_stats_format() -> String {
+    DEFAULT_STATS_FORMAT.to_string()
+}
+
+fn default_stats_interval() -> u32 {
+    DEFAULT_STATS_INTERVAL_SECS
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<StatsConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn normalized_stats_interval(interval_secs: u32) -> u32 {
+    interval_secs.max(MIN_STATS_INTERVAL_SECS)
+}
+
+fn stats_registry() -> &'static BackendRegistry<StatsSharedKey, Broadcaster<StatsUpdate>> {
+    static REGISTRY: OnceLock<BackendRegistry<StatsSharedKey, Broadcaster<StatsUpdate>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_stats(key: StatsSharedKey) -> Subscription<StatsUpdate> {
+    let (broadcaster, start_worker) = stats_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_stats_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_stats_worker(key: StatsSharedKey, broadcaster: Arc<Broadcaster<StatsUpdate>>) {
+    let interval = Duration::from_secs(u64::from(key.interval_secs));
+    let sources = sources_for_format(&key.format);
+
+    std::thread::spawn(move || {
+        let mut previous_cpu = sources.cpu.then(read_cpu_snapshot).and_then(Result::ok);
+        let mut previous_net = sources
+            .network
+            .then(|| read_net_counters(key.interface.as_deref()))
+            .and_then(Result::ok)
+            .map(|counters| (counters, Instant::now()));
+        if previous_cpu.is_some() || previous_net.is_some() {
+            // Rates need two samples; take the second one shortly after.
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        loop {
+            let mut values = StatsValues::default();
+
+            if sources.cpu {
+                let current = read_cpu_snapshot().ok();
+                values.cpu = cpu_percentage(previous_cpu, current);
+                previous_cpu = current;
+            }
+
+            if sources.memory {
+                if let Ok(status) = read_memory_status() {
+                    values.mem = Some(if status.total_bytes == 0 {
+                        0
+                    } else {
+                        ((status.used_bytes as f64 / status.total_bytes as f64) * 100.0).round()
+                            as u16
+                    });
+                    values.mem_used = Some(format_bytes(status.used_bytes));
+                }
+            }
+
+            if sources.temperature {
+                values.temp = read_temperature_reading(&key.sensor_path)
+                    .ok()
+                    .map(|reading| reading.celsius.round() as i32);
+            }
+
+            if sources.network {
+                let now = Instant::now();
+                let current = read_net_counters(key.interface.as_deref()).ok();
+                if let (Some((previous, sampled_at)), Some(current)) = (previous_net, current) {
+                    let (down, up) = net_rates(previous, current, now.duration_since(sampled_at));
+                    values.net_down = Some(format!("{}/s", format_bytes(down)));
+                    values.net_up = Some(format!("{}/s", format_bytes(up)));
+                }
+                previous_net = current.map(|counters| (counters, now));
+            }
+
+            broadcaster.broadcast(StatsUpdate {
+                text: render_format(&key.format, &values),
+            });
+            if broadcaster.subscriber_count() == 0 {
+                stats_registry().remove(&key, &broadcaster);
+                return;
+            }
+            std::thread::sleep(interval);
+        }
+    });
+}
+
+fn build_stats_module(config: StatsConfig) -> Label {
+    let label = ModuleLabel::new("stats")
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(config.click.or(config.on_click))
+        .into_label();
+
+    let effective_interval_secs = normalized_stats_interval(config.interval_secs);
+    if effective_interval_secs != config.interval_secs {
+        eprintln!(
+            "stats interval_secs={} is too low; clamping to {} second",
+            config.interval_secs, effective_interval_secs
+        );
+    }
+
+    let subscription = subscribe_shared_stats(StatsSharedKey {
+        format: config.format,
+        interval_secs: effective_interval_secs,
+        sensor_path: resolve_temperature_sensor_path(config.sensor_path, config.thermal_zone),
+        interface: config.interface,
+    });
+
+    attach_subscription(&label, subscription, |label, update| {
+        let visible = !update.text.trim().is_empty();
+        label.set_visible(visible);
+        if visible {
+            label.set_markup(&update.text);
+        }
+    });
+
+    label
+}
+
+fn sources_for_format(format: &str) -> StatsSources {
+    StatsSources {
+        cpu: format.contains("{cpu}"),
+        memory: format.contains("{mem}") || format.contains("{mem_used}"),
+        temperature: format.contains("{temp}"),
+        network: format.contains("{net_down}") || format.contains("{net_up}"),
+    }
+}
+
+fn cpu_percentage(previous: Option<CpuSnapshot>, current: Option<CpuSnapshot>) -> Option<u16> {
+    let (previous, current) = (previous?, current?);
+    Some(
+        cpu_usage_between(previous, current)
+            .clamp(0.0, 100.0)
+            .round() as u16,
+    )
+}
+
+fn read_net_counters(interface: Option<&str>) -> Result<NetCounters, String> {
+    let content = fs::read_to_string(NET_DEV_PATH)
+        .map_err(|err| format!("failed to read {NET_DEV_PATH}: {err}"))?;
+    parse_net_dev(&content, interface)
+}
+
+/// Sums receive/transmit byte counters from `/proc/net/dev`, either for one
+/// interface or for every non-loopback interface.
+fn parse_net_dev(content: &str, interface: Option<&str>) -> Result<NetCounters, String> {
+    let mut counters = NetCounters {
+        rx_bytes: 0,
+        tx_bytes: 0,
+    };
+    let mut matched = false;
+
+    for line in content.lines().skip(2) {
+        let Some((name, fields)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.trim();
+        let wanted = match interface {
+            Some(interface) => name == interface,
+            None => name != "lo",
+        };
+        if !wanted {
+            continue;
+        }
+
+        let fields = fields.split_whitespace().collect::<Vec<_>>();
+        let (Some(rx), Some(tx)) = (fields.first(), fields.get(8)) else {
+            continue;
+        };
+        let (Ok(rx), Ok(tx)) = (rx.parse::<u64>(), tx.parse::<u64>()) else {
+            continue;
+        };
+        counters.rx_bytes = counters.rx_bytes.saturating_add(rx);
+        counters.tx_bytes = counters.tx_bytes.saturating_add(tx);
+        matched = true;
+    }
+
+    if !matched {
+        return Err(match interface {
+            Some(interface) => format!("interface '{interface}' not found in {NET_DEV_PATH}"),
+            None => format!("no network interfaces found in {NET_DEV_PATH}"),
+        });
+    }
+    Ok(counters)
+}
+
+fn net_rates(previous: NetCounters, current: NetCounters, elapsed: Duration) -> (u64, u64) {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return (0, 0);
+    }
+    let rate =
+        |before: u64, after: u64| (after.saturating_sub(before) as f64 / secs).round() as u64;
+    (
+        rate(previous.rx_bytes, current.rx_bytes),
+        rate(previous.tx_bytes, current.tx_bytes),
+    )
+}
+
+fn render_format(format: &str, values: &StatsValues) -> String {
+    fn or_unavailable<T: ToString>(value: Option<&T>) -> String {
+        value
+            .map(ToString::to_string)
+            .unwrap_or_else(|| UNAVAILABLE_VALUE.to_string())
+    }
+
+    render_markup_template(
+        format,
+        &[
+            ("{cpu}", &or_unavailable(values.cpu.as_ref())),
+            ("{mem}", &or_unavailable(values.mem.as_ref())),
+            ("{mem_used}", &or_unavailable(values.mem_used.as_ref())),
+            ("{temp}", &or_unavailable(values.temp.as_ref())),
+            ("{net_down}", &or_unavailable(values.net_down.as_ref())),
+            ("{net_up}", &or_unavailable(values.net_up.as_ref())),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+
+    const NET_DEV_SAMPLE: &str = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:  5000      10    0    0    0     0          0         0     5000      10    0    0    0     0       0          0
+  eth0:  1000      10    0    0    0     0          0         0     2000      20    0    0    0     0       0          0
+ wlan0:   300       3    0    0    0     0          0         0      400       4    0    0    0     0       0          0
+";
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'stats'"));
+    }
+
+    #[test]
+    fn parse_config_uses_defaults() {
+        let module = ModuleConfig::new(MODULE_TYPE, Map::new());
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.format, DEFAULT_STATS_FORMAT);
+        assert_eq!(cfg.interval_secs, DEFAULT_STATS_INTERVAL_SECS);
+    }
+
+    #[test]
+    fn sources_for_format_only_enables_referenced_metrics() {
+        let sources = sources_for_format("C:{cpu}% {net_up}");
+        assert_eq!(
+            sources,
+            StatsSources {
+                cpu: true,
+                memory: false,
+                temperature: false,
+                network: true,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_net_dev_sums_non_loopback_interfaces() {
+        let counters = parse_net_dev(NET_DEV_SAMPLE, None).expect("should parse");
+        assert_eq!(counters.rx_bytes, 1300);
+        assert_eq!(counters.tx_bytes, 2400);
+    }
+
+    #[test]
+    fn parse_net_dev_filters_by_interface() {
+        let counters = parse_net_dev(NET_DEV_SAMPLE, Some("wlan0")).expect("should parse");
+        assert_eq!(counters.rx_bytes, 300);
+        assert_eq!(counters.tx_bytes, 400);
+        assert!(parse_net_dev(NET_DEV_SAMPLE, Some("eth9")).is_err());
+    }
+
+    #[test]
+    fn net_rates_divides_by_elapsed_time() {
+        let previous = NetCounters {
+            rx_bytes: 1000,
+            tx_bytes: 500,
+        };
+        let current = NetCounters {
+            rx_bytes: 3000,
+            tx_bytes: 500,
+        };
+        assert_eq!(
+            net_rates(previous, current, Duration::from_secs(2)),
+            (1000, 0)
+        );
+    }
+
+    #[test]
+    fn render_format_marks_missing_values() {
+        let values = StatsValues {
+            cpu: Some(12),
+            mem: Some(48),
+            ..StatsValues::default()
+        };
+        assert_eq!(
+            render_format("C:{cpu}% M:{mem}% {temp}°C", &values),
+            "C:12% M:48% ?°C"
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Visibility asked for by a sway `bar` block, for the top-level
+//! `sway-bar-id`: its `mode`, and the modifier reveal in `hide` mode. Not a
+//! module by itself.
+
+use std::env;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::modules::backend_errors;
+use crate::modules::broadcaster::{BackendRegistry, Broadcaster, Subscription};
+
+/// Name under which bar lookup and event failures are reported.
+const ERROR_BACKEND: &str = "sway-bar";
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+// Raw i3-ipc framing: swayipc's `BarConfig` drops `hidden_state`, so bar
+// configs and their update events are read as plain JSON.
+const IPC_MAGIC: &[u8; 6] = b"i3-ipc";
+const IPC_HEADER_LEN: usize = 14;
+const IPC_SUBSCRIBE: u32 = 2;
+const IPC_GET_BAR_CONFIG: u32 = 6;
+const IPC_EVENT_BARCONFIG_UPDATE: u32 = 0x8000_0004;
+const IPC_EVENT_BAR_STATE_UPDATE: u32 = 0x8000_0014;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SwayBarMode {
+    Dock,
+    Hide,
+    Invisible,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SwayBarState {
+    mode: SwayBarMode,
+    /// The bar's `hidden_state` is `show`, which keeps a `hide` bar up.
+    shown: bool,
+    /// The bar's `modifier` is held.
+    revealed: bool,
+}
+
+impl SwayBarState {
+    pub(crate) fn visible(self) -> bool {
+        match self.mode {
+            SwayBarMode::Dock => true,
+            SwayBarMode::Hide => self.shown || self.revealed,
+            SwayBarMode::Invisible => false,
+        }
+    }
+
+    /// Takes `mode` and `hidden_state` from a GET_BAR_CONFIG reply or a
+    /// `barconfig_update` event.
+    fn apply_config(&mut self, config: &Value) {
+        self.mode = match config.get("mode").and_then(Value::as_str) {
+            Some("hide") => SwayBarMode::Hide,
+            Some("invisible") => SwayBarMode::Invisible,
+            _ => SwayBarMode::Dock,
+        };
+        self.shown = config.get("hidden_state").and_then(Value::as_str) == Some("show");
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SwayBarSharedKey {
+    id: String,
+}
+
+fn sway_bar_registry() -> &'static BackendRegistry<SwayBarSharedKey, Broadcaster<SwayBarState>> {
+    static REGISTRY: OnceLock<BackendRegistry<SwayBarSharedKey, Broadcaster<SwayBarState>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+/// Follows the sway bar `id`; the first update arrives once its config has
+/// been read.
+pub(crate) fn subscribe_sway_bar_state(id: String) -> Subscription<SwayBarState> {
+    let key = SwayBarSharedKey { id };
+    let (broadcaster, start_worker) =
+        sway_bar_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_sway_bar_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_sway_bar_worker(key: SwayBarSharedKey, broadcaster: Arc<Broadcaster<SwayBarState>>) {
+    std::thread::spawn(move || loop {
+        if broadcaster.subscriber_count() == 0 {
+            sway_bar_registry().remove(&key, &broadcaster);
+            return;
+        }
+        if let Err(err) = follow_bar(&key.id, &broadcaster) {
+            backend_errors::report(ERROR_BACKEND, err);
+        }
+        std::thread::sleep(RETRY_DELAY);
+    });
+}
+
+/// Streams state changes for bar `id` until the connection drops or no one
+/// is listening.
+fn follow_bar(id: &str, broadcaster: &Broadcaster<SwayBarState>) -> Result<(), String> {
+    let path = env::var_os("SWAYSOCK")
+        .or_else(|| env::var_os("I3SOCK"))
+        .ok_or_else(|| "failed to connect to sway: SWAYSOCK is not set".to_string())?;
+    let mut stream =
+        UnixStream::connect(path).map_err(|err| format!("failed to connect to sway: {err}"))?;
+
+    let config = ipc_request(&mut stream, IPC_GET_BAR_CONFIG, id.as_bytes())
+        .map_err(|err| format!("failed to read sway bar '{id}': {err}"))?;
+    if config.get("id").and_then(Value::as_str) != Some(id) {
+        return Err(format!("no sway bar with id '{id}'"));
+    }
+    let subscribed = ipc_request(
+        &mut stream,
+        IPC_SUBSCRIBE,
+        br#"["barconfig_update","bar_state_update"]"#,
+    )
+    .map_err(|err| format!("failed to subscribe to bar events: {err}"))?;
+    if subscribed.get("success").and_then(Value::as_bool) != Some(true) {
+        return Err("failed to subscribe to bar events: rejected by sway".to_string());
+    }
+    backend_errors::clear(ERROR_BACKEND);
+
+    let mut state = SwayBarState {
+        mode: SwayBarMode::Dock,
+        shown: false,
+        revealed: false,
+    };
+    state.apply_config(&config);
+    broadcaster.broadcast(state);
+
+    loop {
+        let (kind, event) = read_ipc_message(&mut stream)
+            .map_err(|err| format!("bar event stream read failed: {err}"))?;
+        if broadcaster.subscriber_count() == 0 {
+            return Ok(());
+        }
+        if event.get("id").and_then(Value::as_str) != Some(id) {
+            continue;
+        }
+        match kind {
+            IPC_EVENT_BARCONFIG_UPDATE => state.apply_config(&event),
+            IPC_EVENT_BAR_STATE_UPDATE => {
+                state.revealed = event
+                    .get("visible_by_modifier")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+            }
+            _ => continue,
+        }
+        broadcaster.broadcast(state);
+    }
+}
+
+fn ipc_request(stream: &mut UnixStream, kind: u32, payload: &[u8]) -> io::Result<Value> {
+    stream.write_all(&encode_ipc_message(kind, payload))?;
+    let (_, reply) = read_ipc_message(stream)?;
+    Ok(reply)
+}
+
+fn encode_ipc_message(kind: u32, payload: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(IPC_HEADER_LEN + payload.len());
+    message.extend_from_slice(IPC_MAGIC);
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&kind.to_ne_bytes());
+    message.extend_from_slice(payload);
+    message
+}
+
+fn read_ipc_message(stream: &mut impl Read) -> io::Result<(u32, Value)> {
+    let mut header = [0_u8; IPC_HEADER_LEN];
+    stream.read_exact(&mut header)?;
+    if &header[..IPC_MAGIC.len()] != IPC_MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "missing i3-ipc magic",
+        ));
+    }
+    let len = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]) as usize;
+    let kind = u32::from_ne_bytes([header[10], header[11], header[12], header[13]]);
+    let mut payload = vec![0_u8; len];
+    stream.read_exact(&mut payload)?;
+    let value = serde_json::from_slice(&payload)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    Ok((kind, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn visibility_follows_mode_and_modifier() {
+        let mut state = SwayBarState {
+            mode: SwayBarMode::Dock,
+            shown: false,
+            revealed: false,
+        };
+        assert!(state.visible());
+
+        state.mode = SwayBarMode::Hide;
+        assert!(!state.visible());
+        state.revealed = true;
+        assert!(state.visible());
+
+        state.mode = SwayBarMode::Invisible;
+        assert!(!state.visible());
+    }
+
+    #[test]
+    fn hidden_state_show_keeps_a_hide_bar_visible() {
+        let mut state = SwayBarState {
+            mode: SwayBarMode::Dock,
+            shown: false,
+            revealed: false,
+        };
+        state.apply_config(&json!({ "id": "vibar", "mode": "hide", "hidden_state": "show" }));
+        assert_eq!(state.mode, SwayBarMode::Hide);
+        assert!(state.visible());
+
+        state.apply_config(&json!({ "id": "vibar", "mode": "hide", "hidden_state": "hide" }));
+        assert!(!state.visible());
+    }
+
+    #[test]
+    fn read_ipc_message_decodes_framed_events() {
+        let message = encode_ipc_message(
+            IPC_EVENT_BAR_STATE_UPDATE,
+            br#"{"id":"vibar","visible_by_modifier":true}"#,
+        );
+        let (kind, event) =
+            read_ipc_message(&mut message.as_slice()).expect("framed message should decode");
+        assert_eq!(kind, IPC_EVENT_BAR_STATE_UPDATE);
+        assert_eq!(event["visible_by_modifier"], json!(true));
+    }
+}
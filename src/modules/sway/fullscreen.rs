@@ -0,0 +1,116 @@
+//! Outputs that currently show a fullscreen window, for bar-level settings
+//! such as `background-opacity-fullscreen`. Not a module by itself.
+
+use std::collections::BTreeSet;
+use std::sync::{Arc, OnceLock};
+
+use swayipc::{EventType, Node, NodeType};
+
+use crate::modules::broadcaster::{BackendRegistry, Broadcaster, Subscription};
+
+use super::ipc::{query_snapshot, recv_relevant_event_coalesced, subscribe_shared_events};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct FullscreenOutputs {
+    outputs: BTreeSet<String>,
+    /// A global fullscreen window spans every output.
+    global: bool,
+}
+
+impl FullscreenOutputs {
+    pub(crate) fn covers(&self, connector: Option<&str>) -> bool {
+        self.global || connector.is_some_and(|connector| self.outputs.contains(connector))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FullscreenSharedKey;
+
+fn fullscreen_registry(
+) -> &'static BackendRegistry<FullscreenSharedKey, Broadcaster<FullscreenOutputs>> {
+    static REGISTRY: OnceLock<
+        BackendRegistry<FullscreenSharedKey, Broadcaster<FullscreenOutputs>>,
+    > = OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+pub(crate) fn subscribe_fullscreen_outputs() -> Subscription<FullscreenOutputs> {
+    let (broadcaster, start_worker) =
+        fullscreen_registry().get_or_create(FullscreenSharedKey, Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_fullscreen_worker(broadcaster);
+    }
+
+    receiver
+}
+
+fn start_fullscreen_worker(broadcaster: Arc<Broadcaster<FullscreenOutputs>>) {
+    std::thread::spawn(move || {
+        let mut last = query_fullscreen_outputs();
+        broadcaster.broadcast(last.clone());
+        let events = subscribe_shared_events();
+
+        loop {
+            if broadcaster.subscriber_count() == 0 {
+                fullscreen_registry().remove(&FullscreenSharedKey, &broadcaster);
+                return;
+            }
+
+            match recv_relevant_event_coalesced(
+                &events,
+                &[EventType::Window, EventType::Workspace, EventType::Output],
+            ) {
+                Ok(true) => {
+                    let current = query_fullscreen_outputs();
+                    if current != last {
+                        broadcaster.broadcast(current.clone());
+                        last = current;
+                    }
+                }
+                Ok(false) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            }
+        }
+    });
+}
+
+fn query_fullscreen_outputs() -> FullscreenOutputs {
+    let mut fullscreen = FullscreenOutputs::default();
+    if let Some(tree) = query_snapshot().tree.as_ref() {
+        collect_fullscreen_outputs(tree, None, &mut fullscreen);
+    }
+    fullscreen
+}
+
+fn collect_fullscreen_outputs(
+    node: &Node,
+    current_output: Option<&str>,
+    fullscreen: &mut FullscreenOutputs,
+) {
+    let output = if node.node_type == NodeType::Output {
+        node.name.as_deref()
+    } else {
+        current_output
+    };
+
+    // Mode 1 fills the node's workspace, mode 2 all outputs; both only
+    // matter while the window is actually on screen.
+    if node.visible == Some(true) {
+        match node.fullscreen_mode {
+            Some(1) => {
+                if let Some(output) = output {
+                    fullscreen.outputs.insert(output.to_string());
+                }
+            }
+            Some(2) => fullscreen.global = true,
+            _ => {}
+        }
+    }
+
+    for child in node.nodes.iter().chain(&node.floating_nodes) {
+        collect_fullscreen_outputs(child, output, fullscreen);
+    }
+}
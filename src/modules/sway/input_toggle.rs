@@ -0,0 +1,399 @@
+use std::sync::{Arc, OnceLock};
+
+use gtk::prelude::*;
+use gtk::{GestureClick, Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+use swayipc::{EventType, SendEvents};
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::sway::ipc::{
+    query_with_connection, quote_sway_arg, recv_relevant_event_coalesced, subscribe_shared_events,
+};
+use crate::modules::{
+    escape_markup_text, render_markup_template, ModuleBuildContext, ModuleConfig, ModuleFactory,
+    ModuleLabel,
+};
+
+const DEFAULT_FORMAT: &str = "{icon}";
+const INPUT_STATE_CLASSES: [&str; 3] = ["input-enabled", "input-disabled", "input-unknown"];
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct InputToggleConfig {
+    pub(crate) identifier: String,
+    #[serde(default = "default_format")]
+    pub(crate) format: String,
+    #[serde(rename = "format-icons", alias = "format_icons", default)]
+    pub(crate) format_icons: InputToggleIcons,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct InputToggleIcons {
+    #[serde(default = "default_enabled_icon")]
+    pub(crate) enabled: String,
+    #[serde(default = "default_disabled_icon")]
+    pub(crate) disabled: String,
+}
+
+impl Default for InputToggleIcons {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled_icon(),
+            disabled: default_disabled_icon(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputState {
+    Enabled,
+    Disabled,
+    DisabledOnExternalMouse,
+}
+
+impl InputState {
+    fn from_send_events(value: SendEvents) -> Self {
+        match value {
+            SendEvents::Enabled => Self::Enabled,
+            SendEvents::Disabled => Self::Disabled,
+            _ => Self::DisabledOnExternalMouse,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Enabled => "enabled",
+            Self::Disabled => "disabled",
+            Self::DisabledOnExternalMouse => "disabled_on_external_mouse",
+        }
+    }
+
+    fn css_class(self) -> &'static str {
+        match self {
+            Self::Enabled => "input-enabled",
+            Self::Disabled | Self::DisabledOnExternalMouse => "input-disabled",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct InputToggleUpdate {
+    text: String,
+    state_class: &'static str,
+    visible: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct InputToggleSharedKey {
+    identifier: String,
+    format: String,
+    format_icons: InputToggleIcons,
+}
+
+pub(crate) struct SwayInputToggleFactory;
+
+pub(crate) const FACTORY: SwayInputToggleFactory = SwayInputToggleFactory;
+pub(crate) const MODULE_TYPE: &str = "sway/input-toggle";
+
+impl ModuleFactory for SwayInputToggleFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        let click_command = parsed.click.or(parsed.on_click);
+        Ok(build_input_toggle_module(
+            InputToggleSharedKey {
+                identifier: parsed.identifier,
+                format: parsed.format,
+                format_icons: parsed.format_icons,
+            },
+            click_command,
+            parsed.class,
+        )
+        .upcast())
+    }
+}
+
+fn default_format() -> String {
+    DEFAULT_FORMAT.to_string()
+}
+
+fn default_enabled_icon() -> String {
+    "󰟸".to_string()
+}
+
+fn default_disabled_icon() -> String {
+    "󰤳".to_string()
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<InputToggleConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn input_toggle_registry(
+) -> &'static BackendRegistry<InputToggleSharedKey, Broadcaster<InputToggleUpdate>> {
+    static REGISTRY: OnceLock<
+        BackendRegistry<InputToggleSharedKey, Broadcaster<InputToggleUpdate>>,
+    > = OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_input_toggle(key: InputToggleSharedKey) -> Subscription<InputToggleUpdate> {
+    let (broadcaster, start_worker) =
+        input_toggle_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_input_toggle_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_input_toggle_worker(
+    key: InputToggleSharedKey,
+    broadcaster: Arc<Broadcaster<InputToggleUpdate>>,
+) {
+    std::thread::spawn(move || {
+        broadcaster.broadcast(query_input_state(&key));
+        let events = subscribe_shared_events();
+
+        loop {
+            if broadcaster.subscriber_count() == 0 {
+                input_toggle_registry().remove(&key, &broadcaster);
+                return;
+            }
+
+            match recv_relevant_event_coalesced(&events, &[EventType::Input]) {
+                Ok(true) => {
+                    broadcaster.broadcast(query_input_state(&key));
+                }
+                Ok(false) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            }
+        }
+    });
+}
+
+fn query_input_state(key: &InputToggleSharedKey) -> InputToggleUpdate {
+    let Some(inputs) = query_with_connection("input-toggle", "get_inputs query", |connection| {
+        connection.get_inputs()
+    }) else {
+        return InputToggleUpdate {
+            text: escape_markup_text("sway?"),
+            state_class: "input-unknown",
+            visible: true,
+        };
+    };
+
+    let state = inputs
+        .iter()
+        .filter(|input| selector_matches(&key.identifier, &input.identifier, &input.input_type))
+        .find_map(|input| {
+            input
+                .libinput
+                .as_ref()
+                .and_then(|libinput| libinput.send_events)
+        })
+        .map(InputState::from_send_events);
+
+    let Some(state) = state else {
+        // Device not present (for example an unplugged tablet): hide the module.
+        return InputToggleUpdate {
+            text: String::new(),
+            state_class: "input-unknown",
+            visible: false,
+        };
+    };
+
+    let text = render_input_format(&key.format, state, &key.format_icons, &key.identifier);
+    InputToggleUpdate {
+        visible: !text.trim().is_empty(),
+        text,
+        state_class: state.css_class(),
+    }
+}
+
+/// Matches a configured selector against a sway input using the same rules
+/// as sway's `input <identifier>` criteria (`*`, `type:<type>`, or exact id).
+pub(super) fn selector_matches(selector: &str, identifier: &str, input_type: &str) -> bool {
+    if selector == "*" {
+        return true;
+    }
+    if let Some(expected_type) = selector.strip_prefix("type:") {
+        return expected_type == input_type;
+    }
+    selector == identifier
+}
+
+fn render_input_format(
+    format: &str,
+    state: InputState,
+    icons: &InputToggleIcons,
+    identifier: &str,
+) -> String {
+    let icon = match state {
+        InputState::Enabled => icons.enabled.as_str(),
+        InputState::Disabled | InputState::DisabledOnExternalMouse => icons.disabled.as_str(),
+    };
+    render_markup_template(
+        format,
+        &[
+            ("{icon}", icon),
+            ("{state}", state.label()),
+            ("{identifier}", identifier),
+        ],
+    )
+}
+
+fn toggle_input_events(identifier: String) {
+    std::thread::spawn(move || {
+        let command = toggle_input_events_command(&identifier);
+        let _ = query_with_connection("input-toggle", "input toggle command", |connection| {
+            connection.run_command(&command)
+        });
+    });
+}
+
+fn toggle_input_events_command(identifier: &str) -> String {
+    format!(
+        "input \"{}\" events toggle enabled disabled",
+        quote_sway_arg(identifier)
+    )
+}
+
+fn build_input_toggle_module(
+    key: InputToggleSharedKey,
+    click_command: Option<String>,
+    class: Option<String>,
+) -> Label {
+    let label = ModuleLabel::new("sway-input-toggle")
+        .with_css_classes(class.as_deref())
+        .with_click_command(click_command.clone())
+        .into_label();
+
+    if click_command.is_none() {
+        label.add_css_class("clickable");
+        let click = GestureClick::builder().button(1).build();
+        let identifier = key.identifier.clone();
+        click.connect_pressed(move |_, _, _, _| {
+            toggle_input_events(identifier.clone());
+        });
+        label.add_controller(click);
+    }
+
+    let subscription = subscribe_shared_input_toggle(key);
+
+    attach_subscription(&label, subscription, |label, update| {
+        label.set_visible(update.visible);
+        if update.visible {
+            label.set_markup(&update.text);
+        }
+        for class_name in INPUT_STATE_CLASSES {
+            label.remove_css_class(class_name);
+        }
+        label.add_css_class(update.state_class);
+    });
+
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map};
+
+    use super::*;
+
+    #[test]
+    fn toggle_input_events_command_escapes_identifier() {
+        assert_eq!(
+            toggle_input_events_command("type:touchpad"),
+            "input \"type:touchpad\" events toggle enabled disabled"
+        );
+        assert_eq!(
+            toggle_input_events_command("a\"; exit; \\"),
+            "input \"a\\\"; exit; \\\\\" events toggle enabled disabled"
+        );
+    }
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'sway/input-toggle'"));
+    }
+
+    #[test]
+    fn parse_config_requires_identifier() {
+        let module = ModuleConfig::new(MODULE_TYPE, Map::new());
+        let err = parse_config(&module).expect_err("missing identifier should fail");
+        assert!(err.contains("identifier"));
+    }
+
+    #[test]
+    fn parse_config_merges_partial_icons_with_defaults() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "identifier": "type:touchpad",
+                "format-icons": { "disabled": "off" }
+            }))
+            .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.format_icons.disabled, "off");
+        assert_eq!(cfg.format_icons.enabled, default_enabled_icon());
+    }
+
+    #[test]
+    fn selector_matches_supports_type_and_wildcard() {
+        assert!(selector_matches(
+            "type:touchpad",
+            "1:2:Touchpad",
+            "touchpad"
+        ));
+        assert!(!selector_matches("type:touchpad", "1:2:Pen", "tablet_tool"));
+        assert!(selector_matches("1:2:Pen", "1:2:Pen", "tablet_tool"));
+        assert!(selector_matches("*", "1:2:Pen", "tablet_tool"));
+    }
+
+    #[test]
+    fn render_input_format_uses_state_icon() {
+        let icons = InputToggleIcons {
+            enabled: "on".to_string(),
+            disabled: "off".to_string(),
+        };
+        assert_eq!(
+            render_input_format("{icon} {state}", InputState::Enabled, &icons, "pad"),
+            "on enabled"
+        );
+        assert_eq!(
+            render_input_format(
+                "{icon} {identifier}",
+                InputState::DisabledOnExternalMouse,
+                &icons,
+                "pad"
+            ),
+            "off pad"
+        );
+    }
+}
@@ -5,8 +5,11 @@ use std::{
 
 use swayipc::{Connection, Event, EventType, Node, Workspace};
 
+use crate::modules::backend_errors;
 use crate::modules::broadcaster::BackendRegistry;
 
+/// Name under which event stream failures are reported.
+const ERROR_BACKEND: &str = "sway";
 const CONNECT_RETRY_DELAY: Duration = Duration::from_millis(500);
 const STREAM_END_RETRY_DELAY: Duration = Duration::from_millis(200);
 const EVENT_IDLE_TIMEOUT: Duration = Duration::from_millis(500);
@@ -17,6 +20,7 @@ const GLOBAL_EVENT_TYPES: &[EventType] = &[
     EventType::Output,
     EventType::Mode,
     EventType::Window,
+    EventType::Input,
 ];
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -135,7 +139,10 @@ pub(crate) fn run_event_loop<FShouldStop, FOnEvent>(
         let connection = match Connection::new() {
             Ok(conn) => conn,
             Err(err) => {
-                debug_log(module, &format!("failed to connect for events: {err}"));
+                backend_errors::report(
+                    ERROR_BACKEND,
+                    format!("failed to connect for events: {err}"),
+                );
                 std::thread::sleep(CONNECT_RETRY_DELAY);
                 continue;
             }
@@ -144,11 +151,15 @@ pub(crate) fn run_event_loop<FShouldStop, FOnEvent>(
         let stream = match connection.subscribe(event_types) {
             Ok(stream) => stream,
             Err(err) => {
-                debug_log(module, &format!("failed to subscribe to events: {err}"));
+                backend_errors::report(
+                    ERROR_BACKEND,
+                    format!("failed to subscribe to events: {err}"),
+                );
                 std::thread::sleep(CONNECT_RETRY_DELAY);
                 continue;
             }
         };
+        backend_errors::clear(ERROR_BACKEND);
 
         for event in stream {
             if should_stop() {
@@ -162,7 +173,10 @@ pub(crate) fn run_event_loop<FShouldStop, FOnEvent>(
                     }
                 }
                 Err(err) => {
-                    debug_log(module, &format!("event stream read failed: {err}"));
+                    backend_errors::report(
+                        ERROR_BACKEND,
+                        format!("event stream read failed: {err}"),
+                    );
                     break;
                 }
             }
@@ -291,6 +305,11 @@ where
     }
 }
 
+/// Escapes `value` for use inside a double-quoted sway command argument.
+pub(crate) fn quote_sway_arg(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 fn ensure_connection(
     connection: &mut Option<Connection>,
     module: &str,
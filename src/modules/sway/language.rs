@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+
+use gtk::prelude::*;
+use gtk::{GestureClick, Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+use swayipc::EventType;
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::sway::input_toggle::selector_matches;
+use crate::modules::sway::ipc::{
+    query_with_connection, quote_sway_arg, recv_relevant_event_coalesced, subscribe_shared_events,
+};
+use crate::modules::{
+    escape_markup_text, render_markup_template, ModuleBuildContext, ModuleConfig, ModuleFactory,
+    ModuleLabel,
+};
+
+const DEFAULT_FORMAT: &str = "{short}";
+const DEFAULT_IDENTIFIER: &str = "type:keyboard";
+pub(crate) const HIDE_WITHOUT_SWAY_KEY: &str = "hide-without-sway";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct LanguageConfig {
+    #[serde(default = "default_identifier")]
+    pub(crate) identifier: String,
+    #[serde(default = "default_format")]
+    pub(crate) format: String,
+    #[serde(rename = "short-names", alias = "short_names", default)]
+    pub(crate) short_names: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+    /// Hides the module instead of showing `sway?` while sway IPC is
+    /// unavailable, for configs shared with other compositors.
+    #[serde(rename = "hide-without-sway", alias = "hide_without_sway", default)]
+    pub(crate) hide_without_sway: bool,
+}
+
+#[derive(Debug, Clone)]
+struct LanguageUpdate {
+    text: String,
+    unknown: bool,
+    visible: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct LanguageSharedKey {
+    identifier: String,
+    format: String,
+    short_names: BTreeMap<String, String>,
+}
+
+pub(crate) struct SwayLanguageFactory;
+
+pub(crate) const FACTORY: SwayLanguageFactory = SwayLanguageFactory;
+pub(crate) const MODULE_TYPE: &str = "sway/language";
+
+impl ModuleFactory for SwayLanguageFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        let click_command = parsed.click.or(parsed.on_click);
+        Ok(build_language_module(
+            LanguageSharedKey {
+                identifier: parsed.identifier,
+                format: parsed.format,
+                short_names: parsed.short_names,
+            },
+            click_command,
+            parsed.class,
+            parsed.hide_without_sway,
+        )
+        .upcast())
+    }
+}
+
+fn default_format() -> String {
+    DEFAULT_FORMAT.to_string()
+}
+
+fn default_identifier() -> String {
+    DEFAULT_IDENTIFIER.to_string()
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<LanguageConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn language_registry() -> &'static BackendRegistry<LanguageSharedKey, Broadcaster<LanguageUpdate>> {
+    static REGISTRY: OnceLock<BackendRegistry<LanguageSharedKey, Broadcaster<LanguageUpdate>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_language(key: LanguageSharedKey) -> Subscription<LanguageUpdate> {
+    let (broadcaster, start_worker) =
+        language_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_language_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_language_worker(key: LanguageSharedKey, broadcaster: Arc<Broadcaster<LanguageUpdate>>) {
+    std::thread::spawn(move || {
+        broadcaster.broadcast(query_layout(&key));
+        let events = subscribe_shared_events();
+
+        loop {
+            if broadcaster.subscriber_count() == 0 {
+                language_registry().remove(&key, &broadcaster);
+                return;
+            }
+
+            match recv_relevant_event_coalesced(&events, &[EventType::Input]) {
+                Ok(true) => {
+                    broadcaster.broadcast(query_layout(&key));
+                }
+                Ok(false) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            }
+        }
+    });
+}
+
+fn query_layout(key: &LanguageSharedKey) -> LanguageUpdate {
+    let Some(inputs) = query_with_connection("language", "get_inputs query", |connection| {
+        connection.get_inputs()
+    }) else {
+        return LanguageUpdate {
+            text: escape_markup_text("sway?"),
+            unknown: true,
+            visible: true,
+        };
+    };
+
+    let active = inputs
+        .iter()
+        .filter(|input| selector_matches(&key.identifier, &input.identifier, &input.input_type))
+        .find_map(|input| {
+            let layout = input.xkb_active_layout_name.as_deref()?;
+            Some((layout, input.xkb_active_layout_index.unwrap_or_default()))
+        });
+
+    let Some((layout, index)) = active else {
+        // No keyboard with an xkb layout (for example all keyboards unplugged).
+        return LanguageUpdate {
+            text: String::new(),
+            unknown: false,
+            visible: false,
+        };
+    };
+
+    let text = render_language_format(&key.format, layout, index, &key.short_names);
+    LanguageUpdate {
+        visible: !text.trim().is_empty(),
+        text,
+        unknown: false,
+    }
+}
+
+/// Short display name for an xkb layout: the configured mapping when present,
+/// otherwise the parenthesized variant ("English (US)" -> "US"), otherwise the
+/// first two letters of the name in upper case.
+fn short_layout_name(layout: &str, short_names: &BTreeMap<String, String>) -> String {
+    if let Some(short) = short_names.get(layout) {
+        return short.clone();
+    }
+    if let Some((_, rest)) = layout.split_once('(') {
+        if let Some((inner, _)) = rest.split_once(')') {
+            let inner = inner.trim();
+            if !inner.is_empty() {
+                return inner.to_string();
+            }
+        }
+    }
+    layout.chars().take(2).collect::<String>().to_uppercase()
+}
+
+fn render_language_format(
+    format: &str,
+    layout: &str,
+    index: i32,
+    short_names: &BTreeMap<String, String>,
+) -> String {
+    let short = short_layout_name(layout, short_names);
+    render_markup_template(
+        format,
+        &[
+            ("{layout}", layout),
+            ("{short}", &short),
+            ("{index}", &index.to_string()),
+        ],
+    )
+}
+
+fn switch_layout(identifier: String) {
+    std::thread::spawn(move || {
+        let command = switch_layout_command(&identifier);
+        let _ = query_with_connection("language", "layout switch command", |connection| {
+            connection.run_command(&command)
+        });
+    });
+}
+
+fn switch_layout_command(identifier: &str) -> String {
+    format!(
+        "input \"{}\" xkb_switch_layout next",
+        quote_sway_arg(identifier)
+    )
+}
+
+fn build_language_module(
+    key: LanguageSharedKey,
+    click_command: Option<String>,
+    class: Option<String>,
+    hide_without_sway: bool,
+) -> Label {
+    let label = ModuleLabel::new("sway-language")
+        .with_css_classes(class.as_deref())
+        .with_click_command(click_command.clone())
+        .into_label();
+
+    if click_command.is_none() {
+        label.add_css_class("clickable");
+        let click = GestureClick::builder().button(1).build();
+        let identifier = key.identifier.clone();
+        click.connect_pressed(move |_, _, _, _| {
+            switch_layout(identifier.clone());
+        });
+        label.add_controller(click);
+    }
+
+    let subscription = subscribe_shared_language(key);
+
+    attach_subscription(&label, subscription, move |label, update| {
+        let visible = update.visible && !(update.unknown && hide_without_sway);
+        label.set_visible(visible);
+        if visible {
+            label.set_markup(&update.text);
+        }
+        if update.unknown {
+            label.add_css_class("language-unknown");
+        } else {
+            label.remove_css_class("language-unknown");
+        }
+    });
+
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map};
+
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'sway/language'"));
+    }
+
+    #[test]
+    fn parse_config_defaults_to_keyboards_and_short_format() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(json!({
+                "short_names": { "German": "DE" }
+            }))
+            .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.identifier, "type:keyboard");
+        assert_eq!(cfg.format, "{short}");
+        assert_eq!(
+            cfg.short_names.get("German").map(String::as_str),
+            Some("DE")
+        );
+    }
+
+    #[test]
+    fn parse_config_supports_hide_without_sway_aliases() {
+        for key in ["hide-without-sway", "hide_without_sway"] {
+            let module = ModuleConfig::new(
+                MODULE_TYPE,
+                serde_json::from_value(json!({ key: true }))
+                    .expect("module config map should parse"),
+            );
+            assert!(
+                parse_config(&module)
+                    .expect("config should parse")
+                    .hide_without_sway
+            );
+        }
+    }
+
+    #[test]
+    fn switch_layout_command_escapes_identifier() {
+        assert_eq!(
+            switch_layout_command("1:1:AT_Translated_Set_2_keyboard"),
+            "input \"1:1:AT_Translated_Set_2_keyboard\" xkb_switch_layout next"
+        );
+        assert_eq!(
+            switch_layout_command("a\"\\b"),
+            "input \"a\\\"\\\\b\" xkb_switch_layout next"
+        );
+    }
+
+    #[test]
+    fn short_layout_name_prefers_mapping_then_parentheses() {
+        let short_names = BTreeMap::from([("English (US)".to_string(), "us".to_string())]);
+        assert_eq!(short_layout_name("English (US)", &short_names), "us");
+        assert_eq!(short_layout_name("English (UK)", &short_names), "UK");
+        assert_eq!(short_layout_name("German", &short_names), "GE");
+        assert_eq!(short_layout_name("Russian ()", &short_names), "RU");
+    }
+
+    #[test]
+    fn render_language_format_escapes_values() {
+        assert_eq!(
+            render_language_format(
+                "{short} {index} {layout}",
+                "English (Dvorak <alt>)",
+                1,
+                &BTreeMap::new()
+            ),
+            "Dvorak &lt;alt&gt; 1 English (Dvorak &lt;alt&gt;)"
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use std::process::Command;
+use std::sync::{Arc, OnceLock};
+
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, Label, Orientation, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+use swayipc::{EventType, Node};
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::sway::ipc::{
+    query_snapshot, recv_relevant_event_coalesced, subscribe_shared_events,
+};
+use crate::modules::{
+    apply_css_classes, render_markup_template, ModuleBuildContext, ModuleConfig, ModuleFactory,
+};
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct MarksConfig {
+    #[serde(default = "default_format")]
+    pub(crate) format: String,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+    #[serde(rename = "button-class", alias = "button_class", default)]
+    pub(crate) button_class: Option<String>,
+    #[serde(rename = "show-hidden", alias = "show_hidden", default)]
+    pub(crate) show_hidden: bool,
+}
+
+#[derive(Debug, Clone)]
+struct MarksUpdate {
+    /// `None` when the sway tree could not be fetched.
+    marks: Option<Vec<MarkInfo>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MarkInfo {
+    name: String,
+    title: String,
+    focused: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MarksSharedKey {}
+
+pub(crate) struct SwayMarksFactory;
+
+pub(crate) const FACTORY: SwayMarksFactory = SwayMarksFactory;
+pub(crate) const MODULE_TYPE: &str = "sway/marks";
+
+impl ModuleFactory for SwayMarksFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_marks_module(parsed).upcast())
+    }
+}
+
+fn default_format() -> String {
+    "{mark}".to_string()
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<MarksConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn marks_registry() -> &'static BackendRegistry<MarksSharedKey, Broadcaster<MarksUpdate>> {
+    static REGISTRY: OnceLock<BackendRegistry<MarksSharedKey, Broadcaster<MarksUpdate>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_marks() -> Subscription<MarksUpdate> {
+    let key = MarksSharedKey {};
+
+    let (broadcaster, start_worker) = marks_registry().get_or_create(key.clone(), Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_marks_worker(key, broadcaster);
+    }
+
+    receiver
+}
+
+fn start_marks_worker(key: MarksSharedKey, broadcaster: Arc<Broadcaster<MarksUpdate>>) {
+    std::thread::spawn(move || {
+        broadcaster.broadcast(query_marks());
+        let events = subscribe_shared_events();
+
+        loop {
+            if broadcaster.subscriber_count() == 0 {
+                marks_registry().remove(&key, &broadcaster);
+                return;
+            }
+
+            // Mark changes arrive as `window` events with change `mark`.
+            match recv_relevant_event_coalesced(&events, &[EventType::Window]) {
+                Ok(true) => {
+                    broadcaster.broadcast(query_marks());
+                }
+                Ok(false) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            }
+        }
+    });
+}
+
+fn query_marks() -> MarksUpdate {
+    let snapshot = query_snapshot();
+    MarksUpdate {
+        marks: snapshot.tree.as_ref().map(|tree| {
+            let mut marks = Vec::new();
+            collect_marks(tree, &mut marks);
+            marks.sort_by(|a, b| a.name.cmp(&b.name));
+            marks
+        }),
+    }
+}
+
+fn collect_marks(node: &Node, marks: &mut Vec<MarkInfo>) {
+    for mark in &node.marks {
+        marks.push(MarkInfo {
+            name: mark.clone(),
+            title: node.name.clone().unwrap_or_default(),
+            focused: node.focused,
+        });
+    }
+    for child in node.nodes.iter().chain(&node.floating_nodes) {
+        collect_marks(child, marks);
+    }
+}
+
+fn build_marks_module(config: MarksConfig) -> GtkBox {
+    let container = GtkBox::new(Orientation::Horizontal, 4);
+    container.add_css_class("module");
+    container.add_css_class("sway-marks");
+    apply_css_classes(&container, config.class.as_deref());
+    container.set_visible(false);
+
+    let subscription = subscribe_shared_marks();
+
+    attach_subscription(&container, subscription, move |container, update| {
+        while let Some(child) = container.first_child() {
+            container.remove(&child);
+        }
+
+        let Some(marks) = update.marks else {
+            let fallback = Label::new(Some("sway?"));
+            fallback.add_css_class("marks-status");
+            container.append(&fallback);
+            container.set_visible(true);
+            return;
+        };
+
+        let marks = visible_marks(marks, config.show_hidden);
+        for mark in &marks {
+            container.append(&build_mark_button(
+                mark,
+                &config.format,
+                config.button_class.as_deref(),
+            ));
+        }
+        container.set_visible(!marks.is_empty());
+    });
+
+    container
+}
+
+/// Sway hides marks starting with `_` from window decorations; do the same
+/// unless `show-hidden` is set.
+fn visible_marks(marks: Vec<MarkInfo>, show_hidden: bool) -> Vec<MarkInfo> {
+    marks
+        .into_iter()
+        .filter(|mark| show_hidden || !mark.name.starts_with('_'))
+        .collect()
+}
+
+fn build_mark_button(mark: &MarkInfo, format: &str, button_class: Option<&str>) -> Button {
+    let label = Label::new(None);
+    label.set_markup(&render_markup_template(
+        format,
+        &[
+            ("{}", &mark.name),
+            ("{mark}", &mark.name),
+            ("{title}", &mark.title),
+        ],
+    ));
+
+    let button = Button::new();
+    button.set_child(Some(&label));
+    button.add_css_class("menu-button");
+    button.add_css_class("mark-button");
+    apply_css_classes(&button, button_class);
+    button.set_focusable(false);
+    if !mark.title.is_empty() {
+        button.set_tooltip_text(Some(&mark.title));
+    }
+    if mark.focused {
+        button.add_css_class("active");
+        button.add_css_class("mark-focused");
+    }
+
+    let command = focus_mark_command(&mark.name);
+    button.connect_clicked(move |_| {
+        let _ = Command::new("swaymsg").arg(&command).output();
+    });
+
+    button
+}
+
+/// `con_mark` is a regex, so the mark is escaped and anchored to match it
+/// exactly; the criteria value is double-quoted.
+fn focus_mark_command(mark: &str) -> String {
+    let mut pattern = String::with_capacity(mark.len() + 2);
+    pattern.push('^');
+    for c in mark.chars() {
+        if "\\.^$|?*+()[]{}".contains(c) {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('$');
+    let quoted = pattern.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("[con_mark=\"{quoted}\"] focus")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'sway/marks'"));
+    }
+
+    #[test]
+    fn parse_config_uses_defaults_and_aliases() {
+        let module = ModuleConfig::new(MODULE_TYPE, Map::new());
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.format, "{mark}");
+        assert!(!cfg.show_hidden);
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_str("{\"show_hidden\":true,\"button_class\":\"v-pill\"}")
+                .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert!(cfg.show_hidden);
+        assert_eq!(cfg.button_class.as_deref(), Some("v-pill"));
+    }
+
+    #[test]
+    fn visible_marks_skips_underscore_marks_unless_requested() {
+        let mark = |name: &str| MarkInfo {
+            name: name.to_string(),
+            title: String::new(),
+            focused: false,
+        };
+        let marks = vec![mark("a"), mark("_scratch"), mark("b")];
+        assert_eq!(
+            visible_marks(marks.clone(), false),
+            vec![mark("a"), mark("b")]
+        );
+        assert_eq!(visible_marks(marks.clone(), true), marks);
+    }
+
+    #[test]
+    fn focus_mark_command_escapes_regex_and_quotes() {
+        assert_eq!(focus_mark_command("term"), "[con_mark=\"^term$\"] focus");
+        assert_eq!(
+            focus_mark_command("a.b\"c"),
+            "[con_mark=\"^a\\\\.b\\\"c$\"] focus"
+        );
+    }
+}
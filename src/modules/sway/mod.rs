@@ -1,4 +1,10 @@
+pub(crate) mod bar_mode;
+pub(crate) mod fullscreen;
+pub(crate) mod input_toggle;
 pub(crate) mod ipc;
+pub(crate) mod language;
+pub(crate) mod marks;
 pub(crate) mod mode;
 pub(crate) mod window;
 pub(crate) mod workspaces;
+pub(crate) mod xwayland;
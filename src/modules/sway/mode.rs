@@ -12,6 +12,7 @@ use crate::modules::broadcaster::{
 use crate::modules::sway::ipc::{
     query_snapshot, recv_relevant_event_coalesced, subscribe_shared_events,
 };
+use crate::modules::text::{attach_text_subscription, TextBlock, TextSink};
 use crate::modules::{
     escape_markup_text, render_markup_template, ModuleBuildContext, ModuleConfig, ModuleFactory,
     ModuleLabel,
@@ -55,6 +56,14 @@ impl ModuleFactory for SwayModeFactory {
         let click_command = parsed.click.or(parsed.on_click);
         Ok(build_mode_module(parsed.format, click_command, parsed.class).upcast())
     }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        let parsed = parse_config(config)?;
+        attach_text_subscription(subscribe_shared_mode(parsed.format), sink, |update| {
+            update.visible.then(|| TextBlock::new(update.text))
+        });
+        Ok(())
+    }
 }
 
 fn default_format() -> String {
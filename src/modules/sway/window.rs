@@ -1,7 +1,7 @@
 use std::sync::{Arc, OnceLock};
 
 use gtk::prelude::*;
-use gtk::{Label, Widget};
+use gtk::Widget;
 use serde::Deserialize;
 use serde_json::Value;
 use swayipc::{EventType, Node, NodeType};
@@ -9,12 +9,15 @@ use swayipc::{EventType, Node, NodeType};
 use crate::modules::broadcaster::{
     attach_subscription, BackendRegistry, Broadcaster, Subscription,
 };
+use crate::modules::scroll_text::{ModuleText, ScrollTextMode};
 use crate::modules::sway::ipc::{
     query_snapshot, recv_relevant_event_coalesced, subscribe_shared_events,
 };
+use crate::modules::text::{attach_text_subscription, TextBlock, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
 use crate::modules::{
-    apply_css_classes, attach_primary_click_command, escape_markup_text, render_markup_template,
-    ModuleBuildContext, ModuleConfig, ModuleFactory,
+    attach_primary_click_command, escape_markup_text, render_markup_template, ModuleBuildContext,
+    ModuleConfig, ModuleFactory,
 };
 
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -27,11 +30,20 @@ pub(crate) struct WindowConfig {
     pub(crate) on_click: Option<String>,
     #[serde(default)]
     pub(crate) class: Option<String>,
+    #[serde(
+        rename = "max-width",
+        alias = "max_width",
+        default = "default_max_width"
+    )]
+    pub(crate) max_width: u32,
+    #[serde(rename = "scroll-text", alias = "scroll_text", default)]
+    pub(crate) scroll_text: ScrollTextMode,
 }
 
 #[derive(Debug, Clone)]
 struct WindowUpdate {
     title: String,
+    tooltip: String,
     output: Option<String>,
     visible: bool,
 }
@@ -39,6 +51,7 @@ struct WindowUpdate {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct WindowSharedKey {
     format: String,
+    tooltip_format: Option<String>,
 }
 
 pub(crate) struct SwayWindowFactory;
@@ -50,21 +63,53 @@ fn default_format() -> String {
     "{}".to_string()
 }
 
+fn default_max_width() -> u32 {
+    80
+}
+
 impl ModuleFactory for SwayWindowFactory {
     fn module_type(&self) -> &'static str {
         MODULE_TYPE
     }
 
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
     fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
         let parsed = parse_config(config)?;
         let click_command = parsed.click.or(parsed.on_click);
+        let text = ModuleText::new(
+            MODULE_TYPE,
+            Some(parsed.max_width),
+            parsed.scroll_text,
+            context.scale(),
+            &["module", "sway-window"],
+            parsed.class.as_deref(),
+        )?;
         Ok(build_window_module(
             context.monitor_connector.clone(),
-            parsed.format,
+            WindowSharedKey {
+                format: parsed.format,
+                tooltip_format: context.tooltip_format.clone(),
+            },
             click_command,
-            parsed.class,
-        )
-        .upcast())
+            text,
+        ))
+    }
+
+    // Shows the focused window on any output; `max-width` and
+    // `scroll-text` only apply to the widget.
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        let parsed = parse_config(config)?;
+        let subscription = subscribe_shared_window(WindowSharedKey {
+            format: parsed.format,
+            tooltip_format: None,
+        });
+        attach_text_subscription(subscription, sink, |update| {
+            update.visible.then(|| TextBlock::new(update.title))
+        });
+        Ok(())
     }
 }
 
@@ -86,11 +131,7 @@ fn window_registry() -> &'static BackendRegistry<WindowSharedKey, Broadcaster<Wi
     REGISTRY.get_or_init(BackendRegistry::new)
 }
 
-fn subscribe_shared_window(format: String) -> Subscription<WindowUpdate> {
-    let key = WindowSharedKey {
-        format: format.clone(),
-    };
-
+fn subscribe_shared_window(key: WindowSharedKey) -> Subscription<WindowUpdate> {
     let (broadcaster, start_worker) =
         window_registry().get_or_create(key.clone(), Broadcaster::new);
     let receiver = broadcaster.subscribe();
@@ -104,7 +145,7 @@ fn subscribe_shared_window(format: String) -> Subscription<WindowUpdate> {
 
 fn start_window_worker(key: WindowSharedKey, broadcaster: Arc<Broadcaster<WindowUpdate>>) {
     std::thread::spawn(move || {
-        broadcaster.broadcast(query_focused_window(&key.format));
+        broadcaster.broadcast(query_focused_window(&key));
         let events = subscribe_shared_events();
 
         loop {
@@ -118,7 +159,7 @@ fn start_window_worker(key: WindowSharedKey, broadcaster: Arc<Broadcaster<Window
                 &[EventType::Window, EventType::Workspace, EventType::Output],
             ) {
                 Ok(true) => {
-                    broadcaster.broadcast(query_focused_window(&key.format));
+                    broadcaster.broadcast(query_focused_window(&key));
                 }
                 Ok(false) => {}
                 Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
@@ -128,13 +169,14 @@ fn start_window_worker(key: WindowSharedKey, broadcaster: Arc<Broadcaster<Window
     });
 }
 
-fn query_focused_window(format: &str) -> WindowUpdate {
+fn query_focused_window(key: &WindowSharedKey) -> WindowUpdate {
     let snapshot = query_snapshot();
     let tree = match snapshot.tree.as_ref() {
         Some(tree) => tree,
         None => {
             return WindowUpdate {
                 title: escape_markup_text("sway?"),
+                tooltip: String::new(),
                 output: None,
                 visible: true,
             };
@@ -143,20 +185,36 @@ fn query_focused_window(format: &str) -> WindowUpdate {
 
     let focused = focused_window_info(tree);
     let output = focused.as_ref().and_then(|info| info.output.clone());
+    let marks = focused
+        .as_ref()
+        .map(|info| info.marks.join(" "))
+        .unwrap_or_default();
     let title = focused.and_then(|info| info.title).unwrap_or_default();
 
     if title.is_empty() {
         return WindowUpdate {
             title: String::new(),
+            tooltip: String::new(),
             output,
             visible: false,
         };
     }
 
-    let rendered = render_markup_template(format, &[("{}", &title), ("{title}", &title)]);
+    let render = |format: &str| {
+        render_markup_template(
+            format,
+            &[("{}", &title), ("{title}", &title), ("{marks}", &marks)],
+        )
+    };
+    let rendered = render(&key.format);
     let visible = !rendered.trim().is_empty();
     WindowUpdate {
         title: rendered,
+        tooltip: key
+            .tooltip_format
+            .as_deref()
+            .map(render)
+            .unwrap_or_default(),
         output,
         visible,
     }
@@ -164,21 +222,20 @@ fn query_focused_window(format: &str) -> WindowUpdate {
 
 fn build_window_module(
     output_filter: Option<String>,
-    format: String,
+    key: WindowSharedKey,
     click_command: Option<String>,
-    class: Option<String>,
-) -> Label {
-    let label = Label::new(None);
-    label.add_css_class("module");
-    label.add_css_class("sway-window");
-    label.set_ellipsize(gtk::pango::EllipsizeMode::End);
-    label.set_max_width_chars(80);
-    apply_css_classes(&label, class.as_deref());
-    attach_primary_click_command(&label, click_command);
-
-    let subscription = subscribe_shared_window(format);
-
-    attach_subscription(&label, subscription, move |label, update| {
+    text: ModuleText,
+) -> Widget {
+    let widget = text.widget();
+    attach_primary_click_command(&widget, click_command);
+
+    let tooltip = key
+        .tooltip_format
+        .is_some()
+        .then(|| ModuleTooltip::attach(&widget));
+    let subscription = subscribe_shared_window(key);
+
+    attach_subscription(&widget, subscription, move |widget, update| {
         let belongs_to_output = match (output_filter.as_deref(), update.output.as_deref()) {
             (Some(expected), Some(current)) => expected == current,
             (Some(_), None) => false,
@@ -186,21 +243,25 @@ fn build_window_module(
         };
 
         if !belongs_to_output || !update.visible {
-            label.set_visible(false);
+            widget.set_visible(false);
             return;
         }
 
-        label.set_visible(true);
-        label.set_markup(&update.title);
+        widget.set_visible(true);
+        text.set_markup(&update.title);
+        if let Some(tooltip) = &tooltip {
+            tooltip.set_markup(&update.tooltip);
+        }
     });
 
-    label
+    widget
 }
 
 #[derive(Debug, Clone)]
 struct FocusedWindowInfo {
     title: Option<String>,
     output: Option<String>,
+    marks: Vec<String>,
 }
 
 fn focused_window_info(root: &Node) -> Option<FocusedWindowInfo> {
@@ -241,6 +302,7 @@ fn focused_window_info_in_node(
     Some(FocusedWindowInfo {
         title,
         output: output_ctx.map(ToOwned::to_owned),
+        marks: node.marks.clone(),
     })
 }
 
@@ -283,5 +345,19 @@ mod tests {
         let module = ModuleConfig::new(MODULE_TYPE, Map::new());
         let cfg = parse_config(&module).expect("config should parse");
         assert_eq!(cfg.format, "{}");
+        assert_eq!(cfg.max_width, 80);
+        assert_eq!(cfg.scroll_text, ScrollTextMode::Off);
+    }
+
+    #[test]
+    fn parse_config_supports_scroll_text_keys() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_str("{\"max-width\":40,\"scroll-text\":\"always\"}")
+                .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.max_width, 40);
+        assert_eq!(cfg.scroll_text, ScrollTextMode::Always);
     }
 }
@@ -4,7 +4,7 @@ use std::{cell::RefCell, rc::Rc};
 
 use gtk::gdk;
 use gtk::prelude::*;
-use gtk::{Box as GtkBox, Button, Label, Orientation, Widget};
+use gtk::{Box as GtkBox, Button, GestureClick, Label, Orientation, Popover, PositionType, Widget};
 use serde::Deserialize;
 use serde_json::{Map, Value};
 use swayipc::EventType;
@@ -13,9 +13,18 @@ use crate::modules::broadcaster::{
     attach_subscription, BackendRegistry, Broadcaster, Subscription,
 };
 use crate::modules::sway::ipc::{
-    query_snapshot, recv_relevant_event_coalesced, subscribe_shared_events,
+    query_snapshot, query_with_connection, quote_sway_arg, recv_relevant_event_coalesced,
+    subscribe_shared_events,
 };
-use crate::modules::{apply_css_classes, ModuleBuildContext, ModuleConfig, ModuleFactory};
+use crate::modules::text::{attach_text_subscription, TextBlock, TextSink};
+use crate::modules::touch::{self, SwipeDirection};
+use crate::modules::workspace_strip;
+use crate::modules::{
+    apply_css_classes, escape_markup_text, events, popovers, render_markup_template,
+    ModuleBuildContext, ModuleConfig, ModuleFactory,
+};
+
+const DEFAULT_OUTPUT_LABEL_FORMAT: &str = "{output}";
 
 #[derive(Debug, Deserialize, Clone, Default)]
 pub(crate) struct WorkspaceConfig {
@@ -23,6 +32,27 @@ pub(crate) struct WorkspaceConfig {
     pub(crate) class: Option<String>,
     #[serde(rename = "button-class", alias = "button_class", default)]
     pub(crate) button_class: Option<String>,
+    #[serde(rename = "group-by-output", alias = "group_by_output", default)]
+    pub(crate) group_by_output: bool,
+    #[serde(rename = "output-format", alias = "output_format", default)]
+    pub(crate) output_format: Option<String>,
+    /// Label of an extra button that jumps to the lowest unused workspace
+    /// number; no button when unset.
+    #[serde(rename = "format-button", alias = "format_button", default)]
+    pub(crate) format_button: Option<String>,
+    #[serde(
+        rename = "middle-click-back-and-forth",
+        alias = "middle_click_back_and_forth",
+        default
+    )]
+    pub(crate) middle_click_back_and_forth: bool,
+    /// Workspaces shown inline per output before the rest collapse into a
+    /// `+N` button.
+    #[serde(rename = "max-visible", alias = "max_visible", default)]
+    pub(crate) max_visible: Option<usize>,
+    /// Caps the scrollable strip, in pixels.
+    #[serde(rename = "max-width", alias = "max_width", default)]
+    pub(crate) max_width: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -32,13 +62,13 @@ struct WorkspacesUpdate {
 }
 
 #[derive(Debug, Clone)]
-struct WorkspaceInfo {
-    name: String,
-    num: i32,
-    output: String,
-    focused: bool,
-    visible: bool,
-    urgent: bool,
+pub(crate) struct WorkspaceInfo {
+    pub(crate) name: String,
+    pub(crate) num: i32,
+    pub(crate) output: String,
+    pub(crate) focused: bool,
+    pub(crate) visible: bool,
+    pub(crate) urgent: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -55,15 +85,27 @@ impl ModuleFactory for SwayWorkspaceFactory {
     }
 
     fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
-        let parsed = parse_config(config)?;
+        let mut parsed = parse_config(config)?;
+        let output_format = parsed.output_format.take();
+        let output_format = parsed
+            .group_by_output
+            .then(|| output_format.unwrap_or_else(|| DEFAULT_OUTPUT_LABEL_FORMAT.to_string()));
         Ok(build_workspaces_module(
             context.monitor_connector.clone(),
             context.monitor.clone(),
-            parsed.class,
-            parsed.button_class,
+            parsed,
+            output_format,
         )
         .upcast())
     }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        parse_config(config)?;
+        attach_text_subscription(subscribe_shared_workspaces(), sink, |update| {
+            Some(render_workspaces_text(&update.workspaces))
+        });
+        Ok(())
+    }
 }
 
 pub(crate) fn default_module_config() -> ModuleConfig {
@@ -108,8 +150,10 @@ fn start_workspaces_worker(
     broadcaster: Arc<Broadcaster<WorkspacesUpdate>>,
 ) {
     std::thread::spawn(move || {
-        broadcaster.broadcast(query_workspaces());
-        let events = subscribe_shared_events();
+        let update = query_workspaces();
+        let mut focused_workspace = update.focused_workspace.clone();
+        broadcaster.broadcast(update);
+        let sway_events = subscribe_shared_events();
 
         loop {
             if broadcaster.subscriber_count() == 0 {
@@ -117,10 +161,19 @@ fn start_workspaces_worker(
                 return;
             }
 
-            match recv_relevant_event_coalesced(&events, &[EventType::Workspace, EventType::Output])
-            {
+            match recv_relevant_event_coalesced(
+                &sway_events,
+                &[EventType::Workspace, EventType::Output],
+            ) {
                 Ok(true) => {
-                    broadcaster.broadcast(query_workspaces());
+                    let update = query_workspaces();
+                    if let Some(name) = update.focused_workspace.as_ref() {
+                        if focused_workspace.as_ref() != Some(name) {
+                            events::emit(events::WORKSPACE_SWITCHED, name.clone());
+                        }
+                        focused_workspace = Some(name.clone());
+                    }
+                    broadcaster.broadcast(update);
                 }
                 Ok(false) => {}
                 Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
@@ -172,9 +225,18 @@ fn query_workspaces() -> WorkspacesUpdate {
 pub(crate) fn build_workspaces_module(
     output_filter: Option<String>,
     monitor: Option<gdk::Monitor>,
-    class: Option<String>,
-    button_class: Option<String>,
+    config: WorkspaceConfig,
+    output_format: Option<String>,
 ) -> GtkBox {
+    let WorkspaceConfig {
+        class,
+        button_class,
+        format_button,
+        middle_click_back_and_forth,
+        max_visible,
+        max_width,
+        ..
+    } = config;
     let resolved_output = Rc::new(RefCell::new(output_filter));
     try_resolve_output_filter(&resolved_output, monitor.as_ref());
 
@@ -182,13 +244,14 @@ pub(crate) fn build_workspaces_module(
     container.add_css_class("module");
     container.add_css_class("workspaces");
     apply_css_classes(&container, class.as_deref());
+    let strip = workspace_strip::attach_scrollable_strip(&container, max_width);
 
     let subscription = subscribe_shared_workspaces();
 
     // Initial render
     {
         let output = resolved_output.borrow().clone();
-        if output.is_some() {
+        if output.is_some() || output_format.is_some() {
             container.set_visible(true);
         } else {
             container.set_visible(false);
@@ -199,6 +262,25 @@ pub(crate) fn build_workspaces_module(
         let resolved_output = Rc::clone(&resolved_output);
         let monitor = monitor.clone();
         move |container, update| {
+            if let Some(output_format) = output_format.as_deref() {
+                render_workspaces_grouped(
+                    &strip,
+                    &update,
+                    output_format,
+                    button_class.as_deref(),
+                    max_visible,
+                );
+                append_new_workspace_button(
+                    &strip,
+                    &update,
+                    format_button.as_deref(),
+                    button_class.as_deref(),
+                    None,
+                );
+                container.set_visible(true);
+                return;
+            }
+
             if resolved_output.borrow().is_none() {
                 try_resolve_output_filter(&resolved_output, monitor.as_ref());
             }
@@ -208,15 +290,44 @@ pub(crate) fn build_workspaces_module(
                 return;
             }
             render_workspaces(
-                container,
+                &strip,
                 &update,
                 output.as_deref(),
                 button_class.as_deref(),
+                max_visible,
+            );
+            append_new_workspace_button(
+                &strip,
+                &update,
+                format_button.as_deref(),
+                button_class.as_deref(),
+                output.as_deref(),
             );
             container.set_visible(true);
         }
     });
 
+    touch::attach_horizontal_swipe(&container, |direction| {
+        let target = match direction {
+            SwipeDirection::Left => "next_on_output",
+            SwipeDirection::Right => "prev_on_output",
+        };
+        let _ = Command::new("swaymsg").arg("workspace").arg(target).spawn();
+    });
+
+    if middle_click_back_and_forth {
+        // Workspace buttons only claim the primary button, so a middle click
+        // anywhere on the strip reaches this gesture.
+        let click = GestureClick::builder().button(2).build();
+        click.connect_released(|_, _, _, _| {
+            let _ = Command::new("swaymsg")
+                .arg("workspace")
+                .arg("back_and_forth")
+                .spawn();
+        });
+        container.add_controller(click);
+    }
+
     // Deferred output resolution for monitors that aren't ready yet
     gtk::glib::timeout_add_local(std::time::Duration::from_millis(200), {
         let container_weak = container.downgrade();
@@ -257,6 +368,7 @@ fn render_workspaces(
     update: &WorkspacesUpdate,
     output_filter: Option<&str>,
     button_class: Option<&str>,
+    max_visible: Option<usize>,
 ) {
     while let Some(child) = container.first_child() {
         container.remove(&child);
@@ -291,35 +403,285 @@ fn render_workspaces(
         );
     }
 
-    for ws in workspaces {
-        let button = Button::with_label(&ws.name);
-        button.add_css_class("menu-button");
-        apply_css_classes(&button, button_class);
-        button.set_focusable(false);
+    workspace_strip::append_workspace_buttons(
+        container,
+        &workspaces,
+        update.focused_workspace.as_deref(),
+        max_visible,
+        button_class,
+        MODULE_TYPE,
+        |ws| build_workspace_button(ws, update, button_class),
+    );
+}
 
-        if update
-            .focused_workspace
-            .as_ref()
-            .is_some_and(|active_name| active_name == &ws.name)
-        {
-            button.add_css_class("active");
-            button.add_css_class("workspace-active");
+fn render_workspaces_grouped(
+    container: &GtkBox,
+    update: &WorkspacesUpdate,
+    output_format: &str,
+    button_class: Option<&str>,
+    max_visible: Option<usize>,
+) {
+    while let Some(child) = container.first_child() {
+        container.remove(&child);
+    }
+
+    if update.workspaces.is_empty() {
+        let fallback = Label::new(Some("sway?"));
+        fallback.add_css_class("workspace-status");
+        container.append(&fallback);
+        return;
+    }
+
+    for (output, workspaces) in group_workspaces_by_output(&update.workspaces) {
+        let group = GtkBox::new(Orientation::Horizontal, 4);
+        group.add_css_class("workspace-group");
+
+        let header = Label::new(None);
+        header.add_css_class("workspace-output-label");
+        let header_text = render_markup_template(output_format, &[("{output}", &output)]);
+        header.set_visible(!header_text.trim().is_empty());
+        header.set_markup(&header_text);
+        group.append(&header);
+
+        workspace_strip::append_workspace_buttons(
+            &group,
+            &workspaces,
+            update.focused_workspace.as_deref(),
+            max_visible,
+            button_class,
+            MODULE_TYPE,
+            |ws| build_workspace_button(ws, update, button_class),
+        );
+
+        container.append(&group);
+    }
+}
+
+/// All workspaces in one block, by number, with the focused one in bold.
+/// Urgent when any workspace is.
+fn render_workspaces_text(workspaces: &[WorkspaceInfo]) -> TextBlock {
+    if workspaces.is_empty() {
+        return TextBlock::new("sway?");
+    }
+
+    let mut workspaces = workspaces.to_vec();
+    workspaces.sort_by_key(|ws| ws.num);
+    let markup = workspaces
+        .iter()
+        .map(|ws| {
+            let name = escape_markup_text(&ws.name);
+            if ws.focused {
+                format!("<b>{name}</b>")
+            } else {
+                name
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    TextBlock::new(markup).urgent(workspaces.iter().any(|ws| ws.urgent))
+}
+
+/// Groups workspaces by output, keeping outputs in the order they are
+/// reported and sorting workspaces within each output by number.
+pub(crate) fn group_workspaces_by_output(
+    workspaces: &[WorkspaceInfo],
+) -> Vec<(String, Vec<WorkspaceInfo>)> {
+    let mut groups: Vec<(String, Vec<WorkspaceInfo>)> = Vec::new();
+    for ws in workspaces {
+        match groups.iter_mut().find(|(output, _)| *output == ws.output) {
+            Some((_, items)) => items.push(ws.clone()),
+            None => groups.push((ws.output.clone(), vec![ws.clone()])),
         }
-        if ws.urgent {
-            button.add_css_class("urgent");
-            button.add_css_class("workspace-urgent");
+    }
+    for (_, items) in &mut groups {
+        items.sort_by_key(|w| w.num);
+    }
+    groups
+}
+
+fn build_workspace_button(
+    ws: &WorkspaceInfo,
+    update: &WorkspacesUpdate,
+    button_class: Option<&str>,
+) -> Button {
+    let button = Button::with_label(&ws.name);
+    button.add_css_class("menu-button");
+    apply_css_classes(&button, button_class);
+    button.set_focusable(false);
+
+    if update
+        .focused_workspace
+        .as_ref()
+        .is_some_and(|active_name| active_name == &ws.name)
+    {
+        button.add_css_class("active");
+        button.add_css_class("workspace-active");
+    }
+    if ws.urgent {
+        button.add_css_class("urgent");
+        button.add_css_class("workspace-urgent");
+    }
+
+    let ws_name = ws.name.clone();
+    button.connect_clicked(move |_| {
+        let _ = Command::new("swaymsg")
+            .arg("workspace")
+            .arg(ws_name.clone())
+            .output();
+    });
+    attach_output_menu(&button, ws);
+
+    button
+}
+
+/// Right click or a long press on a workspace button opens a menu that
+/// moves the workspace to another output. Outputs are queried on open so
+/// the list follows docking changes.
+fn attach_output_menu(button: &Button, ws: &WorkspaceInfo) {
+    let popover = Popover::new();
+    popover.add_css_class("workspace-output-menu");
+    popover.set_autohide(true);
+    popover.set_has_arrow(true);
+    popover.set_position(PositionType::Top);
+    popover.set_parent(button);
+    popovers::track(&popover, MODULE_TYPE);
+
+    let content = GtkBox::new(Orientation::Vertical, 4);
+    content.add_css_class("workspace-output-menu-content");
+    popover.set_child(Some(&content));
+
+    let open_menu = {
+        let workspace = ws.name.clone();
+        let current_output = ws.output.clone();
+        move || {
+            if popover.is_visible() {
+                popover.popdown();
+                return;
+            }
+            fill_output_menu(&popover, &content, &workspace, &current_output);
+            popover.popup();
         }
+    };
+    {
+        let open_menu = open_menu.clone();
+        touch::attach_long_press(button, move |_, _| open_menu());
+    }
+    let secondary = GestureClick::builder().button(3).build();
+    secondary.connect_pressed(move |_, _, _, _| open_menu());
+    button.add_controller(secondary);
+}
 
-        let ws_name = ws.name.clone();
-        button.connect_clicked(move |_| {
-            let _ = Command::new("swaymsg")
-                .arg("workspace")
-                .arg(ws_name.clone())
-                .output();
+fn fill_output_menu(popover: &Popover, content: &GtkBox, workspace: &str, current_output: &str) {
+    while let Some(child) = content.first_child() {
+        content.remove(&child);
+    }
+
+    let title = Label::new(Some(&format!("Move {workspace} to output")));
+    title.add_css_class("workspace-output-menu-title");
+    title.set_xalign(0.0);
+    content.append(&title);
+
+    let targets = move_targets(&query_active_outputs(), current_output);
+    if targets.is_empty() {
+        let empty = Label::new(Some("No other outputs"));
+        empty.add_css_class("workspace-output-menu-empty");
+        content.append(&empty);
+        return;
+    }
+
+    for output in targets {
+        let item = Button::with_label(&format!("\u{2192} {output}"));
+        item.add_css_class("workspace-output-menu-item");
+        item.set_focusable(false);
+        let command = move_workspace_command(workspace, &output);
+        let popover = popover.downgrade();
+        item.connect_clicked(move |_| {
+            let _ = Command::new("swaymsg").arg(&command).spawn();
+            if let Some(popover) = popover.upgrade() {
+                popover.popdown();
+            }
         });
+        content.append(&item);
+    }
+}
+
+fn query_active_outputs() -> Vec<String> {
+    query_with_connection(MODULE_TYPE, "outputs query", |connection| {
+        connection.get_outputs()
+    })
+    .unwrap_or_default()
+    .into_iter()
+    .filter(|output| output.active)
+    .map(|output| output.name)
+    .collect()
+}
+
+/// Outputs a workspace on `current_output` can move to, in sway's order.
+fn move_targets(outputs: &[String], current_output: &str) -> Vec<String> {
+    outputs
+        .iter()
+        .filter(|output| *output != current_output)
+        .cloned()
+        .collect()
+}
+
+/// Focuses `workspace` and moves it, since sway only moves the focused
+/// workspace.
+fn move_workspace_command(workspace: &str, output: &str) -> String {
+    format!(
+        "workspace \"{}\"; move workspace to output \"{}\"",
+        quote_sway_arg(workspace),
+        quote_sway_arg(output)
+    )
+}
 
-        container.append(&button);
+/// Appends the `format-button` button, if configured. With an `output` the
+/// click focuses that output first so the new workspace opens on this bar.
+fn append_new_workspace_button(
+    container: &GtkBox,
+    update: &WorkspacesUpdate,
+    format_button: Option<&str>,
+    button_class: Option<&str>,
+    output: Option<&str>,
+) {
+    let Some(format_button) = format_button else {
+        return;
+    };
+    if update.workspaces.is_empty() {
+        return;
     }
+
+    let num = next_free_workspace_number(&update.workspaces);
+    let label = Label::new(None);
+    label.set_markup(&render_markup_template(
+        format_button,
+        &[("{num}", &num.to_string())],
+    ));
+
+    let button = Button::new();
+    button.set_child(Some(&label));
+    button.add_css_class("menu-button");
+    button.add_css_class("workspace-new");
+    apply_css_classes(&button, button_class);
+    button.set_focusable(false);
+
+    let command = match output {
+        Some(output) => format!("focus output {output}; workspace number {num}"),
+        None => format!("workspace number {num}"),
+    };
+    button.connect_clicked(move |_| {
+        let _ = Command::new("swaymsg").arg(&command).spawn();
+    });
+
+    container.append(&button);
+}
+
+/// Lowest positive workspace number not used on any output; sway and
+/// Hyprland numbers are global, so per-output filtering does not apply here.
+pub(crate) fn next_free_workspace_number(workspaces: &[WorkspaceInfo]) -> i32 {
+    (1..)
+        .find(|num| !workspaces.iter().any(|ws| ws.num == *num))
+        .unwrap_or(1)
 }
 
 fn focused_workspace_name_from_tree(tree: &swayipc::Node) -> Option<String> {
@@ -400,4 +762,92 @@ mod tests {
         let snake_cfg = parse_config(&snake).expect("snake config should parse");
         assert_eq!(snake_cfg.button_class.as_deref(), Some("baz"));
     }
+
+    fn workspace(name: &str, num: i32, output: &str) -> WorkspaceInfo {
+        WorkspaceInfo {
+            name: name.to_string(),
+            num,
+            output: output.to_string(),
+            focused: false,
+            visible: false,
+            urgent: false,
+        }
+    }
+
+    #[test]
+    fn group_workspaces_by_output_preserves_output_order() {
+        let groups = group_workspaces_by_output(&[
+            workspace("3", 3, "HDMI-A-1"),
+            workspace("2", 2, "eDP-1"),
+            workspace("1", 1, "HDMI-A-1"),
+        ]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "HDMI-A-1");
+        assert_eq!(
+            groups[0].1.iter().map(|ws| ws.num).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(groups[1].0, "eDP-1");
+        assert_eq!(groups[1].1.len(), 1);
+    }
+
+    #[test]
+    fn render_workspaces_text_marks_focused_and_urgent() {
+        let focused = WorkspaceInfo {
+            focused: true,
+            ..workspace("2:<web>", 2, "eDP-1")
+        };
+        let block = render_workspaces_text(&[focused, workspace("1", 1, "eDP-1")]);
+        assert_eq!(block.markup, "1 <b>2:&lt;web&gt;</b>");
+        assert!(!block.urgent);
+
+        let urgent = WorkspaceInfo {
+            urgent: true,
+            ..workspace("3", 3, "eDP-1")
+        };
+        assert!(render_workspaces_text(&[urgent]).urgent);
+        assert_eq!(render_workspaces_text(&[]).markup, "sway?");
+    }
+
+    #[test]
+    fn move_targets_skip_current_output() {
+        let outputs = [
+            "eDP-1".to_string(),
+            "DP-1".to_string(),
+            "HDMI-A-1".to_string(),
+        ];
+        assert_eq!(move_targets(&outputs, "DP-1"), ["eDP-1", "HDMI-A-1"]);
+        assert!(move_targets(&outputs[..1], "eDP-1").is_empty());
+    }
+
+    #[test]
+    fn move_workspace_command_quotes_names() {
+        assert_eq!(
+            move_workspace_command("2: web", "DP-1"),
+            "workspace \"2: web\"; move workspace to output \"DP-1\""
+        );
+        assert_eq!(
+            move_workspace_command("a\"b", "DP-1"),
+            "workspace \"a\\\"b\"; move workspace to output \"DP-1\""
+        );
+    }
+
+    #[test]
+    fn next_free_workspace_number_fills_gaps_across_outputs() {
+        assert_eq!(next_free_workspace_number(&[]), 1);
+        assert_eq!(
+            next_free_workspace_number(&[
+                workspace("1", 1, "eDP-1"),
+                workspace("2", 2, "HDMI-A-1"),
+                workspace("4", 4, "eDP-1"),
+            ]),
+            3
+        );
+        // Named workspaces without a number report -1 and do not take a slot.
+        assert_eq!(
+            next_free_workspace_number(&[workspace("mail", -1, "eDP-1")]),
+            1
+        );
+    }
 }
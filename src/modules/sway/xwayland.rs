@@ -0,0 +1,298 @@
+use std::sync::{Arc, OnceLock};
+
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+use swayipc::{EventType, Node, NodeType, ShellType};
+
+use crate::modules::broadcaster::{
+    attach_subscription, BackendRegistry, Broadcaster, Subscription,
+};
+use crate::modules::icons::Glyph;
+use crate::modules::sway::ipc::{
+    query_snapshot, recv_relevant_event_coalesced, subscribe_shared_events,
+};
+use crate::modules::{
+    render_markup_template, ModuleBuildContext, ModuleConfig, ModuleFactory, ModuleLabel,
+};
+
+const DEFAULT_FORMAT: Glyph = Glyph::new("\u{f369}", "X11");
+const XWAYLAND_CLASSES: [&str; 2] = ["xwayland", "native"];
+pub(crate) const MODULE_TYPE: &str = "sway/xwayland";
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct XwaylandConfig {
+    #[serde(default)]
+    pub(crate) format: Option<String>,
+    /// Shown for native Wayland windows; empty hides the module.
+    #[serde(rename = "format-native", alias = "format_native", default)]
+    pub(crate) format_native: Option<String>,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+/// The focused window, if any.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FocusedShell {
+    window: Option<FocusedWindow>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FocusedWindow {
+    xwayland: bool,
+    /// Wayland `app_id`, or the X11 class under XWayland.
+    app: String,
+    title: String,
+    output: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct XwaylandSharedKey;
+
+pub(crate) struct SwayXwaylandFactory;
+
+pub(crate) const FACTORY: SwayXwaylandFactory = SwayXwaylandFactory;
+
+impl ModuleFactory for SwayXwaylandFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        Ok(build_xwayland_module(parsed, context.monitor_connector.clone()).upcast())
+    }
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<XwaylandConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn xwayland_registry() -> &'static BackendRegistry<XwaylandSharedKey, Broadcaster<FocusedShell>> {
+    static REGISTRY: OnceLock<BackendRegistry<XwaylandSharedKey, Broadcaster<FocusedShell>>> =
+        OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+fn subscribe_shared_xwayland() -> Subscription<FocusedShell> {
+    let (broadcaster, start_worker) =
+        xwayland_registry().get_or_create(XwaylandSharedKey, Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_xwayland_worker(broadcaster);
+    }
+
+    receiver
+}
+
+fn start_xwayland_worker(broadcaster: Arc<Broadcaster<FocusedShell>>) {
+    std::thread::spawn(move || {
+        let mut last = query_focused_shell();
+        broadcaster.broadcast(last.clone());
+        let events = subscribe_shared_events();
+
+        loop {
+            if broadcaster.subscriber_count() == 0 {
+                xwayland_registry().remove(&XwaylandSharedKey, &broadcaster);
+                return;
+            }
+
+            match recv_relevant_event_coalesced(
+                &events,
+                &[EventType::Window, EventType::Workspace, EventType::Output],
+            ) {
+                Ok(true) => {
+                    let current = query_focused_shell();
+                    if current != last {
+                        broadcaster.broadcast(current.clone());
+                        last = current;
+                    }
+                }
+                Ok(false) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+            }
+        }
+    });
+}
+
+fn query_focused_shell() -> FocusedShell {
+    FocusedShell {
+        window: query_snapshot()
+            .tree
+            .as_ref()
+            .and_then(|tree| focused_window(tree, None)),
+    }
+}
+
+fn focused_window(node: &Node, current_output: Option<&str>) -> Option<FocusedWindow> {
+    let output = if node.node_type == NodeType::Output {
+        node.name.as_deref()
+    } else {
+        current_output
+    };
+
+    if let Some(window) = node
+        .nodes
+        .iter()
+        .chain(&node.floating_nodes)
+        .find_map(|child| focused_window(child, output))
+    {
+        return Some(window);
+    }
+
+    if !node.focused || node.shell.is_none() {
+        return None;
+    }
+
+    let xwayland = matches!(node.shell, Some(ShellType::Xwayland));
+    let app = node
+        .app_id
+        .clone()
+        .or_else(|| {
+            node.window_properties
+                .as_ref()
+                .and_then(|properties| properties.class.clone())
+        })
+        .unwrap_or_default();
+    Some(FocusedWindow {
+        xwayland,
+        app,
+        title: node.name.clone().unwrap_or_default(),
+        output: output.map(ToOwned::to_owned),
+    })
+}
+
+fn build_xwayland_module(config: XwaylandConfig, output_filter: Option<String>) -> Label {
+    let label = ModuleLabel::new("sway-xwayland")
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(config.click.or(config.on_click))
+        .into_label();
+    label.set_visible(false);
+
+    let format = config
+        .format
+        .unwrap_or_else(|| DEFAULT_FORMAT.get().to_string());
+    let format_native = config.format_native.unwrap_or_default();
+
+    attach_subscription(&label, subscribe_shared_xwayland(), move |label, shell| {
+        let window = shell
+            .window
+            .filter(|window| belongs_to_output(window, output_filter.as_deref()));
+        let text = window
+            .as_ref()
+            .map(|window| render_format(&format, &format_native, window))
+            .unwrap_or_default();
+        for class_name in XWAYLAND_CLASSES {
+            label.remove_css_class(class_name);
+        }
+
+        let visible = !text.trim().is_empty();
+        label.set_visible(visible);
+        if !visible {
+            return;
+        }
+        label.set_markup(&text);
+        if let Some(window) = window {
+            label.add_css_class(if window.xwayland {
+                "xwayland"
+            } else {
+                "native"
+            });
+        }
+    });
+
+    label
+}
+
+/// Like `sway/window`, a bar on another output shows nothing.
+fn belongs_to_output(window: &FocusedWindow, output_filter: Option<&str>) -> bool {
+    match (output_filter, window.output.as_deref()) {
+        (Some(expected), Some(current)) => expected == current,
+        (Some(_), None) => false,
+        (None, _) => true,
+    }
+}
+
+fn render_format(format: &str, format_native: &str, window: &FocusedWindow) -> String {
+    let format = if window.xwayland {
+        format
+    } else {
+        format_native
+    };
+    render_markup_template(
+        format,
+        &[("{app}", &window.app), ("{title}", &window.title)],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+
+    fn window(xwayland: bool, output: Option<&str>) -> FocusedWindow {
+        FocusedWindow {
+            xwayland,
+            app: "steam".to_string(),
+            title: "Steam & friends".to_string(),
+            output: output.map(ToOwned::to_owned),
+        }
+    }
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'sway/xwayland'"));
+    }
+
+    #[test]
+    fn parse_config_reads_native_format_alias() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_str("{\"format_native\":\"wl\"}")
+                .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.format_native.as_deref(), Some("wl"));
+        assert!(cfg.format.is_none());
+    }
+
+    #[test]
+    fn render_format_picks_format_by_shell() {
+        let text = render_format("X {app}: {title}", "", &window(true, None));
+        assert_eq!(text, "X steam: Steam &amp; friends");
+        assert_eq!(render_format("X", "", &window(false, None)), "");
+        assert_eq!(
+            render_format("X", "W {app}", &window(false, None)),
+            "W steam"
+        );
+    }
+
+    #[test]
+    fn belongs_to_output_matches_the_bar_output() {
+        assert!(belongs_to_output(&window(true, Some("DP-1")), Some("DP-1")));
+        assert!(!belongs_to_output(
+            &window(true, Some("DP-1")),
+            Some("HDMI-A-1")
+        ));
+        assert!(!belongs_to_output(&window(true, None), Some("DP-1")));
+        assert!(belongs_to_output(&window(true, None), None));
+    }
+}
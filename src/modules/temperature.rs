@@ -1,4 +1,5 @@
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
@@ -10,8 +11,11 @@ use serde_json::Value;
 use crate::modules::broadcaster::{
     attach_subscription, BackendRegistry, Broadcaster, Subscription,
 };
+use crate::modules::text::{attach_text_subscription, TextBlock, TextSink};
+use crate::modules::tooltip::ModuleTooltip;
 use crate::modules::{
-    escape_markup_text, render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel,
+    escape_markup_text, render_markup_template, state_store, ModuleBuildContext, ModuleConfig,
+    ModuleLabel,
 };
 
 use super::ModuleFactory;
@@ -19,32 +23,37 @@ use super::ModuleFactory;
 const MIN_TEMPERATURE_INTERVAL_SECS: u32 = 1;
 const DEFAULT_TEMPERATURE_INTERVAL_SECS: u32 = 10;
 const DEFAULT_TEMPERATURE_FORMAT: &str = "{temperatureC}°C {icon}";
+const DEFAULT_TEMPERATURE_FORMAT_FAHRENHEIT: &str = "{temperatureF}°F {icon}";
 const TEMPERATURE_STATE_CLASSES: [&str; 4] = [
     "temperature-normal",
     "temperature-warning",
     "temperature-critical",
     "temperature-unknown",
 ];
+const HWMON_ROOT: &str = "/sys/class/hwmon";
 pub(crate) const MODULE_TYPE: &str = "temperature";
 
 #[derive(Debug, Deserialize, Clone)]
 pub(crate) struct TemperatureConfig {
     #[serde(default)]
     pub(crate) format: Option<String>,
-    #[serde(rename = "format-warning", default)]
+    #[serde(rename = "format-warning", alias = "format_warning", default)]
     pub(crate) format_warning: Option<String>,
-    #[serde(rename = "format-critical", default)]
+    #[serde(rename = "format-critical", alias = "format_critical", default)]
     pub(crate) format_critical: Option<String>,
     #[serde(default = "default_temperature_interval")]
     pub(crate) interval_secs: u32,
-    #[serde(rename = "path", alias = "hwmon-path", alias = "hwmon_path", default)]
-    pub(crate) sensor_path: Option<String>,
-    #[serde(rename = "thermal-zone", alias = "thermal_zone", default)]
-    pub(crate) thermal_zone: Option<u32>,
+    #[serde(flatten)]
+    pub(crate) sensor: TemperatureSensorConfig,
+    /// Tried in order; the first one that reads wins.
+    #[serde(default)]
+    pub(crate) sensors: Vec<TemperatureSensorConfig>,
     #[serde(rename = "warning-threshold", alias = "warning_threshold", default)]
     pub(crate) warning_threshold: Option<i32>,
     #[serde(rename = "critical-threshold", alias = "critical_threshold", default)]
     pub(crate) critical_threshold: Option<i32>,
+    #[serde(default)]
+    pub(crate) units: TemperatureUnits,
     #[serde(rename = "format-icons", default = "default_temperature_icons")]
     pub(crate) format_icons: Vec<String>,
     #[serde(default)]
@@ -55,26 +64,91 @@ pub(crate) struct TemperatureConfig {
     pub(crate) class: Option<String>,
 }
 
+/// Where to read a temperature; either a file (`path`, `thermal-zone`) or an
+/// hwmon sensor found by `hwmon-name` and/or `label`.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub(crate) struct TemperatureSensorConfig {
+    #[serde(rename = "path", alias = "hwmon-path", alias = "hwmon_path", default)]
+    pub(crate) path: Option<String>,
+    #[serde(rename = "thermal-zone", alias = "thermal_zone", default)]
+    pub(crate) thermal_zone: Option<u32>,
+    #[serde(rename = "hwmon-name", alias = "hwmon_name", default)]
+    pub(crate) hwmon_name: Option<String>,
+    #[serde(default)]
+    pub(crate) label: Option<String>,
+}
+
+impl TemperatureSensorConfig {
+    fn is_empty(&self) -> bool {
+        self.path.is_none()
+            && self.thermal_zone.is_none()
+            && self.hwmon_name.is_none()
+            && self.label.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum TemperatureSource {
+    Path(String),
+    /// Matched against `/sys/class/hwmon/*/name` and `temp*_label` on every
+    /// read, since hwmon numbering is not stable across boots.
+    Hwmon {
+        name: Option<String>,
+        label: Option<String>,
+    },
+}
+
+/// Unit for `{temperature}`; thresholds are always in Celsius.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TemperatureUnits {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
 #[derive(Debug, Clone, Copy)]
-struct TemperatureReading {
-    celsius: f64,
+pub(super) struct TemperatureReading {
+    pub(super) celsius: f64,
+}
+
+impl TemperatureReading {
+    fn fahrenheit(self) -> f64 {
+        celsius_to_fahrenheit(self.celsius)
+    }
+
+    /// Rounded value in `units`.
+    fn rounded_in(self, units: TemperatureUnits) -> i32 {
+        match units {
+            TemperatureUnits::Celsius => self.celsius.round() as i32,
+            TemperatureUnits::Fahrenheit => self.fahrenheit().round() as i32,
+        }
+    }
+}
+
+fn celsius_to_fahrenheit(celsius: f64) -> f64 {
+    celsius * 1.8 + 32.0
 }
 
 #[derive(Debug, Clone)]
 struct TemperatureUiUpdate {
     text: String,
+    tooltip: String,
     state_class: &'static str,
     visible: bool,
+    published: state_store::PublishedValues,
 }
 
 #[derive(Debug, Clone)]
 struct TemperatureRuntimeConfig {
-    sensor_path: String,
+    sources: Vec<TemperatureSource>,
     base_format: String,
     warning_format: Option<String>,
     critical_format: Option<String>,
+    tooltip_format: Option<String>,
     warning_threshold: Option<i32>,
     critical_threshold: Option<i32>,
+    units: TemperatureUnits,
     format_icons: Vec<String>,
     interval_secs: u32,
     click_command: Option<String>,
@@ -83,12 +157,14 @@ struct TemperatureRuntimeConfig {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct TemperatureSharedKey {
-    sensor_path: String,
+    sources: Vec<TemperatureSource>,
     base_format: String,
     warning_format: Option<String>,
     critical_format: Option<String>,
+    tooltip_format: Option<String>,
     warning_threshold: Option<i32>,
     critical_threshold: Option<i32>,
+    units: TemperatureUnits,
     format_icons: Vec<String>,
     interval_secs: u32,
 }
@@ -102,36 +178,105 @@ impl ModuleFactory for TemperatureFactory {
         MODULE_TYPE
     }
 
-    fn init(&self, config: &ModuleConfig, _context: &ModuleBuildContext) -> Result<Widget, String> {
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
         let parsed = parse_config(config)?;
-        let click_command = parsed.click.or(parsed.on_click);
-        let base_format = parsed
-            .format
-            .unwrap_or_else(|| DEFAULT_TEMPERATURE_FORMAT.to_string());
-
-        Ok(build_temperature_module(TemperatureRuntimeConfig {
-            sensor_path: resolve_temperature_sensor_path(
-                parsed.sensor_path.clone(),
-                parsed.thermal_zone,
-            ),
-            base_format,
-            warning_format: parsed.format_warning,
-            critical_format: parsed.format_critical,
-            warning_threshold: parsed.warning_threshold,
-            critical_threshold: parsed.critical_threshold,
-            format_icons: parsed.format_icons,
-            interval_secs: parsed.interval_secs,
-            click_command,
-            class: parsed.class,
-        })
+        Ok(build_temperature_module(
+            runtime_config(parsed, context.tooltip_format.clone())?,
+            context.published_namespace(MODULE_TYPE),
+        )
         .upcast())
     }
+
+    fn init_text(&self, config: &ModuleConfig, sink: TextSink) -> Result<(), String> {
+        let config = runtime_config(parse_config(config)?, None)?;
+        let config = TemperatureRuntimeConfig {
+            interval_secs: normalized_temperature_interval(config.interval_secs),
+            ..config
+        };
+        attach_text_subscription(subscribe_shared_temperature(&config), sink, |update| {
+            state_store::publish(MODULE_TYPE, &update.published);
+            update.visible.then(|| {
+                TextBlock::new(update.text).urgent(update.state_class == "temperature-critical")
+            })
+        });
+        Ok(())
+    }
+}
+
+fn runtime_config(
+    parsed: TemperatureConfig,
+    tooltip_format: Option<String>,
+) -> Result<TemperatureRuntimeConfig, String> {
+    let base_format = parsed
+        .format
+        .unwrap_or_else(|| default_temperature_format(parsed.units).to_string());
+
+    Ok(TemperatureRuntimeConfig {
+        sources: temperature_sources(parsed.sensor, parsed.sensors)?,
+        base_format,
+        warning_format: parsed.format_warning,
+        critical_format: parsed.format_critical,
+        tooltip_format,
+        warning_threshold: parsed.warning_threshold,
+        critical_threshold: parsed.critical_threshold,
+        units: parsed.units,
+        format_icons: parsed.format_icons,
+        interval_secs: parsed.interval_secs,
+        click_command: parsed.click.or(parsed.on_click),
+        class: parsed.class,
+    })
+}
+
+/// The top-level sensor keys are shorthand for a one-entry `sensors` list.
+fn temperature_sources(
+    top_level: TemperatureSensorConfig,
+    sensors: Vec<TemperatureSensorConfig>,
+) -> Result<Vec<TemperatureSource>, String> {
+    if sensors.is_empty() {
+        return Ok(vec![temperature_source(top_level)?]);
+    }
+    if !top_level.is_empty() {
+        return Err(format!(
+            "invalid {MODULE_TYPE} module config: set sensor keys either at the top level or in `sensors`, not both"
+        ));
+    }
+    sensors.into_iter().map(temperature_source).collect()
+}
+
+fn temperature_source(sensor: TemperatureSensorConfig) -> Result<TemperatureSource, String> {
+    let hwmon = sensor.hwmon_name.is_some() || sensor.label.is_some();
+    if hwmon && (sensor.path.is_some() || sensor.thermal_zone.is_some()) {
+        return Err(format!(
+            "invalid {MODULE_TYPE} module config: `hwmon-name`/`label` cannot be combined with `path` or `thermal-zone`"
+        ));
+    }
+    if hwmon {
+        return Ok(TemperatureSource::Hwmon {
+            name: sensor.hwmon_name,
+            label: sensor.label,
+        });
+    }
+    Ok(TemperatureSource::Path(resolve_temperature_sensor_path(
+        sensor.path,
+        sensor.thermal_zone,
+    )))
 }
 
 fn default_temperature_interval() -> u32 {
     DEFAULT_TEMPERATURE_INTERVAL_SECS
 }
 
+fn default_temperature_format(units: TemperatureUnits) -> &'static str {
+    match units {
+        TemperatureUnits::Celsius => DEFAULT_TEMPERATURE_FORMAT,
+        TemperatureUnits::Fahrenheit => DEFAULT_TEMPERATURE_FORMAT_FAHRENHEIT,
+    }
+}
+
 fn default_temperature_icons() -> Vec<String> {
     vec![
         "".to_string(),
@@ -158,7 +303,7 @@ pub(crate) fn normalized_temperature_interval(interval_secs: u32) -> u32 {
     interval_secs.max(MIN_TEMPERATURE_INTERVAL_SECS)
 }
 
-fn resolve_temperature_sensor_path(
+pub(super) fn resolve_temperature_sensor_path(
     explicit_path: Option<String>,
     thermal_zone: Option<u32>,
 ) -> String {
@@ -182,12 +327,14 @@ fn subscribe_shared_temperature(
     config: &TemperatureRuntimeConfig,
 ) -> Subscription<TemperatureUiUpdate> {
     let key = TemperatureSharedKey {
-        sensor_path: config.sensor_path.clone(),
+        sources: config.sources.clone(),
         base_format: config.base_format.clone(),
         warning_format: config.warning_format.clone(),
         critical_format: config.critical_format.clone(),
+        tooltip_format: config.tooltip_format.clone(),
         warning_threshold: config.warning_threshold,
         critical_threshold: config.critical_threshold,
+        units: config.units,
         format_icons: config.format_icons.clone(),
         interval_secs: config.interval_secs,
     };
@@ -210,36 +357,14 @@ fn start_temperature_worker(
 ) {
     let interval = Duration::from_secs(u64::from(config.interval_secs));
     std::thread::spawn(move || loop {
-        let update = match read_temperature_reading(&config.sensor_path) {
-            Ok(reading) => {
-                let state_class = temperature_state_class(
-                    reading,
-                    config.warning_threshold,
-                    config.critical_threshold,
-                );
-                let chosen_format = match state_class {
-                    "temperature-critical" => config
-                        .critical_format
-                        .as_deref()
-                        .unwrap_or(config.base_format.as_str()),
-                    "temperature-warning" => config
-                        .warning_format
-                        .as_deref()
-                        .unwrap_or(config.base_format.as_str()),
-                    _ => config.base_format.as_str(),
-                };
-                let text = render_temperature_format(chosen_format, reading, &config.format_icons);
-
-                TemperatureUiUpdate {
-                    visible: !text.trim().is_empty(),
-                    text,
-                    state_class,
-                }
-            }
+        let update = match read_first_source(&config.sources, Path::new(HWMON_ROOT)) {
+            Ok((reading, sensor)) => reading_update(&config, reading, &sensor),
             Err(err) => TemperatureUiUpdate {
                 text: escape_markup_text(&format!("temperature error: {err}")),
+                tooltip: String::new(),
                 state_class: "temperature-unknown",
                 visible: true,
+                published: Vec::new(),
             },
         };
 
@@ -252,7 +377,7 @@ fn start_temperature_worker(
     });
 }
 
-fn build_temperature_module(config: TemperatureRuntimeConfig) -> Label {
+fn build_temperature_module(config: TemperatureRuntimeConfig, namespace: String) -> Label {
     let label = ModuleLabel::new("temperature")
         .with_css_classes(config.class.as_deref())
         .with_click_command(config.click_command.clone())
@@ -271,13 +396,21 @@ fn build_temperature_module(config: TemperatureRuntimeConfig) -> Label {
         ..config
     };
 
+    let tooltip = config
+        .tooltip_format
+        .is_some()
+        .then(|| ModuleTooltip::attach(&label));
     let subscription = subscribe_shared_temperature(&config);
 
-    attach_subscription(&label, subscription, |label, update| {
+    attach_subscription(&label, subscription, move |label, update| {
+        state_store::publish(&namespace, &update.published);
         label.set_visible(update.visible);
         if update.visible {
             label.set_markup(&update.text);
         }
+        if let Some(tooltip) = &tooltip {
+            tooltip.set_markup(&update.tooltip);
+        }
         for class_name in TEMPERATURE_STATE_CLASSES {
             label.remove_css_class(class_name);
         }
@@ -287,7 +420,7 @@ fn build_temperature_module(config: TemperatureRuntimeConfig) -> Label {
     label
 }
 
-fn read_temperature_reading(sensor_path: &str) -> Result<TemperatureReading, String> {
+pub(super) fn read_temperature_reading(sensor_path: &str) -> Result<TemperatureReading, String> {
     let raw = fs::read_to_string(sensor_path)
         .map_err(|err| format!("failed to read {sensor_path}: {err}"))?;
     let parsed = raw
@@ -302,34 +435,184 @@ fn read_temperature_reading(sensor_path: &str) -> Result<TemperatureReading, Str
     Ok(TemperatureReading { celsius })
 }
 
+/// Reads the first source that works, with the name `{sensor}` shows for it.
+/// Fails with every source's error when none does.
+fn read_first_source(
+    sources: &[TemperatureSource],
+    hwmon_root: &Path,
+) -> Result<(TemperatureReading, String), String> {
+    let mut errors = Vec::new();
+    for source in sources {
+        let found = match source {
+            TemperatureSource::Path(path) => Ok((PathBuf::from(path), path.clone())),
+            TemperatureSource::Hwmon { name, label } => {
+                find_hwmon_input(hwmon_root, name.as_deref(), label.as_deref())
+            }
+        };
+        let read = found.and_then(|(path, sensor)| {
+            read_temperature_reading(&path.to_string_lossy()).map(|reading| (reading, sensor))
+        });
+        match read {
+            Ok(read) => return Ok(read),
+            Err(err) => errors.push(err),
+        }
+    }
+    Err(errors.join("; "))
+}
+
+/// The first `temp*_input` (by hwmon and input number) whose chip name and
+/// label match, with its label (or chip name) as the sensor name.
+pub(super) fn find_hwmon_input(
+    root: &Path,
+    name: Option<&str>,
+    label: Option<&str>,
+) -> Result<(PathBuf, String), String> {
+    let read_trimmed = |path: PathBuf| {
+        fs::read_to_string(path)
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+
+    for chip in numbered_entries(root, "hwmon", "") {
+        let chip_name = read_trimmed(chip.join("name")).unwrap_or_default();
+        if name.is_some_and(|name| name != chip_name) {
+            continue;
+        }
+        for input in numbered_entries(&chip, "temp", "_input") {
+            let input_label = input
+                .file_name()
+                .and_then(|file| file.to_str())
+                .and_then(|file| file.strip_suffix("_input"))
+                .and_then(|stem| read_trimmed(chip.join(format!("{stem}_label"))));
+            if label.is_some_and(|label| input_label.as_deref() != Some(label)) {
+                continue;
+            }
+            let sensor = input_label.unwrap_or_else(|| chip_name.clone());
+            return Ok((input, sensor));
+        }
+    }
+
+    let mut wanted = Vec::new();
+    if let Some(name) = name {
+        wanted.push(format!("name '{name}'"));
+    }
+    if let Some(label) = label {
+        wanted.push(format!("label '{label}'"));
+    }
+    Err(format!("no hwmon sensor with {}", wanted.join(" and ")))
+}
+
+/// Entries of `dir` named `<prefix><number><suffix>`, sorted by number.
+pub(super) fn numbered_entries(dir: &Path, prefix: &str, suffix: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut numbered: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let number = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix(prefix)?
+                .strip_suffix(suffix)?
+                .parse()
+                .ok()?;
+            Some((number, entry.path()))
+        })
+        .collect();
+    numbered.sort_by_key(|(number, _)| *number);
+    numbered.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Label update for one sensor reading.
+fn reading_update(
+    config: &TemperatureRuntimeConfig,
+    reading: TemperatureReading,
+    sensor: &str,
+) -> TemperatureUiUpdate {
+    let state_class =
+        temperature_state_class(reading, config.warning_threshold, config.critical_threshold);
+    let chosen_format = select_temperature_format(config, state_class);
+    let text = render_temperature_format(
+        chosen_format,
+        reading,
+        sensor,
+        config.units,
+        &config.format_icons,
+    );
+    let tooltip = config
+        .tooltip_format
+        .as_deref()
+        .map(|format| {
+            render_temperature_format(format, reading, sensor, config.units, &config.format_icons)
+        })
+        .unwrap_or_default();
+
+    TemperatureUiUpdate {
+        visible: !text.trim().is_empty(),
+        text,
+        tooltip,
+        state_class,
+        published: vec![
+            (
+                "temperature_c",
+                reading.rounded_in(TemperatureUnits::Celsius).to_string(),
+            ),
+            (
+                "temperature_f",
+                reading.rounded_in(TemperatureUnits::Fahrenheit).to_string(),
+            ),
+        ],
+    }
+}
+
+/// Thresholds are in Celsius whatever `units` is, so switching the display
+/// unit does not move them.
 fn temperature_state_class(
     reading: TemperatureReading,
     warning_threshold: Option<i32>,
     critical_threshold: Option<i32>,
 ) -> &'static str {
-    let celsius = reading.celsius.round() as i32;
+    let value = reading.rounded_in(TemperatureUnits::Celsius);
 
     if let Some(critical) = critical_threshold {
-        if celsius >= critical {
+        if value >= critical {
             return "temperature-critical";
         }
     }
     if let Some(warning) = warning_threshold {
-        if celsius >= warning {
+        if value >= warning {
             return "temperature-warning";
         }
     }
     "temperature-normal"
 }
 
+/// `format-critical` / `format-warning` when above that threshold, falling
+/// back to `format`.
+fn select_temperature_format<'a>(
+    config: &'a TemperatureRuntimeConfig,
+    state_class: &str,
+) -> &'a str {
+    let override_format = match state_class {
+        "temperature-critical" => config.critical_format.as_deref(),
+        "temperature-warning" => config.warning_format.as_deref(),
+        _ => None,
+    };
+    override_format.unwrap_or(config.base_format.as_str())
+}
+
 fn render_temperature_format(
     format: &str,
     reading: TemperatureReading,
+    sensor: &str,
+    units: TemperatureUnits,
     format_icons: &[String],
 ) -> String {
-    let celsius = reading.celsius.round() as i32;
-    let fahrenheit = (reading.celsius * 1.8 + 32.0).round() as i32;
+    let celsius = reading.rounded_in(TemperatureUnits::Celsius);
+    let fahrenheit = reading.rounded_in(TemperatureUnits::Fahrenheit);
     let kelvin = (reading.celsius + 273.15).round() as i32;
+    let temperature = reading.rounded_in(units);
     let icon = super::icon_for_percentage(format_icons, celsius.clamp(0, 100) as u8);
 
     render_markup_template(
@@ -341,7 +624,9 @@ fn render_temperature_format(
             ("{temperatureC}", &celsius.to_string()),
             ("{temperatureF}", &fahrenheit.to_string()),
             ("{temperatureK}", &kelvin.to_string()),
+            ("{temperature}", &temperature.to_string()),
             ("{icon}", icon),
+            ("{sensor}", sensor),
         ],
     )
 }
@@ -395,6 +680,124 @@ mod tests {
         assert_eq!(path, "/sys/class/thermal/thermal_zone2/temp");
     }
 
+    fn hwmon_chip(root: &Path, index: u32, name: &str, inputs: &[(u32, Option<&str>, &str)]) {
+        let chip = root.join(format!("hwmon{index}"));
+        fs::create_dir_all(&chip).expect("test hwmon dir should create");
+        write(&chip.join("name"), &format!("{name}\n"));
+        for (number, label, value) in inputs {
+            write(&chip.join(format!("temp{number}_input")), value);
+            if let Some(label) = label {
+                write(&chip.join(format!("temp{number}_label")), label);
+            }
+        }
+    }
+
+    #[test]
+    fn find_hwmon_input_matches_name_and_label() {
+        let root = test_path("hwmon");
+        hwmon_chip(&root, 0, "acpitz", &[(1, None, "30000")]);
+        hwmon_chip(
+            &root,
+            10,
+            "coretemp",
+            &[
+                (10, Some("Core 8"), "51000"),
+                (1, Some("Package id 0"), "55000"),
+            ],
+        );
+        hwmon_chip(&root, 2, "coretemp", &[(2, Some("Core 0"), "50000")]);
+
+        let (path, sensor) = find_hwmon_input(&root, Some("coretemp"), None).expect("chip found");
+        assert_eq!(path, root.join("hwmon2/temp2_input"));
+        assert_eq!(sensor, "Core 0");
+
+        let (path, sensor) =
+            find_hwmon_input(&root, None, Some("Package id 0")).expect("label found");
+        assert_eq!(path, root.join("hwmon10/temp1_input"));
+        assert_eq!(sensor, "Package id 0");
+
+        let (_, sensor) = find_hwmon_input(&root, Some("acpitz"), None).expect("chip found");
+        assert_eq!(sensor, "acpitz");
+
+        let err = find_hwmon_input(&root, Some("k10temp"), Some("Tctl")).expect_err("no chip");
+        assert_eq!(err, "no hwmon sensor with name 'k10temp' and label 'Tctl'");
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn read_first_source_falls_back_in_order() {
+        let root = test_path("hwmon-fallback");
+        hwmon_chip(&root, 0, "nvme", &[(1, Some("Composite"), "38850")]);
+        let sources = vec![
+            TemperatureSource::Hwmon {
+                name: Some("k10temp".to_string()),
+                label: None,
+            },
+            TemperatureSource::Hwmon {
+                name: Some("nvme".to_string()),
+                label: Some("Composite".to_string()),
+            },
+        ];
+
+        let (reading, sensor) = read_first_source(&sources, &root).expect("fallback reads");
+        assert_eq!(reading.celsius, 38.85);
+        assert_eq!(sensor, "Composite");
+
+        let err = read_first_source(&sources[..1], &root).expect_err("nothing matches");
+        assert!(err.contains("name 'k10temp'"));
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn runtime_config_builds_sensor_chain() {
+        let parse = |value: serde_json::Value| {
+            let module = ModuleConfig::new(
+                MODULE_TYPE,
+                serde_json::from_value(value).expect("module config map should parse"),
+            );
+            runtime_config(parse_config(&module).expect("config should parse"), None)
+        };
+
+        let config = parse(serde_json::json!({
+            "hwmon_name": "coretemp",
+            "label": "Package id 0",
+        }))
+        .expect("hwmon sensor is valid");
+        assert_eq!(
+            config.sources,
+            vec![TemperatureSource::Hwmon {
+                name: Some("coretemp".to_string()),
+                label: Some("Package id 0".to_string()),
+            }]
+        );
+
+        let config = parse(serde_json::json!({
+            "sensors": [{ "hwmon-name": "k10temp", "label": "Tctl" }, { "thermal-zone": 1 }],
+        }))
+        .expect("sensor chain is valid");
+        assert_eq!(
+            config.sources[1],
+            TemperatureSource::Path("/sys/class/thermal/thermal_zone1/temp".to_string())
+        );
+
+        let config = parse(serde_json::json!({})).expect("defaults are valid");
+        assert_eq!(
+            config.sources,
+            vec![TemperatureSource::Path(
+                "/sys/class/thermal/thermal_zone0/temp".to_string()
+            )]
+        );
+
+        assert!(parse(serde_json::json!({
+            "path": "/tmp/sensor",
+            "sensors": [{ "thermal-zone": 1 }],
+        }))
+        .is_err());
+        assert!(parse(serde_json::json!({ "hwmon-name": "coretemp", "thermal-zone": 1 })).is_err());
+    }
+
     #[test]
     fn read_temperature_reading_parses_millidegree_values() {
         let path = test_path("millidegree");
@@ -410,12 +813,90 @@ mod tests {
     #[test]
     fn render_temperature_format_replaces_placeholders() {
         let text = render_temperature_format(
-            "{temperatureC} {temperatureF} {temperatureK} {icon}",
+            "{temperatureC} {temperatureF} {temperatureK} {temperature} {icon}",
             TemperatureReading { celsius: 42.5 },
+            "",
+            TemperatureUnits::Celsius,
             &["cold".to_string(), "hot".to_string()],
         );
 
-        assert_eq!(text, "43 109 316 cold");
+        assert_eq!(text, "43 109 316 43 cold");
+
+        let text = render_temperature_format(
+            "{temperature}",
+            TemperatureReading { celsius: 42.5 },
+            "",
+            TemperatureUnits::Fahrenheit,
+            &[],
+        );
+        assert_eq!(text, "109");
+    }
+
+    #[test]
+    fn celsius_to_fahrenheit_converts_reference_points() {
+        assert_eq!(celsius_to_fahrenheit(0.0), 32.0);
+        assert_eq!(celsius_to_fahrenheit(100.0), 212.0);
+        assert_eq!(celsius_to_fahrenheit(-40.0), -40.0);
+        assert_eq!(
+            TemperatureReading { celsius: 37.0 }.rounded_in(TemperatureUnits::Fahrenheit),
+            99
+        );
+    }
+
+    #[test]
+    fn parse_config_reads_units() {
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(serde_json::json!({ "units": "fahrenheit" }))
+                .expect("module config map should parse"),
+        );
+        let cfg = parse_config(&module).expect("config should parse");
+        assert_eq!(cfg.units, TemperatureUnits::Fahrenheit);
+        assert_eq!(
+            default_temperature_format(cfg.units),
+            DEFAULT_TEMPERATURE_FORMAT_FAHRENHEIT
+        );
+
+        let cfg =
+            parse_config(&ModuleConfig::new(MODULE_TYPE, Map::new())).expect("config should parse");
+        assert_eq!(cfg.units, TemperatureUnits::Celsius);
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(serde_json::json!({ "units": "kelvin" }))
+                .expect("module config map should parse"),
+        );
+        assert!(parse_config(&module).is_err());
+    }
+
+    #[test]
+    fn select_temperature_format_prefers_state_override() {
+        let config = TemperatureRuntimeConfig {
+            sources: vec![TemperatureSource::Path("/tmp/sensor".to_string())],
+            base_format: "base".to_string(),
+            warning_format: None,
+            critical_format: Some("hot!".to_string()),
+            tooltip_format: None,
+            warning_threshold: Some(60),
+            critical_threshold: Some(80),
+            units: TemperatureUnits::Celsius,
+            format_icons: Vec::new(),
+            interval_secs: 10,
+            click_command: None,
+            class: None,
+        };
+        assert_eq!(
+            select_temperature_format(&config, "temperature-critical"),
+            "hot!"
+        );
+        assert_eq!(
+            select_temperature_format(&config, "temperature-warning"),
+            "base"
+        );
+        assert_eq!(
+            select_temperature_format(&config, "temperature-normal"),
+            "base"
+        );
     }
 
     #[test]
@@ -434,10 +915,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn temperature_state_class_keeps_celsius_thresholds() {
+        // 50C is 122F; a Celsius threshold of 85 must not read as 85F.
+        assert_eq!(
+            temperature_state_class(TemperatureReading { celsius: 50.0 }, None, Some(85)),
+            "temperature-normal"
+        );
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(serde_json::json!({
+                "units": "fahrenheit",
+                "critical-threshold": 80,
+            }))
+            .expect("module config map should parse"),
+        );
+        let config = runtime_config(parse_config(&module).expect("config should parse"), None)
+            .expect("runtime config should build");
+        assert_eq!(config.units, TemperatureUnits::Fahrenheit);
+        assert_eq!(
+            reading_update(&config, TemperatureReading { celsius: 80.4 }, "cpu").state_class,
+            "temperature-critical"
+        );
+        assert_eq!(
+            reading_update(&config, TemperatureReading { celsius: 79.4 }, "cpu").state_class,
+            "temperature-normal"
+        );
+    }
+
     #[test]
     fn temperature_visibility_hides_when_selected_format_is_empty() {
         let empty = "";
-        let text = render_temperature_format(empty, TemperatureReading { celsius: 42.0 }, &[]);
+        let text = render_temperature_format(
+            empty,
+            TemperatureReading { celsius: 42.0 },
+            "",
+            TemperatureUnits::Celsius,
+            &[],
+        );
         assert!(text.trim().is_empty());
     }
 }
@@ -0,0 +1,60 @@
+//! Headless module output for `vibar --stdout`.
+//!
+//! Modules that support it render into a [`TextBlock`] instead of a widget;
+//! the stdout runner collects the blocks into i3bar protocol status lines.
+
+use std::rc::Rc;
+
+use gtk::glib;
+use gtk::glib::IOCondition;
+
+use crate::modules::broadcaster::{drain_pipe, Subscription};
+
+/// One rendered module in a status line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TextBlock {
+    /// Pango markup, as it would be shown in the module label.
+    pub(crate) markup: String,
+    pub(crate) urgent: bool,
+}
+
+impl TextBlock {
+    pub(crate) fn new(markup: impl Into<String>) -> Self {
+        Self {
+            markup: markup.into(),
+            urgent: false,
+        }
+    }
+
+    pub(crate) fn urgent(mut self, urgent: bool) -> Self {
+        self.urgent = urgent;
+        self
+    }
+}
+
+/// Receives a module's latest block; `None` hides the module.
+pub(crate) type TextSink = Rc<dyn Fn(Option<TextBlock>)>;
+
+/// Hidden when the markup is blank, like the label-based modules.
+pub(crate) fn visible_block(markup: &str) -> Option<TextBlock> {
+    (!markup.trim().is_empty()).then(|| TextBlock::new(markup))
+}
+
+/// Headless counterpart of
+/// [`attach_subscription`](crate::modules::broadcaster::attach_subscription):
+/// feeds each update through `render` into `sink` on the main loop. The
+/// subscription lives as long as the process.
+pub(crate) fn attach_text_subscription<U: 'static>(
+    subscription: Subscription<U>,
+    sink: TextSink,
+    mut render: impl FnMut(U) -> Option<TextBlock> + 'static,
+) {
+    let fd = subscription.notify_fd;
+    glib::unix_fd_add_local(fd, IOCondition::IN, move |_, _| {
+        drain_pipe(fd);
+        while let Ok(update) = subscription.receiver.try_recv() {
+            sink(render(update));
+        }
+        glib::ControlFlow::Continue
+    });
+}
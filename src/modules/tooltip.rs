@@ -0,0 +1,172 @@
+//! Hover tooltips shared by every module type.
+//!
+//! `tooltip-format` is taken off the module config in `build_module` and
+//! handed to the module through `ModuleBuildContext::tooltip_format`, so
+//! each module renders it with the placeholders of its own `format`. The
+//! text is shown in a [`ModuleTooltip`]: a popover above the module that
+//! opens while the pointer is over it and follows updates live.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{EventControllerMotion, GestureClick, Label, Popover, PositionType, Widget};
+use serde_json::{Map, Value};
+
+const TOOLTIP_FORMAT_KEYS: &[&str] = &["tooltip-format", "tooltip_format"];
+
+/// Removes `tooltip-format` from `config`. An empty format counts as unset.
+pub(crate) fn take_tooltip_format(
+    config: &mut Map<String, Value>,
+) -> Result<Option<String>, String> {
+    let raw = TOOLTIP_FORMAT_KEYS
+        .iter()
+        .filter_map(|key| config.remove(*key))
+        .reduce(|first, _| first);
+    match raw {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(format)) if format.is_empty() => Ok(None),
+        Some(Value::String(format)) => Ok(Some(format)),
+        Some(_) => Err("`tooltip-format` must be a string".to_string()),
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ModuleTooltip {
+    popover: Popover,
+    label: Label,
+    /// Cleared by the owner while the tooltip would add nothing (for
+    /// example, playerctl text that is not truncated).
+    enabled: Rc<Cell<bool>>,
+    /// Set while a module popover is open, or after a click until the
+    /// pointer leaves.
+    suppressed: Rc<Cell<bool>>,
+}
+
+impl ModuleTooltip {
+    pub(crate) fn attach(anchor: &impl IsA<Widget>) -> Self {
+        let popover = Popover::new();
+        popover.add_css_class("module-tooltip-popover");
+        popover.set_has_arrow(true);
+        popover.set_position(PositionType::Top);
+        popover.set_autohide(false);
+        popover.set_can_target(false);
+        popover.set_parent(anchor);
+
+        let label = Label::new(None);
+        label.add_css_class("module-tooltip-label");
+        label.set_xalign(0.0);
+        popover.set_child(Some(&label));
+
+        let tooltip = Self {
+            popover,
+            label,
+            enabled: Rc::new(Cell::new(true)),
+            suppressed: Rc::new(Cell::new(false)),
+        };
+
+        let motion = EventControllerMotion::new();
+        {
+            let tooltip = tooltip.clone();
+            motion.connect_enter(move |_, _, _| {
+                if tooltip.should_show() {
+                    tooltip.popover.popup();
+                }
+            });
+        }
+        {
+            let tooltip = tooltip.clone();
+            motion.connect_leave(move |_| {
+                tooltip.suppressed.set(false);
+                tooltip.popover.popdown();
+            });
+        }
+        anchor.add_controller(motion);
+
+        // Clicks usually open a popover or change what the tooltip explains.
+        let click = GestureClick::builder().button(0).build();
+        {
+            let tooltip = tooltip.clone();
+            click.connect_pressed(move |_, _, _, _| {
+                tooltip.suppressed.set(true);
+                tooltip.popover.popdown();
+            });
+        }
+        anchor.add_controller(click);
+
+        tooltip
+    }
+
+    /// Extra CSS class for the popover, next to `.module-tooltip-popover`.
+    pub(crate) fn with_css_class(self, class: &str) -> Self {
+        self.popover.add_css_class(class);
+        self
+    }
+
+    /// Updates the shown markup; an empty string hides the tooltip.
+    pub(crate) fn set_markup(&self, markup: &str) {
+        self.label.set_markup(markup);
+        self.sync_open();
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+        self.sync_open();
+    }
+
+    /// Keeps the tooltip closed while `popover` is shown.
+    pub(crate) fn suppress_while(&self, popover: &Popover) {
+        {
+            let tooltip = self.clone();
+            popover.connect_show(move |_| {
+                tooltip.suppressed.set(true);
+                tooltip.popover.popdown();
+            });
+        }
+        let suppressed = self.suppressed.clone();
+        popover.connect_hide(move |_| suppressed.set(false));
+    }
+
+    fn should_show(&self) -> bool {
+        self.enabled.get() && !self.suppressed.get() && !self.label.text().is_empty()
+    }
+
+    fn sync_open(&self) {
+        if self.popover.is_visible() && !self.should_show() {
+            self.popover.popdown();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn fields(value: Value) -> Map<String, Value> {
+        serde_json::from_value(value).expect("config map should parse")
+    }
+
+    #[test]
+    fn take_tooltip_format_accepts_both_spellings() {
+        let mut config = fields(json!({ "format": "{}", "tooltip-format": "{used}" }));
+        assert_eq!(
+            take_tooltip_format(&mut config),
+            Ok(Some("{used}".to_string()))
+        );
+        assert!(!config.contains_key("tooltip-format"));
+        assert!(config.contains_key("format"));
+
+        let mut config = fields(json!({ "tooltip_format": "{total}" }));
+        assert_eq!(
+            take_tooltip_format(&mut config),
+            Ok(Some("{total}".to_string()))
+        );
+
+        let mut config = fields(json!({ "tooltip-format": "" }));
+        assert_eq!(take_tooltip_format(&mut config), Ok(None));
+        assert_eq!(take_tooltip_format(&mut fields(json!({}))), Ok(None));
+        assert!(take_tooltip_format(&mut fields(json!({ "tooltip-format": 3 }))).is_err());
+    }
+}
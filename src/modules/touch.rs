@@ -0,0 +1,124 @@
+//! Touchscreen gestures shared by modules.
+//!
+//! All controllers here are touch-only, so mouse and touchpad input keeps
+//! using the existing click/scroll handlers.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use gtk::{EventSequenceState, GestureDrag, GestureLongPress, PropagationPhase, Widget};
+
+/// Minimum horizontal travel before a drag counts as a swipe.
+const SWIPE_THRESHOLD_PX: f64 = 48.0;
+/// Vertical travel per value step while dragging.
+const DRAG_STEP_PX: f64 = 24.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SwipeDirection {
+    Left,
+    Right,
+}
+
+/// Runs `action` on a touch long-press, the touch equivalent of right click.
+pub(crate) fn attach_long_press(widget: &impl IsA<Widget>, action: impl Fn(f64, f64) + 'static) {
+    let long_press = GestureLongPress::new();
+    long_press.set_touch_only(true);
+    long_press.connect_pressed(move |gesture, x, y| {
+        gesture.set_state(EventSequenceState::Claimed);
+        action(x, y);
+    });
+    widget.add_controller(long_press);
+}
+
+/// Runs `on_swipe` once per horizontal touch swipe across `widget`.
+pub(crate) fn attach_horizontal_swipe(
+    widget: &impl IsA<Widget>,
+    on_swipe: impl Fn(SwipeDirection) + 'static,
+) {
+    let drag = touch_drag();
+    drag.connect_drag_update(|gesture, offset_x, offset_y| {
+        if swipe_direction(offset_x, offset_y).is_some() {
+            gesture.set_state(EventSequenceState::Claimed);
+        }
+    });
+    drag.connect_drag_end(move |_, offset_x, offset_y| {
+        if let Some(direction) = swipe_direction(offset_x, offset_y) {
+            on_swipe(direction);
+        }
+    });
+    widget.add_controller(drag);
+}
+
+/// Calls `on_step(increase)` for every [`DRAG_STEP_PX`] of vertical touch
+/// drag; dragging up increases.
+pub(crate) fn attach_vertical_drag_steps(
+    widget: &impl IsA<Widget>,
+    on_step: impl Fn(bool) + 'static,
+) {
+    let emitted = Rc::new(Cell::new(0));
+    let drag = touch_drag();
+    drag.connect_drag_begin({
+        let emitted = Rc::clone(&emitted);
+        move |_, _, _| emitted.set(0)
+    });
+    drag.connect_drag_update(move |gesture, _, offset_y| {
+        let target = drag_step_count(-offset_y);
+        if target != 0 {
+            gesture.set_state(EventSequenceState::Claimed);
+        }
+        while emitted.get() < target {
+            emitted.set(emitted.get() + 1);
+            on_step(true);
+        }
+        while emitted.get() > target {
+            emitted.set(emitted.get() - 1);
+            on_step(false);
+        }
+    });
+    widget.add_controller(drag);
+}
+
+fn touch_drag() -> GestureDrag {
+    let drag = GestureDrag::new();
+    drag.set_touch_only(true);
+    // Capture phase so swipes over child buttons reach the container first.
+    drag.set_propagation_phase(PropagationPhase::Capture);
+    drag
+}
+
+fn swipe_direction(offset_x: f64, offset_y: f64) -> Option<SwipeDirection> {
+    if offset_x.abs() < SWIPE_THRESHOLD_PX || offset_x.abs() <= offset_y.abs() {
+        return None;
+    }
+    if offset_x < 0.0 {
+        Some(SwipeDirection::Left)
+    } else {
+        Some(SwipeDirection::Right)
+    }
+}
+
+fn drag_step_count(offset: f64) -> i32 {
+    (offset / DRAG_STEP_PX).trunc() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swipe_direction_requires_dominant_horizontal_travel() {
+        assert_eq!(swipe_direction(-60.0, 5.0), Some(SwipeDirection::Left));
+        assert_eq!(swipe_direction(60.0, -5.0), Some(SwipeDirection::Right));
+        assert_eq!(swipe_direction(30.0, 0.0), None);
+        assert_eq!(swipe_direction(60.0, 80.0), None);
+    }
+
+    #[test]
+    fn drag_step_count_truncates_toward_zero() {
+        assert_eq!(drag_step_count(0.0), 0);
+        assert_eq!(drag_step_count(23.9), 0);
+        assert_eq!(drag_step_count(48.0), 2);
+        assert_eq!(drag_step_count(-30.0), -1);
+    }
+}
@@ -9,7 +9,10 @@ use gtk::{
 };
 
 use super::menu_dbus::{fetch_dbus_menu_model, send_menu_event};
-use super::types::{TrayMenuEntry, TrayMenuToggleState, TrayMenuToggleType, DEFAULT_ICON_SIZE};
+use super::types::{
+    TrayMenuEntry, TrayMenuToggleState, TrayMenuToggleType, DEFAULT_ICON_SIZE, MODULE_TYPE,
+};
+use crate::modules::popovers;
 
 pub(super) fn show_item_menu(anchor: &Button, destination: String, path: String) -> bool {
     let Some(model) = fetch_dbus_menu_model(&destination, &path) else {
@@ -30,6 +33,7 @@ pub(super) fn show_item_menu(anchor: &Button, destination: String, path: String)
     popover.set_autohide(true);
     popover.set_position(PositionType::Top);
     popover.set_parent(anchor);
+    popovers::track(&popover, MODULE_TYPE);
     let content = GtkBox::new(Orientation::Vertical, 2);
     content.add_css_class("tray-menu-content");
     popover.set_child(Some(&content));
@@ -13,6 +13,8 @@ use serde_json::Value;
 use crate::modules::broadcaster::{
     attach_subscription, BackendRegistry, Broadcaster, Subscription,
 };
+use crate::modules::dbus_supervisor;
+use crate::modules::touch;
 use crate::modules::{apply_css_classes, ModuleBuildContext, ModuleConfig};
 
 use super::ModuleFactory;
@@ -23,8 +25,8 @@ mod sni;
 mod types;
 
 use types::{
-    TrayConfig, TrayIconPixmap, TrayItemSnapshot, MIN_ICON_SIZE, MIN_POLL_INTERVAL_SECS,
-    MODULE_TYPE,
+    TrayConfig, TrayIconPixmap, TrayItemOverride, TrayItemSnapshot, TrayLeftClickMode,
+    MIN_ICON_SIZE, MIN_POLL_INTERVAL_SECS, MODULE_TYPE,
 };
 
 const REFRESH_DEBOUNCE_MILLIS: u64 = 120;
@@ -35,6 +37,22 @@ struct RenderedTrayItem {
     button: Button,
 }
 
+/// What a mouse button does on a tray item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TrayClickAction {
+    Activate,
+    SecondaryActivate,
+    Menu,
+    Command(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TrayClickActions {
+    left: TrayClickAction,
+    middle: TrayClickAction,
+    right: TrayClickAction,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct TraySharedKey {
     icon_size: i32,
@@ -108,6 +126,7 @@ fn start_tray_worker(key: TraySharedKey, broadcaster: Arc<Broadcaster<Vec<TrayIt
 
         let mut last = Vec::<TrayItemSnapshot>::new();
         let mut host_registered = false;
+        let mut generation = dbus_supervisor::session_generation();
         let mut connection = sni::open_session_connection();
 
         while let Ok(()) | Err(RecvTimeoutError::Timeout) =
@@ -120,8 +139,12 @@ fn start_tray_worker(key: TraySharedKey, broadcaster: Arc<Broadcaster<Vec<TrayIt
 
             coalesce_refresh_events(&refresh_rx, Duration::from_millis(REFRESH_DEBOUNCE_MILLIS));
 
-            if connection.is_none() {
+            // After a session bus restart the old connection is dead and the
+            // host registration went with it.
+            let current_generation = dbus_supervisor::session_generation();
+            if connection.is_none() || generation != current_generation {
                 connection = sni::open_session_connection();
+                generation = current_generation;
                 host_registered = false;
             }
 
@@ -160,9 +183,12 @@ fn build_tray_module(config: TrayConfig) -> GtkBox {
     container.add_css_class("tray");
 
     apply_css_classes(&container, config.class.as_deref());
+    dbus_supervisor::attach_disconnected_class(&container);
 
     let icon_size = normalized_icon_size(config.icon_size);
     let poll_interval_secs = normalized_poll_interval_secs(config.poll_interval_secs);
+    let left_click = config.left_click;
+    let overrides = config.items;
 
     let subscription = subscribe_shared_tray(icon_size, poll_interval_secs);
 
@@ -171,7 +197,14 @@ fn build_tray_module(config: TrayConfig) -> GtkBox {
         let mut rendered = HashMap::<String, RenderedTrayItem>::new();
         move |container, snapshot| {
             if snapshot != current {
-                render_tray_items(container, &snapshot, icon_size, &mut rendered);
+                render_tray_items(
+                    container,
+                    &snapshot,
+                    icon_size,
+                    left_click,
+                    &overrides,
+                    &mut rendered,
+                );
                 current = snapshot;
             }
         }
@@ -184,6 +217,8 @@ fn render_tray_items(
     container: &GtkBox,
     items: &[TrayItemSnapshot],
     icon_size: i32,
+    left_click: TrayLeftClickMode,
+    overrides: &HashMap<String, TrayItemOverride>,
     rendered: &mut HashMap<String, RenderedTrayItem>,
 ) {
     let desired_ids = items
@@ -210,7 +245,9 @@ fn render_tray_items(
                 container.remove(&existing.button);
             }
 
-            let button = build_item_button(item, icon_size);
+            let actions =
+                resolve_click_actions(left_click, item.item_is_menu, overrides.get(&item.item_id));
+            let button = build_item_button(item, icon_size, actions);
             rendered.insert(
                 item.id.clone(),
                 RenderedTrayItem {
@@ -231,7 +268,7 @@ fn render_tray_items(
     }
 }
 
-fn build_item_button(item: &TrayItemSnapshot, icon_size: i32) -> Button {
+fn build_item_button(item: &TrayItemSnapshot, icon_size: i32, actions: TrayClickActions) -> Button {
     let button = Button::new();
     button.add_css_class("tray-item");
     button.set_focusable(false);
@@ -246,24 +283,103 @@ fn build_item_button(item: &TrayItemSnapshot, icon_size: i32) -> Button {
     let click_button = button.clone();
     let click = GestureClick::builder().button(0).build();
     click.connect_pressed(move |gesture, _, x, y| {
-        let current_button = gesture.current_button();
-        match current_button {
-            1 => sni::activate_item(destination.clone(), path.clone(), x as i32, y as i32),
-            2 => {
-                sni::secondary_activate_item(destination.clone(), path.clone(), x as i32, y as i32)
-            }
-            3 => {
-                if !menu_ui::show_item_menu(&click_button, destination.clone(), path.clone()) {
-                    sni::context_menu_item(destination.clone(), path.clone(), x as i32, y as i32);
-                }
-            }
-            _ => {}
-        }
+        let action = match gesture.current_button() {
+            1 => &actions.left,
+            2 => &actions.middle,
+            3 => &actions.right,
+            _ => return,
+        };
+        run_click_action(
+            action,
+            &click_button,
+            &destination,
+            &path,
+            x as i32,
+            y as i32,
+        );
     });
     button.add_controller(click);
+
+    let long_press_button = button.clone();
+    let destination = item.destination.clone();
+    let path = item.path.clone();
+    touch::attach_long_press(&button, move |x, y| {
+        open_item_menu(&long_press_button, &destination, &path, x as i32, y as i32)
+    });
     button
 }
 
+fn run_click_action(
+    action: &TrayClickAction,
+    anchor: &Button,
+    destination: &str,
+    path: &str,
+    x: i32,
+    y: i32,
+) {
+    match action {
+        TrayClickAction::Activate => {
+            sni::activate_item(destination.to_string(), path.to_string(), x, y)
+        }
+        TrayClickAction::SecondaryActivate => {
+            sni::secondary_activate_item(destination.to_string(), path.to_string(), x, y)
+        }
+        TrayClickAction::Menu => open_item_menu(anchor, destination, path, x, y),
+        TrayClickAction::Command(command) => {
+            let _ = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .spawn();
+        }
+    }
+}
+
+/// Default SNI behavior per button, with the item's `items` entry applied.
+fn resolve_click_actions(
+    mode: TrayLeftClickMode,
+    item_is_menu: bool,
+    item_override: Option<&TrayItemOverride>,
+) -> TrayClickActions {
+    let default = TrayItemOverride::default();
+    let item_override = item_override.unwrap_or(&default);
+    let command = |primary: &Option<String>, alias: &Option<String>| {
+        primary
+            .clone()
+            .or_else(|| alias.clone())
+            .map(TrayClickAction::Command)
+    };
+
+    let mode = item_override.left_click.unwrap_or(mode);
+    let left = command(&item_override.click, &item_override.on_click).unwrap_or(
+        if left_click_opens_menu(mode, item_is_menu) {
+            TrayClickAction::Menu
+        } else {
+            TrayClickAction::Activate
+        },
+    );
+    TrayClickActions {
+        left,
+        middle: command(&item_override.middle_click, &item_override.on_middle_click)
+            .unwrap_or(TrayClickAction::SecondaryActivate),
+        right: command(&item_override.right_click, &item_override.on_right_click)
+            .unwrap_or(TrayClickAction::Menu),
+    }
+}
+
+fn open_item_menu(anchor: &Button, destination: &str, path: &str, x: i32, y: i32) {
+    if !menu_ui::show_item_menu(anchor, destination.to_string(), path.to_string()) {
+        sni::context_menu_item(destination.to_string(), path.to_string(), x, y);
+    }
+}
+
+fn left_click_opens_menu(mode: TrayLeftClickMode, item_is_menu: bool) -> bool {
+    match mode {
+        TrayLeftClickMode::Auto => item_is_menu,
+        TrayLeftClickMode::Activate => false,
+        TrayLeftClickMode::Menu => true,
+    }
+}
+
 fn image_for_item(item: &TrayItemSnapshot, icon_size: i32) -> Image {
     if !item.icon_name.is_empty() {
         let icon_path = Path::new(&item.icon_name);
@@ -422,7 +538,7 @@ fn is_missing_icon_name(icon_name: Option<&PathBuf>) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use serde_json::Map;
+    use serde_json::{json, Map};
 
     use super::*;
 
@@ -441,6 +557,63 @@ mod tests {
         assert_eq!(parsed.2, "/StatusNotifierItem");
     }
 
+    #[test]
+    fn parse_config_reads_left_click_mode() {
+        let module = ModuleConfig::new(MODULE_TYPE, Map::new());
+        let config = parse_config(&module).expect("config should parse");
+        assert_eq!(config.left_click, TrayLeftClickMode::Auto);
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            Map::from_iter([("left_click".to_string(), json!("menu"))]),
+        );
+        let config = parse_config(&module).expect("config should parse");
+        assert_eq!(config.left_click, TrayLeftClickMode::Menu);
+    }
+
+    #[test]
+    fn left_click_opens_menu_honors_item_is_menu_in_auto_mode() {
+        assert!(left_click_opens_menu(TrayLeftClickMode::Auto, true));
+        assert!(!left_click_opens_menu(TrayLeftClickMode::Auto, false));
+        assert!(!left_click_opens_menu(TrayLeftClickMode::Activate, true));
+        assert!(left_click_opens_menu(TrayLeftClickMode::Menu, false));
+    }
+
+    #[test]
+    fn resolve_click_actions_applies_item_overrides() {
+        let defaults = resolve_click_actions(TrayLeftClickMode::Auto, false, None);
+        assert_eq!(defaults.left, TrayClickAction::Activate);
+        assert_eq!(defaults.middle, TrayClickAction::SecondaryActivate);
+        assert_eq!(defaults.right, TrayClickAction::Menu);
+
+        let module = ModuleConfig::new(
+            MODULE_TYPE,
+            Map::from_iter([(
+                "items".to_string(),
+                json!({
+                    "spotify": { "on-click": "spotify-toggle", "right-click": "pavucontrol" },
+                    "broken-app": { "left-click": "menu" }
+                }),
+            )]),
+        );
+        let config = parse_config(&module).expect("config should parse");
+
+        let spotify = resolve_click_actions(config.left_click, false, config.items.get("spotify"));
+        assert_eq!(
+            spotify.left,
+            TrayClickAction::Command("spotify-toggle".to_string())
+        );
+        assert_eq!(spotify.middle, TrayClickAction::SecondaryActivate);
+        assert_eq!(
+            spotify.right,
+            TrayClickAction::Command("pavucontrol".to_string())
+        );
+
+        let broken =
+            resolve_click_actions(config.left_click, false, config.items.get("broken-app"));
+        assert_eq!(broken.left, TrayClickAction::Menu);
+    }
+
     #[test]
     fn normalized_values_enforce_minimums() {
         assert_eq!(normalized_icon_size(2), MIN_ICON_SIZE);
@@ -16,6 +16,8 @@ use zbus::Error as ZbusError;
 use zbus::MatchRule;
 use zbus::Result as ZbusResult;
 
+use crate::modules::dbus_supervisor::{self, ListenerExit};
+
 use super::types::{
     TrayIconPixmap, TrayItemSnapshot, ITEM_INTERFACE, WATCHER_DESTINATION, WATCHER_INTERFACE,
     WATCHER_PATH,
@@ -98,6 +100,8 @@ impl LocalStatusNotifierWatcher {
 struct LocalWatcherRuntime {
     _connection: Connection,
     _lock_file: File,
+    /// Session bus generation the watcher was registered under.
+    generation: u64,
 }
 
 static LOCAL_WATCHER_RUNTIME: OnceLock<Mutex<Option<LocalWatcherRuntime>>> = OnceLock::new();
@@ -214,25 +218,24 @@ pub(super) fn open_session_connection() -> Option<Connection> {
 }
 
 fn start_name_owner_listener(trigger_tx: std::sync::mpsc::Sender<()>) {
-    thread::spawn(move || {
-        let Ok(connection) = Connection::session() else {
-            if tray_debug_enabled() {
-                eprintln!("vibar/tray: failed to open session bus for NameOwnerChanged listener");
-            }
-            return;
-        };
-        let Ok(proxy) = DBusProxy::new(&connection) else {
-            if tray_debug_enabled() {
-                eprintln!("vibar/tray: failed to create DBus proxy for NameOwnerChanged listener");
-            }
-            return;
+    dbus_supervisor::spawn_listener("tray", move |connection| {
+        let proxy = match DBusProxy::new(connection) {
+            Ok(proxy) => proxy,
+            Err(err) => return ListenerExit::Lost(format!("failed to create DBus proxy: {err}")),
         };
-        let Ok(mut signals) = proxy.receive_name_owner_changed() else {
-            if tray_debug_enabled() {
-                eprintln!("vibar/tray: failed to subscribe to NameOwnerChanged");
+        let mut signals = match proxy.receive_name_owner_changed() {
+            Ok(signals) => signals,
+            Err(err) => {
+                return ListenerExit::Lost(format!(
+                    "failed to subscribe to NameOwnerChanged: {err}"
+                ))
             }
-            return;
         };
+        // Items re-register with the (possibly new) watcher after a bus
+        // restart; refresh once the listener is back.
+        if trigger_tx.send(()).is_err() {
+            return ListenerExit::Stopped;
+        }
 
         for signal in &mut signals {
             let Ok(args) = signal.args() else {
@@ -242,9 +245,10 @@ fn start_name_owner_listener(trigger_tx: std::sync::mpsc::Sender<()>) {
             // Refresh only for tray-related names to avoid turning generic DBus churn
             // into continuous tray snapshot rebuilds.
             if is_tray_relevant_name(&name) && trigger_tx.send(()).is_err() {
-                return;
+                return ListenerExit::Stopped;
             }
         }
+        ListenerExit::Lost("NameOwnerChanged stream ended".to_string())
     });
 }
 
@@ -253,14 +257,7 @@ fn is_tray_relevant_name(name: &str) -> bool {
 }
 
 fn start_watcher_item_listener(trigger_tx: std::sync::mpsc::Sender<()>, member: &'static str) {
-    thread::spawn(move || {
-        let Ok(connection) = Connection::session() else {
-            if tray_debug_enabled() {
-                eprintln!("vibar/tray: failed to open session bus for watcher signal listener");
-            }
-            return;
-        };
-
+    dbus_supervisor::spawn_listener("tray", move |connection| {
         let rule = match MatchRule::builder()
             .msg_type(MessageType::Signal)
             .interface(WATCHER_INTERFACE)
@@ -270,39 +267,33 @@ fn start_watcher_item_listener(trigger_tx: std::sync::mpsc::Sender<()>, member:
         {
             Ok(rule) => rule,
             Err(err) => {
-                if tray_debug_enabled() {
-                    eprintln!(
-                        "vibar/tray: failed to build watcher signal match rule ({member}): {err}"
-                    );
-                }
-                return;
+                eprintln!(
+                    "vibar/tray: failed to build watcher signal match rule ({member}): {err}"
+                );
+                return ListenerExit::Stopped;
             }
         };
 
-        let Ok(iterator) = MessageIterator::for_match_rule(rule, &connection, Some(256)) else {
-            if tray_debug_enabled() {
-                eprintln!("vibar/tray: failed to subscribe to watcher signal ({member})");
+        let iterator = match MessageIterator::for_match_rule(rule, connection, Some(256)) {
+            Ok(iterator) => iterator,
+            Err(err) => {
+                return ListenerExit::Lost(format!(
+                    "failed to subscribe to watcher signal ({member}): {err}"
+                ))
             }
-            return;
         };
 
         for message in iterator {
             if message.is_ok() && trigger_tx.send(()).is_err() {
-                return;
+                return ListenerExit::Stopped;
             }
         }
+        ListenerExit::Lost(format!("watcher signal stream ({member}) ended"))
     });
 }
 
 fn start_item_properties_listener(trigger_tx: std::sync::mpsc::Sender<()>) {
-    thread::spawn(move || {
-        let Ok(connection) = Connection::session() else {
-            if tray_debug_enabled() {
-                eprintln!("vibar/tray: failed to open session bus for item property listener");
-            }
-            return;
-        };
-
+    dbus_supervisor::spawn_listener("tray", move |connection| {
         let rule = match MatchRule::builder()
             .msg_type(MessageType::Signal)
             .interface(DBUS_PROPERTIES_INTERFACE)
@@ -311,18 +302,18 @@ fn start_item_properties_listener(trigger_tx: std::sync::mpsc::Sender<()>) {
         {
             Ok(rule) => rule,
             Err(err) => {
-                if tray_debug_enabled() {
-                    eprintln!("vibar/tray: failed to build properties signal match rule: {err}");
-                }
-                return;
+                eprintln!("vibar/tray: failed to build properties signal match rule: {err}");
+                return ListenerExit::Stopped;
             }
         };
 
-        let Ok(iterator) = MessageIterator::for_match_rule(rule, &connection, Some(512)) else {
-            if tray_debug_enabled() {
-                eprintln!("vibar/tray: failed to subscribe to properties signal");
+        let iterator = match MessageIterator::for_match_rule(rule, connection, Some(512)) {
+            Ok(iterator) => iterator,
+            Err(err) => {
+                return ListenerExit::Lost(format!(
+                    "failed to subscribe to properties signal: {err}"
+                ))
             }
-            return;
         };
 
         for message in iterator {
@@ -330,9 +321,10 @@ fn start_item_properties_listener(trigger_tx: std::sync::mpsc::Sender<()>) {
                 continue;
             };
             if is_tray_item_properties_changed(&message) && trigger_tx.send(()).is_err() {
-                return;
+                return ListenerExit::Stopped;
             }
         }
+        ListenerExit::Lost("properties signal stream ended".to_string())
     });
 }
 
@@ -416,8 +408,12 @@ pub(super) fn fetch_tray_snapshot_with_connection(
     snapshots.sort_by(|a, b| a.id.cmp(&b.id));
     if tray_debug_enabled() {
         eprintln!(
-            "vibar/tray: resolved {} tray snapshot item(s)",
-            snapshots.len()
+            "vibar/tray: resolved {} tray snapshot item(s), ids: {:?}",
+            snapshots.len(),
+            snapshots
+                .iter()
+                .map(|item| item.item_id.as_str())
+                .collect::<Vec<_>>()
         );
     }
     snapshots
@@ -452,7 +448,7 @@ fn fetch_item(
     destination: String,
     path: String,
 ) -> Option<TrayItemSnapshot> {
-    let (icon_name, icon_pixmap, icon_theme_path, title) = {
+    let (item_id, icon_name, icon_pixmap, icon_theme_path, title, item_is_menu) = {
         let proxy = match Proxy::new(
             connection,
             destination.as_str(),
@@ -517,17 +513,33 @@ fn fetch_item(
             .ok()
             .filter(|value: &String| !value.is_empty());
 
-        (icon_name, icon_pixmap, icon_theme_path, title)
+        let item_is_menu = proxy
+            .get_property::<bool>("ItemIsMenu")
+            .ok()
+            .unwrap_or(false);
+
+        let item_id = proxy.get_property::<String>("Id").ok().unwrap_or_default();
+
+        (
+            item_id,
+            icon_name,
+            icon_pixmap,
+            icon_theme_path,
+            title,
+            item_is_menu,
+        )
     };
 
     Some(TrayItemSnapshot {
         id,
+        item_id,
         destination,
         path,
         icon_name,
         icon_pixmap,
         icon_theme_path,
         title,
+        item_is_menu,
     })
 }
 
@@ -563,9 +575,16 @@ fn ensure_local_watcher_fallback() {
     let Ok(mut runtime_guard) = runtime.lock() else {
         return;
     };
-    if runtime_guard.is_some() {
+    let generation = dbus_supervisor::session_generation();
+    if runtime_guard
+        .as_ref()
+        .is_some_and(|runtime| runtime.generation == generation)
+    {
         return;
     }
+    // A watcher from before a session bus restart lost its name; drop it
+    // (and its lock) so it can be served again on the new bus.
+    *runtime_guard = None;
 
     let Some(lock_file) = try_acquire_watcher_lock() else {
         return;
@@ -597,6 +616,7 @@ fn ensure_local_watcher_fallback() {
     *runtime_guard = Some(LocalWatcherRuntime {
         _connection: connection,
         _lock_file: lock_file,
+        generation,
     });
 }
 
@@ -20,10 +20,44 @@ pub(super) struct TrayConfig {
     pub(super) icon_size: i32,
     #[serde(default = "default_poll_interval")]
     pub(super) poll_interval_secs: u32,
+    #[serde(rename = "left-click", alias = "left_click", default)]
+    pub(super) left_click: TrayLeftClickMode,
+    /// Per-item click overrides keyed by the item's SNI `Id`.
+    #[serde(default)]
+    pub(super) items: HashMap<String, TrayItemOverride>,
     #[serde(default)]
     pub(super) class: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Clone, Default, PartialEq, Eq)]
+pub(super) struct TrayItemOverride {
+    #[serde(rename = "left-click", alias = "left_click", default)]
+    pub(super) left_click: Option<TrayLeftClickMode>,
+    /// Shell command run on left click instead of `Activate` or the menu.
+    #[serde(default)]
+    pub(super) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(super) on_click: Option<String>,
+    #[serde(rename = "middle-click", default)]
+    pub(super) middle_click: Option<String>,
+    #[serde(rename = "on-middle-click", default)]
+    pub(super) on_middle_click: Option<String>,
+    #[serde(rename = "right-click", default)]
+    pub(super) right_click: Option<String>,
+    #[serde(rename = "on-right-click", default)]
+    pub(super) on_right_click: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(super) enum TrayLeftClickMode {
+    /// Open the menu for items that set `ItemIsMenu`, otherwise call `Activate`.
+    #[default]
+    Auto,
+    Activate,
+    Menu,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) struct TrayIconPixmap {
     pub(super) width: i32,
@@ -33,13 +67,17 @@ pub(super) struct TrayIconPixmap {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(super) struct TrayItemSnapshot {
+    /// Watcher address (`<bus name>/<object path>`), unique per item.
     pub(super) id: String,
+    /// SNI `Id` property (for example `spotify`); empty when unset.
+    pub(super) item_id: String,
     pub(super) destination: String,
     pub(super) path: String,
     pub(super) icon_name: String,
     pub(super) icon_pixmap: Option<TrayIconPixmap>,
     pub(super) icon_theme_path: Option<String>,
     pub(super) title: String,
+    pub(super) item_is_menu: bool,
 }
 
 #[derive(Debug, Clone)]
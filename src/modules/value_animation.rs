@@ -0,0 +1,302 @@
+//! Shared `value-animation-ms` support: when a module label changes only in
+//! its numbers, the numbers count from the old values to the new ones
+//! instead of snapping.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde_json::{Map, Value};
+
+use crate::modules::animation::{ease_out_cubic, lerp, AnimationStep, FrameAnimation, Tween};
+
+const VALUE_ANIMATION_KEYS: [&str; 2] = ["value-animation-ms", "value_animation_ms"];
+const MAX_VALUE_ANIMATION_MS: u64 = 2000;
+
+/// Removes `value-animation-ms` from a module config. `0` or unset means no
+/// animation.
+pub(crate) fn take_value_animation(
+    config: &mut Map<String, Value>,
+) -> Result<Option<Duration>, String> {
+    let raw = VALUE_ANIMATION_KEYS
+        .iter()
+        .filter_map(|key| config.remove(*key))
+        .reduce(|first, _| first);
+    let millis = match raw {
+        None | Some(Value::Null) => return Ok(None),
+        Some(value) => value
+            .as_u64()
+            .ok_or_else(|| "`value-animation-ms` must be a non-negative integer".to_string())?,
+    };
+    Ok((millis > 0).then(|| Duration::from_millis(millis.min(MAX_VALUE_ANIMATION_MS))))
+}
+
+/// Animates number changes on `widget` when it is a label; other widgets
+/// are left alone.
+pub(crate) fn attach(widget: &Widget, duration: Option<Duration>) {
+    let Some(duration) = duration else {
+        return;
+    };
+    let Some(label) = widget.downcast_ref::<Label>() else {
+        return;
+    };
+
+    let state = Rc::new(RefCell::new(ValueAnimationState {
+        shown: label.label().to_string(),
+        target: label.label().to_string(),
+        transition: None,
+    }));
+    // Set while this module writes the label, so its own writes are not
+    // taken for module updates.
+    let applying = Rc::new(Cell::new(false));
+
+    let animation = FrameAnimation::new(label, {
+        let label = label.downgrade();
+        let state = Rc::clone(&state);
+        let applying = Rc::clone(&applying);
+        move |delta| {
+            let Some(label) = label.upgrade() else {
+                return AnimationStep::Idle;
+            };
+            let mut state = state.borrow_mut();
+            let Some((from, to, mut tween)) = state.transition.take() else {
+                return AnimationStep::Idle;
+            };
+            let progress = tween.advance(delta);
+            let text = if tween.is_finished() {
+                state.target.clone()
+            } else {
+                from.interpolate(&to, progress)
+            };
+            state.show(&label, &applying, text);
+            if tween.is_finished() {
+                return AnimationStep::Idle;
+            }
+            state.transition = Some((from, to, tween));
+            AnimationStep::Running
+        }
+    });
+
+    label.connect_notify_local(Some("label"), move |label, _| {
+        if applying.get() {
+            return;
+        }
+        let mut state = state.borrow_mut();
+        let text = label.label().to_string();
+        if text == state.target && state.transition.is_some() {
+            // The module repeated its current value mid-animation.
+            return;
+        }
+
+        let from = NumericText::parse(&state.shown);
+        let to = NumericText::parse(&text);
+        state.target = text.clone();
+        if !from.can_animate_to(&to) {
+            state.transition = None;
+            state.shown = text;
+            return;
+        }
+
+        let shown = state.shown.clone();
+        state.show(label, &applying, shown);
+        state.transition = Some((from, to, Tween::new(0.0, 1.0, duration, ease_out_cubic)));
+        drop(state);
+        animation.wake();
+    });
+}
+
+struct ValueAnimationState {
+    /// Text currently on the label, possibly mid-animation.
+    shown: String,
+    /// Latest text set by the module.
+    target: String,
+    transition: Option<(NumericText, NumericText, Tween)>,
+}
+
+impl ValueAnimationState {
+    fn show(&mut self, label: &Label, applying: &Cell<bool>, text: String) {
+        applying.set(true);
+        label.set_label(&text);
+        applying.set(false);
+        self.shown = text;
+    }
+}
+
+/// Label text split into the numbers and the text around them. Numbers
+/// inside Pango tags or entities are part of the surrounding text.
+#[derive(Debug, Clone, PartialEq)]
+struct NumericText {
+    /// One more entry than `numbers`.
+    parts: Vec<String>,
+    numbers: Vec<NumberToken>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct NumberToken {
+    value: f64,
+    decimals: usize,
+    /// Zero-padded width, for values such as `05`.
+    width: usize,
+}
+
+impl NumericText {
+    fn parse(text: &str) -> Self {
+        let bytes = text.as_bytes();
+        let mut parts = Vec::new();
+        let mut numbers = Vec::new();
+        let mut part_start = 0;
+        let mut in_tag = false;
+        let mut in_entity = false;
+        let mut index = 0;
+
+        while index < bytes.len() {
+            let byte = bytes[index];
+            if in_tag {
+                in_tag = byte != b'>';
+                index += 1;
+                continue;
+            }
+            if in_entity {
+                in_entity = byte != b';';
+                index += 1;
+                continue;
+            }
+            match byte {
+                b'<' => in_tag = true,
+                b'&' => in_entity = true,
+                b'0'..=b'9' => {
+                    let start = index;
+                    index = skip_digits(bytes, index);
+                    let integer_digits = index - start;
+                    let mut decimals = 0;
+                    if bytes.get(index) == Some(&b'.')
+                        && bytes.get(index + 1).is_some_and(u8::is_ascii_digit)
+                    {
+                        let fraction_start = index + 1;
+                        index = skip_digits(bytes, fraction_start);
+                        decimals = index - fraction_start;
+                    }
+                    let width = if bytes[start] == b'0' && integer_digits > 1 {
+                        index - start
+                    } else {
+                        0
+                    };
+                    parts.push(text[part_start..start].to_string());
+                    numbers.push(NumberToken {
+                        value: text[start..index].parse().unwrap_or_default(),
+                        decimals,
+                        width,
+                    });
+                    part_start = index;
+                    continue;
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+
+        parts.push(text[part_start..].to_string());
+        Self { parts, numbers }
+    }
+
+    /// Same surrounding text and at least one number that changed.
+    fn can_animate_to(&self, other: &Self) -> bool {
+        self.parts == other.parts
+            && self.numbers.len() == other.numbers.len()
+            && self
+                .numbers
+                .iter()
+                .zip(&other.numbers)
+                .any(|(from, to)| from.value != to.value)
+    }
+
+    /// Text at `progress` between `self` and `to`, formatted like `to`.
+    fn interpolate(&self, to: &Self, progress: f64) -> String {
+        let mut text = String::new();
+        for (index, part) in to.parts.iter().enumerate() {
+            text.push_str(part);
+            let (Some(from), Some(target)) = (self.numbers.get(index), to.numbers.get(index))
+            else {
+                continue;
+            };
+            let value = lerp(from.value, target.value, progress);
+            text.push_str(&format!(
+                "{value:0width$.decimals$}",
+                width = from.width.max(target.width),
+                decimals = target.decimals
+            ));
+        }
+        text
+    }
+}
+
+fn skip_digits(bytes: &[u8], mut index: usize) -> usize {
+    while bytes.get(index).is_some_and(u8::is_ascii_digit) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn take_value_animation_reads_millis() {
+        let mut config = json!({ "value_animation_ms": 150, "format": "{}" })
+            .as_object()
+            .cloned()
+            .expect("config should be an object");
+        assert_eq!(
+            take_value_animation(&mut config),
+            Ok(Some(Duration::from_millis(150)))
+        );
+        assert!(!config.contains_key("value_animation_ms"));
+        assert!(config.contains_key("format"));
+
+        let mut config = json!({ "value-animation-ms": 0 })
+            .as_object()
+            .cloned()
+            .expect("config should be an object");
+        assert_eq!(take_value_animation(&mut config), Ok(None));
+
+        let mut config = json!({ "value-animation-ms": "fast" })
+            .as_object()
+            .cloned()
+            .expect("config should be an object");
+        assert!(take_value_animation(&mut config).is_err());
+    }
+
+    #[test]
+    fn parse_skips_markup_tags_and_entities() {
+        let text = NumericText::parse("<span size=\"120%\">vol</span> 42% &#38; 1.50");
+        assert_eq!(
+            text.parts,
+            ["<span size=\"120%\">vol</span> ", "% &#38; ", ""]
+        );
+        assert_eq!(text.numbers.len(), 2);
+        assert_eq!(text.numbers[1].decimals, 2);
+    }
+
+    #[test]
+    fn only_same_shaped_text_animates() {
+        let from = NumericText::parse("cpu 9%");
+        assert!(from.can_animate_to(&NumericText::parse("cpu 10%")));
+        assert!(!from.can_animate_to(&NumericText::parse("cpu 9%")));
+        assert!(!from.can_animate_to(&NumericText::parse("muted")));
+        assert!(!from.can_animate_to(&NumericText::parse("cpu 9% 2")));
+    }
+
+    #[test]
+    fn interpolate_uses_target_formatting() {
+        let from = NumericText::parse("20% 1.0 08");
+        let to = NumericText::parse("40% 2.00 10");
+        assert_eq!(from.interpolate(&to, 0.0), "20% 1.00 08");
+        assert_eq!(from.interpolate(&to, 0.5), "30% 1.50 09");
+        assert_eq!(from.interpolate(&to, 1.0), "40% 2.00 10");
+    }
+}
@@ -0,0 +1,325 @@
+//! `wait-for` startup conditions shared by every module type.
+//!
+//! A module with `wait-for` is built into an empty placeholder box; a worker
+//! thread polls the conditions and the real module (and with it its backend)
+//! is only initialized once they all hold or `wait-for-timeout` elapses.
+
+use std::env;
+use std::fs;
+use std::os::unix::net::UnixStream;
+use std::time::{Duration, Instant};
+
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Orientation, Widget};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use zbus::blocking::fdo::DBusProxy;
+use zbus::blocking::Connection;
+use zbus::names::BusName;
+
+use crate::modules::broadcaster::{attach_subscription, Broadcaster};
+
+const WAIT_FOR_KEYS: &[&str] = &["wait-for", "wait_for"];
+const WAIT_FOR_TIMEOUT_KEYS: &[&str] = &["wait-for-timeout", "wait_for_timeout"];
+const DEFAULT_WAIT_FOR_TIMEOUT_SECS: u64 = 30;
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const DBUS_PREFIX: &str = "dbus:";
+
+/// `wait-for`: a single condition or a list of conditions.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+enum WaitForConfig {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum WaitCondition {
+    /// The sway IPC socket from `$SWAYSOCK` accepts connections.
+    Sway,
+    /// The session bus is up and the given name has an owner.
+    DbusName(String),
+    /// A default IPv4 or IPv6 route exists.
+    NetworkOnline,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct WaitFor {
+    pub(crate) conditions: Vec<WaitCondition>,
+    pub(crate) timeout: Duration,
+}
+
+impl WaitCondition {
+    fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        if let Some(name) = raw.strip_prefix(DBUS_PREFIX) {
+            if name.is_empty() {
+                return Err("`wait-for` dbus condition requires a name".to_string());
+            }
+            BusName::try_from(name)
+                .map_err(|err| format!("invalid `wait-for` dbus name '{name}': {err}"))?;
+            return Ok(Self::DbusName(name.to_string()));
+        }
+        match raw {
+            "sway" => Ok(Self::Sway),
+            "network-online" => Ok(Self::NetworkOnline),
+            _ => Err(format!(
+                "invalid `wait-for` condition '{raw}' (expected `sway`, `dbus:<name>`, or `network-online`)"
+            )),
+        }
+    }
+
+    fn is_met(&self, session_bus: &mut SessionBus) -> bool {
+        match self {
+            Self::Sway => sway_socket_ready(),
+            Self::DbusName(name) => session_bus.name_has_owner(name),
+            Self::NetworkOnline => network_online(),
+        }
+    }
+}
+
+/// Session-bus connection shared by the `dbus:` checks of one wait. Opened
+/// once the bus is up and dropped again if it goes away.
+#[derive(Default)]
+struct SessionBus {
+    connection: Option<Connection>,
+}
+
+impl SessionBus {
+    fn name_has_owner(&mut self, name: &str) -> bool {
+        if self.connection.is_none() {
+            self.connection = Connection::session().ok();
+        }
+        let Some(connection) = self.connection.as_ref() else {
+            return false;
+        };
+        let Ok(proxy) = DBusProxy::new(connection) else {
+            return false;
+        };
+        let Ok(name) = BusName::try_from(name) else {
+            return false;
+        };
+        match proxy.name_has_owner(name) {
+            Ok(has_owner) => has_owner,
+            Err(zbus::fdo::Error::ZBus(zbus::Error::InputOutput(_))) => {
+                self.connection = None;
+                false
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+/// Removes the `wait-for` keys from `config` and parses them. Returns `None`
+/// when the module has no conditions.
+pub(crate) fn take_wait_for(config: &mut Map<String, Value>) -> Result<Option<WaitFor>, String> {
+    let raw = take_first(config, WAIT_FOR_KEYS);
+    let timeout = take_first(config, WAIT_FOR_TIMEOUT_KEYS);
+
+    let Some(raw) = raw else {
+        return Ok(None);
+    };
+    let raw: WaitForConfig =
+        serde_json::from_value(raw).map_err(|err| format!("invalid `wait-for`: {err}"))?;
+    let conditions = match raw {
+        WaitForConfig::One(condition) => vec![WaitCondition::parse(&condition)?],
+        WaitForConfig::Many(conditions) => conditions
+            .iter()
+            .map(|condition| WaitCondition::parse(condition))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    let timeout_secs = timeout
+        .map(|value| {
+            value
+                .as_u64()
+                .ok_or_else(|| "`wait-for-timeout` must be a non-negative integer".to_string())
+        })
+        .transpose()?
+        .unwrap_or(DEFAULT_WAIT_FOR_TIMEOUT_SECS);
+
+    if conditions.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(WaitFor {
+        conditions,
+        timeout: Duration::from_secs(timeout_secs),
+    }))
+}
+
+fn take_first(config: &mut Map<String, Value>, keys: &[&str]) -> Option<Value> {
+    keys.iter()
+        .filter_map(|key| config.remove(*key))
+        .reduce(|first, _| first)
+}
+
+/// Returns a placeholder that hosts the widget produced by `build` once
+/// `wait_for` is satisfied (or timed out). `build` runs on the GTK thread.
+pub(crate) fn build_deferred(
+    module_type: &str,
+    wait_for: WaitFor,
+    build: impl FnOnce() -> Result<Widget, String> + 'static,
+) -> Widget {
+    let placeholder = GtkBox::new(Orientation::Horizontal, 0);
+    placeholder.add_css_class("wait-for");
+
+    let broadcaster = Broadcaster::new();
+    let subscription = broadcaster.subscribe();
+    let module_type = module_type.to_string();
+
+    std::thread::spawn({
+        let module_type = module_type.clone();
+        move || {
+            let satisfied = wait_until_met(&wait_for);
+            if !satisfied {
+                eprintln!(
+                    "vibar/{module_type}: wait-for timed out after {}s; starting anyway",
+                    wait_for.timeout.as_secs()
+                );
+            }
+            broadcaster.broadcast(());
+        }
+    });
+
+    let mut build = Some(build);
+    attach_subscription(&placeholder, subscription, move |placeholder, ()| {
+        let Some(build) = build.take() else {
+            return;
+        };
+        placeholder.remove_css_class("wait-for");
+        match build() {
+            Ok(widget) => placeholder.append(&widget),
+            Err(err) => eprintln!("Failed to initialize module {module_type}: {err}"),
+        }
+    });
+
+    placeholder.upcast()
+}
+
+fn wait_until_met(wait_for: &WaitFor) -> bool {
+    let deadline = Instant::now() + wait_for.timeout;
+    let mut pending = wait_for.conditions.clone();
+    let mut session_bus = SessionBus::default();
+    loop {
+        pending.retain(|condition| !condition.is_met(&mut session_bus));
+        if pending.is_empty() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn sway_socket_ready() -> bool {
+    env::var("SWAYSOCK")
+        .ok()
+        .filter(|path| !path.is_empty())
+        .is_some_and(|path| UnixStream::connect(path).is_ok())
+}
+
+fn network_online() -> bool {
+    let ipv4 = fs::read_to_string("/proc/net/route").unwrap_or_default();
+    let ipv6 = fs::read_to_string("/proc/net/ipv6_route").unwrap_or_default();
+    has_default_ipv4_route(&ipv4) || has_default_ipv6_route(&ipv6)
+}
+
+/// `/proc/net/route`: header line, then `Iface Destination Gateway ...` rows
+/// with hex fields; a default route has destination and mask `00000000`.
+fn has_default_ipv4_route(table: &str) -> bool {
+    table.lines().skip(1).any(|line| {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        fields.len() > 7 && fields[0] != "lo" && fields[1] == "00000000" && fields[7] == "00000000"
+    })
+}
+
+/// `/proc/net/ipv6_route`: `dest dest_len src src_len next_hop metric refcnt
+/// use flags iface`; a default route has an all-zero `/0` destination.
+fn has_default_ipv6_route(table: &str) -> bool {
+    table.lines().any(|line| {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        fields.len() > 9
+            && fields[9] != "lo"
+            && fields[1] == "00"
+            && fields[0].chars().all(|c| c == '0')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn config(value: Value) -> Map<String, Value> {
+        serde_json::from_value(value).expect("config map should parse")
+    }
+
+    #[test]
+    fn take_wait_for_parses_single_and_list_forms() {
+        let mut single = config(json!({ "wait-for": "sway", "format": "{}" }));
+        let wait_for = take_wait_for(&mut single)
+            .expect("wait-for should parse")
+            .expect("wait-for should be set");
+        assert_eq!(wait_for.conditions, vec![WaitCondition::Sway]);
+        assert_eq!(
+            wait_for.timeout,
+            Duration::from_secs(DEFAULT_WAIT_FOR_TIMEOUT_SECS)
+        );
+        assert!(!single.contains_key("wait-for"));
+        assert!(single.contains_key("format"));
+
+        let mut list = config(json!({
+            "wait_for": ["dbus:org.kde.StatusNotifierWatcher", "network-online"],
+            "wait-for-timeout": 5
+        }));
+        let wait_for = take_wait_for(&mut list)
+            .expect("wait-for should parse")
+            .expect("wait-for should be set");
+        assert_eq!(
+            wait_for.conditions,
+            vec![
+                WaitCondition::DbusName("org.kde.StatusNotifierWatcher".to_string()),
+                WaitCondition::NetworkOnline,
+            ]
+        );
+        assert_eq!(wait_for.timeout, Duration::from_secs(5));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn take_wait_for_returns_none_without_conditions() {
+        let mut absent = config(json!({ "format": "{}" }));
+        assert_eq!(take_wait_for(&mut absent).expect("should parse"), None);
+
+        let mut empty = config(json!({ "wait-for": [] }));
+        assert_eq!(take_wait_for(&mut empty).expect("should parse"), None);
+    }
+
+    #[test]
+    fn take_wait_for_rejects_unknown_conditions() {
+        let mut unknown = config(json!({ "wait-for": "coffee" }));
+        let err = take_wait_for(&mut unknown).expect_err("unknown condition should fail");
+        assert!(err.contains("invalid `wait-for` condition 'coffee'"));
+
+        let mut empty_name = config(json!({ "wait-for": "dbus:" }));
+        assert!(take_wait_for(&mut empty_name).is_err());
+
+        let mut bad_timeout = config(json!({ "wait-for": "sway", "wait-for-timeout": -1 }));
+        assert!(take_wait_for(&mut bad_timeout).is_err());
+    }
+
+    #[test]
+    fn default_route_detection_ignores_loopback_and_specific_routes() {
+        let ipv4 = "Iface\tDestination\tGateway\tFlags\tRefCnt\tUse\tMetric\tMask\n\
+                    wlan0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\n";
+        assert!(!has_default_ipv4_route(ipv4));
+        let ipv4 = format!("{ipv4}wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\n");
+        assert!(has_default_ipv4_route(&ipv4));
+
+        let ipv6_lo = "00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo\n";
+        assert!(!has_default_ipv6_route(ipv6_lo));
+        let ipv6 = "00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000002 00000000 00450003    wlan0\n";
+        assert!(has_default_ipv6_route(ipv6));
+    }
+}
@@ -0,0 +1,187 @@
+mod toplevel;
+
+use gtk::prelude::*;
+use gtk::Widget;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::broadcaster::attach_subscription;
+use crate::modules::scroll_text::{ModuleText, ScrollTextMode};
+use crate::modules::{
+    attach_primary_click_command, render_markup_template, ModuleBuildContext, ModuleConfig,
+};
+
+use self::toplevel::{subscribe_focused_toplevel, FocusedToplevel};
+use super::ModuleFactory;
+
+const DEFAULT_MAX_WIDTH: u32 = 80;
+pub(crate) const MODULE_TYPE: &str = "window";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct WindowConfig {
+    #[serde(default = "default_format")]
+    pub(crate) format: String,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+    #[serde(
+        rename = "max-width",
+        alias = "max_width",
+        default = "default_max_width"
+    )]
+    pub(crate) max_width: u32,
+    #[serde(rename = "scroll-text", alias = "scroll_text", default)]
+    pub(crate) scroll_text: ScrollTextMode,
+}
+
+pub(crate) struct WindowFactory;
+
+pub(crate) const FACTORY: WindowFactory = WindowFactory;
+
+impl ModuleFactory for WindowFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        let text = ModuleText::new(
+            MODULE_TYPE,
+            Some(parsed.max_width),
+            parsed.scroll_text,
+            context.scale(),
+            &["module", "window"],
+            parsed.class.as_deref(),
+        )?;
+        Ok(build_window_module(
+            context.monitor_connector.clone(),
+            parsed.format,
+            parsed.click.or(parsed.on_click),
+            text,
+        ))
+    }
+}
+
+fn default_format() -> String {
+    "{title}".to_string()
+}
+
+fn default_max_width() -> u32 {
+    DEFAULT_MAX_WIDTH
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<WindowConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))
+}
+
+fn build_window_module(
+    connector: Option<String>,
+    format: String,
+    click_command: Option<String>,
+    text: ModuleText,
+) -> Widget {
+    let widget = text.widget();
+    attach_primary_click_command(&widget, click_command);
+    widget.set_visible(false);
+
+    attach_subscription(
+        &widget,
+        subscribe_focused_toplevel(),
+        move |widget, focused| {
+            let rendered = focused
+                .filter(|focused| shown_on_output(focused, connector.as_deref()))
+                .map(|focused| render_window(&format, &focused))
+                .unwrap_or_default();
+            let visible = !rendered.trim().is_empty();
+            widget.set_visible(visible);
+            if visible {
+                text.set_markup(&rendered);
+            }
+        },
+    );
+
+    widget
+}
+
+/// Windows whose outputs are unknown show on every bar.
+fn shown_on_output(focused: &FocusedToplevel, connector: Option<&str>) -> bool {
+    match connector {
+        Some(connector) if !focused.outputs.is_empty() => {
+            focused.outputs.iter().any(|output| output == connector)
+        }
+        _ => true,
+    }
+}
+
+fn render_window(format: &str, focused: &FocusedToplevel) -> String {
+    if focused.title.is_empty() && focused.app_id.is_empty() {
+        return String::new();
+    }
+    render_markup_template(
+        format,
+        &[
+            ("{}", &focused.title),
+            ("{title}", &focused.title),
+            ("{app_id}", &focused.app_id),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Map;
+
+    use super::*;
+
+    fn focused(outputs: &[&str]) -> FocusedToplevel {
+        FocusedToplevel {
+            title: "notes.md - vim".to_string(),
+            app_id: "foot".to_string(),
+            outputs: outputs.iter().map(|output| output.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("sway/window", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'window'"));
+    }
+
+    #[test]
+    fn parse_config_has_defaults() {
+        let cfg =
+            parse_config(&ModuleConfig::new(MODULE_TYPE, Map::new())).expect("config should parse");
+        assert_eq!(cfg.format, "{title}");
+        assert_eq!(cfg.max_width, DEFAULT_MAX_WIDTH);
+        assert_eq!(cfg.scroll_text, ScrollTextMode::Off);
+    }
+
+    #[test]
+    fn render_window_fills_placeholders() {
+        assert_eq!(
+            render_window("{app_id}: {title}", &focused(&[])),
+            "foot: notes.md - vim"
+        );
+        assert_eq!(render_window("{}", &FocusedToplevel::default()), "");
+    }
+
+    #[test]
+    fn shown_on_output_filters_by_connector() {
+        assert!(shown_on_output(&focused(&["eDP-1"]), Some("eDP-1")));
+        assert!(!shown_on_output(&focused(&["eDP-1"]), Some("HDMI-A-1")));
+        assert!(shown_on_output(&focused(&[]), Some("HDMI-A-1")));
+        assert!(shown_on_output(&focused(&["eDP-1"]), None));
+    }
+}
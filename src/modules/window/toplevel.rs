@@ -0,0 +1,297 @@
+//! Focused-window tracking over `zwlr_foreign_toplevel_manager_v1`, on a
+//! private Wayland connection owned by a worker thread.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use wayland_client::backend::ObjectId;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::wl_output::{self, WlOutput};
+use wayland_client::protocol::wl_registry::{self, WlRegistry};
+use wayland_client::{event_created_child, Connection, Dispatch, Proxy, QueueHandle};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_handle_v1::{
+    self, ZwlrForeignToplevelHandleV1,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_manager_v1::{
+    self, ZwlrForeignToplevelManagerV1,
+};
+
+use crate::modules::backend_errors;
+use crate::modules::broadcaster::{BackendRegistry, Broadcaster, Subscription};
+
+/// Name under which connection failures are reported.
+const ERROR_BACKEND: &str = "foreign-toplevel";
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+/// `wl_output` v4 adds the connector `name` event.
+const WL_OUTPUT_MAX_VERSION: u32 = 4;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct FocusedToplevel {
+    pub(super) title: String,
+    pub(super) app_id: String,
+    /// Connector names of the outputs showing the window; empty when the
+    /// compositor does not report them.
+    pub(super) outputs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ToplevelState {
+    title: String,
+    app_id: String,
+    activated: bool,
+    outputs: Vec<ObjectId>,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    /// Applied state of every open toplevel.
+    toplevels: HashMap<ObjectId, ToplevelState>,
+    /// Changes received since the handle's last `done` event.
+    pending: HashMap<ObjectId, ToplevelState>,
+    output_names: HashMap<ObjectId, String>,
+    dirty: bool,
+}
+
+impl TrackerState {
+    fn pending_for(&mut self, id: &ObjectId) -> &mut ToplevelState {
+        let applied = &self.toplevels;
+        self.pending
+            .entry(id.clone())
+            .or_insert_with(|| applied.get(id).cloned().unwrap_or_default())
+    }
+
+    fn focused(&self) -> Option<FocusedToplevel> {
+        let toplevel = self
+            .toplevels
+            .values()
+            .find(|toplevel| toplevel.activated)?;
+        Some(FocusedToplevel {
+            title: toplevel.title.clone(),
+            app_id: toplevel.app_id.clone(),
+            outputs: toplevel
+                .outputs
+                .iter()
+                .filter_map(|output| self.output_names.get(output).cloned())
+                .collect(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ToplevelSharedKey;
+
+fn toplevel_registry(
+) -> &'static BackendRegistry<ToplevelSharedKey, Broadcaster<Option<FocusedToplevel>>> {
+    static REGISTRY: OnceLock<
+        BackendRegistry<ToplevelSharedKey, Broadcaster<Option<FocusedToplevel>>>,
+    > = OnceLock::new();
+    REGISTRY.get_or_init(BackendRegistry::new)
+}
+
+pub(super) fn subscribe_focused_toplevel() -> Subscription<Option<FocusedToplevel>> {
+    let (broadcaster, start_worker) =
+        toplevel_registry().get_or_create(ToplevelSharedKey, Broadcaster::new);
+    let receiver = broadcaster.subscribe();
+
+    if start_worker {
+        start_toplevel_worker(broadcaster);
+    }
+
+    receiver
+}
+
+fn start_toplevel_worker(broadcaster: Arc<Broadcaster<Option<FocusedToplevel>>>) {
+    std::thread::spawn(move || loop {
+        if broadcaster.subscriber_count() == 0 {
+            toplevel_registry().remove(&ToplevelSharedKey, &broadcaster);
+            return;
+        }
+
+        if let Err(err) = run_tracker(&broadcaster) {
+            backend_errors::report(ERROR_BACKEND, err);
+            broadcaster.broadcast(None);
+            std::thread::sleep(RETRY_DELAY);
+        }
+    });
+}
+
+/// Dispatches protocol events until the connection fails or the last
+/// subscriber is gone.
+fn run_tracker(broadcaster: &Broadcaster<Option<FocusedToplevel>>) -> Result<(), String> {
+    let connection = Connection::connect_to_env()
+        .map_err(|err| format!("failed to connect to the Wayland display: {err}"))?;
+    let (globals, mut queue) = registry_queue_init::<TrackerState>(&connection)
+        .map_err(|err| format!("failed to list Wayland globals: {err}"))?;
+    let qh = queue.handle();
+    let _manager: ZwlrForeignToplevelManagerV1 = globals
+        .bind(&qh, 1..=3, ())
+        .map_err(|err| format!("compositor lacks wlr-foreign-toplevel-management: {err}"))?;
+    globals.contents().with_list(|list| {
+        for global in list
+            .iter()
+            .filter(|global| global.interface == WlOutput::interface().name)
+        {
+            globals.registry().bind::<WlOutput, _, _>(
+                global.name,
+                global.version.min(WL_OUTPUT_MAX_VERSION),
+                &qh,
+                (),
+            );
+        }
+    });
+    backend_errors::clear(ERROR_BACKEND);
+
+    let mut tracker = TrackerState::default();
+    let mut last = None;
+    loop {
+        queue
+            .blocking_dispatch(&mut tracker)
+            .map_err(|err| format!("Wayland dispatch failed: {err}"))?;
+        if broadcaster.subscriber_count() == 0 {
+            return Ok(());
+        }
+        if !std::mem::take(&mut tracker.dirty) {
+            continue;
+        }
+        let focused = tracker.focused();
+        if last.as_ref() != Some(&focused) {
+            broadcaster.broadcast(focused.clone());
+            last = Some(focused);
+        }
+    }
+}
+
+/// The `state` event carries an array of native-endian `u32` states.
+fn is_activated(states: &[u8]) -> bool {
+    let activated = zwlr_foreign_toplevel_handle_v1::State::Activated as u32;
+    states
+        .chunks_exact(4)
+        .any(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) == activated)
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for TrackerState {
+    fn event(
+        _tracker: &mut Self,
+        registry: &WlRegistry,
+        event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _connection: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        // Outputs plugged in later still need a name for `output_enter`.
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            if interface == WlOutput::interface().name {
+                registry.bind::<WlOutput, _, _>(name, version.min(WL_OUTPUT_MAX_VERSION), qh, ());
+            }
+        }
+    }
+}
+
+impl Dispatch<WlOutput, ()> for TrackerState {
+    fn event(
+        tracker: &mut Self,
+        output: &WlOutput,
+        event: wl_output::Event,
+        _data: &(),
+        _connection: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event {
+            tracker.output_names.insert(output.id(), name);
+            tracker.dirty = true;
+        }
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for TrackerState {
+    fn event(
+        _tracker: &mut Self,
+        _manager: &ZwlrForeignToplevelManagerV1,
+        _event: zwlr_foreign_toplevel_manager_v1::Event,
+        _data: &(),
+        _connection: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // New handles arrive through `event_created_child!` and are listed
+        // on their first `done`.
+    }
+
+    event_created_child!(TrackerState, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for TrackerState {
+    fn event(
+        tracker: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _data: &(),
+        _connection: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        use zwlr_foreign_toplevel_handle_v1::Event;
+
+        let id = handle.id();
+        match event {
+            Event::Title { title } => tracker.pending_for(&id).title = title,
+            Event::AppId { app_id } => tracker.pending_for(&id).app_id = app_id,
+            Event::State { state } => tracker.pending_for(&id).activated = is_activated(&state),
+            Event::OutputEnter { output } => {
+                let output = output.id();
+                let pending = tracker.pending_for(&id);
+                if !pending.outputs.contains(&output) {
+                    pending.outputs.push(output);
+                }
+            }
+            Event::OutputLeave { output } => {
+                let output = output.id();
+                tracker
+                    .pending_for(&id)
+                    .outputs
+                    .retain(|item| *item != output);
+            }
+            Event::Done => {
+                if let Some(pending) = tracker.pending.remove(&id) {
+                    tracker.toplevels.insert(id, pending);
+                    tracker.dirty = true;
+                }
+            }
+            Event::Closed => {
+                tracker.pending.remove(&id);
+                tracker.toplevels.remove(&id);
+                tracker.dirty = true;
+                handle.destroy();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn states(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn is_activated_reads_state_array() {
+        let activated = zwlr_foreign_toplevel_handle_v1::State::Activated as u32;
+        let maximized = zwlr_foreign_toplevel_handle_v1::State::Maximized as u32;
+
+        assert!(is_activated(&states(&[maximized, activated])));
+        assert!(!is_activated(&states(&[maximized])));
+        assert!(!is_activated(&[]));
+    }
+}
@@ -0,0 +1,239 @@
+//! Overflow handling shared by the workspace modules.
+//!
+//! Buttons live in a horizontally scrollable viewport that pages on the
+//! scroll wheel and marks clipped edges with `.overflow-left` /
+//! `.overflow-right`. With `max-visible`, buttons past the limit collapse
+//! into a `+N` button whose popover lists them.
+
+use std::ops::Range;
+
+use gtk::prelude::*;
+use gtk::{
+    Adjustment, Box as GtkBox, Button, EventControllerScroll, EventControllerScrollFlags,
+    MenuButton, Orientation, PolicyType, Popover, PositionType, ScrolledWindow,
+};
+
+use crate::modules::apply_css_classes;
+use crate::modules::popovers;
+use crate::modules::sway::workspaces::WorkspaceInfo;
+
+const OVERFLOW_LEFT_CLASS: &str = "overflow-left";
+const OVERFLOW_RIGHT_CLASS: &str = "overflow-right";
+/// Edge slack so fractional scroll positions do not flicker the classes.
+const EDGE_EPSILON: f64 = 0.5;
+
+/// Appends a scrollable viewport to `container` and returns the box that
+/// holds the workspace buttons. `max_width` caps the viewport in pixels;
+/// without it the viewport only scrolls when the bar runs out of room.
+pub(crate) fn attach_scrollable_strip(container: &GtkBox, max_width: Option<u32>) -> GtkBox {
+    let strip = GtkBox::new(Orientation::Horizontal, 4);
+    strip.add_css_class("workspaces-strip");
+
+    let viewport = ScrolledWindow::new();
+    viewport.add_css_class("workspaces-viewport");
+    viewport.set_policy(PolicyType::External, PolicyType::Never);
+    viewport.set_propagate_natural_width(true);
+    viewport.set_propagate_natural_height(true);
+    // Touch drags stay workspace swipes instead of kinetic scrolling.
+    viewport.set_kinetic_scrolling(false);
+    if let Some(max_width) = max_width.and_then(|width| i32::try_from(width).ok()) {
+        viewport.set_max_content_width(max_width);
+    }
+    viewport.set_child(Some(&strip));
+
+    let adjustment = viewport.hadjustment();
+    let update_edges = {
+        let viewport = viewport.downgrade();
+        move |adjustment: &Adjustment| {
+            if let Some(viewport) = viewport.upgrade() {
+                update_edge_classes(&viewport, adjustment);
+            }
+        }
+    };
+    adjustment.connect_changed(update_edges.clone());
+    adjustment.connect_value_changed(update_edges);
+
+    let scroll = EventControllerScroll::new(
+        EventControllerScrollFlags::BOTH_AXES | EventControllerScrollFlags::DISCRETE,
+    );
+    scroll.connect_scroll({
+        let adjustment = adjustment.clone();
+        move |_, dx, dy| {
+            let delta = if dy != 0.0 { dy } else { dx };
+            if delta == 0.0 || !is_overflowing(&adjustment) {
+                return gtk::glib::Propagation::Proceed;
+            }
+            let page = adjustment.page_size() * delta.signum();
+            adjustment.set_value(adjustment.value() + page);
+            gtk::glib::Propagation::Stop
+        }
+    });
+    viewport.add_controller(scroll);
+
+    container.append(&viewport);
+    strip
+}
+
+/// Appends one button per workspace to `parent`. With `max_visible`, only
+/// a window of that many workspaces is shown inline and the rest move into
+/// a `+N` popover. The window always holds the focused workspace, or on
+/// other outputs the visible one.
+pub(crate) fn append_workspace_buttons(
+    parent: &GtkBox,
+    workspaces: &[WorkspaceInfo],
+    focused: Option<&str>,
+    max_visible: Option<usize>,
+    button_class: Option<&str>,
+    popover_owner: &'static str,
+    build_button: impl Fn(&WorkspaceInfo) -> Button,
+) {
+    let focused_index = focused
+        .and_then(|name| workspaces.iter().position(|ws| ws.name == name))
+        .or_else(|| workspaces.iter().position(|ws| ws.visible));
+    let shown = visible_range(workspaces.len(), focused_index, max_visible);
+
+    for (index, ws) in workspaces.iter().enumerate() {
+        if !shown.contains(&index) {
+            continue;
+        }
+        let button = build_button(ws);
+        if Some(index) == focused_index {
+            reveal_when_mapped(&button);
+        }
+        parent.append(&button);
+    }
+
+    let hidden = workspaces
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !shown.contains(index))
+        .map(|(_, ws)| ws)
+        .collect::<Vec<_>>();
+    if !hidden.is_empty() {
+        parent.append(&build_overflow_button(
+            &hidden,
+            button_class,
+            popover_owner,
+            &build_button,
+        ));
+    }
+}
+
+/// Indices shown inline: the leading `max_visible` workspaces (at least
+/// one), shifted right just far enough to include the focused one.
+fn visible_range(
+    count: usize,
+    focused_index: Option<usize>,
+    max_visible: Option<usize>,
+) -> Range<usize> {
+    let Some(max_visible) = max_visible.map(|max| max.max(1)).filter(|max| *max < count) else {
+        return 0..count;
+    };
+    let start = focused_index
+        .map(|focused| (focused + 1).saturating_sub(max_visible))
+        .unwrap_or(0);
+    start..start + max_visible
+}
+
+fn build_overflow_button(
+    hidden: &[&WorkspaceInfo],
+    button_class: Option<&str>,
+    popover_owner: &'static str,
+    build_button: &impl Fn(&WorkspaceInfo) -> Button,
+) -> MenuButton {
+    let toggle = MenuButton::new();
+    toggle.set_label(&format!("+{}", hidden.len()));
+    toggle.add_css_class("menu-button");
+    toggle.add_css_class("workspace-overflow");
+    apply_css_classes(&toggle, button_class);
+    toggle.set_focusable(false);
+    if hidden.iter().any(|ws| ws.urgent) {
+        toggle.add_css_class("urgent");
+        toggle.add_css_class("workspace-urgent");
+    }
+
+    let list = GtkBox::new(Orientation::Horizontal, 4);
+    list.add_css_class("workspace-overflow-list");
+    let popover = Popover::new();
+    popover.set_autohide(true);
+    popover.set_has_arrow(true);
+    popover.set_position(PositionType::Top);
+    popover.add_css_class("workspace-overflow-popover");
+    for ws in hidden {
+        let button = build_button(ws);
+        let popover = popover.downgrade();
+        button.connect_clicked(move |_| {
+            if let Some(popover) = popover.upgrade() {
+                popover.popdown();
+            }
+        });
+        list.append(&button);
+    }
+    popover.set_child(Some(&list));
+    toggle.set_popover(Some(&popover));
+    popovers::track(&popover, popover_owner);
+
+    toggle
+}
+
+/// Scrolls the enclosing viewport so `button` is visible once it has been
+/// laid out; a no-op outside a viewport.
+fn reveal_when_mapped(button: &Button) {
+    button.connect_map(|button| {
+        let button = button.clone();
+        gtk::glib::idle_add_local_once(move || {
+            let Some(viewport) = button
+                .ancestor(ScrolledWindow::static_type())
+                .and_then(|widget| widget.downcast::<ScrolledWindow>().ok())
+            else {
+                return;
+            };
+            // Bounds relative to the viewport are already offset by the
+            // current scroll position.
+            let Some(bounds) = button.compute_bounds(&viewport) else {
+                return;
+            };
+            let adjustment = viewport.hadjustment();
+            let start = f64::from(bounds.x());
+            let end = start + f64::from(bounds.width());
+            if start < 0.0 {
+                adjustment.set_value(adjustment.value() + start);
+            } else if end > adjustment.page_size() {
+                adjustment.set_value(adjustment.value() + end - adjustment.page_size());
+            }
+        });
+    });
+}
+
+fn is_overflowing(adjustment: &Adjustment) -> bool {
+    adjustment.upper() - adjustment.lower() > adjustment.page_size() + EDGE_EPSILON
+}
+
+fn update_edge_classes(viewport: &ScrolledWindow, adjustment: &Adjustment) {
+    let left = adjustment.value() > adjustment.lower() + EDGE_EPSILON;
+    let right = adjustment.value() + adjustment.page_size() < adjustment.upper() - EDGE_EPSILON;
+    for (class_name, active) in [(OVERFLOW_LEFT_CLASS, left), (OVERFLOW_RIGHT_CLASS, right)] {
+        if active {
+            viewport.add_css_class(class_name);
+        } else {
+            viewport.remove_css_class(class_name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visible_range_keeps_focused_workspace_inline() {
+        assert_eq!(visible_range(5, Some(4), None), 0..5);
+        assert_eq!(visible_range(5, Some(4), Some(8)), 0..5);
+        assert_eq!(visible_range(8, Some(1), Some(3)), 0..3);
+        assert_eq!(visible_range(8, Some(2), Some(3)), 0..3);
+        assert_eq!(visible_range(8, Some(5), Some(3)), 3..6);
+        assert_eq!(visible_range(8, Some(7), Some(3)), 5..8);
+        assert_eq!(visible_range(8, None, Some(3)), 0..3);
+        assert_eq!(visible_range(8, Some(4), Some(0)), 4..5);
+    }
+}
@@ -0,0 +1,243 @@
+use gtk::glib::{self, ControlFlow, TimeZone};
+use gtk::prelude::*;
+use gtk::{Label, Widget};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::modules::tooltip::ModuleTooltip;
+use crate::modules::{render_markup_template, ModuleBuildContext, ModuleConfig, ModuleLabel};
+
+use super::ModuleFactory;
+
+const DEFAULT_WORLDCLOCK_FORMAT: &str = "{label} {time}";
+const DEFAULT_WORLDCLOCK_TIME_FORMAT: &str = "%H:%M";
+const DEFAULT_WORLDCLOCK_SEPARATOR: &str = " · ";
+pub(crate) const MODULE_TYPE: &str = "worldclock";
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct WorldClockConfig {
+    pub(crate) zones: Vec<WorldClockZoneConfig>,
+    #[serde(default = "default_worldclock_format")]
+    pub(crate) format: String,
+    #[serde(
+        rename = "time-format",
+        alias = "time_format",
+        default = "default_worldclock_time_format"
+    )]
+    pub(crate) time_format: String,
+    #[serde(default = "default_worldclock_separator")]
+    pub(crate) separator: String,
+    #[serde(default)]
+    pub(crate) click: Option<String>,
+    #[serde(rename = "on-click", default)]
+    pub(crate) on_click: Option<String>,
+    #[serde(default)]
+    pub(crate) class: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct WorldClockZoneConfig {
+    pub(crate) timezone: String,
+    #[serde(default)]
+    pub(crate) label: Option<String>,
+}
+
+struct WorldClockZone {
+    label: String,
+    time_zone: TimeZone,
+}
+
+pub(crate) struct WorldClockFactory;
+
+pub(crate) const FACTORY: WorldClockFactory = WorldClockFactory;
+
+impl ModuleFactory for WorldClockFactory {
+    fn module_type(&self) -> &'static str {
+        MODULE_TYPE
+    }
+
+    fn supports_tooltip_format(&self) -> bool {
+        true
+    }
+
+    fn init(&self, config: &ModuleConfig, context: &ModuleBuildContext) -> Result<Widget, String> {
+        let parsed = parse_config(config)?;
+        let zones = parsed
+            .zones
+            .iter()
+            .map(|zone| {
+                Ok(WorldClockZone {
+                    label: zone
+                        .label
+                        .clone()
+                        .unwrap_or_else(|| default_zone_label(&zone.timezone)),
+                    time_zone: resolve_time_zone(&zone.timezone)?,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let click_command = parsed.click.clone().or(parsed.on_click.clone());
+        Ok(
+            build_worldclock_module(parsed, zones, click_command, context.tooltip_format.clone())
+                .upcast(),
+        )
+    }
+}
+
+fn default_worldclock_format() -> String {
+    DEFAULT_WORLDCLOCK_FORMAT.to_string()
+}
+
+fn default_worldclock_time_format() -> String {
+    DEFAULT_WORLDCLOCK_TIME_FORMAT.to_string()
+}
+
+fn default_worldclock_separator() -> String {
+    DEFAULT_WORLDCLOCK_SEPARATOR.to_string()
+}
+
+fn parse_config(module: &ModuleConfig) -> Result<WorldClockConfig, String> {
+    if module.module_type != MODULE_TYPE {
+        return Err(format!(
+            "expected module type '{}', got '{}'",
+            MODULE_TYPE, module.module_type
+        ));
+    }
+
+    let parsed: WorldClockConfig = serde_json::from_value(Value::Object(module.config.clone()))
+        .map_err(|err| format!("invalid {} module config: {err}", MODULE_TYPE))?;
+    if parsed.zones.is_empty() {
+        return Err(format!(
+            "invalid {} module config: `zones` must not be empty",
+            MODULE_TYPE
+        ));
+    }
+    Ok(parsed)
+}
+
+/// Resolves an IANA identifier against the system tz database.
+fn resolve_time_zone(identifier: &str) -> Result<TimeZone, String> {
+    TimeZone::from_identifier(Some(identifier))
+        .ok_or_else(|| format!("unknown timezone '{identifier}'"))
+}
+
+/// `America/New_York` -> `New York`.
+fn default_zone_label(identifier: &str) -> String {
+    identifier
+        .rsplit('/')
+        .next()
+        .unwrap_or(identifier)
+        .replace('_', " ")
+}
+
+fn build_worldclock_module(
+    config: WorldClockConfig,
+    zones: Vec<WorldClockZone>,
+    click_command: Option<String>,
+    tooltip_format: Option<String>,
+) -> Label {
+    let label = ModuleLabel::new("worldclock")
+        .with_css_classes(config.class.as_deref())
+        .with_click_command(click_command)
+        .into_label();
+    let tooltip = tooltip_format.map(|format| (ModuleTooltip::attach(&label), format));
+
+    let update = move |label: &Label| {
+        let rendered = render_worldclock_text(&config, &zones, &config.format, &config.separator);
+        let visible = !rendered.trim().is_empty();
+        label.set_visible(visible);
+        if visible {
+            label.set_markup(&rendered);
+        }
+        // The tooltip has room for one zone per line.
+        if let Some((tooltip, format)) = &tooltip {
+            tooltip.set_markup(&render_worldclock_text(&config, &zones, format, "\n"));
+        }
+    };
+
+    update(&label);
+
+    let label_weak = label.downgrade();
+    glib::timeout_add_seconds_local(1, move || {
+        let Some(label) = label_weak.upgrade() else {
+            return ControlFlow::Break;
+        };
+
+        update(&label);
+
+        ControlFlow::Continue
+    });
+
+    label
+}
+
+fn render_worldclock_text(
+    config: &WorldClockConfig,
+    zones: &[WorldClockZone],
+    format: &str,
+    separator: &str,
+) -> String {
+    zones
+        .iter()
+        .map(|zone| {
+            let time = glib::DateTime::now(&zone.time_zone)
+                .and_then(|now| now.format(&config.time_format))
+                .map(|text| text.to_string())
+                .unwrap_or_default();
+            render_markup_template(format, &[("{label}", &zone.label), ("{time}", &time)])
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Map};
+
+    use super::*;
+
+    fn module_with(value: serde_json::Value) -> ModuleConfig {
+        ModuleConfig::new(
+            MODULE_TYPE,
+            serde_json::from_value(value).expect("module config map should parse"),
+        )
+    }
+
+    #[test]
+    fn parse_config_rejects_wrong_module_type() {
+        let module = ModuleConfig::new("clock", Map::new());
+        let err = parse_config(&module).expect_err("wrong type should fail");
+        assert!(err.contains("expected module type 'worldclock'"));
+    }
+
+    #[test]
+    fn parse_config_requires_zones() {
+        let err = parse_config(&module_with(json!({ "zones": [] })))
+            .expect_err("empty zones should fail");
+        assert!(err.contains("`zones` must not be empty"));
+    }
+
+    #[test]
+    fn parse_config_applies_defaults() {
+        let cfg = parse_config(&module_with(json!({
+            "zones": [{ "timezone": "Asia/Tokyo", "label": "TYO" }]
+        })))
+        .expect("config should parse");
+        assert_eq!(cfg.format, DEFAULT_WORLDCLOCK_FORMAT);
+        assert_eq!(cfg.time_format, DEFAULT_WORLDCLOCK_TIME_FORMAT);
+        assert_eq!(cfg.separator, DEFAULT_WORLDCLOCK_SEPARATOR);
+        assert_eq!(cfg.zones[0].label.as_deref(), Some("TYO"));
+    }
+
+    #[test]
+    fn default_zone_label_uses_city_segment() {
+        assert_eq!(default_zone_label("America/New_York"), "New York");
+        assert_eq!(default_zone_label("UTC"), "UTC");
+    }
+
+    #[test]
+    fn resolve_time_zone_rejects_unknown_identifier() {
+        assert!(resolve_time_zone("UTC").is_ok());
+        let err = resolve_time_zone("Not/AZone").expect_err("bogus zone should fail");
+        assert!(err.contains("unknown timezone"));
+    }
+}
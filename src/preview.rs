@@ -0,0 +1,174 @@
+//! `vibar preview`: renders one module once, with live data from its
+//! backend, and prints the result. Meant for iterating on `format` strings
+//! without reloading the bar. Uses the `--stdout` text rendering, so only
+//! modules that support that mode can be previewed.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use gtk::glib;
+use gtk::pango;
+use serde_json::{Map, Value};
+
+use crate::modules::text::{TextBlock, TextSink};
+use crate::modules::{self, ModuleConfig};
+
+pub(crate) const COMMAND: &str = "preview";
+
+const USAGE: &str =
+    "usage: vibar preview --module <type> [--format <format>] [--set <key>=<value>...]";
+const PREVIEW_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq)]
+struct PreviewArgs {
+    module_type: String,
+    fields: Map<String, Value>,
+}
+
+pub(crate) fn run(args: &[String]) -> Result<(), String> {
+    let args = parse_args(args)?;
+    let module = ModuleConfig::new(args.module_type, args.fields);
+
+    let main_loop = glib::MainLoop::new(None, false);
+    let first_block = Rc::new(RefCell::new(None::<TextBlock>));
+    let sink: TextSink = {
+        let main_loop = main_loop.clone();
+        let first_block = Rc::clone(&first_block);
+        Rc::new(move |block| {
+            // Modules may report "hidden" before their first real value.
+            let Some(block) = block else {
+                return;
+            };
+            let mut first = first_block.borrow_mut();
+            if first.is_none() {
+                *first = Some(block);
+                main_loop.quit();
+            }
+        })
+    };
+    modules::build_text_module(&module, sink)
+        .map_err(|err| format!("cannot preview module '{}': {err}", module.module_type))?;
+
+    glib::timeout_add_local_once(PREVIEW_TIMEOUT, {
+        let main_loop = main_loop.clone();
+        move || main_loop.quit()
+    });
+    main_loop.run();
+
+    let Some(block) = first_block.take() else {
+        return Err(format!(
+            "module '{}' rendered nothing within {}s (empty output hides the module)",
+            module.module_type,
+            PREVIEW_TIMEOUT.as_secs()
+        ));
+    };
+    println!("{}", describe_block(&block)?);
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> Result<PreviewArgs, String> {
+    let mut module_type = None;
+    let mut fields = Map::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value_for = |option: &str| {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("missing value for {option}\n{USAGE}"))
+        };
+        match arg.as_str() {
+            "--module" => module_type = Some(value_for("--module")?),
+            "--format" => {
+                fields.insert("format".to_string(), Value::String(value_for("--format")?));
+            }
+            "--set" => {
+                let assignment = value_for("--set")?;
+                let Some((key, value)) = assignment.split_once('=') else {
+                    return Err(format!("invalid --set '{assignment}' (expected key=value)"));
+                };
+                fields.insert(key.to_string(), parse_field_value(value));
+            }
+            other => return Err(format!("unexpected argument '{other}'\n{USAGE}")),
+        }
+    }
+
+    let module_type = module_type.ok_or_else(|| USAGE.to_string())?;
+    Ok(PreviewArgs {
+        module_type,
+        fields,
+    })
+}
+
+/// JSON when it parses (`5`, `true`, `["a","b"]`), otherwise a plain string.
+fn parse_field_value(value: &str) -> Value {
+    serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+}
+
+/// The markup as configured plus the text the bar would show. Fails on
+/// markup Pango would reject, since the bar would show nothing useful.
+fn describe_block(block: &TextBlock) -> Result<String, String> {
+    let (_, text, _) = pango::parse_markup(&block.markup, '\0')
+        .map_err(|err| format!("invalid Pango markup '{}': {err}", block.markup))?;
+    let mut description = format!("markup: {}\ntext:   {text}", block.markup);
+    if block.urgent {
+        description.push_str("\nurgent: true");
+    }
+    Ok(description)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_args_collects_format_and_fields() {
+        let parsed = parse_args(&args(&[
+            "--module",
+            "cpu",
+            "--format",
+            "<b>{used_percentage}%</b>",
+            "--set",
+            "interval_secs=2",
+            "--set",
+            "class=busy",
+        ]))
+        .expect("args should parse");
+
+        assert_eq!(parsed.module_type, "cpu");
+        assert_eq!(
+            Value::Object(parsed.fields),
+            json!({
+                "format": "<b>{used_percentage}%</b>",
+                "interval_secs": 2,
+                "class": "busy"
+            })
+        );
+    }
+
+    #[test]
+    fn parse_args_rejects_missing_or_unknown_arguments() {
+        assert!(parse_args(&args(&["--format", "{}"])).is_err());
+        assert!(parse_args(&args(&["--module"])).is_err());
+        assert!(parse_args(&args(&["--module", "cpu", "--set", "novalue"])).is_err());
+        assert!(parse_args(&args(&["--module", "cpu", "extra"])).is_err());
+    }
+
+    #[test]
+    fn describe_block_strips_markup_and_rejects_invalid_markup() {
+        let block = TextBlock::new("<b>42%</b> &amp; more").urgent(true);
+        assert_eq!(
+            describe_block(&block).expect("markup should parse"),
+            "markup: <b>42%</b> &amp; more\ntext:   42% & more\nurgent: true"
+        );
+
+        assert!(describe_block(&TextBlock::new("<b>unclosed")).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! Renders bar windows to PNG files for `vibar screenshot`.
+
+use std::path::{Path, PathBuf};
+
+use gtk::prelude::*;
+use gtk::{ApplicationWindow, Snapshot, WidgetPaintable};
+
+/// Renders `window` with its own GSK renderer and writes the result as PNG.
+pub(crate) fn save_window_png(window: &ApplicationWindow, path: &Path) -> Result<(), String> {
+    if !window.is_mapped() {
+        return Err("bar is not visible (run `vibar toggle` to show it)".to_string());
+    }
+    let width = window.width();
+    let height = window.height();
+    if width <= 0 || height <= 0 {
+        return Err("bar has no size yet".to_string());
+    }
+    let renderer = window
+        .renderer()
+        .ok_or_else(|| "bar has no renderer".to_string())?;
+
+    let paintable = WidgetPaintable::new(Some(window));
+    let snapshot = Snapshot::new();
+    paintable.snapshot(&snapshot, f64::from(width), f64::from(height));
+    let node = snapshot
+        .to_node()
+        .ok_or_else(|| "bar rendered nothing".to_string())?;
+    let texture = renderer.render_texture(&node, None);
+    texture
+        .save_to_png(path)
+        .map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+/// One file per bar: `path` itself for a single bar, otherwise the bar's
+/// name is appended to the file stem (`bar.png` -> `bar-DP-1.png`).
+pub(crate) fn output_paths(path: &Path, names: &[String]) -> Vec<PathBuf> {
+    if names.len() <= 1 {
+        return names.iter().map(|_| path.to_path_buf()).collect();
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().into_owned());
+    names
+        .iter()
+        .map(|name| {
+            let file_name = match &extension {
+                Some(extension) => format!("{stem}-{name}.{extension}"),
+                None => format!("{stem}-{name}"),
+            };
+            path.with_file_name(file_name)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn output_paths_keeps_path_for_single_bar() {
+        assert_eq!(
+            output_paths(Path::new("/tmp/bar.png"), &names(&["DP-1"])),
+            vec![PathBuf::from("/tmp/bar.png")]
+        );
+        assert!(output_paths(Path::new("/tmp/bar.png"), &[]).is_empty());
+    }
+
+    #[test]
+    fn output_paths_suffixes_bar_names() {
+        assert_eq!(
+            output_paths(Path::new("/tmp/bar.png"), &names(&["DP-1", "HDMI-A-1"])),
+            vec![
+                PathBuf::from("/tmp/bar-DP-1.png"),
+                PathBuf::from("/tmp/bar-HDMI-A-1.png"),
+            ]
+        );
+        assert_eq!(
+            output_paths(Path::new("shot"), &names(&["a", "b"])),
+            vec![PathBuf::from("shot-a"), PathBuf::from("shot-b")]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::rc::{Rc, Weak};
@@ -8,21 +9,30 @@ use gtk::gdk;
 use gtk::gio;
 use gtk::prelude::*;
 
-use crate::config::{resolve_style_path, StyleConfig};
+use crate::config::{user_style_path, StyleConfig};
 
 const USER_STYLE_RELOAD_DEBOUNCE_MILLIS: u64 = 150;
 
 pub(crate) struct StyleRuntime {
     display: gdk::Display,
     default_provider: Option<gtk::CssProvider>,
+    colors_provider: Option<gtk::CssProvider>,
     user_css_path: Option<PathBuf>,
-    user_css_provider: RefCell<Option<gtk::CssProvider>>,
+    /// Stays installed for the runtime's lifetime; reloads replace its
+    /// rules in place.
+    user_css_provider: Option<gtk::CssProvider>,
+    /// Last applied user CSS, to skip reloads for saves without changes.
+    user_css_content: RefCell<Option<String>>,
     user_css_monitor: RefCell<Option<gio::FileMonitor>>,
     reload_debounce_source: RefCell<Option<gtk::glib::SourceId>>,
 }
 
 impl StyleRuntime {
-    pub(crate) fn install(style: &StyleConfig, config_source: Option<&Path>) -> Option<Rc<Self>> {
+    pub(crate) fn install(
+        style: &StyleConfig,
+        colors: &BTreeMap<String, String>,
+        config_source: Option<&Path>,
+    ) -> Option<Rc<Self>> {
         let display = gdk::Display::default()?;
 
         let default_provider = if style.load_default {
@@ -38,16 +48,49 @@ impl StyleRuntime {
             None
         };
 
-        let user_css_path = style
-            .path
-            .as_deref()
-            .map(|path| resolve_style_path(path, config_source));
+        // Named colors resolve across all providers by priority, so config
+        // tokens override `@define-color` rules from both default and user CSS.
+        let colors_provider = if colors.is_empty() {
+            None
+        } else {
+            let colors_provider = gtk::CssProvider::new();
+            colors_provider.load_from_data(&color_definitions_css(colors));
+            gtk::style_context_add_provider_for_display(
+                &display,
+                &colors_provider,
+                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 2,
+            );
+            Some(colors_provider)
+        };
+
+        let user_css_path = user_style_path(style, config_source);
+        let user_css_provider = user_css_path.as_ref().map(|path| {
+            let provider = gtk::CssProvider::new();
+            let error_path = path.clone();
+            provider.connect_parsing_error(move |_, section, error| {
+                let location = section.start_location();
+                eprintln!(
+                    "CSS error in {}:{}:{}: {error}",
+                    error_path.display(),
+                    location.lines() + 1,
+                    location.line_chars() + 1
+                );
+            });
+            gtk::style_context_add_provider_for_display(
+                &display,
+                &provider,
+                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+            );
+            provider
+        });
 
         let runtime = Rc::new(Self {
             display,
             default_provider,
+            colors_provider,
             user_css_path,
-            user_css_provider: RefCell::new(None),
+            user_css_provider,
+            user_css_content: RefCell::new(None),
             user_css_monitor: RefCell::new(None),
             reload_debounce_source: RefCell::new(None),
         });
@@ -58,8 +101,13 @@ impl StyleRuntime {
         Some(runtime)
     }
 
+    /// Replaces the user provider's rules in one step, so the bar never
+    /// renders without them. Unreadable files (for example mid-save) keep
+    /// the previous rules.
     fn load_user_css_once(&self) {
-        let Some(path) = self.user_css_path.as_ref() else {
+        let (Some(path), Some(provider)) =
+            (self.user_css_path.as_ref(), self.user_css_provider.as_ref())
+        else {
             return;
         };
 
@@ -70,20 +118,12 @@ impl StyleRuntime {
                 return;
             }
         };
-
-        let provider = gtk::CssProvider::new();
-        provider.load_from_data(&content);
-
-        if let Some(previous) = self.user_css_provider.borrow_mut().take() {
-            gtk::style_context_remove_provider_for_display(&self.display, &previous);
+        if self.user_css_content.borrow().as_ref() == Some(&content) {
+            return;
         }
 
-        gtk::style_context_add_provider_for_display(
-            &self.display,
-            &provider,
-            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
-        );
-        *self.user_css_provider.borrow_mut() = Some(provider);
+        provider.load_from_data(&content);
+        *self.user_css_content.borrow_mut() = Some(content);
     }
 
     fn install_user_css_watch(self: &Rc<Self>) {
@@ -136,7 +176,11 @@ impl Drop for StyleRuntime {
             source_id.remove();
         }
 
-        if let Some(provider) = self.user_css_provider.borrow_mut().take() {
+        if let Some(provider) = self.user_css_provider.take() {
+            gtk::style_context_remove_provider_for_display(&self.display, &provider);
+        }
+
+        if let Some(provider) = self.colors_provider.take() {
             gtk::style_context_remove_provider_for_display(&self.display, &provider);
         }
 
@@ -145,3 +189,55 @@ impl Drop for StyleRuntime {
         }
     }
 }
+
+/// Renders `colors` as `@define-color` rules, skipping (and logging) entries
+/// whose name is not a CSS identifier or whose value could escape the rule.
+fn color_definitions_css(colors: &BTreeMap<String, String>) -> String {
+    let mut css = String::new();
+    for (name, value) in colors {
+        if !is_color_name(name) {
+            eprintln!(
+                "Ignoring color '{name}': names may only contain letters, digits, '_' and '-'"
+            );
+            continue;
+        }
+        let value = value.trim();
+        if value.is_empty() || value.contains([';', '{', '}', '\n']) {
+            eprintln!("Ignoring color '{name}': invalid value '{value}'");
+            continue;
+        }
+        css.push_str(&format!("@define-color {name} {value};\n"));
+    }
+    css
+}
+
+fn is_color_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_definitions_css_emits_valid_tokens_only() {
+        let colors = BTreeMap::from([
+            ("accent".to_string(), " #ff8800 ".to_string()),
+            ("critical".to_string(), "alpha(@accent, 0.5)".to_string()),
+            ("1bad".to_string(), "red".to_string()),
+            (
+                "sneaky".to_string(),
+                "red; } .bar { color: blue".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            color_definitions_css(&colors),
+            "@define-color accent #ff8800;\n@define-color critical alpha(@accent, 0.5);\n"
+        );
+    }
+}
@@ -0,0 +1,637 @@
+//! `vibar import-waybar`: converts a Waybar config (and its `style.css`)
+//! into a vibar `config.jsonc`, reporting every option that has no vibar
+//! equivalent. Runs locally; it does not talk to a running bar.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use crate::config::{home_config_path, user_config_dir, CURRENT_CONFIG_VERSION};
+
+pub(crate) const COMMAND: &str = "import-waybar";
+
+const WAYBAR_CONFIG_NAMES: [&str; 2] = ["config.jsonc", "config"];
+const WAYBAR_STYLE_NAME: &str = "style.css";
+const AREA_KEYS: [(&str, &str); 3] = [
+    ("modules-left", "left"),
+    ("modules-center", "center"),
+    ("modules-right", "right"),
+];
+
+/// Waybar module name, vibar module type, and the options that carry over
+/// as `(waybar key, vibar key)`.
+type ModuleMapping = (
+    &'static str,
+    &'static str,
+    &'static [(&'static str, &'static str)],
+);
+
+const MODULE_MAPPINGS: &[ModuleMapping] = &[
+    (
+        "clock",
+        "clock",
+        &[
+            ("format", "format"),
+            ("locale", "locale"),
+            ("on-click", "on-click"),
+        ],
+    ),
+    (
+        "cpu",
+        "cpu",
+        &[
+            ("format", "format"),
+            ("interval", "interval_secs"),
+            ("on-click", "on-click"),
+        ],
+    ),
+    (
+        "memory",
+        "memory",
+        &[
+            ("format", "format"),
+            ("interval", "interval_secs"),
+            ("on-click", "on-click"),
+        ],
+    ),
+    (
+        "network",
+        "network",
+        &[
+            ("format", "format"),
+            ("format-wifi", "format-wifi"),
+            ("format-ethernet", "format-ethernet"),
+            ("format-disconnected", "format-disconnected"),
+            ("interface", "interface"),
+            ("interval", "interval_secs"),
+            ("on-click", "on-click"),
+        ],
+    ),
+    (
+        "disk",
+        "disk",
+        &[
+            ("format", "format"),
+            ("path", "path"),
+            ("interval", "interval_secs"),
+            ("on-click", "on-click"),
+        ],
+    ),
+    (
+        "temperature",
+        "temperature",
+        &[
+            ("format", "format"),
+            ("format-critical", "format-critical"),
+            ("critical-threshold", "critical-threshold"),
+            ("hwmon-path", "path"),
+            ("thermal-zone", "thermal-zone"),
+            ("format-icons", "format-icons"),
+            ("interval", "interval_secs"),
+            ("on-click", "on-click"),
+        ],
+    ),
+    (
+        "backlight",
+        "backlight",
+        &[
+            ("format", "format"),
+            ("device", "device"),
+            ("format-icons", "format-icons"),
+            ("interval", "interval_secs"),
+            ("scroll-step", "scroll-step"),
+            ("on-click", "on-click"),
+        ],
+    ),
+    (
+        "battery",
+        "battery",
+        &[
+            ("format", "format"),
+            ("format-charging", "format-charging"),
+            ("format-discharging", "format-discharging"),
+            ("bat", "device"),
+            ("format-icons", "format-icons"),
+            ("interval", "interval_secs"),
+            ("on-click", "on-click"),
+        ],
+    ),
+    (
+        "pulseaudio",
+        "pulseaudio",
+        &[
+            ("format", "format"),
+            ("format-bluetooth", "format-bluetooth"),
+            ("format-bluetooth-muted", "format-bluetooth-muted"),
+            ("format-muted", "format-muted"),
+            ("format-source", "format-source"),
+            ("format-source-muted", "format-source-muted"),
+            ("format-icons", "format-icons"),
+            ("scroll-step", "scroll-step"),
+            ("max-volume", "max-volume"),
+            ("on-click", "on-click"),
+            ("on-click-right", "right-click"),
+        ],
+    ),
+    (
+        "bluetooth",
+        "bluetooth",
+        &[
+            ("format", "format"),
+            ("format-on", "format-on"),
+            ("format-off", "format-off"),
+            ("format-connected", "format-connected"),
+            ("format-no-controller", "format-no-controller"),
+            ("on-click", "on-click"),
+        ],
+    ),
+    ("sway/workspaces", "sway/workspaces", &[]),
+    (
+        "sway/mode",
+        "sway/mode",
+        &[("format", "format"), ("on-click", "on-click")],
+    ),
+    (
+        "sway/window",
+        "sway/window",
+        &[("format", "format"), ("on-click", "on-click")],
+    ),
+    ("tray", "tray", &[("icon-size", "icon_size")]),
+    ("mpris", "playerctl", &[]),
+];
+
+/// Options every vibar module accepts, carried over for all mapped modules.
+const COMMON_MAPPING: &[(&str, &str)] = &[
+    ("on-scroll-up", "on-scroll-up"),
+    ("on-scroll-down", "on-scroll-down"),
+    ("on-click-middle", "on-middle-click"),
+];
+
+/// `custom/<name>` becomes `exec` with these options.
+const CUSTOM_MAPPING: &[(&str, &str)] = &[
+    ("exec", "command"),
+    ("format", "format"),
+    ("interval", "interval_secs"),
+    ("signal", "signal"),
+    ("on-click", "on-click"),
+];
+
+/// Custom module options that vibar's `exec` handles on its own.
+const CUSTOM_IMPLIED_KEYS: &[&str] = &["return-type"];
+
+/// Waybar CSS ids and the vibar class that styles the same widget.
+const CSS_ID_CLASSES: &[(&str, &str)] = &[
+    ("waybar", "bar"),
+    ("workspaces", "workspaces"),
+    ("mode", "sway-mode"),
+    ("window", "sway-window"),
+    ("clock", "clock"),
+    ("cpu", "cpu"),
+    ("memory", "memory"),
+    ("network", "network"),
+    ("disk", "disk"),
+    ("temperature", "temperature"),
+    ("backlight", "backlight"),
+    ("battery", "battery"),
+    ("pulseaudio", "pulseaudio"),
+    ("bluetooth", "bluetooth"),
+    ("tray", "tray"),
+    ("mpris", "playerctl"),
+];
+
+/// Runs the importer with the arguments after the verb:
+/// `[waybar config file or dir] [vibar config dir]`.
+pub(crate) fn run(args: &[String]) -> Result<(), String> {
+    let (source, target_dir) = match args {
+        [] => (None, None),
+        [source] => (Some(PathBuf::from(source)), None),
+        [source, target] => (Some(PathBuf::from(source)), Some(PathBuf::from(target))),
+        _ => {
+            return Err(format!(
+                "usage: vibar {COMMAND} [waybar-config] [vibar-config-dir]"
+            ))
+        }
+    };
+
+    let source = match source {
+        Some(source) => source,
+        None => user_config_dir()
+            .map(|dir| dir.join("waybar"))
+            .ok_or("cannot locate the waybar config directory; pass it explicitly")?,
+    };
+    let waybar_config_path = resolve_waybar_config_path(&source)?;
+    let target_dir = match target_dir {
+        Some(target_dir) => target_dir,
+        None => home_config_path()
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+            .ok_or("cannot locate the vibar config directory; pass it explicitly")?,
+    };
+
+    let content = fs::read_to_string(&waybar_config_path)
+        .map_err(|err| format!("failed to read {}: {err}", waybar_config_path.display()))?;
+    let waybar_config = json5::from_str::<Value>(&content)
+        .map_err(|err| format!("failed to parse {}: {err}", waybar_config_path.display()))?;
+
+    let mut report = Vec::new();
+    let mut config = convert_config(&waybar_config, &mut report)?;
+
+    let style_source = waybar_config_path.with_file_name(WAYBAR_STYLE_NAME);
+    let style = fs::read_to_string(&style_source)
+        .ok()
+        .map(|css| translate_css(&css, &mut report));
+    if style.is_some() {
+        config["style"] = json!({ "path": WAYBAR_STYLE_NAME });
+    }
+
+    let config_target = target_dir.join("config.jsonc");
+    let style_target = target_dir.join(WAYBAR_STYLE_NAME);
+    for target in [Some(&config_target), style.as_ref().map(|_| &style_target)]
+        .into_iter()
+        .flatten()
+    {
+        if target.exists() {
+            return Err(format!(
+                "{} already exists; pass another output directory",
+                target.display()
+            ));
+        }
+    }
+
+    fs::create_dir_all(&target_dir)
+        .map_err(|err| format!("failed to create {}: {err}", target_dir.display()))?;
+    let rendered = format!(
+        "// Imported from {} by `vibar {COMMAND}`.\n{}\n",
+        waybar_config_path.display(),
+        serde_json::to_string_pretty(&config).map_err(|err| err.to_string())?
+    );
+    write_file(&config_target, &rendered)?;
+    println!("wrote {}", config_target.display());
+    if let Some(style) = style {
+        write_file(&style_target, &style)?;
+        println!("wrote {}", style_target.display());
+    }
+
+    if !report.is_empty() {
+        eprintln!("Not imported ({} item(s)):", report.len());
+        for line in &report {
+            eprintln!("  - {line}");
+        }
+    }
+    Ok(())
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    fs::write(path, content).map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+fn resolve_waybar_config_path(source: &Path) -> Result<PathBuf, String> {
+    if !source.is_dir() {
+        return Ok(source.to_path_buf());
+    }
+
+    WAYBAR_CONFIG_NAMES
+        .iter()
+        .map(|name| source.join(name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("no waybar config found in {}", source.display()))
+}
+
+/// Builds the vibar config object; everything that could not be carried
+/// over is appended to `report`.
+fn convert_config(waybar: &Value, report: &mut Vec<String>) -> Result<Value, String> {
+    let bar = match waybar {
+        Value::Array(bars) => {
+            if bars.len() > 1 {
+                report.push(format!(
+                    "only the first of {} bars was imported; use top-level `outputs` for the rest",
+                    bars.len()
+                ));
+            }
+            bars.first().ok_or("waybar config has no bars")?
+        }
+        bar => bar,
+    };
+    let Value::Object(bar) = bar else {
+        return Err("waybar config must be an object or a list of objects".to_string());
+    };
+
+    let mut referenced = Vec::new();
+    let mut areas = Map::new();
+    for (waybar_key, area) in AREA_KEYS {
+        let names = module_names(bar.get(waybar_key));
+        let modules = names
+            .iter()
+            .filter_map(|name| convert_module(name, bar, report, &mut referenced))
+            .collect::<Vec<_>>();
+        areas.insert(area.to_string(), Value::Array(modules));
+    }
+
+    for (key, value) in bar {
+        if AREA_KEYS.iter().any(|(area_key, _)| area_key == key)
+            || referenced.iter().any(|name| name == key)
+        {
+            continue;
+        }
+        if value.is_object() {
+            report.push(format!("module config `{key}` is not used by any bar area"));
+        } else {
+            report.push(format!("bar option `{key}`"));
+        }
+    }
+
+    Ok(json!({
+        "config-version": CURRENT_CONFIG_VERSION,
+        "areas": areas,
+    }))
+}
+
+fn module_names(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(|name| name.as_str().map(ToOwned::to_owned))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn convert_module(
+    name: &str,
+    bar: &Map<String, Value>,
+    report: &mut Vec<String>,
+    referenced: &mut Vec<String>,
+) -> Option<Value> {
+    referenced.push(name.to_string());
+    let (base, instance) = match name.split_once('#') {
+        Some((base, instance)) => (base, Some(instance)),
+        None => (name, None),
+    };
+    let options = bar
+        .get(name)
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+
+    let (module_type, mapping, mut classes) = if let Some(custom) = base.strip_prefix("custom/") {
+        ("exec", CUSTOM_MAPPING, vec![format!("custom-{custom}")])
+    } else if base.starts_with("group/") {
+        return Some(convert_group(name, &options, bar, report, referenced));
+    } else if let Some((_, module_type, mapping)) = MODULE_MAPPINGS
+        .iter()
+        .find(|(waybar, _, _)| *waybar == base)
+    {
+        (*module_type, *mapping, Vec::new())
+    } else {
+        report.push(format!("module `{name}` has no vibar equivalent"));
+        return None;
+    };
+    classes.extend(instance.map(ToOwned::to_owned));
+
+    let mut module = Map::new();
+    module.insert("type".to_string(), Value::from(module_type));
+    for (key, value) in options {
+        match mapping
+            .iter()
+            .chain(COMMON_MAPPING)
+            .find(|(waybar_key, _)| *waybar_key == key)
+        {
+            // Waybar's `"interval": "once"` maps to the same exec mode.
+            Some((_, "interval_secs")) if module_type == "exec" && value == "once" => {
+                module.insert("interval_secs".to_string(), value);
+            }
+            Some((_, "interval_secs")) if !value.is_u64() => {
+                report.push(format!(
+                    "`{name}.{key}` ({value}) must be a number of seconds"
+                ));
+            }
+            Some((_, vibar_key)) => {
+                module.insert(vibar_key.to_string(), value);
+            }
+            None if module_type == "exec" && CUSTOM_IMPLIED_KEYS.contains(&key.as_str()) => {}
+            None => report.push(format!("`{name}.{key}`")),
+        }
+    }
+
+    if module_type == "exec" && !module.contains_key("command") {
+        report.push(format!("module `{name}` has no `exec` command"));
+        return None;
+    }
+    if module_type == "clock" {
+        split_clock_format(&mut module);
+    }
+    if !classes.is_empty() {
+        module.insert("class".to_string(), Value::from(classes.join(" ")));
+    }
+    Some(Value::Object(module))
+}
+
+fn convert_group(
+    name: &str,
+    options: &Map<String, Value>,
+    bar: &Map<String, Value>,
+    report: &mut Vec<String>,
+    referenced: &mut Vec<String>,
+) -> Value {
+    let children = module_names(options.get("modules"))
+        .iter()
+        .filter_map(|child| convert_module(child, bar, report, referenced))
+        .collect::<Vec<_>>();
+    for key in options.keys().filter(|key| key.as_str() != "modules") {
+        report.push(format!("`{name}.{key}`"));
+    }
+    json!({ "type": "group", "modules": children })
+}
+
+/// Waybar clocks embed the time format in `format` (`"{:%H:%M}"`); vibar
+/// keeps it in `time-format` and marks its place with `{}`.
+fn split_clock_format(module: &mut Map<String, Value>) {
+    let Some(format) = module.get("format").and_then(Value::as_str) else {
+        return;
+    };
+    let Some(start) = format.find("{:") else {
+        return;
+    };
+    let Some(len) = format[start..].find('}') else {
+        return;
+    };
+
+    let time_format = format[start + 2..start + len].to_string();
+    let template = format!("{}{{}}{}", &format[..start], &format[start + len + 1..]);
+    module.insert("format".to_string(), Value::from(template));
+    module.insert("time-format".to_string(), Value::from(time_format));
+}
+
+/// Rewrites Waybar's `#id` selectors to vibar classes. Declarations,
+/// comments, and unknown ids are left as they are.
+fn translate_css(css: &str, report: &mut Vec<String>) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut depth = 0usize;
+    let mut rest = css;
+
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("/*") {
+            let end = rest.find("*/").map_or(rest.len(), |end| end + 2);
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            '#' if depth == 0 => {
+                let id_len = rest[1..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+                    .unwrap_or(rest.len() - 1);
+                let id = &rest[1..1 + id_len];
+                if let Some(class) = css_class_for_id(id) {
+                    if id == "waybar" && out.ends_with("window") {
+                        out.truncate(out.len() - "window".len());
+                    }
+                    out.push('.');
+                    out.push_str(&class);
+                    rest = &rest[1 + id_len..];
+                    continue;
+                }
+                if !id.is_empty() && !report.iter().any(|line| line.contains(&format!("`#{id}`"))) {
+                    report.push(format!("CSS selector `#{id}`"));
+                }
+            }
+            _ => {}
+        }
+
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out.replace("button.focused", "button.active")
+}
+
+fn css_class_for_id(id: &str) -> Option<String> {
+    if id.starts_with("custom-") {
+        return Some(id.to_string());
+    }
+    CSS_ID_CLASSES
+        .iter()
+        .find(|(waybar, _)| *waybar == id)
+        .map(|(_, class)| class.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_config_maps_modules_and_reports_leftovers() {
+        let waybar = json!({
+            "height": 30,
+            "modules-left": ["sway/workspaces", "custom/vpn"],
+            "modules-center": ["clock"],
+            "modules-right": ["cpu", "battery#bat1", "idle_inhibitor"],
+            "sway/workspaces": { "disable-scroll": true },
+            "custom/vpn": { "exec": "vpn-status", "interval": 10, "return-type": "json" },
+            "clock": { "format": " {:%H:%M}", "tooltip-format": "{:%A}" },
+            "cpu": { "interval": 2, "format": "{usage}%", "on-scroll-up": "top" },
+            "battery#bat1": { "bat": "BAT1", "states": { "warning": 30 } },
+            "idle_inhibitor": { "format": "{icon}" }
+        });
+        let mut report = Vec::new();
+        let config = convert_config(&waybar, &mut report).expect("config should convert");
+
+        assert_eq!(
+            config["areas"]["left"],
+            json!([
+                { "type": "sway/workspaces" },
+                {
+                    "type": "exec",
+                    "command": "vpn-status",
+                    "interval_secs": 10,
+                    "class": "custom-vpn"
+                }
+            ])
+        );
+        assert_eq!(
+            config["areas"]["center"],
+            json!([{ "type": "clock", "format": " {}", "time-format": "%H:%M" }])
+        );
+        assert_eq!(
+            config["areas"]["right"],
+            json!([
+                {
+                    "type": "cpu",
+                    "interval_secs": 2,
+                    "format": "{usage}%",
+                    "on-scroll-up": "top"
+                },
+                { "type": "battery", "device": "BAT1", "class": "bat1" }
+            ])
+        );
+
+        report.sort();
+        assert_eq!(
+            report,
+            vec![
+                "`battery#bat1.states`",
+                "`clock.tooltip-format`",
+                "`sway/workspaces.disable-scroll`",
+                "bar option `height`",
+                "module `idle_inhibitor` has no vibar equivalent",
+            ]
+        );
+    }
+
+    #[test]
+    fn convert_config_takes_first_bar_and_converts_groups() {
+        let waybar = json!([
+            {
+                "modules-right": ["group/hw"],
+                "group/hw": { "modules": ["cpu", "memory"], "orientation": "inherit" }
+            },
+            { "modules-left": ["clock"] }
+        ]);
+        let mut report = Vec::new();
+        let config = convert_config(&waybar, &mut report).expect("config should convert");
+
+        assert_eq!(config["areas"]["left"], json!([]));
+        assert_eq!(
+            config["areas"]["right"],
+            json!([{
+                "type": "group",
+                "modules": [{ "type": "cpu" }, { "type": "memory" }]
+            }])
+        );
+        assert_eq!(report.len(), 2);
+        assert!(report[0].contains("only the first of 2 bars"));
+        assert_eq!(report[1], "`group/hw.orientation`");
+    }
+
+    #[test]
+    fn convert_module_skips_custom_without_exec() {
+        let bar = Map::from_iter([("custom/x".to_string(), json!({ "format": "x" }))]);
+        let mut report = Vec::new();
+        assert!(convert_module("custom/x", &bar, &mut report, &mut Vec::new()).is_none());
+        assert_eq!(report, vec!["module `custom/x` has no `exec` command"]);
+    }
+
+    #[test]
+    fn translate_css_rewrites_selectors_only() {
+        let css = "window#waybar { background: #1e1e2e; }\n\
+                   /* #clock stays */\n\
+                   #clock, #custom-vpn:hover { color: #fff; }\n\
+                   #workspaces button.focused { color: red; }\n\
+                   #idle_inhibitor { color: blue; }\n";
+        let mut report = Vec::new();
+        assert_eq!(
+            translate_css(css, &mut report),
+            ".bar { background: #1e1e2e; }\n\
+             /* #clock stays */\n\
+             .clock, .custom-vpn:hover { color: #fff; }\n\
+             .workspaces button.active { color: red; }\n\
+             #idle_inhibitor { color: blue; }\n"
+        );
+        assert_eq!(report, vec!["CSS selector `#idle_inhibitor`"]);
+    }
+}